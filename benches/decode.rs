@@ -0,0 +1,66 @@
+//! Benchmarks for decoding single values and slices of values with each processor type,
+//! and for encoding slices of values.
+use c3dio::Processor;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+const PROCESSORS: [Processor; 3] = [Processor::Intel, Processor::Dec, Processor::SgiMips];
+
+fn single_value(c: &mut Criterion) {
+    let mut group = c.benchmark_group("single_value");
+    for processor in PROCESSORS {
+        let name = processor.to_string();
+        group.bench_function(BenchmarkId::new("u16", &name), |b| {
+            b.iter(|| black_box(processor).u16(black_box([0x12, 0x34])))
+        });
+        group.bench_function(BenchmarkId::new("i16", &name), |b| {
+            b.iter(|| black_box(processor).i16(black_box([0x12, 0x84])))
+        });
+        group.bench_function(BenchmarkId::new("f32", &name), |b| {
+            b.iter(|| black_box(processor).f32(black_box([0x12, 0x34, 0x56, 0x41])))
+        });
+    }
+    group.finish();
+}
+
+fn slice(c: &mut Criterion) {
+    let mut group = c.benchmark_group("slice");
+    for size in [4 * 1024, 1024 * 1024] {
+        let bytes: Vec<u8> = (0..size).map(|i| (i % 251) as u8 | 0x01).collect();
+        group.throughput(Throughput::Bytes(size as u64));
+        for processor in PROCESSORS {
+            let name = format!("{}/{}", processor.to_string(), size);
+            group.bench_with_input(BenchmarkId::new("u16", &name), &bytes, |b, bytes| {
+                b.iter(|| processor.u16_slice(black_box(bytes)))
+            });
+            group.bench_with_input(BenchmarkId::new("i16", &name), &bytes, |b, bytes| {
+                b.iter(|| processor.i16_slice(black_box(bytes)))
+            });
+            group.bench_with_input(BenchmarkId::new("f32", &name), &bytes, |b, bytes| {
+                b.iter(|| processor.f32_slice(black_box(bytes)))
+            });
+        }
+    }
+    group.finish();
+}
+
+fn slice_to_bytes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("slice_to_bytes");
+    for count in [1024, 256 * 1024] {
+        let f32s: Vec<f32> = (0..count).map(|i| i as f32 * 0.37).collect();
+        let i16s: Vec<i16> = (0..count).map(|i| (i % 65521) as i16).collect();
+        group.throughput(Throughput::Elements(count as u64));
+        for processor in PROCESSORS {
+            let name = format!("{}/{}", processor.to_string(), count);
+            group.bench_with_input(BenchmarkId::new("i16", &name), &i16s, |b, values| {
+                b.iter(|| processor.i16_slice_to_bytes(black_box(values)))
+            });
+            group.bench_with_input(BenchmarkId::new("f32", &name), &f32s, |b, values| {
+                b.iter(|| processor.f32_slice_to_bytes(black_box(values)))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, single_value, slice, slice_to_bytes);
+criterion_main!(benches);
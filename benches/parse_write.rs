@@ -0,0 +1,77 @@
+//! Benchmarks for parsing and writing whole files of different sizes.
+use c3dio::prelude::*;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use grid::Grid;
+
+/// Generates a file with the given number of frames by repeating the frames of a sample file.
+fn synthetic_c3d(num_frames: usize) -> C3d {
+    let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let mut points = Grid::new(0, c3d.points.cols());
+    for frame in 0..num_frames {
+        points.push_row(
+            c3d.points
+                .iter_row(frame % c3d.points.rows())
+                .cloned()
+                .collect(),
+        );
+    }
+    let samples = c3d.analog.samples_per_channel_per_frame as usize;
+    let mut analog = Grid::new(0, c3d.analog.cols());
+    for row in 0..num_frames * samples {
+        analog.push_row(
+            c3d.analog
+                .iter_row(row % c3d.analog.rows())
+                .cloned()
+                .collect(),
+        );
+    }
+    c3d.points.points = points;
+    c3d.analog.analog = analog;
+    c3d.points.last_frame = c3d.points.first_frame + num_frames as u16 - 1;
+    c3d
+}
+
+/// Writes a synthetic file and returns its bytes.
+fn synthetic_bytes(num_frames: usize) -> Vec<u8> {
+    let path = std::env::temp_dir().join(format!("c3dio_bench_{}.c3d", num_frames));
+    synthetic_c3d(num_frames).write_path(path.clone()).unwrap();
+    let bytes = std::fs::read(&path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    bytes
+}
+
+const SIZES: [usize; 3] = [100, 1_000, 10_000];
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    group.sample_size(20);
+    for num_frames in SIZES {
+        let bytes = synthetic_bytes(num_frames);
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(num_frames),
+            &bytes,
+            |b, bytes| b.iter(|| C3d::from_bytes(black_box(bytes)).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+fn write(c: &mut Criterion) {
+    let mut group = c.benchmark_group("write");
+    group.sample_size(20);
+    let path = std::env::temp_dir().join("c3dio_bench_write.c3d");
+    for num_frames in SIZES {
+        let c3d = synthetic_c3d(num_frames);
+        group.bench_with_input(BenchmarkId::from_parameter(num_frames), &c3d, |b, c3d| {
+            b.iter(|| {
+                c3d.write_path(path.clone()).unwrap();
+            })
+        });
+    }
+    let _ = std::fs::remove_file(path);
+    group.finish();
+}
+
+criterion_group!(benches, parse, write);
+criterion_main!(benches);
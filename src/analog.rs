@@ -1,15 +1,19 @@
 //! Includes the analog data and parameters.
 use std::collections::HashMap;
-use std::ops::{Deref, DerefMut};
+use std::ops::{Deref, DerefMut, Index, IndexMut, Range};
 
-use crate::data::{get_analog_bytes_per_frame, get_point_bytes_per_frame, DataFormat};
-use crate::parameters::{Parameter, ParameterData, Parameters};
+use crate::data::{
+    decode_chunks, get_analog_bytes_per_frame, get_point_bytes_per_frame, select_columns,
+    select_rows, DataFormat, LabelMatch,
+};
+use crate::parameters::{write_continued, Parameter, ParameterData, Parameters};
 use crate::processor::Processor;
 use crate::{C3dParseError, C3dWriteError};
 use grid::Grid;
 
 /// Analog format describes whether the analog data is signed or unsigned.
 #[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AnalogFormat {
     #[default]
     Signed,
@@ -38,6 +42,7 @@ impl AnalogFormat {
 
 /// An offset that is subtracted from the analog data before scaling.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AnalogOffset {
     Signed(Vec<i16>),
     Unsigned(Vec<u16>),
@@ -54,15 +59,12 @@ impl AnalogOffset {
         parameters: &mut Parameters,
         format: &AnalogFormat,
     ) -> Result<AnalogOffset, C3dParseError> {
-        let offset = parameters.remove("ANALOG", "OFFSET");
-        let offset = match offset {
-            Some(offset) => offset,
-            None => return Ok(AnalogOffset::Signed(Vec::new())),
-        };
+        let offset: Vec<i16> = parameters
+            .remove_continued("ANALOG", "OFFSET")?
+            .unwrap_or_default();
         match format {
-            AnalogFormat::Signed => Ok(AnalogOffset::Signed(offset.as_ref().try_into()?)),
+            AnalogFormat::Signed => Ok(AnalogOffset::Signed(offset)),
             AnalogFormat::Unsigned => {
-                let offset: Vec<i16> = offset.as_ref().try_into()?;
                 let offset: Vec<u16> = offset.iter().map(|x| *x as u16).collect();
                 Ok(AnalogOffset::Unsigned(offset))
             }
@@ -73,8 +75,10 @@ impl AnalogOffset {
 /// Analog data and parameters are stored as a struct.
 /// Definitions for data and parameters are from the C3D file format documentation.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Analog {
     parsed_header: bool,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::grid"))]
     pub analog: Grid<f64>,
     pub labels: Vec<String>,
     pub descriptions: Vec<String>,
@@ -138,6 +142,58 @@ impl DerefMut for Analog {
     }
 }
 
+/// Indexes the rows of the grid, as it would be through `Deref`, which the
+/// label index below would otherwise hide.
+impl Index<usize> for Analog {
+    type Output = [f64];
+
+    fn index(&self, row: usize) -> &Self::Output {
+        &self.analog[row]
+    }
+}
+
+impl IndexMut<usize> for Analog {
+    fn index_mut(&mut self, row: usize) -> &mut Self::Output {
+        &mut self.analog[row]
+    }
+}
+
+impl Index<(usize, usize)> for Analog {
+    type Output = f64;
+
+    fn index(&self, index: (usize, usize)) -> &Self::Output {
+        &self.analog[index]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Analog {
+    fn index_mut(&mut self, index: (usize, usize)) -> &mut Self::Output {
+        &mut self.analog[index]
+    }
+}
+
+/// Indexes a channel by frame and label, e.g. `[(0, "Fz1")]`, matching labels as
+/// `get_by_label` does. Panics if no channel has the label or the frame is out of range.
+impl Index<(usize, &str)> for Analog {
+    type Output = f64;
+
+    fn index(&self, (frame, label): (usize, &str)) -> &Self::Output {
+        match self.label_index(label) {
+            Some(column) => &self.analog[(frame, column)],
+            None => panic!("no channel labelled {}", label),
+        }
+    }
+}
+
+impl IndexMut<(usize, &str)> for Analog {
+    fn index_mut(&mut self, (frame, label): (usize, &str)) -> &mut Self::Output {
+        match self.label_index(label) {
+            Some(column) => &mut self.analog[(frame, column)],
+            None => panic!("no channel labelled {}", label),
+        }
+    }
+}
+
 impl ToString for Analog {
     fn to_string(&self) -> String {
         let mut string = String::new();
@@ -160,6 +216,26 @@ impl ToString for Analog {
     }
 }
 
+/// The metadata of an analog channel, gathered from the per-channel parameters of the
+/// ANALOG group, as returned by `C3d::analog_channels`.
+/// Its samples are in column `index` of `C3d::analog`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnalogChannel {
+    pub index: usize,
+    pub label: String,
+    pub description: String,
+    pub units: String,
+    /// The gain code in ANALOG:GAIN, e.g. 2 for a range of ±5 V,
+    /// or `None` if the parameter is missing or does not cover the channel.
+    pub gain: Option<i16>,
+    /// The value of ANALOG:SCALE, which is 1 if the file does not give one.
+    pub scale: f32,
+    /// The value of ANALOG:OFFSET, which is 0 if the file does not give one.
+    /// It is read as unsigned for files whose ANALOG:FORMAT is UNSIGNED.
+    pub offset: i32,
+}
+
 impl Analog {
     pub(crate) fn new() -> Self {
         Analog::default()
@@ -172,26 +248,370 @@ impl Analog {
         analog
     }
 
-    pub(crate) fn parse(
+    /// Parses the analog parameters and returns the number of channels stored in each sample.
+    pub(crate) fn parse_parameters(
         &mut self,
-        data_bytes: &Vec<u8>,
         parameters: &mut Parameters,
-        processor: &Processor,
-        num_frames: usize,
-        format: &DataFormat,
-        points_per_frame: usize,
-    ) -> Result<&mut Self, C3dParseError> {
+    ) -> Result<u16, C3dParseError> {
         let analog_used = self.get_analog_parameters(parameters)?;
-        self.parse_analog(
-            data_bytes,
-            processor,
-            num_frames,
-            format,
-            points_per_frame,
-            analog_used,
+        self.check_analog_used(analog_used)?;
+        Ok(analog_used)
+    }
+
+    /// Returns whether the stored integer samples are signed or unsigned,
+    /// as given by ANALOG:FORMAT.
+    pub fn format(&self) -> AnalogFormat {
+        match self.offset {
+            AnalogOffset::Signed(_) => AnalogFormat::Signed,
+            AnalogOffset::Unsigned(_) => AnalogFormat::Unsigned,
+        }
+    }
+
+    /// Returns the samples in physical units, with the offset removed and
+    /// ANALOG:SCALE and ANALOG:GEN_SCALE applied. These are the values stored in `analog`.
+    pub fn scaled(&self) -> &Grid<f64> {
+        &self.analog
+    }
+
+    /// Returns the samples as stored in the file, e.g. the counts of the
+    /// analog-to-digital converter, by undoing the scaling of `scaled`.
+    /// Channels without a scale or offset are taken to have a scale of 1 and no offset.
+    pub fn raw(&self) -> Grid<f64> {
+        let mut raw = self.analog.clone();
+        for channel in 0..raw.cols() {
+            raw.iter_col_mut(channel)
+                .for_each(|value| *value = self.raw_value(channel, *value));
+        }
+        raw
+    }
+
+    /// Returns the shape and the scaled samples as a row-major array of samples by
+    /// channels, e.g. to build an `ndarray::Array2` with `Array2::from_shape_vec`.
+    pub fn to_array2(&self) -> ([usize; 2], Vec<f64>) {
+        ([self.rows(), self.cols()], self.as_slice().to_vec())
+    }
+
+    /// Returns all scaled samples as one contiguous row-major slice of samples by
+    /// channels, borrowed from the storage without copying.
+    pub fn as_slice(&self) -> &[f64] {
+        self.analog.flatten()
+    }
+
+    /// Returns the scaled samples of all channels in a frame of the points as a
+    /// contiguous slice, holding `samples_per_channel_per_frame` rows of channels,
+    /// or `None` if the frame is not stored.
+    /// The samples of a single channel are not contiguous, see `iter_col` for them.
+    pub fn frame(&self, frame: usize) -> Option<&[f64]> {
+        let range = self.frame_range(frame)?;
+        Some(&self.analog.flatten()[range])
+    }
+
+    /// Returns the scaled samples of all channels in a frame of the points as a
+    /// mutable slice, or `None` if the frame is not stored.
+    pub fn frame_mut(&mut self, frame: usize) -> Option<&mut [f64]> {
+        let range = self.frame_range(frame)?;
+        Some(&mut self.analog.iter_mut().into_slice()[range])
+    }
+
+    /// The indices in the storage of the samples in a frame of the points.
+    fn frame_range(&self, frame: usize) -> Option<Range<usize>> {
+        let rows = self.samples_of_frame(frame)?;
+        Some(rows.start * self.cols()..rows.end * self.cols())
+    }
+
+    /// Returns the number of frames of the points whose analog samples are stored.
+    pub fn frame_count(&self) -> usize {
+        match self.samples_per_channel_per_frame {
+            0 => 0,
+            rows => self.rows() / rows as usize,
+        }
+    }
+
+    /// Iterates over the frames of the points, giving the scaled samples of all channels
+    /// in each frame as `frame` does.
+    pub fn frames(&self) -> impl Iterator<Item = &[f64]> {
+        (0..self.frame_count()).filter_map(move |frame| self.frame(frame))
+    }
+
+    /// Returns the samples of a channel taken during a frame of the points,
+    /// `samples_per_channel_per_frame` of them, or `None` if the frame or channel is
+    /// not stored.
+    pub fn frame_samples(&self, frame: usize, channel: usize) -> Option<Vec<f64>> {
+        if channel >= self.cols() {
+            return None;
+        }
+        let samples = self.frame(frame)?;
+        Some(
+            samples
+                .iter()
+                .skip(channel)
+                .step_by(self.cols())
+                .copied()
+                .collect(),
         )
     }
 
+    /// Returns the frame of the points an analog sample was taken during, and the index
+    /// of the sample within the frame, or `None` if the sample is not stored.
+    /// Samples are counted from the first stored sample, as the rows of `analog` are.
+    pub fn frame_of_sample(&self, sample: usize) -> Option<(usize, usize)> {
+        let rows = self.samples_per_channel_per_frame as usize;
+        match rows > 0 && sample < self.rows() {
+            true => Some((sample / rows, sample % rows)),
+            false => None,
+        }
+    }
+
+    /// Returns the analog samples taken during a frame of the points, as indices of the
+    /// rows of `analog`, or `None` if the frame is not stored.
+    pub fn samples_of_frame(&self, frame: usize) -> Option<Range<usize>> {
+        let rows = self.samples_per_channel_per_frame as usize;
+        let end = (frame + 1) * rows;
+        match rows > 0 && end <= self.rows() {
+            true => Some(frame * rows..end),
+            false => None,
+        }
+    }
+
+    /// Returns the smallest and largest raw sample the converter can produce,
+    /// from ANALOG:BITS and ANALOG:FORMAT, or `None` if the number of bits is not
+    /// between 1 and 16.
+    pub fn raw_range(&self) -> Option<(i32, i32)> {
+        if !(1..=16).contains(&self.bits) {
+            return None;
+        }
+        let levels = 1i32 << self.bits;
+        match self.format() {
+            AnalogFormat::Signed => Some((-levels / 2, levels / 2 - 1)),
+            AnalogFormat::Unsigned => Some((0, levels - 1)),
+        }
+    }
+
+    /// Returns the number of samples of a channel at the smallest or largest raw sample
+    /// of `raw_range`, which a signal beyond the range of the converter is clipped to.
+    /// Returns `None` if the channel is not stored or the range is not known.
+    pub fn clipped_samples(&self, channel: usize) -> Option<usize> {
+        let (min, max) = self.raw_range()?;
+        if channel >= self.cols() {
+            return None;
+        }
+        let (min, max) = (min as f64, max as f64);
+        Some(
+            self.analog
+                .iter_col(channel)
+                .map(|&value| self.raw_value(channel, value).round())
+                .filter(|&raw| raw <= min || raw >= max)
+                .count(),
+        )
+    }
+
+    /// Converts a scaled sample of a channel back to the value stored in the file.
+    fn raw_value(&self, channel: usize, value: f64) -> f64 {
+        let offset = match &self.offset {
+            AnalogOffset::Signed(offset) => offset.get(channel).map(|&x| x as f64),
+            AnalogOffset::Unsigned(offset) => offset.get(channel).map(|&x| x as f64),
+        };
+        let scale = self.scales.get(channel).copied().unwrap_or(1.);
+        value / scale as f64 / self.gen_scale as f64 + offset.unwrap_or(0.)
+    }
+
+    /// Returns the channel with the given label.
+    pub(crate) fn label_index(&self, label: &str) -> Option<usize> {
+        self.find_label(label, LabelMatch::Trimmed)
+    }
+
+    /// Returns the first channel whose label matches the one given, if it has data.
+    pub fn find_label(&self, label: &str, matching: LabelMatch) -> Option<usize> {
+        matching.find(&self.labels, self.cols(), label)
+    }
+
+    /// Returns every sample of the channel with the given label, ignoring the
+    /// whitespace around labels, or `None` if no channel has that label.
+    pub fn get_by_label(&self, label: &str) -> Option<Vec<f64>> {
+        self.get_by_label_matching(label, LabelMatch::Trimmed)
+    }
+
+    /// Returns every sample of the first channel whose label matches the one given.
+    pub fn get_by_label_matching(&self, label: &str, matching: LabelMatch) -> Option<Vec<f64>> {
+        let column = self.find_label(label, matching)?;
+        Some(self.analog.iter_col(column).copied().collect())
+    }
+
+    /// Adds a channel after the others, with the units, scale and offset of the channel
+    /// at index `like`, e.g. an EMG envelope computed from that channel.
+    /// Returns the index of the new channel, or `None` if there is no channel at `like`
+    /// or the number of samples differs from the other channels.
+    pub fn add_channel(
+        &mut self,
+        label: &str,
+        description: &str,
+        samples: Vec<f64>,
+        like: usize,
+    ) -> Option<usize> {
+        let channels = self.cols();
+        if like >= channels || samples.len() != self.rows() {
+            return None;
+        }
+        self.labels.resize(channels, String::new());
+        self.labels.push(label.to_string());
+        self.descriptions.resize(channels, String::new());
+        self.descriptions.push(description.to_string());
+        let unit = self.units.get(like).cloned().unwrap_or_default();
+        self.units.resize(channels, String::new());
+        self.units.push(unit);
+        if !self.scales.is_empty() {
+            let scale = self.scales.get(like).copied().unwrap_or(1.);
+            self.scales.resize(channels, 1.);
+            self.scales.push(scale);
+        }
+        match &mut self.offset {
+            AnalogOffset::Signed(offset) if !offset.is_empty() => {
+                let value = offset.get(like).copied().unwrap_or(0);
+                offset.resize(channels, 0);
+                offset.push(value);
+            }
+            AnalogOffset::Unsigned(offset) if !offset.is_empty() => {
+                let value = offset.get(like).copied().unwrap_or(0);
+                offset.resize(channels, 0);
+                offset.push(value);
+            }
+            _ => {}
+        }
+        self.analog.push_col(samples);
+        self.samples_per_frame += self.samples_per_channel_per_frame;
+        Some(channels)
+    }
+
+    /// Returns the metadata of a channel, without its gain, which `Analog` does not hold.
+    pub(crate) fn channel(&self, index: usize) -> Option<AnalogChannel> {
+        if index >= self.cols() {
+            return None;
+        }
+        let offset = match &self.offset {
+            AnalogOffset::Signed(offset) => offset.get(index).map(|&x| x as i32),
+            AnalogOffset::Unsigned(offset) => offset.get(index).map(|&x| x as i32),
+        };
+        Some(AnalogChannel {
+            index,
+            label: self.labels.get(index).cloned().unwrap_or_default(),
+            description: self.descriptions.get(index).cloned().unwrap_or_default(),
+            units: self.units.get(index).cloned().unwrap_or_default(),
+            gain: None,
+            scale: self.scales.get(index).copied().unwrap_or(1.),
+            offset: offset.unwrap_or(0),
+        })
+    }
+
+    /// Stores the metadata of a channel in the per-channel parameters, extending those
+    /// shorter than the number of channels. The scaled samples are kept.
+    pub(crate) fn set_channel(&mut self, channel: &AnalogChannel) -> Result<(), C3dWriteError> {
+        let (index, channels) = (channel.index, self.cols());
+        if index >= channels {
+            return Err(C3dWriteError::InvalidAnalogChannel(format!(
+                "no channel {} in {} channels",
+                index, channels
+            )));
+        }
+        let out_of_range = || {
+            C3dWriteError::InvalidAnalogChannel(format!(
+                "offset {} of channel {} does not fit ANALOG:FORMAT",
+                channel.offset, index
+            ))
+        };
+        match &mut self.offset {
+            AnalogOffset::Signed(offset) => {
+                let value = i16::try_from(channel.offset).map_err(|_| out_of_range())?;
+                offset.resize(channels.max(offset.len()), 0);
+                offset[index] = value;
+            }
+            AnalogOffset::Unsigned(offset) => {
+                let value = u16::try_from(channel.offset).map_err(|_| out_of_range())?;
+                offset.resize(channels.max(offset.len()), 0);
+                offset[index] = value;
+            }
+        }
+        for (entries, value) in [
+            (&mut self.labels, &channel.label),
+            (&mut self.descriptions, &channel.description),
+            (&mut self.units, &channel.units),
+        ] {
+            entries.resize(channels.max(entries.len()), String::new());
+            entries[index] = value.clone();
+        }
+        self.scales.resize(channels.max(self.scales.len()), 1.);
+        self.scales[index] = channel.scale;
+        Ok(())
+    }
+
+    /// Chooses ANALOG:SCALE for each channel so that its samples use the full range of
+    /// 16-bit integers around the channel offset, keeping the sign of the scale.
+    /// Offsets that leave no room on either side are moved to the middle of the range.
+    pub(crate) fn fit_integer_scales(&mut self) {
+        let channels = self.cols();
+        self.scales.resize(channels, 1.);
+        if self.gen_scale == 0. {
+            self.gen_scale = 1.;
+        }
+        match &mut self.offset {
+            AnalogOffset::Signed(offset) => offset.resize(channels, 0),
+            AnalogOffset::Unsigned(offset) => offset.resize(channels, 32768),
+        }
+        for channel in 0..channels {
+            let room = match &mut self.offset {
+                AnalogOffset::Signed(offset) => {
+                    let room = |offset: i16| {
+                        (i16::MAX as i32 - offset as i32).min(offset as i32 - i16::MIN as i32)
+                    };
+                    if room(offset[channel]) < 1 {
+                        offset[channel] = 0;
+                    }
+                    room(offset[channel]) as f64
+                }
+                AnalogOffset::Unsigned(offset) => {
+                    let room = |offset: u16| (u16::MAX - offset).min(offset);
+                    if room(offset[channel]) < 1 {
+                        offset[channel] = 32768;
+                    }
+                    room(offset[channel]) as f64
+                }
+            };
+            let largest = self
+                .analog
+                .iter_col(channel)
+                .filter(|x| x.is_finite())
+                .fold(0f64, |largest, x| largest.max(x.abs()));
+            if largest == 0. {
+                continue;
+            }
+            let scale = (largest / room / self.gen_scale as f64) as f32;
+            self.scales[channel] = match self.scales[channel] < 0. {
+                true => -scale,
+                false => scale,
+            };
+        }
+    }
+
+    /// Keeps only the given rows of analog samples.
+    pub(crate) fn crop(&mut self, rows: Range<usize>) {
+        self.analog = select_rows(&self.analog, rows);
+    }
+
+    /// Keeps the per-channel parameters of the given channels, after only those were decoded.
+    pub(crate) fn select_channels(&mut self, channels: &[usize]) {
+        self.labels = select_columns(&self.labels, channels);
+        self.descriptions = select_columns(&self.descriptions, channels);
+        self.units = select_columns(&self.units, channels);
+        self.scales = select_columns(&self.scales, channels);
+        self.offset = match &self.offset {
+            AnalogOffset::Signed(offset) => AnalogOffset::Signed(select_columns(offset, channels)),
+            AnalogOffset::Unsigned(offset) => {
+                AnalogOffset::Unsigned(select_columns(offset, channels))
+            }
+        };
+        self.samples_per_frame =
+            (channels.len() * self.samples_per_channel_per_frame as usize) as u16;
+    }
+
     pub(crate) fn write_parameters(
         &self,
         processor: &Processor,
@@ -205,20 +625,17 @@ impl Analog {
             group_names_to_ids["ANALOG"],
             false,
         )?);
-        // "ANALOG", "LABELS"
-        bytes.extend(Parameter::strings(self.labels.clone()).write(
-            processor,
-            "LABELS".to_string(),
-            group_names_to_ids["ANALOG"],
-            false,
-        )?);
-        // "ANALOG", "DESCRIPTIONS"
-        bytes.extend(Parameter::strings(self.descriptions.clone()).write(
-            processor,
-            "DESCRIPTIONS".to_string(),
-            group_names_to_ids["ANALOG"],
-            false,
-        )?);
+        // "ANALOG", "LABELS" and "DESCRIPTIONS", continued after 255 channels
+        let group_id = group_names_to_ids["ANALOG"];
+        let strings = |strings| Ok(Parameter::strings(strings));
+        for (name, entries) in [
+            ("LABELS", &self.labels),
+            ("DESCRIPTIONS", &self.descriptions),
+        ] {
+            bytes.extend(write_continued(
+                entries, strings, processor, name, group_id,
+            )?);
+        }
         // "ANALOG", "GEN_SCALE"
         bytes.extend(Parameter::float(self.gen_scale).write(
             processor,
@@ -227,19 +644,21 @@ impl Analog {
             false,
         )?);
         // "ANALOG", "UNITS"
-        bytes.extend(Parameter::strings(self.units.clone()).write(
+        bytes.extend(write_continued(
+            &self.units,
+            strings,
             processor,
-            "UNITS".to_string(),
-            group_names_to_ids["ANALOG"],
-            false,
+            "UNITS",
+            group_id,
         )?);
         // "ANALOG", "SCALE"
         if self.scales.len() != 0 {
-            bytes.extend(Parameter::floats(self.scales.clone())?.write(
+            bytes.extend(write_continued(
+                &self.scales,
+                Parameter::floats,
                 processor,
-                "SCALE".to_string(),
-                group_names_to_ids["ANALOG"],
-                false,
+                "SCALE",
+                group_id,
             )?);
         }
         // "ANALOG", "RATE"
@@ -281,11 +700,12 @@ impl Analog {
             AnalogOffset::Unsigned(offset) => offset.iter().map(|x| *x as i16).collect(),
         };
         if offset.len() != 0 {
-            bytes.extend(Parameter::integers(offset)?.write(
+            bytes.extend(write_continued(
+                &offset,
+                Parameter::integers,
                 processor,
-                "OFFSET".to_string(),
-                group_names_to_ids["ANALOG"],
-                false,
+                "OFFSET",
+                group_id,
             )?);
         }
         Ok(bytes)
@@ -297,48 +717,32 @@ impl Analog {
         processor: &Processor,
         data_format: &DataFormat,
     ) -> Vec<u8> {
-        let mut bytes = Vec::new();
-
         let start_row = frame * self.samples_per_channel_per_frame as usize;
         let end_row = start_row + self.samples_per_channel_per_frame as usize;
         if end_row > self.analog.size().0 {
-            return bytes;
-        }
-        for i in start_row..end_row {
-            for (column, value) in self.analog.iter_row(i).enumerate() {
-                match data_format {
-                    DataFormat::Float => {
-                        let offset = match &self.offset {
-                            AnalogOffset::Signed(offset) => offset[column] as f64,
-                            AnalogOffset::Unsigned(offset) => offset[column] as f64,
-                        };
-                        let value = value / self.scales[column] as f64 / self.gen_scale as f64
-                            + offset as f64;
-                        let value = processor.f32_to_bytes(value as f32);
-                        bytes.extend_from_slice(&value);
-                    }
-                    DataFormat::Integer => match &self.offset {
-                        AnalogOffset::Signed(offset) => {
-                            let offset = offset[column] as f64;
-                            let value = value / self.scales[column] as f64 / self.gen_scale as f64
-                                + offset as f64;
-                            let value = value.round() as i16;
-                            let value = processor.i16_to_bytes(value);
-                            bytes.extend_from_slice(&value);
-                        }
-                        AnalogOffset::Unsigned(offset) => {
-                            let offset = offset[column] as f64;
-                            let value = value / self.scales[column] as f64 / self.gen_scale as f64
-                                + offset as f64;
-                            let value = value.round() as u16;
-                            let value = processor.u16_to_bytes(value);
-                            bytes.extend_from_slice(&value);
-                        }
-                    },
-                }
+            return Vec::new();
+        }
+        let values = (start_row..end_row).flat_map(|i| {
+            self.analog
+                .iter_row(i)
+                .enumerate()
+                .map(|(column, value)| self.raw_value(column, *value))
+        });
+        // the samples of the whole frame are converted to bytes at once
+        match (data_format, &self.offset) {
+            (DataFormat::Float, _) => {
+                let samples: Vec<f32> = values.map(|value| value as f32).collect();
+                processor.f32_slice_to_bytes(&samples)
+            }
+            (DataFormat::Integer, AnalogOffset::Signed(_)) => {
+                let samples: Vec<i16> = values.map(|value| value.round() as i16).collect();
+                processor.i16_slice_to_bytes(&samples)
+            }
+            (DataFormat::Integer, AnalogOffset::Unsigned(_)) => {
+                let samples: Vec<u16> = values.map(|value| value.round() as u16).collect();
+                processor.u16_slice_to_bytes(&samples)
             }
         }
-        bytes
     }
 
     fn get_analog_parameters(&mut self, parameters: &mut Parameters) -> Result<u16, C3dParseError> {
@@ -361,27 +765,22 @@ impl Analog {
             self.bits = 0;
             return Ok(0);
         } else {
-            self.labels = parameters
-                .remove_or_err("ANALOG", "LABELS")?
-                .as_ref()
-                .try_into()?;
+            self.labels = parameters.remove_continued("ANALOG", "LABELS")?.ok_or(
+                C3dParseError::ParameterNotFound("ANALOG".to_string(), "LABELS".to_string()),
+            )?;
             self.descriptions = parameters
-                .remove("ANALOG", "DESCRIPTIONS")
-                .unwrap_or(Parameter::strings(vec![" ".to_string()]))
-                .as_ref()
-                .try_into()?;
+                .remove_continued("ANALOG", "DESCRIPTIONS")?
+                .unwrap_or(vec![" ".to_string()]);
             self.gen_scale = parameters
                 .remove_or_err("ANALOG", "GEN_SCALE")?
                 .as_ref()
                 .try_into()?;
-            self.units = parameters
-                .remove_or_err("ANALOG", "UNITS")?
-                .as_ref()
-                .try_into()?;
-            self.scales = parameters
-                .remove_or_err("ANALOG", "SCALE")?
-                .as_ref()
-                .try_into()?;
+            self.units = parameters.remove_continued("ANALOG", "UNITS")?.ok_or(
+                C3dParseError::ParameterNotFound("ANALOG".to_string(), "UNITS".to_string()),
+            )?;
+            self.scales = parameters.remove_continued("ANALOG", "SCALE")?.ok_or(
+                C3dParseError::ParameterNotFound("ANALOG".to_string(), "SCALE".to_string()),
+            )?;
             self.rate = parameters
                 .remove_or_err("ANALOG", "RATE")?
                 .as_ref()
@@ -405,7 +804,7 @@ impl Analog {
         Ok(used.unwrap().as_ref().try_into()?)
     }
 
-    fn parse_analog(
+    pub(crate) fn parse_analog(
         &mut self,
         data_bytes: &Vec<u8>,
         processor: &Processor,
@@ -414,58 +813,87 @@ impl Analog {
         points_per_frame: usize,
         analog_used: u16,
     ) -> Result<&mut Self, C3dParseError> {
-        let mut analog_data = Grid::new(
-            num_frames * self.samples_per_channel_per_frame as usize,
-            analog_used as usize,
-        );
+        self.check_analog_used(analog_used)?;
+        let channels: Vec<usize> = (0..analog_used as usize).collect();
+        self.parse_analog_channels(
+            data_bytes,
+            processor,
+            num_frames,
+            format,
+            points_per_frame,
+            &channels,
+        )
+    }
 
+    /// Checks that the analog samples of each frame hold every channel in use.
+    fn check_analog_used(&self, analog_used: u16) -> Result<(), C3dParseError> {
+        if self.samples_per_frame as usize
+            != analog_used as usize * self.samples_per_channel_per_frame as usize
+        {
+            return Err(C3dParseError::AnalogBytesPerFrameMismatch);
+        }
+        Ok(())
+    }
+
+    /// Decodes only the given channels of the stored analog data, in the given order.
+    /// The layout of the frames is the one checked by `check_analog_used`.
+    pub(crate) fn parse_analog_channels(
+        &mut self,
+        data_bytes: &[u8],
+        processor: &Processor,
+        num_frames: usize,
+        format: &DataFormat,
+        points_per_frame: usize,
+        channels: &[usize],
+    ) -> Result<&mut Self, C3dParseError> {
         let point_bytes_per_frame = get_point_bytes_per_frame(format, points_per_frame) as usize;
 
         let analog_bytes_per_frame = get_analog_bytes_per_frame(format, self.samples_per_frame)?;
         let bytes_per_frame = point_bytes_per_frame + analog_bytes_per_frame;
-        let bytes_per_analog_point = match self.samples_per_frame {
+        let bytes_per_sample = match self.samples_per_channel_per_frame {
             0 => 0,
-            _ => analog_bytes_per_frame / self.samples_per_frame as usize,
+            samples => analog_bytes_per_frame / samples as usize,
         };
-        if analog_bytes_per_frame
-            != bytes_per_analog_point
-                * analog_used as usize
-                * self.samples_per_channel_per_frame as usize
-        {
-            return Err(C3dParseError::AnalogBytesPerFrameMismatch);
-        }
-        for i in 0..num_frames {
+        let unsigned = self.format() == AnalogFormat::Unsigned;
+        let samples_per_channel_per_frame = self.samples_per_channel_per_frame as usize;
+        let frame_len = samples_per_channel_per_frame * channels.len();
+        let analog_data = decode_chunks(num_frames, frame_len, |i, frame| {
             let start = i * bytes_per_frame as usize;
             let end = start + bytes_per_frame as usize;
             let analog_frame_data = &data_bytes[start + point_bytes_per_frame as usize..end];
-            for j in 0..self.samples_per_channel_per_frame {
-                let start = j as usize * bytes_per_analog_point * analog_used as usize;
-                let end = start + (bytes_per_analog_point * analog_used as usize);
+            for (j, row) in frame.chunks_mut(channels.len()).enumerate() {
+                let start = j * bytes_per_sample;
+                let end = start + bytes_per_sample;
                 let analog_slice = &analog_frame_data[start as usize..end as usize];
-                let temp_analog_data = match format {
-                    DataFormat::Float => {
-                        parse_analog_data_float(analog_slice, analog_used as usize, processor)
+                // the samples of all channels are converted at once
+                match (format, unsigned) {
+                    (DataFormat::Float, _) => {
+                        let samples = processor.f32_slice(analog_slice);
+                        select_samples(row, &samples, channels);
                     }
-                    DataFormat::Integer => {
-                        parse_analog_data_int(analog_slice, analog_used as usize, processor)
+                    (DataFormat::Integer, true) => {
+                        let samples = processor.u16_slice(analog_slice);
+                        select_samples(row, &samples, channels);
+                    }
+                    (DataFormat::Integer, false) => {
+                        let samples = processor.i16_slice(analog_slice);
+                        select_samples(row, &samples, channels);
                     }
-                };
-                for k in 0..analog_used as usize {
-                    let row = i * self.samples_per_channel_per_frame as usize + j as usize;
-                    analog_data[row][k] = temp_analog_data[k] as f64;
                 }
             }
-        }
+        });
+        let mut analog_data = Grid::from_vec(analog_data, channels.len());
         let offset_len = match &self.offset {
             AnalogOffset::Signed(offset) => offset.len(),
             AnalogOffset::Unsigned(offset) => offset.len(),
         };
-        if analog_used > 0 && offset_len != self.scales.len() {
+        if !channels.is_empty() && offset_len != self.scales.len() {
             return Err(C3dParseError::AnalogOffsetScaleMismatch);
         }
-        if analog_used as usize <= offset_len {
-            for i in 0..analog_data.cols() {
-                let col_iter = analog_data.iter_col_mut(i);
+        if channels.iter().all(|&channel| channel < offset_len) {
+            // a grid without rows has no columns either
+            for (column, &i) in channels.iter().enumerate().take(analog_data.cols()) {
+                let col_iter = analog_data.iter_col_mut(column);
                 match &self.offset {
                     AnalogOffset::Signed(offset) => col_iter.for_each(|x| {
                         *x -= offset[i] as f64;
@@ -485,34 +913,9 @@ impl Analog {
     }
 }
 
-fn parse_analog_data_float(
-    analog_frame_data: &[u8],
-    num_analog_channels: usize,
-    processor: &Processor,
-) -> Vec<f32> {
-    let mut analog_data = Vec::with_capacity(num_analog_channels);
-    for i in 0..num_analog_channels {
-        let start = i * 4;
-        let end = start + 4;
-        let analog_slice = analog_frame_data[start..end].try_into().unwrap();
-        let analog = processor.f32(analog_slice);
-        analog_data.push(analog);
-    }
-    analog_data
-}
-
-fn parse_analog_data_int(
-    analog_frame_data: &[u8],
-    num_analog_channels: usize,
-    processor: &Processor,
-) -> Vec<f32> {
-    let mut analog_data = Vec::with_capacity(num_analog_channels);
-    for i in 0..num_analog_channels {
-        let start = i * 2;
-        let end = start + 2;
-        let analog_slice = analog_frame_data[start..end].try_into().unwrap();
-        let analog = processor.i16(analog_slice) as f32;
-        analog_data.push(analog);
-    }
-    analog_data
+/// Keeps the samples of the given channels, in the given order.
+fn select_samples<T: Copy + Into<f64>>(row: &mut [f64], samples: &[T], channels: &[usize]) {
+    for (value, &channel) in row.iter_mut().zip(channels) {
+        *value = samples[channel].into();
+    }
 }
@@ -0,0 +1,104 @@
+//! Loads every C3D file in a directory in parallel, e.g. to index thousands of trials,
+//! reporting the files that fail without stopping at them.
+//! Requires the `rayon` and `fs` features.
+use crate::c3d::C3d;
+use crate::C3dParseError;
+use rayon::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A file or directory found by `load_metadata` or `process`, with the result of
+/// loading it. Directories only appear for the errors of reading them.
+#[derive(Debug)]
+pub struct BatchEntry<T> {
+    pub path: PathBuf,
+    pub result: Result<T, C3dParseError>,
+}
+
+/// Loads the header and parameters of every C3D file in a directory and its
+/// subdirectories in parallel, as `C3d::load_header_and_parameters` does.
+/// See `process`.
+///
+/// # Examples
+/// ```
+/// use c3dio::batch::load_metadata;
+///
+/// for entry in load_metadata("tests/c3d_org_samples/sample_02") {
+///     match entry.result {
+///         Ok(c3d) => println!("{}: {} frames", entry.path.display(), c3d.declared_frame_count()),
+///         Err(e) => println!("{}: {}", entry.path.display(), e),
+///     }
+/// }
+/// ```
+pub fn load_metadata<P: AsRef<Path>>(dir: P) -> Vec<BatchEntry<C3d>> {
+    process(dir, load_header_and_parameters)
+}
+
+fn load_header_and_parameters(path: &Path) -> Result<C3d, C3dParseError> {
+    C3d::load_header_and_parameters(path.to_path_buf())
+}
+
+/// Calls `load` on every C3D file in a directory and its subdirectories in parallel,
+/// e.g. to load the whole files or to keep only a summary of each.
+/// Files are found by their `.c3d` extension, ignoring case, and symbolic links to
+/// directories are not followed.
+///
+/// Returns an entry per file sorted by path, followed by an entry with a
+/// `C3dParseError::ReadError` for each directory that could not be read.
+pub fn process<P, T, F>(dir: P, load: F) -> Vec<BatchEntry<T>>
+where
+    P: AsRef<Path>,
+    T: Send,
+    F: Fn(&Path) -> Result<T, C3dParseError> + Sync,
+{
+    let mut files = Vec::new();
+    let mut errors = Vec::new();
+    find_files(dir.as_ref(), &mut files, &mut errors);
+    files.sort();
+    let mut entries: Vec<BatchEntry<T>> = files
+        .into_par_iter()
+        .map(|path| {
+            let result = load(&path);
+            BatchEntry { path, result }
+        })
+        .collect();
+    entries.extend(errors);
+    entries
+}
+
+/// Collects the C3D files under a directory, and the directories that cannot be read.
+fn find_files<T>(dir: &Path, files: &mut Vec<PathBuf>, errors: &mut Vec<BatchEntry<T>>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            errors.push(BatchEntry {
+                path: dir.to_path_buf(),
+                result: Err(C3dParseError::ReadError(e)),
+            });
+            return;
+        }
+    };
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                errors.push(BatchEntry {
+                    path: dir.to_path_buf(),
+                    result: Err(C3dParseError::ReadError(e)),
+                });
+                continue;
+            }
+        };
+        let path = entry.path();
+        match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => find_files(&path, files, errors),
+            _ if is_c3d(&path) => files.push(path),
+            _ => {}
+        }
+    }
+}
+
+fn is_c3d(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("c3d"))
+}
@@ -0,0 +1,183 @@
+//! The `c3d` command line tool, built with the `cli` feature:
+//! `cargo install c3dio --features cli`.
+use c3dio::prelude::*;
+use c3dio::Csv;
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use std::error::Error;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+fn cli() -> Command {
+    let file = || {
+        Arg::new("file")
+            .help("The C3D file to read")
+            .required(true)
+            .value_parser(value_parser!(PathBuf))
+    };
+    let output = || {
+        Arg::new("output")
+            .short('o')
+            .long("output")
+            .help("The file to write, next to the input file by default")
+            .value_parser(value_parser!(PathBuf))
+    };
+    Command::new("c3d")
+        .about("Inspects, validates and converts C3D motion capture files")
+        .version(env!("CARGO_PKG_VERSION"))
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("info")
+                .about("Prints the frames, rates, labels, force platforms and events of files")
+                .arg(file().action(ArgAction::Append)),
+        )
+        .subcommand(
+            Command::new("convert")
+                .about("Exports the points or the analog channels of a file")
+                .arg(file())
+                .arg(
+                    Arg::new("to")
+                        .long("to")
+                        .help("The format to export to")
+                        .required(true)
+                        .value_parser(["trc", "csv", "sto"]),
+                )
+                .arg(output()),
+        )
+        .subcommand(
+            Command::new("validate")
+                .about("Checks files against the C3D specification, failing if any is invalid")
+                .arg(file().action(ArgAction::Append)),
+        )
+        .subcommand(
+            Command::new("crop")
+                .about("Writes a copy of a file holding only a range of its frames")
+                .arg(file())
+                .arg(
+                    Arg::new("frames")
+                        .long("frames")
+                        .help(
+                            "The frames to keep, counted from zero and excluding the end, \
+                             e.g. 100:500, 100: or :500",
+                        )
+                        .required(true)
+                        .value_parser(parse_frames),
+                )
+                .arg(output()),
+        )
+}
+
+/// Parses a range of frames such as `100:500`, where either end may be left out.
+fn parse_frames(frames: &str) -> Result<Range<usize>, String> {
+    let (start, end) = frames
+        .split_once(':')
+        .ok_or_else(|| format!("expected START:END, found {}", frames))?;
+    let bound = |value: &str, default: usize| match value.trim() {
+        "" => Ok(default),
+        value => value
+            .parse::<usize>()
+            .map_err(|_| format!("{} is not a frame", value)),
+    };
+    let range = bound(start, 0)?..bound(end, usize::MAX)?;
+    match range.start <= range.end {
+        true => Ok(range),
+        false => Err(format!("the range {} ends before it starts", frames)),
+    }
+}
+
+/// Returns the output file given on the command line, or the input file with a new
+/// extension and an optional suffix.
+fn output_file(matches: &ArgMatches, file: &Path, suffix: &str, extension: &str) -> PathBuf {
+    match matches.get_one::<PathBuf>("output") {
+        Some(output) => output.clone(),
+        None => {
+            let stem = file.file_stem().unwrap_or_default().to_string_lossy();
+            file.with_file_name(format!("{}{}.{}", stem, suffix, extension))
+        }
+    }
+}
+
+fn load(file: &Path) -> Result<C3d, Box<dyn Error>> {
+    C3d::load(&file.to_string_lossy()).map_err(|e| format!("{}: {}", file.display(), e).into())
+}
+
+fn files(matches: &ArgMatches) -> impl Iterator<Item = &PathBuf> {
+    matches.get_many::<PathBuf>("file").unwrap_or_default()
+}
+
+fn info(matches: &ArgMatches) -> Result<bool, Box<dyn Error>> {
+    let files: Vec<&PathBuf> = files(matches).collect();
+    for (i, file) in files.iter().enumerate() {
+        if files.len() > 1 {
+            if i > 0 {
+                println!();
+            }
+            println!("{}", file.display());
+        }
+        print!("{}", load(file)?.summary());
+    }
+    Ok(true)
+}
+
+fn convert(matches: &ArgMatches) -> Result<bool, Box<dyn Error>> {
+    let file = matches.get_one::<PathBuf>("file").unwrap();
+    let format = matches.get_one::<String>("to").unwrap();
+    let output = output_file(matches, file, "", format);
+    let c3d = load(file)?;
+    match format.as_str() {
+        "trc" => Trc::from_c3d(&c3d).write(output.clone())?,
+        "csv" => Csv::from_c3d(&c3d).write(output.clone())?,
+        _ => Sto::from_c3d(&c3d).write(output.clone())?,
+    }
+    println!("Wrote {}", output.display());
+    Ok(true)
+}
+
+fn validate(matches: &ArgMatches) -> Result<bool, Box<dyn Error>> {
+    let mut valid = true;
+    for file in files(matches) {
+        let report = C3d::validate_file(&file.to_string_lossy())
+            .map_err(|e| format!("{}: {}", file.display(), e))?;
+        for issue in &report.issues {
+            println!("{}: {}", file.display(), issue);
+        }
+        match report.is_valid() {
+            true => println!("{}: valid", file.display()),
+            false => {
+                println!("{}: invalid", file.display());
+                valid = false;
+            }
+        }
+    }
+    Ok(valid)
+}
+
+fn crop(matches: &ArgMatches) -> Result<bool, Box<dyn Error>> {
+    let file = matches.get_one::<PathBuf>("file").unwrap();
+    let frames = matches.get_one::<Range<usize>>("frames").unwrap();
+    let output = output_file(matches, file, "-cropped", "c3d");
+    let c3d = load(file)?.crop(frames.clone());
+    c3d.write(&output.to_string_lossy())?;
+    println!("Wrote {} frames to {}", c3d.frame_count(), output.display());
+    Ok(true)
+}
+
+fn main() -> ExitCode {
+    let matches = cli().get_matches();
+    let result = match matches.subcommand() {
+        Some(("info", matches)) => info(matches),
+        Some(("convert", matches)) => convert(matches),
+        Some(("validate", matches)) => validate(matches),
+        Some(("crop", matches)) => crop(matches),
+        _ => unreachable!("a subcommand is required"),
+    };
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
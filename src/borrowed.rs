@@ -0,0 +1,330 @@
+//! A view of the header and parameters of a file that borrows its names and strings
+//! from the bytes of the file, for scanning the metadata of many files without the
+//! allocations of loading each of them into a `C3d`.
+use crate::c3d::C3d;
+use crate::parameters::{
+    section_byte, section_bytes, utf8_lossy, DataType, Parameter, ParameterData,
+};
+use crate::processor::Processor;
+use crate::C3dParseError;
+use std::borrow::Cow;
+
+/// The header and parameters of a file, parsed from a borrowed byte slice.
+/// Names, descriptions and strings borrow from the slice, so listing the parameters
+/// or labels of a file only allocates the vectors holding them.
+/// The data section is not read: use `to_c3d` to load the file.
+///
+/// Strings that are not ASCII are decoded one byte per character, as `C3d` does,
+/// and are the only ones copied. Descriptions that are not valid UTF-8 are decoded with
+/// U+FFFD in place of the invalid bytes, as `C3d` does, and are also copied.
+/// Names keep their case and are matched ignoring it.
+/// A file whose processor byte is not recognized is read as Intel.
+///
+/// # Examples
+/// ```
+/// use c3dio::prelude::*;
+///
+/// let bytes = std::fs::read("tests/data/short.c3d").unwrap();
+/// let c3d = C3d::from_bytes_ref(&bytes).unwrap();
+/// assert_eq!(c3d.point_labels().unwrap()[0], "THEA");
+/// ```
+#[derive(Debug, Clone)]
+pub struct C3dRef<'a> {
+    bytes: &'a [u8],
+    processor: Processor,
+    groups: Vec<GroupRef<'a>>,
+    parameters: Vec<ParameterRef<'a>>,
+}
+
+/// A group of the parameter section, borrowed from the bytes of a file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupRef<'a> {
+    /// The ID the parameters of the group refer to, which is positive: a file storing
+    /// the ID of a group as -128 fails to parse with `InvalidGroupId`.
+    pub id: i8,
+    pub name: Cow<'a, str>,
+    pub description: Cow<'a, str>,
+    /// Whether the group is locked, from the sign of the length of its name.
+    pub locked: bool,
+}
+
+/// A parameter of the parameter section, borrowed from the bytes of a file.
+/// Its data is decoded when one of the accessors for its data type is called.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParameterRef<'a> {
+    /// The ID of the group of the parameter.
+    pub group_id: i8,
+    pub name: Cow<'a, str>,
+    pub description: Cow<'a, str>,
+    pub data_type: DataType,
+    /// Whether the parameter is locked, from the sign of the length of its name.
+    pub locked: bool,
+    dimensions: &'a [u8],
+    data: &'a [u8],
+    processor: Processor,
+}
+
+impl<'a> C3dRef<'a> {
+    /// Parses the header and parameter section of the bytes of a file.
+    /// Fails where `C3d::from_bytes` fails to find or parse the parameter section, but
+    /// does not need the data section.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<C3dRef<'a>, C3dParseError> {
+        let header = bytes
+            .get(..512)
+            .ok_or_else(|| C3dParseError::InsufficientBlocks("header".to_string()))?;
+        let parameter_start_block = header[0] as usize;
+        if parameter_start_block < 2 {
+            return Err(C3dParseError::InvalidParameterStartBlock);
+        }
+        let start = 512 * (parameter_start_block - 1);
+        if bytes.len() < start + 512 {
+            return Err(C3dParseError::InsufficientBlocks("parameter".to_string()));
+        }
+        let processor = Processor::from_byte(bytes[start + 3]).unwrap_or_default();
+        let data_start_block = processor.u16([header[16], header[17]]) as usize;
+        if data_start_block <= parameter_start_block {
+            return Err(C3dParseError::InvalidDataStartBlock);
+        }
+        let end = (512 * (data_start_block - 1)).min(bytes.len());
+        let mut c3d = C3dRef {
+            bytes,
+            processor,
+            groups: Vec::new(),
+            parameters: Vec::new(),
+        };
+        c3d.parse_parameters(&bytes[start..end])?;
+        Ok(c3d)
+    }
+
+    fn parse_parameters(&mut self, section: &'a [u8]) -> Result<(), C3dParseError> {
+        let processor = self.processor;
+        // the records start after the four bytes of the parameter header
+        let mut index = 4;
+        while let Some(&id) = section.get(index + 1) {
+            let id = id as i8;
+            if id == 0 {
+                break;
+            }
+            let name_length = section[index] as i8;
+            let mut i = index + 2;
+            let name = section_bytes(section, i, name_length.unsigned_abs() as usize)?;
+            i += name.len();
+            let offset = section_bytes(section, i, 2)?;
+            let offset = processor.u16([offset[0], offset[1]]) as usize;
+            let next = i + offset;
+            i += 2;
+            if id < 0 {
+                let description_length = section_byte(section, i)? as usize;
+                let description = section_bytes(section, i + 1, description_length)?;
+                self.groups.push(GroupRef {
+                    id: id.checked_neg().ok_or(C3dParseError::InvalidGroupId)?,
+                    name: latin1(name),
+                    description: utf8_lossy(description),
+                    locked: name_length < 0,
+                });
+            } else {
+                let data_type = DataType::try_from(section_byte(section, i)? as i8)?;
+                let num_dimensions = section_byte(section, i + 1)? as usize;
+                let dimensions = section_bytes(section, i + 2, num_dimensions)?;
+                i += 2 + num_dimensions;
+                let len = dimensions
+                    .iter()
+                    .filter(|&&dimension| dimension != 0)
+                    .try_fold(usize::from(data_type), |size, &x| {
+                        size.checked_mul(x as usize)
+                    })
+                    .ok_or(C3dParseError::InvalidParameterData)?;
+                let data = section_bytes(section, i, len)
+                    .map_err(|_| C3dParseError::InvalidParameterData)?;
+                i += len;
+                let description_length = section_byte(section, i)? as usize;
+                let description = section_bytes(section, i + 1, description_length)?;
+                self.parameters.push(ParameterRef {
+                    group_id: id,
+                    name: latin1(name),
+                    description: utf8_lossy(description),
+                    data_type,
+                    locked: name_length < 0,
+                    dimensions,
+                    data,
+                    processor,
+                });
+            }
+            if offset == 0 {
+                break;
+            }
+            index = next;
+        }
+        Ok(())
+    }
+
+    pub fn processor(&self) -> Processor {
+        self.processor
+    }
+
+    fn header_word(&self, word: usize) -> u16 {
+        self.processor
+            .u16([self.bytes[2 * word], self.bytes[2 * word + 1]])
+    }
+
+    /// Returns the first frame in the header.
+    pub fn first_frame(&self) -> u16 {
+        self.header_word(3)
+    }
+
+    /// Returns the last frame in the header, which is 65535 for trials whose last frame
+    /// is in the TRIAL group.
+    pub fn last_frame(&self) -> u16 {
+        self.header_word(4)
+    }
+
+    /// Returns the frame rate in the header.
+    pub fn frame_rate(&self) -> f32 {
+        self.processor.f32(self.bytes[20..24].try_into().unwrap())
+    }
+
+    /// Returns the groups in the order they are stored.
+    pub fn groups(&self) -> &[GroupRef<'a>] {
+        &self.groups
+    }
+
+    /// Returns the parameters in the order they are stored.
+    pub fn parameters(&self) -> &[ParameterRef<'a>] {
+        &self.parameters
+    }
+
+    /// Returns the first group with the given name.
+    pub fn group(&self, name: &str) -> Option<&GroupRef<'a>> {
+        self.groups
+            .iter()
+            .find(|group| group.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the group a parameter belongs to, if the file defines it.
+    pub fn group_of(&self, parameter: &ParameterRef<'a>) -> Option<&GroupRef<'a>> {
+        self.groups
+            .iter()
+            .find(|group| group.id == parameter.group_id)
+    }
+
+    /// Returns the first definition of a parameter in any group with the given name.
+    pub fn parameter(&self, group: &str, name: &str) -> Option<&ParameterRef<'a>> {
+        self.parameters.iter().find(|parameter| {
+            parameter.name.eq_ignore_ascii_case(name)
+                && self
+                    .group_of(parameter)
+                    .is_some_and(|found| found.name.eq_ignore_ascii_case(group))
+        })
+    }
+
+    /// Returns the strings of a parameter joined with those of its continuations, such as
+    /// POINT:LABELS, POINT:LABELS2 and so on for files with more than 255 points.
+    pub fn strings(&self, group: &str, name: &str) -> Option<Vec<Cow<'a, str>>> {
+        let mut strings = self.parameter(group, name)?.strings()?;
+        for index in 2.. {
+            match self.parameter(group, &format!("{}{}", name, index)) {
+                Some(continued) => strings.extend(continued.strings()?),
+                None => break,
+            }
+        }
+        Some(strings)
+    }
+
+    /// Returns POINT:LABELS and its continuations.
+    pub fn point_labels(&self) -> Option<Vec<Cow<'a, str>>> {
+        self.strings("POINT", "LABELS")
+    }
+
+    /// Returns ANALOG:LABELS and its continuations.
+    pub fn analog_labels(&self) -> Option<Vec<Cow<'a, str>>> {
+        self.strings("ANALOG", "LABELS")
+    }
+
+    /// Loads the whole file, as `C3d::from_bytes` does.
+    pub fn to_c3d(&self) -> Result<C3d, C3dParseError> {
+        C3d::from_bytes(self.bytes)
+    }
+}
+
+impl<'a> ParameterRef<'a> {
+    /// Returns the dimensions of the parameter as `Parameter` holds them, without
+    /// dimensions of 0, and of 1 for data that is not characters.
+    pub fn dimensions(&self) -> Vec<u8> {
+        let mut dimensions: Vec<u8> = self
+            .dimensions
+            .iter()
+            .copied()
+            .filter(|&x| x != 0 && (x != 1 || self.data_type == DataType::Char))
+            .collect();
+        if dimensions.is_empty() {
+            dimensions.push(1);
+        }
+        dimensions
+    }
+
+    /// Returns the strings of a character parameter, one per column of a two dimensional
+    /// array, trimmed. Returns `None` for other data types or more dimensions.
+    pub fn strings(&self) -> Option<Vec<Cow<'a, str>>> {
+        if self.data_type != DataType::Char {
+            return None;
+        }
+        let dimensions = self.dimensions();
+        let (length, count) = match dimensions[..] {
+            [length] => (length as usize, 1),
+            [length, count] => (length as usize, count as usize),
+            _ => return None,
+        };
+        Some(
+            (0..count)
+                .map(|i| trim(latin1(&self.data[i * length..(i + 1) * length])))
+                .collect(),
+        )
+    }
+
+    /// Returns the bytes of a byte parameter.
+    pub fn bytes(&self) -> Option<&'a [u8]> {
+        match self.data_type {
+            DataType::Byte => Some(self.data),
+            _ => None,
+        }
+    }
+
+    pub fn integers(&self) -> Option<Vec<i16>> {
+        match self.data_type {
+            DataType::Integer => Some(self.processor.i16_slice(self.data)),
+            _ => None,
+        }
+    }
+
+    pub fn floats(&self) -> Option<Vec<f32>> {
+        match self.data_type {
+            DataType::Float => Some(self.processor.f32_slice(self.data)),
+            _ => None,
+        }
+    }
+
+    /// Copies the parameter into a `Parameter`, named in upper case as `C3d` names it.
+    pub fn to_parameter(&self) -> Result<Parameter, C3dParseError> {
+        let dimensions = self.dimensions();
+        let data = ParameterData::new(self.data, &dimensions, self.data_type, &self.processor)?;
+        let mut parameter = Parameter::new(self.description.to_string(), dimensions, data)?;
+        parameter.name = self.name.to_ascii_uppercase();
+        Ok(parameter)
+    }
+}
+
+/// Borrows ASCII bytes as a string, and decodes others one byte per character.
+fn latin1(bytes: &[u8]) -> Cow<'_, str> {
+    match bytes.is_ascii() {
+        // ASCII is valid UTF-8
+        true => Cow::Borrowed(std::str::from_utf8(bytes).unwrap()),
+        false => Cow::Owned(bytes.iter().map(|&c| c as char).collect()),
+    }
+}
+
+fn trim(string: Cow<'_, str>) -> Cow<'_, str> {
+    match string {
+        Cow::Borrowed(string) => Cow::Borrowed(string.trim()),
+        Cow::Owned(string) => Cow::Owned(string.trim().to_string()),
+    }
+}
@@ -1,26 +1,258 @@
 //! The builder module is focused on building a C3d struct for writing to a file.
 //! It is focused on composing parts into a valid C3d struct.
 
+use crate::analog::AnalogOffset;
 use crate::c3d::C3d;
+use crate::data::{DataFormat, MarkerPoint};
+use crate::forces::ForcePlatform;
+use crate::processor::Processor;
+use grid::Grid;
 use std::{error::Error, fmt};
 
+/// Groups that are written for every file, even if they hold no data.
+const REQUIRED_GROUPS: [&str; 4] = ["POINT", "ANALOG", "FORCE_PLATFORM", "EVENT"];
+
 /// The C3dBuilder is a struct that is used to build a C3d struct.
-#[derive(Debug, Default)]
+/// Labels, rates and units are set first, after which frames are pushed one at a time.
+/// The mandatory POINT and ANALOG parameters and the header fields are derived
+/// from them when the file is built.
+///
+/// # Examples
+/// ```
+/// use c3dio::prelude::*;
+/// use c3dio::C3dBuilder;
+///
+/// let c3d = C3dBuilder::new()
+///     .point_labels(&["LASI", "RASI"])
+///     .frame_rate(100.)
+///     .analog_channels(&["Fz"], 10)
+///     .push_frame(&[[0., 0., 0.], [100., 0., 0.]], &[1.; 10])
+///     .unwrap()
+///     .build()
+///     .unwrap();
+/// assert_eq!(c3d.points.rows(), 1);
+/// assert_eq!(c3d.analog.rate, 1000.);
+/// ```
+#[derive(Debug)]
 pub struct C3dBuilder {
     pub(crate) c3d: C3d,
+    points: Vec<MarkerPoint>,
+    analog: Vec<f64>,
+    num_frames: usize,
 }
 
 impl C3dBuilder {
     /// Creates a new C3dBuilder.
+    /// Points are stored in millimeters as floats, with one analog sample per frame.
     pub fn new() -> Self {
+        let mut c3d = C3d::default();
+        c3d.points.units = ['m', 'm', ' ', ' '];
+        c3d.points.scale_factor = 1.;
+        c3d.analog.samples_per_channel_per_frame = 1;
+        c3d.analog.gen_scale = 1.;
+        c3d.analog.bits = 16;
         C3dBuilder {
-            c3d: C3d::default(),
+            c3d,
+            points: Vec::new(),
+            analog: Vec::new(),
+            num_frames: 0,
         }
     }
 
+    /// Sets the labels of the points, one per point stored in each frame.
+    /// The descriptions are set to the labels unless they are set separately.
+    pub fn point_labels(mut self, labels: &[&str]) -> Self {
+        self.c3d.points.labels = labels.iter().map(|x| x.to_string()).collect();
+        self.c3d.points.descriptions = self.c3d.points.labels.clone();
+        self
+    }
+
+    pub fn point_descriptions(mut self, descriptions: &[&str]) -> Self {
+        self.c3d.points.descriptions = descriptions.iter().map(|x| x.to_string()).collect();
+        self
+    }
+
+    /// Sets the units of the points, e.g. "mm". At most 4 characters are kept.
+    pub fn point_units(mut self, units: &str) -> Self {
+        let mut chars = [' '; 4];
+        for (i, c) in units.chars().take(4).enumerate() {
+            chars[i] = c;
+        }
+        self.c3d.points.units = chars;
+        self
+    }
+
+    /// Sets the rate of the point frames in Hz.
+    pub fn frame_rate(mut self, frame_rate: f32) -> Self {
+        self.c3d.points.frame_rate = frame_rate;
+        self
+    }
+
+    /// Sets the number of the first frame, which is 1 by default.
+    pub fn first_frame(mut self, first_frame: u16) -> Self {
+        self.c3d.points.first_frame = first_frame;
+        self
+    }
+
+    /// Sets whether the data is stored as floats or integers.
+    /// Integer data is divided by the scale factor when written.
+    pub fn data_format(mut self, format: DataFormat, scale_factor: f32) -> Self {
+        self.c3d.points.format = format;
+        self.c3d.points.scale_factor = scale_factor.abs();
+        self
+    }
+
+    pub fn processor(mut self, processor: Processor) -> Self {
+        self.c3d.convert_to(processor);
+        self
+    }
+
+    /// Sets the labels of the analog channels and the number of samples
+    /// of each channel per point frame.
+    pub fn analog_channels(mut self, labels: &[&str], samples_per_channel_per_frame: u16) -> Self {
+        self.c3d.analog.labels = labels.iter().map(|x| x.to_string()).collect();
+        self.c3d.analog.descriptions = self.c3d.analog.labels.clone();
+        self.c3d.analog.samples_per_channel_per_frame = samples_per_channel_per_frame;
+        self
+    }
+
+    pub fn analog_descriptions(mut self, descriptions: &[&str]) -> Self {
+        self.c3d.analog.descriptions = descriptions.iter().map(|x| x.to_string()).collect();
+        self
+    }
+
+    /// Sets the units of each analog channel, e.g. "N" or "V".
+    pub fn analog_units(mut self, units: &[&str]) -> Self {
+        self.c3d.analog.units = units.iter().map(|x| x.to_string()).collect();
+        self
+    }
+
+    /// Sets the scale of each analog channel, used to store integer data.
+    /// The scales default to 1.
+    pub fn analog_scales(mut self, scales: &[f32]) -> Self {
+        self.c3d.analog.scales = scales.to_vec();
+        self
+    }
+
+    /// Adds a force platform, written to the FORCE_PLATFORM group with the platforms
+    /// added before it. Its channels must be among the analog channels when the file
+    /// is built.
+    pub fn force_platform(mut self, platform: ForcePlatform) -> Self {
+        self.c3d.forces.push(platform);
+        self
+    }
+
+    /// Adds a frame with the position of every point and the analog samples recorded
+    /// during the frame. The samples are ordered by sample and then by channel.
+    pub fn push_frame(self, points: &[[f32; 3]], analog: &[f64]) -> Result<Self, C3dBuilderError> {
+        let points: Vec<MarkerPoint> = points
+            .iter()
+            .map(|point| MarkerPoint::new(point[0], point[1], point[2]))
+            .collect();
+        self.push_marker_frame(&points, analog)
+    }
+
+    /// Adds a frame like `push_frame`, including the residual and cameras of each point.
+    pub fn push_marker_frame(
+        mut self,
+        points: &[MarkerPoint],
+        analog: &[f64],
+    ) -> Result<Self, C3dBuilderError> {
+        if points.len() != self.c3d.points.labels.len() {
+            return Err(C3dBuilderError::PointCountMismatch(
+                self.c3d.points.labels.len(),
+                points.len(),
+            ));
+        }
+        let num_samples = self.num_analog_samples();
+        if analog.len() != num_samples {
+            return Err(C3dBuilderError::AnalogSampleCountMismatch(
+                num_samples,
+                analog.len(),
+            ));
+        }
+        self.points.extend_from_slice(points);
+        self.analog.extend_from_slice(analog);
+        self.num_frames += 1;
+        Ok(self)
+    }
+
+    fn num_analog_samples(&self) -> usize {
+        self.c3d.analog.labels.len() * self.c3d.analog.samples_per_channel_per_frame as usize
+    }
+
     /// Consumes the builder and returns a C3d struct.
-    pub fn build(self) -> C3d {
-        self.c3d
+    /// Fails if the labels or channels were changed after frames were pushed,
+    /// or if the data does not fit in a C3D file.
+    pub fn build(self) -> Result<C3d, C3dBuilderError> {
+        let mut c3d = self.c3d;
+        let num_points = c3d.points.labels.len();
+        let num_channels = c3d.analog.labels.len();
+        if self.points.len() != self.num_frames * num_points {
+            return Err(C3dBuilderError::PointCountMismatch(
+                num_points,
+                self.points.len() / self.num_frames.max(1),
+            ));
+        }
+        let samples_per_frame = num_channels * c3d.analog.samples_per_channel_per_frame as usize;
+        if self.analog.len() != self.num_frames * samples_per_frame {
+            return Err(C3dBuilderError::AnalogSampleCountMismatch(
+                samples_per_frame,
+                self.analog.len() / self.num_frames.max(1),
+            ));
+        }
+        if num_points > u16::MAX as usize || samples_per_frame > u16::MAX as usize {
+            return Err(C3dBuilderError::InvalidParameter);
+        }
+        for (i, platform) in c3d.forces.iter().enumerate() {
+            let channels = &platform.channels[..platform.num_channels()];
+            if let Some(&channel) = channels
+                .iter()
+                .find(|&&channel| channel == 0 || channel as usize > num_channels)
+            {
+                return Err(C3dBuilderError::ForcePlatformChannelNotFound(i, channel));
+            }
+        }
+        if c3d.points.first_frame == 0 {
+            c3d.points.first_frame = 1;
+        }
+        let last_frame = c3d.points.first_frame as usize + self.num_frames.max(1) - 1;
+        if last_frame > u32::MAX as usize {
+            return Err(C3dBuilderError::TooManyFrames(self.num_frames));
+        }
+        // the header holds at most 65535 frames, longer trials are written to the TRIAL group
+        c3d.points.last_frame = last_frame.min(u16::MAX as usize) as u16;
+        c3d.points.points = match num_points {
+            0 => Grid::new(0, 0),
+            _ => Grid::from_vec(self.points, num_points),
+        };
+
+        c3d.analog.samples_per_frame = samples_per_frame as u16;
+        c3d.analog.rate = c3d.points.frame_rate * c3d.analog.samples_per_channel_per_frame as f32;
+        if c3d.analog.units.len() != num_channels {
+            c3d.analog.units = vec!["V".to_string(); num_channels];
+        }
+        if c3d.analog.scales.len() != num_channels {
+            c3d.analog.scales = vec![1.; num_channels];
+        }
+        c3d.analog.offset = AnalogOffset::Signed(vec![0; num_channels]);
+        c3d.analog.analog = match num_channels {
+            0 => Grid::new(0, 0),
+            _ => Grid::from_vec(self.analog, num_channels),
+        };
+
+        for group in REQUIRED_GROUPS {
+            if c3d.parameters.get_group(group).is_none() {
+                c3d.parameters.insert_group(group, String::new());
+            }
+        }
+        Ok(c3d)
+    }
+}
+
+impl Default for C3dBuilder {
+    fn default() -> Self {
+        C3dBuilder::new()
     }
 }
 
@@ -28,6 +260,16 @@ impl C3dBuilder {
 #[derive(Debug)]
 pub enum C3dBuilderError {
     InvalidParameter,
+    /// The number of point labels and the number of points in a frame differ.
+    PointCountMismatch(usize, usize),
+    /// The number of analog samples per frame expected from the channels
+    /// and the number of samples in a frame differ.
+    AnalogSampleCountMismatch(usize, usize),
+    /// The number of frames does not fit in TRIAL:ACTUAL_END_FIELD.
+    TooManyFrames(usize),
+    /// A force platform, counted from 0, reads a channel that is 0 or not among the
+    /// analog channels, counted from 1.
+    ForcePlatformChannelNotFound(usize, u8),
 }
 
 impl Error for C3dBuilderError {}
@@ -1,24 +1,60 @@
 //! Includes the C3d struct implementation and high-level functions for reading and writing C3D files.
-use crate::analog::Analog;
-use crate::data::DataFormat;
-use crate::forces::ForcePlatforms;
+use crate::analog::{Analog, AnalogChannel};
+use crate::borrowed::C3dRef;
+use crate::data::{
+    concat_rows, get_analog_bytes_per_frame, get_point_bytes_per_frame, selected_columns,
+    DataFormat, FrameIter, FrameSink, MarkerPoint,
+};
+use crate::diff::{pair_columns, C3dDiff, DiffOptions, DiffSide, Difference};
+use crate::emg::EmgProcessing;
+use crate::events::detect::{detect, Detection, Foot};
+use crate::filters::{filter_points, filtfilt, Butterworth};
+use crate::forces::{ForceFrame, ForcePlatformData, ForcePlatforms};
+use crate::gaps::{fill_pattern, fill_spline, gaps, GapFill};
+use crate::kinematics::{angle_between_frames, rotate, segment_frame};
+use crate::labels::RelabelReport;
 use crate::manufacturer::Manufacturer;
-use crate::parameters::Parameters;
-use crate::points::Points;
+use crate::parameters::{encode_text, Parameter, ParameterData, Parameters, TextEncoding};
+use crate::points::{Points, ScreenAxis};
+use crate::progress::{report, Progress, ProgressStage, CHUNK_SIZE};
+use crate::quality::{force_platform_quality, marker_quality, AnalogQuality, QualityReport};
+use crate::repair::{repair, Repair};
+use crate::resample::{resample_column, resample_rows, Interpolation};
+use crate::rigid_body::{Pose, RigidBody};
+use crate::rotations::{Rotation, Rotations};
 use crate::seg::Seg;
+use crate::smoothing::Smoother;
+use crate::subjects::Subjects;
+use crate::timecode::{Timecode, TimecodeGroup};
+use crate::units::{AngleUnit, LengthUnit, MomentUnit, PointChannel, PointKind, UnitSet};
+use crate::validate::{validate, ValidationLocation, ValidationReport};
+use crate::vendor::Processing;
 
-use crate::events::Events;
+use crate::events::{Event, Events};
 use crate::processor::Processor;
-use crate::{C3dParseError, C3dWriteError};
+use crate::{
+    C3dFilterError, C3dMergeError, C3dParseError, C3dWriteError, ErrorContext, ParseWarning,
+};
 
+use std::borrow::Cow;
+use std::collections::HashMap;
+#[cfg(feature = "fs")]
 use std::fs::File;
+#[cfg(feature = "fs")]
+use std::io::BufReader;
 use std::io::{Read, Seek, SeekFrom, Write};
-use std::path::PathBuf;
+use std::ops::{ControlFlow, Range};
+#[cfg(feature = "fs")]
+use std::path::{Path, PathBuf};
 
 use std::fmt::{Debug, Formatter};
 
+use grid::Grid;
+
 /// Represents a parsed C3D file.
 /// Each field contains the data from the corresponding section of the file.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct C3d {
     pub parameters: Parameters,
     processor: Processor,
@@ -28,7 +64,276 @@ pub struct C3d {
     pub manufacturer: Manufacturer,
     pub seg: Seg,
     pub forces: ForcePlatforms,
+    pub rotations: Rotations,
+    pub warnings: Vec<ParseWarning>,
+    options: ParseOptions,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::header_bytes"))]
     header_bytes: [u8; 512],
+    #[cfg_attr(feature = "serde", serde(skip))]
+    original_bytes: Option<Vec<u8>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    trailing_bytes: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    dirty: bool,
+}
+
+/// Controls how tolerant the parser is of malformed files.
+/// In lenient mode (the default) recoverable violations of the C3D format are reported
+/// as `ParseWarning`s in the `warnings` field of the parsed `C3d`, so files written by
+/// vendors that bend the format still load. In strict mode they are errors.
+/// Quirks that do not affect the decoded data, such as descriptions that are not
+/// valid text or unused blocks after the data section, are warnings in both modes.
+/// With `preserve_bytes` the original file is kept in memory, and writing a file
+/// that has not been modified reproduces it byte for byte. Blocks after the data
+/// section that do not belong to any frame, such as vendor data, are also kept and
+/// written back after the frames when a modified file is written.
+/// Groups and parameters that are not interpreted are always kept.
+/// With `point_labels` or `analog_labels` only the points or analog channels with
+/// those labels are decoded, and the others are dropped from the parsed file.
+/// `limits` bounds the sizes a file may declare, for parsing files from untrusted sources.
+/// With `duplicates` set, groups and parameters a file defines more than once are
+/// resolved by the policy in both modes; see `DuplicatePolicy`.
+/// `encoding` is the encoding of labels, descriptions and other text that is not ASCII,
+/// which is also used to write the file; see `TextEncoding`.
+/// With `assume_intel` a file whose processor byte is not recognized is read as Intel
+/// with a warning in lenient mode, instead of failing with `InvalidProcessorType`.
+/// `ParseOptions::lenient` sets it.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParseOptions {
+    pub strict: bool,
+    pub preserve_bytes: bool,
+    pub point_labels: Option<Vec<String>>,
+    pub analog_labels: Option<Vec<String>>,
+    pub limits: ParseLimits,
+    pub duplicates: Option<DuplicatePolicy>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub encoding: Option<TextEncoding>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub assume_intel: bool,
+}
+
+/// Which definition is kept of a group or parameter that a file defines more than once,
+/// as some software does, e.g. Motion Analysis files repeating the ANALOG group.
+/// Without a policy a parameter defined again with a different value is an error in
+/// strict mode, and the first definition is kept in lenient mode.
+/// Every definition of a repeated parameter is available from `Parameters::definitions`,
+/// and the parameters of a repeated group are merged into one group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DuplicatePolicy {
+    /// Keep the definition that comes first in the file.
+    FirstWins,
+    /// Keep the definition that comes last in the file, as readers that store each
+    /// definition over the previous one do.
+    LastWins,
+}
+
+/// Limits on the sizes a file may declare, checked while it is parsed.
+/// A file exceeding a limit fails to parse with `C3dParseError::LimitExceeded`.
+/// The sizes in the header and parameters are always checked against the length of
+/// the file, so a truncated file cannot make the parser allocate more than the file
+/// holds. The limits additionally bound files that are large but well formed.
+/// By default there are no limits; `ParseLimits::untrusted` gives limits suited to
+/// files uploaded by users.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParseLimits {
+    /// The most frames that are decoded.
+    pub max_frames: Option<usize>,
+    /// The most parameters the parameter section may hold.
+    pub max_parameters: Option<usize>,
+    /// The most characters in each of the labels of a LABELS parameter,
+    /// such as POINT:LABELS or ANALOG:LABELS.
+    pub max_label_length: Option<usize>,
+    /// The most bytes held in memory for the file, counting the bytes read from it
+    /// and the decoded points and analog samples.
+    pub max_allocation: Option<usize>,
+}
+
+impl ParseLimits {
+    /// Limits that hold for the files of long trials, but keep a file from using
+    /// more than 512 MiB of memory while it is parsed.
+    pub fn untrusted() -> ParseLimits {
+        ParseLimits {
+            max_frames: Some(1_000_000),
+            max_parameters: Some(10_000),
+            max_label_length: Some(128),
+            max_allocation: Some(512 * 1024 * 1024),
+        }
+    }
+
+    pub(crate) fn check_frames(&self, frames: usize) -> Result<(), C3dParseError> {
+        check_limit(frames, self.max_frames, "frames")
+    }
+
+    pub(crate) fn check_parameters(&self, parameters: usize) -> Result<(), C3dParseError> {
+        check_limit(parameters, self.max_parameters, "parameters")
+    }
+
+    pub(crate) fn check_label_length(&self, length: usize) -> Result<(), C3dParseError> {
+        check_limit(length, self.max_label_length, "characters in a label")
+    }
+
+    pub(crate) fn check_allocation(&self, bytes: usize) -> Result<(), C3dParseError> {
+        check_limit(bytes, self.max_allocation, "bytes of memory")
+    }
+}
+
+fn check_limit(size: usize, limit: Option<usize>, what: &str) -> Result<(), C3dParseError> {
+    match limit {
+        Some(limit) if size > limit => {
+            Err(C3dParseError::LimitExceeded(what.to_string(), size, limit))
+        }
+        _ => Ok(()),
+    }
+}
+
+impl ParseOptions {
+    /// Options that turn recoverable violations into warnings, as the default does,
+    /// and also read a file with an unknown processor byte as Intel.
+    pub fn lenient() -> ParseOptions {
+        ParseOptions {
+            strict: false,
+            assume_intel: true,
+            ..ParseOptions::default()
+        }
+    }
+
+    /// Options that make any violation of the C3D format an error, e.g. to check files
+    /// before archiving them.
+    pub fn strict() -> ParseOptions {
+        ParseOptions {
+            strict: true,
+            ..ParseOptions::default()
+        }
+    }
+
+    /// The same options, keeping the original bytes of the file for lossless writing.
+    pub fn with_preserved_bytes(self) -> ParseOptions {
+        ParseOptions {
+            preserve_bytes: true,
+            ..self
+        }
+    }
+
+    /// The same options, decoding only the points with the given labels.
+    /// Labels are compared without surrounding whitespace, and labels that are
+    /// not in the file are ignored.
+    pub fn with_point_labels(self, labels: &[&str]) -> ParseOptions {
+        ParseOptions {
+            point_labels: Some(labels.iter().map(|label| label.to_string()).collect()),
+            ..self
+        }
+    }
+
+    /// The same options, decoding only the analog channels with the given labels.
+    /// The channels of the force platforms are renumbered to match, and set to 0
+    /// for channels that are not decoded.
+    pub fn with_analog_labels(self, labels: &[&str]) -> ParseOptions {
+        ParseOptions {
+            analog_labels: Some(labels.iter().map(|label| label.to_string()).collect()),
+            ..self
+        }
+    }
+
+    /// The same options, with the given limits on the sizes a file may declare.
+    pub fn with_limits(self, limits: ParseLimits) -> ParseOptions {
+        ParseOptions { limits, ..self }
+    }
+
+    /// The same options, resolving repeated groups and parameters with the given policy.
+    pub fn with_duplicates(self, policy: DuplicatePolicy) -> ParseOptions {
+        ParseOptions {
+            duplicates: Some(policy),
+            ..self
+        }
+    }
+
+    /// The same options, reading text that is not ASCII in the given encoding, e.g.
+    /// `TextEncoding::Windows1252` for the labels of files from older Windows software.
+    pub fn with_encoding(self, encoding: TextEncoding) -> ParseOptions {
+        ParseOptions {
+            encoding: Some(encoding),
+            ..self
+        }
+    }
+
+    /// Whether only some of the points or analog channels are decoded.
+    fn is_selective(&self) -> bool {
+        self.point_labels.is_some() || self.analog_labels.is_some()
+    }
+}
+
+/// Options for writing a C3D file with `C3d::write_with_options`.
+/// By default a file is written in the processor format and data format it has.
+/// With `format` set to `DataFormat::Integer` points and analog samples are stored
+/// as scaled 16-bit integers, as some older software requires, with scales
+/// computed from the data as `C3d::convert_format` does.
+/// With `snap_events` set, events are written at the time of their nearest frame, as by
+/// `C3d::snap_events_to_frames`.
+/// Text is written in the encoding the file was parsed with unless `encoding` is set.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WriteOptions {
+    pub processor: Option<Processor>,
+    pub format: Option<DataFormat>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub snap_events: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub encoding: Option<TextEncoding>,
+}
+
+impl WriteOptions {
+    /// Options that write points and analog samples as scaled 16-bit integers.
+    pub fn integer() -> WriteOptions {
+        WriteOptions::default().with_format(DataFormat::Integer)
+    }
+
+    /// The same options, encoding the file for the given processor type.
+    pub fn with_processor(self, processor: Processor) -> WriteOptions {
+        WriteOptions {
+            processor: Some(processor),
+            ..self
+        }
+    }
+
+    /// The same options, storing points and analog samples in the given format.
+    pub fn with_format(self, format: DataFormat) -> WriteOptions {
+        WriteOptions {
+            format: Some(format),
+            ..self
+        }
+    }
+
+    /// The same options, moving events to the time of their nearest frame if `snap` is set.
+    pub fn with_snapped_events(self, snap: bool) -> WriteOptions {
+        WriteOptions {
+            snap_events: snap,
+            ..self
+        }
+    }
+
+    /// The same options, writing labels, descriptions and other text in the given encoding,
+    /// e.g. `TextEncoding::Windows1252` for software that does not read UTF-8.
+    pub fn with_encoding(self, encoding: TextEncoding) -> WriteOptions {
+        WriteOptions {
+            encoding: Some(encoding),
+            ..self
+        }
+    }
+}
+
+/// How `C3d::merge_points` handles a point label used in both files.
+/// Labels are compared without surrounding whitespace.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LabelCollision {
+    /// Fail with `C3dMergeError::LabelCollision`.
+    Error,
+    /// Keep the point of the first file and drop the point of the second.
+    Skip,
+    /// Prefix the label of the point of the second file, e.g. with "Device2:".
+    Prefix(String),
 }
 
 impl PartialEq for C3d {
@@ -39,6 +344,7 @@ impl PartialEq for C3d {
             && self.manufacturer == other.manufacturer
             && self.seg == other.seg
             && self.forces == other.forces
+            && self.rotations == other.rotations
             && self.parameters == other.parameters
             && self.events == other.events
     }
@@ -53,6 +359,7 @@ impl Debug for C3d {
             .field("manufacturer", &self.manufacturer)
             .field("seg", &self.seg)
             .field("forces", &self.forces)
+            .field("rotations", &self.rotations)
             .field("parameters", &self.parameters)
             .field("events", &self.events)
             .finish()
@@ -70,7 +377,13 @@ impl Default for C3d {
             manufacturer: Manufacturer::default(),
             seg: Seg::default(),
             forces: ForcePlatforms::default(),
+            rotations: Rotations::default(),
+            warnings: Vec::new(),
+            options: ParseOptions::default(),
             header_bytes: [0u8; 512],
+            original_bytes: None,
+            trailing_bytes: Vec::new(),
+            dirty: false,
         }
     }
 }
@@ -94,6 +407,7 @@ impl C3d {
     /// Parses a C3D file from a file path string.
     /// loading from a string is less inclusive than loading from a PathBuf
     /// <https://users.rust-lang.org/t/pathbuf-and-path-why-not-string/28777>
+    #[cfg(feature = "fs")]
     pub fn load(file_name: &str) -> Result<C3d, C3dParseError> {
         C3d::load_path(PathBuf::from(file_name))
     }
@@ -101,51 +415,405 @@ impl C3d {
     /// Parses a C3D file from a file path.
     /// PathBuf is more inclusive than String
     /// <https://users.rust-lang.org/t/pathbuf-and-path-why-not-string/28777>
+    #[cfg(feature = "fs")]
     pub fn load_path(file_path: PathBuf) -> Result<C3d, C3dParseError> {
-        let c3d = C3d::new();
-        let (c3d, mut file) = c3d.open_file(file_path)?;
-        let (c3d, header_bytes, parameter_bytes, _) = c3d.parse_basic_info(&mut file)?;
+        C3d::load_path_with_options(file_path, ParseOptions::default())
+    }
+
+    /// Parses a C3D file from a file path string using the given parse options.
+    #[cfg(feature = "fs")]
+    pub fn load_with_options(file_name: &str, options: ParseOptions) -> Result<C3d, C3dParseError> {
+        C3d::load_path_with_options(PathBuf::from(file_name), options)
+    }
+
+    /// Parses a C3D file from a file path using the given parse options.
+    #[cfg(feature = "fs")]
+    pub fn load_path_with_options(
+        file_path: PathBuf,
+        options: ParseOptions,
+    ) -> Result<C3d, C3dParseError> {
+        let file = File::open(file_path).map_err(C3dParseError::ReadError)?;
+        C3d::from_reader_with_options(file, options)
+    }
+
+    /// Parses a C3D file from any seekable source, such as an in-memory buffer
+    /// or a file inside an archive. The file is expected to start at the current
+    /// position of the reader.
+    pub fn from_reader<R: Read + Seek>(reader: R) -> Result<C3d, C3dParseError> {
+        C3d::from_reader_with_options(reader, ParseOptions::default())
+    }
+
+    /// Parses a C3D file from any seekable source using the given parse options.
+    pub fn from_reader_with_options<R: Read + Seek>(
+        mut reader: R,
+        options: ParseOptions,
+    ) -> Result<C3d, C3dParseError> {
+        if options.preserve_bytes {
+            let bytes = read_data_bytes(reader, &options.limits)?;
+            return C3d::from_bytes_with_options(&bytes, options);
+        }
+        let c3d = C3d::with_options(options);
+        let (c3d, header_bytes, parameter_bytes, _) = c3d.parse_basic_info(&mut reader)?;
         Ok(c3d
             .parse_header(&header_bytes)?
             .parse_parameters(&header_bytes, &parameter_bytes)?
-            .parse_data(file)?)
+            .parse_data(reader)?)
+    }
+
+    /// Parses a C3D file from a file path string like `load_with_options`, reporting the
+    /// progress of reading and decoding it to a callback, e.g. to show a progress bar.
+    /// The callback can cancel loading by returning `ControlFlow::Break(())`, which
+    /// returns `C3dParseError::Cancelled`.
+    ///
+    /// # Examples
+    /// ```
+    /// use c3dio::prelude::*;
+    /// use std::ops::ControlFlow;
+    ///
+    /// let c3d = C3d::load_with_progress("tests/data/short.c3d", ParseOptions::default(), |progress| {
+    ///     println!("{:?} {:.0}%", progress.stage, progress.fraction() * 100.);
+    ///     ControlFlow::Continue(())
+    /// });
+    /// assert!(c3d.is_ok());
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn load_with_progress<F>(
+        file_name: &str,
+        options: ParseOptions,
+        progress: F,
+    ) -> Result<C3d, C3dParseError>
+    where
+        F: FnMut(Progress) -> ControlFlow<()>,
+    {
+        let file = File::open(file_name).map_err(C3dParseError::ReadError)?;
+        C3d::from_reader_with_progress(BufReader::new(file), options, progress)
+    }
+
+    /// Parses a C3D file from any seekable source like `from_reader_with_options`,
+    /// reporting progress to a callback as `load_with_progress` does.
+    pub fn from_reader_with_progress<R: Read + Seek, F>(
+        mut reader: R,
+        options: ParseOptions,
+        mut progress: F,
+    ) -> Result<C3d, C3dParseError>
+    where
+        F: FnMut(Progress) -> ControlFlow<()>,
+    {
+        let start = reader.stream_position().map_err(C3dParseError::ReadError)?;
+        let end = reader
+            .seek(SeekFrom::End(0))
+            .map_err(C3dParseError::ReadError)?;
+        reader
+            .seek(SeekFrom::Start(start))
+            .map_err(C3dParseError::ReadError)?;
+        let total = end.saturating_sub(start);
+        if options.preserve_bytes {
+            options.limits.check_allocation(total as usize)?;
+            let bytes = read_with_progress(&mut reader, 0, total, &mut progress)?;
+            let c3d = C3d::from_bytes_with_options(&bytes, options)?;
+            let frames = c3d.stored_frame_count() as u64;
+            report(&mut progress, ProgressStage::Decoding, frames, frames);
+            return Ok(c3d);
+        }
+        let c3d = C3d::with_options(options);
+        let (c3d, header_bytes, parameter_bytes, _) = c3d.parse_basic_info(&mut reader)?;
+        let c3d = c3d
+            .parse_header(&header_bytes)?
+            .parse_parameters(&header_bytes, &parameter_bytes)?;
+        let done = reader.stream_position().map_err(C3dParseError::ReadError)? - start;
+        c3d.options
+            .limits
+            .check_allocation(total.saturating_sub(done) as usize)?;
+        let data_bytes = read_with_progress(&mut reader, done, total, &mut progress)?;
+        // the frames are counted from the layout in the header until they are decoded
+        let frames = match c3d.bytes_per_frame() {
+            Some(bytes_per_frame) if bytes_per_frame > 0 => data_bytes.len() / bytes_per_frame,
+            _ => 0,
+        };
+        if !report(&mut progress, ProgressStage::Decoding, 0, frames as u64) {
+            return Err(C3dParseError::Cancelled);
+        }
+        let c3d = c3d.parse_data_bytes(data_bytes, true)?;
+        let frames = c3d.stored_frame_count() as u64;
+        report(&mut progress, ProgressStage::Decoding, frames, frames);
+        Ok(c3d)
     }
 
     /// Parses a C3D file from a byte slice.
     pub fn from_bytes(bytes: &[u8]) -> Result<C3d, C3dParseError> {
+        C3d::from_bytes_with_options(bytes, ParseOptions::default())
+    }
+
+    /// Parses the header and parameters of a C3D file from a byte slice into a view that
+    /// borrows its names and strings from the slice, e.g. to index the metadata of many
+    /// files. See `C3dRef`.
+    pub fn from_bytes_ref(bytes: &[u8]) -> Result<C3dRef<'_>, C3dParseError> {
+        C3dRef::from_bytes(bytes)
+    }
+
+    /// Parses a C3D file from a byte slice using the given parse options.
+    pub fn from_bytes_with_options(
+        bytes: &[u8],
+        options: ParseOptions,
+    ) -> Result<C3d, C3dParseError> {
+        let preserve_bytes = options.preserve_bytes;
         let (c3d, header_bytes, parameter_bytes, data_start_block_index) =
-            C3d::new().parse_basic_info_from_bytes(bytes)?;
-        Ok(c3d
+            C3d::with_options(options).parse_basic_info_from_bytes(bytes)?;
+        let mut c3d = c3d
             .parse_header(&header_bytes)?
             .parse_parameters(&header_bytes, &parameter_bytes)?
-            .parse_data_from_bytes(bytes, data_start_block_index)?)
+            .parse_data_from_bytes(bytes, data_start_block_index)?;
+        // a selectively decoded file cannot be written back as the original bytes
+        if preserve_bytes && !c3d.options.is_selective() {
+            c3d.original_bytes = Some(bytes.to_vec());
+        }
+        Ok(c3d)
+    }
+
+    /// Parses the header and parameters of a C3D file and returns an iterator
+    /// decoding the data section frame by frame, without loading it into memory.
+    #[cfg(feature = "fs")]
+    pub fn frames(file_path: PathBuf) -> Result<FrameIter<BufReader<File>>, C3dParseError> {
+        let file = File::open(file_path).map_err(C3dParseError::ReadError)?;
+        C3d::frames_from_reader(BufReader::new(file))
+    }
+
+    /// Parses the header and parameters from any seekable source and returns
+    /// an iterator decoding the data section frame by frame.
+    pub fn frames_from_reader<R: Read + Seek>(
+        mut reader: R,
+    ) -> Result<FrameIter<R>, C3dParseError> {
+        let c3d = C3d::new();
+        let (c3d, header_bytes, parameter_bytes, _) = c3d.parse_basic_info(&mut reader)?;
+        let c3d = c3d
+            .parse_header(&header_bytes)?
+            .parse_parameters(&header_bytes, &parameter_bytes)?;
+        FrameIter::new(c3d, reader)
+    }
+
+    /// Parses the header and parameters of a C3D file and decodes its data section
+    /// frame by frame into a sink, as `FrameIter::drive` does, without loading it into
+    /// memory. Returns the number of frames given to the sink.
+    #[cfg(feature = "fs")]
+    pub fn stream_frames<S: FrameSink + ?Sized>(
+        file_path: PathBuf,
+        sink: &mut S,
+    ) -> Result<usize, C3dParseError> {
+        C3d::frames(file_path)?.drive(sink)
+    }
+
+    /// Parses the header and parameters from any seekable source and decodes the data
+    /// section frame by frame into a sink, as `stream_frames` does.
+    pub fn stream_frames_from_reader<R: Read + Seek, S: FrameSink + ?Sized>(
+        reader: R,
+        sink: &mut S,
+    ) -> Result<usize, C3dParseError> {
+        C3d::frames_from_reader(reader)?.drive(sink)
     }
 
     /// Parses a C3D file with just the header data.
+    #[cfg(feature = "fs")]
     pub fn load_header(file_name: PathBuf) -> Result<C3d, C3dParseError> {
+        let file = File::open(file_name).map_err(C3dParseError::ReadError)?;
+        C3d::header_from_reader(BufReader::new(file))
+    }
+
+    /// Parses just the header from any seekable source. See `load_header`.
+    pub fn header_from_reader<R: Read + Seek>(mut reader: R) -> Result<C3d, C3dParseError> {
         let c3d = C3d::new();
-        let (c3d, mut file) = c3d.open_file(file_name)?;
-        let (c3d, header_bytes, _, _) = c3d.parse_basic_info(&mut file)?;
-        Ok(c3d.parse_header(&header_bytes)?)
+        let (c3d, header_bytes, _, _) = c3d.parse_basic_info(&mut reader)?;
+        c3d.parse_header(&header_bytes)
     }
 
     /// Parses a C3D file with just the header and parameter data.
     /// The parameter data cannot be parsed without the header data.
     /// The parameter data is parsed into a `Parameters` struct.
     /// The `Parameters` struct can be accessed via the `parameters` field.
+    #[cfg(feature = "fs")]
     pub fn load_parameters(file_name: PathBuf) -> Result<C3d, C3dParseError> {
+        let file = File::open(file_name).map_err(C3dParseError::ReadError)?;
+        C3d::parameters_from_reader(BufReader::new(file))
+    }
+
+    /// Parses just the header and parameter data from any seekable source.
+    /// See `load_parameters`.
+    pub fn parameters_from_reader<R: Read + Seek>(mut reader: R) -> Result<C3d, C3dParseError> {
         let c3d = C3d::new();
-        let (c3d, mut file) = c3d.open_file(file_name)?;
-        let (c3d, header_bytes, parameter_bytes, _) = c3d.parse_basic_info(&mut file)?;
-        Ok(c3d
-            .parse_header(&header_bytes)?
-            .parse_parameters(&header_bytes, &parameter_bytes)?)
+        let (c3d, header_bytes, parameter_bytes, _) = c3d.parse_basic_info(&mut reader)?;
+        c3d.parse_header(&header_bytes)?
+            .parse_parameters(&header_bytes, &parameter_bytes)
+    }
+
+    /// Parses the header and parameters of a C3D file without reading its data section,
+    /// for indexing many files quickly.
+    /// Unlike `load_parameters`, the point and analog parameters are parsed too, so the
+    /// labels, units, rates and events are available, but the point and analog data is empty.
+    /// The number of frames in the file is given by `declared_frame_count`.
+    #[cfg(feature = "fs")]
+    pub fn load_header_and_parameters(file_name: PathBuf) -> Result<C3d, C3dParseError> {
+        let file = File::open(file_name).map_err(C3dParseError::ReadError)?;
+        C3d::header_and_parameters_from_reader(BufReader::new(file))
+    }
+
+    /// Parses the header and parameters from any seekable source without reading the data section.
+    /// See `load_header_and_parameters`.
+    pub fn header_and_parameters_from_reader<R: Read + Seek>(
+        mut reader: R,
+    ) -> Result<C3d, C3dParseError> {
+        let c3d = C3d::new();
+        let (c3d, header_bytes, parameter_bytes, _) = c3d.parse_basic_info(&mut reader)?;
+        c3d.parse_header(&header_bytes)?
+            .parse_parameters(&header_bytes, &parameter_bytes)?
+            .parse_data_bytes(Vec::new(), false)
     }
 
     pub fn new() -> C3d {
         C3d::default()
     }
 
+    fn with_options(options: ParseOptions) -> C3d {
+        C3d {
+            options,
+            ..C3d::default()
+        }
+    }
+
+    /// Returns the bytes of the file as it was loaded,
+    /// if it was parsed with `ParseOptions::preserve_bytes`.
+    pub fn original_bytes(&self) -> Option<&[u8]> {
+        self.original_bytes.as_deref()
+    }
+
+    /// Returns the blocks after the data section that do not belong to any frame,
+    /// if the file was parsed with `ParseOptions::preserve_bytes`.
+    /// They are written back after the frames, starting on a new block.
+    pub fn trailing_bytes(&self) -> &[u8] {
+        &self.trailing_bytes
+    }
+
+    /// Returns whether the file was modified by a method of `C3d` since it was loaded.
+    /// Changes made through the public fields are not tracked, but are still
+    /// detected when writing a file with preserved bytes.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Marks the file as modified, so that it is re-serialized when written.
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Returns the original bytes if writing them reproduces the current state of the file.
+    /// The file is re-parsed to detect changes made through the public fields, including
+    /// the data format and the other state that only affects how the file is written.
+    /// The parse is about as costly as encoding the file, which it replaces when the bytes
+    /// are kept, and unlike keeping a parsed copy to compare with it takes no memory
+    /// until the file is written.
+    fn unmodified_original_bytes(&self) -> Option<&[u8]> {
+        let bytes = self.original_bytes.as_deref()?;
+        if self.dirty {
+            return None;
+        }
+        match C3d::from_bytes_with_options(bytes, self.options.clone()) {
+            Ok(original) if original == *self && original.writes_like(self) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// Whether two files that are equal are also written the same way, as equality
+    /// ignores the processor, the data format and the blocks after the data section.
+    fn writes_like(&self, other: &C3d) -> bool {
+        self.processor == other.processor
+            && self.points.format == other.points.format
+            && self.options.encoding == other.options.encoding
+            && self.trailing_bytes == other.trailing_bytes
+    }
+
+    /// Returns the processor type the file was parsed with.
+    pub fn processor(&self) -> Processor {
+        self.processor
+    }
+
+    /// Returns the encoding of text that is not ASCII the file was parsed with and is
+    /// written in, or `None` for the default of `TextEncoding`.
+    pub fn encoding(&self) -> Option<TextEncoding> {
+        self.options.encoding
+    }
+
+    /// Sets the encoding text that is not ASCII is written in.
+    pub fn set_encoding(&mut self, encoding: Option<TextEncoding>) {
+        if encoding != self.options.encoding {
+            self.options.encoding = encoding;
+            self.original_bytes = None;
+            self.dirty = true;
+        }
+    }
+
+    /// Converts the file to be written with the given processor type.
+    /// The parsed data is independent of the processor type, so only the
+    /// encoding used when writing the file changes.
+    pub fn convert_to(&mut self, processor: Processor) {
+        // the header words that are read back from the parsed header: the points and
+        // analog samples per frame, the data start block and the events label key
+        for byte in [2, 4, 16, 298] {
+            let word = self
+                .processor
+                .u16([self.header_bytes[byte], self.header_bytes[byte + 1]]);
+            let temp = processor.u16_to_bytes(word);
+            self.header_bytes[byte] = temp[0];
+            self.header_bytes[byte + 1] = temp[1];
+        }
+        self.processor = processor;
+        self.dirty = true;
+    }
+
+    /// Changes the format points and analog samples are stored in when written.
+    /// Converting to integers sets POINT:SCALE and ANALOG:SCALE so that the largest
+    /// coordinate and the largest sample of each channel use the full 16-bit range,
+    /// keeping as much resolution as integers allow. Residuals larger than 255 times
+    /// POINT:SCALE are clamped when written. Converting to floats keeps the scales.
+    pub fn convert_format(&mut self, format: DataFormat) {
+        if format == DataFormat::Integer {
+            self.points.scale_factor = self.points.integer_scale_factor();
+            self.analog.fit_integer_scales();
+        }
+        self.points.format = format;
+        self.original_bytes = None;
+        self.dirty = true;
+    }
+
+    /// Returns the file in Intel format, borrowing it if it already is
+    /// and converting a copy of it otherwise.
+    pub fn as_intel(&self) -> Cow<'_, C3d> {
+        match self.processor {
+            Processor::Intel => Cow::Borrowed(self),
+            _ => {
+                let mut c3d = self.clone();
+                c3d.convert_to(Processor::Intel);
+                Cow::Owned(c3d)
+            }
+        }
+    }
+
+    /// Determines the processor type from the parameter start block.
+    /// With `assume_intel` in lenient mode an unknown processor byte falls back to Intel
+    /// with a warning, since the vast majority of files are stored in Intel format.
+    fn processor_from_block(
+        &mut self,
+        parameter_start_block: [u8; 512],
+    ) -> Result<Processor, C3dParseError> {
+        match Processor::from_parameter_start_block(parameter_start_block) {
+            Err(C3dParseError::InvalidProcessorType)
+                if self.options.assume_intel && !self.options.strict =>
+            {
+                self.warnings
+                    .push(ParseWarning::UnknownProcessorByte(parameter_start_block[3]));
+                Ok(Processor::Intel)
+            }
+            result => result,
+        }
+    }
+
     fn force_analog_data(&self, force_plate: usize, frame: usize) -> Option<[f32; 8]> {
         if self.forces.len() <= force_plate {
             return None;
@@ -169,29 +837,1653 @@ impl C3d {
         Some(analog)
     }
 
+    /// Calculates the force of a force platform at the first analog sample of a frame,
+    /// in platform coordinates. The calibration matrix is applied for type 4 platforms
+    /// and the eight channels of type 3 platforms are combined.
     pub fn force(&self, force_plate: usize, frame: usize) -> Option<[f32; 3]> {
         let analog = self.force_analog_data(force_plate, frame)?;
-        self.forces.force_from_analog(analog, force_plate)
+        Some(self.forces[force_plate].force_and_moment(analog).0)
     }
 
+    /// Calculates the center of pressure of a force platform at the first analog sample
+    /// of a frame, relative to the center of the working surface in platform coordinates.
+    /// Returns `None` if there is no vertical force.
     pub fn center_of_pressure(&self, force_plate: usize, frame: usize) -> Option<[f32; 2]> {
         let analog = self.force_analog_data(force_plate, frame)?;
-        self.forces
-            .center_of_pressure_from_analog(analog, force_plate)
+        self.forces[force_plate].center_of_pressure(analog)
+    }
+
+    /// Calculates the ground reaction force, center of pressure and free moment
+    /// of a force platform for every analog sample, in lab coordinates.
+    /// Returns `None` if the platform does not exist or its channels are not in the analog data.
+    pub fn force_platform_data(&self, force_plate: usize) -> Option<ForcePlatformData> {
+        self.forces.get(force_plate)?.data(&self.analog)
+    }
+
+    /// Calculates the ground reaction force, center of pressure and free moment
+    /// of a force platform for every analog sample, in the given coordinate frame.
+    /// Returns `None` if the platform does not exist or its channels are not in the analog
+    /// data, or if lab coordinates are asked for and its corners do not span a plane.
+    pub fn force_platform_data_in(
+        &self,
+        force_plate: usize,
+        frame: ForceFrame,
+    ) -> Option<ForcePlatformData> {
+        self.forces.get(force_plate)?.data_in(&self.analog, frame)
+    }
+
+    /// Calculates the angle in degrees between two segments for every frame.
+    /// Each segment is defined by three marker labels: the segment origin, a marker
+    /// along the primary axis of the segment, and a third marker in the segment plane.
+    /// Frames where any of the six markers is invalid are NaN.
+    /// Returns `None` if any of the labels is not found in the point data.
+    pub fn joint_angle(&self, proximal: [&str; 3], distal: [&str; 3]) -> Option<Vec<f32>> {
+        let mut indices = [0usize; 6];
+        for (i, label) in proximal.iter().chain(distal.iter()).enumerate() {
+            indices[i] = self.points.label_index(label)?;
+        }
+        let mut angles = Vec::with_capacity(self.points.rows());
+        for frame in 0..self.points.rows() {
+            let markers = indices.map(|index| self.points[frame][index]);
+            if markers.iter().any(|marker| !marker.is_valid()) {
+                angles.push(f32::NAN);
+                continue;
+            }
+            let proximal_frame =
+                segment_frame(markers[0].point, markers[1].point, markers[2].point);
+            let distal_frame = segment_frame(markers[3].point, markers[4].point, markers[5].point);
+            angles.push(match (proximal_frame, distal_frame) {
+                (Some(proximal_frame), Some(distal_frame)) => {
+                    angle_between_frames(&proximal_frame, &distal_frame)
+                }
+                _ => f32::NAN,
+            });
+        }
+        Some(angles)
+    }
+
+    /// Calculates the centroid of a set of markers for every frame.
+    /// Invalid markers are ignored, and frames where none of the markers are valid are NaN.
+    /// Labels that are not found in the point data are ignored.
+    pub fn marker_centroid(&self, labels: &[&str]) -> Vec<[f32; 3]> {
+        let indices = self.valid_label_indices(labels);
+        let mut centroids = Vec::with_capacity(self.points.rows());
+        for frame in 0..self.points.rows() {
+            let mut sum = [0f32; 3];
+            let mut count = 0;
+            for &index in &indices {
+                let marker = self.points[frame][index];
+                if marker.is_valid() {
+                    for i in 0..3 {
+                        sum[i] += marker[i];
+                    }
+                    count += 1;
+                }
+            }
+            centroids.push(match count {
+                0 => [f32::NAN; 3],
+                _ => sum.map(|x| x / count as f32),
+            });
+        }
+        centroids
+    }
+
+    /// Calculates the axis-aligned bounding box of a set of markers for every frame
+    /// as a pair of minimum and maximum corners.
+    /// Invalid markers are ignored, and frames where none of the markers are valid are NaN.
+    /// Labels that are not found in the point data are ignored.
+    pub fn marker_set_bounds(&self, labels: &[&str]) -> Vec<([f32; 3], [f32; 3])> {
+        let indices = self.valid_label_indices(labels);
+        let mut bounds = Vec::with_capacity(self.points.rows());
+        for frame in 0..self.points.rows() {
+            let mut min = [f32::INFINITY; 3];
+            let mut max = [f32::NEG_INFINITY; 3];
+            let mut count = 0;
+            for &index in &indices {
+                let marker = self.points[frame][index];
+                if marker.is_valid() {
+                    for i in 0..3 {
+                        min[i] = min[i].min(marker[i]);
+                        max[i] = max[i].max(marker[i]);
+                    }
+                    count += 1;
+                }
+            }
+            bounds.push(match count {
+                0 => ([f32::NAN; 3], [f32::NAN; 3]),
+                _ => (min, max),
+            });
+        }
+        bounds
+    }
+
+    /// Calculates the pose of a rigid body in every stored frame, fitted to the markers
+    /// of the body that are valid in the frame.
+    /// Frames with fewer than three valid markers spanning a plane have no pose.
+    /// Returns `None` if any of the labels of the body is not found in the point data.
+    pub fn rigid_body_poses(&self, body: &RigidBody) -> Option<Vec<Option<Pose>>> {
+        let indices = body
+            .labels
+            .iter()
+            .map(|label| self.points.label_index(label))
+            .collect::<Option<Vec<usize>>>()?;
+        let poses = (0..self.points.rows())
+            .map(|frame| {
+                let markers: Vec<MarkerPoint> = indices
+                    .iter()
+                    .map(|&index| self.points[frame][index])
+                    .collect();
+                body.fit(&markers)
+            })
+            .collect();
+        Some(poses)
+    }
+
+    /// Adds the poses of a rigid body as four virtual points: the origin of the body
+    /// labelled with the name of the body and `_O`, and the ends of its X, Y and Z axes
+    /// at `axis_length` from the origin labelled with `_X`, `_Y` and `_Z`.
+    /// The residual of the points is that of the pose, and the points are invalid in
+    /// frames without a pose. Returns the column of the origin, or `None` if any of the
+    /// labels of the body is not found or any of the new labels is already used.
+    pub fn add_rigid_body_points(&mut self, body: &RigidBody, axis_length: f32) -> Option<usize> {
+        let poses = self.rigid_body_poses(body)?;
+        let offsets = [
+            ("_O", [0.; 3]),
+            ("_X", [axis_length, 0., 0.]),
+            ("_Y", [0., axis_length, 0.]),
+            ("_Z", [0., 0., axis_length]),
+        ];
+        let labels = offsets.map(|(suffix, _)| format!("{}{}", body.name.trim(), suffix));
+        if labels
+            .iter()
+            .any(|label| self.points.label_index(label).is_some())
+        {
+            return None;
+        }
+        let origin = self.points.cols();
+        for ((_, offset), label) in offsets.iter().zip(&labels) {
+            let samples = poses
+                .iter()
+                .map(|pose| match pose {
+                    Some(pose) => MarkerPoint {
+                        point: pose.transform(*offset),
+                        residual: pose.residual,
+                        cameras: [false; 7],
+                    },
+                    None => MarkerPoint {
+                        point: [0.; 3],
+                        residual: -1.,
+                        cameras: [false; 7],
+                    },
+                })
+                .collect();
+            let description = format!("Rigid body {}", body.name.trim());
+            self.add_point(label, &description, samples).ok()?;
+        }
+        Some(origin)
+    }
+
+    /// Adds the poses of a rigid body to the ROTATION data, with one sample per frame if
+    /// the file has no bodies yet, returning the column of the body.
+    /// Frames without a pose are invalid.
+    /// Returns `None` if any of the labels of the body is not found, or the bodies of the
+    /// file have more than one sample per frame.
+    pub fn add_rigid_body_rotation(&mut self, body: &RigidBody) -> Option<usize> {
+        let poses = self.rigid_body_poses(body)?;
+        if self.rotations.cols() > 0 && self.rotations.ratio != 1 {
+            return None;
+        }
+        let samples = poses
+            .iter()
+            .map(|pose| match pose {
+                Some(pose) => Rotation::from_pose(pose),
+                None => Rotation::invalid(),
+            })
+            .collect();
+        let description = format!("Rigid body {}", body.name.trim());
+        let index = self.rotations.add_body(
+            &body.name,
+            &description,
+            samples,
+            self.points.rows(),
+            self.points.frame_rate,
+        )?;
+        self.original_bytes = None;
+        self.dirty = true;
+        Some(index)
+    }
+
+    /// Returns the maximum gap, in frames, that the software that created the file
+    /// interpolated across, as stored in the header.
+    /// Returns `None` if the gap is not set.
+    pub fn max_interpolation_gap(&self) -> Option<u16> {
+        match self.points.max_interpolation_gap {
+            0 => None,
+            gap => Some(gap),
+        }
+    }
+
+    /// Returns the frames of every gap of invalid samples of a marker that has a valid
+    /// sample before and after it, or `None` if the marker is not found.
+    pub fn gaps(&self, label: &str) -> Option<Vec<Range<usize>>> {
+        let index = self.points.label_index(label)?;
+        let points: Vec<MarkerPoint> = self.points.iter_col(index).cloned().collect();
+        Some(gaps(&points))
+    }
+
+    /// Fills the gaps of a marker that are at most `max_gap` frames long, as listed by
+    /// `gaps`, and returns the number of frames filled.
+    /// Filled samples are valid with a residual of 0 and no cameras, as other software
+    /// writes interpolated samples.
+    /// Returns `None` if the marker, or the donor marker of `GapFill::Pattern`, is not found.
+    pub fn fill_gaps(&mut self, label: &str, method: &GapFill, max_gap: usize) -> Option<usize> {
+        let index = self.points.label_index(label)?;
+        let donor: Option<Vec<MarkerPoint>> = match method {
+            GapFill::Spline => None,
+            GapFill::Pattern(donor) => {
+                let donor = self.points.label_index(donor)?;
+                Some(self.points.iter_col(donor).cloned().collect())
+            }
+        };
+        let mut points: Vec<MarkerPoint> = self.points.iter_col(index).cloned().collect();
+        let mut filled = 0;
+        for gap in gaps(&points) {
+            if gap.len() > max_gap {
+                continue;
+            }
+            let length = gap.len();
+            match &donor {
+                None => fill_spline(&mut points, gap),
+                Some(donor) => {
+                    if !fill_pattern(&mut points, donor, gap) {
+                        continue;
+                    }
+                }
+            }
+            filled += length;
+        }
+        if filled > 0 {
+            self.original_bytes = None;
+            self.dirty = true;
+            for (frame, point) in points.into_iter().enumerate() {
+                self.points[frame][index] = point;
+            }
+        }
+        Some(filled)
+    }
+
+    /// Measures the quality of the trial in one pass, e.g. to triage incoming files:
+    /// the visibility, gaps and mean residual of every marker, the samples of every
+    /// analog channel clipped by the converter, and the baseline noise of every force
+    /// platform in the samples where it carries less than `quality::UNLOADED_FORCE`.
+    ///
+    /// # Examples
+    /// ```
+    /// use c3dio::prelude::*;
+    ///
+    /// let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    /// let report = c3d.quality_report();
+    /// for marker in report.markers.iter().filter(|x| x.visible_percent < 90.) {
+    ///     println!("{}: {} gaps", marker.label, marker.gaps);
+    /// }
+    /// ```
+    pub fn quality_report(&self) -> QualityReport {
+        let markers = (0..self.points.cols())
+            .map(|column| {
+                let label = self.points.labels.get(column).map_or("", String::as_str);
+                let points: Vec<MarkerPoint> = self.points.iter_col(column).cloned().collect();
+                marker_quality(label, &points)
+            })
+            .collect();
+        let analog = (0..self.analog.cols())
+            .map(|channel| AnalogQuality {
+                label: self
+                    .analog
+                    .labels
+                    .get(channel)
+                    .map_or("", |label| label.trim())
+                    .to_string(),
+                clipped_samples: self.analog.clipped_samples(channel),
+            })
+            .collect();
+        let force_platforms = self
+            .forces
+            .iter()
+            .map(|platform| {
+                let data = platform.data_in(&self.analog, ForceFrame::Plate);
+                force_platform_quality(data.as_ref())
+            })
+            .collect();
+        QualityReport {
+            frames: self.points.rows(),
+            markers,
+            analog,
+            force_platforms,
+        }
+    }
+
+    /// Filters the trajectory of every point at the point frame rate, filtering
+    /// each run of valid samples on its own.
+    pub fn filter_points(&mut self, filter: &Butterworth) -> Result<(), C3dFilterError> {
+        let sections = filter.sections(self.points.frame_rate as f64)?;
+        self.original_bytes = None;
+        self.dirty = true;
+        for column in 0..self.points.cols() {
+            let mut points: Vec<MarkerPoint> = self.points.iter_col(column).cloned().collect();
+            filter_points(&sections, &mut points);
+            for (frame, point) in points.into_iter().enumerate() {
+                self.points[frame][column] = point;
+            }
+        }
+        Ok(())
+    }
+
+    /// Smooths the trajectory of every point, smoothing each run of valid samples on its
+    /// own and keeping the residuals, e.g. for short trials where the ends of a filtered
+    /// trajectory are dominated by the transients of the filter.
+    pub fn smooth_points(&mut self, smoother: &Smoother) -> Result<(), C3dFilterError> {
+        self.original_bytes = None;
+        self.dirty = true;
+        for column in 0..self.points.cols() {
+            let mut points: Vec<MarkerPoint> = self.points.iter_col(column).cloned().collect();
+            smoother.smooth_points(&mut points)?;
+            for (frame, point) in points.into_iter().enumerate() {
+                self.points[frame][column] = point;
+            }
+        }
+        Ok(())
+    }
+
+    /// Filters every analog channel at the analog rate.
+    pub fn filter_analog(&mut self, filter: &Butterworth) -> Result<(), C3dFilterError> {
+        let sections = filter.sections(self.analog.rate as f64)?;
+        self.original_bytes = None;
+        self.dirty = true;
+        for channel in 0..self.analog.cols() {
+            let samples: Vec<f64> = self.analog.iter_col(channel).cloned().collect();
+            for (row, sample) in filtfilt(&sections, &samples).into_iter().enumerate() {
+                self.analog[row][channel] = sample;
+            }
+        }
+        Ok(())
+    }
+
+    /// Processes the EMG recorded in the analog channel with the given label,
+    /// returning one value for each analog sample.
+    pub fn emg_envelope(
+        &self,
+        label: &str,
+        processing: &EmgProcessing,
+    ) -> Result<Vec<f64>, C3dFilterError> {
+        let channel = self
+            .analog
+            .label_index(label)
+            .ok_or_else(|| C3dFilterError::ChannelNotFound(label.to_string()))?;
+        let samples: Vec<f64> = self.analog.iter_col(channel).cloned().collect();
+        processing.process(&samples, self.analog.rate as f64)
+    }
+
+    /// Processes the EMG in an analog channel as `emg_envelope` does, and adds the result
+    /// as a new channel with the units and scale of the original, returning its index.
+    pub fn add_emg_envelope(
+        &mut self,
+        label: &str,
+        envelope_label: &str,
+        processing: &EmgProcessing,
+    ) -> Result<usize, C3dFilterError> {
+        let envelope = self.emg_envelope(label, processing)?;
+        let channel = self
+            .analog
+            .label_index(label)
+            .ok_or_else(|| C3dFilterError::ChannelNotFound(label.to_string()))?;
+        let description = format!("EMG envelope of {}", label.trim());
+        let index = self
+            .analog
+            .add_channel(envelope_label, &description, envelope, channel)
+            .ok_or_else(|| C3dFilterError::ChannelNotFound(label.to_string()))?;
+        self.original_bytes = None;
+        self.dirty = true;
+        Ok(index)
     }
 
-    fn open_file(self, file_path: PathBuf) -> Result<(C3d, File), C3dParseError> {
-        let file = File::open(file_path).map_err(|e| C3dParseError::ReadError(e))?;
-        Ok((self, file))
+    /// Returns the events with a time in seconds from `start_s` (inclusive)
+    /// to `end_s` (exclusive), in the order they are stored in the file.
+    /// Consecutive ranges such as gait cycles therefore never share an event.
+    pub fn events_in_range(&self, start_s: f64, end_s: f64) -> Vec<&Event> {
+        self.events
+            .iter()
+            .filter(|event| {
+                let time = event.time as f64;
+                time >= start_s && time < end_s
+            })
+            .collect()
     }
 
-    fn parse_basic_info(
+    /// Groups the events by their context, e.g. "Left", "Right" or "General".
+    /// The context is trimmed, and events keep the order they are stored in the file.
+    /// Context metadata such as icons and colours is available in `Events`.
+    pub fn events_by_context(&self) -> HashMap<String, Vec<&Event>> {
+        let mut contexts: HashMap<String, Vec<&Event>> = HashMap::new();
+        for event in self.events.iter() {
+            contexts
+                .entry(event.context.trim().to_string())
+                .or_default()
+                .push(event);
+        }
+        contexts
+    }
+
+    /// Returns the index of the stored frame nearest to an event.
+    /// Event times are counted in seconds from frame 1, so files starting at a later
+    /// frame are offset by their first frame number.
+    /// Returns `None` if the event is outside the stored frames.
+    /// See `Event::frame`.
+    pub fn event_frame(&self, event: &Event) -> Option<usize> {
+        let frame = event.frame(self.points.frame_rate)? as i64;
+        let index = frame - self.points.first_frame.max(1) as i64;
+        match index >= 0 && (index as usize) < self.stored_frame_count() {
+            true => Some(index as usize),
+            false => None,
+        }
+    }
+
+    /// Moves every event to the time of its nearest frame, so that event times are exact
+    /// multiples of the frame period. See `Event::frame`.
+    pub fn snap_events_to_frames(&mut self) {
+        let rate = self.points.frame_rate;
+        for event in self.events.iter_mut() {
+            event.snap_to_frame(rate);
+        }
+        self.original_bytes = None;
+        self.dirty = true;
+    }
+
+    /// Returns the position in stored frames of a time in seconds, counted from frame 1
+    /// as event times are, e.g. `1.5` for halfway between the second and third stored frame.
+    /// Returns `None` if the frame rate is not positive or the time is more than half a
+    /// frame outside the stored frames.
+    pub fn frame_position(&self, time: f64) -> Option<f64> {
+        let rate = self.points.frame_rate as f64;
+        let start = (self.points.first_frame.max(1) - 1) as f64 / rate;
+        sample_position(time - start, rate, self.stored_frame_count())
+    }
+
+    /// Returns a point at a time in seconds, counted from frame 1 as event times are.
+    /// Without interpolation the point of the nearest frame is returned.
+    /// Points next to an invalid point are not interpolated and take the nearest point.
+    /// Returns `None` if there is no point with the label or the time is outside
+    /// the stored frames, as for `frame_position`.
+    pub fn point_at_time(
+        &self,
+        label: &str,
+        time: f64,
+        interpolation: Option<Interpolation>,
+    ) -> Option<MarkerPoint> {
+        let column = self.points.label_index(label)?;
+        let position = self.frame_position(time)?;
+        match interpolation {
+            Some(interpolation) => {
+                resample_column(&self.points, column, &[position], interpolation).pop()
+            }
+            None => Some(self.points[(position.round() as usize, column)]),
+        }
+    }
+
+    /// Returns a sample of the analog channel with the given label at a time in seconds,
+    /// counted from frame 1 as event times are, using the analog rate.
+    /// Without interpolation the nearest sample is returned.
+    /// Returns `None` if there is no channel with the label, the analog rate is not
+    /// positive or the time is more than half a sample outside the stored samples.
+    pub fn analog_at_time(
+        &self,
+        label: &str,
+        time: f64,
+        interpolation: Option<Interpolation>,
+    ) -> Option<f64> {
+        let column = self.analog.label_index(label)?;
+        let start = (self.points.first_frame.max(1) - 1) as f64 / self.points.frame_rate as f64;
+        let rate = self.analog.rate as f64;
+        let position = sample_position(time - start, rate, self.analog.rows())?;
+        match interpolation {
+            Some(interpolation) => {
+                let sampled = resample_rows(&self.analog, &[position], interpolation);
+                Some(sampled[(0, column)])
+            }
+            None => Some(self.analog[(position.round() as usize, column)]),
+        }
+    }
+
+    /// Returns the stored frames from each event with the start label to the next event
+    /// with the end label, both in the given context, e.g. from "Foot Off" to "Foot Strike".
+    /// Labels and contexts are compared without surrounding whitespace, and events are
+    /// taken in order of time. Ranges include the start frame and exclude the end frame,
+    /// so consecutive ranges never share a frame.
+    /// Events outside the stored frames and start events without a later end event are skipped.
+    pub fn frames_between_events(
+        &self,
+        start_label: &str,
+        end_label: &str,
+        context: &str,
+    ) -> Vec<Range<usize>> {
+        let mut events: Vec<(f32, &str, usize)> = self
+            .events
+            .iter()
+            .filter(|event| event.context.trim() == context.trim())
+            .filter_map(|event| Some((event.time, event.label.trim(), self.event_frame(event)?)))
+            .collect();
+        events.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut ranges = Vec::new();
+        for (i, &(_, label, start)) in events.iter().enumerate() {
+            if label != start_label.trim() {
+                continue;
+            }
+            let end = events[i + 1..]
+                .iter()
+                .find(|(_, label, _)| *label == end_label.trim());
+            if let Some(&(_, _, end)) = end {
+                ranges.push(start..end);
+            }
+        }
+        ranges
+    }
+
+    /// Returns the stored frames of each gait cycle of one side, e.g. "Left",
+    /// from one "Foot Strike" event to the next one in the same context.
+    pub fn gait_cycles(&self, context: &str) -> Vec<Range<usize>> {
+        self.frames_between_events("Foot Strike", "Foot Strike", context)
+    }
+
+    /// Detects the Foot Strike and Foot Off events of the given feet and adds them
+    /// to the events, which are then sorted by time. Returns the number of events added.
+    /// See `events::detect::detect` for when detection fails.
+    pub fn detect_gait_events(
+        &mut self,
+        detection: &Detection,
+        feet: &[Foot],
+    ) -> Result<usize, C3dFilterError> {
+        let detected = detect(self, detection, feet)?;
+        for event in &detected {
+            self.events
+                .add_event(&event.label, &event.context, event.time);
+        }
+        self.events.sort_by_time();
+        self.original_bytes = None;
+        self.dirty = true;
+        Ok(detected.len())
+    }
+
+    /// Returns the analog rows sampled during a range of frames,
+    /// e.g. to select the analog data of a gait cycle.
+    pub fn analog_rows(&self, frames: Range<usize>) -> Range<usize> {
+        let samples = self.analog.samples_per_channel_per_frame as usize;
+        let end = (frames.end * samples).min(self.analog.rows());
+        (frames.start * samples).min(end)..end
+    }
+
+    /// Returns a copy of the file holding only a range of its stored frames,
+    /// e.g. a gait cycle from `gait_cycles`. Frames are counted from zero and the
+    /// range is clamped to the stored frames.
+    /// The cropped file starts at frame 1: its analog samples, POINT:FRAMES, the header
+    /// frame range and a TRIAL frame range are written for the kept frames, and events
+    /// outside the range are dropped while the others are re-timed to the new first frame,
+    /// staying nearest to the same frame.
+    pub fn crop(&self, frames: Range<usize>) -> C3d {
+        let stored_frames = self.stored_frame_count();
+        let end = frames.end.min(stored_frames);
+        let frames = frames.start.min(end)..end;
+        let mut c3d = self.clone();
+        c3d.original_bytes = None;
+        c3d.dirty = true;
+        c3d.analog.crop(self.analog_rows(frames.clone()));
+        c3d.rotations.crop(frames.clone());
+        if self.points.rows() > 0 {
+            c3d.points.crop(frames.clone());
+        } else {
+            c3d.points.set_frame_range(1, frames.len());
+        }
+        let offset = (self.points.first_frame.max(1) as usize - 1 + frames.start) as i64;
+        c3d.events.clear();
+        for event in self.events.iter() {
+            if self
+                .event_frame(event)
+                .is_some_and(|frame| frames.contains(&frame))
+            {
+                let mut event = event.clone();
+                event.shift_frames(-offset, self.points.frame_rate);
+                c3d.events.push(event);
+            }
+        }
+        c3d
+    }
+
+    /// Splits the file into one file per cycle between consecutive events with the given
+    /// label and context, e.g. one file per gait cycle with "Foot Strike" and "Left".
+    /// Each cycle is cropped as by `crop`, from the frame of an event up to, but not
+    /// including, the frame of the next one, so the files start at frame 1 with their
+    /// events re-timed to it.
+    pub fn split_by_events(&self, label: &str, context: &str) -> Vec<C3d> {
+        self.frames_between_events(label, label, context)
+            .into_iter()
+            .map(|frames| self.crop(frames))
+            .collect()
+    }
+
+    /// Splits the stored frames into files of `n_frames` frames each, cropped as by
+    /// `crop`. The last file holds the frames left over and may be shorter.
+    /// A file without frames, or `n_frames` of 0, gives no files.
+    pub fn split_every(&self, n_frames: usize) -> Vec<C3d> {
+        if n_frames == 0 {
+            return Vec::new();
+        }
+        (0..self.stored_frame_count())
+            .step_by(n_frames)
+            .map(|start| self.crop(start..start.saturating_add(n_frames)))
+            .collect()
+    }
+
+    /// Appends the frames of several trials into one file, e.g. captures split
+    /// into consecutive files by the acquisition system.
+    /// Every trial must have the same point labels and frame rate, the same analog
+    /// channels, rate and samples per frame, and the same rigid bodies and rotation
+    /// samples per frame as the first trial.
+    /// The combined file takes its parameters from the first trial and starts at its
+    /// first frame. The events of every trial are kept, offset by the duration of the
+    /// trials before it.
+    pub fn concat(trials: &[&C3d]) -> Result<C3d, C3dMergeError> {
+        let first = trials.first().ok_or(C3dMergeError::NoTrials)?;
+        let same_labels = |a: &[String], b: &[String]| {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.trim() == b.trim())
+        };
+        for (i, trial) in trials.iter().enumerate().skip(1) {
+            if trial.points.cols() != first.points.cols()
+                || !same_labels(&trial.points.labels, &first.points.labels)
+            {
+                return Err(C3dMergeError::PointLabelsMismatch(i));
+            }
+            if trial.points.frame_rate != first.points.frame_rate {
+                return Err(C3dMergeError::FrameRateMismatch(
+                    i,
+                    first.points.frame_rate,
+                    trial.points.frame_rate,
+                ));
+            }
+            if trial.analog.cols() != first.analog.cols()
+                || trial.analog.rate != first.analog.rate
+                || trial.analog.samples_per_channel_per_frame
+                    != first.analog.samples_per_channel_per_frame
+                || !same_labels(&trial.analog.labels, &first.analog.labels)
+            {
+                return Err(C3dMergeError::AnalogLayoutMismatch(i));
+            }
+            if trial.rotations.cols() != first.rotations.cols()
+                || (first.rotations.cols() > 0 && trial.rotations.ratio != first.rotations.ratio)
+                || !same_labels(&trial.rotations.labels, &first.rotations.labels)
+            {
+                return Err(C3dMergeError::RotationLayoutMismatch(i));
+            }
+        }
+        let mut c3d = (*first).clone();
+        c3d.original_bytes = None;
+        c3d.dirty = true;
+        let points: Vec<&Grid<MarkerPoint>> = trials.iter().map(|x| &x.points.points).collect();
+        c3d.points.points = concat_rows(&points);
+        let analog: Vec<&Grid<f64>> = trials.iter().map(|x| &x.analog.analog).collect();
+        c3d.analog.analog = concat_rows(&analog);
+        // rotation samples missing from a trial are padded so the next trial stays in step
+        let rotations: Vec<Grid<Rotation>> = trials
+            .iter()
+            .map(|x| x.rotations.frame_samples(x.stored_frame_count()))
+            .collect();
+        c3d.rotations.rotations = concat_rows(&rotations.iter().collect::<Vec<_>>());
+        c3d.events.clear();
+        let mut frames = 0;
+        for trial in trials {
+            // event times count from frame 1 of their own trial
+            let offset = first.points.first_frame.max(1) as i64
+                - trial.points.first_frame.max(1) as i64
+                + frames as i64;
+            for event in trial.events.iter() {
+                let mut event = event.clone();
+                event.shift_frames(offset, first.points.frame_rate);
+                c3d.events.push(event);
+            }
+            frames += trial.stored_frame_count();
+        }
+        let first_frame = c3d.points.first_frame.max(1);
+        c3d.points.set_frame_range(first_frame, frames);
+        Ok(c3d)
+    }
+
+    /// Adds a point with one sample for each stored frame, such as a joint centre computed
+    /// from other markers, returning its column. POINT:USED, the header and the layout of
+    /// the frames follow from the points when the file is written.
+    pub fn add_point(
+        &mut self,
+        label: &str,
+        description: &str,
+        samples: Vec<MarkerPoint>,
+    ) -> Result<usize, C3dMergeError> {
+        if self.points.label_index(label).is_some() {
+            return Err(C3dMergeError::LabelCollision(label.trim().to_string()));
+        }
+        let num_frames = self.stored_frame_count();
+        if samples.len() != num_frames {
+            return Err(C3dMergeError::FrameCountMismatch(samples.len(), num_frames));
+        }
+        let index = self.points.add_point(label, description, samples).ok_or(
+            C3dMergeError::FrameCountMismatch(num_frames, self.points.rows()),
+        )?;
+        self.original_bytes = None;
+        self.dirty = true;
+        Ok(index)
+    }
+
+    /// Adds a point computed in every stored frame from the positions of other points, given
+    /// in the order of `sources`, e.g. the midpoint of two markers as a joint centre.
+    /// The new point is invalid in frames where any of the sources is invalid, and otherwise
+    /// has the largest residual of the sources. Returns its column.
+    pub fn add_computed_point<F>(
+        &mut self,
+        label: &str,
+        description: &str,
+        sources: &[&str],
+        compute: F,
+    ) -> Result<usize, C3dMergeError>
+    where
+        F: Fn(&[[f32; 3]]) -> [f32; 3],
+    {
+        let indices = sources
+            .iter()
+            .map(|source| {
+                self.points
+                    .label_index(source)
+                    .ok_or_else(|| C3dMergeError::LabelNotFound(source.trim().to_string()))
+            })
+            .collect::<Result<Vec<usize>, C3dMergeError>>()?;
+        let samples = (0..self.points.rows())
+            .map(|frame| {
+                let markers: Vec<MarkerPoint> = indices
+                    .iter()
+                    .map(|&index| self.points[frame][index])
+                    .collect();
+                if markers.iter().any(|marker| !marker.is_valid()) {
+                    return MarkerPoint {
+                        point: [0.; 3],
+                        residual: -1.,
+                        cameras: [false; 7],
+                    };
+                }
+                let positions: Vec<[f32; 3]> = markers.iter().map(|marker| marker.point).collect();
+                let residual = markers.iter().map(|marker| marker.residual);
+                MarkerPoint {
+                    point: compute(&positions),
+                    residual: residual.fold(0., f32::max),
+                    cameras: [false; 7],
+                }
+            })
+            .collect();
+        self.add_point(label, description, samples)
+    }
+
+    /// Renames points as `Points::relabel` does, e.g. to harmonize the marker names of
+    /// files from different labs, and renames them in the lists of POINT:ANGLES,
+    /// POINT:FORCES, POINT:MOMENTS, POINT:POWERS and POINT:SCALARS.
+    /// The report lists the renamed points, the labels of the mapping that no point has
+    /// and the labels shared by more than one point after renaming.
+    pub fn relabel_points(&mut self, mapping: &HashMap<String, String>) -> RelabelReport {
+        let report = self.points.relabel(mapping);
+        if report.renamed.is_empty() {
+            return report;
+        }
+        self.original_bytes = None;
+        self.dirty = true;
+        for kind in [
+            PointKind::Angle,
+            PointKind::Force,
+            PointKind::Moment,
+            PointKind::Power,
+            PointKind::Scalar,
+        ] {
+            let parameter_name = match kind.labels_parameter() {
+                Some(parameter_name) => parameter_name,
+                None => continue,
+            };
+            let listed: Vec<String> = match self.parameters.get_vec("POINT", parameter_name) {
+                Ok(listed) => listed,
+                Err(_) => continue,
+            };
+            let renamed: Vec<String> = listed
+                .iter()
+                .map(|label| {
+                    report
+                        .renamed
+                        .iter()
+                        .find(|(old, _)| old == label.trim())
+                        .map_or(label.clone(), |(_, new)| new.clone())
+                })
+                .collect();
+            if renamed == listed {
+                continue;
+            }
+            let mut parameter = Parameter::strings(renamed);
+            if let Some(existing) = self.parameters.get("POINT", parameter_name) {
+                parameter.description = existing.description.clone();
+            }
+            self.parameters.insert("POINT", parameter_name, parameter);
+        }
+        report
+    }
+
+    /// Adds the points of another file recorded at the same time and rate, such as
+    /// the markers of a second capture system, after the points of this file.
+    /// Labels used in both files are handled by `collisions`, and the lists of
+    /// POINT:ANGLES, POINT:FORCES, POINT:MOMENTS, POINT:POWERS and POINT:SCALARS
+    /// of the other file are added with the new labels.
+    /// If the files hold a different number of frames, the other points are resampled
+    /// over the frames of this file by linear interpolation between valid samples.
+    /// The analog data and other parameters are those of this file.
+    pub fn merge_points(
+        &self,
+        other: &C3d,
+        collisions: LabelCollision,
+    ) -> Result<C3d, C3dMergeError> {
+        if other.points.frame_rate != self.points.frame_rate {
+            return Err(C3dMergeError::FrameRateMismatch(
+                1,
+                self.points.frame_rate,
+                other.points.frame_rate,
+            ));
+        }
+        let mut labels = self.points.labels.clone();
+        let mut added = Vec::new();
+        for column in 0..other.points.cols() {
+            let label = other.points.labels.get(column).cloned().unwrap_or_default();
+            let collides = |label: &str| {
+                self.points
+                    .labels
+                    .iter()
+                    .any(|existing| existing.trim() == label.trim())
+            };
+            let label = match (&collisions, collides(&label)) {
+                (_, false) => label,
+                (LabelCollision::Error, true) => {
+                    return Err(C3dMergeError::LabelCollision(label.trim().to_string()))
+                }
+                (LabelCollision::Skip, true) => continue,
+                (LabelCollision::Prefix(prefix), true) => {
+                    let prefixed = format!("{}{}", prefix, label.trim());
+                    if collides(&prefixed) {
+                        return Err(C3dMergeError::LabelCollision(prefixed));
+                    }
+                    prefixed
+                }
+            };
+            labels.push(label.clone());
+            added.push((column, other.points.labels.get(column).cloned(), label));
+        }
+
+        let mut c3d = self.clone();
+        c3d.original_bytes = None;
+        c3d.dirty = true;
+        let num_frames = self.stored_frame_count();
+        let cols = self.points.cols() + added.len();
+        // the other points are stretched over the frames of this file
+        let stored = other.points.rows();
+        let positions: Vec<f64> = (0..num_frames)
+            .map(|frame| match num_frames {
+                1 => 0.,
+                _ => frame as f64 * stored.saturating_sub(1) as f64 / (num_frames - 1) as f64,
+            })
+            .collect();
+        let resampled: Vec<Vec<MarkerPoint>> = added
+            .iter()
+            .map(|(column, _, _)| {
+                resample_column(
+                    &other.points.points,
+                    *column,
+                    &positions,
+                    Interpolation::Linear,
+                )
+            })
+            .collect();
+        let mut points = Vec::with_capacity(num_frames * cols);
+        for frame in 0..num_frames {
+            for column in 0..self.points.cols() {
+                points.push(self.points[frame][column]);
+            }
+            for column in &resampled {
+                points.push(column[frame]);
+            }
+        }
+        c3d.points.points = match cols {
+            0 => Grid::new(0, 0),
+            _ => Grid::from_vec(points, cols),
+        };
+        c3d.points
+            .descriptions
+            .resize(self.points.cols(), " ".to_string());
+        for (column, _, _) in &added {
+            let description = other.points.descriptions.get(*column).cloned();
+            c3d.points
+                .descriptions
+                .push(description.unwrap_or(" ".to_string()));
+        }
+        c3d.points.labels = labels;
+        for kind in [
+            PointKind::Angle,
+            PointKind::Force,
+            PointKind::Moment,
+            PointKind::Power,
+            PointKind::Scalar,
+        ] {
+            let parameter_name = match kind.labels_parameter() {
+                Some(parameter_name) => parameter_name,
+                None => continue,
+            };
+            let listed: Vec<String> = other
+                .parameters
+                .get_vec::<String>("POINT", parameter_name)
+                .unwrap_or_default();
+            let renamed: Vec<String> = added
+                .iter()
+                .filter(|(_, original, _)| {
+                    original
+                        .as_ref()
+                        .is_some_and(|original| listed.iter().any(|x| x.trim() == original.trim()))
+                })
+                .map(|(_, _, label)| label.clone())
+                .collect();
+            if renamed.is_empty() {
+                continue;
+            }
+            let mut merged = self
+                .parameters
+                .get_vec::<String>("POINT", parameter_name)
+                .unwrap_or_default();
+            merged.extend(renamed);
+            let mut parameter = Parameter::strings(merged);
+            if let Some(existing) = self
+                .parameters
+                .get("POINT", parameter_name)
+                .or(other.parameters.get("POINT", parameter_name))
+            {
+                parameter.description = existing.description.clone();
+            }
+            c3d.parameters.insert("POINT", parameter_name, parameter);
+        }
+        Ok(c3d)
+    }
+
+    /// Returns a copy of the file resampled to a new point frame rate, e.g. from 250 Hz
+    /// to 100 Hz, covering the same time span as the stored frames.
+    /// Points next to an invalid point are not interpolated and take the nearest point.
+    /// Analog channels keep their rate if it is a multiple of the new frame rate, with
+    /// ANALOG:RATE and the samples per frame updated to match, and are otherwise
+    /// resampled to the same number of samples per frame at the new rate.
+    /// Rigid bodies keep their samples per frame, with their rotations interpolated
+    /// along the shortest arc between the two nearest samples.
+    /// Events keep their time relative to the first frame, which is renumbered for the
+    /// new rate.
+    /// Returns an unchanged copy if either frame rate is not positive.
+    pub fn resample(&self, frame_rate: f32, interpolation: Interpolation) -> C3d {
+        let mut c3d = self.clone();
+        let old_rate = self.points.frame_rate as f64;
+        let new_rate = frame_rate as f64;
+        if !(old_rate.is_finite() && new_rate.is_finite() && old_rate > 0. && new_rate > 0.) {
+            return c3d;
+        }
+        c3d.original_bytes = None;
+        c3d.dirty = true;
+        let num_frames = self.stored_frame_count();
+        let new_frames = match num_frames {
+            0 => 0,
+            _ => ((num_frames - 1) as f64 * new_rate / old_rate + 1e-9).floor() as usize + 1,
+        };
+        let positions: Vec<f64> = (0..new_frames)
+            .map(|frame| frame as f64 * old_rate / new_rate)
+            .collect();
+        let columns: Vec<Vec<MarkerPoint>> = (0..self.points.cols())
+            .map(|column| resample_column(&self.points.points, column, &positions, interpolation))
+            .collect();
+        c3d.points.points = match self.points.rows() > 0 && !columns.is_empty() {
+            true => Grid::from_vec(
+                (0..new_frames)
+                    .flat_map(|frame| columns.iter().map(move |column| column[frame]))
+                    .collect(),
+                columns.len(),
+            ),
+            false => Grid::new(0, 0),
+        };
+
+        let samples = self.analog.samples_per_channel_per_frame.max(1) as f64;
+        let samples_per_frame = self.analog.rate as f64 / new_rate;
+        let new_samples = match samples_per_frame >= 1.
+            && (samples_per_frame - samples_per_frame.round()).abs() < 1e-3
+            && samples_per_frame.round() <= u16::MAX as f64
+        {
+            true => samples_per_frame.round(),
+            false => samples,
+        };
+        let analog_positions: Vec<f64> = (0..new_frames * new_samples as usize)
+            .map(|sample| sample as f64 * old_rate * samples / (new_rate * new_samples))
+            .collect();
+        c3d.analog.analog = resample_rows(&self.analog.analog, &analog_positions, interpolation);
+        if self.analog.samples_per_channel_per_frame > 0 {
+            c3d.analog.samples_per_channel_per_frame = new_samples as u16;
+            c3d.analog.samples_per_frame = (self.analog.cols() * new_samples as usize) as u16;
+            c3d.analog.rate = (new_rate * new_samples) as f32;
+        }
+
+        let ratio = self.rotations.ratio.max(1) as usize;
+        let rotation_positions: Vec<f64> = (0..new_frames * ratio)
+            .map(|sample| sample as f64 * old_rate / new_rate)
+            .collect();
+        let rotation_rate = (new_rate * ratio as f64) as f32;
+        c3d.rotations.resample(&rotation_positions, rotation_rate);
+
+        c3d.points.frame_rate = frame_rate;
+        let first_frame = self.points.first_frame.max(1) as f64;
+        let new_first_frame = ((first_frame - 1.) * new_rate / old_rate).round() + 1.;
+        let new_first_frame = new_first_frame.min(u16::MAX as f64) as u16;
+        c3d.points.set_frame_range(new_first_frame, new_frames);
+        c3d.points.max_interpolation_gap =
+            (self.points.max_interpolation_gap as f64 * new_rate / old_rate).round() as u16;
+        // the first frame moves by less than a frame, so events move with it
+        let offset = (new_first_frame as f64 - 1.) / new_rate - (first_frame - 1.) / old_rate;
+        for event in c3d.events.iter_mut() {
+            event.time += offset as f32;
+        }
+        c3d
+    }
+
+    /// Classifies every point by the kind of quantity it stores,
+    /// based on the labels listed in the POINT:ANGLES, POINT:FORCES, POINT:MOMENTS,
+    /// POINT:POWERS and POINT:SCALARS parameters.
+    pub fn point_kinds(&self) -> Vec<PointKind> {
+        let listed: Vec<(PointKind, Vec<String>)> = [
+            PointKind::Angle,
+            PointKind::Force,
+            PointKind::Moment,
+            PointKind::Power,
+            PointKind::Scalar,
+        ]
+        .iter()
+        .filter_map(|kind| {
+            let parameter = self.parameters.get("POINT", kind.labels_parameter()?)?;
+            Some((*kind, parameter.try_into().ok()?))
+        })
+        .collect();
+        (0..self.points.cols())
+            .map(|i| {
+                let label = self.points.labels.get(i).map(|x| x.trim()).unwrap_or("");
+                listed
+                    .iter()
+                    .find(|(_, labels)| labels.iter().any(|x| x == label))
+                    .map(|(kind, _)| *kind)
+                    .unwrap_or(PointKind::Marker)
+            })
+            .collect()
+    }
+
+    /// Classifies every point like `point_kinds`, along with its label and units.
+    ///
+    /// # Examples
+    /// ```
+    /// use c3dio::prelude::*;
+    /// use c3dio::PointKind;
+    ///
+    /// let c3d = C3d::load("tests/c3d_org_samples/sample_03/gait-pig.c3d").unwrap();
+    /// for angle in c3d.points_of_kind(PointKind::Angle) {
+    ///     let first = c3d.points[0][angle.index];
+    ///     println!("{} {:?} {:?}", angle.label, first.point, angle.units);
+    /// }
+    /// ```
+    pub fn point_channels(&self) -> Vec<PointChannel> {
+        let marker_units = self.points.units.iter().collect::<String>();
+        let units = |kind: PointKind| match kind {
+            PointKind::Marker => Some(marker_units.trim().to_string()),
+            _ => Some(self.point_units(kind)?.trim().to_string()),
+        };
+        self.point_kinds()
+            .into_iter()
+            .enumerate()
+            .map(|(index, kind)| PointChannel {
+                index,
+                label: self.points.labels.get(index).cloned().unwrap_or_default(),
+                kind,
+                units: units(kind).filter(|units| !units.is_empty()),
+            })
+            .collect()
+    }
+
+    /// Returns the points of the given kind, in the order they are stored.
+    pub fn points_of_kind(&self, kind: PointKind) -> Vec<PointChannel> {
+        self.point_channels()
+            .into_iter()
+            .filter(|channel| channel.kind == kind)
+            .collect()
+    }
+
+    /// Returns the points that are not markers, such as the joint angles, forces,
+    /// moments and powers computed by Vicon Plug-in Gait.
+    pub fn model_outputs(&self) -> Vec<PointChannel> {
+        self.point_channels()
+            .into_iter()
+            .filter(PointChannel::is_model_output)
+            .collect()
+    }
+
+    /// Returns the metadata of every analog channel, bundling the entries of
+    /// ANALOG:LABELS, DESCRIPTIONS, UNITS, GAIN, SCALE and OFFSET for each of them.
+    ///
+    /// # Examples
+    /// ```
+    /// use c3dio::prelude::*;
+    ///
+    /// let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    /// for channel in c3d.analog_channels() {
+    ///     println!("{} {} {} {:?}", channel.label, channel.units, channel.scale, channel.gain);
+    /// }
+    /// ```
+    pub fn analog_channels(&self) -> Vec<AnalogChannel> {
+        (0..self.analog.cols())
+            .filter_map(|index| self.analog_channel_at(index))
+            .collect()
+    }
+
+    /// Returns the metadata of the first analog channel with the given label,
+    /// ignoring the whitespace around labels.
+    pub fn analog_channel(&self, label: &str) -> Option<AnalogChannel> {
+        self.analog_channel_at(self.analog.label_index(label)?)
+    }
+
+    fn analog_channel_at(&self, index: usize) -> Option<AnalogChannel> {
+        let mut channel = self.analog.channel(index)?;
+        let gains: Option<Vec<i16>> = self.parameters.get_vec("ANALOG", "GAIN").ok();
+        channel.gain = gains.and_then(|gains| gains.get(index).copied());
+        Some(channel)
+    }
+
+    /// Stores the metadata of the analog channel at `channel.index`, e.g. one returned
+    /// by `analog_channels` and edited, in the per-channel parameters of the ANALOG
+    /// group they are written to. A gain of `None` leaves ANALOG:GAIN as it is.
+    /// The samples are kept in physical units, so a new scale or offset changes the
+    /// values stored in the file rather than the samples.
+    ///
+    /// Fails with `InvalidAnalogChannel` if there is no channel at the index or the
+    /// offset does not fit ANALOG:FORMAT, leaving the parameters unchanged.
+    pub fn set_analog_channel(&mut self, channel: &AnalogChannel) -> Result<(), C3dWriteError> {
+        self.analog.set_channel(channel)?;
+        if let Some(gain) = channel.gain {
+            let mut gains: Vec<i16> = self
+                .parameters
+                .get_vec("ANALOG", "GAIN")
+                .unwrap_or_default();
+            gains.resize(self.analog.cols().max(gains.len()), 0);
+            gains[channel.index] = gain;
+            self.parameters.set("ANALOG", "GAIN", gains)?;
+        }
+        self.original_bytes = None;
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Converts the point data to the target units according to the kind of each point.
+    /// Marker coordinates and residuals, and the positions of the rigid bodies, are
+    /// converted between length units, angles between angle units and moments between
+    /// moment units. Forces, powers and scalars are left unchanged. Points whose current
+    /// units are missing or not recognized are left unchanged as well. The units parameters are updated to the target units, and
+    /// POINT:SCALE follows the markers as in `convert_units`.
+    pub fn convert_units_by_kind(&mut self, target: UnitSet) {
+        self.original_bytes = None;
+        self.dirty = true;
+        let kinds = self.point_kinds();
+        self.convert_marker_units(&kinds, target.length);
+        if let Some(from) = self.point_units(PointKind::Angle) {
+            if let Some(from) = AngleUnit::from_symbol(&from) {
+                self.scale_points_of_kind(&kinds, PointKind::Angle, from.factor_to(target.angle));
+                self.set_point_units(PointKind::Angle, target.angle.symbol());
+            }
+        }
+        if let Some(from) = self.point_units(PointKind::Moment) {
+            if let Some(from) = MomentUnit::from_symbol(&from) {
+                self.scale_points_of_kind(&kinds, PointKind::Moment, from.factor_to(target.moment));
+                self.set_point_units(PointKind::Moment, target.moment.symbol());
+            }
+        }
+        self.fit_scale_factor();
+    }
+
+    /// Converts every length in the file to the target unit, based on POINT:UNITS.
+    /// Marker coordinates and residuals, the moments stored as points, the positions of
+    /// the rigid bodies, the corners and origins of the force platforms, and the analog
+    /// channels of the platforms that hold centers of pressure or moments are all
+    /// multiplied by the same factor, so forces and moments computed from them stay
+    /// consistent.
+    /// For type 4 platforms with a calibration matrix the matrix is converted instead
+    /// of the channels. POINT:SCALE is converted too, unless the points of other kinds
+    /// would then no longer fit in the integer format. POINT:UNITS, POINT:MOMENT_UNITS
+    /// and the ANALOG:UNITS of the converted channels are updated. Returns the factor,
+    /// or `None` and leaves the file unchanged if POINT:UNITS is not a recognized
+    /// length unit.
+    ///
+    /// # Examples
+    /// ```
+    /// use c3dio::prelude::*;
+    /// use c3dio::LengthUnit;
+    ///
+    /// let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    /// assert_eq!(c3d.convert_units(LengthUnit::Meter), Some(0.001));
+    /// assert_eq!(c3d.points.units, ['m', ' ', ' ', ' ']);
+    /// ```
+    pub fn convert_units(&mut self, target: LengthUnit) -> Option<f32> {
+        let kinds = self.point_kinds();
+        let factor = self.convert_marker_units(&kinds, target)?;
+        let moments = self.point_units(PointKind::Moment);
+        if let Some(from) = moments.as_deref().and_then(MomentUnit::from_symbol) {
+            let factor = from.factor_to(target.moment());
+            self.scale_points_of_kind(&kinds, PointKind::Moment, factor);
+            self.set_point_units(PointKind::Moment, target.moment().symbol());
+        }
+        self.fit_scale_factor();
+        let mut channels = Vec::new();
+        for platform in self.forces.iter_mut() {
+            for channel in platform.scale_lengths(factor) {
+                if channel < self.analog.cols() && !channels.contains(&channel) {
+                    channels.push(channel);
+                }
+            }
+        }
+        for channel in channels {
+            for sample in 0..self.analog.rows() {
+                self.analog[sample][channel] *= factor as f64;
+            }
+            if let Some(scale) = self.analog.scales.get_mut(channel) {
+                *scale *= factor;
+            }
+            if let Some(units) = self.analog.units.get_mut(channel) {
+                if LengthUnit::from_symbol(units).is_some() {
+                    *units = target.symbol().to_string();
+                } else if MomentUnit::from_symbol(units).is_some() {
+                    *units = target.moment().symbol().to_string();
+                }
+            }
+        }
+        Some(factor)
+    }
+
+    fn point_units(&self, kind: PointKind) -> Option<String> {
+        let parameter = self.parameters.get("POINT", kind.units_parameter()?)?;
+        parameter.try_into().ok()
+    }
+
+    fn set_point_units(&mut self, kind: PointKind, symbol: &str) {
+        let units = match Parameter::string(symbol.to_string()) {
+            Ok(units) => units,
+            Err(_) => return,
+        };
+        if let Some(parameter) = kind
+            .units_parameter()
+            .and_then(|name| self.parameters.get_mut("POINT", name))
+        {
+            parameter.dimensions = units.dimensions;
+            parameter.data = units.data;
+        }
+    }
+
+    /// Converts the marker coordinates and residuals and the positions of the rigid bodies
+    /// to the target length unit, along with POINT:UNITS and POINT:SCALE. Returns the
+    /// factor, or `None` and leaves the points unchanged if POINT:UNITS is not a
+    /// recognized length unit.
+    fn convert_marker_units(&mut self, kinds: &[PointKind], target: LengthUnit) -> Option<f32> {
+        let from = LengthUnit::from_symbol(&self.points.units.iter().collect::<String>())?;
+        let factor = from.factor_to(target);
+        self.original_bytes = None;
+        self.dirty = true;
+        self.scale_points_of_kind(kinds, PointKind::Marker, factor);
+        self.rotations.scale_positions(factor);
+        let mut units = [' '; 4];
+        for (i, c) in target.symbol().chars().enumerate() {
+            units[i] = c;
+        }
+        self.points.units = units;
+        self.points.scale_factor *= factor;
+        Some(factor)
+    }
+
+    /// Raises POINT:SCALE of an integer file until every valid point fits in 16-bit
+    /// integers, for the points that were not converted with the markers.
+    fn fit_scale_factor(&mut self) {
+        if self.points.format != DataFormat::Integer {
+            return;
+        }
+        let largest = self
+            .points
+            .iter()
+            .filter(|point| point.is_valid())
+            .flat_map(|point| point.point)
+            .fold(0f32, |largest, x| largest.max(x.abs()));
+        self.points.scale_factor = self.points.scale_factor.max(largest / i16::MAX as f32);
+    }
+
+    fn scale_points_of_kind(&mut self, kinds: &[PointKind], kind: PointKind, factor: f32) {
+        if factor == 1. {
+            return;
+        }
+        for frame in 0..self.points.rows() {
+            for (i, _) in kinds.iter().enumerate().filter(|(_, x)| **x == kind) {
+                let point = &mut self.points[frame][i];
+                point.point = point.point.map(|x| x * factor);
+                if kind == PointKind::Marker && point.is_valid() {
+                    point.residual *= factor;
+                }
+            }
+        }
+    }
+
+    /// Moves the data into another lab coordinate system, mapping each position `p`
+    /// to `rotation * p + translation`, with the rotation matrix given as rows.
+    /// Marker coordinates, the corners of the force platforms and the positions of the
+    /// rigid bodies are rotated and translated, the orientations of the rigid bodies
+    /// are rotated, and point forces and moments are only rotated, so the forces of
+    /// the platforms, which are given in platform coordinates, follow their corners.
+    /// Angles, powers and scalars, and invalid points, are left unchanged.
+    /// POINT:X_SCREEN, POINT:Y_SCREEN and POINT:Z_SCREEN are set to the axes closest
+    /// to where the rotation takes them.
+    ///
+    /// # Examples
+    /// ```
+    /// use c3dio::prelude::*;
+    ///
+    /// let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    /// // from a lab with Y pointing up to one with Z pointing up
+    /// let y_up_to_z_up = [[1., 0., 0.], [0., 0., -1.], [0., 1., 0.]];
+    /// c3d.transform(y_up_to_z_up, [0., 0., 0.]);
+    /// ```
+    pub fn transform(&mut self, rotation: [[f32; 3]; 3], translation: [f32; 3]) {
+        let kinds = self.point_kinds();
+        self.original_bytes = None;
+        self.dirty = true;
+        for frame in 0..self.points.rows() {
+            for (i, kind) in kinds.iter().enumerate() {
+                let point = &mut self.points[frame][i];
+                if !point.is_valid() {
+                    continue;
+                }
+                match kind {
+                    PointKind::Marker => {
+                        let rotated = rotate(&rotation, point.point);
+                        point.point = [0, 1, 2].map(|axis| rotated[axis] + translation[axis]);
+                    }
+                    PointKind::Force | PointKind::Moment => {
+                        point.point = rotate(&rotation, point.point);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        for platform in self.forces.iter_mut() {
+            for corner in platform.corners.iter_mut() {
+                let rotated = rotate(&rotation, *corner);
+                *corner = [0, 1, 2].map(|axis| rotated[axis] + translation[axis]);
+            }
+        }
+        self.rotations.transform(&rotation, translation);
+        self.points.x_screen = self
+            .points
+            .x_screen
+            .map(|axis| rotate_screen_axis(&rotation, axis));
+        self.points.y_screen = self
+            .points
+            .y_screen
+            .map(|axis| rotate_screen_axis(&rotation, axis));
+        if let Ok(axis) = self.parameters.get_as::<[char; 2]>("POINT", "Z_SCREEN") {
+            let axis: String = rotate_screen_axis(&rotation, axis).iter().collect();
+            let _ = self.parameters.set("POINT", "Z_SCREEN", axis);
+        }
+    }
+
+    /// Sets the lab axes pointing right and up on the screen in POINT:X_SCREEN and
+    /// POINT:Y_SCREEN, e.g. after moving the data into a lab with another vertical axis,
+    /// so that viewers show it the right way up. POINT:Z_SCREEN, which some software
+    /// writes, is set to the axis pointing out of the screen if the file has it.
+    ///
+    /// Fails with `InvalidScreenAxes` if both axes are along the same lab axis.
+    ///
+    /// # Examples
+    /// ```
+    /// use c3dio::prelude::*;
+    /// use c3dio::ScreenAxis;
+    ///
+    /// let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    /// c3d.set_screen_axes(ScreenAxis::PlusX, ScreenAxis::PlusZ).unwrap();
+    /// assert_eq!(c3d.points.y_screen, Some(['+', 'Z']));
+    /// ```
+    pub fn set_screen_axes(&mut self, x: ScreenAxis, y: ScreenAxis) -> Result<(), C3dWriteError> {
+        let z = ScreenAxis::out_of_screen(x, y).ok_or(C3dWriteError::InvalidScreenAxes(x, y))?;
+        if self.parameters.contains("POINT", "Z_SCREEN") {
+            let z: String = z.to_chars().iter().collect();
+            self.parameters.set("POINT", "Z_SCREEN", z)?;
+        }
+        self.points.x_screen = Some(x.to_chars());
+        self.points.y_screen = Some(y.to_chars());
+        self.original_bytes = None;
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Splits a file containing the markers of several subjects into one file per subject.
+    /// Subjects and their label prefixes are taken from SUBJECTS:NAMES and
+    /// SUBJECTS:LABEL_PREFIXES when SUBJECTS:USES_PREFIXES is set, otherwise from
+    /// label prefixes such as "Subject1:RKNE". Each file keeps only the points whose
+    /// labels start with the subject's prefix, and per-subject SUBJECTS parameters
+    /// are narrowed to that subject.
+    /// Subjects without any points are skipped.
+    pub fn split_by_subject(&self) -> Vec<(String, C3d)> {
+        let subjects = self.subject_prefixes();
+        let mut split = Vec::new();
+        for (i, (name, prefix)) in subjects.iter().enumerate() {
+            let indices: Vec<usize> = (0..self.points.cols())
+                .filter(|&j| match self.points.labels.get(j) {
+                    Some(label) => label.trim().starts_with(prefix.as_str()),
+                    None => false,
+                })
+                .collect();
+            if indices.is_empty() {
+                continue;
+            }
+            let mut c3d = self.clone();
+            c3d.select_points(&indices);
+            if subjects.len() > 1 {
+                if let Some(group) = self.parameters.get_group("SUBJECTS") {
+                    for (parameter_name, parameter) in group {
+                        if let Some(parameter) = select_subject(parameter, i, subjects.len()) {
+                            c3d.parameters.insert("SUBJECTS", parameter_name, parameter);
+                        }
+                    }
+                }
+                if let Some(used) = c3d.parameters.get_mut("SUBJECTS", "USED") {
+                    used.data = ParameterData::Integer(vec![1]);
+                }
+            }
+            split.push((name.clone(), c3d));
+        }
+        split
+    }
+
+    /// Returns the subjects listed in the SUBJECTS group.
+    /// The subjects are empty for files without the group.
+    pub fn subjects(&self) -> Subjects {
+        Subjects::from_parameters(&self.parameters)
+    }
+
+    /// Stores subjects in the SUBJECTS group, replacing SUBJECTS:NAMES,
+    /// SUBJECTS:LABEL_PREFIXES and the flags of the group and keeping its other parameters.
+    /// The point labels are not renamed to match new prefixes.
+    pub fn set_subjects(&mut self, subjects: &Subjects) -> Result<(), C3dWriteError> {
+        subjects.write_parameters(&mut self.parameters)?;
+        self.original_bytes = None;
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Returns the points belonging to the subject with the given name, as found by
+    /// `Subjects::subject_of`, in the order they are stored.
+    pub fn subject_points(&self, name: &str) -> Vec<PointChannel> {
+        let subjects = self.subjects();
+        self.point_channels()
+            .into_iter()
+            .filter(|channel| {
+                subjects
+                    .subject_of(&channel.label)
+                    .is_some_and(|subject| subject.name == name)
+            })
+            .collect()
+    }
+
+    /// Returns the name and label prefix of every subject in the file.
+    fn subject_prefixes(&self) -> Vec<(String, String)> {
+        let listed = self.subjects();
+        let mut subjects: Vec<(String, String)> = match listed.uses_prefixes {
+            false => Vec::new(),
+            true => listed
+                .subjects
+                .into_iter()
+                .filter(|subject| !subject.label_prefix.is_empty())
+                .map(|subject| match subject.name.is_empty() {
+                    false => (subject.name, subject.label_prefix),
+                    true => (
+                        subject.label_prefix.trim_end_matches(':').to_string(),
+                        subject.label_prefix,
+                    ),
+                })
+                .collect(),
+        };
+        if !subjects.is_empty() {
+            return subjects;
+        }
+        for label in &self.points.labels {
+            if let Some((name, _)) = label.trim().split_once(':') {
+                if !subjects.iter().any(|(x, _)| x == name) {
+                    subjects.push((name.to_string(), format!("{}:", name)));
+                }
+            }
+        }
+        subjects
+    }
+
+    /// Keeps only the points in the given columns, along with their labels,
+    /// descriptions and POINT parameters listing point labels.
+    fn select_points(&mut self, indices: &[usize]) {
+        self.original_bytes = None;
+        self.dirty = true;
+        let mut points = Vec::with_capacity(self.points.rows() * indices.len());
+        for frame in 0..self.points.rows() {
+            points.extend(indices.iter().map(|&i| self.points[frame][i]));
+        }
+        self.points.points = Grid::from_vec(points, indices.len());
+        let select = |strings: &Vec<String>| -> Vec<String> {
+            indices
+                .iter()
+                .filter_map(|&i| strings.get(i).cloned())
+                .collect()
+        };
+        self.points.labels = select(&self.points.labels);
+        self.points.descriptions = select(&self.points.descriptions);
+        for kind in [
+            PointKind::Angle,
+            PointKind::Force,
+            PointKind::Moment,
+            PointKind::Power,
+            PointKind::Scalar,
+        ] {
+            let parameter_name = match kind.labels_parameter() {
+                Some(parameter_name) => parameter_name,
+                None => continue,
+            };
+            let parameter = match self.parameters.get("POINT", parameter_name) {
+                Some(parameter) => parameter.clone(),
+                None => continue,
+            };
+            let listed: Vec<String> = (&parameter).try_into().unwrap_or_default();
+            let listed: Vec<String> = listed
+                .into_iter()
+                .filter(|x| self.points.labels.iter().any(|label| label.trim() == x))
+                .collect();
+            match listed.is_empty() {
+                true => {
+                    self.parameters.remove("POINT", parameter_name);
+                }
+                false => {
+                    let mut selected = Parameter::strings(listed);
+                    selected.description = parameter.description;
+                    self.parameters.insert("POINT", parameter_name, selected);
+                }
+            }
+        }
+    }
+
+    /// Calculates the absolute byte offset in the file where the data of a frame begins.
+    /// Frames are counted from zero, regardless of the first frame number in the header.
+    /// Returns `None` if the frame is not stored in the file or the file was not parsed.
+    pub fn frame_byte_offset(&self, frame: usize) -> Option<u64> {
+        if frame >= self.stored_frame_count() {
+            return None;
+        }
+        let data_start_block =
+            self.processor
+                .u16([self.header_bytes[16], self.header_bytes[17]]) as u64;
+        if data_start_block == 0 {
+            return None;
+        }
+        // the layout of the file is read from the header, as not every point
+        // or analog channel is decoded when loading selectively
+        let bytes_per_frame = self.bytes_per_frame()?;
+        Some((data_start_block - 1) * 512 + frame as u64 * bytes_per_frame as u64)
+    }
+
+    /// Returns the number of frames that can be indexed in the point and analog data.
+    /// Iterating over frames and indexing them by frame is bounded by this value.
+    /// This is the count most users need; see `declared_frame_count` and
+    /// `stored_frame_count` for the details of how it is determined.
+    pub fn frame_count(&self) -> usize {
+        self.stored_frame_count()
+    }
+
+    /// Returns the number of frames declared by the file, resolved from the header
+    /// frame range, POINT:FRAMES, POINT:LONG_FRAMES and the TRIAL:ACTUAL_START_FIELD and
+    /// TRIAL:ACTUAL_END_FIELD parameters. This may be larger than the number of frames
+    /// actually stored if the file was truncated.
+    pub fn declared_frame_count(&self) -> usize {
+        self.points.declared_frames()
+    }
+
+    /// Returns the number of frames actually stored in the data section,
+    /// which is the declared frame count limited by the length of the data.
+    pub fn stored_frame_count(&self) -> usize {
+        match self.points.rows() == 0
+            && self.analog.rows() > 0
+            && self.analog.samples_per_channel_per_frame != 0
+        {
+            true => self.analog.rows() / self.analog.samples_per_channel_per_frame as usize,
+            false => self.points.rows(),
+        }
+    }
+
+    fn valid_label_indices(&self, labels: &[&str]) -> Vec<usize> {
+        labels
+            .iter()
+            .filter_map(|label| self.points.label_index(label))
+            .collect()
+    }
+
+    fn parse_basic_info<R: Read + Seek>(
         mut self,
-        file: &mut File,
+        reader: &mut R,
     ) -> Result<(C3d, [u8; 512], Vec<u8>, usize), C3dParseError> {
-        let header_bytes = read_header_bytes(file)?;
+        let header_bytes = read_header_bytes(reader)?;
         let (processor, parameter_bytes, data_start_block_index) =
-            read_parameter_bytes(file, &header_bytes)?;
+            read_parameter_bytes(&mut self, reader, &header_bytes)?;
         self.processor = processor;
         Ok((self, header_bytes, parameter_bytes, data_start_block_index))
     }
@@ -206,7 +2498,9 @@ impl C3d {
         let header_bytes: [u8; 512] = bytes[0..512].try_into().unwrap();
 
         let parameter_start_block_index = header_bytes[0] as usize;
-
+        if parameter_start_block_index < 2 {
+            return Err(C3dParseError::InvalidParameterStartBlock);
+        }
         if bytes.len() < 512 * (parameter_start_block_index) {
             return Err(C3dParseError::InsufficientBlocks("parameter".to_string()));
         }
@@ -215,18 +2509,21 @@ impl C3d {
             .try_into()
             .unwrap();
 
-        self.processor =
-            Processor::from_parameter_start_block(parameter_start_block.try_into().unwrap())?;
+        self.processor = self.processor_from_block(parameter_start_block)?;
         let data_start_block_index =
             self.processor.u16([header_bytes[16], header_bytes[17]]) as usize;
 
-        if bytes.len() < 512 * (data_start_block_index) {
+        if data_start_block_index <= parameter_start_block_index {
+            return Err(C3dParseError::InvalidDataStartBlock);
+        }
+        // a file without frames ends with the parameter section
+        if bytes.len() < 512 * (data_start_block_index - 1) {
             return Err(C3dParseError::InsufficientBlocks("data".to_string()));
         }
 
-        let parameter_bytes: Vec<u8> = bytes[512..(512 * (data_start_block_index - 1))]
-            .try_into()
-            .unwrap();
+        // the parameter section starts at the block given by the header, which leaves
+        // any blocks between the header and the parameters out
+        let parameter_bytes = bytes[blocks_to_skip..(512 * (data_start_block_index - 1))].to_vec();
 
         Ok((self, header_bytes, parameter_bytes, data_start_block_index))
     }
@@ -238,26 +2535,51 @@ impl C3d {
         Ok(self)
     }
 
-    fn parse_parameters(
+    fn parse_parameters(
+        mut self,
+        header_bytes: &[u8; 512],
+        parameter_bytes: &Vec<u8>,
+    ) -> Result<C3d, C3dParseError> {
+        let parameters = Parameters::parse_parameter_blocks(
+            parameter_bytes,
+            &self.processor,
+            &self.options,
+            &mut self.warnings,
+        )
+        .map_err(|e| in_parameter_section(e, header_bytes))?;
+        self.with_parameters(header_bytes, parameters)
+    }
+
+    /// Parses the groups known to this crate out of the parameters, keeping the others.
+    fn with_parameters(
         mut self,
         header_bytes: &[u8; 512],
-        parameter_bytes: &Vec<u8>,
+        parameters: Parameters,
     ) -> Result<C3d, C3dParseError> {
-        self.parameters = Parameters::parse_parameter_blocks(parameter_bytes, &self.processor)?;
+        let in_group = |group: &str| {
+            let context = ErrorContext::new("group").group(group);
+            move |e: C3dParseError| e.context(context)
+        };
+        self.parameters = parameters;
         self.events = Events::from_header_and_parameters(
             &header_bytes,
             &mut self.parameters,
             &self.processor,
-        )?;
-        self.manufacturer = Manufacturer::from_parameters(&mut self.parameters)?;
-        self.seg = Seg::from_parameters(&mut self.parameters)?;
-        self.forces = ForcePlatforms::from_parameters(&mut self.parameters)?;
+        )
+        .map_err(in_group("EVENT"))?;
+        self.manufacturer = Manufacturer::from_parameters(&mut self.parameters)
+            .map_err(in_group("MANUFACTURER"))?;
+        self.seg = Seg::from_parameters(&mut self.parameters).map_err(in_group("SEG"))?;
+        self.forces = ForcePlatforms::from_parameters(&mut self.parameters)
+            .map_err(in_group("FORCE_PLATFORM"))?;
+        self.rotations = Rotations::from_parameters(&mut self.parameters, self.points.frame_rate)
+            .map_err(in_group("ROTATION"))?;
         Ok(self)
     }
 
-    fn parse_data(self, file: File) -> Result<C3d, C3dParseError> {
-        let data_bytes = read_data_bytes(file)?;
-        self.parse_data_bytes(data_bytes)
+    fn parse_data<R: Read>(self, reader: R) -> Result<C3d, C3dParseError> {
+        let data_bytes = read_data_bytes(reader, &self.options.limits)?;
+        self.parse_data_bytes(data_bytes, true)
     }
 
     fn parse_data_from_bytes(
@@ -269,27 +2591,160 @@ impl C3d {
         if bytes.len() < data_start_byte {
             return Err(C3dParseError::InsufficientBlocks("data".to_string()));
         }
-        self.parse_data_bytes(bytes[data_start_byte..].to_vec())
+        self.parse_data_bytes(bytes[data_start_byte..].to_vec(), true)
     }
 
-    fn parse_data_bytes(mut self, data_bytes: Vec<u8>) -> Result<C3d, C3dParseError> {
-        let (_, num_frames) = self.points.parse(
-            &data_bytes,
-            &mut self.parameters,
-            &self.processor,
-            self.analog.samples_per_frame,
-        )?;
-        self.analog.parse(
-            &data_bytes,
-            &mut self.parameters,
-            &self.processor,
-            num_frames,
-            &self.points.format,
-            self.points.cols(),
-        )?;
+    /// Decodes the data section, or the start of it if `complete` is false, as when only
+    /// the metadata or the first frame is read.
+    /// A complete data section holding fewer frames than declared has its complete
+    /// frames read, with a warning.
+    pub(crate) fn parse_data_bytes(
+        mut self,
+        data_bytes: Vec<u8>,
+        complete: bool,
+    ) -> Result<C3d, C3dParseError> {
+        self.check_frame_count()?;
+        // the number of points stored in each frame, before any are dropped
+        let points_per_frame = self.points.stored_points();
+        let data_start = (self
+            .processor
+            .u16([self.header_bytes[16], self.header_bytes[17]]) as u64)
+            .saturating_sub(1)
+            * 512;
+        let in_data_section =
+            |e: C3dParseError| e.context(ErrorContext::new("the data section").at(data_start));
+        let (_, num_frames) = self
+            .points
+            .parse(
+                &data_bytes,
+                &mut self.parameters,
+                &self.processor,
+                self.analog.samples_per_frame,
+                self.options.point_labels.as_deref(),
+                &self.options.limits,
+            )
+            .map_err(|e| e.context(ErrorContext::new("group").group("POINT")))?;
+        let analog_used = self
+            .analog
+            .parse_parameters(&mut self.parameters)
+            .map_err(|e| e.context(ErrorContext::new("group").group("ANALOG")))?
+            as usize;
+        let channels = match &self.options.analog_labels {
+            None => (0..analog_used).collect(),
+            Some(selection) => selected_columns(&self.analog.labels, analog_used, selection),
+        };
+        self.analog
+            .parse_analog_channels(
+                &data_bytes,
+                &self.processor,
+                num_frames,
+                &self.points.format,
+                points_per_frame,
+                &channels,
+            )
+            .map_err(in_data_section)?;
+        if self.options.analog_labels.is_some() {
+            self.analog.select_channels(&channels);
+            // force platforms refer to the channels by their number in the file
+            for force_platform in self.forces.iter_mut() {
+                for channel in force_platform.channels.iter_mut() {
+                    *channel = channels
+                        .iter()
+                        .position(|&selected| selected + 1 == *channel as usize)
+                        .map_or(0, |position| position as u8 + 1);
+                }
+            }
+        }
+        let point_data_start = self
+            .processor
+            .u16([self.header_bytes[16], self.header_bytes[17]]);
+        let rotations_end =
+            self.rotations
+                .parse(&data_bytes, point_data_start, num_frames, &self.processor);
+        let declared_frames = self.points.declared_frames();
+        let truncated = complete
+            && num_frames < declared_frames
+            && self.bytes_per_frame().is_some_and(|bytes| bytes > 0);
+        if truncated {
+            self.warnings
+                .push(ParseWarning::TruncatedData(num_frames, declared_frames));
+        }
+        // the bytes after the frames of a truncated file are part of the next frame
+        if let Some(bytes_per_frame) = self.bytes_per_frame().filter(|_| !truncated) {
+            let end = (num_frames * bytes_per_frame).max(rotations_end.unwrap_or(0));
+            let trailing = data_bytes.len().saturating_sub(end);
+            // the last block is padded, so only whole blocks after it are unexpected
+            if trailing >= 512 {
+                self.warnings.push(ParseWarning::TrailingData(trailing));
+                if self.options.preserve_bytes {
+                    let start = end.div_ceil(512) * 512;
+                    self.trailing_bytes = data_bytes[start..].to_vec();
+                }
+            }
+        }
         Ok(self)
     }
 
+    /// Compares the number of frames in the header with POINT:FRAMES.
+    /// The header cannot hold more than 65535 frames, and POINT:FRAMES is -1 when
+    /// the count is stored elsewhere, so those files are not compared. Files without
+    /// frames are not compared either, as their header often declares a single frame.
+    fn check_frame_count(&mut self) -> Result<(), C3dParseError> {
+        let header_frames = self.points.last_frame as i64 - self.points.first_frame as i64 + 1;
+        let parameter_frames = match self.parameters.get("POINT", "FRAMES").map(|x| &x.data) {
+            Some(ParameterData::Integer(frames)) if !frames.is_empty() => frames[0] as u16 as i64,
+            Some(ParameterData::Float(frames)) if !frames.is_empty() => frames[0] as i64,
+            _ => return Ok(()),
+        };
+        if self.points.last_frame == u16::MAX
+            || parameter_frames == u16::MAX as i64
+            || parameter_frames == 0
+            || header_frames <= 0
+            || header_frames == parameter_frames
+        {
+            return Ok(());
+        }
+        let (header_frames, parameter_frames) = (header_frames as usize, parameter_frames as usize);
+        if self.options.strict {
+            return Err(C3dParseError::NumFramesMismatch(
+                header_frames,
+                parameter_frames,
+            ));
+        }
+        self.warnings.push(ParseWarning::FrameCountMismatch(
+            header_frames,
+            parameter_frames,
+        ));
+        Ok(())
+    }
+
+    /// Returns the block the data section starts in and the number of points stored in
+    /// each frame, as given by the header.
+    pub(crate) fn header_layout(&self) -> (u16, usize) {
+        let data_start_block = self
+            .processor
+            .u16([self.header_bytes[16], self.header_bytes[17]]);
+        let points_per_frame = self
+            .processor
+            .u16([self.header_bytes[2], self.header_bytes[3]]);
+        (data_start_block, points_per_frame as usize)
+    }
+
+    /// Returns the number of bytes of each frame in the file, from the layout in the header,
+    /// or `None` if the file was not parsed.
+    pub(crate) fn bytes_per_frame(&self) -> Option<usize> {
+        let points_per_frame =
+            self.processor
+                .u16([self.header_bytes[2], self.header_bytes[3]]) as usize;
+        let analog_samples_per_frame = self
+            .processor
+            .u16([self.header_bytes[4], self.header_bytes[5]]);
+        Some(
+            get_point_bytes_per_frame(&self.points.format, points_per_frame)
+                + get_analog_bytes_per_frame(&self.points.format, analog_samples_per_frame).ok()?,
+        )
+    }
+
     /// A function to write a C3D header to bytes.
     fn write_header(&self, data_start_block_index: u16) -> Result<[u8; 512], C3dWriteError> {
         let mut header_bytes = [0u8; 512];
@@ -349,11 +2804,13 @@ impl C3d {
             true => {
                 header_bytes[298] = temp[0];
                 header_bytes[299] = temp[1];
-                let temp = self.processor.u16_to_bytes(self.events.len() as u16);
+                // the header only has room for the first 18 events, the rest are in EVENT:TIMES
+                let header_events = self.events.len().min(18);
+                let temp = self.processor.u16_to_bytes(header_events as u16);
                 header_bytes[300] = temp[0];
                 header_bytes[301] = temp[1];
 
-                for i in 0..self.events.len() {
+                for i in 0..header_events {
                     let temp = self.processor.f32_to_bytes(self.events[i].time);
                     header_bytes[304 + i * 4] = temp[0];
                     header_bytes[305 + i * 4] = temp[1];
@@ -380,7 +2837,11 @@ impl C3d {
         Ok(header_bytes)
     }
 
-    fn write_parameter_blocks(&self) -> Result<Vec<u8>, C3dWriteError> {
+    fn write_parameter_blocks(
+        &self,
+        num_frames: usize,
+        data_start_block_index: u16,
+    ) -> Result<Vec<u8>, C3dWriteError> {
         let mut parameter_bytes: Vec<u8> = Vec::new();
         parameter_bytes.append(vec![0, 0, 0].as_mut());
         parameter_bytes.push(match self.processor {
@@ -388,75 +2849,735 @@ impl C3d {
             Processor::Dec => 0x55,
             Processor::SgiMips => 0x56,
         });
-        let (group_bytes, group_names_to_ids) = self.parameters.write_groups(&self.processor)?;
+        // trials longer than the header can hold store their frame range in the TRIAL group
+        let mut parameters = Cow::Borrowed(&self.parameters);
+        if self.points.actual_fields(num_frames).is_some()
+            && self.parameters.get_group("TRIAL").is_none()
+        {
+            parameters.to_mut().add_group("TRIAL", "")?;
+        }
+        if !self.rotations.is_unused() && self.parameters.get_group("ROTATION").is_none() {
+            parameters.to_mut().add_group("ROTATION", "")?;
+        }
+        let (group_bytes, group_names_to_ids) = parameters.write_groups(&self.processor)?;
         parameter_bytes.extend(group_bytes);
 
-        let num_frames = match self.points.rows() == 0
-            && self.analog.rows() > 0
-            && self.analog.samples_per_channel_per_frame != 0
-        {
-            true => self.analog.rows() / self.analog.samples_per_channel_per_frame as usize,
-            false => self.points.rows(),
-        };
-        parameter_bytes.extend(self.points.write_parameters(
+        let mut component_bytes = self.points.write_parameters(
             &self.processor,
             &group_names_to_ids,
             num_frames,
-        )?);
-        parameter_bytes.extend(
+            data_start_block_index,
+        )?;
+        component_bytes.extend(
             self.analog
                 .write_parameters(&self.processor, &group_names_to_ids)?,
         );
-        parameter_bytes.extend(self.forces.write(&self.processor, &group_names_to_ids)?);
-        parameter_bytes.extend(self.events.write(&self.processor, &group_names_to_ids)?);
-        parameter_bytes.extend(
+        component_bytes.extend(self.forces.write(&self.processor, &group_names_to_ids)?);
+        component_bytes.extend(self.events.write(&self.processor, &group_names_to_ids)?);
+        component_bytes.extend(
             self.manufacturer
                 .write(&self.processor, &group_names_to_ids)?,
         );
-        parameter_bytes.extend(self.seg.write(&self.processor, &group_names_to_ids)?);
-        parameter_bytes.extend(
-            self.parameters
-                .write_parameters(&self.processor, &group_names_to_ids)?,
-        );
+        component_bytes.extend(self.seg.write(&self.processor, &group_names_to_ids)?);
+        // the samples of the bodies start in the block after the frames
+        let rotations_start = data_start_block_index as usize + self.frame_blocks();
+        component_bytes.extend(self.rotations.write(
+            &self.processor,
+            &group_names_to_ids,
+            rotations_start.min(u16::MAX as usize) as u16,
+        )?);
+        // parameters left unparsed by a component, e.g. POINT:LABELS of a file without points,
+        // are replaced by the values the component writes
+        let component_bytes =
+            parameters.restore_attributes(&component_bytes, &self.processor, &group_names_to_ids);
+        let written = written_parameters(&component_bytes, &self.processor, &group_names_to_ids);
+        let written: Vec<(&str, &str)> = written
+            .iter()
+            .map(|(group, parameter)| (group.as_str(), parameter.as_str()))
+            .collect();
+        parameter_bytes.extend(component_bytes);
+        parameter_bytes.extend(parameters.write_parameters(
+            &self.processor,
+            &group_names_to_ids,
+            &written,
+        )?);
+
+        if let Some(encoding) = self.options.encoding {
+            parameter_bytes = encode_text(&parameter_bytes, &self.processor, encoding)?;
+        }
 
-        let num_blocks = parameter_bytes.len() / 512 + 1;
-        parameter_bytes[2] = num_blocks as u8;
+        let num_blocks = parameter_bytes.len().div_ceil(512);
+        parameter_bytes[2] = block_count_byte(num_blocks);
         Ok(parameter_bytes)
     }
 
-    fn write_data(&self) -> Result<Vec<u8>, C3dWriteError> {
+    /// Writes the header and the parameter section, padded to whole blocks.
+    fn write_header_and_parameters(&self) -> Result<([u8; 512], Vec<u8>), C3dWriteError> {
+        self.write_header_and_parameters_for(self.stored_frame_count(), 0)
+    }
+
+    /// Writes the header and the parameter section for the given number of frames,
+    /// padding the parameter section to at least `min_blocks` blocks, e.g. to rewrite
+    /// it in place once the number of frames of a streamed file is known.
+    pub(crate) fn write_header_and_parameters_for(
+        &self,
+        num_frames: usize,
+        min_blocks: usize,
+    ) -> Result<([u8; 512], Vec<u8>), C3dWriteError> {
+        // the size of the parameter section does not depend on the value of POINT:DATA_START,
+        // so the section is written once to find where the data starts
+        let parameter_blocks = self
+            .write_parameter_blocks(num_frames, 0)?
+            .len()
+            .div_ceil(512)
+            .max(min_blocks);
+        let data_start_block_index = u16::try_from(2 + parameter_blocks)
+            .map_err(|_| C3dWriteError::ParameterSectionTooLarge(parameter_blocks))?;
+        let mut parameter_bytes =
+            self.write_parameter_blocks(num_frames, data_start_block_index)?;
+        // add padding
+        parameter_bytes.resize(parameter_blocks * 512, 0);
+        parameter_bytes[2] = block_count_byte(parameter_blocks);
+        let header_bytes = self.write_header(data_start_block_index)?;
+        Ok((header_bytes, parameter_bytes))
+    }
+
+    /// Encodes the data section, reporting each frame to the callback.
+    fn encode_data<F>(&self, progress: &mut F) -> Result<Vec<u8>, C3dWriteError>
+    where
+        F: FnMut(Progress) -> ControlFlow<()>,
+    {
         let mut data_bytes = Vec::new();
-        let num_frames = match self.points.rows() == 0
-            && self.analog.rows() > 0
-            && self.analog.samples_per_channel_per_frame != 0
-        {
-            true => self.analog.rows() / self.analog.samples_per_channel_per_frame as usize,
-            false => self.points.rows(),
-        };
+        let num_frames = self.stored_frame_count();
+        if !report(progress, ProgressStage::Encoding, 0, num_frames as u64) {
+            return Err(C3dWriteError::Cancelled);
+        }
         for i in 0..num_frames {
             data_bytes.extend(self.points.write_frame(i, &self.processor));
             data_bytes.extend(
                 self.analog
                     .write_frame(i, &self.processor, &self.points.format),
             );
+            if !report(
+                progress,
+                ProgressStage::Encoding,
+                i as u64 + 1,
+                num_frames as u64,
+            ) {
+                return Err(C3dWriteError::Cancelled);
+            }
         }
+        // the file ends on a whole block, as do the frames when followed by the rotations
+        data_bytes.resize(data_bytes.len().div_ceil(512) * 512, 0);
+        if self.rotations.cols() > 0 {
+            data_bytes.extend(self.rotations.write_data(num_frames, &self.processor));
+            data_bytes.resize(data_bytes.len().div_ceil(512) * 512, 0);
+        }
+        data_bytes.extend_from_slice(&self.trailing_bytes);
         Ok(data_bytes)
     }
 
+    /// Returns the number of blocks the point and analog frames take when written.
+    fn frame_blocks(&self) -> usize {
+        let num_frames = self.stored_frame_count();
+        if num_frames == 0 {
+            return 0;
+        }
+        let bytes_per_frame = self.points.write_frame(0, &self.processor).len()
+            + self
+                .analog
+                .write_frame(0, &self.processor, &self.points.format)
+                .len();
+        (num_frames * bytes_per_frame).div_ceil(512)
+    }
+
+    /// Returns the subject measurements stored in the PROCESSING group by Vicon Nexus.
+    /// The measurements are empty for files without the group.
+    pub fn processing(&self) -> Processing {
+        Processing::from_parameters(&self.parameters)
+    }
+
+    /// Stores subject measurements in the PROCESSING group, replacing those with the same
+    /// name and keeping the others, e.g. to copy them from a static trial to a dynamic one.
+    pub fn set_processing(&mut self, processing: &Processing) -> Result<(), C3dWriteError> {
+        processing.write_parameters(&mut self.parameters)?;
+        self.original_bytes = None;
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Returns the timecode settings stored in the TIMECODE group,
+    /// or `None` for files without the group.
+    pub fn timecode(&self) -> Option<TimecodeGroup> {
+        TimecodeGroup::from_parameters(&self.parameters, self.points.frame_rate)
+    }
+
+    /// Stores timecode settings in the TIMECODE group, keeping its other parameters.
+    pub fn set_timecode(&mut self, timecode: &TimecodeGroup) -> Result<(), C3dWriteError> {
+        timecode.write_parameters(&mut self.parameters)?;
+        self.original_bytes = None;
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Returns the timecode of a frame, counted from zero at the first stored frame,
+    /// or `None` if the file has no start timecode. See `TimecodeGroup::timecode_of_frame`.
+    pub fn frame_timecode(&self, frame: usize) -> Option<Timecode> {
+        self.timecode()?
+            .timecode_of_frame(frame, self.points.frame_rate)
+    }
+
+    /// Returns the frame with the given timecode, counted from zero at the first stored
+    /// frame, or `None` if it is outside the trial or the file has no start timecode.
+    pub fn timecode_frame(&self, timecode: &Timecode) -> Option<usize> {
+        self.timecode()?
+            .frame_of_timecode(timecode, self.points.frame_rate)
+            .filter(|&frame| frame < self.frame_count())
+    }
+
+    /// Returns a short description of the file for reading, e.g. to print while debugging:
+    /// the processor and data format, the number of frames and their duration, the point
+    /// and analog labels, the force platforms, the events and the software that wrote it.
+    /// `to_string` lists every field instead.
+    pub fn summary(&self) -> String {
+        let mut s = String::new();
+        s.push_str(&format!(
+            "Processor: {:?}, {:?} data\n",
+            self.processor, self.points.format
+        ));
+        let frames = self.frame_count();
+        let rate = self.points.frame_rate;
+        s.push_str(&format!("Frames: {}", frames));
+        if frames > 0 {
+            let first_frame = self.points.first_frame as usize;
+            let last_frame = first_frame + frames - 1;
+            s.push_str(&format!(" ({} to {})", first_frame, last_frame));
+        }
+        if rate > 0. {
+            s.push_str(&format!(" at {} Hz, {:.3} s", rate, frames as f32 / rate));
+        }
+        s.push('\n');
+
+        let units: String = self.points.units.iter().collect();
+        let units = units.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+        s.push_str(&format!("Points: {}", self.points.cols()));
+        if self.points.cols() > 0 && !units.is_empty() {
+            s.push_str(&format!(" in {}", units));
+        }
+        s.push('\n');
+        if self.points.cols() > 0 {
+            let labels: Vec<&str> = self
+                .points
+                .labels
+                .iter()
+                .take(self.points.cols())
+                .map(|label| label.trim())
+                .collect();
+            s.push_str(&format!("  {}\n", labels.join(", ")));
+        }
+
+        s.push_str(&format!("Analog: {} channels", self.analog.cols()));
+        if self.analog.cols() > 0 && self.analog.rate > 0. {
+            s.push_str(&format!(
+                " at {} Hz, {} samples per frame",
+                self.analog.rate, self.analog.samples_per_channel_per_frame
+            ));
+        }
+        s.push('\n');
+        if self.analog.cols() > 0 {
+            let channels: Vec<String> = (0..self.analog.cols())
+                .map(|channel| {
+                    let label = self.analog.labels.get(channel).map_or("", |l| l.trim());
+                    match self.analog.units.get(channel).map(|u| u.trim()) {
+                        Some(units) if !units.is_empty() => format!("{} ({})", label, units),
+                        _ => label.to_string(),
+                    }
+                })
+                .collect();
+            s.push_str(&format!("  {}\n", channels.join(", ")));
+        }
+
+        s.push_str(&format!("Force platforms: {}\n", self.forces.len()));
+        for (i, force_platform) in self.forces.iter().enumerate() {
+            let channels: Vec<u8> = force_platform
+                .channels
+                .iter()
+                .copied()
+                .filter(|&channel| channel != 0)
+                .collect();
+            s.push_str(&format!(
+                "  {}: {:?}, channels {:?}\n",
+                i + 1,
+                force_platform.plate_type,
+                channels
+            ));
+        }
+
+        s.push_str(&format!("Events: {}\n", self.events.len()));
+        let mut events: Vec<&Event> = self.events.iter().collect();
+        events.sort_by(|a, b| a.time.total_cmp(&b.time));
+        for event in events {
+            let name: Vec<&str> = [event.context.trim(), event.label.trim()]
+                .into_iter()
+                .filter(|part| !part.is_empty())
+                .collect();
+            s.push_str(&format!("  {:.3} s: {}\n", event.time, name.join(" ")));
+        }
+
+        let software: Vec<&str> = [&self.manufacturer.company, &self.manufacturer.software]
+            .into_iter()
+            .flatten()
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+            .collect();
+        if !software.is_empty() {
+            s.push_str(&format!("Manufacturer: {}\n", software.join(", ")));
+        }
+        s
+    }
+
+    /// Checks the file this `C3d` would be written as against the C3D specification:
+    /// required parameters are present, per-channel parameters match the number used,
+    /// and the header agrees with the parameters.
+    /// Constructed files are checked the same way as parsed ones.
+    pub fn validate(&self) -> Result<ValidationReport, C3dWriteError> {
+        let (header_bytes, parameter_bytes) = self.write_header_and_parameters()?;
+        let mut warnings = Vec::new();
+        let parameters = Parameters::parse_parameter_blocks(
+            &parameter_bytes,
+            &self.processor,
+            &ParseOptions::lenient(),
+            &mut warnings,
+        );
+        let mut report = match parameters {
+            Ok(parameters) => validate(
+                &header_bytes,
+                &parameter_bytes,
+                &parameters,
+                &self.processor,
+                &warnings,
+                None,
+            ),
+            Err(e) => {
+                let mut report = ValidationReport::default();
+                report.error(
+                    ValidationLocation::ParameterSection,
+                    format!("parameters cannot be read back: {:?}", e),
+                );
+                report
+            }
+        };
+        let samples_per_channel = self.analog.samples_per_channel_per_frame as usize;
+        if self.points.rows() > 0
+            && self.analog.rows() > 0
+            && self.analog.rows() != self.points.rows() * samples_per_channel
+        {
+            report.error(
+                ValidationLocation::Data,
+                format!(
+                    "{} analog samples per channel for {} frames of {} samples",
+                    self.analog.rows(),
+                    self.points.rows(),
+                    samples_per_channel
+                ),
+            );
+        }
+        Ok(report)
+    }
+
+    /// Returns every parameter the file would be written with, including those stored
+    /// in `points`, `analog` and the other fields, as pairs of a key such as `POINT:RATE`
+    /// and its values as a JSON array, e.g. `[100]` or `["LASI","RASI"]`, with one string
+    /// per row of a character parameter. The pairs are sorted by key, e.g. to be stored
+    /// as the key-value metadata of an Arrow schema or a Parquet file next to a `Table`.
+    pub fn parameter_metadata(&self) -> Result<Vec<(String, String)>, C3dWriteError> {
+        let parameters = self.written_parameters()?;
+        let mut metadata = Vec::new();
+        for group in parameters.groups() {
+            for parameter in parameters.parameters(group).unwrap_or_default() {
+                let key = format!("{}:{}", group, parameter.name);
+                metadata.push((key, json_values(parameter)));
+            }
+        }
+        metadata.sort();
+        Ok(metadata)
+    }
+
+    /// Returns the parameters the file would be written with, as they would be read back.
+    fn written_parameters(&self) -> Result<Parameters, C3dWriteError> {
+        let (_, parameter_bytes) = self.write_header_and_parameters()?;
+        Parameters::parse_parameter_blocks(
+            &parameter_bytes,
+            &self.processor,
+            &ParseOptions {
+                encoding: self.options.encoding,
+                ..ParseOptions::lenient()
+            },
+            &mut Vec::new(),
+        )
+        .map_err(|e| {
+            C3dWriteError::WriteParametersError(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("{:?}", e),
+            ))
+        })
+    }
+
+    /// Compares the contents of two files with the default tolerances of `DiffOptions`.
+    /// See `diff_with_options`.
+    pub fn diff(&self, other: &C3d) -> Result<C3dDiff, C3dWriteError> {
+        self.diff_with_options(other, &DiffOptions::default())
+    }
+
+    /// Compares the contents of two files rather than their bytes, so files written
+    /// with different processors or data formats can be compared.
+    /// The parameters are compared as the files would be written, including those
+    /// stored in `points`, `analog` and the other fields. Points and analog channels are
+    /// paired by label and compared in the frames both files have, and events are paired
+    /// by label, context and time.
+    pub fn diff_with_options(
+        &self,
+        other: &C3d,
+        options: &DiffOptions,
+    ) -> Result<C3dDiff, C3dWriteError> {
+        let mut diff = C3dDiff::default();
+        diff.header("processor", self.processor, other.processor);
+        diff.header("data format", &self.points.format, &other.points.format);
+        diff.header(
+            "first frame",
+            self.points.first_frame,
+            other.points.first_frame,
+        );
+        diff.header("frames", self.frame_count(), other.frame_count());
+        diff.parameters(
+            &self.written_parameters()?,
+            &other.written_parameters()?,
+            options.parameter_tolerance,
+            options.max_ulps,
+        );
+        diff.events(
+            &self.events,
+            &other.events,
+            options.time_tolerance,
+            options.max_ulps,
+        );
+
+        let column_labels = |labels: &[String], cols: usize| -> Vec<String> {
+            (0..cols)
+                .map(|i| labels.get(i).cloned().unwrap_or_default())
+                .collect()
+        };
+        let left = column_labels(&self.points.labels, self.points.cols());
+        let right = column_labels(&other.points.labels, other.points.cols());
+        let (pairs, left_only, right_only) = pair_columns(&left, &right);
+        for (labels, only, only_in) in [
+            (&left, left_only, DiffSide::Left),
+            (&right, right_only, DiffSide::Right),
+        ] {
+            for i in only {
+                diff.differences.push(Difference::MissingPoint {
+                    label: labels[i].clone(),
+                    only_in,
+                });
+            }
+        }
+        for (i, j) in pairs {
+            diff.point(
+                &left[i],
+                self.points.iter_col(i).copied(),
+                other.points.iter_col(j).copied(),
+                options.point_tolerance,
+                options.max_ulps,
+            );
+        }
+
+        let left = column_labels(&self.analog.labels, self.analog.cols());
+        let right = column_labels(&other.analog.labels, other.analog.cols());
+        let (pairs, left_only, right_only) = pair_columns(&left, &right);
+        for (labels, only, only_in) in [
+            (&left, left_only, DiffSide::Left),
+            (&right, right_only, DiffSide::Right),
+        ] {
+            for i in only {
+                diff.differences.push(Difference::MissingAnalog {
+                    label: labels[i].clone(),
+                    only_in,
+                });
+            }
+        }
+        for (i, j) in pairs {
+            diff.analog(
+                &left[i],
+                self.analog.iter_col(i).copied(),
+                other.analog.iter_col(j).copied(),
+                options.analog_tolerance,
+                options.max_ulps,
+            );
+        }
+        Ok(diff)
+    }
+
+    /// Returns whether two files have the same contents within the tolerances, whatever
+    /// processor they are stored for, e.g. to check a conversion between DEC and IEEE
+    /// floats with `DiffOptions::processor_conversion`.
+    /// Files whose parameters cannot be written are not equal. See `diff_with_options`.
+    pub fn approx_eq(&self, other: &C3d, options: &DiffOptions) -> bool {
+        match self.diff_with_options(other, options) {
+            Ok(diff) => diff.differences.iter().all(|difference| {
+                matches!(difference, Difference::Header { field, .. } if field == "processor")
+            }),
+            Err(_) => false,
+        }
+    }
+
+    /// Checks a C3D file as stored, before any of its parameters are interpreted.
+    /// Fails only if the header or the parameter section cannot be read at all.
+    pub fn validate_bytes(bytes: &[u8]) -> Result<ValidationReport, C3dParseError> {
+        let (mut c3d, header_bytes, parameter_bytes, data_start_block_index) =
+            C3d::with_options(ParseOptions::lenient()).parse_basic_info_from_bytes(bytes)?;
+        let parameters = Parameters::parse_parameter_blocks(
+            &parameter_bytes,
+            &c3d.processor,
+            &c3d.options,
+            &mut c3d.warnings,
+        )
+        .map_err(|e| in_parameter_section(e, &header_bytes))?;
+        let data_length = bytes
+            .len()
+            .saturating_sub(512 * (data_start_block_index - 1));
+        Ok(validate(
+            &header_bytes,
+            &parameter_bytes,
+            &parameters,
+            &c3d.processor,
+            &c3d.warnings,
+            Some(data_length),
+        ))
+    }
+
+    /// Reads a C3D file and checks it with `validate_bytes`.
+    #[cfg(feature = "fs")]
+    pub fn validate_file(file_name: &str) -> Result<ValidationReport, C3dParseError> {
+        let bytes = std::fs::read(file_name).map_err(C3dParseError::ReadError)?;
+        C3d::validate_bytes(&bytes)
+    }
+
+    /// Parses a file that other readers open but the strict parser rejects, fixing
+    /// inconsistencies between its header and parameters first:
+    /// a stale data start pointer, disagreeing frame counts, POINT:FRAMES stuck at 65535
+    /// and points or analog channels without labels.
+    /// The file is parsed in lenient mode, and the repairs made are returned with it.
+    /// Writing the returned `C3d` produces a clean file.
+    pub fn repair(bytes: &[u8]) -> Result<(C3d, Vec<Repair>), C3dParseError> {
+        let repaired = repair(bytes)?;
+        let mut c3d = C3d::with_options(ParseOptions::lenient());
+        c3d.processor = repaired.processor;
+        c3d.warnings = repaired.warnings;
+        let c3d = c3d
+            .parse_header(&repaired.header)?
+            .with_parameters(&repaired.header, repaired.parameters)?
+            .parse_data_bytes(bytes[repaired.data_start_byte..].to_vec(), true)?;
+        Ok((c3d, repaired.repairs))
+    }
+
+    /// Reads a file and parses it with `repair`.
+    #[cfg(feature = "fs")]
+    pub fn repair_file(file_name: &str) -> Result<(C3d, Vec<Repair>), C3dParseError> {
+        let bytes = std::fs::read(file_name).map_err(C3dParseError::ReadError)?;
+        C3d::repair(&bytes)
+    }
+
+    #[cfg(feature = "fs")]
     pub fn write(&self, file_name: &str) -> Result<&Self, C3dWriteError> {
         self.write_path(PathBuf::from(file_name))
     }
 
+    /// Writes the C3D file to a file path like `write`, with the given options.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use c3dio::prelude::*;
+    /// use c3dio::WriteOptions;
+    ///
+    /// let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    /// c3d.write_with_options("short_integer.c3d", &WriteOptions::integer())
+    ///     .unwrap();
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn write_with_options(
+        &self,
+        file_name: &str,
+        options: &WriteOptions,
+    ) -> Result<&Self, C3dWriteError> {
+        self.write_file(PathBuf::from(file_name), options)
+    }
+
+    /// Writes the C3D file to a file path like `write`, encoded for the given processor
+    /// type, e.g. to move files recorded on DEC or SGI MIPS systems to Intel.
+    /// Every parameter is re-encoded, including those not interpreted by this crate,
+    /// so converting a file back to its original processor reproduces it.
+    #[cfg(feature = "fs")]
+    pub fn write_with_processor(
+        &self,
+        file_name: &str,
+        processor: Processor,
+    ) -> Result<&Self, C3dWriteError> {
+        self.write_with_options(
+            file_name,
+            &WriteOptions::default().with_processor(processor),
+        )
+    }
+
+    /// Writes the C3D file to a file path like `write_with_options`, reporting the progress
+    /// of encoding and writing it to a callback, e.g. to show a progress bar.
+    /// The callback can cancel writing by returning `ControlFlow::Break(())`, which
+    /// removes the partly written file and returns `C3dWriteError::Cancelled`.
+    #[cfg(feature = "fs")]
+    pub fn write_with_progress<F>(
+        &self,
+        file_name: &str,
+        options: &WriteOptions,
+        progress: F,
+    ) -> Result<&Self, C3dWriteError>
+    where
+        F: FnMut(Progress) -> ControlFlow<()>,
+    {
+        let file_name = PathBuf::from(file_name);
+        self.check_file_name(&file_name)?;
+        let mut file = File::create(file_name.clone())
+            .map_err(|e| C3dWriteError::WriteError(file_name.clone(), e))?;
+        match self.write_to_with_progress(&mut file, options, progress) {
+            Err(C3dWriteError::Cancelled) => {
+                drop(file);
+                std::fs::remove_file(&file_name)
+                    .map_err(|e| C3dWriteError::WriteError(file_name.clone(), e))?;
+                return Err(C3dWriteError::Cancelled);
+            }
+            result => result?,
+        };
+        file.sync_all()
+            .map_err(|e| C3dWriteError::WriteError(file_name.clone(), e))?;
+        Ok(self)
+    }
+
+    /// Writes the C3D file to any sink like `write_to_with_options`, reporting progress
+    /// to a callback as `write_with_progress` does. Bytes already written to the sink
+    /// are not undone when writing is cancelled.
+    pub fn write_to_with_progress<W: Write, F>(
+        &self,
+        writer: W,
+        options: &WriteOptions,
+        mut progress: F,
+    ) -> Result<&Self, C3dWriteError>
+    where
+        F: FnMut(Progress) -> ControlFlow<()>,
+    {
+        self.write_converted(writer, options, &mut progress)?;
+        Ok(self)
+    }
+
+    /// Writes the file with the given options, on a converted copy if they change its
+    /// processor, data format, events or encoding, and reports progress to the callback.
+    /// Every writer goes through here, so they all convert files and reuse preserved
+    /// bytes the same way.
+    fn write_converted<W: Write, F>(
+        &self,
+        mut writer: W,
+        options: &WriteOptions,
+        progress: &mut F,
+    ) -> Result<(), C3dWriteError>
+    where
+        F: FnMut(Progress) -> ControlFlow<()>,
+    {
+        let processor = options.processor.unwrap_or(self.processor);
+        let format = options.format.clone().unwrap_or(self.points.format.clone());
+        let encoding = options.encoding.or(self.options.encoding);
+        if processor != self.processor
+            || format != self.points.format
+            || options.snap_events
+            || encoding != self.options.encoding
+        {
+            let mut c3d = self.clone();
+            c3d.convert_to(processor);
+            if format != self.points.format {
+                c3d.convert_format(format);
+            }
+            if options.snap_events {
+                c3d.snap_events_to_frames();
+            }
+            c3d.set_encoding(encoding);
+            return c3d.write_converted(writer, &WriteOptions::default(), progress);
+        }
+        let sections = match self.unmodified_original_bytes() {
+            Some(bytes) => {
+                let data_start = self.frame_byte_offset(0).unwrap_or(bytes.len() as u64) as usize;
+                let data_start = data_start.clamp(512, bytes.len());
+                [
+                    Cow::Borrowed(&bytes[..512]),
+                    Cow::Borrowed(&bytes[512..data_start]),
+                    Cow::Borrowed(&bytes[data_start..]),
+                ]
+            }
+            None => {
+                let (header_bytes, parameter_bytes) = self.write_header_and_parameters()?;
+                let data_bytes = self.encode_data(progress)?;
+                [
+                    Cow::Owned(header_bytes.to_vec()),
+                    Cow::Owned(parameter_bytes),
+                    Cow::Owned(data_bytes),
+                ]
+            }
+        };
+        let total: usize = sections.iter().map(|section| section.len()).sum();
+        let mut done = 0;
+        let errors = [
+            C3dWriteError::WriteHeaderError,
+            C3dWriteError::WriteParametersError,
+            C3dWriteError::WriteDataError,
+        ];
+        for (section, error) in sections.iter().zip(errors) {
+            for chunk in section.chunks(CHUNK_SIZE) {
+                writer.write_all(chunk).map_err(error)?;
+                done += chunk.len();
+                if !report(progress, ProgressStage::Writing, done as u64, total as u64) {
+                    return Err(C3dWriteError::Cancelled);
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// A function to write a C3D file to a file path.
     /// This function will overwrite any existing file.
     /// If the file path does not exist, it will be created.
     /// If the file path is a directory, an error will be returned.
     /// If the file path is not writable, an error will be returned.
     /// If the file path is not a valid UTF-8 string, an error will be returned.
+    #[cfg(feature = "fs")]
     pub fn write_path(&self, file_name: PathBuf) -> Result<&Self, C3dWriteError> {
+        self.write_file(file_name, &WriteOptions::default())
+    }
+
+    #[cfg(feature = "fs")]
+    fn write_file(
+        &self,
+        file_name: PathBuf,
+        options: &WriteOptions,
+    ) -> Result<&Self, C3dWriteError> {
+        self.check_file_name(&file_name)?;
+        let mut file = File::create(file_name.clone())
+            .map_err(|e| C3dWriteError::WriteError(file_name.clone(), e))?;
+        self.write_to_with_options(&mut file, options)?;
+        file.sync_all()
+            .map_err(|e| C3dWriteError::WriteError(file_name.clone(), e))?;
+        Ok(self)
+    }
+
+    #[cfg(feature = "fs")]
+    fn check_file_name(&self, file_name: &Path) -> Result<(), C3dWriteError> {
         // Check if the file path is a directory.
         if file_name.is_dir() {
-            return Err(C3dWriteError::InvalidFilePath(file_name));
+            return Err(C3dWriteError::InvalidFilePath(file_name.to_path_buf()));
         }
         // Check if file_name ends with ".c3d", ".C3D", ".c3D", or ".C3d".
         let extension = file_name
@@ -469,42 +3590,307 @@ impl C3d {
                 file_name.to_string_lossy().to_string(),
             ));
         }
-        let mut file = File::create(file_name.clone())
+        Ok(())
+    }
+
+    /// Writes the C3D file to any sink like `write_to`, with the given options.
+    /// The file is converted on a copy, so `self` keeps its processor and data format.
+    pub fn write_to_with_options<W: Write>(
+        &self,
+        writer: W,
+        options: &WriteOptions,
+    ) -> Result<&Self, C3dWriteError> {
+        self.write_converted(writer, options, &mut |_| ControlFlow::Continue(()))?;
+        Ok(self)
+    }
+
+    /// Writes the C3D file to any sink, such as an in-memory buffer or a network stream,
+    /// encoded for the given processor type.
+    /// A file parsed with `ParseOptions::preserve_bytes` is written verbatim if it is
+    /// unmodified and `processor` is the processor type it was parsed with.
+    pub fn write_to<W: Write>(
+        &self,
+        writer: W,
+        processor: Processor,
+    ) -> Result<&Self, C3dWriteError> {
+        self.write_to_with_options(writer, &WriteOptions::default().with_processor(processor))
+    }
+
+    /// Rewrites the header and parameter section of a C3D file in place, leaving its data
+    /// section untouched, e.g. to fix a label or add an event to a large trial loaded
+    /// with `load_header_and_parameters`. See `write_parameters_in_place_to`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use c3dio::prelude::*;
+    ///
+    /// let mut c3d = C3d::load_header_and_parameters("trial.c3d".into()).unwrap();
+    /// c3d.points.labels[0] = "LASI".to_string();
+    /// c3d.write_parameters_in_place("trial.c3d").unwrap();
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn write_parameters_in_place(&self, file_name: &str) -> Result<&Self, C3dWriteError> {
+        let file_name = PathBuf::from(file_name);
+        let mut file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&file_name)
             .map_err(|e| C3dWriteError::WriteError(file_name.clone(), e))?;
-        let mut parameter_bytes = self.write_parameter_blocks()?;
-        if parameter_bytes.len() % 512 != 0 {
-            // add padding
-            let padding = 512 - parameter_bytes.len() % 512;
-            parameter_bytes.extend(vec![0u8; padding]);
-        }
-        let data_start_block_index = 2 + parameter_bytes.len() / 512;
-        let header_bytes = self.write_header(data_start_block_index as u16)?;
-        let data_bytes = self.write_data()?;
-
-        file.write_all(&header_bytes)
-            .map_err(|e| C3dWriteError::WriteHeaderError(e))?;
-        file.write_all(&parameter_bytes)
-            .map_err(|e| C3dWriteError::WriteParametersError(e))?;
-        file.write_all(&data_bytes)
-            .map_err(|e| C3dWriteError::WriteDataError(e))?;
+        self.write_parameters_in_place_to(&mut file)?;
         file.sync_all()
             .map_err(|e| C3dWriteError::WriteError(file_name.clone(), e))?;
         Ok(self)
     }
+
+    /// Rewrites the header and parameter section of the C3D file starting at the current
+    /// position of the writer, in the blocks the parameter section already takes up.
+    /// The data section is neither read nor written, so only the parameters of `self` are
+    /// used and its point and analog data may be empty. Edits to loaded point or analog
+    /// data are not written, so a file with its data loaded is rejected once it is dirty,
+    /// e.g. after `convert_units`, and it must keep the scale factor of the stored data.
+    ///
+    /// The file must keep its processor type, the number of frames it declares, and
+    /// the number of points, analog samples and data format of each frame, which
+    /// `C3dWriteError::CannotWriteInPlace` is returned for, as it is for parameters that
+    /// no longer fit in the parameter section. Nothing is written if an error is returned.
+    pub fn write_parameters_in_place_to<W: Read + Write + Seek>(
+        &self,
+        mut writer: W,
+    ) -> Result<&Self, C3dWriteError> {
+        let start = writer
+            .stream_position()
+            .map_err(C3dWriteError::WriteHeaderError)?;
+        let on_disk = C3d::header_and_parameters_from_reader(&mut writer)
+            .map_err(|e| C3dWriteError::InPlaceParseError(Box::new(e)))?;
+        if self.processor != on_disk.processor {
+            return Err(C3dWriteError::CannotWriteInPlace(
+                "the processor type changed".to_string(),
+            ));
+        }
+        if !self.rotations.is_unused() || !on_disk.rotations.is_unused() {
+            return Err(C3dWriteError::CannotWriteInPlace(
+                "the ROTATION samples are stored after the frames".to_string(),
+            ));
+        }
+        let num_frames = on_disk.declared_frame_count();
+        if self.declared_frame_count() != num_frames {
+            return Err(C3dWriteError::CannotWriteInPlace(
+                "the number of frames changed".to_string(),
+            ));
+        }
+        let data_loaded = self.points.rows() > 0 || self.analog.rows() > 0;
+        if data_loaded && self.dirty {
+            return Err(C3dWriteError::CannotWriteInPlace(
+                "the point or analog data changed".to_string(),
+            ));
+        }
+        // without its data, the layout of the frames is taken from the header it was parsed with
+        let mut c3d = Cow::Borrowed(self);
+        if !data_loaded {
+            let (_, points_per_frame) = self.header_layout();
+            let samples_per_channel = self.analog.samples_per_channel_per_frame as usize;
+            let channels = match samples_per_channel {
+                0 => 0,
+                _ => self.analog.samples_per_frame as usize / samples_per_channel,
+            };
+            let c3d = c3d.to_mut();
+            c3d.points.points = Grid::new(1, points_per_frame);
+            c3d.analog.analog = Grid::new(samples_per_channel, channels);
+        }
+        let (data_start_block, _) = on_disk.header_layout();
+        let parameter_blocks = (data_start_block as usize).saturating_sub(2);
+        let (header_bytes, parameter_bytes) =
+            c3d.write_header_and_parameters_for(num_frames, parameter_blocks)?;
+        if parameter_bytes.len() != parameter_blocks * 512 {
+            return Err(C3dWriteError::CannotWriteInPlace(
+                "the parameters do not fit in the parameter section".to_string(),
+            ));
+        }
+        // the words giving the points per frame, analog samples per frame, the sign of the
+        // scale factor for the data format and the data start block must be unchanged,
+        // as must the scale factor itself for loaded data, which is stored scaled by it
+        let words = |bytes: &[u8]| {
+            [
+                self.processor.u16([bytes[2], bytes[3]]),
+                self.processor.u16([bytes[4], bytes[5]]),
+                self.processor.u16([bytes[16], bytes[17]]),
+                self.processor.u16([bytes[18], bytes[19]]),
+            ]
+        };
+        let scale = |bytes: &[u8]| {
+            let scale = self.processor.f32(bytes[12..16].try_into().unwrap());
+            (scale < 0., data_loaded.then_some(scale))
+        };
+        if words(&header_bytes) != words(&on_disk.header_bytes)
+            || scale(&header_bytes) != scale(&on_disk.header_bytes)
+        {
+            return Err(C3dWriteError::CannotWriteInPlace(
+                "the layout of the frames changed".to_string(),
+            ));
+        }
+        writer
+            .seek(SeekFrom::Start(start))
+            .map_err(C3dWriteError::WriteHeaderError)?;
+        writer
+            .write_all(&header_bytes)
+            .map_err(C3dWriteError::WriteHeaderError)?;
+        writer
+            .write_all(&parameter_bytes)
+            .map_err(C3dWriteError::WriteParametersError)?;
+        writer
+            .flush()
+            .map_err(C3dWriteError::WriteParametersError)?;
+        Ok(self)
+    }
+}
+
+/// Returns the group and name of each parameter in a run of written parameter records.
+fn written_parameters(
+    bytes: &[u8],
+    processor: &Processor,
+    group_names_to_ids: &HashMap<String, usize>,
+) -> Vec<(String, String)> {
+    let mut written = Vec::new();
+    let mut i = 0;
+    while i + 2 < bytes.len() {
+        let name_length = (bytes[i] as i8).unsigned_abs() as usize;
+        let group_id = bytes[i + 1] as usize;
+        let next_index = i + 2 + name_length;
+        if name_length == 0 || next_index + 2 > bytes.len() {
+            break;
+        }
+        let name = String::from_utf8_lossy(&bytes[i + 2..next_index]).to_string();
+        if let Some((group, _)) = group_names_to_ids.iter().find(|(_, &id)| id == group_id) {
+            written.push((group.clone(), name));
+        }
+        let offset = processor.u16([bytes[next_index], bytes[next_index + 1]]) as usize;
+        if offset == 0 {
+            break;
+        }
+        i = next_index + offset;
+    }
+    written
+}
+
+/// Formats the values of a parameter as a JSON array, with one string per row
+/// of a character parameter and `null` for numbers that are not finite.
+fn json_values(parameter: &Parameter) -> String {
+    let values: Vec<String> = match &parameter.data {
+        ParameterData::Char(chars) => {
+            let strings: Vec<String> = Vec::try_from(parameter)
+                .unwrap_or_else(|_| vec![chars.iter().collect::<String>().trim().to_string()]);
+            strings.iter().map(|string| json_string(string)).collect()
+        }
+        ParameterData::Byte(bytes) => bytes.iter().map(|x| x.to_string()).collect(),
+        ParameterData::Integer(integers) => integers.iter().map(|x| x.to_string()).collect(),
+        ParameterData::Float(floats) => floats
+            .iter()
+            .map(|x| match x.is_finite() {
+                true => x.to_string(),
+                false => "null".to_string(),
+            })
+            .collect(),
+    };
+    format!("[{}]", values.join(","))
+}
+
+/// Quotes a string for JSON, escaping quotes, backslashes and control characters.
+fn json_string(string: &str) -> String {
+    let mut escaped = String::from('"');
+    for c in string.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Returns the position in samples of a time in seconds after the first sample,
+/// clamped to the stored samples, or `None` if the rate is not positive or the time
+/// is more than half a sample outside them.
+fn sample_position(time: f64, rate: f64, len: usize) -> Option<f64> {
+    if !rate.is_finite() || rate <= 0. || len == 0 {
+        return None;
+    }
+    let position = time * rate;
+    match position >= -0.5 && position <= len as f64 - 0.5 {
+        true => Some(position.clamp(0., (len - 1) as f64)),
+        false => None,
+    }
+}
+
+/// Narrows a parameter holding one entry per subject down to the entry of one subject.
+/// Returns `None` if the parameter does not hold one entry per subject.
+fn select_subject(parameter: &Parameter, subject: usize, num_subjects: usize) -> Option<Parameter> {
+    let (&last, rest) = parameter.dimensions.split_last()?;
+    let per_subject = match parameter.data {
+        ParameterData::Char(_) => !rest.is_empty(),
+        _ => true,
+    };
+    if !per_subject || last as usize != num_subjects {
+        return None;
+    }
+    let stride = rest.iter().map(|&x| x as usize).product::<usize>();
+    let range = subject * stride..(subject + 1) * stride;
+    let mut selected = parameter.clone();
+    selected.data = match &parameter.data {
+        ParameterData::Char(data) => ParameterData::Char(data.get(range)?.to_vec()),
+        ParameterData::Byte(data) => ParameterData::Byte(data.get(range)?.to_vec()),
+        ParameterData::Integer(data) => ParameterData::Integer(data.get(range)?.to_vec()),
+        ParameterData::Float(data) => ParameterData::Float(data.get(range)?.to_vec()),
+    };
+    selected.dimensions = match rest.is_empty() {
+        true => vec![1],
+        false => rest.to_vec(),
+    };
+    Some(selected)
+}
+
+/// Returns the lab axis closest to a rotated screen axis such as `+X`.
+/// Axes that are not X, Y or Z are returned unchanged.
+fn rotate_screen_axis(rotation: &[[f32; 3]; 3], axis: [char; 2]) -> [char; 2] {
+    match ScreenAxis::from_chars(axis) {
+        Some(axis) => axis.rotated(rotation).to_chars(),
+        None => axis,
+    }
 }
 
-fn read_header_bytes(file: &mut File) -> Result<[u8; 512], C3dParseError> {
+fn read_header_bytes<R: Read>(file: &mut R) -> Result<[u8; 512], C3dParseError> {
     let mut header_bytes = [0u8; 512];
     file.read_exact(&mut header_bytes)
         .map_err(|e| C3dParseError::ReadError(e))?;
     Ok(header_bytes)
 }
 
-fn read_parameter_bytes(
-    file: &mut File,
+/// Places an error found in the parameter section in the file, moving the offsets of
+/// the parameters from the start of the section to the start of the file.
+fn in_parameter_section(error: C3dParseError, header_bytes: &[u8; 512]) -> C3dParseError {
+    let start = (header_bytes[0] as u64).saturating_sub(1) * 512;
+    error
+        .offset_by(start)
+        .context(ErrorContext::new("the parameter section").at(start))
+}
+
+/// Returns the number of parameter blocks as stored in the third byte of the section.
+/// Sections of more blocks than the byte holds store 255, which readers take as a lower
+/// bound, finding where the data starts in the header.
+fn block_count_byte(blocks: usize) -> u8 {
+    blocks.min(u8::MAX as usize) as u8
+}
+
+fn read_parameter_bytes<R: Read + Seek>(
+    c3d: &mut C3d,
+    file: &mut R,
     header_bytes: &[u8; 512],
 ) -> Result<(Processor, Vec<u8>, usize), C3dParseError> {
     let parameter_start_block_index = header_bytes[0] as usize;
+    if parameter_start_block_index < 2 {
+        return Err(C3dParseError::InvalidParameterStartBlock);
+    }
 
     let blocks_to_skip = parameter_start_block_index - 2;
     file.seek(SeekFrom::Current((512 * blocks_to_skip) as i64))
@@ -514,18 +3900,23 @@ fn read_parameter_bytes(
     file.read_exact(&mut parameter_start_block)
         .map_err(|e| C3dParseError::ReadError(e))?;
 
-    let processor = Processor::from_parameter_start_block(parameter_start_block)?;
+    let processor = c3d.processor_from_block(parameter_start_block)?;
     let data_start_block_index = processor.u16([header_bytes[16], header_bytes[17]]) as usize;
+    if data_start_block_index <= parameter_start_block_index {
+        return Err(C3dParseError::InvalidDataStartBlock);
+    }
 
-    let mut parameter_bytes_tail =
-        Vec::with_capacity((data_start_block_index - parameter_start_block_index - 1) * 512)
-            as Vec<u8>;
-
-    for _ in 0..(data_start_block_index - parameter_start_block_index - 1) {
-        let mut block = [0u8; 512];
-        file.read_exact(&mut block)
-            .map_err(|e| C3dParseError::ReadError(e))?;
-        parameter_bytes_tail.extend(block.iter());
+    // the blocks are read as they come, so a short file fails without allocating them all
+    let tail_length = (data_start_block_index - parameter_start_block_index - 1) * 512;
+    c3d.options.limits.check_allocation(tail_length + 512)?;
+    let mut parameter_bytes_tail = Vec::new();
+    file.take(tail_length as u64)
+        .read_to_end(&mut parameter_bytes_tail)
+        .map_err(|e| C3dParseError::ReadError(e))?;
+    if parameter_bytes_tail.len() < tail_length {
+        return Err(C3dParseError::ReadError(std::io::Error::from(
+            std::io::ErrorKind::UnexpectedEof,
+        )));
     }
 
     let parameter_bytes = [
@@ -537,10 +3928,42 @@ fn read_parameter_bytes(
     Ok((processor, parameter_bytes, data_start_block_index))
 }
 
-fn read_data_bytes(mut file: File) -> Result<Vec<u8>, C3dParseError> {
-    let mut data: Vec<u8> = Vec::new();
+/// Reads the rest of a file in chunks, reporting the bytes read so far out of `total`,
+/// counting from `done`.
+fn read_with_progress<R: Read, F>(
+    reader: &mut R,
+    mut done: u64,
+    total: u64,
+    progress: &mut F,
+) -> Result<Vec<u8>, C3dParseError>
+where
+    F: FnMut(Progress) -> ControlFlow<()>,
+{
+    let mut bytes = Vec::with_capacity(total.saturating_sub(done) as usize);
+    let mut chunk = vec![0u8; CHUNK_SIZE];
+    loop {
+        let read = match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(C3dParseError::ReadError(e)),
+        };
+        bytes.extend_from_slice(&chunk[..read]);
+        done += read as u64;
+        if !report(progress, ProgressStage::Reading, done, total) {
+            return Err(C3dParseError::Cancelled);
+        }
+    }
+    Ok(bytes)
+}
 
-    file.read_to_end(&mut data)
+/// Reads the rest of a file, failing as soon as it holds more bytes than the limits allow.
+fn read_data_bytes<R: Read>(file: R, limits: &ParseLimits) -> Result<Vec<u8>, C3dParseError> {
+    let mut data: Vec<u8> = Vec::new();
+    let max_bytes = limits.max_allocation.map_or(u64::MAX, |max| max as u64 + 1);
+    file.take(max_bytes)
+        .read_to_end(&mut data)
         .map_err(|e| C3dParseError::ReadError(e))?;
+    limits.check_allocation(data.len())?;
     Ok(data)
 }
@@ -1,11 +1,15 @@
 //! Includes data structures and functions for parsing and storing data from a C3D file.
+use crate::c3d::C3d;
 use crate::C3dParseError;
-use std::ops::{Deref, DerefMut};
+use grid::Grid;
+use std::io::{ErrorKind, Read};
+use std::ops::{ControlFlow, Deref, DerefMut, Range};
 
 /// DataFormat is the format of the data in the file.
 /// Floating point data is larger than integer data, but the loss of precision
 /// in integer data may be acceptable for some applications.
 #[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DataFormat {
     #[default]
     Float,
@@ -33,10 +37,126 @@ pub(crate) fn get_analog_bytes_per_frame(
     Ok(bytes_per_analog_point * analog_samples_per_frame as usize)
 }
 
+/// Decodes `chunks` runs of `chunk_len` values, such as the values of each frame,
+/// filling each run from its index with `decode`.
+/// With the `rayon` feature the runs are decoded in parallel.
+pub(crate) fn decode_chunks<T, F>(chunks: usize, chunk_len: usize, decode: F) -> Vec<T>
+where
+    T: Clone + Default + Send,
+    F: Fn(usize, &mut [T]) + Sync,
+{
+    if chunk_len == 0 {
+        return Vec::new();
+    }
+    let mut values = vec![T::default(); chunks * chunk_len];
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        values
+            .par_chunks_mut(chunk_len)
+            .enumerate()
+            .for_each(|(chunk, values)| decode(chunk, values));
+    }
+    #[cfg(not(feature = "rayon"))]
+    values
+        .chunks_mut(chunk_len)
+        .enumerate()
+        .for_each(|(chunk, values)| decode(chunk, values));
+    values
+}
+
+/// How a label given by the caller is compared with the labels stored in a file.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LabelMatch {
+    /// The labels must be identical, including any padding.
+    Exact,
+    /// Leading and trailing whitespace is ignored, as labels are padded with spaces in the file.
+    #[default]
+    Trimmed,
+    /// Whitespace is ignored as for `Trimmed`, and so is the case of the labels.
+    IgnoreCase,
+}
+
+impl LabelMatch {
+    /// Returns whether a stored label matches the one given.
+    pub fn matches(&self, stored: &str, label: &str) -> bool {
+        match self {
+            LabelMatch::Exact => stored == label,
+            LabelMatch::Trimmed => stored.trim() == label.trim(),
+            LabelMatch::IgnoreCase => stored.trim().eq_ignore_ascii_case(label.trim()),
+        }
+    }
+
+    /// Returns the first of the stored columns whose label matches the one given.
+    pub(crate) fn find(&self, labels: &[String], stored: usize, label: &str) -> Option<usize> {
+        labels
+            .iter()
+            .take(stored)
+            .position(|stored| self.matches(stored, label))
+    }
+}
+
+/// Returns the stored columns whose label is in the selection, in file order.
+pub(crate) fn selected_columns(
+    labels: &[String],
+    stored: usize,
+    selection: &[String],
+) -> Vec<usize> {
+    (0..stored)
+        .filter(|&column| {
+            labels.get(column).is_some_and(|label| {
+                selection
+                    .iter()
+                    .any(|selected| selected.trim() == label.trim())
+            })
+        })
+        .collect()
+}
+
+/// Keeps the elements of a per-column parameter at the given columns.
+/// Columns without an element are dropped, as parameters may be shorter than the data.
+pub(crate) fn select_columns<T: Clone>(values: &[T], columns: &[usize]) -> Vec<T> {
+    columns
+        .iter()
+        .filter_map(|&column| values.get(column).cloned())
+        .collect()
+}
+
+/// Keeps the given rows of a grid, e.g. a range of frames.
+pub(crate) fn select_rows<T: Clone + Default>(grid: &Grid<T>, rows: Range<usize>) -> Grid<T> {
+    let cols = grid.cols();
+    match cols {
+        0 => Grid::new(0, 0),
+        _ => Grid::from_vec(
+            grid.flatten()[rows.start * cols..rows.end * cols].to_vec(),
+            cols,
+        ),
+    }
+}
+
+/// Stacks the rows of grids with the same number of columns.
+pub(crate) fn concat_rows<T: Clone + Default>(grids: &[&Grid<T>]) -> Grid<T> {
+    let cols = grids.first().map_or(0, |grid| grid.cols());
+    match cols {
+        0 => Grid::new(0, 0),
+        _ => Grid::from_vec(
+            grids
+                .iter()
+                .flat_map(|grid| grid.flatten().iter().cloned())
+                .collect(),
+            cols,
+        ),
+    }
+}
+
 /// MarkerPoint contains both the points and residuals for a marker.
-/// The residuals are the average distance between the marker and the reconstructed point.
+/// The residuals are the average distance between the marker and the reconstructed point,
+/// in the same units as the point for both integer and float storage.
+/// Invalid points have a residual of -1.
 /// Cameras is a bitfield of which cameras saw the marker.
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MarkerPoint {
     pub point: [f32; 3],
     pub residual: f32,
@@ -78,6 +198,24 @@ impl MarkerPoint {
         *self
     }
 
+    /// A negative residual marks the point as invalid, e.g. when the marker
+    /// was not seen by enough cameras to be reconstructed.
+    pub fn is_valid(&self) -> bool {
+        self.residual >= 0.0
+    }
+
+    /// Returns the residual, or `None` if the point is invalid.
+    pub fn valid_residual(&self) -> Option<f32> {
+        self.is_valid().then_some(self.residual)
+    }
+
+    /// Returns the cameras that saw the marker, counted from 0 as in the camera mask.
+    pub fn cameras_seen(&self) -> Vec<usize> {
+        (0..self.cameras.len())
+            .filter(|&camera| self.cameras[camera])
+            .collect()
+    }
+
     /// used for writing to file
     pub(crate) fn cameras_as_byte(&self) -> u8 {
         let mut cameras_byte = 0;
@@ -89,3 +227,215 @@ impl MarkerPoint {
         cameras_byte
     }
 }
+
+/// The data of a single frame, as decoded by `FrameIter`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Frame {
+    /// Index of the frame from the start of the data section.
+    pub index: usize,
+    pub points: Vec<MarkerPoint>,
+    /// One row per analog sample in the frame and one column per channel.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::grid"))]
+    pub analog: Grid<f64>,
+}
+
+/// A consumer of decoded frames, driven by `FrameIter::drive` and `C3d::stream_frames`
+/// as the data section is read, e.g. to compute statistics, send the frames over a
+/// socket or convert them to another format without storing them in a `C3d`.
+/// Closures taking the arguments of `on_frame` are sinks.
+///
+/// # Examples
+/// ```
+/// use c3dio::prelude::*;
+/// use std::ops::ControlFlow;
+///
+/// let mut visible = 0;
+/// let frames = C3d::stream_frames("tests/data/short.c3d".into(), &mut |points: &[MarkerPoint], _: &[f64], _| {
+///     visible += points.iter().filter(|x| x.is_valid()).count();
+///     ControlFlow::Continue(())
+/// })
+/// .unwrap();
+/// assert!(frames > 0 && visible > 0);
+/// ```
+pub trait FrameSink {
+    /// Called once before the first frame with the parsed header and parameters,
+    /// e.g. to read the labels or rates. Its point and analog data hold the first frame.
+    fn on_start(&mut self, _c3d: &C3d) {}
+
+    /// Called for every frame in order with the points in the order of their labels and
+    /// the analog samples of the frame, `samples_per_channel_per_frame` rows of
+    /// channels as `Analog::frame` gives them. The index counts frames from the start of
+    /// the data section. Returning `ControlFlow::Break(())` stops decoding.
+    fn on_frame(&mut self, points: &[MarkerPoint], analog: &[f64], index: usize)
+        -> ControlFlow<()>;
+}
+
+impl<F> FrameSink for F
+where
+    F: FnMut(&[MarkerPoint], &[f64], usize) -> ControlFlow<()>,
+{
+    fn on_frame(
+        &mut self,
+        points: &[MarkerPoint],
+        analog: &[f64],
+        index: usize,
+    ) -> ControlFlow<()> {
+        self(points, analog, index)
+    }
+}
+
+/// Iterator decoding the data section of a C3D file one frame at a time.
+/// Only a single frame is held in memory, so files larger than the available
+/// memory can be scanned sequentially. Created by `C3d::frames`.
+/// Iteration stops early if the data section is shorter than declared.
+pub struct FrameIter<R: Read> {
+    c3d: C3d,
+    reader: R,
+    bytes_per_frame: usize,
+    num_frames: usize,
+    frame: usize,
+}
+
+impl<R: Read> FrameIter<R> {
+    /// Creates the iterator from a file with parsed header and parameters
+    /// and a reader positioned at the start of the data section.
+    /// The first frame is decoded together with the point and analog parameters.
+    pub(crate) fn new(c3d: C3d, mut reader: R) -> Result<FrameIter<R>, C3dParseError> {
+        let bytes_per_frame =
+            get_point_bytes_per_frame(&c3d.points.format, c3d.points.stored_points())
+                + get_analog_bytes_per_frame(&c3d.points.format, c3d.analog.samples_per_frame)?;
+        let mut first_frame = Vec::with_capacity(bytes_per_frame);
+        (&mut reader)
+            .take(bytes_per_frame as u64)
+            .read_to_end(&mut first_frame)
+            .map_err(C3dParseError::ReadError)?;
+        let complete = first_frame.len() == bytes_per_frame;
+        let c3d = c3d.parse_data_bytes(first_frame, false)?;
+        let declared_frames = c3d.points.declared_frames();
+        let num_frames = match complete {
+            // a file declaring only the 65535 frames the header can hold is read to its end,
+            // unless its frames are empty and the end would never be reached
+            true if bytes_per_frame > 0 && c3d.points.frame_count_saturated(declared_frames) => {
+                usize::MAX
+            }
+            true => declared_frames,
+            false => 0,
+        };
+        Ok(FrameIter {
+            c3d,
+            reader,
+            bytes_per_frame,
+            num_frames,
+            frame: 0,
+        })
+    }
+
+    /// The parsed header and parameters of the file.
+    /// Its point and analog data only hold the most recently decoded frame.
+    pub fn c3d(&self) -> &C3d {
+        &self.c3d
+    }
+
+    /// Returns the number of frames declared by the file,
+    /// which is the most frames the iterator yields.
+    /// This is `usize::MAX` if the header and POINT:FRAMES are stuck at 65535 and the
+    /// frames hold any samples, as the iterator then reads every frame up to the end
+    /// of the file.
+    pub fn num_frames(&self) -> usize {
+        self.num_frames
+    }
+
+    /// Decodes the samples of every remaining frame into a sink, without copying them
+    /// out of the iterator, and returns the number of frames given to the sink.
+    /// The sink is given the parsed header and parameters first. Decoding stops early,
+    /// without an error, when the sink returns `ControlFlow::Break(())`.
+    pub fn drive<S: FrameSink + ?Sized>(&mut self, sink: &mut S) -> Result<usize, C3dParseError> {
+        sink.on_start(&self.c3d);
+        let mut frames = 0;
+        while self.frame < self.num_frames {
+            match self.read_frame() {
+                Ok(true) => {}
+                Ok(false) => {
+                    self.frame = self.num_frames;
+                    break;
+                }
+                Err(e) => {
+                    self.frame = self.num_frames;
+                    return Err(e);
+                }
+            }
+            let index = self.frame;
+            self.frame += 1;
+            frames += 1;
+            let (points, analog) = (self.c3d.points.flatten(), self.c3d.analog.as_slice());
+            if sink.on_frame(points, analog, index).is_break() {
+                break;
+            }
+        }
+        Ok(frames)
+    }
+
+    /// Decodes the next frame into the points and analog data of `c3d`, which already
+    /// hold the first frame. Returns `false` at the end of the data section.
+    fn read_frame(&mut self) -> Result<bool, C3dParseError> {
+        if self.frame > 0 {
+            let mut bytes = vec![0u8; self.bytes_per_frame];
+            match self.reader.read_exact(&mut bytes) {
+                Ok(()) => {}
+                Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(false),
+                Err(e) => return Err(C3dParseError::ReadError(e)),
+            }
+            let processor = self.c3d.processor();
+            let format = self.c3d.points.format.clone();
+            let points_per_frame = self.c3d.points.stored_points();
+            let analog_used = self.c3d.analog.cols() as u16;
+            self.c3d.points.parse_points(
+                &bytes,
+                &processor,
+                self.c3d.analog.samples_per_frame,
+                1,
+            )?;
+            self.c3d.analog.parse_analog(
+                &bytes,
+                &processor,
+                1,
+                &format,
+                points_per_frame,
+                analog_used,
+            )?;
+        }
+        Ok(true)
+    }
+
+    fn decode_frame(&mut self) -> Result<Option<Frame>, C3dParseError> {
+        if !self.read_frame()? {
+            return Ok(None);
+        }
+        let points = match self.c3d.points.rows() {
+            0 => Vec::new(),
+            _ => self.c3d.points.iter_row(0).cloned().collect(),
+        };
+        Ok(Some(Frame {
+            index: self.frame,
+            points,
+            analog: self.c3d.analog.analog.clone(),
+        }))
+    }
+}
+
+impl<R: Read> Iterator for FrameIter<R> {
+    type Item = Result<Frame, C3dParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.frame >= self.num_frames {
+            return None;
+        }
+        let frame = self.decode_frame();
+        match frame {
+            Ok(Some(_)) => self.frame += 1,
+            _ => self.frame = self.num_frames,
+        }
+        frame.transpose()
+    }
+}
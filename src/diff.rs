@@ -0,0 +1,443 @@
+//! Compares two C3D files by their contents rather than their bytes, e.g. to check the
+//! output of a processing pipeline or a round trip between processor formats.
+use crate::data::MarkerPoint;
+use crate::events::Event;
+use crate::parameters::{Parameter, ParameterData, Parameters};
+use std::fmt;
+
+/// The tolerances used by `C3d::diff_with_options` to compare floating point values.
+/// Values closer than the tolerance are considered equal.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiffOptions {
+    /// The largest distance between the positions of a point, in the units of the points.
+    pub point_tolerance: f32,
+    /// The largest difference between analog samples, in the units of the channel.
+    pub analog_tolerance: f64,
+    /// The largest difference between the values of a floating point parameter.
+    pub parameter_tolerance: f32,
+    /// The largest difference between the times of an event, in seconds.
+    pub time_tolerance: f32,
+    /// Values further apart than the tolerances are still equal if they are within
+    /// this many steps between single precision floats of each other, as for `approx_eq`.
+    pub max_ulps: Option<u32>,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions {
+            point_tolerance: 1e-4,
+            analog_tolerance: 1e-6,
+            parameter_tolerance: 1e-6,
+            time_tolerance: 1e-5,
+            max_ulps: None,
+        }
+    }
+}
+
+impl DiffOptions {
+    /// Returns the default tolerances, also accepting values up to four single precision
+    /// steps apart, for files converted between DEC and IEEE floats by other software.
+    pub fn processor_conversion() -> Self {
+        DiffOptions {
+            max_ulps: Some(4),
+            ..DiffOptions::default()
+        }
+    }
+}
+
+/// The smallest normal IEEE float. A conversion between DEC and IEEE floats flushes
+/// smaller values to zero if they are too small for a DEC float, and rounds the others
+/// to fewer bits as IEEE subnormals.
+const MIN_NORMAL: f64 = f32::MIN_POSITIVE as f64;
+
+/// Returns whether two values are at most `max_ulps` apart in steps between the single
+/// precision floats of their magnitude, the precision C3D files store floats in.
+/// Values smaller than the smallest normal IEEE float are equal, as are two NaNs.
+///
+/// # Examples
+/// ```
+/// use c3dio::diff::approx_eq;
+///
+/// let step = f32::from_bits(1000_f32.to_bits() + 1) - 1000.;
+/// assert!(approx_eq(1000., 1000. + step as f64, 1));
+/// assert!(!approx_eq(1000., 1000. + 2. * step as f64, 1));
+/// assert!(approx_eq(1e-39, 0., 0));
+/// ```
+pub fn approx_eq(a: f64, b: f64, max_ulps: u32) -> bool {
+    if a == b || (a.is_nan() && b.is_nan()) {
+        return true;
+    }
+    let magnitude = a.abs().max(b.abs());
+    if magnitude < MIN_NORMAL {
+        return true;
+    }
+    if magnitude.is_infinite() {
+        return false;
+    }
+    let step = (magnitude.log2().floor() - 23.).exp2();
+    (a - b).abs() <= max_ulps as f64 * step
+}
+
+/// Returns whether two values are within the tolerance, or the steps of `max_ulps`.
+fn within(a: f64, b: f64, tolerance: f64, max_ulps: Option<u32>) -> bool {
+    a == b
+        || (a.is_nan() && b.is_nan())
+        || (a - b).abs() <= tolerance
+        || max_ulps.is_some_and(|max_ulps| approx_eq(a, b, max_ulps))
+}
+
+/// Which of the two compared files something was found in,
+/// `Left` being the file `diff` is called on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DiffSide {
+    Left,
+    Right,
+}
+
+/// A single difference between two files found by `C3d::diff`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Difference {
+    /// A property of the whole file differs, e.g. the processor or the number of frames.
+    Header {
+        field: String,
+        left: String,
+        right: String,
+    },
+    /// A parameter the file is written with is only in one of the files.
+    MissingParameter {
+        group: String,
+        parameter: String,
+        only_in: DiffSide,
+    },
+    /// A parameter differs in type, dimensions or values.
+    Parameter {
+        group: String,
+        parameter: String,
+        left: Parameter,
+        right: Parameter,
+    },
+    /// An event has no event with the same label, context and subject at the same time
+    /// in the other file.
+    MissingEvent { event: Event, only_in: DiffSide },
+    /// A point label is only in one of the files.
+    MissingPoint { label: String, only_in: DiffSide },
+    /// A point is further apart than the tolerance in some of the frames both files have,
+    /// or is missing in only one of them.
+    /// `max_difference` is the largest distance in the frames where it is present in both.
+    Point {
+        label: String,
+        frames: usize,
+        first_frame: usize,
+        max_difference: f32,
+    },
+    /// An analog channel label is only in one of the files.
+    MissingAnalog { label: String, only_in: DiffSide },
+    /// Samples of an analog channel differ by more than the tolerance.
+    Analog {
+        label: String,
+        samples: usize,
+        first_sample: usize,
+        max_difference: f64,
+    },
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Difference::Header { field, left, right } => {
+                write!(f, "{}: {} != {}", field, left, right)
+            }
+            Difference::MissingParameter {
+                group,
+                parameter,
+                only_in,
+            } => write!(f, "{}:{} only in {:?}", group, parameter, only_in),
+            Difference::Parameter {
+                group,
+                parameter,
+                left,
+                right,
+            } => write!(
+                f,
+                "{}:{}: {:?} != {:?}",
+                group, parameter, left.data, right.data
+            ),
+            Difference::MissingEvent { event, only_in } => write!(
+                f,
+                "event {} {} at {}s only in {:?}",
+                event.context, event.label, event.time, only_in
+            ),
+            Difference::MissingPoint { label, only_in } => {
+                write!(f, "point {} only in {:?}", label, only_in)
+            }
+            Difference::Point {
+                label,
+                frames,
+                first_frame,
+                max_difference,
+            } => write!(
+                f,
+                "point {} differs in {} frames from frame {}, by up to {}",
+                label, frames, first_frame, max_difference
+            ),
+            Difference::MissingAnalog { label, only_in } => {
+                write!(f, "analog channel {} only in {:?}", label, only_in)
+            }
+            Difference::Analog {
+                label,
+                samples,
+                first_sample,
+                max_difference,
+            } => write!(
+                f,
+                "analog channel {} differs in {} samples from sample {}, by up to {}",
+                label, samples, first_sample, max_difference
+            ),
+        }
+    }
+}
+
+/// The differences between two files found by `C3d::diff`, in the order of the
+/// header, the parameters, the events, the points and the analog channels.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct C3dDiff {
+    pub differences: Vec<Difference>,
+}
+
+impl C3dDiff {
+    /// Returns true if the files are equal within the tolerances.
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty()
+    }
+
+    pub(crate) fn header<T: fmt::Debug + PartialEq>(&mut self, field: &str, left: T, right: T) {
+        if left != right {
+            self.differences.push(Difference::Header {
+                field: field.to_string(),
+                left: format!("{:?}", left),
+                right: format!("{:?}", right),
+            });
+        }
+    }
+
+    /// Compares the parameters two files are written with, leaving out the events,
+    /// which are compared by `events`, and where the data starts, which depends on
+    /// the size of the parameter section.
+    pub(crate) fn parameters(
+        &mut self,
+        left: &Parameters,
+        right: &Parameters,
+        tolerance: f32,
+        max_ulps: Option<u32>,
+    ) {
+        let skipped = |group: &str, parameter: &str| {
+            group == "EVENT" || (group == "POINT" && parameter == "DATA_START")
+        };
+        let mut keys = Vec::new();
+        for (parameters, side) in [(left, DiffSide::Left), (right, DiffSide::Right)] {
+            for group in parameters.groups() {
+                for parameter in parameters.parameters(group).unwrap_or_default() {
+                    keys.push((group.clone(), parameter.name.clone(), side));
+                }
+            }
+        }
+        keys.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+        keys.dedup_by(|a, b| a.0 == b.0 && a.1 == b.1);
+        for (group, parameter, side) in keys {
+            if skipped(&group, &parameter) {
+                continue;
+            }
+            match (left.get(&group, &parameter), right.get(&group, &parameter)) {
+                (Some(left), Some(right)) => {
+                    if !parameters_match(left, right, tolerance, max_ulps) {
+                        self.differences.push(Difference::Parameter {
+                            group,
+                            parameter,
+                            left: left.clone(),
+                            right: right.clone(),
+                        });
+                    }
+                }
+                _ => self.differences.push(Difference::MissingParameter {
+                    group,
+                    parameter,
+                    only_in: side,
+                }),
+            }
+        }
+    }
+
+    /// Pairs each event with an unpaired event of the other file with the same label,
+    /// context and subject, within the time tolerance.
+    pub(crate) fn events(
+        &mut self,
+        left: &[Event],
+        right: &[Event],
+        tolerance: f32,
+        max_ulps: Option<u32>,
+    ) {
+        let mut paired = vec![false; right.len()];
+        let mut missing = Vec::new();
+        for event in left {
+            let pair = right.iter().enumerate().position(|(i, other)| {
+                !paired[i]
+                    && other.label == event.label
+                    && other.context == event.context
+                    && other.subject == event.subject
+                    && within(
+                        other.time as f64,
+                        event.time as f64,
+                        tolerance as f64,
+                        max_ulps,
+                    )
+            });
+            match pair {
+                Some(i) => paired[i] = true,
+                None => missing.push((event, DiffSide::Left)),
+            }
+        }
+        for (event, paired) in right.iter().zip(paired) {
+            if !paired {
+                missing.push((event, DiffSide::Right));
+            }
+        }
+        for (event, only_in) in missing {
+            self.differences.push(Difference::MissingEvent {
+                event: event.clone(),
+                only_in,
+            });
+        }
+    }
+
+    /// Compares the frames both files have of a point found in both.
+    pub(crate) fn point(
+        &mut self,
+        label: &str,
+        left: impl Iterator<Item = MarkerPoint>,
+        right: impl Iterator<Item = MarkerPoint>,
+        tolerance: f32,
+        max_ulps: Option<u32>,
+    ) {
+        let mut frames = 0;
+        let mut first_frame = 0;
+        let mut max_difference: f32 = 0.;
+        for (frame, (left, right)) in left.zip(right).enumerate() {
+            let differs = match (left.is_valid(), right.is_valid()) {
+                (true, true) => {
+                    let distance = left
+                        .point
+                        .iter()
+                        .zip(right.point.iter())
+                        .map(|(a, b)| (a - b) * (a - b))
+                        .sum::<f32>()
+                        .sqrt();
+                    max_difference = max_difference.max(distance);
+                    distance > tolerance
+                        && !max_ulps.is_some_and(|max_ulps| {
+                            left.point
+                                .iter()
+                                .zip(right.point.iter())
+                                .all(|(&a, &b)| approx_eq(a as f64, b as f64, max_ulps))
+                        })
+                }
+                (false, false) => false,
+                _ => true,
+            };
+            if differs {
+                if frames == 0 {
+                    first_frame = frame;
+                }
+                frames += 1;
+            }
+        }
+        if frames > 0 {
+            self.differences.push(Difference::Point {
+                label: label.to_string(),
+                frames,
+                first_frame,
+                max_difference,
+            });
+        }
+    }
+
+    /// Compares the samples both files have of an analog channel found in both.
+    pub(crate) fn analog(
+        &mut self,
+        label: &str,
+        left: impl Iterator<Item = f64>,
+        right: impl Iterator<Item = f64>,
+        tolerance: f64,
+        max_ulps: Option<u32>,
+    ) {
+        let mut samples = 0;
+        let mut first_sample = 0;
+        let mut max_difference: f64 = 0.;
+        for (sample, (left, right)) in left.zip(right).enumerate() {
+            let difference = (left - right).abs();
+            if !within(left, right, tolerance, max_ulps) {
+                if samples == 0 {
+                    first_sample = sample;
+                }
+                samples += 1;
+                max_difference = max_difference.max(difference);
+            }
+        }
+        if samples > 0 {
+            self.differences.push(Difference::Analog {
+                label: label.to_string(),
+                samples,
+                first_sample,
+                max_difference,
+            });
+        }
+    }
+}
+
+fn parameters_match(
+    left: &Parameter,
+    right: &Parameter,
+    tolerance: f32,
+    max_ulps: Option<u32>,
+) -> bool {
+    if left.dimensions != right.dimensions {
+        return false;
+    }
+    match (&left.data, &right.data) {
+        (ParameterData::Float(left), ParameterData::Float(right)) => {
+            left.len() == right.len()
+                && left
+                    .iter()
+                    .zip(right)
+                    .all(|(&a, &b)| within(a as f64, b as f64, tolerance as f64, max_ulps))
+        }
+        (left, right) => left == right,
+    }
+}
+
+/// Pairs the columns of two files by label, the n-th column with a label in one file
+/// with the n-th column with the same label in the other.
+/// Returns the pairs and the columns of each file without a pair, in column order.
+pub(crate) fn pair_columns(
+    left: &[String],
+    right: &[String],
+) -> (Vec<(usize, usize)>, Vec<usize>, Vec<usize>) {
+    let mut paired = vec![false; right.len()];
+    let mut pairs = Vec::new();
+    let mut left_only = Vec::new();
+    for (i, label) in left.iter().enumerate() {
+        let label = label.trim();
+        match (0..right.len()).find(|&j| !paired[j] && right[j].trim() == label) {
+            Some(j) => {
+                paired[j] = true;
+                pairs.push((i, j));
+            }
+            None => left_only.push(i),
+        }
+    }
+    let right_only = (0..right.len()).filter(|&j| !paired[j]).collect();
+    (pairs, left_only, right_only)
+}
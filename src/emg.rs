@@ -0,0 +1,112 @@
+//! Processing of EMG recorded in analog channels, from raw signal to envelope.
+use crate::filters::Butterworth;
+use crate::C3dFilterError;
+
+/// How an EMG signal is smoothed after it is rectified.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EmgEnvelope {
+    /// The rectified signal, without smoothing.
+    Rectified,
+    /// A linear envelope, low-pass filtering the rectified signal at the given
+    /// cutoff in Hz with a second order Butterworth filter.
+    Linear(f64),
+    /// The root mean square over a moving window of the given length in seconds,
+    /// centred on each sample.
+    Rms(f64),
+}
+
+/// The steps applied to an EMG channel by `C3d::emg_envelope`, in order:
+/// offset removal, band-pass filtering, full-wave rectification and smoothing.
+/// The default removes the offset, band-passes between 20 and 450 Hz and
+/// takes a linear envelope at 6 Hz.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EmgProcessing {
+    pub remove_offset: bool,
+    pub band_pass: Option<Butterworth>,
+    pub envelope: EmgEnvelope,
+}
+
+impl Default for EmgProcessing {
+    fn default() -> Self {
+        EmgProcessing {
+            remove_offset: true,
+            band_pass: Some(Butterworth::band_pass(20., 450., 2)),
+            envelope: EmgEnvelope::Linear(6.),
+        }
+    }
+}
+
+impl EmgProcessing {
+    /// The same processing, with the given band-pass filter or none.
+    pub fn with_band_pass(self, band_pass: Option<Butterworth>) -> EmgProcessing {
+        EmgProcessing { band_pass, ..self }
+    }
+
+    /// The same processing, smoothing the rectified signal as given.
+    pub fn with_envelope(self, envelope: EmgEnvelope) -> EmgProcessing {
+        EmgProcessing { envelope, ..self }
+    }
+
+    /// Processes the samples of one channel, taken at the given rate.
+    pub fn process(&self, samples: &[f64], rate: f64) -> Result<Vec<f64>, C3dFilterError> {
+        let mut samples = match self.remove_offset {
+            true => remove_offset(samples),
+            false => samples.to_vec(),
+        };
+        if let Some(band_pass) = &self.band_pass {
+            samples = band_pass.filtfilt(&samples, rate)?;
+        }
+        match self.envelope {
+            EmgEnvelope::Rectified => Ok(rectify(&samples)),
+            EmgEnvelope::Linear(cutoff) => linear_envelope(&samples, rate, cutoff),
+            EmgEnvelope::Rms(window) => {
+                let window = (window * rate).round().max(1.) as usize;
+                Ok(moving_rms(&samples, window))
+            }
+        }
+    }
+}
+
+/// Subtracts the mean of the samples from each sample.
+pub fn remove_offset(samples: &[f64]) -> Vec<f64> {
+    let mean = samples.iter().sum::<f64>() / samples.len().max(1) as f64;
+    samples.iter().map(|sample| sample - mean).collect()
+}
+
+/// Returns the absolute value of each sample.
+pub fn rectify(samples: &[f64]) -> Vec<f64> {
+    samples.iter().map(|sample| sample.abs()).collect()
+}
+
+/// Rectifies the samples and low-pass filters them at the given cutoff in Hz
+/// with a second order zero-lag Butterworth filter.
+pub fn linear_envelope(
+    samples: &[f64],
+    rate: f64,
+    cutoff: f64,
+) -> Result<Vec<f64>, C3dFilterError> {
+    Butterworth::low_pass(cutoff, 2).filtfilt(&rectify(samples), rate)
+}
+
+/// Returns the root mean square of the samples over a window of the given number
+/// of samples centred on each sample, with the extra sample of an even window after it.
+/// The window is cut short at the ends.
+pub fn moving_rms(samples: &[f64], window: usize) -> Vec<f64> {
+    let mut sums = Vec::with_capacity(samples.len() + 1);
+    sums.push(0.);
+    for sample in samples {
+        sums.push(sums[sums.len() - 1] + sample * sample);
+    }
+    let before = window.saturating_sub(1) / 2;
+    let after = window.max(1) - 1 - before;
+    (0..samples.len())
+        .map(|i| {
+            let (start, end) = (i.saturating_sub(before), (i + after + 1).min(samples.len()));
+            ((sums[end] - sums[start]) / (end - start) as f64)
+                .max(0.)
+                .sqrt()
+        })
+        .collect()
+}
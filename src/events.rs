@@ -4,6 +4,9 @@ use crate::processor::Processor;
 use crate::{C3dWriteError, C3dParseError};
 use grid::Grid;
 use std::collections::HashMap;
+
+pub mod detect;
+
 /// # Events
 ///
 /// Events are time points in the C3D file that are marked with a label.
@@ -18,6 +21,7 @@ use std::collections::HashMap;
 use std::ops::{Deref, DerefMut};
 
 #[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EventContext {
     pub used: Option<i16>,
     pub icon_ids: Option<Vec<u16>>,
@@ -31,6 +35,25 @@ impl EventContext {
         EventContext::default()
     }
 
+    /// Returns the position of a context in EVENT_CONTEXT:LABELS, ignoring padding.
+    fn index(&self, context: &str) -> Option<usize> {
+        self.labels
+            .as_ref()?
+            .iter()
+            .position(|label| label.trim() == context.trim())
+    }
+
+    /// Returns the colour Vicon Nexus draws the events of a context with,
+    /// such as `Left`, from EVENT_CONTEXT:COLOURS.
+    pub fn colour(&self, context: &str) -> Option<[u8; 3]> {
+        self.colours.as_ref()?.get(self.index(context)?).copied()
+    }
+
+    /// Returns the icon of the events of a context, from EVENT_CONTEXT:ICON_IDS.
+    pub fn icon_id(&self, context: &str) -> Option<u16> {
+        self.icon_ids.as_ref()?.get(self.index(context)?).copied()
+    }
+
     pub(crate) fn from_parameters(parameters: &mut Parameters) -> Result<Self, C3dParseError> {
         let used = parameters.remove("EVENT_CONTEXT", "USED");
         let used = match used {
@@ -62,34 +85,32 @@ impl EventContext {
     }
 }
 
+/// Reads the RGB colours of a parameter, one row of three values per colour. Vicon Nexus
+/// stores them as integers, which are clamped to the range of a byte.
 fn get_colour_array(
     parameters: &mut Parameters,
     group_name: &str,
     parameter_name: &str,
 ) -> Option<Vec<[u8; 3]>> {
     let parameter = parameters.remove(group_name, parameter_name)?;
-    match &parameter.data {
-        ParameterData::Byte(data) => {
-            if parameter.dimensions.len() == 2 {
-                let mut colours = Vec::new();
-                for row in 0..data.len() % 3 {
-                    let mut colour = [0; 3];
-                    colour[0] = data[row * 3];
-                    colour[1] = data[row * 3 + 1];
-                    colour[2] = data[row * 3 + 2];
-                    colours.push(colour);
-                }
-                Some(colours)
-            } else {
-                None
-            }
-        }
-        _ => None,
+    if parameter.dimensions.len() != 2 {
+        return None;
     }
+    let data: Vec<u8> = match &parameter.data {
+        ParameterData::Byte(data) => data.clone(),
+        ParameterData::Integer(data) => data.iter().map(|&x| x.clamp(0, 255) as u8).collect(),
+        _ => return None,
+    };
+    Some(
+        data.chunks_exact(3)
+            .map(|colour| [colour[0], colour[1], colour[2]])
+            .collect(),
+    )
 }
 
 /// The `Events` struct contains the events from the C3D file header.
 #[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Events {
     pub supports_events_labels: bool,
     events: Vec<Event>,
@@ -139,6 +160,7 @@ impl DerefMut for Events {
 
 /// The `Event` struct contains the information for a single event.
 #[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Event {
     pub id: [char; 4], // found in header
     pub label: String, // found in parameter section
@@ -156,6 +178,68 @@ impl Event {
     pub fn new() -> Event {
         Event::default()
     }
+
+    /// Returns the number of the frame nearest to the event at the given point rate.
+    /// Event times are counted in seconds from frame 1, so an event at 0 s is in frame 1,
+    /// whatever the first frame of the file is. An event halfway between two frames is
+    /// in the later one.
+    /// Returns `None` if the rate is not positive or the event is before frame 1.
+    pub fn frame(&self, point_rate: f32) -> Option<usize> {
+        match Event::signed_frame(self.time, point_rate)? {
+            frame if frame >= 1 => Some(frame as usize),
+            _ => None,
+        }
+    }
+
+    /// Returns the frame number of `frame` for a time, which is 0 or negative before frame 1.
+    /// Halves are rounded up rather than away from zero, so that moving an event by whole
+    /// frames moves its frame by as many.
+    fn signed_frame(time: f32, point_rate: f32) -> Option<i64> {
+        if !point_rate.is_finite() || point_rate <= 0. {
+            return None;
+        }
+        let frame = (time as f64 * point_rate as f64 + 0.5).floor() + 1.;
+        match frame.is_finite() {
+            true => Some(frame as i64),
+            false => None,
+        }
+    }
+
+    /// Moves the event to the time of a frame number, counted as for `frame`.
+    pub fn set_frame(&mut self, frame: usize, point_rate: f32) {
+        self.time = (frame.saturating_sub(1) as f64 / point_rate as f64) as f32;
+    }
+
+    /// Moves the event to the time of the frame nearest to it, as given by `frame`.
+    /// Events without a frame are left unchanged.
+    pub fn snap_to_frame(&mut self, point_rate: f32) {
+        if let Some(frame) = self.frame(point_rate) {
+            self.set_frame(frame, point_rate);
+        }
+    }
+
+    /// Moves the event by a number of frames, keeping its position within its frame.
+    /// Where rounding the new time in single precision moves an event halfway between two
+    /// frames to the other one, it is placed on the side of the frame it was nearest to.
+    pub(crate) fn shift_frames(&mut self, frames: i64, point_rate: f32) {
+        let time = self.time;
+        self.time += frames as f32 / point_rate;
+        let Some(target) = Event::signed_frame(time, point_rate).map(|frame| frame + frames) else {
+            return;
+        };
+        if Event::signed_frame(self.time, point_rate) == Some(target) {
+            return;
+        }
+        let rate = point_rate as f64;
+        self.time = ((time as f64 * rate + frames as f64) / rate) as f32;
+        for _ in 0..4 {
+            match Event::signed_frame(self.time, point_rate) {
+                Some(frame) if frame < target => self.time = self.time.next_up(),
+                Some(frame) if frame > target => self.time = self.time.next_down(),
+                _ => break,
+            }
+        }
+    }
 }
 
 impl Events {
@@ -181,6 +265,59 @@ impl Events {
         }
     }
 
+    /// Returns the event at the specified index to be edited.
+    /// EVENT:USED and the per-event parameters are derived from the events when the file is written.
+    pub fn event_mut(&mut self, index: usize) -> Option<&mut Event> {
+        self.events.get_mut(index)
+    }
+
+    /// Adds an event at the given time in seconds, e.g. `add_event("Foot Strike", "Left", 1.2)`.
+    /// The header ID is the first four characters of the label.
+    /// Other fields, such as the icon ID or subject, can be set on the returned event.
+    pub fn add_event(&mut self, label: &str, context: &str, time: f32) -> &mut Event {
+        let mut id = [' '; 4];
+        for (i, c) in label.chars().take(4).enumerate() {
+            id[i] = c;
+        }
+        // events are only written to the header of files supporting event labels
+        self.supports_events_labels = true;
+        self.events.push(Event {
+            id,
+            label: label.to_string(),
+            display_flag: true,
+            time,
+            context: context.to_string(),
+            ..Event::default()
+        });
+        self.events.last_mut().unwrap()
+    }
+
+    /// Removes and returns the event at the specified index,
+    /// or `None` if the index is out of bounds.
+    pub fn remove_event(&mut self, index: usize) -> Option<Event> {
+        match index < self.events.len() {
+            true => Some(self.events.remove(index)),
+            false => None,
+        }
+    }
+
+    /// Returns the contexts events are grouped by, with the icon and colour of each,
+    /// from the EVENT_CONTEXT group.
+    pub fn context(&self) -> &EventContext {
+        &self.event_context
+    }
+
+    /// Returns the event contexts to be edited. They are written back to the
+    /// EVENT_CONTEXT group, with EVENT_CONTEXT:USED set from `used`.
+    pub fn context_mut(&mut self) -> &mut EventContext {
+        &mut self.event_context
+    }
+
+    /// Sorts the events by time, keeping the order of events at the same time.
+    pub fn sort_by_time(&mut self) {
+        self.events.sort_by(|a, b| a.time.total_cmp(&b.time));
+    }
+
     pub(crate) fn from_header_and_parameters(
         header_block: &[u8; 512],
         parameters: &mut Parameters,
@@ -204,10 +341,7 @@ impl Events {
         // event times start at byte 306
         for event_num in 0..num_time_events {
             let id = get_event_id(event_num, header_block)?;
-            let label = match supports_events_labels && labels.len() > event_num {
-                true => labels[event_num].clone(),
-                false => "".to_string(),
-            };
+            let label = labels.get(event_num).cloned().unwrap_or_default();
             let display_flag = match supports_events_labels {
                 true => get_display_flag(event_num, header_block),
                 false => true,
@@ -252,10 +386,10 @@ impl Events {
         let times = self
             .events
             .iter()
-            .map(|event| event.time)
+            .flat_map(|event| [0., event.time])
             .collect::<Vec<f32>>();
         if times.len() > 0 {
-            bytes.extend(Parameter::floats(times)?.write(
+            bytes.extend(Parameter::float_grid(Grid::from_vec(times, 2)).write(
                 processor,
                 "TIMES".to_string(),
                 group_names_to_ids["EVENT"],
@@ -395,9 +529,9 @@ impl Events {
         if event_context_colours.len() > 0 {
             let mut colours_grid = Grid::new(0, 3);
             for colour in event_context_colours {
-                colours_grid.push_row(colour.to_vec());
+                colours_grid.push_row(colour.iter().map(|&x| x as i16).collect());
             }
-            bytes.extend(Parameter::byte_grid(colours_grid).write(
+            bytes.extend(Parameter::integer_grid(colours_grid).write(
                 processor,
                 "COLOURS".to_string(),
                 group_names_to_ids["EVENT_CONTEXT"],
@@ -418,7 +552,7 @@ fn get_num_time_events(
         true => {
             let num_time_events = processor.i16([header_block[300], header_block[301]]);
 
-            if num_time_events > 18 {
+            if !(0..=18).contains(&num_time_events) {
                 return Err(C3dParseError::TooManyEvents(num_time_events));
             }
             let parameter_num_time_events = parameters.remove("EVENT", "USED");
@@ -426,7 +560,7 @@ fn get_num_time_events(
                 let parameter_num_time_events: i16 =
                     parameter_num_time_events.unwrap().as_ref().try_into()?;
                 if parameter_num_time_events != num_time_events as i16 {
-                    return Ok(parameter_num_time_events as usize);
+                    return Ok(parameter_num_time_events as u16 as usize);
                 } else {
                     return Ok(num_time_events as usize);
                 }
@@ -437,7 +571,7 @@ fn get_num_time_events(
             if parameter_num_time_events.is_some() {
                 let parameter_num_time_events: i16 =
                     parameter_num_time_events.unwrap().as_ref().try_into()?;
-                return Ok(parameter_num_time_events as usize);
+                return Ok(parameter_num_time_events as u16 as usize);
             }
         }
     }
@@ -454,7 +588,7 @@ fn get_times_array(parameters: &mut Parameters) -> Result<Vec<[f32; 2]>, C3dPars
             ParameterData::Float(data) => {
                 if parameter.dimensions.len() == 2 && data.len() > 1 {
                     let mut times = Vec::new();
-                    for row in 0..data.len() % 2 {
+                    for row in 0..data.len() / 2 {
                         let mut time = [0.0; 2];
                         time[0] = data[row * 2];
                         time[1] = data[row * 2 + 1];
@@ -532,19 +666,27 @@ fn get_generic_flags_array(parameters: &mut Parameters) -> Vec<i16> {
     }
 }
 
+/// Returns the time of an event in seconds.
+/// EVENT:TIMES stores the minutes and seconds of every event, and is preferred over the
+/// header, which only has room for the first 18 events.
 fn verify_time(
     event_num: usize,
     header_block: &[u8; 512],
-    _time: &Vec<[f32; 2]>,
+    times: &[[f32; 2]],
     processor: &Processor,
 ) -> Result<f32, C3dParseError> {
+    if let Some([minutes, seconds]) = times.get(event_num) {
+        return Ok(minutes * 60. + seconds);
+    }
+    if event_num >= 18 {
+        return Ok(0.);
+    }
     let time_start = 304 + (event_num * 4);
     Ok(processor.f32(header_block[time_start..time_start + 4].try_into().unwrap()))
-    // TODO: use time
 }
 
 fn get_event_id(event_num: usize, header_block: &[u8; 512]) -> Result<[char; 4], C3dParseError> {
-    if event_num > 18 {
+    if event_num >= 18 {
         return Ok([0x00 as char; 4]);
     }
     let label_start = 396 + (event_num * 4);
@@ -559,6 +701,9 @@ fn get_event_id(event_num: usize, header_block: &[u8; 512]) -> Result<[char; 4],
 }
 
 fn get_display_flag(event_num: usize, header_block: &[u8; 512]) -> bool {
+    if event_num >= 18 {
+        return true;
+    }
     let display_flag_start = 376 + event_num;
     header_block[display_flag_start] == 0
 }
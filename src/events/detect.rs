@@ -0,0 +1,359 @@
+//! Detects Foot Strike and Foot Off events from force platforms or marker trajectories.
+use crate::c3d::C3d;
+use crate::data::MarkerPoint;
+use crate::filters::Butterworth;
+use crate::C3dFilterError;
+
+/// The markers of one foot, and the context its events are given, e.g. `Left`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Foot {
+    pub context: String,
+    pub heel: String,
+    pub toe: String,
+}
+
+impl Foot {
+    pub fn new(context: &str, heel: &str, toe: &str) -> Foot {
+        Foot {
+            context: context.to_string(),
+            heel: heel.to_string(),
+            toe: toe.to_string(),
+        }
+    }
+
+    /// The left foot of the Plug-in Gait marker set, with the `LHEE` and `LTOE` markers.
+    pub fn left() -> Foot {
+        Foot::new("Left", "LHEE", "LTOE")
+    }
+
+    /// The right foot of the Plug-in Gait marker set, with the `RHEE` and `RTOE` markers.
+    pub fn right() -> Foot {
+        Foot::new("Right", "RHEE", "RTOE")
+    }
+}
+
+/// The algorithm used to detect gait events.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Detection {
+    /// The force on a platform rising above and falling below the given threshold in N.
+    /// Each contact is given to the foot whose heel and toe are closest to the center
+    /// of pressure, and contacts that start or end outside the trial are ignored.
+    ForcePlatforms(f32),
+    /// The heel furthest ahead of and the toe furthest behind the centroid of the given
+    /// pelvis markers, e.g. `SACR`, along the direction of walking (Zeni et al., 2008).
+    Coordinates(Vec<String>),
+    /// The heel slowing below and the toe speeding up above the given speed, in the
+    /// units of the points per second, e.g. 500 mm/s for walking (after Ghoussayni
+    /// et al., 2004, with a higher threshold as the trajectories are smoothed).
+    FootVelocity(f32),
+}
+
+/// A detected event, at a time in seconds from the first frame of the file
+/// as for `Event::time`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GaitEvent {
+    pub label: String,
+    pub context: String,
+    pub time: f32,
+}
+
+impl GaitEvent {
+    fn new(label: &str, foot: &Foot, time: f32) -> GaitEvent {
+        GaitEvent {
+            label: label.to_string(),
+            context: foot.context.clone(),
+            time,
+        }
+    }
+}
+
+/// The half-width in seconds of the window in which a kinematic event must be
+/// the largest or smallest value, and the shortest time between two crossings
+/// of a speed threshold, which are below half of a gait cycle.
+const EXTREMUM_WINDOW: f32 = 0.2;
+
+/// The cutoff in Hz of the low-pass filter applied to trajectories before detection.
+const SMOOTHING: f64 = 6.;
+
+/// Detects the Foot Strike and Foot Off events of the given feet, sorted by time.
+/// Fails if a marker of a foot or the pelvis is not found, or if the point frame rate
+/// is too low to smooth the trajectories.
+pub fn detect(
+    c3d: &C3d,
+    detection: &Detection,
+    feet: &[Foot],
+) -> Result<Vec<GaitEvent>, C3dFilterError> {
+    let mut trajectories = Vec::with_capacity(feet.len());
+    for foot in feet {
+        trajectories.push((trajectory(c3d, &foot.heel)?, trajectory(c3d, &foot.toe)?));
+    }
+    let mut events = match detection {
+        Detection::ForcePlatforms(threshold) => {
+            force_platforms(c3d, *threshold, feet, &trajectories)
+        }
+        Detection::Coordinates(pelvis) => {
+            let pelvis = centroid(c3d, pelvis)?;
+            coordinates(c3d, &pelvis, feet, &trajectories)
+        }
+        Detection::FootVelocity(threshold) => foot_velocity(c3d, *threshold, feet, &trajectories),
+    };
+    events.sort_by(|a, b| a.time.total_cmp(&b.time));
+    Ok(events)
+}
+
+/// The smoothed positions of a marker, or `None` where it is invalid.
+type Trajectory = Vec<Option<[f32; 3]>>;
+
+fn trajectory(c3d: &C3d, label: &str) -> Result<Trajectory, C3dFilterError> {
+    let column = c3d
+        .points
+        .label_index(label)
+        .ok_or_else(|| C3dFilterError::PointNotFound(label.to_string()))?;
+    let mut points: Vec<MarkerPoint> = c3d.points.iter_col(column).cloned().collect();
+    Butterworth::low_pass(SMOOTHING, 2).filter_points(&mut points, c3d.points.frame_rate as f64)?;
+    Ok(points
+        .into_iter()
+        .map(|point| point.is_valid().then_some(point.point))
+        .collect())
+}
+
+fn centroid(c3d: &C3d, labels: &[String]) -> Result<Trajectory, C3dFilterError> {
+    let mut trajectories = Vec::with_capacity(labels.len());
+    for label in labels {
+        trajectories.push(trajectory(c3d, label)?);
+    }
+    Ok((0..c3d.points.rows())
+        .map(|frame| {
+            let points: Option<Vec<[f32; 3]>> = trajectories.iter().map(|x| x[frame]).collect();
+            let points = points.filter(|points| !points.is_empty())?;
+            let sum = points.iter().fold([0.; 3], |sum, point| {
+                [sum[0] + point[0], sum[1] + point[1], sum[2] + point[2]]
+            });
+            Some(sum.map(|x| x / points.len() as f32))
+        })
+        .collect())
+}
+
+/// Returns the vertical axis of the lab, the axis pointing up on the screen as given
+/// by POINT:Y_SCREEN, or Z if it is not given.
+fn vertical_axis(c3d: &C3d) -> usize {
+    c3d.points.y_screen_axis().map_or(2, |axis| axis.index())
+}
+
+/// Returns the time of a stored frame, as for `Event::time`.
+fn frame_time(c3d: &C3d, frame: f32) -> f32 {
+    (frame + c3d.points.first_frame.max(1) as f32 - 1.) / c3d.points.frame_rate
+}
+
+/// Returns the frames where a value is larger than every other value within `window`
+/// frames, and its neighbours are valid.
+fn peaks(values: &[Option<f32>], window: usize) -> Vec<usize> {
+    (1..values.len().saturating_sub(1))
+        .filter(|&frame| {
+            let value = match (values[frame - 1], values[frame], values[frame + 1]) {
+                (Some(_), Some(value), Some(_)) => value,
+                _ => return false,
+            };
+            let start = frame.saturating_sub(window);
+            let end = (frame + window + 1).min(values.len());
+            (start..end).all(|other| match values[other] {
+                Some(other_value) if other < frame => value > other_value,
+                Some(other_value) => other == frame || value >= other_value,
+                None => true,
+            })
+        })
+        .collect()
+}
+
+fn troughs(values: &[Option<f32>], window: usize) -> Vec<usize> {
+    let negated: Vec<Option<f32>> = values.iter().map(|x| x.map(|x| -x)).collect();
+    peaks(&negated, window)
+}
+
+fn extremum_window(c3d: &C3d) -> usize {
+    ((EXTREMUM_WINDOW * c3d.points.frame_rate).round() as usize).max(1)
+}
+
+fn force_platforms(
+    c3d: &C3d,
+    threshold: f32,
+    feet: &[Foot],
+    trajectories: &[(Trajectory, Trajectory)],
+) -> Vec<GaitEvent> {
+    let mut events = Vec::new();
+    let samples_per_frame = c3d.analog.samples_per_channel_per_frame.max(1) as f32;
+    let sample_time = |sample: usize| frame_time(c3d, sample as f32 / samples_per_frame);
+    for platform in 0..c3d.forces.len() {
+        let data = match c3d.force_platform_data(platform) {
+            Some(data) => data,
+            None => continue,
+        };
+        let loaded: Vec<bool> = data
+            .force
+            .iter()
+            .map(|force| force.iter().map(|x| x * x).sum::<f32>().sqrt() > threshold)
+            .collect();
+        let mut start = 0;
+        while start < loaded.len() {
+            if !loaded[start] {
+                start += 1;
+                continue;
+            }
+            let end = loaded[start..]
+                .iter()
+                .position(|&loaded| !loaded)
+                .map_or(loaded.len(), |len| start + len);
+            let contact = (start, end);
+            start = end;
+            if contact.0 == 0 || contact.1 == loaded.len() {
+                continue;
+            }
+            // the center of pressure halfway through the contact, when the foot is flat
+            let middle = (contact.0 + contact.1) / 2;
+            let frame = (middle as f32 / samples_per_frame) as usize;
+            let center_of_pressure = data.center_of_pressure[middle];
+            let distance = |point: Option<[f32; 3]>| {
+                point.map(|point| {
+                    (0..3)
+                        .filter(|&axis| axis != vertical_axis(c3d))
+                        .map(|axis| (point[axis] - center_of_pressure[axis]).powi(2))
+                        .sum::<f32>()
+                })
+            };
+            let nearest = trajectories
+                .iter()
+                .enumerate()
+                .filter_map(|(foot, (heel, toe))| {
+                    let heel = distance(heel.get(frame).copied().flatten())?;
+                    let toe = distance(toe.get(frame).copied().flatten())?;
+                    Some((foot, heel.min(toe)))
+                })
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            if let Some((foot, _)) = nearest {
+                let foot = &feet[foot];
+                events.push(GaitEvent::new("Foot Strike", foot, sample_time(contact.0)));
+                events.push(GaitEvent::new("Foot Off", foot, sample_time(contact.1)));
+            }
+        }
+    }
+    events
+}
+
+fn coordinates(
+    c3d: &C3d,
+    pelvis: &Trajectory,
+    feet: &[Foot],
+    trajectories: &[(Trajectory, Trajectory)],
+) -> Vec<GaitEvent> {
+    let vertical = vertical_axis(c3d);
+    // the direction of walking is the horizontal axis along which the pelvis moves the most
+    let valid: Vec<[f32; 3]> = pelvis.iter().flatten().copied().collect();
+    let (first, last) = match (valid.first(), valid.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Vec::new(),
+    };
+    let axis = (0..3)
+        .filter(|&axis| axis != vertical)
+        .max_by(|&a, &b| {
+            (last[a] - first[a])
+                .abs()
+                .total_cmp(&(last[b] - first[b]).abs())
+        })
+        .unwrap_or(0);
+    let direction = (last[axis] - first[axis]).signum();
+    let relative = |marker: &Trajectory| -> Vec<Option<f32>> {
+        marker
+            .iter()
+            .zip(pelvis)
+            .map(|(marker, pelvis)| {
+                Some((marker.as_ref()?[axis] - pelvis.as_ref()?[axis]) * direction)
+            })
+            .collect()
+    };
+    let window = extremum_window(c3d);
+    let mut events = Vec::new();
+    for (foot, (heel, toe)) in feet.iter().zip(trajectories) {
+        for frame in peaks(&relative(heel), window) {
+            events.push(GaitEvent::new(
+                "Foot Strike",
+                foot,
+                frame_time(c3d, frame as f32),
+            ));
+        }
+        for frame in troughs(&relative(toe), window) {
+            events.push(GaitEvent::new(
+                "Foot Off",
+                foot,
+                frame_time(c3d, frame as f32),
+            ));
+        }
+    }
+    events
+}
+
+/// Returns the speed of a marker in each frame from central differences,
+/// or `None` where it or a neighbouring frame is invalid.
+fn speed(c3d: &C3d, marker: &Trajectory) -> Vec<Option<f32>> {
+    let rate = c3d.points.frame_rate;
+    (0..marker.len())
+        .map(|frame| {
+            let previous = marker.get(frame.checked_sub(1)?).copied().flatten()?;
+            let next = marker.get(frame + 1).copied().flatten()?;
+            let distance = (0..3)
+                .map(|axis| (next[axis] - previous[axis]).powi(2))
+                .sum::<f32>()
+                .sqrt();
+            Some(distance * rate / 2.)
+        })
+        .collect()
+}
+
+/// Returns the frames where the speed crosses the threshold, from above if `slowing`,
+/// at least `window` frames after the previous crossing.
+fn crossings(speed: &[Option<f32>], threshold: f32, slowing: bool, window: usize) -> Vec<usize> {
+    let mut frames: Vec<usize> = Vec::new();
+    for frame in 1..speed.len() {
+        let (before, after) = match (speed[frame - 1], speed[frame]) {
+            (Some(before), Some(after)) => (before, after),
+            _ => continue,
+        };
+        let crossed = match slowing {
+            true => before >= threshold && after < threshold,
+            false => before < threshold && after >= threshold,
+        };
+        if crossed && frames.last().is_none_or(|&last| frame - last >= window) {
+            frames.push(frame);
+        }
+    }
+    frames
+}
+
+fn foot_velocity(
+    c3d: &C3d,
+    threshold: f32,
+    feet: &[Foot],
+    trajectories: &[(Trajectory, Trajectory)],
+) -> Vec<GaitEvent> {
+    let window = extremum_window(c3d);
+    let mut events = Vec::new();
+    for (foot, (heel, toe)) in feet.iter().zip(trajectories) {
+        for frame in crossings(&speed(c3d, heel), threshold, true, window) {
+            events.push(GaitEvent::new(
+                "Foot Strike",
+                foot,
+                frame_time(c3d, frame as f32),
+            ));
+        }
+        for frame in crossings(&speed(c3d, toe), threshold, false, window) {
+            events.push(GaitEvent::new(
+                "Foot Off",
+                foot,
+                frame_time(c3d, frame as f32),
+            ));
+        }
+    }
+    events
+}
@@ -0,0 +1,342 @@
+//! A C ABI for loading, reading and writing C3D files from other languages, such as
+//! MATLAB MEX files or C++, enabled with the `ffi` feature.
+//!
+//! Build the library as a C library with
+//! `cargo rustc --release --features ffi --crate-type cdylib` (or `staticlib`).
+//! A file is loaded into an opaque `C3d` handle that is released with `c3dio_free`.
+//! Functions that fail return a null handle or a negative status, and
+//! `c3dio_last_error` describes the last failure on the calling thread.
+//!
+//! ```c
+//! typedef struct C3d C3d;
+//! C3d *c3dio_load(const char *path);
+//! C3d *c3dio_from_bytes(const uint8_t *bytes, size_t len);
+//! void c3dio_free(C3d *c3d);
+//! size_t c3dio_last_error(char *buffer, size_t len);
+//! size_t c3dio_frame_count(const C3d *c3d);
+//! size_t c3dio_point_count(const C3d *c3d);
+//! float c3dio_point_rate(const C3d *c3d);
+//! size_t c3dio_point_label(const C3d *c3d, size_t point, char *buffer, size_t len);
+//! size_t c3dio_read_point(const C3d *c3d, size_t point, float *samples, size_t len);
+//! size_t c3dio_analog_channel_count(const C3d *c3d);
+//! size_t c3dio_analog_sample_count(const C3d *c3d);
+//! float c3dio_analog_rate(const C3d *c3d);
+//! size_t c3dio_analog_label(const C3d *c3d, size_t channel, char *buffer, size_t len);
+//! size_t c3dio_read_analog(const C3d *c3d, size_t channel, double *samples, size_t len);
+//! int c3dio_write(const C3d *c3d, const char *path);
+//! uint8_t *c3dio_write_bytes(const C3d *c3d, size_t *len);
+//! void c3dio_free_bytes(uint8_t *bytes, size_t len);
+//! ```
+use crate::C3d;
+use std::cell::RefCell;
+use std::ffi::c_char;
+#[cfg(feature = "fs")]
+use std::ffi::{c_int, CStr};
+
+thread_local! {
+    static LAST_ERROR: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+fn set_last_error(error: impl std::fmt::Display) {
+    LAST_ERROR.with(|last| *last.borrow_mut() = error.to_string());
+}
+
+/// Copies a string into a buffer as a nul-terminated C string, truncating it to fit,
+/// and returns the length of the whole string without the terminator.
+///
+/// # Safety
+/// `buffer` must be null or valid for writes of `len` bytes.
+unsafe fn copy_string(text: &str, buffer: *mut c_char, len: usize) -> usize {
+    if !buffer.is_null() && len > 0 {
+        let copied = text.len().min(len - 1);
+        std::ptr::copy_nonoverlapping(text.as_ptr(), buffer as *mut u8, copied);
+        *buffer.add(copied) = 0;
+    }
+    text.len()
+}
+
+/// Loads a C3D file from a nul-terminated UTF-8 path.
+/// Returns null if the file cannot be loaded.
+///
+/// # Safety
+/// `path` must be null or a valid nul-terminated string.
+#[cfg(feature = "fs")]
+#[no_mangle]
+pub unsafe extern "C" fn c3dio_load(path: *const c_char) -> *mut C3d {
+    if path.is_null() {
+        set_last_error("the path is null");
+        return std::ptr::null_mut();
+    }
+    let path = match CStr::from_ptr(path).to_str() {
+        Ok(path) => path,
+        Err(e) => {
+            set_last_error(e);
+            return std::ptr::null_mut();
+        }
+    };
+    match C3d::load(path) {
+        Ok(c3d) => Box::into_raw(Box::new(c3d)),
+        Err(e) => {
+            set_last_error(e);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Parses a C3D file from `len` bytes in memory.
+/// Returns null if the bytes cannot be parsed.
+///
+/// # Safety
+/// `bytes` must be null or valid for reads of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn c3dio_from_bytes(bytes: *const u8, len: usize) -> *mut C3d {
+    if bytes.is_null() {
+        set_last_error("the bytes are null");
+        return std::ptr::null_mut();
+    }
+    match C3d::from_bytes(std::slice::from_raw_parts(bytes, len)) {
+        Ok(c3d) => Box::into_raw(Box::new(c3d)),
+        Err(e) => {
+            set_last_error(e);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Releases a handle returned by `c3dio_load` or `c3dio_from_bytes`.
+///
+/// # Safety
+/// `c3d` must be null or a handle that has not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn c3dio_free(c3d: *mut C3d) {
+    if !c3d.is_null() {
+        drop(Box::from_raw(c3d));
+    }
+}
+
+/// Copies the description of the last failure on this thread into a buffer,
+/// returning its length. See `copy_string`.
+///
+/// # Safety
+/// `buffer` must be null or valid for writes of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn c3dio_last_error(buffer: *mut c_char, len: usize) -> usize {
+    LAST_ERROR.with(|last| copy_string(&last.borrow(), buffer, len))
+}
+
+/// Returns the number of stored frames, or 0 for a null handle.
+///
+/// # Safety
+/// `c3d` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn c3dio_frame_count(c3d: *const C3d) -> usize {
+    c3d.as_ref().map_or(0, |c3d| c3d.stored_frame_count())
+}
+
+/// Returns the number of points, or 0 for a null handle.
+///
+/// # Safety
+/// `c3d` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn c3dio_point_count(c3d: *const C3d) -> usize {
+    c3d.as_ref().map_or(0, |c3d| c3d.points.cols())
+}
+
+/// Returns the frame rate of the points in Hz, or 0 for a null handle.
+///
+/// # Safety
+/// `c3d` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn c3dio_point_rate(c3d: *const C3d) -> f32 {
+    c3d.as_ref().map_or(0., |c3d| c3d.points.frame_rate)
+}
+
+/// Copies the trimmed label of a point into a buffer, returning its length.
+/// A point without a label has an empty one.
+///
+/// # Safety
+/// `c3d` must be null or a valid handle, and `buffer` null or valid for writes of
+/// `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn c3dio_point_label(
+    c3d: *const C3d,
+    point: usize,
+    buffer: *mut c_char,
+    len: usize,
+) -> usize {
+    let label = c3d
+        .as_ref()
+        .and_then(|c3d| c3d.points.labels.get(point))
+        .map_or("", |label| label.trim());
+    copy_string(label, buffer, len)
+}
+
+/// Copies the X, Y, Z and residual of a point in each frame into `samples`, which
+/// holds `len` floats, returning the number of frames copied.
+/// Invalid samples have a residual of -1.
+///
+/// # Safety
+/// `c3d` must be null or a valid handle, and `samples` null or valid for writes of
+/// `len` floats.
+#[no_mangle]
+pub unsafe extern "C" fn c3dio_read_point(
+    c3d: *const C3d,
+    point: usize,
+    samples: *mut f32,
+    len: usize,
+) -> usize {
+    let c3d = match c3d.as_ref() {
+        Some(c3d) if point < c3d.points.cols() && !samples.is_null() => c3d,
+        _ => return 0,
+    };
+    let samples = std::slice::from_raw_parts_mut(samples, len);
+    let mut frames = 0;
+    for (sample, marker) in samples.chunks_exact_mut(4).zip(c3d.points.iter_col(point)) {
+        sample[..3].copy_from_slice(&marker.point);
+        sample[3] = marker.residual;
+        frames += 1;
+    }
+    frames
+}
+
+/// Returns the number of analog channels, or 0 for a null handle.
+///
+/// # Safety
+/// `c3d` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn c3dio_analog_channel_count(c3d: *const C3d) -> usize {
+    c3d.as_ref().map_or(0, |c3d| c3d.analog.cols())
+}
+
+/// Returns the number of samples of each analog channel, or 0 for a null handle.
+///
+/// # Safety
+/// `c3d` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn c3dio_analog_sample_count(c3d: *const C3d) -> usize {
+    c3d.as_ref().map_or(0, |c3d| c3d.analog.rows())
+}
+
+/// Returns the sample rate of the analog channels in Hz, or 0 for a null handle.
+///
+/// # Safety
+/// `c3d` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn c3dio_analog_rate(c3d: *const C3d) -> f32 {
+    c3d.as_ref().map_or(0., |c3d| c3d.analog.rate)
+}
+
+/// Copies the trimmed label of an analog channel into a buffer, returning its length.
+///
+/// # Safety
+/// `c3d` must be null or a valid handle, and `buffer` null or valid for writes of
+/// `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn c3dio_analog_label(
+    c3d: *const C3d,
+    channel: usize,
+    buffer: *mut c_char,
+    len: usize,
+) -> usize {
+    let label = c3d
+        .as_ref()
+        .and_then(|c3d| c3d.analog.labels.get(channel))
+        .map_or("", |label| label.trim());
+    copy_string(label, buffer, len)
+}
+
+/// Copies the scaled samples of an analog channel into `samples`, which holds `len`
+/// doubles, returning the number of samples copied.
+///
+/// # Safety
+/// `c3d` must be null or a valid handle, and `samples` null or valid for writes of
+/// `len` doubles.
+#[no_mangle]
+pub unsafe extern "C" fn c3dio_read_analog(
+    c3d: *const C3d,
+    channel: usize,
+    samples: *mut f64,
+    len: usize,
+) -> usize {
+    let c3d = match c3d.as_ref() {
+        Some(c3d) if channel < c3d.analog.cols() && !samples.is_null() => c3d,
+        _ => return 0,
+    };
+    let samples = std::slice::from_raw_parts_mut(samples, len);
+    let mut copied = 0;
+    for (sample, value) in samples.iter_mut().zip(c3d.analog.iter_col(channel)) {
+        *sample = *value;
+        copied += 1;
+    }
+    copied
+}
+
+/// Writes the file to a nul-terminated UTF-8 path, returning 0 on success
+/// and -1 on failure.
+///
+/// # Safety
+/// `c3d` must be null or a valid handle, and `path` null or a valid nul-terminated
+/// string.
+#[cfg(feature = "fs")]
+#[no_mangle]
+pub unsafe extern "C" fn c3dio_write(c3d: *const C3d, path: *const c_char) -> c_int {
+    let (c3d, path) = match (c3d.as_ref(), path.is_null()) {
+        (Some(c3d), false) => (c3d, CStr::from_ptr(path)),
+        _ => {
+            set_last_error("the handle or the path is null");
+            return -1;
+        }
+    };
+    let path = match path.to_str() {
+        Ok(path) => path,
+        Err(e) => {
+            set_last_error(e);
+            return -1;
+        }
+    };
+    match c3d.write(path) {
+        Ok(_) => 0,
+        Err(e) => {
+            set_last_error(e);
+            -1
+        }
+    }
+}
+
+/// Writes the file to a new buffer, storing its length in `len`.
+/// The buffer is released with `c3dio_free_bytes`. Returns null on failure.
+///
+/// # Safety
+/// `c3d` must be null or a valid handle, and `len` valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn c3dio_write_bytes(c3d: *const C3d, len: *mut usize) -> *mut u8 {
+    let c3d = match c3d.as_ref() {
+        Some(c3d) if !len.is_null() => c3d,
+        _ => {
+            set_last_error("the handle or the length is null");
+            return std::ptr::null_mut();
+        }
+    };
+    let mut bytes = Vec::new();
+    if let Err(e) = c3d.write_to(&mut bytes, c3d.processor()) {
+        set_last_error(e);
+        return std::ptr::null_mut();
+    }
+    let bytes = bytes.into_boxed_slice();
+    *len = bytes.len();
+    Box::into_raw(bytes) as *mut u8
+}
+
+/// Releases a buffer returned by `c3dio_write_bytes`.
+///
+/// # Safety
+/// `bytes` must be null or a buffer returned by `c3dio_write_bytes` with its length,
+/// that has not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn c3dio_free_bytes(bytes: *mut u8, len: usize) {
+    if !bytes.is_null() {
+        drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+            bytes, len,
+        )));
+    }
+}
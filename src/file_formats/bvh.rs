@@ -0,0 +1,180 @@
+//! BVH files hold a skeleton hierarchy and its motion, and are read by animation tools
+//! such as Blender and MotionBuilder.
+use crate::{C3d, C3dWriteError};
+use grid::Grid;
+#[cfg(feature = "fs")]
+use std::io::BufWriter;
+use std::io::Write;
+#[cfg(feature = "fs")]
+use std::path::PathBuf;
+
+/// The BVH struct contains the data for writing a BVH file.
+/// The skeleton has a root at the origin with one joint per marker, and each joint
+/// moves by its own position channels, so the markers can be used as targets for
+/// constraints or retargeting in animation tools.
+/// BVH files cannot hold missing data, so a marker keeps its last valid position
+/// during a gap, or its first valid position before it is first seen.
+///
+/// # Examples
+/// ```
+/// use c3dio::prelude::*;
+/// use c3dio::Bvh;
+///
+/// let c3d = C3d::load("tests/data/short.c3d").unwrap();
+/// // C3D files are usually Z-up in millimetres, Blender is Y-up in metres
+/// let bvh = Bvh::from_c3d(&c3d).with_y_up().with_scale(0.001);
+/// let mut bytes = Vec::new();
+/// bvh.write_to(&mut bytes).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Bvh {
+    pub frame_rate: f32,
+    pub joint_names: Vec<String>,
+    /// The position of each joint in each frame, as frames by joints.
+    pub positions: Grid<[f32; 3]>,
+}
+
+impl Bvh {
+    /// Creates a BVH file with a joint for every point of the file.
+    pub fn from_c3d(c3d: &C3d) -> Self {
+        let columns: Vec<usize> = (0..c3d.points.cols()).collect();
+        Bvh::from_columns(c3d, &columns)
+    }
+
+    /// Creates a BVH file with a joint for each of the given markers, in the given order.
+    /// Labels that are not found are skipped.
+    pub fn from_c3d_markers(c3d: &C3d, labels: &[&str]) -> Self {
+        let columns: Vec<usize> = labels
+            .iter()
+            .filter_map(|label| c3d.points.label_index(label))
+            .collect();
+        Bvh::from_columns(c3d, &columns)
+    }
+
+    fn from_columns(c3d: &C3d, columns: &[usize]) -> Self {
+        let joint_names = columns
+            .iter()
+            .map(|&i| match c3d.points.labels.get(i) {
+                Some(label) if !label.trim().is_empty() => joint_name(label),
+                _ => format!("M{}", i + 1),
+            })
+            .collect();
+        let num_frames = c3d.points.rows();
+        let mut positions = Grid::new(num_frames, columns.len());
+        for (joint, &column) in columns.iter().enumerate() {
+            let mut last = c3d
+                .points
+                .iter_col(column)
+                .find(|point| point.is_valid())
+                .map_or([0.; 3], |point| point.point);
+            for frame in 0..num_frames {
+                let point = c3d.points[(frame, column)];
+                if point.is_valid() {
+                    last = point.point;
+                }
+                positions[(frame, joint)] = last;
+            }
+        }
+        Bvh {
+            frame_rate: c3d.points.frame_rate,
+            joint_names,
+            positions,
+        }
+    }
+
+    /// Rotates the positions from a Z-up to a Y-up coordinate system,
+    /// keeping the X axis, as expected by most animation tools.
+    pub fn with_y_up(mut self) -> Self {
+        for position in self.positions.iter_mut() {
+            *position = [position[0], position[2], -position[1]];
+        }
+        self
+    }
+
+    /// Multiplies the positions by a factor, e.g. 0.001 to convert millimetres to metres.
+    pub fn with_scale(mut self, factor: f32) -> Self {
+        for position in self.positions.iter_mut() {
+            *position = position.map(|x| x * factor);
+        }
+        self
+    }
+
+    #[cfg(feature = "fs")]
+    pub fn write(&self, file_name: PathBuf) -> Result<(), C3dWriteError> {
+        if file_name.is_dir() {
+            return Err(C3dWriteError::InvalidFilePath(file_name));
+        }
+        let extension = file_name
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if !extension.to_lowercase().eq("bvh") {
+            return Err(C3dWriteError::InvalidFileExtension(extension));
+        }
+        let file = std::fs::File::create(file_name.clone())
+            .map_err(|e| C3dWriteError::WriteError(file_name.clone(), e))?;
+        let mut writer = BufWriter::new(file);
+        self.write_to(&mut writer)?;
+        writer
+            .flush()
+            .map_err(|e| C3dWriteError::WriteError(file_name.clone(), e))?;
+        Ok(())
+    }
+
+    /// Writes the BVH file to any `Write` sink, such as a `Vec<u8>`.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), C3dWriteError> {
+        let num_joints = self.positions.cols();
+        let mut header = String::new();
+        header.push_str("HIERARCHY\nROOT Markers\n{\n");
+        header.push_str("\tOFFSET 0.0 0.0 0.0\n");
+        header
+            .push_str("\tCHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation\n");
+        for joint in 0..num_joints {
+            let name = match self.joint_names.get(joint) {
+                Some(name) => joint_name(name),
+                None => format!("M{}", joint + 1),
+            };
+            header.push_str(&format!("\tJOINT {}\n\t{{\n", name));
+            header.push_str("\t\tOFFSET 0.0 0.0 0.0\n");
+            header.push_str("\t\tCHANNELS 3 Xposition Yposition Zposition\n");
+            header.push_str("\t\tEnd Site\n\t\t{\n\t\t\tOFFSET 0.0 0.0 0.0\n\t\t}\n\t}\n");
+        }
+        header.push_str("}\nMOTION\n");
+        let frame_time = match self.frame_rate > 0. {
+            true => 1. / self.frame_rate,
+            false => 0.,
+        };
+        header.push_str(&format!(
+            "Frames: {}\nFrame Time: {}\n",
+            self.positions.rows(),
+            frame_time
+        ));
+        writer
+            .write_all(header.as_bytes())
+            .map_err(C3dWriteError::WriteHeaderError)?;
+        for frame in 0..self.positions.rows() {
+            // the root stays at the origin without rotation
+            let mut line = "0 0 0 0 0 0".to_string();
+            for position in self.positions.iter_row(frame) {
+                line.push_str(&format!(" {} {} {}", position[0], position[1], position[2]));
+            }
+            line.push('\n');
+            writer
+                .write_all(line.as_bytes())
+                .map_err(C3dWriteError::WriteDataError)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns a label as a joint name, which cannot contain whitespace.
+fn joint_name(label: &str) -> String {
+    label
+        .trim()
+        .chars()
+        .map(|c| match c.is_whitespace() {
+            true => '_',
+            false => c,
+        })
+        .collect()
+}
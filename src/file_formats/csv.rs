@@ -0,0 +1,254 @@
+//! CSV files are a flat table of comma-separated values that can be opened in
+//! spreadsheets or read by R and Python without a C3D parser.
+use crate::{C3d, C3dWriteError};
+#[cfg(feature = "fs")]
+use std::io::BufWriter;
+use std::io::Write;
+#[cfg(feature = "fs")]
+use std::path::PathBuf;
+
+/// A single cell of a CSV file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CsvValue {
+    /// Single precision numbers such as points, written without widening to f64.
+    F32(f32),
+    F64(f64),
+    Text(String),
+    /// Written as an empty cell, e.g. for invalid points.
+    Empty,
+}
+
+/// The CSV struct contains the data for writing a CSV file.
+/// The first row holds the column names and each following row one frame, sample or event.
+///
+/// # Examples
+/// ```
+/// use c3dio::prelude::*;
+/// use c3dio::Csv;
+///
+/// let c3d = C3d::load("tests/data/short.c3d").unwrap();
+/// let csv = Csv::points_from_c3d(&c3d).with_delimiter(';').with_precision(3);
+/// let mut bytes = Vec::new();
+/// csv.write_to(&mut bytes).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Csv {
+    pub delimiter: char,
+    /// Number of decimal places of each number.
+    /// Numbers are written with as many digits as needed if none is set.
+    pub precision: Option<usize>,
+    pub column_names: Vec<String>,
+    pub rows: Vec<Vec<CsvValue>>,
+}
+
+impl Csv {
+    /// Creates a CSV file with one row per point frame, with the frame number, the time
+    /// and the X, Y and Z columns of each point. Invalid points are left empty.
+    pub fn points_from_c3d(c3d: &C3d) -> Self {
+        let mut csv = Csv::frames(c3d);
+        csv.column_names.extend(point_columns(c3d));
+        for (frame, row) in csv.rows.iter_mut().enumerate() {
+            push_points(c3d, frame, row);
+        }
+        csv
+    }
+
+    /// Creates a CSV file with one row per analog sample, with the time
+    /// and one column per analog channel.
+    pub fn analog_from_c3d(c3d: &C3d) -> Self {
+        let first_frame = c3d.points.first_frame.max(1) as usize;
+        let start = (first_frame - 1) as f64 / c3d.points.frame_rate as f64;
+        let mut column_names = vec!["Time".to_string()];
+        column_names.extend(analog_labels(c3d));
+        let rows = (0..c3d.analog.rows())
+            .map(|sample| {
+                let mut row = vec![CsvValue::F64(
+                    start + sample as f64 / c3d.analog.rate as f64,
+                )];
+                row.extend(c3d.analog[sample].iter().map(|&x| CsvValue::F64(x)));
+                row
+            })
+            .collect();
+        Csv::new(column_names, rows)
+    }
+
+    /// Creates a CSV file with one row per point frame holding both the points and the
+    /// analog samples of the frame. When there is more than one analog sample per frame,
+    /// each channel has one column per sample, e.g. `Fz_1` and `Fz_2`.
+    pub fn from_c3d(c3d: &C3d) -> Self {
+        let samples_per_frame = c3d.analog.samples_per_channel_per_frame as usize;
+        let mut csv = Csv::frames(c3d);
+        csv.column_names.extend(point_columns(c3d));
+        for label in analog_labels(c3d) {
+            match samples_per_frame {
+                1 => csv.column_names.push(label),
+                _ => csv
+                    .column_names
+                    .extend((1..=samples_per_frame).map(|sample| format!("{}_{}", label, sample))),
+            }
+        }
+        for (frame, row) in csv.rows.iter_mut().enumerate() {
+            push_points(c3d, frame, row);
+            for channel in 0..c3d.analog.cols() {
+                for sample in 0..samples_per_frame {
+                    let sample = frame * samples_per_frame + sample;
+                    if sample < c3d.analog.rows() {
+                        row.push(CsvValue::F64(c3d.analog[sample][channel]));
+                    } else {
+                        row.push(CsvValue::Empty);
+                    }
+                }
+            }
+        }
+        csv
+    }
+
+    /// Creates a CSV file with one row per event, with its time, context, label and description.
+    pub fn events_from_c3d(c3d: &C3d) -> Self {
+        let column_names = ["Time", "Context", "Label", "Description"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        let rows = c3d
+            .events
+            .iter()
+            .map(|event| {
+                vec![
+                    CsvValue::F32(event.time),
+                    CsvValue::Text(event.context.trim().to_string()),
+                    CsvValue::Text(event.label.trim().to_string()),
+                    CsvValue::Text(event.description.trim().to_string()),
+                ]
+            })
+            .collect();
+        Csv::new(column_names, rows)
+    }
+
+    fn new(column_names: Vec<String>, rows: Vec<Vec<CsvValue>>) -> Self {
+        Csv {
+            delimiter: ',',
+            precision: None,
+            column_names,
+            rows,
+        }
+    }
+
+    /// Starts a CSV file with the frame number and time of every point frame.
+    fn frames(c3d: &C3d) -> Self {
+        let first_frame = c3d.points.first_frame.max(1) as usize;
+        let rows = (first_frame..first_frame + c3d.points.rows())
+            .map(|frame| {
+                vec![
+                    CsvValue::Text(frame.to_string()),
+                    CsvValue::F64((frame - 1) as f64 / c3d.points.frame_rate as f64),
+                ]
+            })
+            .collect();
+        Csv::new(vec!["Frame".to_string(), "Time".to_string()], rows)
+    }
+
+    pub fn with_delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    pub fn with_precision(mut self, precision: usize) -> Self {
+        self.precision = Some(precision);
+        self
+    }
+
+    #[cfg(feature = "fs")]
+    pub fn write(&self, file_name: PathBuf) -> Result<(), C3dWriteError> {
+        if file_name.is_dir() {
+            return Err(C3dWriteError::InvalidFilePath(file_name));
+        }
+        let file = std::fs::File::create(file_name.clone())
+            .map_err(|e| C3dWriteError::WriteError(file_name.clone(), e))?;
+        let mut writer = BufWriter::new(file);
+        self.write_to(&mut writer)?;
+        writer
+            .flush()
+            .map_err(|e| C3dWriteError::WriteError(file_name.clone(), e))?;
+        Ok(())
+    }
+
+    /// Writes the CSV file to any `Write` sink, such as a `Vec<u8>`.
+    /// Text containing the delimiter, quotes or line breaks is quoted.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), C3dWriteError> {
+        let header = self
+            .column_names
+            .iter()
+            .map(|name| self.escape(name))
+            .collect::<Vec<String>>();
+        let mut line = header.join(&self.delimiter.to_string());
+        line.push('\n');
+        writer
+            .write_all(line.as_bytes())
+            .map_err(C3dWriteError::WriteHeaderError)?;
+        for row in &self.rows {
+            let mut line = row
+                .iter()
+                .map(|value| match value {
+                    CsvValue::F32(number) => match self.precision {
+                        Some(precision) => format!("{:.*}", precision, number),
+                        None => number.to_string(),
+                    },
+                    CsvValue::F64(number) => match self.precision {
+                        Some(precision) => format!("{:.*}", precision, number),
+                        None => number.to_string(),
+                    },
+                    CsvValue::Text(text) => self.escape(text),
+                    CsvValue::Empty => String::new(),
+                })
+                .collect::<Vec<String>>()
+                .join(&self.delimiter.to_string());
+            line.push('\n');
+            writer
+                .write_all(line.as_bytes())
+                .map_err(C3dWriteError::WriteDataError)?;
+        }
+        Ok(())
+    }
+
+    fn escape(&self, text: &str) -> String {
+        if text.contains(self.delimiter) || text.contains(['"', '\n', '\r']) {
+            format!("\"{}\"", text.replace('"', "\"\""))
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+fn point_columns(c3d: &C3d) -> Vec<String> {
+    let mut columns = Vec::new();
+    for i in 0..c3d.points.cols() {
+        let label = match c3d.points.labels.get(i) {
+            Some(label) if !label.trim().is_empty() => label.trim().to_string(),
+            _ => format!("M{}", i + 1),
+        };
+        for axis in ["X", "Y", "Z"] {
+            columns.push(format!("{}_{}", label, axis));
+        }
+    }
+    columns
+}
+
+fn push_points(c3d: &C3d, frame: usize, row: &mut Vec<CsvValue>) {
+    for i in 0..c3d.points.cols() {
+        let point = &c3d.points[(frame, i)];
+        if point.is_valid() {
+            row.extend(point.iter().map(|&x| CsvValue::F32(x)));
+        } else {
+            row.extend([CsvValue::Empty, CsvValue::Empty, CsvValue::Empty]);
+        }
+    }
+}
+
+fn analog_labels(c3d: &C3d) -> Vec<String> {
+    (0..c3d.analog.cols())
+        .map(|i| match c3d.analog.labels.get(i) {
+            Some(label) if !label.trim().is_empty() => label.trim().to_string(),
+            _ => format!("Channel_{}", i + 1),
+        })
+        .collect()
+}
@@ -1,5 +1,8 @@
 //! Structures to represent auxiliary data types that C3D files can be written to.
 
+pub mod bvh;
 pub mod trc;
 pub mod sto;
+pub mod csv;
+pub mod table;
 
@@ -1,16 +1,24 @@
 //! STO file formats are used by OpenSim to store table-based data such as joint angles, muscle activations, and muscle forces.
 //! They can be used to store ground reaction forces/moments and emg data as well.
+//! MOT files share the same format and can be written with the same struct.
 use crate::{C3d, C3dWriteError};
+use grid::Grid;
+#[cfg(feature = "fs")]
+use std::io::BufWriter;
 use std::io::Write;
+#[cfg(feature = "fs")]
 use std::path::PathBuf;
-use grid::Grid;
 
 /// The STO struct contains the data for writing an STO file.
 #[derive(Debug, Clone)]
 pub struct Sto {
+    /// Name written in the first line of the file.
+    /// The name of the written file is used if none is set.
     pub file_description: Option<String>,
     pub version: u8,
     pub in_degrees: bool,
+    /// Number of the first row, counted at the data rate and starting at 1.
+    /// It sets the time of the first row.
     pub first_frame: usize,
     pub data_rate: f32,
     pub column_names: Vec<String>,
@@ -18,90 +26,148 @@ pub struct Sto {
 }
 
 impl Sto {
+    /// Creates an STO file with one column per analog channel, at the analog rate.
     pub fn from_c3d(c3d: &C3d) -> Self {
+        let first_frame = c3d.points.first_frame.max(1) as usize;
+        let samples_per_frame = c3d.analog.samples_per_channel_per_frame.max(1) as usize;
         Sto {
             file_description: None,
             version: 1,
             in_degrees: false,
-            first_frame: c3d.points.first_frame as usize,
-            column_names: c3d.analog.labels.clone(),
+            first_frame: (first_frame - 1) * samples_per_frame + 1,
+            column_names: c3d
+                .analog
+                .labels
+                .iter()
+                .map(|label| label.trim().to_string())
+                .collect(),
             data_rate: c3d.analog.rate,
             data: c3d.analog.analog.clone(),
         }
     }
 
+    /// Creates an STO file with the ground reaction force and center of pressure
+    /// of every force platform, at the point frame rate.
+    /// The columns of each platform are named as OpenSim external loads expect,
+    /// e.g. `1_ground_force_vx` to `1_ground_force_pz` for the first platform.
+    /// The center of pressure is stored in the X-Y plane of the platform, with a Z of 0.
+    /// Frames without analog data for a platform are NaN, as is the center of pressure
+    /// of frames without vertical force.
+    pub fn forces_from_c3d(c3d: &C3d) -> Self {
+        let num_frames = c3d.points.rows();
+        let num_platforms = c3d.forces.len();
+        let mut column_names = Vec::new();
+        for platform in 1..=num_platforms {
+            for column in ["vx", "vy", "vz", "px", "py", "pz"] {
+                column_names.push(format!("{}_ground_force_{}", platform, column));
+            }
+        }
+        let mut data = Vec::with_capacity(num_frames * column_names.len());
+        for frame in 0..num_frames {
+            for platform in 0..num_platforms {
+                match c3d.force(platform, frame) {
+                    Some(force) => {
+                        data.extend(force.iter().map(|&x| x as f64));
+                        match c3d.center_of_pressure(platform, frame) {
+                            Some(center_of_pressure) => {
+                                data.extend(center_of_pressure.iter().map(|&x| x as f64));
+                                data.push(0.);
+                            }
+                            None => data.extend([f64::NAN; 3]),
+                        }
+                    }
+                    None => data.extend([f64::NAN; 6]),
+                }
+            }
+        }
+        Sto {
+            file_description: None,
+            version: 1,
+            in_degrees: false,
+            first_frame: c3d.points.first_frame.max(1) as usize,
+            data_rate: c3d.points.frame_rate,
+            data: match column_names.len() {
+                0 => Grid::new(0, 0),
+                columns => Grid::from_vec(data, columns),
+            },
+            column_names,
+        }
+    }
+
+    /// Writes the STO file. Both the `.sto` and `.mot` extensions are accepted.
+    #[cfg(feature = "fs")]
     pub fn write(&self, file_name: PathBuf) -> Result<(), C3dWriteError> {
         if file_name.is_dir() {
             return Err(C3dWriteError::InvalidFilePath(file_name));
         }
-        if !file_name
+        let extension = file_name
             .extension()
-            .unwrap()
-            .to_str()
-            .unwrap()
-            .to_lowercase()
-            .eq("sto")
-        {
-            return Err(C3dWriteError::InvalidFileExtension(
-                file_name.extension().unwrap().to_str().unwrap().to_string(),
-            ));
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if extension != "sto" && extension != "mot" {
+            return Err(C3dWriteError::InvalidFileExtension(extension));
         }
-        let mut file = std::fs::File::create(file_name.clone()).map_err(|e| {
-            C3dWriteError::WriteError(
-                file_name.clone(),
-                std::io::Error::new(std::io::ErrorKind::Other, e),
-            )
-        })?;
+        let file = std::fs::File::create(file_name.clone())
+            .map_err(|e| C3dWriteError::WriteError(file_name.clone(), e))?;
+        let mut writer = BufWriter::new(file);
+        match &self.file_description {
+            Some(_) => self.write_to(&mut writer)?,
+            None => {
+                let mut sto = self.clone();
+                sto.file_description = file_name
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string());
+                sto.write_to(&mut writer)?;
+            }
+        }
+        writer
+            .flush()
+            .map_err(|e| C3dWriteError::WriteError(file_name.clone(), e))?;
+        Ok(())
+    }
+
+    /// Writes the STO file to any `Write` sink, such as a `Vec<u8>`.
+    /// The column count in the header includes the time column.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), C3dWriteError> {
+        let (num_rows, num_columns) = self.data.size();
         let mut header = String::new();
-        let description = match &self.file_description {
-            None => file_name.to_str().unwrap().to_string(),
-            Some(description) => description.clone(),
-        };
         let degrees = if self.in_degrees { "yes" } else { "no" };
         header.push_str(&format!(
             "{}\n\
-             version: {}\n\
-             nRows: {}\n\
-             nColumns: {}\n\
-             inDegrees: {}\n\
+             version={}\n\
+             nRows={}\n\
+             nColumns={}\n\
+             inDegrees={}\n\
              endheader\n",
-            description,
+            self.file_description.clone().unwrap_or_default(),
             self.version,
-            self.data.size().0,
-            self.data.size().1,
+            num_rows,
+            num_columns + 1,
             degrees,
         ));
-        header.push_str("time\t");
-        for i in 0..self.data.size().1 {
-            if i >= self.column_names.len() {
-                header.push_str(&format!("Column_{}\t", i));
-            } else {
-                header.push_str(&format!("{}\t", self.column_names[i]));
+        header.push_str("time");
+        for i in 0..num_columns {
+            match self.column_names.get(i) {
+                Some(name) => header.push_str(&format!("\t{}", name)),
+                None => header.push_str(&format!("\tColumn_{}", i)),
             }
         }
-        header.push_str("\n");
-        file.write_all(header.as_bytes()).map_err(|e| {
-            C3dWriteError::WriteError(
-                file_name.clone(),
-                std::io::Error::new(std::io::ErrorKind::Other, e),
-            )
-        })?;
-        for row in 0..self.data.size().0 {
-            let mut row_string = String::new();
-            row_string.push_str(&format!(
-                "{}\t",
-                (row + self.first_frame) as f32 / self.data_rate
-            ));
-            for column in 0..self.data.size().1 {
-                row_string.push_str(&format!("{}\t", self.data[(row, column)]));
+        header.push('\n');
+        writer
+            .write_all(header.as_bytes())
+            .map_err(C3dWriteError::WriteHeaderError)?;
+        for row in 0..num_rows {
+            let mut row_string = format!(
+                "{}",
+                (row + self.first_frame.max(1) - 1) as f32 / self.data_rate
+            );
+            for column in 0..num_columns {
+                row_string.push_str(&format!("\t{}", self.data[(row, column)]));
             }
-            row_string.push_str("\n");
-            file.write_all(row_string.as_bytes()).map_err(|e| {
-                C3dWriteError::WriteError(
-                    file_name.clone(),
-                    std::io::Error::new(std::io::ErrorKind::Other, e),
-                )
-            })?;
+            row_string.push('\n');
+            writer
+                .write_all(row_string.as_bytes())
+                .map_err(C3dWriteError::WriteDataError)?;
         }
         Ok(())
     }
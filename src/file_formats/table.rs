@@ -0,0 +1,72 @@
+//! Tables hold the same columns as the CSV exports as vectors of numbers, to be loaded
+//! into data frame libraries such as Polars without writing and re-parsing text.
+use crate::file_formats::csv::{Csv, CsvValue};
+use crate::C3d;
+
+/// A table of numeric columns, all of the same length.
+/// Invalid points are stored as NaN, which Polars turns into nulls with `fill_nan(None)`.
+///
+/// # Examples
+/// ```
+/// use c3dio::prelude::*;
+/// use c3dio::Table;
+///
+/// let c3d = C3d::load("tests/data/short.c3d").unwrap();
+/// let table = Table::from_c3d(&c3d);
+/// let time = table.column("Time").unwrap();
+/// assert_eq!(time.len(), c3d.points.rows());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    pub column_names: Vec<String>,
+    pub columns: Vec<Vec<f64>>,
+}
+
+impl Table {
+    /// Creates a table with the columns of `Csv::points_from_c3d`.
+    pub fn points_from_c3d(c3d: &C3d) -> Self {
+        Table::from_csv(&Csv::points_from_c3d(c3d))
+    }
+
+    /// Creates a table with the columns of `Csv::analog_from_c3d`.
+    pub fn analog_from_c3d(c3d: &C3d) -> Self {
+        Table::from_csv(&Csv::analog_from_c3d(c3d))
+    }
+
+    /// Creates a table with the columns of `Csv::from_c3d`: the frame number, the time,
+    /// the X, Y and Z columns of each point and the analog samples of each frame.
+    pub fn from_c3d(c3d: &C3d) -> Self {
+        Table::from_csv(&Csv::from_c3d(c3d))
+    }
+
+    /// Converts the rows of a CSV file to columns. Empty cells and text that is
+    /// not a number are stored as NaN.
+    pub fn from_csv(csv: &Csv) -> Self {
+        let mut columns = vec![Vec::with_capacity(csv.rows.len()); csv.column_names.len()];
+        for row in &csv.rows {
+            for (column, value) in columns.iter_mut().zip(row) {
+                column.push(match value {
+                    CsvValue::F32(number) => *number as f64,
+                    CsvValue::F64(number) => *number,
+                    CsvValue::Text(text) => text.trim().parse().unwrap_or(f64::NAN),
+                    CsvValue::Empty => f64::NAN,
+                });
+            }
+        }
+        Table {
+            column_names: csv.column_names.clone(),
+            columns,
+        }
+    }
+
+    /// Returns the values of the column with the given name.
+    pub fn column(&self, name: &str) -> Option<&[f64]> {
+        let index = self.column_names.iter().position(|x| x == name)?;
+        self.columns.get(index).map(|column| column.as_slice())
+    }
+
+    /// Returns the number of rows of the table.
+    pub fn rows(&self) -> usize {
+        self.columns.first().map_or(0, |column| column.len())
+    }
+}
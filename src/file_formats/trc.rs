@@ -1,18 +1,26 @@
 //! TRC file format is a Motion Analysis Corporation file format for storing 3D marker data.
 //! It is also used by OpenSim to store marker data.
+use crate::builder::{C3dBuilder, C3dBuilderError};
 use crate::C3d;
-use crate::C3dWriteError;
-use std::io::Write;
+use crate::{C3dParseError, C3dWriteError};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+#[cfg(feature = "fs")]
+use std::io::{BufReader, BufWriter};
 use std::path::PathBuf;
 
 use crate::data::MarkerPoint;
 use grid::Grid;
 
-/// The TRC struct contains the data for writing a TRC file.
+/// The TRC struct contains the data for writing and reading a TRC file.
+/// Each marker is written as an X, Y and Z column, and invalid points are left empty,
+/// which OpenSim reads as missing data.
 #[derive(Debug, Clone)]
 pub struct Trc {
     pub path_file_type: u8,
     pub path_file_type_description: String,
+    /// Name written in the first line of the file.
+    /// The name of the written file is used if none is set.
     pub file_name: Option<PathBuf>,
     pub data_rate: f32,
     pub camera_rate: f32,
@@ -30,11 +38,13 @@ impl Trc {
         let data_rate = c3d.points.frame_rate;
         let camera_rate = c3d.points.frame_rate;
         let num_frames = c3d.points.size().0;
-        let units = c3d.points.units.clone();
-        let mut marker_names = c3d.points.labels.clone();
-        if marker_names.len() > c3d.points.size().1 {
-            marker_names = marker_names[0..c3d.points.size().1].to_vec();
-        }
+        let units = c3d.points.units;
+        let marker_names = (0..c3d.points.size().1)
+            .map(|i| match c3d.points.labels.get(i) {
+                Some(label) if !label.trim().is_empty() => label.trim().to_string(),
+                _ => format!("M{}", i + 1),
+            })
+            .collect();
         let first_frame = c3d.points.first_frame as usize;
         let data = c3d.points.points.clone();
         Trc {
@@ -51,89 +61,250 @@ impl Trc {
         }
     }
 
+    /// Builds a C3d struct with the markers of the TRC file as points.
+    /// The points are stored as floats in the units of the TRC file,
+    /// and missing points are marked as invalid.
+    pub fn to_c3d(&self) -> Result<C3d, C3dBuilderError> {
+        let num_markers = self.data.size().1;
+        let labels: Vec<&str> = (0..num_markers)
+            .map(|i| self.marker_names.get(i).map_or("", |name| name.as_str()))
+            .collect();
+        let first_frame = u16::try_from(self.first_frame.max(1))
+            .map_err(|_| C3dBuilderError::TooManyFrames(self.first_frame))?;
+        let mut builder = C3dBuilder::new()
+            .point_labels(&labels)
+            .point_units(&self.units.iter().collect::<String>())
+            .frame_rate(self.data_rate)
+            .first_frame(first_frame);
+        for i in 0..self.data.size().0 {
+            let frame: Vec<MarkerPoint> = self.data.iter_row(i).copied().collect();
+            builder = builder.push_marker_frame(&frame, &[])?;
+        }
+        builder.build()
+    }
+
+    /// Reads a TRC file, such as one written by OpenSim.
+    #[cfg(feature = "fs")]
+    pub fn load(file_name: PathBuf) -> Result<Trc, C3dParseError> {
+        let file = std::fs::File::open(file_name).map_err(C3dParseError::ReadError)?;
+        Trc::from_reader(BufReader::new(file))
+    }
+
+    /// Reads a TRC file from any `BufRead` source.
+    /// Empty or `NaN` coordinates are read as invalid points with a residual of -1.
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Trc, C3dParseError> {
+        let mut lines = Vec::new();
+        for line in reader.lines() {
+            lines.push(line.map_err(C3dParseError::ReadError)?);
+        }
+        let invalid_line = |i: usize| {
+            C3dParseError::InvalidTrcLine(i + 1, lines.get(i).cloned().unwrap_or_default())
+        };
+        if lines.len() < 5 {
+            return Err(invalid_line(lines.len()));
+        }
+
+        let first_line: Vec<&str> = lines[0].split('\t').collect();
+        if first_line[0].trim() != "PathFileType" || first_line.len() < 3 {
+            return Err(invalid_line(0));
+        }
+        let path_file_type = first_line[1].trim().parse().map_err(|_| invalid_line(0))?;
+        let path_file_type_description = first_line[2].trim().to_string();
+        let file_name = first_line
+            .get(3)
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+            .map(PathBuf::from);
+
+        let header: HashMap<&str, &str> = lines[1]
+            .split('\t')
+            .map(|key| key.trim())
+            .zip(lines[2].split('\t').map(|value| value.trim()))
+            .collect();
+        let data_rate: f32 = header
+            .get("DataRate")
+            .ok_or_else(|| invalid_line(1))?
+            .parse()
+            .map_err(|_| invalid_line(2))?;
+        let camera_rate: f32 = match header.get("CameraRate") {
+            Some(rate) => rate.parse().map_err(|_| invalid_line(2))?,
+            None => data_rate,
+        };
+        let num_markers: usize = header
+            .get("NumMarkers")
+            .ok_or_else(|| invalid_line(1))?
+            .parse()
+            .map_err(|_| invalid_line(2))?;
+        // every marker takes three columns after the frame and time, on the line of the
+        // marker names or on the data lines, so a count beyond them is not allocated
+        let columns = lines[3..]
+            .iter()
+            .map(|line| line.split('\t').count())
+            .max()
+            .unwrap_or(0);
+        if num_markers > columns.saturating_sub(2).div_ceil(3) {
+            return Err(invalid_line(2));
+        }
+        let mut units = [' '; 4];
+        for (i, c) in header
+            .get("Units")
+            .ok_or_else(|| invalid_line(1))?
+            .chars()
+            .take(4)
+            .enumerate()
+        {
+            units[i] = c;
+        }
+
+        let mut marker_names: Vec<String> = lines[3]
+            .split('\t')
+            .skip(2)
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+            .map(|name| name.to_string())
+            .collect();
+        if marker_names.len() > num_markers {
+            return Err(invalid_line(3));
+        }
+        for i in marker_names.len()..num_markers {
+            marker_names.push(format!("M{}", i + 1));
+        }
+
+        let mut points = Vec::new();
+        let mut first_frame = None;
+        let mut num_frames = 0;
+        for (i, line) in lines.iter().enumerate().skip(5) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let values: Vec<&str> = line.split('\t').map(|value| value.trim()).collect();
+            if first_frame.is_none() {
+                first_frame = Some(values[0].parse::<usize>().map_err(|_| invalid_line(i))?);
+            }
+            for marker in 0..num_markers {
+                let mut point = MarkerPoint::default();
+                for axis in 0..3 {
+                    point[axis] = match values.get(2 + 3 * marker + axis) {
+                        None | Some(&"") => f32::NAN,
+                        Some(value) => value.parse::<f32>().map_err(|_| invalid_line(i))?,
+                    };
+                }
+                if point.iter().any(|value| value.is_nan()) {
+                    point = MarkerPoint {
+                        residual: -1.,
+                        ..MarkerPoint::default()
+                    };
+                }
+                points.push(point);
+            }
+            num_frames += 1;
+        }
+        let data = match num_markers {
+            0 => Grid::new(0, 0),
+            _ => Grid::from_vec(points, num_markers),
+        };
+        Ok(Trc {
+            path_file_type,
+            path_file_type_description,
+            file_name,
+            data_rate,
+            camera_rate,
+            num_frames,
+            units,
+            marker_names,
+            first_frame: first_frame.unwrap_or(1),
+            data,
+        })
+    }
+
+    #[cfg(feature = "fs")]
     pub fn write(&self, file_name: PathBuf) -> Result<(), C3dWriteError> {
         if file_name.is_dir() {
             return Err(C3dWriteError::InvalidFilePath(file_name));
         }
-        if !file_name
+        let extension = file_name
             .extension()
-            .unwrap()
-            .to_str()
-            .unwrap()
-            .to_lowercase()
-            .eq("trc")
-        {
-            return Err(C3dWriteError::InvalidFileExtension(
-                file_name.extension().unwrap().to_str().unwrap().to_string(),
-            ));
-        }
-        let mut file = std::fs::File::create(file_name.clone()).map_err(|e| {
-            C3dWriteError::WriteError(
-                file_name.clone(),
-                std::io::Error::new(std::io::ErrorKind::Other, e),
-            )
-        })?;
+            .map(|extension| extension.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if !extension.to_lowercase().eq("trc") {
+            return Err(C3dWriteError::InvalidFileExtension(extension));
+        }
+        let file = std::fs::File::create(file_name.clone())
+            .map_err(|e| C3dWriteError::WriteError(file_name.clone(), e))?;
+        let mut writer = BufWriter::new(file);
+        match &self.file_name {
+            Some(_) => self.write_to(&mut writer)?,
+            None => {
+                let mut trc = self.clone();
+                trc.file_name = file_name.file_name().map(PathBuf::from);
+                trc.write_to(&mut writer)?;
+            }
+        }
+        writer
+            .flush()
+            .map_err(|e| C3dWriteError::WriteError(file_name.clone(), e))?;
+        Ok(())
+    }
+
+    /// Writes the TRC file to any `Write` sink, such as a `Vec<u8>`.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), C3dWriteError> {
+        let num_markers = self.data.size().1;
         let mut header = String::new();
         header.push_str(&format!(
-            "PathFileType\t{}\t{}\t",
-            self.path_file_type, self.path_file_type_description,
+            "PathFileType\t{}\t{}\t{}\n",
+            self.path_file_type,
+            self.path_file_type_description,
+            self.file_name
+                .as_ref()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
         ));
-        header.push_str(file_name.to_string_lossy().to_string().as_str());
-        header.push_str("\n");
         header.push_str("DataRate\tCameraRate\tNumFrames\tNumMarkers\tUnits\tOrigDataRate\tOrigDataStartFrame\tOrigNumFrames\n");
         header.push_str(&format!(
             "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
             self.data_rate,
             self.camera_rate,
             self.num_frames,
-            self.marker_names.len(),
+            num_markers,
             self.units.iter().collect::<String>().trim(),
             self.data_rate,
             self.first_frame,
             self.num_frames,
         ));
-        header.push_str("Frame#\tTime\t");
-        for i in 0..self.data.size().1 {
-            if i >= self.marker_names.len() {
-                header.push_str(&format!("{}\t\t\t", i));
-            } else {
-                header.push_str(&format!("{}\t\t\t", self.marker_names[i].trim()));
+        header.push_str("Frame#\tTime");
+        for i in 0..num_markers {
+            match self.marker_names.get(i) {
+                Some(name) => header.push_str(&format!("\t{}\t\t", name.trim())),
+                None => header.push_str(&format!("\tM{}\t\t", i + 1)),
             }
         }
-        header.push_str("\n");
-        header.push_str("\t\t");
-        for i in 0..self.data.size().1 {
-            header.push_str(&format!("X{}\tY{}\tZ{}\t", i + 1, i + 1, i + 1));
+        header.push_str("\n\t");
+        for i in 0..num_markers {
+            header.push_str(&format!("\tX{}\tY{}\tZ{}", i + 1, i + 1, i + 1));
         }
         header.push_str("\n\n");
-        file.write_all(header.as_bytes()).map_err(|e| {
-            C3dWriteError::WriteError(
-                file_name.clone(),
-                std::io::Error::new(std::io::ErrorKind::Other, e),
-            )
-        })?;
-        for i in 0..self.num_frames {
-            let mut line = String::new();
-            line.push_str(&format!(
-                "{}\t{}\t",
-                i + self.first_frame,
-                (i + self.first_frame) as f32 / self.data_rate
-            ));
-            for j in 0..self.marker_names.len() {
-                line.push_str(&format!(
-                    "{}\t{}\t{}\t",
-                    self.data[(i, j)][0],
-                    self.data[(i, j)][1],
-                    self.data[(i, j)][2]
-                ));
+        writer
+            .write_all(header.as_bytes())
+            .map_err(C3dWriteError::WriteHeaderError)?;
+        for i in 0..self.num_frames.min(self.data.size().0) {
+            let frame = i + self.first_frame;
+            let mut line = format!(
+                "{}\t{}",
+                frame,
+                frame.saturating_sub(1) as f32 / self.data_rate
+            );
+            for j in 0..num_markers {
+                let point = &self.data[(i, j)];
+                if point.is_valid() {
+                    line.push_str(&format!("\t{}\t{}\t{}", point[0], point[1], point[2]));
+                } else {
+                    line.push_str("\t\t\t");
+                }
             }
-            line.push_str("\n");
-            file.write_all(line.as_bytes()).map_err(|e| {
-                C3dWriteError::WriteError(
-                    file_name.clone(),
-                    std::io::Error::new(std::io::ErrorKind::Other, e),
-                )
-            })?;
+            line.push('\n');
+            writer
+                .write_all(line.as_bytes())
+                .map_err(C3dWriteError::WriteDataError)?;
         }
         Ok(())
     }
@@ -0,0 +1,232 @@
+//! Zero-lag Butterworth filters for marker trajectories and analog channels.
+use crate::data::MarkerPoint;
+use crate::C3dFilterError;
+use std::f64::consts::PI;
+
+/// The frequencies passed by a `Butterworth` filter, in Hz.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FilterBand {
+    /// Passes frequencies below the cutoff.
+    LowPass(f64),
+    /// Passes frequencies above the cutoff.
+    HighPass(f64),
+    /// Passes frequencies between the two cutoffs, as a high-pass and a
+    /// low-pass filter in series.
+    BandPass(f64, f64),
+}
+
+/// A Butterworth filter, applied forwards and backwards so that it does not shift
+/// the signal in time.
+///
+/// The order is that of a single pass, so running the filter in both directions
+/// doubles it, and a cutoff is where a single pass attenuates by 3 dB.
+/// Biomechanics software often uses a second order low-pass filter at 6 Hz for
+/// marker trajectories.
+///
+/// # Examples
+/// ```
+/// use c3dio::prelude::*;
+/// use c3dio::Butterworth;
+///
+/// let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+/// c3d.filter_points(&Butterworth::low_pass(6., 2)).unwrap();
+/// c3d.filter_analog(&Butterworth::band_pass(20., 400., 2)).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Butterworth {
+    pub band: FilterBand,
+    pub order: usize,
+}
+
+impl Butterworth {
+    pub fn low_pass(cutoff: f64, order: usize) -> Self {
+        Butterworth {
+            band: FilterBand::LowPass(cutoff),
+            order,
+        }
+    }
+
+    pub fn high_pass(cutoff: f64, order: usize) -> Self {
+        Butterworth {
+            band: FilterBand::HighPass(cutoff),
+            order,
+        }
+    }
+
+    pub fn band_pass(low_cutoff: f64, high_cutoff: f64, order: usize) -> Self {
+        Butterworth {
+            band: FilterBand::BandPass(low_cutoff, high_cutoff),
+            order,
+        }
+    }
+
+    /// Filters samples taken at the given rate forwards and backwards.
+    /// The ends are extended by reflecting the samples about the first and last one,
+    /// like SciPy's `filtfilt`, to reduce the transients there.
+    pub fn filtfilt(&self, samples: &[f64], rate: f64) -> Result<Vec<f64>, C3dFilterError> {
+        let sections = self.sections(rate)?;
+        Ok(filtfilt(&sections, samples))
+    }
+
+    /// Filters each axis of a marker trajectory, taken at the given rate.
+    /// Every run of valid samples is filtered on its own, so the samples next to
+    /// a gap are not pulled towards the invalid samples in it, and runs of fewer
+    /// than two samples are left as they are. Residuals and cameras are kept.
+    pub fn filter_points(
+        &self,
+        points: &mut [MarkerPoint],
+        rate: f64,
+    ) -> Result<(), C3dFilterError> {
+        let sections = self.sections(rate)?;
+        filter_points(&sections, points);
+        Ok(())
+    }
+
+    /// Returns the second order sections of a single pass of the filter.
+    pub(crate) fn sections(&self, rate: f64) -> Result<Vec<Section>, C3dFilterError> {
+        if self.order == 0 {
+            return Err(C3dFilterError::ZeroOrder);
+        }
+        let nyquist = rate / 2.;
+        let check = |cutoff: f64| match cutoff > 0. && cutoff < nyquist {
+            true => Ok(cutoff),
+            false => Err(C3dFilterError::InvalidCutoff(cutoff, nyquist)),
+        };
+        Ok(match self.band {
+            FilterBand::LowPass(cutoff) => sections(self.order, check(cutoff)? / rate, false),
+            FilterBand::HighPass(cutoff) => sections(self.order, check(cutoff)? / rate, true),
+            FilterBand::BandPass(low, high) => {
+                let (low, high) = (check(low)?, check(high)?);
+                if low >= high {
+                    return Err(C3dFilterError::InvalidBand(low, high));
+                }
+                let mut band = sections(self.order, low / rate, true);
+                band.extend(sections(self.order, high / rate, false));
+                band
+            }
+        })
+    }
+}
+
+/// A second order section in transposed direct form II, normalised so that `a0` is 1.
+/// First order sections have `b[2]` and `a[1]` set to 0.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Section {
+    b: [f64; 3],
+    a: [f64; 2],
+}
+
+impl Section {
+    /// Returns the state of the section after a long run of the given sample.
+    fn steady_state(&self, sample: f64) -> [f64; 2] {
+        let gain = (self.b[0] + self.b[1] + self.b[2]) / (1. + self.a[0] + self.a[1]);
+        let output = gain * sample;
+        let z2 = self.b[2] * sample - self.a[1] * output;
+        [output - self.b[0] * sample, z2]
+    }
+
+    /// Filters the samples in place, starting from the steady state of the first sample.
+    fn filter(&self, samples: &mut [f64]) {
+        let mut z = match samples.first() {
+            Some(&first) => self.steady_state(first),
+            None => return,
+        };
+        for sample in samples.iter_mut() {
+            let input = *sample;
+            let output = self.b[0] * input + z[0];
+            z[0] = self.b[1] * input - self.a[0] * output + z[1];
+            z[1] = self.b[2] * input - self.a[1] * output;
+            *sample = output;
+        }
+    }
+}
+
+/// Designs a Butterworth filter as second order sections with the bilinear transform,
+/// given the cutoff as a fraction of the sampling rate.
+fn sections(order: usize, cutoff: f64, high_pass: bool) -> Vec<Section> {
+    let k = (PI * cutoff).tan();
+    let mut sections = Vec::with_capacity(order.div_ceil(2));
+    if order % 2 == 1 {
+        let norm = 1. / (1. + k);
+        let b = match high_pass {
+            true => [norm, -norm, 0.],
+            false => [k * norm, k * norm, 0.],
+        };
+        sections.push(Section {
+            b,
+            a: [(k - 1.) * norm, 0.],
+        });
+    }
+    for pair in 0..order / 2 {
+        // the angle of the pair of poles from the negative real axis
+        let angle = match order % 2 {
+            0 => (2 * pair + 1) as f64 * PI / (2 * order) as f64,
+            _ => (pair + 1) as f64 * PI / order as f64,
+        };
+        let q = 1. / (2. * angle.cos());
+        let norm = 1. / (1. + k / q + k * k);
+        let b = match high_pass {
+            true => [norm, -2. * norm, norm],
+            false => [k * k * norm, 2. * k * k * norm, k * k * norm],
+        };
+        sections.push(Section {
+            b,
+            a: [2. * (k * k - 1.) * norm, (1. - k / q + k * k) * norm],
+        });
+    }
+    sections
+}
+
+/// Filters the samples forwards and backwards through every section,
+/// after reflecting them about their ends.
+pub(crate) fn filtfilt(sections: &[Section], samples: &[f64]) -> Vec<f64> {
+    let len = samples.len();
+    if len < 2 || sections.is_empty() {
+        return samples.to_vec();
+    }
+    let padding = (3 * (2 * sections.len() + 1)).min(len - 1);
+    let (first, last) = (samples[0], samples[len - 1]);
+    let mut padded = Vec::with_capacity(len + 2 * padding);
+    padded.extend((1..=padding).rev().map(|i| 2. * first - samples[i]));
+    padded.extend_from_slice(samples);
+    padded.extend((1..=padding).map(|i| 2. * last - samples[len - 1 - i]));
+    for section in sections {
+        section.filter(&mut padded);
+    }
+    padded.reverse();
+    for section in sections {
+        section.filter(&mut padded);
+    }
+    padded.reverse();
+    padded[padding..padding + len].to_vec()
+}
+
+/// Filters every run of valid samples of a marker trajectory on its own.
+pub(crate) fn filter_points(sections: &[Section], points: &mut [MarkerPoint]) {
+    let mut start = 0;
+    while start < points.len() {
+        if !points[start].is_valid() {
+            start += 1;
+            continue;
+        }
+        let end = points[start..]
+            .iter()
+            .position(|point| !point.is_valid())
+            .map_or(points.len(), |len| start + len);
+        for axis in 0..3 {
+            let samples: Vec<f64> = points[start..end]
+                .iter()
+                .map(|point| point.point[axis] as f64)
+                .collect();
+            for (point, sample) in points[start..end]
+                .iter_mut()
+                .zip(filtfilt(sections, &samples))
+            {
+                point.point[axis] = sample as f32;
+            }
+        }
+        start = end;
+    }
+}
@@ -1,5 +1,7 @@
 //! Contains force platform information in the form of the `ForcePlatforms` struct.
 //! Includes the C3d struct implementation and high-level functions for reading and writing C3D files.
+use crate::analog::Analog;
+use crate::kinematics::{add, cross, dot, normalize, rotate, subtract};
 use crate::parameters::{Parameter, ParameterData, Parameters};
 use crate::processor::Processor;
 use crate::{C3dParseError, C3dWriteError};
@@ -8,6 +10,7 @@ use std::ops::{Deref, DerefMut};
 use std::ops::{Index, IndexMut};
 
 #[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ForcePlatforms {
     pub force_platforms: Vec<ForcePlatform>,
     pub zero: [u16; 2],
@@ -42,6 +45,7 @@ impl ToString for ForcePlatforms {
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ForcePlatform {
     pub plate_type: ForcePlatformType,
     pub corners: ForcePlatformCorners,
@@ -50,6 +54,310 @@ pub struct ForcePlatform {
     pub cal_matrix: Option<[[f32; 6]; 6]>,
 }
 
+/// Ground reaction force, center of pressure and free moment of a force platform,
+/// with one element per analog sample, in the coordinate frame given by `frame`.
+/// The center of pressure and free moment are NaN for samples without vertical force.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ForcePlatformData {
+    pub force: Vec<[f32; 3]>,
+    pub center_of_pressure: Vec<[f32; 3]>,
+    /// The moment about the plate normal at the center of pressure, as a vector along the normal.
+    pub free_moment: Vec<[f32; 3]>,
+    pub frame: ForceFrame,
+}
+
+/// The coordinate frame the data of a force platform is expressed in.
+/// Inverse dynamics needs the forces in the same frame as the markers, which is the lab
+/// frame, while the plate frame is the one the platform measures in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ForceFrame {
+    /// The axes of the platform given by `ForcePlatform::axes`, with the origin at the
+    /// center of the working surface.
+    Plate,
+    /// The lab axes and origin, with the platform placed by FORCE_PLATFORM:CORNERS.
+    #[default]
+    Lab,
+}
+
+impl ForcePlatformData {
+    /// Returns the data expressed in another coordinate frame, using the corners of the
+    /// platform it was calculated for.
+    /// Returns `None` if the corners do not span a plane.
+    pub fn expressed_in(
+        &self,
+        platform: &ForcePlatform,
+        frame: ForceFrame,
+    ) -> Option<ForcePlatformData> {
+        if self.frame == frame {
+            return Some(self.clone());
+        }
+        let axes = platform.axes()?;
+        let center = platform.center();
+        let vector = |v: [f32; 3]| match frame {
+            ForceFrame::Lab => plate_to_lab(&axes, v),
+            ForceFrame::Plate => rotate(&axes, v),
+        };
+        let point = |p: [f32; 3]| match frame {
+            ForceFrame::Lab => add(center, vector(p)),
+            ForceFrame::Plate => vector(subtract(p, center)),
+        };
+        Some(ForcePlatformData {
+            force: self.force.iter().map(|&v| vector(v)).collect(),
+            center_of_pressure: self.center_of_pressure.iter().map(|&p| point(p)).collect(),
+            free_moment: self.free_moment.iter().map(|&v| vector(v)).collect(),
+            frame,
+        })
+    }
+}
+
+/// Expresses a vector given in the frame of a platform with the given axes in lab axes.
+fn plate_to_lab(axes: &[[f32; 3]; 3], vector: [f32; 3]) -> [f32; 3] {
+    let mut lab = [0.; 3];
+    for (axis, value) in axes.iter().zip(vector) {
+        for i in 0..3 {
+            lab[i] += axis[i] * value;
+        }
+    }
+    lab
+}
+
+impl ForcePlatform {
+    /// Creates a platform from its geometry, e.g. to add it to a file with
+    /// `C3dBuilder::force_platform`.
+    /// The corners of the working surface are in lab coordinates, numbered as described
+    /// by `axes`, and the origin is the vector from the center of the surface to the
+    /// transducer origin in platform coordinates. The channels are the analog channels,
+    /// counted from 1, in the order of FORCE_PLATFORM:CHANNEL: eight for type 3
+    /// platforms and six for the other types. Channels past the eighth are ignored.
+    pub fn new(
+        plate_type: ForcePlatformType,
+        corners: [[f32; 3]; 4],
+        origin: [f32; 3],
+        channels: &[u8],
+    ) -> ForcePlatform {
+        let mut platform_channels = [0; 8];
+        for (channel, &value) in platform_channels.iter_mut().zip(channels) {
+            *channel = value;
+        }
+        ForcePlatform {
+            plate_type,
+            corners: ForcePlatformCorners { corners },
+            origin: ForcePlatformOrigin { origin },
+            channels: platform_channels,
+            cal_matrix: None,
+        }
+    }
+
+    /// Sets the calibration matrix of a type 4 platform from its rows, so that
+    /// calibrated channel `i` is the sum of `rows[i][j]` times channel `j`.
+    /// The matrix is stored in `cal_matrix` with the row index varying fastest,
+    /// as in FORCE_PLATFORM:CAL_MATRIX.
+    pub fn with_cal_matrix(self, rows: [[f32; 6]; 6]) -> ForcePlatform {
+        let columns = std::array::from_fn(|column| rows.map(|row| row[column]));
+        ForcePlatform {
+            cal_matrix: Some(columns),
+            ..self
+        }
+    }
+
+    /// Returns the number of channels the platform reads, from the start of `channels`.
+    pub(crate) fn num_channels(&self) -> usize {
+        match self.plate_type {
+            ForcePlatformType::Type3 => 8,
+            _ => 6,
+        }
+    }
+
+    /// Returns the center of the working surface in lab coordinates.
+    pub fn center(&self) -> [f32; 3] {
+        let mut center = [0.; 3];
+        for corner in self.corners.iter() {
+            for i in 0..3 {
+                center[i] += corner[i] / 4.;
+            }
+        }
+        center
+    }
+
+    /// Returns the axes of the platform in lab coordinates as rows.
+    /// Following the C3D convention, the X axis points from corner 2 to corner 1
+    /// and the Y axis from corner 4 to corner 1, so that Z points into the plate
+    /// when the corners are numbered clockwise from above.
+    /// Returns `None` if the corners do not span a plane.
+    pub fn axes(&self) -> Option<[[f32; 3]; 3]> {
+        let x = normalize(subtract(self.corners[0], self.corners[1]))?;
+        let z = normalize(cross(x, subtract(self.corners[0], self.corners[3])))?;
+        let y = cross(z, x);
+        Some([x, y, z])
+    }
+
+    /// Calculates the force, center of pressure and free moment of every analog sample,
+    /// in lab coordinates.
+    /// The channels are applied the calibration matrix for type 4 platforms,
+    /// and the moments are transferred from the transducer origin to the working surface.
+    /// Returns `None` if a channel of the platform is not in the analog data
+    /// or the corners do not span a plane.
+    pub fn data(&self, analog: &Analog) -> Option<ForcePlatformData> {
+        self.data_in(analog, ForceFrame::Lab)
+    }
+
+    /// Calculates the force, center of pressure and free moment of every analog sample
+    /// in the given coordinate frame, as `data` does in lab coordinates.
+    /// Data in the plate frame does not need the corners to span a plane.
+    pub fn data_in(&self, analog: &Analog, frame: ForceFrame) -> Option<ForcePlatformData> {
+        let num_channels = self.num_channels();
+        let mut columns = Vec::with_capacity(num_channels);
+        for &channel in &self.channels[..num_channels] {
+            if channel == 0 || channel as usize > analog.cols() {
+                return None;
+            }
+            columns.push(channel as usize - 1);
+        }
+        let mut data = ForcePlatformData {
+            frame: ForceFrame::Plate,
+            ..ForcePlatformData::default()
+        };
+        for sample in 0..analog.rows() {
+            let mut values = [0f32; 8];
+            for (value, &column) in values.iter_mut().zip(&columns) {
+                *value = analog[sample][column] as f32;
+            }
+            let (force, center_of_pressure, free_moment) = self.sample(values);
+            data.force.push(force);
+            data.center_of_pressure.push(center_of_pressure);
+            data.free_moment.push([0., 0., free_moment]);
+        }
+        data.expressed_in(self, frame)
+    }
+
+    /// Combines the analog channels of one sample into the force and the moment about
+    /// the center of the working surface, in platform coordinates.
+    /// The channels are in the order of `FORCE_PLATFORM:CHANNEL`, so type 3 platforms use
+    /// all eight values and the other types the first six.
+    pub fn force_and_moment(&self, values: [f32; 8]) -> ([f32; 3], [f32; 3]) {
+        let origin = *self.origin;
+        let (force, moment, origin) = match self.plate_type {
+            ForcePlatformType::Type1 => {
+                // the center of pressure and free moment are measured directly
+                let force = [values[0], values[1], values[2]];
+                let (x, y) = (values[3], values[4]);
+                let moment = [
+                    y * force[2],
+                    -x * force[2],
+                    x * force[1] - y * force[0] + values[5],
+                ];
+                return (force, moment);
+            }
+            ForcePlatformType::Type3 => {
+                // ORIGIN holds the sensor offsets and the depth of the sensor plane
+                let [a, b, depth] = origin;
+                let force = [
+                    values[0] + values[1],
+                    values[2] + values[3],
+                    values[4] + values[5] + values[6] + values[7],
+                ];
+                let moment = [
+                    b * (values[4] + values[5] - values[6] - values[7]),
+                    a * (-values[4] + values[5] + values[6] - values[7]),
+                    b * (-values[0] + values[1]) + a * (values[2] - values[3]),
+                ];
+                (force, moment, [0., 0., depth.abs()])
+            }
+            ForcePlatformType::Type2 => (
+                [values[0], values[1], values[2]],
+                [values[3], values[4], values[5]],
+                origin,
+            ),
+            ForcePlatformType::Type4 => {
+                let mut calibrated = [0f32; 6];
+                if let Some(cal_matrix) = &self.cal_matrix {
+                    // the first index of CAL_MATRIX is the row, and varies fastest in the file
+                    for (row, calibrated) in calibrated.iter_mut().enumerate() {
+                        for column in 0..6 {
+                            *calibrated += cal_matrix[column][row] * values[column];
+                        }
+                    }
+                } else {
+                    calibrated.copy_from_slice(&values[..6]);
+                }
+                (
+                    [calibrated[0], calibrated[1], calibrated[2]],
+                    [calibrated[3], calibrated[4], calibrated[5]],
+                    origin,
+                )
+            }
+        };
+        // ORIGIN points from the surface down to the transducer origin,
+        // but some manufacturers store the opposite vector
+        let origin = if origin[2] < 0. {
+            [-origin[0], -origin[1], -origin[2]]
+        } else {
+            origin
+        };
+        let transferred = cross(origin, force);
+        let moment = [
+            moment[0] + transferred[0],
+            moment[1] + transferred[1],
+            moment[2] + transferred[2],
+        ];
+        (force, moment)
+    }
+
+    /// Calculates the force, the center of pressure relative to the center of the
+    /// working surface and the free moment of one sample, in platform coordinates.
+    fn sample(&self, values: [f32; 8]) -> ([f32; 3], [f32; 3], f32) {
+        let (force, moment) = self.force_and_moment(values);
+        if force[2] == 0. {
+            return (force, [f32::NAN; 3], f32::NAN);
+        }
+        let x = -moment[1] / force[2];
+        let y = moment[0] / force[2];
+        let free_moment = moment[2] - dot([x, y, 0.], [force[1], -force[0], 0.]);
+        (force, [x, y, 0.], free_moment)
+    }
+
+    /// Calculates the center of pressure of one sample relative to the center of
+    /// the working surface, in platform coordinates.
+    /// Returns `None` if there is no vertical force.
+    pub fn center_of_pressure(&self, values: [f32; 8]) -> Option<[f32; 2]> {
+        let (force, center_of_pressure, _) = self.sample(values);
+        match force[2] {
+            0. => None,
+            _ => Some([center_of_pressure[0], center_of_pressure[1]]),
+        }
+    }
+
+    /// Multiplies the corners and origin by a length conversion factor, and the
+    /// moment rows of the calibration matrix of type 4 platforms.
+    /// Returns the analog channels, counted from 0, that hold centers of pressure or
+    /// moments and have to be multiplied by the factor as well.
+    pub(crate) fn scale_lengths(&mut self, factor: f32) -> Vec<usize> {
+        for corner in self.corners.iter_mut() {
+            *corner = corner.map(|x| x * factor);
+        }
+        *self.origin = self.origin.map(|x| x * factor);
+        let channels = match (&self.plate_type, &mut self.cal_matrix) {
+            (ForcePlatformType::Type3, _) => return Vec::new(),
+            (ForcePlatformType::Type4, Some(cal_matrix)) => {
+                for column in cal_matrix.iter_mut() {
+                    for value in column[3..].iter_mut() {
+                        *value *= factor;
+                    }
+                }
+                return Vec::new();
+            }
+            _ => &self.channels[3..6],
+        };
+        channels
+            .iter()
+            .filter(|&&channel| channel != 0)
+            .map(|&channel| channel as usize - 1)
+            .collect()
+    }
+}
+
 impl PartialEq for ForcePlatform {
     fn eq(&self, other: &Self) -> bool {
         self.plate_type == other.plate_type
@@ -280,42 +588,6 @@ impl ForcePlatforms {
         Ok(bytes)
     }
 
-    pub(crate) fn force_from_analog(
-        &self,
-        analog: [f32; 8],
-        force_platform: usize,
-    ) -> Option<[f32; 3]> {
-        if force_platform < self.force_platforms.len() {
-            Some(
-                self.force_platforms[force_platform]
-                    .plate_type
-                    .force_from_analog(analog),
-            )
-        } else {
-            None
-        }
-    }
-
-    pub(crate) fn center_of_pressure_from_analog(
-        &self,
-        analog: [f32; 8],
-        force_platform: usize,
-    ) -> Option<[f32; 2]> {
-        if force_platform < self.force_platforms.len() {
-            Some(
-                self.force_platforms[force_platform]
-                    .plate_type
-                    .center_of_pressure_from_analog(
-                        analog,
-                        self.force_platforms[force_platform].origin.clone(),
-                        self.force_platforms[force_platform].corners.clone(),
-                    ),
-            )
-        } else {
-            None
-        }
-    }
-
     pub fn origin(&self, force_platform: usize) -> Option<&ForcePlatformOrigin> {
         if force_platform < self.force_platforms.len() {
             Some(&self.force_platforms[force_platform].origin)
@@ -326,6 +598,7 @@ impl ForcePlatforms {
 }
 
 #[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ForcePlatformType {
     Type1,
     #[default]
@@ -360,47 +633,10 @@ impl ForcePlatformType {
         }
         Ok(force_platform_type)
     }
-
-    fn force_from_analog(&self, analog: [f32; 8]) -> [f32; 3] {
-        match self {
-            ForcePlatformType::Type3 => {
-                return [
-                    analog[0] + analog[1],
-                    analog[2] + analog[3],
-                    analog[4] + analog[5] + analog[6] + analog[7],
-                ];
-            }
-            _ => {
-                return [analog[0], analog[1], analog[2]];
-            }
-        }
-    }
-
-    fn center_of_pressure_from_analog(
-        &self,
-        analog: [f32; 8],
-        origin: ForcePlatformOrigin,
-        corners: ForcePlatformCorners,
-    ) -> [f32; 2] {
-        match self {
-            ForcePlatformType::Type1 => [analog[3], analog[4]],
-            ForcePlatformType::Type3 => {
-                [(analog[0] + analog[1]) / 2., (analog[2] + analog[3]) / 2.]
-            }
-            _ => {
-                let origin = origin.origin;
-                let height = corners[0][2] - origin[2];
-                let x = (-height * analog[0] - analog[4]) / analog[2];
-                let y = (-height * analog[1] - analog[3]) / analog[2];
-                let x = -x;
-                let y = -y;
-                [x, y]
-            }
-        }
-    }
 }
 
 #[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ForcePlatformCorners {
     corners: [[f32; 3]; 4],
 }
@@ -500,6 +736,7 @@ impl ForcePlatformCorners {
 }
 
 #[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ForcePlatformOrigin {
     origin: [f32; 3],
 }
@@ -580,8 +817,7 @@ fn get_channels(
     used: u16,
     contains_type_3: bool,
 ) -> Result<Vec<[u8; 8]>, C3dParseError> {
-    let channel_parameter = parameters.remove_or_err("FORCE_PLATFORM", "CHANNEL");
-    let parameter = channel_parameter.unwrap();
+    let parameter = parameters.remove_or_err("FORCE_PLATFORM", "CHANNEL")?;
     match &parameter.data {
         ParameterData::Integer(data) => {
             let dimensions: Vec<usize> = parameter.dimensions.iter().map(|&x| x as usize).collect();
@@ -687,7 +923,13 @@ fn get_cal_matrix_vector(
     let mut count = 0;
     for i in 0..plate_type.len() {
         if plate_type[i] == ForcePlatformType::Type4 {
-            cal_matrix_vec.push(Some(cal_matrices[count]));
+            // the matrices are either stored for every platform or only for type 4 ones
+            let index = if cal_matrices.len() == plate_type.len() {
+                i
+            } else {
+                count
+            };
+            cal_matrix_vec[i] = Some(cal_matrices[index]);
             count += 1;
         }
     }
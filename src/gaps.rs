@@ -0,0 +1,110 @@
+//! Fills gaps of invalid samples in marker trajectories.
+use crate::data::MarkerPoint;
+use std::ops::Range;
+
+/// How the samples of a gap are reconstructed by `C3d::fill_gaps`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GapFill {
+    /// A cubic Hermite spline from the last valid sample before the gap to the first
+    /// valid sample after it, following the velocity of the trajectory at both ends.
+    Spline,
+    /// The trajectory of a donor marker with the given label, such as another marker
+    /// on the same segment, offset to meet the trajectory at both ends of the gap.
+    /// Gaps where the donor is not valid are left as they are.
+    Pattern(String),
+}
+
+/// Returns the frames of every gap of invalid samples that has a valid sample
+/// before and after it. Gaps at the start or end of the trial cannot be filled.
+pub(crate) fn gaps(points: &[MarkerPoint]) -> Vec<Range<usize>> {
+    let mut gaps = Vec::new();
+    let mut start = None;
+    let mut seen_valid = false;
+    for (frame, point) in points.iter().enumerate() {
+        match (point.is_valid(), start) {
+            (false, None) if seen_valid => start = Some(frame),
+            (true, Some(gap_start)) => {
+                gaps.push(gap_start..frame);
+                start = None;
+            }
+            _ => {}
+        }
+        seen_valid |= point.is_valid();
+    }
+    gaps
+}
+
+/// Fills a gap with a spline, using the samples next to the ends of the gap
+/// for the velocity if they are valid.
+pub(crate) fn fill_spline(points: &mut [MarkerPoint], gap: Range<usize>) {
+    let (before, after) = (gap.start - 1, gap.end);
+    let previous = match before > 0 && points[before - 1].is_valid() {
+        true => before - 1,
+        false => before,
+    };
+    let next = match after + 1 < points.len() && points[after + 1].is_valid() {
+        true => after + 1,
+        false => after,
+    };
+    let length = (after - before) as f32;
+    let (p0, p1) = (points[before].point, points[after].point);
+    let mut tangents = [[0.; 3]; 2];
+    for axis in 0..3 {
+        let velocity_before =
+            (p0[axis] - points[previous].point[axis]) / (before - previous).max(1) as f32;
+        let velocity_after = (points[next].point[axis] - p1[axis]) / (next - after).max(1) as f32;
+        tangents[0][axis] = velocity_before * length;
+        tangents[1][axis] = velocity_after * length;
+    }
+    for frame in gap {
+        let t = (frame - before) as f32 / length;
+        let (t2, t3) = (t * t, t * t * t);
+        let h00 = 2. * t3 - 3. * t2 + 1.;
+        let h10 = t3 - 2. * t2 + t;
+        let h01 = -2. * t3 + 3. * t2;
+        let h11 = t3 - t2;
+        let mut point = [0.; 3];
+        for axis in 0..3 {
+            point[axis] =
+                h00 * p0[axis] + h10 * tangents[0][axis] + h01 * p1[axis] + h11 * tangents[1][axis];
+        }
+        points[frame] = filled(point);
+    }
+}
+
+/// Fills a gap with the trajectory of a donor, offset by the distance between the
+/// trajectory and the donor at the ends of the gap, which is interpolated linearly.
+/// Returns `false` and leaves the gap as it is if the donor is not valid over the gap.
+pub(crate) fn fill_pattern(
+    points: &mut [MarkerPoint],
+    donor: &[MarkerPoint],
+    gap: Range<usize>,
+) -> bool {
+    let (before, after) = (gap.start - 1, gap.end);
+    if donor[before..=after].iter().any(|point| !point.is_valid()) {
+        return false;
+    }
+    let length = (after - before) as f32;
+    for frame in gap {
+        let t = (frame - before) as f32 / length;
+        let mut point = [0.; 3];
+        for (axis, value) in point.iter_mut().enumerate() {
+            let offset_before = points[before].point[axis] - donor[before].point[axis];
+            let offset_after = points[after].point[axis] - donor[after].point[axis];
+            *value = donor[frame].point[axis] + offset_before + (offset_after - offset_before) * t;
+        }
+        points[frame] = filled(point);
+    }
+    true
+}
+
+/// A reconstructed sample, valid with a residual of 0 and not seen by any camera,
+/// as written for interpolated samples by other software.
+fn filled(point: [f32; 3]) -> MarkerPoint {
+    MarkerPoint {
+        point,
+        residual: 0.,
+        cameras: [false; 7],
+    }
+}
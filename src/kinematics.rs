@@ -0,0 +1,54 @@
+//! Geometry helpers for computing segment orientations and joint angles from marker data.
+
+/// Builds an orthonormal frame from three non-collinear markers.
+/// The first axis points from `origin` towards `axis`, the third axis is normal
+/// to the plane containing all three markers, and the second axis completes
+/// the right-handed frame. The axes are returned as rows.
+/// Returns `None` if the markers are coincident or collinear.
+pub fn segment_frame(origin: [f32; 3], axis: [f32; 3], plane: [f32; 3]) -> Option<[[f32; 3]; 3]> {
+    let x = normalize(subtract(axis, origin))?;
+    let z = normalize(cross(x, subtract(plane, origin)))?;
+    let y = cross(z, x);
+    Some([x, y, z])
+}
+
+/// Calculates the angle in degrees of the rotation that takes the `proximal`
+/// frame onto the `distal` frame. Both frames are expected as returned by
+/// `segment_frame`.
+pub fn angle_between_frames(proximal: &[[f32; 3]; 3], distal: &[[f32; 3]; 3]) -> f32 {
+    let trace: f32 = (0..3).map(|i| dot(proximal[i], distal[i])).sum();
+    ((trace - 1.) / 2.).clamp(-1., 1.).acos().to_degrees()
+}
+
+/// Multiplies a vector by a rotation matrix given as rows.
+pub(crate) fn rotate(rotation: &[[f32; 3]; 3], a: [f32; 3]) -> [f32; 3] {
+    rotation.map(|row| dot(row, a))
+}
+
+pub(crate) fn add(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+pub(crate) fn subtract(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub(crate) fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub(crate) fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+pub(crate) fn normalize(a: [f32; 3]) -> Option<[f32; 3]> {
+    let length = dot(a, a).sqrt();
+    if length <= f32::EPSILON || !length.is_finite() {
+        return None;
+    }
+    Some([a[0] / length, a[1] / length, a[2] / length])
+}
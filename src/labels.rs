@@ -0,0 +1,98 @@
+//! Renaming of points to harmonize marker sets between labs and models.
+use std::collections::HashMap;
+
+/// The markers of the Plug-in Gait full body model, head to toe, left before right.
+/// SACR is placed instead of LPSI and RPSI in some labs.
+pub const PLUG_IN_GAIT_MARKERS: [&str; 40] = [
+    "LFHD", "RFHD", "LBHD", "RBHD", "C7", "T10", "CLAV", "STRN", "RBAK", "LSHO", "LUPA", "LELB",
+    "LFRM", "LWRA", "LWRB", "LFIN", "RSHO", "RUPA", "RELB", "RFRM", "RWRA", "RWRB", "RFIN", "LASI",
+    "RASI", "LPSI", "RPSI", "SACR", "LTHI", "LKNE", "LTIB", "LANK", "LHEE", "LTOE", "RTHI", "RKNE",
+    "RTIB", "RANK", "RHEE", "RTOE",
+];
+
+/// A named correspondence between the labels of a custom marker set and the
+/// Plug-in Gait labels, used to rename markers either way with `C3d::relabel_points`.
+///
+/// # Examples
+/// ```
+/// use c3dio::prelude::*;
+/// use c3dio::LabelPreset;
+///
+/// // the markers of this subject are labelled A22:LASI, A22:RASI and so on
+/// let mut c3d = C3d::load("tests/c3d_org_samples/sample_03/gait-pig.c3d").unwrap();
+/// let preset = LabelPreset::with_prefix("A22", "A22:");
+/// let report = c3d.relabel_points(&preset.to_plug_in_gait());
+/// assert!(report.duplicates.is_empty());
+/// assert!(c3d.points.get_by_label("LASI").is_some());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LabelPreset {
+    pub name: String,
+    /// The Plug-in Gait label of each custom label.
+    pub mapping: HashMap<String, String>,
+}
+
+impl LabelPreset {
+    /// Creates a preset from pairs of a custom label and its Plug-in Gait label.
+    pub fn new(name: &str, pairs: &[(&str, &str)]) -> Self {
+        LabelPreset {
+            name: name.to_string(),
+            mapping: pairs
+                .iter()
+                .map(|(custom, plug_in_gait)| (custom.to_string(), plug_in_gait.to_string()))
+                .collect(),
+        }
+    }
+
+    /// Creates a preset for a marker set that adds a prefix to the Plug-in Gait
+    /// labels, such as the subject name Vicon Nexus adds in `Subject:LASI`.
+    pub fn with_prefix(name: &str, prefix: &str) -> Self {
+        LabelPreset {
+            name: name.to_string(),
+            mapping: PLUG_IN_GAIT_MARKERS
+                .iter()
+                .map(|label| (format!("{}{}", prefix, label), label.to_string()))
+                .collect(),
+        }
+    }
+
+    /// Returns the mapping that renames the custom labels to Plug-in Gait labels.
+    pub fn to_plug_in_gait(&self) -> HashMap<String, String> {
+        self.mapping.clone()
+    }
+
+    /// Returns the mapping that renames Plug-in Gait labels to the custom labels.
+    /// If several custom labels share a Plug-in Gait label, the first in
+    /// alphabetical order is used.
+    pub fn to_custom(&self) -> HashMap<String, String> {
+        let mut pairs: Vec<(&String, &String)> = self.mapping.iter().collect();
+        pairs.sort();
+        let mut mapping = HashMap::new();
+        for (custom, plug_in_gait) in pairs {
+            mapping
+                .entry(plug_in_gait.clone())
+                .or_insert_with(|| custom.clone());
+        }
+        mapping
+    }
+}
+
+/// The outcome of renaming points with `C3d::relabel_points`.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RelabelReport {
+    /// The old and new label of each point that was renamed, in column order.
+    pub renamed: Vec<(String, String)>,
+    /// The labels of the mapping that no point has, sorted.
+    pub unmatched: Vec<String>,
+    /// The labels shared by more than one point after renaming, sorted.
+    pub duplicates: Vec<String>,
+}
+
+impl RelabelReport {
+    /// Whether every label of the mapping was found and the labels are still unique.
+    pub fn is_clean(&self) -> bool {
+        self.unmatched.is_empty() && self.duplicates.is_empty()
+    }
+}
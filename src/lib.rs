@@ -15,50 +15,120 @@ use std::path::PathBuf;
 use std::{error::Error, fmt};
 
 pub mod analog;
+#[cfg(all(feature = "rayon", feature = "fs"))]
+pub mod batch;
+pub mod borrowed;
+pub mod builder;
 pub mod c3d;
 pub mod data;
+pub mod diff;
+pub mod emg;
 pub mod events;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod filters;
 pub mod forces;
+pub mod gaps;
+pub mod kinematics;
+pub mod labels;
 pub mod manufacturer;
 pub mod parameters;
 pub mod points;
 mod processor;
+pub mod progress;
+pub mod quality;
+pub mod repair;
+pub mod resample;
+pub mod rigid_body;
+pub mod rotations;
 pub mod seg;
-pub mod builder;
+#[cfg(feature = "serde")]
+mod serde_support;
+pub mod smoothing;
+pub mod subjects;
+pub mod timecode;
+pub mod units;
+pub mod validate;
+pub mod vendor;
+pub mod writer;
 
 #[path = "file_formats/mod.rs"]
 pub mod file_formats;
 
 pub use analog::Analog;
+pub use analog::AnalogChannel;
 pub use analog::AnalogFormat;
 pub use analog::AnalogOffset;
-pub use builder::C3dBuilder;
+pub use borrowed::{C3dRef, GroupRef, ParameterRef};
+pub use builder::{C3dBuilder, C3dBuilderError};
 pub use c3d::C3d;
+pub use c3d::{DuplicatePolicy, LabelCollision, ParseLimits, ParseOptions, WriteOptions};
 pub use data::DataFormat;
+pub use data::LabelMatch;
 pub use data::MarkerPoint;
+pub use data::{Frame, FrameIter, FrameSink};
+pub use diff::{C3dDiff, DiffOptions, DiffSide, Difference};
+pub use emg::{EmgEnvelope, EmgProcessing};
 pub use events::Event;
 pub use events::EventContext;
 pub use events::Events;
+pub use file_formats::bvh::Bvh;
+pub use file_formats::csv::{Csv, CsvValue};
+pub use file_formats::sto::Sto;
+pub use file_formats::table::Table;
+pub use file_formats::trc::Trc;
+pub use filters::{Butterworth, FilterBand};
+pub use forces::ForceFrame;
 pub use forces::ForcePlatform;
 pub use forces::ForcePlatformCorners;
+pub use forces::ForcePlatformData;
 pub use forces::ForcePlatformOrigin;
 pub use forces::ForcePlatformType;
 pub use forces::ForcePlatforms;
+pub use gaps::GapFill;
+pub use labels::{LabelPreset, RelabelReport};
 pub use manufacturer::Manufacturer;
 pub use manufacturer::ManufacturerVersion;
-pub use parameters::{Parameter, ParameterData, Parameters};
-pub use points::Points;
+pub use parameters::{DataType, Parameter, ParameterData, Parameters, TextEncoding};
+pub use points::{Derivatives, Points, ScreenAxis};
 pub use processor::Processor;
+pub use progress::{Progress, ProgressStage};
+pub use quality::{AnalogQuality, ForcePlatformQuality, MarkerQuality, QualityReport};
+pub use repair::Repair;
+pub use resample::Interpolation;
+pub use rigid_body::{Pose, RigidBody};
+pub use rotations::{Rotation, Rotations};
 pub use seg::Seg;
-pub use file_formats::trc::Trc;
-pub use file_formats::sto::Sto;
+pub use smoothing::Smoother;
+pub use subjects::{Subject, Subjects};
+pub use timecode::{Timecode, TimecodeGroup, TimecodeStandard};
+pub use units::{AngleUnit, LengthUnit, MomentUnit, PointChannel, PointKind, UnitSet};
+pub use validate::{Severity, ValidationIssue, ValidationLocation, ValidationReport};
+pub use vendor::{Processing, Vendor};
+pub use writer::C3dWriter;
 
-/// Contains the most commonly used types and functions from this crate.
+/// The types for loading, building and writing C3D files, with their options and errors.
+///
+/// # Examples
+/// ```
+/// use c3dio::prelude::*;
+///
+/// let options = ParseOptions::default().with_limits(ParseLimits::untrusted());
+/// let c3d: Result<C3d, C3dParseError> =
+///     C3d::load_with_options("tests/data/short.c3d", options);
+/// let c3d = c3d.unwrap();
+/// let _: Processor = c3d.processor();
+/// let _: &Points = &c3d.points;
+/// let _: DataFormat = c3d.points.format.clone();
+/// let _: Option<&[MarkerPoint]> = c3d.points.frame(0);
+/// ```
 pub mod prelude {
     pub use crate::{
-        Analog, AnalogFormat, AnalogOffset, C3d, C3dParseError, C3dWriteError, Events,
-        ForcePlatform, ForcePlatformType, ForcePlatforms, Manufacturer, ManufacturerVersion,
-        MarkerPoint, Parameter, ParameterData, Parameters, Points, Processor, Seg, Sto, Trc
+        Analog, AnalogFormat, AnalogOffset, C3d, C3dBuilder, C3dBuilderError, C3dFilterError,
+        C3dMergeError, C3dParseError, C3dWriteError, DataFormat, Events, ForcePlatform,
+        ForcePlatformType, ForcePlatforms, Manufacturer, ManufacturerVersion, MarkerPoint,
+        Parameter, ParameterData, Parameters, ParseLimits, ParseOptions, ParseWarning, Points,
+        Processor, Seg, Sto, Trc,
     };
 }
 
@@ -102,15 +172,296 @@ pub enum C3dParseError {
     AnalogBytesPerFrameMismatch,
     FrameRateMismatch(f32, f32),
     ScaleFactorMismatch(f32, f32),
+    DuplicateParameter(String, String),
+    /// A line of a TRC file could not be read, given its line number and contents.
+    InvalidTrcLine(usize, String),
+    /// Loading was cancelled by the progress callback.
+    Cancelled,
+    /// A size in the file exceeds one of the `ParseLimits`, given what was counted,
+    /// the size found and the limit.
+    LimitExceeded(String, usize, usize),
+    /// An error together with where in the file it was found. Errors can be wrapped in
+    /// several contexts, the outermost describing the largest part of the file, such as
+    /// the parameter section, and the innermost the parameter being read.
+    /// Use `kind` to match on the error itself.
+    Context(Box<C3dParseError>, ErrorContext),
+}
+
+/// Where in a file a `C3dParseError` was found: the part of the file being parsed,
+/// its byte offset from the start of the file, and the group and parameter it belongs to.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ErrorContext {
+    /// What was being parsed, e.g. `parameter` or `the data section`.
+    pub part: String,
+    pub offset: Option<u64>,
+    pub group: Option<String>,
+    pub parameter: Option<String>,
+}
+
+impl ErrorContext {
+    pub(crate) fn new(part: &str) -> ErrorContext {
+        ErrorContext {
+            part: part.to_string(),
+            ..ErrorContext::default()
+        }
+    }
+
+    pub(crate) fn at(mut self, offset: u64) -> ErrorContext {
+        self.offset = Some(offset);
+        self
+    }
+
+    pub(crate) fn group(mut self, group: &str) -> ErrorContext {
+        self.group = Some(group.to_string());
+        self
+    }
+
+    pub(crate) fn parameter(mut self, parameter: &str) -> ErrorContext {
+        self.parameter = Some(parameter.to_string());
+        self
+    }
+
+    /// Returns the 512-byte block of the file the offset is in, counted from 1
+    /// as in the header.
+    pub fn block(&self) -> Option<u64> {
+        self.offset.map(|offset| offset / 512 + 1)
+    }
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "in {}", self.part)?;
+        match (&self.group, &self.parameter) {
+            (Some(group), Some(parameter)) => write!(f, " {}:{}", group, parameter)?,
+            (Some(group), None) => write!(f, " {}", group)?,
+            (None, Some(parameter)) => write!(f, " {}", parameter)?,
+            (None, None) => {}
+        }
+        if let (Some(offset), Some(block)) = (self.offset, self.block()) {
+            write!(f, " at byte {} (block {})", offset, block)?;
+        }
+        Ok(())
+    }
+}
+
+impl C3dParseError {
+    /// Wraps the error in a context describing where it was found.
+    pub(crate) fn context(self, context: ErrorContext) -> C3dParseError {
+        C3dParseError::Context(Box::new(self), context)
+    }
+
+    /// Moves the offsets of the error's contexts by the offset of the part of the file
+    /// they were found in, e.g. from the start of the parameter section.
+    pub(crate) fn offset_by(self, base: u64) -> C3dParseError {
+        match self {
+            C3dParseError::Context(error, mut context) => {
+                context.offset = context.offset.map(|offset| offset + base);
+                error.offset_by(base).context(context)
+            }
+            error => error,
+        }
+    }
+
+    /// Returns the error without its contexts.
+    pub fn kind(&self) -> &C3dParseError {
+        match self {
+            C3dParseError::Context(error, _) => error.kind(),
+            error => error,
+        }
+    }
+
+    /// Returns the error without its contexts, to match on it, e.g.
+    /// `matches!(error.into_kind(), C3dParseError::DuplicateParameter(..))`.
+    pub fn into_kind(self) -> C3dParseError {
+        match self {
+            C3dParseError::Context(error, _) => error.into_kind(),
+            error => error,
+        }
+    }
+
+    /// Returns the contexts of the error, from the outermost to the innermost.
+    pub fn contexts(&self) -> Vec<&ErrorContext> {
+        let mut contexts = Vec::new();
+        let mut error = self;
+        while let C3dParseError::Context(inner, context) = error {
+            contexts.push(context);
+            error = inner;
+        }
+        contexts
+    }
+
+    /// Returns the byte offset from the start of the file of the innermost context
+    /// that has one.
+    pub fn offset(&self) -> Option<u64> {
+        self.contexts()
+            .iter()
+            .rev()
+            .find_map(|context| context.offset)
+    }
+
+    /// Returns the group and parameter being parsed when the error occurred, if known.
+    pub fn parameter(&self) -> Option<(&str, &str)> {
+        let contexts = self.contexts();
+        let parameter = contexts.iter().rev().find_map(|c| c.parameter.as_deref())?;
+        let group = contexts.iter().rev().find_map(|c| c.group.as_deref())?;
+        Some((group, parameter))
+    }
+
+    fn message(&self) -> String {
+        use C3dParseError::*;
+        match self {
+            ReadError(e) => format!("the file could not be read: {}", e),
+            InsufficientBlocks(section) => {
+                format!("the file is too short to hold its {} section", section)
+            }
+            InvalidHeaderStartBlock => "the header does not start the file".to_string(),
+            InvalidParameterStartBlock => {
+                "the parameter section does not start where the header says".to_string()
+            }
+            InvalidParameterData => {
+                "the data of a parameter does not match its dimensions".to_string()
+            }
+            InvalidDataStartBlock => {
+                "the data section does not start where the header says".to_string()
+            }
+            InvalidProcessorType => "the processor type is not Intel, DEC or MIPS".to_string(),
+            InvalidDataType => "a parameter has an unknown data type".to_string(),
+            InvalidParametersOffset => "a parameter points past the parameter section".to_string(),
+            InvalidDescription => "a description is not valid text".to_string(),
+            MissingGroup(group) => format!("the {} group is missing", group),
+            MissingParameter(parameter) => format!("the parameter {} is missing", parameter),
+            InvalidGroupId => "a parameter belongs to a group that does not exist".to_string(),
+            MissingPointScale => "POINT:SCALE is missing".to_string(),
+            FileNotOpen => "the file is not open".to_string(),
+            NotEnoughData => "the file ends before all of its data".to_string(),
+            InvalidNextParameter => "a parameter points to an invalid next parameter".to_string(),
+            TooManyEvents(events) => {
+                format!(
+                    "the header lists {} events, more than the 18 it holds",
+                    events
+                )
+            }
+            NumFramesMismatch(header, parameter) => format!(
+                "the header declares {} frames but POINT:FRAMES declares {}",
+                header, parameter
+            ),
+            GroupNotFound(group) => format!("the {} group was not found", group),
+            ParameterNotFound(group, parameter) => {
+                format!("the parameter {}:{} was not found", group, parameter)
+            }
+            RequiredParameterNotFound(parameter) => {
+                format!("the required parameter {} was not found", parameter)
+            }
+            InvalidData(parameter, expected) => format!(
+                "the parameter {} does not hold data of type {}",
+                parameter.name, expected
+            ),
+            InvalidParameterFormat(value) => format!("a parameter has an invalid value: {}", value),
+            AnalogOffsetScaleMismatch => {
+                "ANALOG:OFFSET and ANALOG:SCALE have different lengths".to_string()
+            }
+            InsufficientAnalogOffsets => {
+                "ANALOG:OFFSET has fewer values than channels used".to_string()
+            }
+            InvalidParameterDimensions(parameter) => {
+                format!("the parameter {} has invalid dimensions", parameter)
+            }
+            InvalidParameterType(parameter) => {
+                format!("the parameter {} has an invalid type", parameter)
+            }
+            InvalidEventLabel(expected, found) => {
+                format!("the event label {} does not match {}", found, expected)
+            }
+            MissingEventTime(event) => format!("event {} has no time", event),
+            MissingEventLabel(event) => format!("event {} has no label", event),
+            NoParameterTimeEvents => "EVENT:TIMES is missing".to_string(),
+            HeaderNotParsed => "the header was not parsed".to_string(),
+            AnalogBytesPerFrameMismatch => {
+                "the analog samples per frame do not match the data section".to_string()
+            }
+            FrameRateMismatch(header, parameter) => format!(
+                "the header frame rate {} does not match POINT:RATE {}",
+                header, parameter
+            ),
+            ScaleFactorMismatch(header, parameter) => format!(
+                "the header scale factor {} does not match POINT:SCALE {}",
+                header, parameter
+            ),
+            DuplicateParameter(group, parameter) => {
+                format!("the parameter {}:{} is defined twice", group, parameter)
+            }
+            InvalidTrcLine(line, contents) => {
+                format!("line {} of the TRC file is invalid: {}", line, contents)
+            }
+            Cancelled => "loading was cancelled".to_string(),
+            LimitExceeded(what, size, limit) => {
+                format!("{} {} exceed the limit of {}", size, what, limit)
+            }
+            Context(error, _) => error.message(),
+        }
+    }
 }
 
-impl Error for C3dParseError {}
+impl Error for C3dParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            C3dParseError::ReadError(e) => Some(e),
+            C3dParseError::Context(error, _) => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Describes the error for end users, followed by where it was found from the innermost
+/// to the outermost context, e.g. `the data of a parameter does not match its dimensions,
+/// in parameter POINT:LABELS at byte 1540 (block 4), in the parameter section at byte 512
+/// (block 2)`.
 impl fmt::Display for C3dParseError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "C3dParseError: {:?}", self)
+        write!(f, "{}", self.message())?;
+        for context in self.contexts().iter().rev() {
+            write!(f, ", {}", context)?;
+        }
+        Ok(())
     }
 }
 
+/// Reports recoverable problems found while parsing a C3D file in lenient mode.
+/// The warnings are collected in the `warnings` field of the parsed `C3d`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ParseWarning {
+    /// The processor byte in the parameter start block was not recognized,
+    /// so the file was parsed as Intel.
+    UnknownProcessorByte(u8),
+    /// A group contained more than one parameter with the same name, so only one was
+    /// kept: the first, unless `ParseOptions::duplicates` says otherwise.
+    /// This is an error in strict mode if the definitions differ and no policy is set.
+    DuplicateParameter(String, String),
+    /// More than one group had the given name. Their parameters were merged into one
+    /// group, with the description chosen as for repeated parameters. Reported in both modes.
+    DuplicateGroup(String),
+    /// The description of a group or parameter, given as `GROUP` or `GROUP:PARAMETER`,
+    /// was not valid UTF-8 and was read with U+FFFD in place of the invalid bytes.
+    /// Reported in both modes, unless a single-byte `ParseOptions::encoding` is given.
+    InvalidDescription(String),
+    /// The header and POINT:FRAMES disagree on the number of frames, given in that order.
+    /// POINT:FRAMES is used. This is an error in strict mode.
+    FrameCountMismatch(usize, usize),
+    /// The data section holds the given number of bytes after the last frame,
+    /// beyond the padding of the last block. Reported in both modes.
+    TrailingData(usize),
+    /// The data section ends before the frames the file declares, as when a capture is cut
+    /// short by a power loss, given the number of complete frames stored and the number
+    /// declared. Only the complete frames are read, of the points and the analog channels
+    /// alike. Reported in both modes.
+    TruncatedData(usize, usize),
+}
+
+/// The warnings collected while parsing, under the name used by the other public types.
+pub type C3dWarning = ParseWarning;
+
 /// Reports errors that occurred while writing a C3D file.
 /// The error type is returned by the `write` method.
 #[derive(Debug)]
@@ -129,6 +480,31 @@ pub enum C3dWriteError {
     InvalidParameterDimensions(String),
     ParameterDescriptionTooLong(String),
     InvalidForcePlatformInfo(String),
+    /// The channel given to `C3d::set_analog_channel` is not stored or cannot be written.
+    InvalidAnalogChannel(String),
+    /// The axes given to `C3d::set_screen_axes` for the screen X and Y are along the
+    /// same lab axis.
+    InvalidScreenAxes(ScreenAxis, ScreenAxis),
+    /// More groups than the 127 that can be given an ID.
+    TooManyGroups(usize),
+    /// Writing was cancelled by the progress callback.
+    Cancelled,
+    /// The layout given to a `C3dWriter` or a frame pushed to it is invalid.
+    InvalidLayout(C3dBuilderError),
+    /// The file given to `C3dWriter::append` could not be parsed.
+    AppendParseError(Box<C3dParseError>),
+    /// Frames cannot be appended to the file without rewriting it, for the given reason.
+    CannotAppend(String),
+    /// The file given to `C3d::write_parameters_in_place_to` could not be parsed.
+    InPlaceParseError(Box<C3dParseError>),
+    /// The header and parameters cannot be rewritten without rewriting the data section,
+    /// for the given reason.
+    CannotWriteInPlace(String),
+    /// The parameter section takes the given number of blocks, too many for the data
+    /// to start at a block the header can point to.
+    ParameterSectionTooLarge(usize),
+    /// The parameter takes more bytes than the offset to the next parameter can skip.
+    ParameterTooLarge(String),
 }
 
 impl Error for C3dWriteError {}
@@ -137,3 +513,68 @@ impl fmt::Display for C3dWriteError {
         write!(f, "C3dWriteError: {:?}", self)
     }
 }
+
+/// Reports why trials could not be combined into one file.
+/// The error type is returned by the `concat`, `merge_points` and `add_point` methods.
+/// Trials are given by their index, where the first trial is the reference.
+#[derive(Debug, Clone, PartialEq)]
+pub enum C3dMergeError {
+    /// No trials were given.
+    NoTrials,
+    /// The trial has different point labels than the first trial.
+    PointLabelsMismatch(usize),
+    /// The trial has a different point frame rate than the first trial, given in that order.
+    FrameRateMismatch(usize, f32, f32),
+    /// The trial has different analog channels, analog rate or samples per frame
+    /// than the first trial.
+    AnalogLayoutMismatch(usize),
+    /// The trial has different rigid bodies in the ROTATION group, or a different number
+    /// of rotation samples per frame, than the first trial.
+    RotationLayoutMismatch(usize),
+    /// The point label is used in both files being merged, or by the point being added.
+    LabelCollision(String),
+    /// No point has the label.
+    LabelNotFound(String),
+    /// The point being added has a different number of samples than the stored frames,
+    /// given in that order.
+    FrameCountMismatch(usize, usize),
+}
+
+impl Error for C3dMergeError {}
+impl fmt::Display for C3dMergeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "C3dMergeError: {:?}", self)
+    }
+}
+
+/// Reports why a filter cannot be applied at a sampling rate.
+/// The error type is returned by the `filter_points`, `filter_analog`, `emg_envelope`
+/// and `derivatives` methods, and by the smoothers of `Smoother`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum C3dFilterError {
+    /// The filter has an order of 0.
+    ZeroOrder,
+    /// The cutoff is not between 0 and the Nyquist frequency, given in that order.
+    InvalidCutoff(f64, f64),
+    /// The low cutoff of a band-pass filter is not below the high cutoff.
+    InvalidBand(f64, f64),
+    /// No analog channel has the given label.
+    ChannelNotFound(String),
+    /// No point has the given label.
+    PointNotFound(String),
+    /// The window of a smoother is not an odd number of samples longer than the order
+    /// of its polynomial.
+    InvalidWindow(usize),
+    /// The smoother cannot compute the derivative, as a moving average cannot or the
+    /// polynomial of a Savitzky–Golay smoother is of a lower order.
+    InvalidDerivative(usize),
+    /// There are fewer samples than the smoother needs, given in that order.
+    TooFewSamples(usize, usize),
+}
+
+impl Error for C3dFilterError {}
+impl fmt::Display for C3dFilterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "C3dFilterError: {:?}", self)
+    }
+}
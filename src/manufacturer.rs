@@ -1,10 +1,12 @@
 //! Contains manufacturer data from the parameter section of a C3D file, if provided.
 use crate::parameters::{Parameter, ParameterData, Parameters};
 use crate::processor::Processor;
+use crate::vendor::Vendor;
 use crate::{C3dWriteError, C3dParseError};
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ManufacturerVersion {
     String(String),
     Float(f32),
@@ -12,6 +14,7 @@ pub enum ManufacturerVersion {
 }
 
 #[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Manufacturer {
     pub company: Option<String>,
     pub software: Option<String>,
@@ -53,6 +56,12 @@ impl Manufacturer {
         Manufacturer::default()
     }
 
+    /// Recognizes the vendor of the software that wrote the file from the company and
+    /// software names, or returns `None` if neither is given.
+    pub fn vendor(&self) -> Option<Vendor> {
+        Vendor::recognize(self.company.as_deref(), self.software.as_deref())
+    }
+
     pub(crate) fn from_parameters(parameters: &mut Parameters) -> Result<Self, C3dParseError> {
         let company = parameters.remove("MANUFACTURER", "COMPANY");
         let company: Option<String> = match company {
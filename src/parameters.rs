@@ -1,8 +1,11 @@
 //! Logic for parsing and writing parameters.
 use crate::processor::Processor;
-use crate::{C3dParseError, C3dWriteError};
+use crate::{
+    C3dParseError, C3dWriteError, DuplicatePolicy, ErrorContext, ParseOptions, ParseWarning,
+};
 use grid::Grid;
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 
 /// The parameters of a C3D file are stored in a `Parameters` struct.
 /// Each group of parameters is stored in a separate struct.
@@ -10,8 +13,22 @@ use std::collections::HashMap;
 /// The first key is the group name, and the second key is the parameter name.
 /// The value is a tuple of the parameter data and the description.
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Parameters {
     parameters: HashMap<String, (String, HashMap<String, Parameter>)>,
+    /// Every definition of the parameters the file defined more than once, with their
+    /// group, in the order they were read.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    definitions: Vec<(String, Parameter)>,
+    /// The descriptions of parameters not held here, such as those removed when they are
+    /// parsed into other fields of `C3d`, by group and parameter name. They are written
+    /// with the parameters written from those fields.
+    #[cfg_attr(feature = "serde", serde(default))]
+    detached_descriptions: HashMap<String, HashMap<String, String>>,
+    /// The locked groups and parameters as `(group, parameter)`, with an empty parameter
+    /// name for groups.
+    #[cfg_attr(feature = "serde", serde(default))]
+    locked: HashSet<(String, String)>,
 }
 
 impl ToString for Parameters {
@@ -43,17 +60,19 @@ impl Parameters {
     pub(crate) fn parse_parameter_blocks(
         parameter_blocks: &Vec<u8>,
         processor: &Processor,
+        options: &ParseOptions,
+        warnings: &mut Vec<ParseWarning>,
     ) -> Result<Self, C3dParseError> {
-        Parameters::from_bytes(parameter_blocks, processor)
+        Parameters::from_bytes(parameter_blocks, processor, options, warnings)
     }
 
     pub(crate) fn from_bytes(
         parameter_blocks: &Vec<u8>,
         processor: &Processor,
+        options: &ParseOptions,
+        warnings: &mut Vec<ParseWarning>,
     ) -> Result<Self, C3dParseError> {
-        Ok(Parameters {
-            parameters: parse_parameters(parameter_blocks, processor)?,
-        })
+        parse_parameters(parameter_blocks, processor, options, warnings)
     }
 
     pub(crate) fn write_groups(
@@ -62,12 +81,15 @@ impl Parameters {
     ) -> Result<(Vec<u8>, HashMap<String, usize>), C3dWriteError> {
         let mut bytes = Vec::new();
         let mut group_names_to_ids = HashMap::new();
-        let mut group_id = 1;
-        for (group, (group_description, _parameters)) in self.parameters.iter() {
+        if self.parameters.len() > i8::MAX as usize {
+            return Err(C3dWriteError::TooManyGroups(self.parameters.len()));
+        }
+        for (group_id, group) in (1..).zip(self.sorted_groups()) {
+            let group_description = &self.parameters[group].0;
             if group.len() > 127 {
                 return Err(C3dWriteError::GroupNameTooLong(group.clone()));
             }
-            bytes.push(group.len() as u8);
+            bytes.push(locked_length(group.len(), self.is_group_locked(group)));
             bytes.push(-(group_id as i8) as u8);
             if !group.is_ascii() {
                 return Err(C3dWriteError::GroupNameNotAscii(group.clone()));
@@ -78,36 +100,135 @@ impl Parameters {
                     group_description.clone(),
                 ));
             }
-            let bytes_to_end = processor.u16_to_bytes(group_description.len() as u16 + 2);
+            let bytes_to_end = processor.u16_to_bytes(group_description.len() as u16 + 3);
             bytes.extend(bytes_to_end);
+            bytes.push(group_description.len() as u8);
             bytes.extend(group_description.as_bytes());
 
             group_names_to_ids.insert(group.clone(), group_id);
-            group_id += 1;
         }
         Ok((bytes, group_names_to_ids))
     }
 
+    /// Returns the group names in order, so that a file is always written the same way
+    /// whatever the order the parameters are held in.
+    fn sorted_groups(&self) -> Vec<&String> {
+        let mut groups: Vec<&String> = self.parameters.keys().collect();
+        groups.sort();
+        groups
+    }
+
+    /// Writes every parameter except those in `skip`, which are written elsewhere.
     pub(crate) fn write_parameters(
         &self,
         processor: &Processor,
         group_names_to_ids: &HashMap<String, usize>,
+        skip: &[(&str, &str)],
     ) -> Result<Vec<u8>, C3dWriteError> {
+        let is_skipped = |group: &str, parameter: &str| {
+            skip.iter().any(|&(skip_group, skip_parameter)| {
+                skip_group == group && skip_parameter == parameter
+            })
+        };
         let mut bytes = Vec::new();
-        for (i, (group, (_, parameters))) in self.parameters.iter().enumerate() {
+        let num_parameters: usize = self
+            .parameters
+            .iter()
+            .map(|(group, (_, parameters))| {
+                parameters
+                    .keys()
+                    .filter(|parameter| !is_skipped(group, parameter))
+                    .count()
+            })
+            .sum();
+        let mut written = 0;
+        for group in self.sorted_groups() {
+            let parameters = &self.parameters[group].1;
             let group_id = group_names_to_ids.get(group).unwrap();
-            for (j, (parameter_name, parameter)) in parameters.iter().enumerate() {
-                bytes.extend(parameter.write(
+            let mut parameters: Vec<(&String, &Parameter)> = parameters.iter().collect();
+            parameters.sort_by(|a, b| a.0.cmp(b.0));
+            for (parameter_name, parameter) in parameters {
+                if is_skipped(group, parameter_name) {
+                    continue;
+                }
+                written += 1;
+                let mut parameter_bytes = parameter.write(
                     processor,
                     parameter_name.clone(),
                     *group_id,
-                    j == parameters.len() - 1 && i == self.parameters.len() - 1,
-                )?);
+                    written == num_parameters,
+                )?;
+                parameter_bytes[0] =
+                    locked_length(parameter_name.len(), self.is_locked(group, parameter_name));
+                bytes.extend(parameter_bytes);
             }
         }
         Ok(bytes)
     }
 
+    /// Restores the locks and descriptions of parameters written from the other fields
+    /// of `C3d`, which are written unlocked and only described if the field describes them.
+    pub(crate) fn restore_attributes(
+        &self,
+        bytes: &[u8],
+        processor: &Processor,
+        group_names_to_ids: &HashMap<String, usize>,
+    ) -> Vec<u8> {
+        let mut restored = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i + 2 < bytes.len() {
+            let name_length = (bytes[i] as i8).unsigned_abs() as usize;
+            let offset_index = i + 2 + name_length;
+            if name_length == 0 || offset_index + 4 > bytes.len() {
+                break;
+            }
+            let num_dimensions = bytes[offset_index + 3] as usize;
+            let dimensions_index = offset_index + 4;
+            let element_size = (bytes[offset_index + 2] as i8).unsigned_abs() as usize;
+            let values = match bytes.get(dimensions_index..dimensions_index + num_dimensions) {
+                Some(dimensions) => dimensions.iter().map(|&x| x as usize).product::<usize>(),
+                None => break,
+            };
+            let description_index = dimensions_index + num_dimensions + element_size * values;
+            let end = match bytes.get(description_index) {
+                Some(&length) => description_index + 1 + length as usize,
+                None => break,
+            };
+            if end > bytes.len() {
+                break;
+            }
+            let name = String::from_utf8_lossy(&bytes[i + 2..offset_index]).to_string();
+            let group = group_names_to_ids
+                .iter()
+                .find(|(_, &id)| id == bytes[i + 1] as usize)
+                .map(|(group, _)| group.as_str())
+                .unwrap_or_default();
+            let description = match bytes[description_index] {
+                0 => self
+                    .detached_descriptions
+                    .get(group)
+                    .and_then(|descriptions| descriptions.get(&name))
+                    .map(|description| description.as_bytes())
+                    .unwrap_or_default(),
+                _ => &bytes[description_index + 1..end],
+            };
+            let offset = processor.u16([bytes[offset_index], bytes[offset_index + 1]]);
+            let offset = match offset {
+                0 => 0,
+                _ => (description_index + 1 + description.len() - offset_index) as u16,
+            };
+            restored.push(locked_length(name_length, self.is_locked(group, &name)));
+            restored.extend_from_slice(&bytes[i + 1..offset_index]);
+            restored.extend(processor.u16_to_bytes(offset));
+            restored.extend_from_slice(&bytes[offset_index + 2..description_index]);
+            restored.push(description.len() as u8);
+            restored.extend_from_slice(description);
+            i = end;
+        }
+        restored.extend_from_slice(&bytes[i..]);
+        restored
+    }
+
     pub fn get(&self, group: &str, parameter: &str) -> Option<&Parameter> {
         self.parameters
             .get(group)
@@ -140,8 +261,46 @@ impl Parameters {
             ))
     }
 
+    /// Returns the value of a parameter converted to the requested type,
+    /// e.g. `parameters.get_as::<f32>("POINT", "SCALE")`.
+    /// Fails with `ParameterNotFound` if the parameter is missing
+    /// and with `InvalidData` if it does not hold data of that type.
+    pub fn get_as<T>(&self, group: &str, parameter: &str) -> Result<T, C3dParseError>
+    where
+        T: for<'a> TryFrom<&'a Parameter, Error = C3dParseError>,
+    {
+        T::try_from(self.get_or_err(group, parameter)?)
+    }
+
+    /// Returns the values of a parameter converted to a vector of the requested type,
+    /// e.g. `parameters.get_vec::<String>("POINT", "LABELS")`.
+    pub fn get_vec<T>(&self, group: &str, parameter: &str) -> Result<Vec<T>, C3dParseError>
+    where
+        Vec<T>: for<'a> TryFrom<&'a Parameter, Error = C3dParseError>,
+    {
+        self.get_as::<Vec<T>>(group, parameter)
+    }
+
+    /// Sets the value of a parameter from a typed value, e.g.
+    /// `parameters.set("POINT", "SCALE", -0.1)`.
+    /// The description of an existing parameter is kept and the group is created if needed.
+    pub fn set<T>(&mut self, group: &str, parameter: &str, value: T) -> Result<(), C3dWriteError>
+    where
+        Parameter: TryFrom<T, Error = C3dWriteError>,
+    {
+        let mut value = Parameter::try_from(value)?;
+        if let Some(description) = self.description(group, parameter) {
+            value.description = description.to_string();
+        }
+        self.insert(group, parameter, value);
+        Ok(())
+    }
+
     pub fn insert(&mut self, group: &str, parameter: &str, mut value: Parameter) {
         value.name = parameter.to_string();
+        if let Some(descriptions) = self.detached_descriptions.get_mut(group) {
+            descriptions.remove(parameter);
+        }
         self.parameters
             .entry(group.to_string())
             .or_insert((String::new(), HashMap::new()))
@@ -149,10 +308,125 @@ impl Parameters {
             .insert(parameter.to_string(), value);
     }
 
+    /// Removes a parameter, keeping its description and lock for when a parameter
+    /// with the same name is set or written from another field of `C3d`.
     pub fn remove(&mut self, group: &str, parameter: &str) -> Option<Parameter> {
-        self.parameters
+        let removed = self
+            .parameters
             .get_mut(group)
-            .and_then(|(_, group)| group.remove(parameter))
+            .and_then(|(_, group)| group.remove(parameter))?;
+        if !removed.description.is_empty() {
+            self.detached_descriptions
+                .entry(group.to_string())
+                .or_default()
+                .insert(parameter.to_string(), removed.description.clone());
+        }
+        Some(removed)
+    }
+
+    /// Returns the description of a parameter, including the parameters parsed into other
+    /// fields of `C3d`, such as POINT:LABELS, which keep the description read from the file.
+    pub fn description(&self, group: &str, parameter: &str) -> Option<&str> {
+        match self.get(group, parameter) {
+            Some(parameter) => Some(parameter.description.as_str()),
+            None => self
+                .detached_descriptions
+                .get(group)
+                .and_then(|descriptions| descriptions.get(parameter))
+                .map(|description| description.as_str()),
+        }
+    }
+
+    /// Sets the description of a parameter. The description of a parameter parsed into
+    /// another field of `C3d`, or not written yet, is written with the parameter when it
+    /// is written from that field.
+    pub fn set_description(
+        &mut self,
+        group: &str,
+        parameter: &str,
+        description: &str,
+    ) -> Result<(), C3dWriteError> {
+        if description.len() > 255 {
+            return Err(C3dWriteError::ParameterDescriptionTooLong(
+                description.to_string(),
+            ));
+        }
+        match self.get_mut(group, parameter) {
+            Some(parameter) => parameter.description = description.to_string(),
+            None => {
+                self.detached_descriptions
+                    .entry(group.to_string())
+                    .or_default()
+                    .insert(parameter.to_string(), description.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns whether a parameter is locked, which software such as Vicon Nexus takes
+    /// to mean it should not be edited. Locks are read from and written to the file,
+    /// and kept when the parameter is changed or parsed into another field of `C3d`.
+    pub fn is_locked(&self, group: &str, parameter: &str) -> bool {
+        self.locked
+            .contains(&(group.to_string(), parameter.to_string()))
+    }
+
+    pub fn lock(&mut self, group: &str, parameter: &str) {
+        self.locked
+            .insert((group.to_string(), parameter.to_string()));
+    }
+
+    pub fn unlock(&mut self, group: &str, parameter: &str) {
+        self.locked
+            .remove(&(group.to_string(), parameter.to_string()));
+    }
+
+    /// Returns the locks of the groups and parameters held here.
+    fn held_locks(&self) -> HashSet<&(String, String)> {
+        self.locked
+            .iter()
+            .filter(|(group, parameter)| match parameter.is_empty() {
+                true => self.parameters.contains_key(group),
+                false => self.contains(group, parameter),
+            })
+            .collect()
+    }
+
+    /// Returns whether a group is locked. See `is_locked`.
+    pub fn is_group_locked(&self, group: &str) -> bool {
+        self.is_locked(group, "")
+    }
+
+    pub fn lock_group(&mut self, group: &str) {
+        self.lock(group, "");
+    }
+
+    pub fn unlock_group(&mut self, group: &str) {
+        self.unlock(group, "");
+    }
+
+    /// Removes a parameter holding one entry per point or channel, joined with its
+    /// continuations `LABELS2`, `LABELS3` and so on, which hold the entries after the
+    /// first 255. Returns `None` if the parameter is not present.
+    pub(crate) fn remove_continued<T>(
+        &mut self,
+        group: &str,
+        parameter: &str,
+    ) -> Result<Option<Vec<T>>, C3dParseError>
+    where
+        for<'a> Vec<T>: TryFrom<&'a Parameter, Error = C3dParseError>,
+    {
+        let mut entries: Vec<T> = match self.remove(group, parameter) {
+            Some(first) => first.as_ref().try_into()?,
+            None => return Ok(None),
+        };
+        for index in 2.. {
+            match self.remove(group, &format!("{}{}", parameter, index)) {
+                Some(continued) => entries.extend(Vec::<T>::try_from(&continued)?),
+                None => break,
+            }
+        }
+        Ok(Some(entries))
     }
 
     pub fn remove_or_err(
@@ -190,6 +464,66 @@ impl Parameters {
             .map(|(group_description, _)| group_description)
     }
 
+    /// Adds a group that is written with the rest of the parameters.
+    /// Group names are stored in uppercase, as they are read back from a file.
+    /// If the group already exists its description is replaced and its parameters are kept.
+    /// The ID of each group is assigned when the file is written.
+    pub fn add_group(&mut self, group_name: &str, description: &str) -> Result<(), C3dWriteError> {
+        if group_name.is_empty() || group_name.len() > 127 {
+            return Err(C3dWriteError::GroupNameTooLong(group_name.to_string()));
+        }
+        if !group_name.is_ascii() {
+            return Err(C3dWriteError::GroupNameNotAscii(group_name.to_string()));
+        }
+        if description.len() > 255 {
+            return Err(C3dWriteError::GroupDescriptionTooLong(
+                description.to_string(),
+            ));
+        }
+        self.parameters
+            .entry(group_name.to_ascii_uppercase())
+            .or_insert((String::new(), HashMap::new()))
+            .0 = description.to_string();
+        Ok(())
+    }
+
+    /// Adds a parameter with the given description, dimensions and data to a group,
+    /// replacing any parameter with the same name.
+    /// The group is created if it does not exist yet.
+    /// The dimensions must match the amount of data, e.g. `[4, 2]` for two strings of four characters.
+    pub fn add_parameter(
+        &mut self,
+        group_name: &str,
+        parameter_name: &str,
+        description: &str,
+        dimensions: Vec<u8>,
+        data: ParameterData,
+    ) -> Result<(), C3dWriteError> {
+        if parameter_name.is_empty() || parameter_name.len() > 127 {
+            return Err(C3dWriteError::ParameterNameTooLong(
+                parameter_name.to_string(),
+            ));
+        }
+        if !parameter_name.is_ascii() {
+            return Err(C3dWriteError::ParameterNameNotAscii(
+                parameter_name.to_string(),
+            ));
+        }
+        if description.len() > 255 {
+            return Err(C3dWriteError::ParameterDescriptionTooLong(
+                description.to_string(),
+            ));
+        }
+        let parameter = Parameter::new(description.to_string(), dimensions, data)
+            .map_err(|_| C3dWriteError::InvalidParameterDimensions(parameter_name.to_string()))?;
+        let group_name = group_name.to_ascii_uppercase();
+        if self.get_group(&group_name).is_none() {
+            self.add_group(&group_name, "")?;
+        }
+        self.insert(&group_name, &parameter_name.to_ascii_uppercase(), parameter);
+        Ok(())
+    }
+
     pub fn insert_group(&mut self, group_name: &str, description: String) {
         self.parameters
             .insert(group_name.to_string(), (description, HashMap::new()));
@@ -212,6 +546,36 @@ impl Parameters {
             .get(group)
             .map(|(_, group)| group.values().collect())
     }
+
+    /// Returns every definition of a parameter, in the order they were read from the file.
+    /// A parameter the file defined more than once has all of its definitions returned,
+    /// including those not kept, even after the parameter is changed or parsed into another
+    /// field of `C3d`. Other parameters are returned as `get` returns them.
+    pub fn definitions(&self, group: &str, parameter: &str) -> Vec<&Parameter> {
+        let repeated: Vec<&Parameter> = self
+            .definitions
+            .iter()
+            .filter(|(defined_in, defined)| defined_in == group && defined.name == parameter)
+            .map(|(_, defined)| defined)
+            .collect();
+        match repeated.is_empty() {
+            true => self.get(group, parameter).into_iter().collect(),
+            false => repeated,
+        }
+    }
+
+    /// Returns the group and name of each parameter the file defined more than once,
+    /// in the order they were first read.
+    pub fn duplicates(&self) -> Vec<(&str, &str)> {
+        let mut duplicates: Vec<(&str, &str)> = Vec::new();
+        for (group, parameter) in &self.definitions {
+            let key = (group.as_str(), parameter.name.as_str());
+            if !duplicates.contains(&key) {
+                duplicates.push(key);
+            }
+        }
+        duplicates
+    }
 }
 
 impl PartialEq for Parameters {
@@ -240,8 +604,216 @@ impl PartialEq for Parameters {
                 }
             }
         }
-        true
+        // the detached descriptions and locks of parameters held elsewhere are compared
+        // through the fields holding them, as they are only written with those fields
+        self.held_locks() == other.held_locks()
+    }
+}
+
+/// Returns the byte holding the length of the name of a group or parameter,
+/// which is negative if it is locked.
+fn locked_length(name_length: usize, locked: bool) -> u8 {
+    match locked {
+        true => -(name_length as i8) as u8,
+        false => name_length as u8,
+    }
+}
+
+/// Writes a parameter holding one entry per point or channel, continuing it in
+/// `LABELS2`, `LABELS3` and so on if there are more than 255 entries.
+pub(crate) fn write_continued<T: Clone>(
+    entries: &[T],
+    to_parameter: impl Fn(Vec<T>) -> Result<Parameter, C3dWriteError>,
+    processor: &Processor,
+    parameter: &str,
+    group_id: usize,
+) -> Result<Vec<u8>, C3dWriteError> {
+    let mut bytes = Vec::new();
+    let mut chunks: Vec<&[T]> = entries.chunks(u8::MAX as usize).collect();
+    if chunks.is_empty() {
+        chunks.push(&[]);
+    }
+    for (index, chunk) in (1..).zip(chunks) {
+        let name = match index {
+            1 => parameter.to_string(),
+            _ => format!("{}{}", parameter, index),
+        };
+        bytes.extend(to_parameter(chunk.to_vec())?.write(processor, name, group_id, false)?);
     }
+    Ok(bytes)
+}
+
+/// The encoding of the text in character parameters and descriptions, such as labels,
+/// units and the descriptions of groups, for files whose text is not ASCII.
+/// The names of groups and parameters are ASCII and are not affected.
+///
+/// Without an encoding, character data is read as Latin-1 and descriptions as UTF-8,
+/// with bytes that are not valid UTF-8 replaced by U+FFFD. Character data is written
+/// as Latin-1, with the characters only Windows-1252 has written as in Windows-1252,
+/// and descriptions as UTF-8.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TextEncoding {
+    /// UTF-8, with bytes that are not valid UTF-8 replaced by U+FFFD when decoding.
+    /// Character data is a fixed number of bytes per string, so strings longer in
+    /// UTF-8 than in one byte per character widen the parameter when written.
+    Utf8,
+    /// ISO 8859-1, which gives each byte the character with the same code.
+    /// Characters outside Latin-1 are written as `?`.
+    Latin1,
+    /// Windows-1252, the code page of Western European versions of Windows, which has
+    /// printable characters such as `€`, `Š` and `œ` where Latin-1 has control codes.
+    /// Characters outside Windows-1252 are written as `?`.
+    Windows1252,
+}
+
+/// The characters of Windows-1252 from 0x80 to 0x9F, with the five codes it leaves
+/// undefined read as the control codes of Latin-1.
+const WINDOWS_1252: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8D}', 'Ž', '\u{8F}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9D}', 'ž', 'Ÿ',
+];
+
+impl TextEncoding {
+    /// Decodes text stored in a file.
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        match self {
+            TextEncoding::Utf8 => String::from_utf8_lossy(bytes).into_owned(),
+            TextEncoding::Latin1 => bytes.iter().map(|&byte| byte as char).collect(),
+            TextEncoding::Windows1252 => bytes.iter().map(|&byte| windows_1252(byte)).collect(),
+        }
+    }
+
+    /// Encodes text to be stored in a file, replacing the characters that cannot be
+    /// encoded by `?`.
+    pub fn encode(&self, text: &str) -> Vec<u8> {
+        match self {
+            TextEncoding::Utf8 => text.as_bytes().to_vec(),
+            TextEncoding::Latin1 => text.chars().map(latin_1).collect(),
+            TextEncoding::Windows1252 => text.chars().map(single_byte).collect(),
+        }
+    }
+
+    /// Decodes character data read one byte per character, in strings of `row` bytes.
+    /// Strings decoded from UTF-8 are padded with spaces to keep `row` characters.
+    fn decode_chars(&self, chars: &[char], row: usize) -> Vec<char> {
+        chars
+            .chunks(row)
+            .flat_map(|string| {
+                let bytes: Vec<u8> = string.iter().map(|&c| c as u8).collect();
+                let mut decoded: Vec<char> = self.decode(&bytes).chars().collect();
+                decoded.resize(string.len().max(decoded.len()), ' ');
+                decoded
+            })
+            .collect()
+    }
+}
+
+fn windows_1252(byte: u8) -> char {
+    match byte {
+        0x80..=0x9F => WINDOWS_1252[byte as usize - 0x80],
+        _ => byte as char,
+    }
+}
+
+/// Encodes a character as Latin-1, or as `?` if Latin-1 does not have it.
+fn latin_1(c: char) -> u8 {
+    u8::try_from(c).unwrap_or(b'?')
+}
+
+/// Encodes a character in one byte, as Latin-1, or as Windows-1252 for the characters
+/// only it has, or as `?` for those neither has.
+fn single_byte(c: char) -> u8 {
+    match u8::try_from(c) {
+        Ok(byte) => byte,
+        Err(_) => WINDOWS_1252
+            .iter()
+            .position(|&x| x == c)
+            .map_or(b'?', |index| 0x80 + index as u8),
+    }
+}
+
+/// Re-encodes the text of a parameter section written without an encoding, i.e. with
+/// character data in one byte per character and descriptions in UTF-8.
+/// Character data is re-encoded for UTF-8, where the strings are widened to the longest
+/// of them, and for Latin-1, where the characters only Windows-1252 has become `?`.
+/// The offsets to the next group or parameter are updated.
+pub(crate) fn encode_text(
+    bytes: &[u8],
+    processor: &Processor,
+    encoding: TextEncoding,
+) -> Result<Vec<u8>, C3dWriteError> {
+    let mut encoded = bytes[..4.min(bytes.len())].to_vec();
+    let mut i = 4;
+    while i + 4 < bytes.len() && bytes[i + 1] != 0 {
+        let name_length = (bytes[i] as i8).unsigned_abs() as usize;
+        let offset_index = i + 2 + name_length;
+        let name = String::from_utf8_lossy(&bytes[i + 2..offset_index.min(bytes.len())]);
+        let too_large = || C3dWriteError::ParameterTooLarge(name.to_string());
+        let record = bytes
+            .get(offset_index..offset_index + 2)
+            .ok_or_else(too_large)?;
+        let offset = processor.u16([record[0], record[1]]);
+        let mut body = Vec::new();
+        let mut j = offset_index + 2;
+        if (bytes[i + 1] as i8) > 0 {
+            let (data_type, num_dimensions) = (bytes[j] as i8, bytes[j + 1] as usize);
+            let mut dimensions = bytes[j + 2..j + 2 + num_dimensions].to_vec();
+            let length = dimensions.iter().map(|&x| x as usize).product::<usize>()
+                * data_type.unsigned_abs() as usize;
+            let data = &bytes[j + 2 + num_dimensions..j + 2 + num_dimensions + length];
+            j += 2 + num_dimensions + length;
+            let data = match (data_type, encoding) {
+                (-1, TextEncoding::Utf8) => {
+                    let row = dimensions.first().map_or(length, |&x| x as usize).max(1);
+                    let strings: Vec<String> = data
+                        .chunks(row)
+                        .map(|string| string.iter().map(|&byte| windows_1252(byte)).collect())
+                        .collect();
+                    let width = strings
+                        .iter()
+                        .map(|string| string.trim_end().len())
+                        .fold(row, usize::max);
+                    if let Some(first) = dimensions.first_mut() {
+                        *first = u8::try_from(width).map_err(|_| {
+                            C3dWriteError::InvalidParameterDimensions(name.to_string())
+                        })?;
+                    }
+                    strings
+                        .iter()
+                        .flat_map(|string| {
+                            let mut string = string.trim_end().as_bytes().to_vec();
+                            string.resize(width, b' ');
+                            string
+                        })
+                        .collect()
+                }
+                (-1, TextEncoding::Latin1) => data
+                    .iter()
+                    .map(|&byte| latin_1(windows_1252(byte)))
+                    .collect(),
+                _ => data.to_vec(),
+            };
+            body.extend([data_type as u8, num_dimensions as u8]);
+            body.extend(dimensions);
+            body.extend(data);
+        }
+        let description_length = bytes[j] as usize;
+        let description = String::from_utf8_lossy(&bytes[j + 1..j + 1 + description_length]);
+        let description = encoding.encode(&description);
+        body.push(description.len() as u8);
+        body.extend(description);
+        let offset = match offset {
+            0 => 0,
+            _ => u16::try_from(body.len() + 2).map_err(|_| too_large())?,
+        };
+        encoded.extend_from_slice(&bytes[i..offset_index]);
+        encoded.extend(processor.u16_to_bytes(offset));
+        encoded.extend(body);
+        i = j + 1 + description_length;
+    }
+    encoded.extend_from_slice(&bytes[i.min(bytes.len())..]);
+    Ok(encoded)
 }
 
 impl AsRef<Parameter> for Parameter {
@@ -583,7 +1155,64 @@ impl TryFrom<&Parameter> for [char; 2] {
     }
 }
 
+impl TryFrom<u8> for Parameter {
+    type Error = C3dWriteError;
+    fn try_from(data: u8) -> Result<Self, Self::Error> {
+        Ok(Parameter::byte(data))
+    }
+}
+
+impl TryFrom<i16> for Parameter {
+    type Error = C3dWriteError;
+    fn try_from(data: i16) -> Result<Self, Self::Error> {
+        Ok(Parameter::integer(data))
+    }
+}
+
+impl TryFrom<f32> for Parameter {
+    type Error = C3dWriteError;
+    fn try_from(data: f32) -> Result<Self, Self::Error> {
+        Ok(Parameter::float(data))
+    }
+}
+
+impl TryFrom<&str> for Parameter {
+    type Error = C3dWriteError;
+    fn try_from(data: &str) -> Result<Self, Self::Error> {
+        Parameter::string(data.to_string())
+    }
+}
+
+impl TryFrom<String> for Parameter {
+    type Error = C3dWriteError;
+    fn try_from(data: String) -> Result<Self, Self::Error> {
+        Parameter::string(data)
+    }
+}
+
+impl TryFrom<Vec<i16>> for Parameter {
+    type Error = C3dWriteError;
+    fn try_from(data: Vec<i16>) -> Result<Self, Self::Error> {
+        Parameter::integers(data)
+    }
+}
+
+impl TryFrom<Vec<f32>> for Parameter {
+    type Error = C3dWriteError;
+    fn try_from(data: Vec<f32>) -> Result<Self, Self::Error> {
+        Parameter::floats(data)
+    }
+}
+
+impl TryFrom<Vec<String>> for Parameter {
+    type Error = C3dWriteError;
+    fn try_from(data: Vec<String>) -> Result<Self, Self::Error> {
+        Ok(Parameter::strings(data))
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DataType {
     Char = -1,
     Byte = 1,
@@ -617,6 +1246,7 @@ impl TryFrom<i8> for DataType {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Parameter {
     pub(crate) name: String,
     pub description: String,
@@ -633,8 +1263,8 @@ impl Parameter {
         let data_length = match &data {
             ParameterData::Char(data) => data.len(),
             ParameterData::Byte(data) => data.len(),
-            ParameterData::Integer(data) => data.len() * 2,
-            ParameterData::Float(data) => data.len() * 4,
+            ParameterData::Integer(data) => data.len(),
+            ParameterData::Float(data) => data.len(),
         };
         let mut dimensions = dimensions;
         dimensions.retain(|&x| x != 0);
@@ -884,7 +1514,9 @@ impl Parameter {
                 ));
             }
             bytes_to_end += self.description.len();
-            bytes.extend(processor.u16_to_bytes(bytes_to_end as u16));
+            let bytes_to_end = u16::try_from(bytes_to_end)
+                .map_err(|_| C3dWriteError::ParameterTooLarge(parameter_name.clone()))?;
+            bytes.extend(processor.u16_to_bytes(bytes_to_end));
         }
         match &self.data {
             ParameterData::Char(_) => {
@@ -911,7 +1543,7 @@ impl Parameter {
         bytes.extend(&self.dimensions);
         match &self.data {
             ParameterData::Char(data) => {
-                bytes.extend(data.iter().map(|&x| x as u8));
+                bytes.extend(data.iter().map(|&x| single_byte(x)));
             }
             ParameterData::Byte(data) => {
                 bytes.extend(data);
@@ -933,6 +1565,7 @@ impl Parameter {
 /// are also stored in the file. This struct stores the data type and dimensions, and provides
 /// methods to convert the data to a more useful format.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ParameterData {
     Char(Vec<char>),
     Byte(Vec<u8>),
@@ -991,7 +1624,9 @@ impl ParameterData {
 fn parse_parameters(
     parameter_blocks: &Vec<u8>,
     processor: &Processor,
-) -> Result<HashMap<String, (String, HashMap<String, Parameter>)>, C3dParseError> {
+    options: &ParseOptions,
+    warnings: &mut Vec<ParseWarning>,
+) -> Result<Parameters, C3dParseError> {
     if parameter_blocks.len() < 512 {
         return Err(C3dParseError::InvalidParameterStartBlock);
     }
@@ -1003,19 +1638,37 @@ fn parse_parameters(
 
     while index != 0 {
         index = parse_next_group_or_parameter(
-            &parameter_blocks,
+            parameter_blocks,
             index,
             &mut groups,
             &mut parameters,
             processor,
+            options.encoding,
         )?;
+        options.limits.check_parameters(parameters.len())?;
     }
     let mut groups_map = HashMap::new();
     let mut temp_group_id_to_name = HashMap::new();
+    let mut locked = HashSet::new();
     for group in groups {
+        if group.locked {
+            locked.insert((group.name.clone(), String::new()));
+        }
+        if !group.valid_description {
+            warnings.push(ParseWarning::InvalidDescription(group.name.clone()));
+        }
         temp_group_id_to_name.insert(group.id, group.name.clone());
+        // the parameters of a repeated group are merged, whatever group ID they refer to
+        if let Some((description, _)) = groups_map.get_mut(&group.name) {
+            warnings.push(ParseWarning::DuplicateGroup(group.name.clone()));
+            if options.duplicates == Some(DuplicatePolicy::LastWins) {
+                *description = group.description;
+            }
+            continue;
+        }
         groups_map.insert(group.name.clone(), (group.description, HashMap::new()));
     }
+    let mut definitions: Vec<(String, Parameter)> = Vec::new();
     insert_missing_required_groups(&mut groups_map, &mut temp_group_id_to_name)?;
     for parameter in parameters {
         let group_name = match temp_group_id_to_name.contains_key(&parameter.group_id) {
@@ -1033,19 +1686,69 @@ fn parse_parameters(
             }
         };
         let name = parameter.name.clone();
+        if name.starts_with("LABELS") && matches!(parameter.data, ParameterData::Char(_)) {
+            let length = parameter.dimensions.first().copied().unwrap_or(0) as usize;
+            options.limits.check_label_length(length).map_err(|e| {
+                e.context(
+                    ErrorContext::new("parameter")
+                        .group(&group_name)
+                        .parameter(&name),
+                )
+            })?;
+        }
+        if !parameter.valid_description {
+            warnings.push(ParseWarning::InvalidDescription(format!(
+                "{}:{}",
+                group_name, name
+            )));
+        }
+        if parameter.locked {
+            locked.insert((group_name.clone(), name.clone()));
+        }
         let parameter = Parameter {
             name: parameter.name,
             description: parameter.description,
             dimensions: parameter.dimensions,
             data: parameter.data,
         };
-        groups_map
+        let group = &mut groups_map
             .get_mut(&group_name)
             .ok_or(C3dParseError::InvalidGroupId)?
-            .1
-            .insert(name, parameter);
-    }
-    Ok(groups_map)
+            .1;
+        // a malformed file may define the same parameter twice in a group,
+        // in which case the first definition is kept unless a policy says otherwise.
+        // Some software repeats identical definitions, which is not ambiguous and
+        // only warned about.
+        if let Some(kept) = group.get(&name) {
+            let identical = kept.dimensions == parameter.dimensions
+                && kept.data == parameter.data
+                && kept.description == parameter.description;
+            if options.strict && !identical && options.duplicates.is_none() {
+                let context = ErrorContext::new("parameter")
+                    .group(&group_name)
+                    .parameter(&name);
+                return Err(C3dParseError::DuplicateParameter(group_name, name).context(context));
+            }
+            if !definitions
+                .iter()
+                .any(|(group, defined)| group == &group_name && defined.name == name)
+            {
+                definitions.push((group_name.clone(), kept.clone()));
+            }
+            definitions.push((group_name.clone(), parameter.clone()));
+            warnings.push(ParseWarning::DuplicateParameter(group_name, name.clone()));
+            if options.duplicates != Some(DuplicatePolicy::LastWins) {
+                continue;
+            }
+        }
+        group.insert(name, parameter);
+    }
+    Ok(Parameters {
+        parameters: groups_map,
+        definitions,
+        detached_descriptions: HashMap::new(),
+        locked,
+    })
 }
 
 fn parse_next_group_or_parameter(
@@ -1054,6 +1757,7 @@ fn parse_next_group_or_parameter(
     groups: &mut Vec<ParsedGroup>,
     parameters: &mut Vec<ParsedParameter>,
     processor: &Processor,
+    encoding: Option<TextEncoding>,
 ) -> Result<usize, C3dParseError> {
     if index + 1 >= parameter_blocks.len() {
         return Ok(0);
@@ -1064,41 +1768,86 @@ fn parse_next_group_or_parameter(
     if group_id == 0 {
         return Ok(0);
     } else if group_id < 0 {
-        let (group, next_index) = parse_group(&parameter_blocks, index, processor)?;
+        let (group, next_index) = parse_group(parameter_blocks, index, processor, encoding)
+            .map_err(|e| e.context(ErrorContext::new("group").at(index as u64)))?;
         groups.push(group);
         Ok(next_index as usize)
     } else {
-        let (parameter, next_index) = parse_parameter(&parameter_blocks, index, processor)?;
+        let (parameter, next_index) = parse_parameter(parameter_blocks, index, processor, encoding)
+            .map_err(|e| e.context(parameter_context(parameter_blocks, index, groups)))?;
         parameters.push(parameter);
         Ok(next_index as usize)
     }
 }
 
+/// Describes the parameter starting at the index, with as much of its name and group
+/// as can be read.
+fn parameter_context(
+    parameter_blocks: &[u8],
+    index: usize,
+    groups: &[ParsedGroup],
+) -> ErrorContext {
+    let mut context = ErrorContext::new("parameter").at(index as u64);
+    let name_length = (parameter_blocks[index] as i8).unsigned_abs() as usize;
+    if let Some(name) = parameter_blocks.get(index + 2..index + 2 + name_length) {
+        context = context.parameter(&String::from_utf8_lossy(name).to_ascii_uppercase());
+    }
+    let group_id = parameter_blocks[index + 1] as i8;
+    if let Some(group) = groups.iter().find(|group| group.id == group_id) {
+        context = context.group(&group.name);
+    }
+    context
+}
+
+/// Returns the bytes of the parameter section from the index, or an error if the
+/// section ends before them.
+pub(crate) fn section_bytes(
+    parameter_blocks: &[u8],
+    index: usize,
+    len: usize,
+) -> Result<&[u8], C3dParseError> {
+    index
+        .checked_add(len)
+        .and_then(|end| parameter_blocks.get(index..end))
+        .ok_or(C3dParseError::InvalidParametersOffset)
+}
+
+pub(crate) fn section_byte(parameter_blocks: &[u8], index: usize) -> Result<u8, C3dParseError> {
+    Ok(section_bytes(parameter_blocks, index, 1)?[0])
+}
+
 fn parse_group(
     parameter_blocks: &Vec<u8>,
     index: usize,
     processor: &Processor,
+    encoding: Option<TextEncoding>,
 ) -> Result<(ParsedGroup, usize), C3dParseError> {
     let mut i = index;
-    let num_chars_in_name = parameter_blocks[i] as i8;
+    let num_chars_in_name = section_byte(parameter_blocks, i)? as i8;
     i += 1;
-    let id = (parameter_blocks[i] as i8).abs();
+    // -128 has no positive counterpart for a parameter to refer to
+    let id = (section_byte(parameter_blocks, i)? as i8)
+        .checked_neg()
+        .ok_or(C3dParseError::InvalidGroupId)?;
     i += 1;
-    let name = parse_group_name(&parameter_blocks, i, num_chars_in_name)?;
-    i += num_chars_in_name.abs() as usize;
-    let next_group_index_bytes = &parameter_blocks[i..i + 2];
+    let name = parse_group_name(parameter_blocks, i, num_chars_in_name)?;
+    i += num_chars_in_name.unsigned_abs() as usize;
+    let next_group_index_bytes = section_bytes(parameter_blocks, i, 2)?;
     let next_group_index =
         processor.u16(next_group_index_bytes.try_into().unwrap()) as usize + i as usize;
     i += 2;
-    let num_chars_in_description = parameter_blocks[i];
+    let num_chars_in_description = section_byte(parameter_blocks, i)?;
     i += 1;
-    let description = parse_description(&parameter_blocks, i, num_chars_in_description)?;
+    let (description, valid_description) =
+        parse_description(parameter_blocks, i, num_chars_in_description, encoding)?;
 
     Ok((
         ParsedGroup {
             id,
             name,
+            valid_description,
             description,
+            locked: num_chars_in_name < 0,
         },
         next_group_index,
     ))
@@ -1109,31 +1858,46 @@ fn parse_group_name(
     index: usize,
     num_chars_in_name: i8,
 ) -> Result<String, C3dParseError> {
-    let mut group_name = String::new();
-
-    for i in 0..num_chars_in_name.abs() {
-        group_name.push(parameter_blocks[index + i as usize] as char);
-    }
-
-    Ok(group_name)
+    let length = num_chars_in_name.unsigned_abs() as usize;
+    let name = section_bytes(parameter_blocks, index, length)?;
+    Ok(name.iter().map(|&c| c as char).collect())
 }
 
+/// Returns the description and whether it was valid text. Without an encoding, or in
+/// UTF-8, bytes that are not valid UTF-8 are replaced by U+FFFD, which is common enough
+/// in files from older systems that it is not an error. A replacement takes more bytes
+/// than the byte it replaces, so the description is cut to the 255 bytes a file can
+/// store, to keep the file writable.
 fn parse_description(
-    parameter_blocks: &Vec<u8>,
+    parameter_blocks: &[u8],
     index: usize,
     num_chars_in_description: u8,
-) -> Result<String, C3dParseError> {
-    let mut description = Vec::new();
-    for i in 0..num_chars_in_description {
-        description.push(parameter_blocks[index + i as usize]);
+    encoding: Option<TextEncoding>,
+) -> Result<(String, bool), C3dParseError> {
+    let description = section_bytes(parameter_blocks, index, num_chars_in_description as usize)?;
+    match encoding {
+        None | Some(TextEncoding::Utf8) => {
+            let decoded = utf8_lossy(description);
+            let valid = matches!(decoded, Cow::Borrowed(_));
+            Ok((decoded.into_owned(), valid))
+        }
+        Some(encoding) => Ok((encoding.decode(description), true)),
     }
-    let utf = String::from_utf8(description.clone());
-    match utf {
-        Ok(utf) => Ok(utf),
-        //Err(_) => Err(C3dParseError::InvalidDescription),
-        Err(_) => {
-            Ok("".to_string()) // some files have invalid descriptions
+}
+
+/// Decodes a description as UTF-8, borrowing it if it is valid and otherwise replacing
+/// the invalid bytes by U+FFFD and cutting it to 255 bytes.
+pub(crate) fn utf8_lossy(description: &[u8]) -> Cow<'_, str> {
+    match String::from_utf8_lossy(description) {
+        Cow::Owned(mut decoded) => {
+            let mut end = decoded.len().min(u8::MAX as usize);
+            while !decoded.is_char_boundary(end) {
+                end -= 1;
+            }
+            decoded.truncate(end);
+            Cow::Owned(decoded)
         }
+        borrowed => borrowed,
     }
 }
 
@@ -1141,29 +1905,35 @@ fn parse_parameter(
     parameter_blocks: &Vec<u8>,
     index: usize,
     processor: &Processor,
+    encoding: Option<TextEncoding>,
 ) -> Result<(ParsedParameter, usize), C3dParseError> {
     let mut i = index;
-    let num_chars_in_name = parameter_blocks[i] as i8;
+    let num_chars_in_name = section_byte(parameter_blocks, i)? as i8;
     i += 1;
-    let group_id = parameter_blocks[i] as i8;
+    let group_id = section_byte(parameter_blocks, i)? as i8;
     i += 1;
-    let name = parse_parameter_name(&parameter_blocks, i, num_chars_in_name)?.to_ascii_uppercase();
-    i += num_chars_in_name.abs() as usize;
-    let next_index_bytes = &parameter_blocks[i..i + 2];
+    let name = parse_parameter_name(parameter_blocks, i, num_chars_in_name)?.to_ascii_uppercase();
+    i += num_chars_in_name.unsigned_abs() as usize;
+    let next_index_bytes = section_bytes(parameter_blocks, i, 2)?;
     let next_index = processor.u16(next_index_bytes.try_into().unwrap()) as usize + i as usize;
     i += 2;
-    let data_type = DataType::try_from(parameter_blocks[i] as i8)?;
+    let data_type = DataType::try_from(section_byte(parameter_blocks, i)? as i8)?;
     i += 1;
-    let num_dimensions = parameter_blocks[i];
+    let num_dimensions = section_byte(parameter_blocks, i)?;
     i += 1;
-    let dimensions = parse_dimensions(&parameter_blocks, i, num_dimensions, data_type)?;
+    let dimensions = parse_dimensions(parameter_blocks, i, num_dimensions, data_type)?;
     i += num_dimensions as usize;
-    let (data, data_byte_size) =
-        parse_data(&parameter_blocks, i, &dimensions, data_type, processor)?;
+    let (mut data, data_byte_size) =
+        parse_data(parameter_blocks, i, &dimensions, data_type, processor)?;
     i += data_byte_size;
-    let num_chars_in_description = parameter_blocks[i];
+    if let (ParameterData::Char(chars), Some(encoding)) = (&mut data, encoding) {
+        let row = dimensions.first().copied().unwrap_or(1).max(1) as usize;
+        *chars = encoding.decode_chars(chars, row);
+    }
+    let num_chars_in_description = section_byte(parameter_blocks, i)?;
     i += 1;
-    let description = parse_description(&parameter_blocks, i, num_chars_in_description)?;
+    let (description, valid_description) =
+        parse_description(parameter_blocks, i, num_chars_in_description, encoding)?;
 
     Ok((
         ParsedParameter {
@@ -1171,7 +1941,9 @@ fn parse_parameter(
             name,
             data,
             dimensions,
+            valid_description,
             description,
+            locked: num_chars_in_name < 0,
         },
         next_index,
     ))
@@ -1182,13 +1954,9 @@ fn parse_parameter_name(
     index: usize,
     num_chars_in_name: i8,
 ) -> Result<String, C3dParseError> {
-    let mut parameter_name = String::new();
-
-    for i in 0..num_chars_in_name.abs() {
-        parameter_name.push(parameter_blocks[index + i as usize] as char);
-    }
-
-    Ok(parameter_name)
+    let length = num_chars_in_name.unsigned_abs() as usize;
+    let name = section_bytes(parameter_blocks, index, length)?;
+    Ok(name.iter().map(|&c| c as char).collect())
 }
 
 fn parse_dimensions(
@@ -1197,11 +1965,7 @@ fn parse_dimensions(
     num_dimensions: u8,
     data_type: DataType,
 ) -> Result<Vec<u8>, C3dParseError> {
-    let mut dimensions = Vec::new();
-
-    for i in 0..num_dimensions {
-        dimensions.push(parameter_blocks[index + i as usize]);
-    }
+    let mut dimensions = section_bytes(parameter_blocks, index, num_dimensions as usize)?.to_vec();
     dimensions.retain(|&x| x != 0);
     if data_type != DataType::Char {
         dimensions.retain(|&x| x != 1);
@@ -1220,19 +1984,16 @@ fn parse_data(
     data_type: DataType,
     processor: &Processor,
 ) -> Result<(ParameterData, usize), C3dParseError> {
-    let dimensions_product = &dimensions
-        .clone()
+    // the product of many dimensions can overflow, and is bounded by the section anyway
+    let data_byte_size = dimensions
         .iter()
-        .map(|x| *x as usize)
-        .product::<usize>();
-
-    let data_byte_size = dimensions_product * usize::from(data_type);
-
-    if index + data_byte_size > parameter_blocks.len() {
-        return Err(C3dParseError::InvalidParameterData);
-    }
+        .try_fold(usize::from(data_type), |size, &x| {
+            size.checked_mul(x as usize)
+        })
+        .ok_or(C3dParseError::InvalidParameterData)?;
 
-    let bytes: &[u8] = &parameter_blocks[index..index + data_byte_size];
+    let bytes = section_bytes(parameter_blocks, index, data_byte_size)
+        .map_err(|_| C3dParseError::InvalidParameterData)?;
 
     Ok((
         ParameterData::new(bytes, dimensions, data_type, processor)?,
@@ -1245,6 +2006,10 @@ struct ParsedGroup {
     id: i8,
     name: String,
     description: String,
+    /// False if the description was not valid UTF-8 and was decoded with U+FFFD in place
+    /// of the invalid bytes.
+    valid_description: bool,
+    locked: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -1254,6 +2019,8 @@ struct ParsedParameter {
     pub data: ParameterData,
     dimensions: Vec<u8>,
     description: String,
+    valid_description: bool,
+    locked: bool,
 }
 
 const REQUIRED_GROUPS: [&str; 8] = [
@@ -1,16 +1,28 @@
 //! Implements the Points struct and methods for parsing and writing point data.
-use crate::data::{get_analog_bytes_per_frame, get_point_bytes_per_frame, DataFormat, MarkerPoint};
-use crate::parameters::{Parameter, ParameterData, Parameters};
+use crate::data::{
+    decode_chunks, get_analog_bytes_per_frame, get_point_bytes_per_frame, select_columns,
+    select_rows, selected_columns, DataFormat, LabelMatch, MarkerPoint,
+};
+use crate::filters::Butterworth;
+use crate::kinematics::rotate;
+use crate::labels::RelabelReport;
+use crate::parameters::{write_continued, Parameter, ParameterData, Parameters};
 use crate::processor::Processor;
-use crate::{C3dParseError, C3dWriteError};
+use crate::smoothing::Smoother;
+use crate::{C3dFilterError, C3dParseError, C3dWriteError, ParseLimits};
 use grid::Grid;
 use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
-use std::ops::{Deref, DerefMut};
+use std::ops::{Deref, DerefMut, Index, IndexMut, Range};
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Points {
     parsed_header: bool,
+    declared_frames: usize,
+    /// The number of points stored in each frame, as given by the header.
+    stored_points: usize,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::grid"))]
     pub points: Grid<MarkerPoint>,
     pub labels: Vec<String>,
     pub descriptions: Vec<String>,
@@ -43,6 +55,105 @@ impl PartialEq for Points {
     }
 }
 
+/// The velocity and acceleration of a point in each stored frame, as returned by
+/// `Points::derivatives`. Frames where they cannot be computed are `None`.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Derivatives {
+    pub velocity: Vec<Option<[f32; 3]>>,
+    pub acceleration: Vec<Option<[f32; 3]>>,
+}
+
+/// A direction along an axis of the lab, as stored in POINT:X_SCREEN and POINT:Y_SCREEN
+/// to tell viewers which lab axes point right and up on the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScreenAxis {
+    PlusX,
+    MinusX,
+    PlusY,
+    MinusY,
+    PlusZ,
+    MinusZ,
+}
+
+impl ScreenAxis {
+    const AXES: [ScreenAxis; 6] = [
+        ScreenAxis::PlusX,
+        ScreenAxis::MinusX,
+        ScreenAxis::PlusY,
+        ScreenAxis::MinusY,
+        ScreenAxis::PlusZ,
+        ScreenAxis::MinusZ,
+    ];
+
+    /// Parses an axis as stored in the parameters, e.g. `['+', 'X']`, ignoring case.
+    /// Axes without a minus sign are taken as positive.
+    pub fn from_chars(chars: [char; 2]) -> Option<ScreenAxis> {
+        let index = match chars[1].to_ascii_uppercase() {
+            'X' => 0,
+            'Y' => 1,
+            'Z' => 2,
+            _ => return None,
+        };
+        Some(Self::AXES[2 * index + (chars[0] == '-') as usize])
+    }
+
+    pub fn to_chars(&self) -> [char; 2] {
+        let sign = match self.is_negative() {
+            true => '-',
+            false => '+',
+        };
+        [sign, ['X', 'Y', 'Z'][self.index()]]
+    }
+
+    /// Returns the index of the lab axis, 0 for X, 1 for Y and 2 for Z.
+    pub fn index(&self) -> usize {
+        *self as usize / 2
+    }
+
+    pub fn is_negative(&self) -> bool {
+        *self as usize % 2 == 1
+    }
+
+    /// Returns the unit vector of the direction in lab coordinates.
+    pub fn direction(&self) -> [f32; 3] {
+        let mut direction = [0.; 3];
+        direction[self.index()] = match self.is_negative() {
+            true => -1.,
+            false => 1.,
+        };
+        direction
+    }
+
+    /// Returns the axis closest to a direction in lab coordinates.
+    pub fn closest(direction: [f32; 3]) -> ScreenAxis {
+        let index = (0..3)
+            .max_by(|&a, &b| direction[a].abs().total_cmp(&direction[b].abs()))
+            .unwrap_or(0);
+        Self::AXES[2 * index + (direction[index] < 0.) as usize]
+    }
+
+    /// Returns the axis closest to where a rotation, given as rows, takes this one.
+    pub fn rotated(&self, rotation: &[[f32; 3]; 3]) -> ScreenAxis {
+        ScreenAxis::closest(rotate(rotation, self.direction()))
+    }
+
+    /// Returns the axis pointing out of the screen, towards the viewer, for the axes
+    /// pointing right and up, or `None` if they are along the same lab axis.
+    pub fn out_of_screen(x: ScreenAxis, y: ScreenAxis) -> Option<ScreenAxis> {
+        if x.index() == y.index() {
+            return None;
+        }
+        let (a, b) = (x.direction(), y.direction());
+        Some(ScreenAxis::closest([
+            a[1] * b[2] - a[2] * b[1],
+            a[2] * b[0] - a[0] * b[2],
+            a[0] * b[1] - a[1] * b[0],
+        ]))
+    }
+}
+
 impl Debug for Points {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Points")
@@ -76,6 +187,58 @@ impl DerefMut for Points {
     }
 }
 
+/// Indexes the rows of the grid, as it would be through `Deref`, which the
+/// label index below would otherwise hide.
+impl Index<usize> for Points {
+    type Output = [MarkerPoint];
+
+    fn index(&self, row: usize) -> &Self::Output {
+        &self.points[row]
+    }
+}
+
+impl IndexMut<usize> for Points {
+    fn index_mut(&mut self, row: usize) -> &mut Self::Output {
+        &mut self.points[row]
+    }
+}
+
+impl Index<(usize, usize)> for Points {
+    type Output = MarkerPoint;
+
+    fn index(&self, index: (usize, usize)) -> &Self::Output {
+        &self.points[index]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Points {
+    fn index_mut(&mut self, index: (usize, usize)) -> &mut Self::Output {
+        &mut self.points[index]
+    }
+}
+
+/// Indexes a point by frame and label, e.g. `[(0, "LASI")]`, matching labels as
+/// `get_by_label` does. Panics if no point has the label or the frame is out of range.
+impl Index<(usize, &str)> for Points {
+    type Output = MarkerPoint;
+
+    fn index(&self, (frame, label): (usize, &str)) -> &Self::Output {
+        match self.label_index(label) {
+            Some(column) => &self.points[(frame, column)],
+            None => panic!("no point labelled {}", label),
+        }
+    }
+}
+
+impl IndexMut<(usize, &str)> for Points {
+    fn index_mut(&mut self, (frame, label): (usize, &str)) -> &mut Self::Output {
+        match self.label_index(label) {
+            Some(column) => &mut self.points[(frame, column)],
+            None => panic!("no point labelled {}", label),
+        }
+    }
+}
+
 impl ToString for Points {
     fn to_string(&self) -> String {
         let mut string = String::new();
@@ -103,6 +266,8 @@ impl Default for Points {
     fn default() -> Self {
         Points {
             parsed_header: false,
+            declared_frames: 0,
+            stored_points: 0,
             points: Grid::new(0, 0),
             labels: Vec::new(),
             descriptions: Vec::new(),
@@ -124,15 +289,324 @@ impl Points {
         Points::default()
     }
 
+    /// Returns the lab axis pointing right on the screen, from POINT:X_SCREEN,
+    /// or `None` if the parameter is missing or is not an axis.
+    pub fn x_screen_axis(&self) -> Option<ScreenAxis> {
+        ScreenAxis::from_chars(self.x_screen?)
+    }
+
+    /// Returns the lab axis pointing up on the screen, from POINT:Y_SCREEN,
+    /// or `None` if the parameter is missing or is not an axis.
+    pub fn y_screen_axis(&self) -> Option<ScreenAxis> {
+        ScreenAxis::from_chars(self.y_screen?)
+    }
+
+    /// Returns the column of the marker with the given label, if it has data.
+    pub(crate) fn label_index(&self, label: &str) -> Option<usize> {
+        self.find_label(label, LabelMatch::Trimmed)
+    }
+
+    /// Returns the column of the first point whose label matches the one given,
+    /// if it has data.
+    pub fn find_label(&self, label: &str, matching: LabelMatch) -> Option<usize> {
+        matching.find(&self.labels, self.cols(), label)
+    }
+
+    /// Returns every frame of the point with the given label, ignoring the
+    /// whitespace around labels, or `None` if no point has that label.
+    pub fn get_by_label(&self, label: &str) -> Option<Vec<MarkerPoint>> {
+        self.get_by_label_matching(label, LabelMatch::Trimmed)
+    }
+
+    /// Returns every frame of the first point whose label matches the one given.
+    pub fn get_by_label_matching(
+        &self,
+        label: &str,
+        matching: LabelMatch,
+    ) -> Option<Vec<MarkerPoint>> {
+        let column = self.find_label(label, matching)?;
+        Some(self.points.iter_col(column).copied().collect())
+    }
+
+    /// Renames the points whose label is a key of the mapping to the value of that key,
+    /// ignoring the whitespace around labels. A description that repeats the old label
+    /// is renamed with it. Points of other kinds than markers, such as angles, are
+    /// renamed too, but the POINT parameters listing them are only updated by
+    /// `C3d::relabel_points`.
+    pub fn relabel(&mut self, mapping: &HashMap<String, String>) -> RelabelReport {
+        let mut report = RelabelReport::default();
+        let mut matched = vec![false; mapping.len()];
+        let keys: Vec<&String> = mapping.keys().collect();
+        for column in 0..self.labels.len().min(self.cols()) {
+            let old = self.labels[column].trim().to_string();
+            let key = match keys.iter().position(|key| key.trim() == old) {
+                Some(key) => key,
+                None => continue,
+            };
+            matched[key] = true;
+            let new = mapping[keys[key]].trim().to_string();
+            if new == old {
+                continue;
+            }
+            if let Some(description) = self.descriptions.get_mut(column) {
+                if description.trim() == old {
+                    *description = new.clone();
+                }
+            }
+            self.labels[column] = new.clone();
+            report.renamed.push((old, new));
+        }
+        report.unmatched = keys
+            .iter()
+            .zip(matched)
+            .filter(|(_, matched)| !matched)
+            .map(|(key, _)| key.trim().to_string())
+            .collect();
+        report.unmatched.sort();
+        let mut labels: Vec<&str> = self.labels.iter().map(|label| label.trim()).collect();
+        labels.sort();
+        report.duplicates = labels
+            .windows(2)
+            .filter(|pair| pair[0] == pair[1])
+            .map(|pair| pair[0].to_string())
+            .collect();
+        report.duplicates.dedup();
+        report
+    }
+
+    /// Returns the number of frames declared by the header and parameters.
+    pub(crate) fn declared_frames(&self) -> usize {
+        self.declared_frames
+    }
+
+    /// Returns the number of points stored in each frame, as given by the header.
+    pub(crate) fn stored_points(&self) -> usize {
+        self.stored_points
+    }
+
+    /// Returns the POINT:SCALE that maps the largest coordinate of the valid points
+    /// onto the largest 16-bit integer, or the current scale if every point is invalid.
+    pub(crate) fn integer_scale_factor(&self) -> f32 {
+        let largest = self
+            .points
+            .iter()
+            .filter(|point| point.is_valid())
+            .flat_map(|point| point.point)
+            .filter(|x| x.is_finite())
+            .fold(0f32, |largest, x| largest.max(x.abs()));
+        match largest > 0. {
+            true => largest / i16::MAX as f32,
+            false if self.scale_factor > 0. => self.scale_factor,
+            false => 1.,
+        }
+    }
+
+    /// Keeps only the given frames, which are numbered from frame 1.
+    pub(crate) fn crop(&mut self, frames: Range<usize>) {
+        self.points = select_rows(&self.points, frames.clone());
+        self.set_frame_range(1, frames.len());
+    }
+
+    /// Sets the header frame range and the declared number of frames,
+    /// after frames were added or removed.
+    pub(crate) fn set_frame_range(&mut self, first_frame: u16, num_frames: usize) {
+        self.first_frame = first_frame;
+        self.last_frame =
+            (first_frame as usize + num_frames.max(1) - 1).min(u16::MAX as usize) as u16;
+        self.declared_frames = num_frames;
+    }
+
+    /// Whether both the header and the given POINT:FRAMES are stuck at 65535,
+    /// the most frames they can hold.
+    pub(crate) fn frame_count_saturated(&self, num_frames: usize) -> bool {
+        self.last_frame == u16::MAX && num_frames == u16::MAX as usize
+    }
+
+    /// Returns the first and last frame of a trial given its number of frames,
+    /// if the last frame does not fit in the header and is written to the TRIAL group.
+    pub(crate) fn actual_fields(&self, num_frames: usize) -> Option<(usize, usize)> {
+        let first_frame = self.first_frame.max(1) as usize;
+        let last_frame = first_frame + num_frames.max(1) - 1;
+        match last_frame > u16::MAX as usize {
+            true => Some((first_frame, last_frame)),
+            false => None,
+        }
+    }
+
+    /// Returns the time in seconds of each stored frame, relative to the first frame.
+    pub fn frame_times(&self) -> Vec<f32> {
+        (0..self.rows())
+            .map(|frame| frame as f32 / self.frame_rate)
+            .collect()
+    }
+
+    /// Whether the point in the given column was reconstructed in the given stored frame.
+    /// Returns `false` if the frame or column is out of range.
+    pub fn is_valid(&self, frame: usize, point: usize) -> bool {
+        self.get(frame, point).is_some_and(MarkerPoint::is_valid)
+    }
+
+    /// Returns the residual of the point in the given column and stored frame, in the
+    /// units of the point, or `None` if it is invalid or out of range.
+    pub fn residual(&self, frame: usize, point: usize) -> Option<f32> {
+        self.get(frame, point)?.valid_residual()
+    }
+
+    /// Returns the residual of the point in the given column in every stored frame.
+    pub fn residuals(&self, point: usize) -> Vec<Option<f32>> {
+        match point < self.cols() {
+            true => self.iter_col(point).map(|x| x.valid_residual()).collect(),
+            false => Vec::new(),
+        }
+    }
+
+    /// Returns the cameras, counted from 0, that contributed to the point in the given
+    /// column and stored frame. Invalid and out of range points were seen by none.
+    pub fn cameras_seen(&self, frame: usize, point: usize) -> Vec<usize> {
+        self.get(frame, point)
+            .map(MarkerPoint::cameras_seen)
+            .unwrap_or_default()
+    }
+
+    /// Returns all samples as one contiguous row-major slice of frames by points,
+    /// borrowed from the storage without copying.
+    pub fn as_slice(&self) -> &[MarkerPoint] {
+        self.points.flatten()
+    }
+
+    /// Returns the samples of all points in a stored frame as a contiguous slice,
+    /// or `None` if the frame is not stored.
+    /// The samples of a single point are not contiguous, see `iter_col` for them.
+    pub fn frame(&self, frame: usize) -> Option<&[MarkerPoint]> {
+        match frame < self.rows() {
+            true => Some(self.points.iter_row(frame).as_slice()),
+            false => None,
+        }
+    }
+
+    /// Returns the samples of all points in a stored frame as a mutable slice,
+    /// or `None` if the frame is not stored.
+    pub fn frame_mut(&mut self, frame: usize) -> Option<&mut [MarkerPoint]> {
+        match frame < self.rows() {
+            true => Some(self.points.iter_row_mut(frame).into_slice()),
+            false => None,
+        }
+    }
+
+    /// Returns the shape and the samples of the points as a row-major array of
+    /// frames by points by X, Y, Z and residual, e.g. to build an `ndarray::Array3`
+    /// with `Array3::from_shape_vec(shape, samples)` without reshaping.
+    pub fn to_array3(&self) -> ([usize; 3], Vec<f32>) {
+        let mut samples = Vec::with_capacity(self.rows() * self.cols() * 4);
+        for point in self.points.iter() {
+            samples.extend_from_slice(&point.point);
+            samples.push(point.residual);
+        }
+        ([self.rows(), self.cols(), 4], samples)
+    }
+
+    /// Returns the X, Y and Z coordinates of a point in every frame, e.g. to build
+    /// `nalgebra::Vector3`s with `Vector3::from`. Invalid samples are included as stored,
+    /// and a point that is not stored has no frames.
+    pub fn positions(&self, point: usize) -> Vec<[f32; 3]> {
+        match point < self.cols() {
+            true => self.points.iter_col(point).map(|x| x.point).collect(),
+            false => Vec::new(),
+        }
+    }
+
+    /// Adds a point with one sample for each stored frame, such as a virtual marker
+    /// computed from other markers, returning its column.
+    /// The first point sets the number of frames.
+    /// Returns `None` if the number of samples does not match the stored frames.
+    pub fn add_point(
+        &mut self,
+        label: &str,
+        description: &str,
+        samples: Vec<MarkerPoint>,
+    ) -> Option<usize> {
+        let points = self.cols();
+        match points {
+            0 if samples.is_empty() => return None,
+            0 => self.points = Grid::from_vec(samples, 1),
+            _ if samples.len() != self.rows() => return None,
+            _ => self.points.push_col(samples),
+        }
+        self.labels.resize(points, String::new());
+        self.labels.push(label.to_string());
+        self.descriptions.resize(points, String::new());
+        self.descriptions.push(description.to_string());
+        Some(points)
+    }
+
+    /// Returns the velocity and acceleration of a point in each stored frame, in its units
+    /// per second and per second squared, from central differences between frames.
+    /// With `smoothing` the trajectory is filtered first, as `C3d::filter_points` does.
+    /// The ends of each run of valid samples use one-sided differences for the velocity
+    /// and have no acceleration, and frames without a valid sample have neither.
+    pub fn derivatives(
+        &self,
+        label: &str,
+        smoothing: Option<&Butterworth>,
+    ) -> Result<Derivatives, C3dFilterError> {
+        let column = self
+            .label_index(label)
+            .ok_or_else(|| C3dFilterError::PointNotFound(label.to_string()))?;
+        let mut points: Vec<MarkerPoint> = self.iter_col(column).cloned().collect();
+        if let Some(smoothing) = smoothing {
+            smoothing.filter_points(&mut points, self.frame_rate as f64)?;
+        }
+        let rate = self.frame_rate;
+        let valid = |frame: usize| points.get(frame).filter(|point| point.is_valid());
+        let difference = |a: &MarkerPoint, b: &MarkerPoint, frames: f32| {
+            [0, 1, 2].map(|axis| (b.point[axis] - a.point[axis]) * rate / frames)
+        };
+        let mut derivatives = Derivatives {
+            velocity: Vec::with_capacity(points.len()),
+            acceleration: Vec::with_capacity(points.len()),
+        };
+        for frame in 0..points.len() {
+            let previous = frame.checked_sub(1).and_then(valid);
+            let (point, next) = (valid(frame), valid(frame + 1));
+            derivatives.velocity.push(match (previous, point, next) {
+                (Some(previous), Some(_), Some(next)) => Some(difference(previous, next, 2.)),
+                (Some(previous), Some(point), None) => Some(difference(previous, point, 1.)),
+                (None, Some(point), Some(next)) => Some(difference(point, next, 1.)),
+                _ => None,
+            });
+            let acceleration = match (previous, point, next) {
+                (Some(previous), Some(point), Some(next)) => Some([0, 1, 2].map(|axis| {
+                    (next.point[axis] - 2. * point.point[axis] + previous.point[axis]) * rate * rate
+                })),
+                _ => None,
+            };
+            derivatives.acceleration.push(acceleration);
+        }
+        Ok(derivatives)
+    }
+
+    /// Returns the velocity and acceleration of a point in each stored frame, in its units
+    /// per second and per second squared, from the derivatives of the Savitzky–Golay
+    /// polynomials fit to each run of valid samples. Runs with no more samples than the
+    /// order of the polynomials have neither, and no acceleration is given below order 2.
+    pub fn smoothed_derivatives(
+        &self,
+        label: &str,
+        smoother: &Smoother,
+    ) -> Result<Derivatives, C3dFilterError> {
+        let column = self
+            .label_index(label)
+            .ok_or_else(|| C3dFilterError::PointNotFound(label.to_string()))?;
+        let points: Vec<MarkerPoint> = self.iter_col(column).cloned().collect();
+        smoother.derivatives(&points, self.frame_rate as f64)
+    }
+
     pub(crate) fn parse_header(header: &[u8; 512], processor: &Processor) -> Self {
         let mut points = Points::new();
-        let num_markers = processor.u16([header[2], header[3]]);
+        // the points are only allocated once the data section shows how many it holds
+        points.stored_points = processor.u16([header[2], header[3]]) as usize;
         points.first_frame = processor.u16([header[6], header[7]]);
         points.last_frame = processor.u16([header[8], header[9]]);
-        points.points = Grid::new(
-            (points.last_frame - points.first_frame + 1) as usize,
-            num_markers as usize,
-        );
         points.max_interpolation_gap = processor.u16([header[10], header[11]]);
         let scale_factor = processor.f32([header[12], header[13], header[14], header[15]]);
         if scale_factor <= 0.0 {
@@ -152,6 +626,8 @@ impl Points {
         parameters: &mut Parameters,
         processor: &Processor,
         analog_samples_per_frame: u16,
+        selection: Option<&[String]>,
+        limits: &ParseLimits,
     ) -> Result<(&mut Self, usize), C3dParseError> {
         if !self.parsed_header {
             return Err(C3dParseError::HeaderNotParsed);
@@ -166,7 +642,32 @@ impl Points {
             long_frames,
             analog_samples_per_frame,
         )?;
-        self.parse_points(data_bytes, processor, analog_samples_per_frame, num_frames)?;
+        limits.check_frames(num_frames)?;
+        // the analog samples are decoded from the same frames, after the points
+        let decoded_frame = self.stored_points * size_of::<MarkerPoint>()
+            + analog_samples_per_frame as usize * size_of::<f64>();
+        limits.check_allocation(
+            data_bytes
+                .len()
+                .saturating_add(num_frames.saturating_mul(decoded_frame)),
+        )?;
+        match selection {
+            None => {
+                self.parse_points(data_bytes, processor, analog_samples_per_frame, num_frames)?;
+            }
+            Some(selection) => {
+                let columns = selected_columns(&self.labels, self.stored_points, selection);
+                self.parse_point_columns(
+                    data_bytes,
+                    processor,
+                    analog_samples_per_frame,
+                    num_frames,
+                    &columns,
+                )?;
+                self.labels = select_columns(&self.labels, &columns);
+                self.descriptions = select_columns(&self.descriptions, &columns);
+            }
+        }
         Ok((self, num_frames))
     }
 
@@ -175,6 +676,7 @@ impl Points {
         processor: &Processor,
         group_names_to_ids: &HashMap<String, usize>,
         num_frames: usize,
+        data_start_block_index: u16,
     ) -> Result<Vec<u8>, C3dWriteError> {
         let mut bytes: Vec<u8> = Vec::new();
         bytes.extend(Parameter::integer(self.cols() as i16).write(
@@ -189,18 +691,19 @@ impl Points {
             group_names_to_ids["POINT"],
             false,
         )?);
-        bytes.extend(Parameter::strings(self.labels.clone()).write(
-            processor,
-            "LABELS".to_string(),
-            group_names_to_ids["POINT"],
-            false,
-        )?);
-        bytes.extend(Parameter::strings(self.descriptions.clone()).write(
-            processor,
-            "DESCRIPTIONS".to_string(),
-            group_names_to_ids["POINT"],
-            false,
-        )?);
+        let strings = |strings| Ok(Parameter::strings(strings));
+        for (name, entries) in [
+            ("LABELS", &self.labels),
+            ("DESCRIPTIONS", &self.descriptions),
+        ] {
+            bytes.extend(write_continued(
+                entries,
+                strings,
+                processor,
+                name,
+                group_names_to_ids["POINT"],
+            )?);
+        }
         if self.x_screen.is_some() {
             bytes.extend(Parameter::chars(self.x_screen.unwrap().to_vec())?.write(
                 processor,
@@ -217,67 +720,81 @@ impl Points {
                 false,
             )?);
         }
+        bytes.extend(Parameter::float(self.frame_rate).write(
+            processor,
+            "RATE".to_string(),
+            group_names_to_ids["POINT"],
+            false,
+        )?);
         bytes.extend(Parameter::float(num_frames as f32).write(
             processor,
             "FRAMES".to_string(),
             group_names_to_ids["POINT"],
             false,
         )?);
+        // the sign of the scale factor gives the data format, as in the header
+        let scale_factor = match self.format {
+            DataFormat::Float => -self.scale_factor,
+            DataFormat::Integer => self.scale_factor,
+        };
+        bytes.extend(Parameter::float(scale_factor).write(
+            processor,
+            "SCALE".to_string(),
+            group_names_to_ids["POINT"],
+            false,
+        )?);
+        bytes.extend(Parameter::integer(data_start_block_index as i16).write(
+            processor,
+            "DATA_START".to_string(),
+            group_names_to_ids["POINT"],
+            false,
+        )?);
+        if let Some((first_frame, last_frame)) = self.actual_fields(num_frames) {
+            for (name, field) in [
+                ("ACTUAL_START_FIELD", first_frame),
+                ("ACTUAL_END_FIELD", last_frame),
+            ] {
+                let words = vec![field as u16 as i16, (field >> 16) as u16 as i16];
+                bytes.extend(Parameter::integers(words)?.write(
+                    processor,
+                    name.to_string(),
+                    group_names_to_ids["TRIAL"],
+                    false,
+                )?);
+            }
+        }
         Ok(bytes)
     }
 
     pub(crate) fn write_frame(&self, frame: usize, processor: &Processor) -> Vec<u8> {
-        let mut bytes = Vec::new();
         if frame >= self.points.rows() {
-            return bytes;
+            return Vec::new();
         }
-        for (column, _) in self.iter_cols().enumerate() {
-            match self.format {
-                DataFormat::Float => {
-                    let x = processor.f32_to_bytes(self.points[frame][column][0] as f32);
-                    let y = processor.f32_to_bytes(self.points[frame][column][1] as f32);
-                    let z = processor.f32_to_bytes(self.points[frame][column][2] as f32);
-                    bytes.extend_from_slice(&x);
-                    bytes.extend_from_slice(&y);
-                    bytes.extend_from_slice(&z);
-                    let residual =
-                        (self.points[frame][column].residual / self.scale_factor).round() as i8;
-                    let cameras = self.points[frame][column].cameras_as_byte();
-                    let cameras_and_residual =
-                        processor
-                            .f32_to_bytes(i16::from_be_bytes([cameras, residual as u8]) as f32);
-                    bytes.extend_from_slice(&cameras_and_residual);
+        // the words of the whole frame are converted to bytes at once
+        match self.format {
+            DataFormat::Float => {
+                let mut words = Vec::with_capacity(self.points.cols() * 4);
+                for point in self.points.iter_row(frame) {
+                    let cameras_and_residual = write_cameras_and_residual(point, self.scale_factor);
+                    words.extend_from_slice(&point.point);
+                    words.push(cameras_and_residual as f32);
                 }
-                DataFormat::Integer => {
-                    let x = processor.i16_to_bytes(
-                        (self.points[frame][column][0] / self.scale_factor).round() as i16,
-                    );
-                    let y = processor.i16_to_bytes(
-                        (self.points[frame][column][1] / self.scale_factor).round() as i16,
-                    );
-                    let z = processor.i16_to_bytes(
-                        (self.points[frame][column][2] / self.scale_factor).round() as i16,
+                processor.f32_slice_to_bytes(&words)
+            }
+            DataFormat::Integer => {
+                let mut words = Vec::with_capacity(self.points.cols() * 4);
+                for point in self.points.iter_row(frame) {
+                    let cameras_and_residual = write_cameras_and_residual(point, self.scale_factor);
+                    words.extend(
+                        point
+                            .point
+                            .map(|value| (value / self.scale_factor).round() as i16),
                     );
-                    bytes.extend_from_slice(&x);
-                    bytes.extend_from_slice(&y);
-                    bytes.extend_from_slice(&z);
-                    let residual =
-                        (self.points[frame][column].residual / self.scale_factor).round() as i16;
-                    let cameras = self.points[frame][column].cameras_as_byte();
-                    if residual > 0 {
-                        bytes.extend(
-                            processor.u16_to_bytes(u16::from_be_bytes([cameras, residual as u8])),
-                        );
-                    } else {
-                        let cameras = cameras | 0x80;
-                        bytes.extend(
-                            processor.u16_to_bytes(u16::from_be_bytes([cameras, residual as u8])),
-                        );
-                    }
+                    words.push(cameras_and_residual);
                 }
+                processor.i16_slice_to_bytes(&words)
             }
         }
-        bytes
     }
 
     fn get_point_parameters(
@@ -302,6 +819,8 @@ impl Points {
             },
             None => None,
         };
+        // the data start is read from the header, and written from the size of the parameters
+        parameters.remove("POINT", "DATA_START");
         let used = parameters.remove("POINT", "USED");
         let mut is_none_or_zero = used.is_none();
         if !is_none_or_zero {
@@ -317,68 +836,59 @@ impl Points {
             }
         }
         if is_none_or_zero {
-            Ok((
-                point_frames,
-                actual_start_field,
-                actual_end_field,
-                long_frames,
-            ))
+            // analog-only files may still declare labels for points that were not captured
+            parameters.remove_continued::<String>("POINT", "LABELS")?;
+            parameters.remove_continued::<String>("POINT", "DESCRIPTIONS")?;
         } else {
-            self.labels = parameters
-                .remove_or_err("POINT", "LABELS")?
-                .as_ref()
-                .try_into()?;
+            self.labels = parameters.remove_continued("POINT", "LABELS")?.ok_or(
+                C3dParseError::ParameterNotFound("POINT".to_string(), "LABELS".to_string()),
+            )?;
             self.descriptions = parameters
-                .remove("POINT", "DESCRIPTIONS")
-                .unwrap_or(Parameter::strings(vec![" ".to_string()]))
-                .as_ref()
-                .try_into()?;
-            self.units = parameters
-                .remove_or_err("POINT", "UNITS")?
-                .as_ref()
-                .try_into()?;
-            let x_screen = parameters.remove("POINT", "X_SCREEN");
-            self.x_screen = match x_screen {
-                Some(parameter) => Some(parameter.as_ref().try_into()?),
-                None => None,
-            };
-            let y_screen = parameters.remove("POINT", "Y_SCREEN");
-            self.y_screen = match y_screen {
-                Some(parameter) => Some(parameter.as_ref().try_into()?),
-                None => None,
-            };
-            // some c3d files don't have a POINT:RATE parameter
-            // but it is required by the c3d spec
-            let rate = parameters.remove("POINT", "RATE");
-            match rate {
-                Some(rate) => {
-                    let rate: f32 = rate.as_ref().try_into()?;
-                    if rate != self.frame_rate {
-                        return Err(C3dParseError::FrameRateMismatch(self.frame_rate, rate));
-                    }
-                }
-                None => {}
+                .remove_continued("POINT", "DESCRIPTIONS")?
+                .unwrap_or(vec![" ".to_string()]);
+        }
+        // POINT:UNITS is only required when there are points to measure
+        match parameters.remove("POINT", "UNITS") {
+            Some(units) => self.units = units.as_ref().try_into()?,
+            None if !is_none_or_zero => {
+                return Err(C3dParseError::ParameterNotFound(
+                    "POINT".to_string(),
+                    "UNITS".to_string(),
+                ))
             }
-            let scale_factor = parameters.remove("POINT", "SCALE");
-            match scale_factor {
-                Some(scale_factor) => {
-                    let scale_factor: f32 = scale_factor.as_ref().try_into()?;
-                    if scale_factor.abs() != self.scale_factor {
-                        return Err(C3dParseError::ScaleFactorMismatch(
-                            self.scale_factor,
-                            scale_factor,
-                        ));
-                    }
-                }
-                None => {}
+            None => {}
+        }
+        let x_screen = parameters.remove("POINT", "X_SCREEN");
+        self.x_screen = match x_screen {
+            Some(parameter) => Some(parameter.as_ref().try_into()?),
+            None => None,
+        };
+        let y_screen = parameters.remove("POINT", "Y_SCREEN");
+        self.y_screen = match y_screen {
+            Some(parameter) => Some(parameter.as_ref().try_into()?),
+            None => None,
+        };
+        // some c3d files don't have a POINT:RATE parameter
+        // but it is required by the c3d spec
+        if let Some(rate) = parameters.remove("POINT", "RATE") {
+            self.frame_rate = resolve_frame_rate(self.frame_rate, &rate)?;
+        }
+        if let Some(scale_factor) = parameters.remove("POINT", "SCALE") {
+            let scale_factor: f32 = scale_factor.as_ref().try_into()?;
+            // without points the scale factor only gives the format, read from the header
+            if !is_none_or_zero && scale_factor.abs() != self.scale_factor {
+                return Err(C3dParseError::ScaleFactorMismatch(
+                    self.scale_factor,
+                    scale_factor,
+                ));
             }
-            Ok((
-                point_frames,
-                actual_start_field,
-                actual_end_field,
-                long_frames,
-            ))
         }
+        Ok((
+            point_frames,
+            actual_start_field,
+            actual_end_field,
+            long_frames,
+        ))
     }
 
     fn calc_num_frames(
@@ -390,7 +900,8 @@ impl Points {
         long_frames: Option<usize>,
         analog_samples_per_frame: u16,
     ) -> Result<usize, C3dParseError> {
-        let mut num_frames = (self.last_frame as usize - self.first_frame as usize) + 1;
+        let mut num_frames =
+            (self.last_frame as usize + 1).saturating_sub(self.first_frame as usize);
         if num_frames != point_frames {
             num_frames = point_frames;
             //    return Err(C3dParseError::NumFramesMismatch(
@@ -398,10 +909,9 @@ impl Points {
             //        num_frames,
             //    ));
         }
-        if actual_start_field.is_some() && actual_end_field.is_some() {
-            if (actual_end_field.unwrap() - actual_start_field.unwrap() + 1) as usize != num_frames
-            {
-                num_frames = (actual_end_field.unwrap() - actual_start_field.unwrap() + 1) as usize;
+        if let (Some(start_field), Some(end_field)) = (actual_start_field, actual_end_field) {
+            if end_field >= start_field {
+                num_frames = end_field - start_field + 1;
             }
         }
         if long_frames.is_some() {
@@ -409,68 +919,94 @@ impl Points {
                 num_frames = long_frames.unwrap();
             }
         }
-        let point_bytes_per_frame = get_point_bytes_per_frame(&self.format, self.cols()) as usize;
+        let point_bytes_per_frame = get_point_bytes_per_frame(&self.format, self.stored_points);
 
         let analog_bytes_per_frame =
             get_analog_bytes_per_frame(&self.format, analog_samples_per_frame)?;
 
         let bytes_per_frame = point_bytes_per_frame + analog_bytes_per_frame;
-        num_frames = match data_bytes.len() < num_frames * bytes_per_frame as usize {
-            true => {
-                let num_frames = data_bytes.len() / bytes_per_frame as usize;
-                num_frames
-            }
-            false => num_frames,
+        // without a TRIAL or LONG_FRAMES count, files longer than the header can hold
+        // only declare 65535 frames, so every complete frame in the data is read
+        if actual_end_field.is_none()
+            && long_frames.is_none()
+            && self.frame_count_saturated(num_frames)
+            && bytes_per_frame > 0
+        {
+            num_frames = num_frames.max(data_bytes.len() / bytes_per_frame);
+        }
+        self.declared_frames = num_frames;
+        // a declared size too large to multiply cannot fit in the data either
+        num_frames = match num_frames.checked_mul(bytes_per_frame) {
+            Some(bytes) if bytes <= data_bytes.len() => num_frames,
+            _ => data_bytes.len() / bytes_per_frame,
             //return Err(C3dParseError::NotEnoughData);
         };
         Ok(num_frames)
     }
 
-    fn parse_points(
+    pub(crate) fn parse_points(
         &mut self,
         data_bytes: &[u8],
         processor: &Processor,
         analog_samples_per_frame: u16,
         num_frames: usize,
     ) -> Result<&mut Self, C3dParseError> {
-        let mut point_data = Grid::new(num_frames, self.cols() as usize);
+        let columns: Vec<usize> = (0..self.stored_points).collect();
+        self.parse_point_columns(
+            data_bytes,
+            processor,
+            analog_samples_per_frame,
+            num_frames,
+            &columns,
+        )
+    }
 
-        let point_bytes_per_frame = get_point_bytes_per_frame(&self.format, self.cols()) as usize;
+    /// Decodes only the given columns of the stored points, in the given order.
+    fn parse_point_columns(
+        &mut self,
+        data_bytes: &[u8],
+        processor: &Processor,
+        analog_samples_per_frame: u16,
+        num_frames: usize,
+        columns: &[usize],
+    ) -> Result<&mut Self, C3dParseError> {
+        let point_bytes_per_frame = get_point_bytes_per_frame(&self.format, self.stored_points);
         let analog_bytes_per_frame =
             get_analog_bytes_per_frame(&self.format, analog_samples_per_frame)?;
         let bytes_per_frame = point_bytes_per_frame + analog_bytes_per_frame;
-        let bytes_per_point: u16 = match self.cols() {
-            0 => 0,
-            _ => point_bytes_per_frame as u16 / self.cols() as u16,
-        };
 
-        for i in 0..point_data.rows() {
-            let start = i * bytes_per_frame as usize;
-            let end = start + bytes_per_frame as usize;
-            let point_frame_data = &data_bytes[start..end - analog_bytes_per_frame as usize];
-            for j in 0..self.cols() as u16 {
-                let start = j * bytes_per_point;
-                let end = start + bytes_per_point;
-                let point_slice = &point_frame_data[start as usize..end as usize];
-                let mut point = match self.format {
-                    DataFormat::Float => parse_point_data_float(point_slice, processor),
-                    DataFormat::Integer => parse_point_data_int(point_slice, processor),
-                };
-                let point = match self.format {
-                    DataFormat::Integer => point
-                        .scale(self.scale_factor)
-                        .scale_residual(self.scale_factor),
-                    DataFormat::Float => point.scale_residual(self.scale_factor),
-                };
-                point_data[i][j as usize] = point;
+        let (format, scale_factor) = (&self.format, self.scale_factor);
+        let point_data = decode_chunks(num_frames, columns.len(), |i, frame| {
+            let start = i * bytes_per_frame;
+            let point_frame_data = &data_bytes[start..start + point_bytes_per_frame];
+            // each point is stored as four words, converted for the whole frame at once
+            match format {
+                DataFormat::Float => {
+                    let words = processor.f32_slice(point_frame_data);
+                    for (point, &column) in frame.iter_mut().zip(columns) {
+                        *point = parse_point_data_float(&words[column * 4..column * 4 + 4]);
+                    }
+                }
+                DataFormat::Integer => {
+                    let words = processor.i16_slice(point_frame_data);
+                    for (point, &column) in frame.iter_mut().zip(columns) {
+                        *point = parse_point_data_int(&words[column * 4..column * 4 + 4])
+                            .scale(scale_factor);
+                    }
+                }
             }
-        }
-        self.points = point_data;
+            for point in frame.iter_mut().filter(|point| point.is_valid()) {
+                point.scale_residual(scale_factor);
+            }
+        });
+        self.points = Grid::from_vec(point_data, columns.len());
 
         Ok(self)
     }
 }
 
+/// Reads the first and last frame of the trial from the TRIAL group.
+/// Each is stored as two unsigned words, the low word first.
 fn get_actual_start_and_end_fields(
     parameters: &mut Parameters,
 ) -> Result<(Option<usize>, Option<usize>), C3dParseError> {
@@ -480,7 +1016,7 @@ fn get_actual_start_and_end_fields(
         if end_field.len() != 2 {
             None
         } else {
-            Some(end_field[0] as u16 as usize + (end_field[1] as u16 * 65535) as usize)
+            Some(end_field[0] as u16 as usize + ((end_field[1] as u16 as usize) << 16))
         }
     } else {
         None
@@ -491,7 +1027,7 @@ fn get_actual_start_and_end_fields(
         if start_field.len() != 2 {
             None
         } else {
-            Some(start_field[0] as u16 as usize + (start_field[1] as u16 * 65535) as usize)
+            Some(start_field[0] as u16 as usize + ((start_field[1] as u16 as usize) << 16))
         }
     } else {
         None
@@ -499,46 +1035,74 @@ fn get_actual_start_and_end_fields(
     Ok((actual_start_field, actual_end_field))
 }
 
-fn parse_point_data_float(point_frame_data: &[u8], processor: &Processor) -> MarkerPoint {
-    let x = processor.f32(point_frame_data[0..4].try_into().unwrap());
-    let y = processor.f32(point_frame_data[4..8].try_into().unwrap());
-    let z = processor.f32(point_frame_data[8..12].try_into().unwrap());
-    let cameras_and_residual = processor.f32(point_frame_data[12..16].try_into().unwrap()) as i16;
-    let cameras_and_residual = i16::from_be_bytes(cameras_and_residual.to_be_bytes());
-    //let cameras_and_residual = processor.i16([
-    //    (cameras_and_residual >> 8) as u8,
-    //    cameras_and_residual as u8,
-    //]);
-    let cameras = byte_to_bool((cameras_and_residual >> 8) as u8);
-    let residual = (cameras_and_residual & 0xFF) as i8 as f32;
+/// Reconciles the frame rate in the header with the POINT:RATE parameter.
+/// The spec stores POINT:RATE as a float, but some writers store it as an
+/// integer, which cannot represent rates such as 59.94 Hz. A float rate is
+/// preferred over the header, while an integer rate only confirms the header
+/// rate it rounds to. The parameter is used if the header rate is not set.
+fn resolve_frame_rate(header_rate: f32, rate: &Parameter) -> Result<f32, C3dParseError> {
+    let has_header_rate = header_rate.is_finite() && header_rate > 0.;
+    match &rate.data {
+        ParameterData::Float(data) if data.len() == 1 => {
+            let rate = data[0];
+            if !has_header_rate || (rate - header_rate).abs() <= header_rate * 1e-4 {
+                Ok(rate)
+            } else {
+                Err(C3dParseError::FrameRateMismatch(header_rate, rate))
+            }
+        }
+        ParameterData::Integer(data) if data.len() == 1 => {
+            let rate = data[0] as f32;
+            if !has_header_rate {
+                Ok(rate)
+            } else if header_rate.round() == rate {
+                Ok(header_rate)
+            } else {
+                Err(C3dParseError::FrameRateMismatch(header_rate, rate))
+            }
+        }
+        _ => Err(C3dParseError::InvalidData(rate.clone(), "f32".to_string())),
+    }
+}
+
+fn parse_point_data_float(words: &[f32]) -> MarkerPoint {
+    let (cameras, residual) = parse_cameras_and_residual(words[3] as i16);
     MarkerPoint {
-        point: [x, y, z],
+        point: [words[0], words[1], words[2]],
         cameras,
         residual,
     }
 }
 
-fn parse_point_data_int(point_frame_data: &[u8], processor: &Processor) -> MarkerPoint {
-    let x = processor.i16(point_frame_data[0..2].try_into().unwrap());
-    let y = processor.i16(point_frame_data[2..4].try_into().unwrap());
-    let z = processor.i16(point_frame_data[4..6].try_into().unwrap());
-    let cameras_and_residual = processor.i16(point_frame_data[6..8].try_into().unwrap());
-    let cameras = byte_to_bool((cameras_and_residual >> 8) as u8);
-    let residual = cameras_and_residual as u8;
-    // if the first bit in the i16 is 0, then the residual is positive
-    if cameras_and_residual >> 8 as i8 >= 0 {
-        MarkerPoint {
-            point: [x as f32, y as f32, z as f32],
-            cameras,
-            residual: residual as f32,
-        }
-    } else {
-        MarkerPoint {
-            point: [x as f32, y as f32, z as f32],
-            cameras,
-            residual: residual as i8 as f32,
-        }
+fn parse_point_data_int(words: &[i16]) -> MarkerPoint {
+    let [x, y, z, cameras_and_residual] = [words[0], words[1], words[2], words[3]];
+    let (cameras, residual) = parse_cameras_and_residual(cameras_and_residual);
+    MarkerPoint {
+        point: [x as f32, y as f32, z as f32],
+        cameras,
+        residual,
+    }
+}
+
+/// Splits the fourth word of a point into the cameras that saw the marker and the residual.
+/// The high byte holds one bit per camera and the low byte the residual as an unsigned
+/// integer, which is scaled by POINT:SCALE in both integer and float storage.
+/// A negative word marks the point as invalid, which is returned as a residual of -1.
+fn parse_cameras_and_residual(cameras_and_residual: i16) -> ([bool; 7], f32) {
+    if cameras_and_residual < 0 {
+        return ([false; 7], -1.);
+    }
+    let [cameras, residual] = cameras_and_residual.to_be_bytes();
+    (byte_to_bool(cameras), residual as f32)
+}
+
+/// Builds the fourth word of a point, the inverse of `parse_cameras_and_residual`.
+fn write_cameras_and_residual(point: &MarkerPoint, scale_factor: f32) -> i16 {
+    if !point.is_valid() {
+        return -1;
     }
+    let residual = (point.residual / scale_factor).round().clamp(0., 255.) as u8;
+    i16::from_be_bytes([point.cameras_as_byte() & 0x7F, residual])
 }
 
 fn byte_to_bool(byte: u8) -> [bool; 7] {
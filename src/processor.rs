@@ -6,6 +6,7 @@ use crate::C3dParseError;
 ///
 /// c3dio supports reading and writing all three formats.
 #[derive(Debug, Copy, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Processor {
     /// Dec (Digital Equipment Corporation) is the default format for data created on a DEC computer.
     /// Traditionally, this data was produced on a VAX or RSX-11M operating system.
@@ -50,7 +51,12 @@ impl Processor {
     pub(crate) fn from_parameter_start_block(
         parameter_start_block: [u8; 512],
     ) -> Result<Processor, C3dParseError> {
-        match parameter_start_block[3] {
+        Processor::from_byte(parameter_start_block[3])
+    }
+
+    /// Creates a Processor from the processor byte of the parameter start block.
+    pub(crate) fn from_byte(byte: u8) -> Result<Processor, C3dParseError> {
+        match byte {
             0x54 => Ok(Processor::Intel),
             0x55 => Ok(Processor::Dec),
             0x56 => Ok(Processor::SgiMips),
@@ -59,7 +65,7 @@ impl Processor {
     }
 
     /// Calculates the u16 value from the bytes based on the processor type.
-    pub(crate) fn u16(self, bytes: [u8; 2]) -> u16 {
+    pub fn u16(self, bytes: [u8; 2]) -> u16 {
         match self {
             Processor::Intel => intel_u16(bytes),
             Processor::Dec => dec_u16(bytes),
@@ -68,7 +74,7 @@ impl Processor {
     }
 
     /// Calculates the i16 value from the bytes based on the processor type.
-    pub(crate) fn i16(self, bytes: [u8; 2]) -> i16 {
+    pub fn i16(self, bytes: [u8; 2]) -> i16 {
         match self {
             Processor::Intel => intel_i16(bytes) as i16,
             Processor::Dec => dec_i16(bytes) as i16,
@@ -77,7 +83,7 @@ impl Processor {
     }
 
     /// Calculates the f32 value from the bytes based on the processor type.
-    pub(crate) fn f32(self, bytes: [u8; 4]) -> f32 {
+    pub fn f32(self, bytes: [u8; 4]) -> f32 {
         match self {
             Processor::Intel => intel_f32(bytes),
             Processor::Dec => dec_f32(bytes),
@@ -85,6 +91,65 @@ impl Processor {
         }
     }
 
+    /// Calculates the u16 values from a slice of bytes based on the processor type.
+    /// Trailing bytes that do not make up a whole value are ignored.
+    /// The processor type is matched once, so the conversion loop can be vectorized.
+    pub fn u16_slice(self, bytes: &[u8]) -> Vec<u16> {
+        match self {
+            Processor::Intel => convert_slice(bytes, intel_u16),
+            Processor::Dec => convert_slice(bytes, dec_u16),
+            Processor::SgiMips => convert_slice(bytes, sgi_mips_u16),
+        }
+    }
+
+    /// Calculates the i16 values from a slice of bytes based on the processor type.
+    /// Trailing bytes that do not make up a whole value are ignored.
+    pub fn i16_slice(self, bytes: &[u8]) -> Vec<i16> {
+        match self {
+            Processor::Intel => convert_slice(bytes, intel_i16),
+            Processor::Dec => convert_slice(bytes, dec_i16),
+            Processor::SgiMips => convert_slice(bytes, sgi_mips_i16),
+        }
+    }
+
+    /// Calculates the f32 values from a slice of bytes based on the processor type.
+    /// Trailing bytes that do not make up a whole value are ignored.
+    pub fn f32_slice(self, bytes: &[u8]) -> Vec<f32> {
+        match self {
+            Processor::Intel => convert_slice(bytes, intel_f32),
+            Processor::Dec => convert_slice(bytes, dec_f32),
+            Processor::SgiMips => convert_slice(bytes, sgi_mips_f32),
+        }
+    }
+
+    /// Calculates the bytes of a slice of u16 values based on the processor type,
+    /// the inverse of `u16_slice`.
+    pub fn u16_slice_to_bytes(self, values: &[u16]) -> Vec<u8> {
+        match self {
+            Processor::Intel | Processor::Dec => convert_values(values, u16::to_le_bytes),
+            Processor::SgiMips => convert_values(values, u16::to_be_bytes),
+        }
+    }
+
+    /// Calculates the bytes of a slice of i16 values based on the processor type,
+    /// the inverse of `i16_slice`.
+    pub fn i16_slice_to_bytes(self, values: &[i16]) -> Vec<u8> {
+        match self {
+            Processor::Intel | Processor::Dec => convert_values(values, i16::to_le_bytes),
+            Processor::SgiMips => convert_values(values, i16::to_be_bytes),
+        }
+    }
+
+    /// Calculates the bytes of a slice of f32 values based on the processor type,
+    /// the inverse of `f32_slice`.
+    pub fn f32_slice_to_bytes(self, values: &[f32]) -> Vec<u8> {
+        match self {
+            Processor::Intel => convert_values(values, f32::to_le_bytes),
+            Processor::Dec => convert_values(values, dec_f32_to_bytes),
+            Processor::SgiMips => convert_values(values, f32::to_be_bytes),
+        }
+    }
+
     /// Calculates the bytes from the u16 value based on the processor type.
     pub(crate) fn u16_to_bytes(self, value: u16) -> [u8; 2] {
         match self {
@@ -106,19 +171,29 @@ impl Processor {
     pub(crate) fn f32_to_bytes(self, value: f32) -> [u8; 4] {
         match self {
             Processor::Intel => value.to_le_bytes(),
-            Processor::Dec => {
-                let temp = value.to_le_bytes();
-                if temp[3] == 255 {
-                    [temp[2], temp[3], temp[0], temp[1]]
-                } else {
-                    [temp[2], temp[3] + 1, temp[0], temp[1]]
-                }
-            }
+            Processor::Dec => dec_f32_to_bytes(value),
             Processor::SgiMips => value.to_be_bytes(),
         }
     }
 }
 
+/// Converts each whole run of `N` bytes into a value.
+fn convert_slice<T, const N: usize>(bytes: &[u8], convert: fn([u8; N]) -> T) -> Vec<T> {
+    bytes
+        .chunks_exact(N)
+        .map(|x| convert(x.try_into().unwrap()))
+        .collect()
+}
+
+/// Converts each value into its run of `N` bytes.
+fn convert_values<T: Copy, const N: usize>(values: &[T], convert: fn(T) -> [u8; N]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(values.len() * N);
+    for &value in values {
+        bytes.extend_from_slice(&convert(value));
+    }
+    bytes
+}
+
 /// Conversion of the raw bytes into intel u16 format
 fn intel_u16(bytes: [u8; 2]) -> u16 {
     u16::from_le_bytes(bytes)
@@ -166,6 +241,16 @@ fn dec_f32(bytes: [u8; 4]) -> f32 {
     }
 }
 
+/// Conversion of an f32 value into dec bytes, the inverse of `dec_f32`
+fn dec_f32_to_bytes(value: f32) -> [u8; 4] {
+    let temp = value.to_le_bytes();
+    if temp[3] == 255 {
+        [temp[2], temp[3], temp[0], temp[1]]
+    } else {
+        [temp[2], temp[3] + 1, temp[0], temp[1]]
+    }
+}
+
 /// Conversion of the raw bytes into sgi_mips f32 format
 fn sgi_mips_f32(bytes: [u8; 4]) -> f32 {
     f32::from_be_bytes(bytes)
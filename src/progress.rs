@@ -0,0 +1,49 @@
+//! Progress reporting for loading and writing large files, e.g. to show a progress bar.
+use std::ops::ControlFlow;
+
+/// The part of loading or writing a file a `Progress` refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ProgressStage {
+    /// Reading the file, counted in bytes.
+    Reading,
+    /// Decoding the points and analog samples, counted in frames.
+    Decoding,
+    /// Encoding the points and analog samples, counted in frames.
+    Encoding,
+    /// Writing the file, counted in bytes.
+    Writing,
+}
+
+/// How far loading or writing a file has come, as passed to the callback of
+/// `C3d::load_with_progress` and `C3d::write_with_progress`.
+/// The callback returns `ControlFlow::Break(())` to cancel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Progress {
+    pub stage: ProgressStage,
+    pub done: u64,
+    pub total: u64,
+}
+
+impl Progress {
+    /// Returns the completed fraction of the stage, from 0 to 1.
+    /// A stage with nothing to do is complete.
+    pub fn fraction(&self) -> f64 {
+        match self.total {
+            0 => 1.,
+            _ => (self.done as f64 / self.total as f64).min(1.),
+        }
+    }
+}
+
+/// The number of bytes read or written between two reports.
+pub(crate) const CHUNK_SIZE: usize = 1 << 20;
+
+/// Reports progress to a callback, returning whether to go on.
+pub(crate) fn report<F>(progress: &mut F, stage: ProgressStage, done: u64, total: u64) -> bool
+where
+    F: FnMut(Progress) -> ControlFlow<()>,
+{
+    progress(Progress { stage, done, total }).is_continue()
+}
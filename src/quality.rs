@@ -0,0 +1,130 @@
+//! Measures the quality of the data of a trial, e.g. to triage incoming files before
+//! processing them.
+use crate::data::MarkerPoint;
+use crate::forces::ForcePlatformData;
+use crate::gaps::gaps;
+
+/// The force, in the units of the analog channels, below which a platform is taken to
+/// be unloaded when measuring the noise of its baseline.
+pub const UNLOADED_FORCE: f32 = 20.;
+
+/// The quality of the markers, analog channels and force platforms of a trial,
+/// as returned by `C3d::quality_report`.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QualityReport {
+    /// The number of stored frames of the points.
+    pub frames: usize,
+    /// One entry per point, in the order of the columns of `points`.
+    pub markers: Vec<MarkerQuality>,
+    /// One entry per analog channel, in the order of the columns of `analog`.
+    pub analog: Vec<AnalogQuality>,
+    /// One entry per force platform, in the order of `forces`.
+    pub force_platforms: Vec<ForcePlatformQuality>,
+}
+
+/// The visibility and gaps of the trajectory of a marker.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MarkerQuality {
+    pub label: String,
+    /// The number of frames where the marker is valid.
+    pub visible_frames: usize,
+    /// The percentage of the stored frames where the marker is valid, or 0 for a trial
+    /// without frames.
+    pub visible_percent: f32,
+    /// The number of gaps with a valid sample before and after them, as listed by
+    /// `C3d::gaps`. Frames missing at the start or end of the trial are not gaps.
+    pub gaps: usize,
+    /// The length of the longest gap in frames, or 0 without gaps.
+    pub longest_gap: usize,
+    /// The mean residual of the valid samples, or `None` if the marker is never valid.
+    pub mean_residual: Option<f32>,
+}
+
+/// The samples of an analog channel at the limits of the converter.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnalogQuality {
+    pub label: String,
+    /// The number of samples at the smallest or largest raw value the converter can
+    /// produce, or `None` if ANALOG:BITS does not give its range.
+    pub clipped_samples: Option<usize>,
+}
+
+impl AnalogQuality {
+    /// Whether any sample of the channel is at the limits of the converter.
+    pub fn is_clipped(&self) -> bool {
+        self.clipped_samples.is_some_and(|samples| samples > 0)
+    }
+}
+
+/// The baseline of a force platform, measured in the samples where it is unloaded.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ForcePlatformQuality {
+    /// The number of samples whose force is smaller than `UNLOADED_FORCE`.
+    pub unloaded_samples: usize,
+    /// The mean force of the unloaded samples in platform coordinates, which is the
+    /// offset left in the channels, or `None` without unloaded samples.
+    pub baseline: Option<[f32; 3]>,
+    /// The standard deviation of the force of the unloaded samples along each axis of
+    /// the platform, or `None` without unloaded samples.
+    pub baseline_noise: Option<[f32; 3]>,
+}
+
+/// Measures the trajectory of a marker in every stored frame.
+pub(crate) fn marker_quality(label: &str, points: &[MarkerPoint]) -> MarkerQuality {
+    let residuals: Vec<f32> = points
+        .iter()
+        .filter_map(MarkerPoint::valid_residual)
+        .collect();
+    let gaps = gaps(points);
+    let visible_percent = match points.len() {
+        0 => 0.,
+        frames => 100. * residuals.len() as f32 / frames as f32,
+    };
+    MarkerQuality {
+        label: label.trim().to_string(),
+        visible_frames: residuals.len(),
+        visible_percent,
+        gaps: gaps.len(),
+        longest_gap: gaps.iter().map(|gap| gap.len()).max().unwrap_or(0),
+        mean_residual: match residuals.is_empty() {
+            true => None,
+            false => Some(residuals.iter().sum::<f32>() / residuals.len() as f32),
+        },
+    }
+}
+
+/// Measures the baseline of a force platform from its data in platform coordinates.
+pub(crate) fn force_platform_quality(data: Option<&ForcePlatformData>) -> ForcePlatformQuality {
+    let unloaded: Vec<[f32; 3]> = data
+        .map(|data| {
+            data.force
+                .iter()
+                .copied()
+                .filter(|force| force.iter().map(|x| x * x).sum::<f32>().sqrt() < UNLOADED_FORCE)
+                .collect()
+        })
+        .unwrap_or_default();
+    if unloaded.is_empty() {
+        return ForcePlatformQuality::default();
+    }
+    let count = unloaded.len() as f32;
+    let mean: [f32; 3] =
+        std::array::from_fn(|axis| unloaded.iter().map(|force| force[axis]).sum::<f32>() / count);
+    let noise = std::array::from_fn(|axis| {
+        let variance = unloaded
+            .iter()
+            .map(|force| (force[axis] - mean[axis]).powi(2))
+            .sum::<f32>()
+            / count;
+        variance.sqrt()
+    });
+    ForcePlatformQuality {
+        unloaded_samples: unloaded.len(),
+        baseline: Some(mean),
+        baseline_noise: Some(noise),
+    }
+}
@@ -0,0 +1,234 @@
+//! Repairs well-known inconsistencies between the header and the parameters of a C3D file.
+use crate::data::{get_analog_bytes_per_frame, get_point_bytes_per_frame, DataFormat};
+use crate::parameters::{ParameterData, Parameters};
+use crate::processor::Processor;
+use crate::validate::number;
+use crate::{C3dParseError, ParseOptions, ParseWarning};
+
+/// A repair made by `C3d::repair`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Repair {
+    /// The parameters could not be read up to the data start block in the header,
+    /// so the data was read from the second block given.
+    HeaderDataStart(usize, usize),
+    /// POINT:DATA_START held the given block, while the header and the data start
+    /// at the second block given.
+    ParameterDataStart(usize, usize),
+    /// The header and POINT:FRAMES declared the first two numbers of frames,
+    /// and the third was used, as it fits in the data section.
+    FrameCount(usize, usize, usize),
+    /// POINT:FRAMES was stuck at 65535, while the data section holds the given number of frames.
+    FramesOverflow(usize),
+    /// The group had fewer labels than channels, and the given number of labels were added.
+    MissingLabels(String, usize),
+}
+
+/// The header and parameters of a file after repair, and where its data starts.
+pub(crate) struct Repaired {
+    pub(crate) header: [u8; 512],
+    pub(crate) parameters: Parameters,
+    pub(crate) processor: Processor,
+    pub(crate) data_start_byte: usize,
+    pub(crate) warnings: Vec<ParseWarning>,
+    pub(crate) repairs: Vec<Repair>,
+}
+
+/// Reads the header and parameters of a file and fixes what other readers tolerate.
+/// Fails only if the parameter section cannot be found or read.
+pub(crate) fn repair(bytes: &[u8]) -> Result<Repaired, C3dParseError> {
+    if bytes.len() < 1024 {
+        return Err(C3dParseError::InsufficientBlocks("header".to_string()));
+    }
+    let mut header: [u8; 512] = bytes[0..512].try_into().unwrap();
+    let parameter_start_block = header[0] as usize;
+    if parameter_start_block < 2 || bytes.len() < 512 * parameter_start_block {
+        return Err(C3dParseError::InvalidParameterStartBlock);
+    }
+    let parameter_start_byte = 512 * (parameter_start_block - 1);
+    let mut warnings = Vec::new();
+    // the processor type is needed to read anything else, so an unknown one is read as Intel
+    let processor = Processor::from_byte(bytes[parameter_start_byte + 3]).unwrap_or_else(|_| {
+        warnings.push(ParseWarning::UnknownProcessorByte(
+            bytes[parameter_start_byte + 3],
+        ));
+        Processor::Intel
+    });
+    let header_data_start = processor.u16([header[16], header[17]]) as usize;
+    let file_blocks = bytes.len().div_ceil(512);
+    let mut repairs = Vec::new();
+    // the parameter section ends where the header says the data starts, like when parsing,
+    // unless the parameters cannot be read up to there
+    let header_parameters =
+        match header_data_start > parameter_start_block && header_data_start <= file_blocks + 1 {
+            true => {
+                let mut header_warnings = warnings.clone();
+                read_parameters(
+                    &bytes[parameter_start_byte..(512 * (header_data_start - 1)).min(bytes.len())],
+                    &processor,
+                    &mut header_warnings,
+                )
+                .ok()
+                .map(|parameters| (parameters, header_warnings))
+            }
+            false => None,
+        };
+    let (mut parameters, data_start) = match header_parameters {
+        Some((parameters, header_warnings)) => {
+            warnings = header_warnings;
+            let parameter_data_start = number(&parameters, "POINT", "DATA_START");
+            if let Some(parameter_data_start) = parameter_data_start.map(|x| x as usize) {
+                if parameter_data_start != header_data_start {
+                    repairs.push(Repair::ParameterDataStart(
+                        parameter_data_start,
+                        header_data_start,
+                    ));
+                }
+            }
+            (parameters, header_data_start)
+        }
+        None => {
+            // the first block after the parameter section, as declared by the section itself
+            let parameter_end = (parameter_start_block + bytes[parameter_start_byte + 2] as usize)
+                .clamp(parameter_start_block + 1, file_blocks + 1);
+            let parameters = read_parameters(
+                &bytes[parameter_start_byte..(512 * (parameter_end - 1)).min(bytes.len())],
+                &processor,
+                &mut warnings,
+            )?;
+            let data_start = number(&parameters, "POINT", "DATA_START")
+                .map(|x| x as usize)
+                .filter(|&block| block >= parameter_end && block <= file_blocks + 1)
+                .unwrap_or(parameter_end);
+            repairs.push(Repair::HeaderDataStart(header_data_start, data_start));
+            (parameters, data_start)
+        }
+    };
+    header[16..18].copy_from_slice(&processor.u16_to_bytes(data_start as u16));
+    let data_start_byte = (512 * (data_start - 1)).min(bytes.len());
+
+    repair_frame_count(
+        &mut header,
+        &mut parameters,
+        &processor,
+        bytes.len() - data_start_byte,
+        &mut repairs,
+    );
+    let points = processor.u16([header[2], header[3]]) as usize;
+    let analog_channels = number(&parameters, "ANALOG", "USED").unwrap_or(0.) as usize;
+    for (group, channels, prefix) in [("POINT", points, "M"), ("ANALOG", analog_channels, "A")] {
+        repair_labels(&mut parameters, group, channels, prefix, &mut repairs);
+    }
+    Ok(Repaired {
+        header,
+        parameters,
+        processor,
+        data_start_byte,
+        warnings,
+        repairs,
+    })
+}
+
+fn read_parameters(
+    parameter_blocks: &[u8],
+    processor: &Processor,
+    warnings: &mut Vec<ParseWarning>,
+) -> Result<Parameters, C3dParseError> {
+    Parameters::parse_parameter_blocks(
+        &parameter_blocks.to_vec(),
+        processor,
+        &ParseOptions::lenient(),
+        warnings,
+    )
+}
+
+/// Makes the header and POINT:FRAMES agree on a number of frames that fits in the data section.
+/// Files storing the number of frames elsewhere are left as they are.
+fn repair_frame_count(
+    header: &mut [u8; 512],
+    parameters: &mut Parameters,
+    processor: &Processor,
+    data_length: usize,
+    repairs: &mut Vec<Repair>,
+) {
+    if parameters.contains("POINT", "LONG_FRAMES")
+        || parameters.contains("TRIAL", "ACTUAL_START_FIELD")
+    {
+        return;
+    }
+    let parameter_frames = match number(parameters, "POINT", "FRAMES") {
+        Some(frames) => frames as usize,
+        None => return,
+    };
+    let format = match processor.f32([header[12], header[13], header[14], header[15]]) < 0. {
+        true => DataFormat::Float,
+        false => DataFormat::Integer,
+    };
+    let analog_samples_per_frame = processor.u16([header[4], header[5]]);
+    let bytes_per_frame =
+        get_point_bytes_per_frame(&format, processor.u16([header[2], header[3]]) as usize)
+            + get_analog_bytes_per_frame(&format, analog_samples_per_frame).unwrap_or(0);
+    if bytes_per_frame == 0 {
+        return;
+    }
+    let available_frames = data_length / bytes_per_frame;
+    let first_frame = processor.u16([header[6], header[7]]).max(1);
+    let last_frame = processor.u16([header[8], header[9]]);
+    let header_frames = (last_frame as usize + 1).saturating_sub(first_frame as usize);
+
+    let frames = if parameter_frames == u16::MAX as usize && available_frames > parameter_frames {
+        repairs.push(Repair::FramesOverflow(available_frames));
+        available_frames
+    } else if last_frame != u16::MAX && parameter_frames > 0 && header_frames != parameter_frames {
+        let frames = if parameter_frames <= available_frames {
+            parameter_frames
+        } else if header_frames > 0 && header_frames <= available_frames {
+            header_frames
+        } else {
+            available_frames
+        };
+        repairs.push(Repair::FrameCount(header_frames, parameter_frames, frames));
+        frames
+    } else {
+        return;
+    };
+    // counts above 65535 cannot be stored as an integer
+    let _ = match frames > u16::MAX as usize {
+        true => parameters.set("POINT", "FRAMES", frames as f32),
+        false => parameters.set("POINT", "FRAMES", frames as u16 as i16),
+    };
+    let last_frame = (first_frame as usize + frames.max(1) - 1).min(u16::MAX as usize) as u16;
+    header[6..8].copy_from_slice(&processor.u16_to_bytes(first_frame));
+    header[8..10].copy_from_slice(&processor.u16_to_bytes(last_frame));
+}
+
+/// Adds labels named after their position, e.g. `M12`, for channels without a label.
+/// Files with more than 255 channels continue their labels in `LABELS2` and are left as they are.
+fn repair_labels(
+    parameters: &mut Parameters,
+    group: &str,
+    channels: usize,
+    prefix: &str,
+    repairs: &mut Vec<Repair>,
+) {
+    if channels == 0 || channels > u8::MAX as usize || parameters.contains(group, "LABELS2") {
+        return;
+    }
+    if parameters
+        .get(group, "LABELS")
+        .is_some_and(|labels| !matches!(labels.data, ParameterData::Char(_)))
+    {
+        return;
+    }
+    let mut labels = parameters
+        .get_vec::<String>(group, "LABELS")
+        .unwrap_or_default();
+    if labels.len() >= channels {
+        return;
+    }
+    let missing = channels - labels.len();
+    labels.extend((labels.len()..channels).map(|i| format!("{}{}", prefix, i + 1)));
+    if parameters.set(group, "LABELS", labels).is_ok() {
+        repairs.push(Repair::MissingLabels(group.to_string(), missing));
+    }
+}
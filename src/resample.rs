@@ -0,0 +1,119 @@
+//! Interpolation of point and analog samples at positions between stored frames.
+use crate::data::MarkerPoint;
+use grid::Grid;
+
+/// How samples between two stored frames are interpolated.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Interpolation {
+    /// Straight lines between the two nearest samples.
+    #[default]
+    Linear,
+    /// Catmull-Rom splines through the four nearest samples,
+    /// which keeps velocities continuous.
+    Cubic,
+}
+
+impl Interpolation {
+    /// Returns the indices of the samples used to interpolate at a position,
+    /// clamped to the stored samples, and the fraction between the middle two.
+    fn neighbours(&self, position: f64, len: usize) -> (Vec<usize>, f64) {
+        let last = len.saturating_sub(1);
+        let position = position.clamp(0., last as f64);
+        let index = (position.floor() as usize).min(last);
+        let fraction = position - index as f64;
+        let indices = match self {
+            Interpolation::Linear => vec![index, (index + 1).min(last)],
+            Interpolation::Cubic => vec![
+                index.saturating_sub(1),
+                index,
+                (index + 1).min(last),
+                (index + 2).min(last),
+            ],
+        };
+        (indices, fraction)
+    }
+
+    /// Interpolates between the samples returned by `neighbours`.
+    fn interpolate(&self, samples: &[f64], t: f64) -> f64 {
+        match self {
+            Interpolation::Linear => samples[0] + (samples[1] - samples[0]) * t,
+            Interpolation::Cubic => {
+                let [p0, p1, p2, p3] = [samples[0], samples[1], samples[2], samples[3]];
+                0.5 * (2. * p1
+                    + (p2 - p0) * t
+                    + (2. * p0 - 5. * p1 + 4. * p2 - p3) * t * t
+                    + (3. * p1 - p0 - 3. * p2 + p3) * t * t * t)
+            }
+        }
+    }
+}
+
+/// Samples one column of points at positions given in stored frames, e.g. `1.5`
+/// for halfway between the second and third frame.
+/// Points next to an invalid point are not interpolated and take the nearest point.
+/// A column without any frames is invalid at every position.
+pub(crate) fn resample_column(
+    points: &Grid<MarkerPoint>,
+    column: usize,
+    positions: &[f64],
+    interpolation: Interpolation,
+) -> Vec<MarkerPoint> {
+    let len = points.rows();
+    let invalid = MarkerPoint {
+        residual: -1.,
+        ..MarkerPoint::default()
+    };
+    positions
+        .iter()
+        .map(|&position| {
+            if len == 0 {
+                return invalid;
+            }
+            let (indices, fraction) = interpolation.neighbours(position, len);
+            let neighbours: Vec<MarkerPoint> =
+                indices.iter().map(|&i| points[(i, column)]).collect();
+            let nearest = match fraction < 0.5 {
+                true => points[(indices[indices.len() / 2 - 1], column)],
+                false => points[(indices[indices.len() / 2], column)],
+            };
+            if fraction == 0. || neighbours.iter().any(|point| !point.is_valid()) {
+                return nearest;
+            }
+            let mut point = nearest;
+            for axis in 0..3 {
+                let samples: Vec<f64> = neighbours.iter().map(|x| x.point[axis] as f64).collect();
+                point.point[axis] = interpolation.interpolate(&samples, fraction) as f32;
+            }
+            let samples: Vec<f64> = neighbours.iter().map(|x| x.residual as f64).collect();
+            // residuals are interpolated linearly, so they never overshoot
+            let middle = &samples[indices.len() / 2 - 1..=indices.len() / 2];
+            point.residual = Interpolation::Linear.interpolate(middle, fraction) as f32;
+            point
+        })
+        .collect()
+}
+
+/// Samples every column of analog data at positions given in stored samples.
+pub(crate) fn resample_rows(
+    analog: &Grid<f64>,
+    positions: &[f64],
+    interpolation: Interpolation,
+) -> Grid<f64> {
+    let (len, cols) = (analog.rows(), analog.cols());
+    if len == 0 || cols == 0 {
+        return Grid::new(0, 0);
+    }
+    let mut samples = Vec::with_capacity(positions.len() * cols);
+    for &position in positions {
+        let (indices, fraction) = interpolation.neighbours(position, len);
+        for column in 0..cols {
+            let neighbours: Vec<f64> = indices.iter().map(|&i| analog[(i, column)]).collect();
+            samples.push(match fraction == 0. {
+                true => neighbours[indices.len() / 2 - 1],
+                false => interpolation.interpolate(&neighbours, fraction),
+            });
+        }
+    }
+    Grid::from_vec(samples, cols)
+}
@@ -0,0 +1,280 @@
+//! Poses of rigid marker clusters, such as the segments of a body or tracked objects.
+use crate::kinematics::{cross, dot, rotate, subtract};
+use crate::{C3d, MarkerPoint};
+
+/// A cluster of markers assumed to move as a rigid body, described by the position of
+/// each marker in the coordinate system of the body.
+///
+/// # Examples
+/// ```
+/// use c3dio::prelude::*;
+/// use c3dio::RigidBody;
+///
+/// let c3d = C3d::load("tests/c3d_org_samples/sample_03/gait-pig.c3d").unwrap();
+/// let labels = ["A22:LASI", "A22:RASI", "A22:SACR"];
+/// // the pelvis is defined by the positions of its markers in the first frame
+/// let pelvis = RigidBody::from_frame(&c3d, "PELVIS", &labels, 0).unwrap();
+/// let poses = c3d.rigid_body_poses(&pelvis).unwrap();
+/// let first = poses[0].unwrap();
+/// assert!(first.residual < 1e-3);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RigidBody {
+    pub name: String,
+    pub labels: Vec<String>,
+    /// The position of each marker in the coordinate system of the body.
+    pub reference: Vec<[f32; 3]>,
+}
+
+/// The position and orientation of a rigid body in one frame.
+/// A point `p` in the coordinate system of the body is at `rotation * p + position`
+/// in the coordinate system of the lab.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pose {
+    /// The origin of the body in the coordinate system of the lab.
+    pub position: [f32; 3],
+    /// The rotation from the body to the lab as a unit quaternion `[w, x, y, z]`.
+    pub rotation: [f32; 4],
+    /// The root mean square distance between the measured markers and the
+    /// reference markers placed by the pose, in the units of the points.
+    pub residual: f32,
+}
+
+impl RigidBody {
+    /// Creates a rigid body from the positions of its markers in the coordinate system
+    /// of the body. Returns `None` if the numbers of labels and positions differ.
+    pub fn new(name: &str, labels: &[&str], reference: &[[f32; 3]]) -> Option<Self> {
+        if labels.len() != reference.len() {
+            return None;
+        }
+        Some(RigidBody {
+            name: name.to_string(),
+            labels: labels.iter().map(|label| label.to_string()).collect(),
+            reference: reference.to_vec(),
+        })
+    }
+
+    /// Creates a rigid body from the positions of its markers in a stored frame, such as
+    /// a frame of a static trial. The body has its origin at the centroid of the markers
+    /// and its axes aligned with the lab.
+    /// Returns `None` if a label is not found, a marker is invalid in the frame, or the
+    /// markers do not span a plane.
+    pub fn from_frame(c3d: &C3d, name: &str, labels: &[&str], frame: usize) -> Option<Self> {
+        let mut positions = Vec::with_capacity(labels.len());
+        for label in labels {
+            let index = c3d.points.label_index(label)?;
+            let marker = c3d.points.get(frame, index)?;
+            if !marker.is_valid() {
+                return None;
+            }
+            positions.push(marker.point);
+        }
+        if !spans_plane(&positions) {
+            return None;
+        }
+        let centroid = centroid(&positions);
+        let reference: Vec<[f32; 3]> = positions
+            .iter()
+            .map(|&position| subtract(position, centroid))
+            .collect();
+        RigidBody::new(name, labels, &reference)
+    }
+
+    /// Calculates the pose that best places the reference markers onto the measured
+    /// markers in the least-squares sense, given in the order of the labels.
+    /// Invalid markers are ignored.
+    /// Returns `None` if fewer than three markers are valid or they do not span a plane.
+    pub fn fit(&self, markers: &[MarkerPoint]) -> Option<Pose> {
+        let (reference, measured): (Vec<[f32; 3]>, Vec<[f32; 3]>) = self
+            .reference
+            .iter()
+            .zip(markers)
+            .filter(|(_, marker)| marker.is_valid())
+            .map(|(reference, marker)| (*reference, marker.point))
+            .unzip();
+        if !spans_plane(&reference) || !spans_plane(&measured) {
+            return None;
+        }
+        let reference_centroid = centroid(&reference);
+        let measured_centroid = centroid(&measured);
+        // the cross-covariance of the centred markers
+        let mut s = [[0f64; 3]; 3];
+        for (r, m) in reference.iter().zip(&measured) {
+            let r = subtract(*r, reference_centroid);
+            let m = subtract(*m, measured_centroid);
+            for a in 0..3 {
+                for b in 0..3 {
+                    s[a][b] += r[a] as f64 * m[b] as f64;
+                }
+            }
+        }
+        // the quaternion is the eigenvector of the largest eigenvalue of this matrix,
+        // as shown by Horn (1987)
+        let n = [
+            [
+                s[0][0] + s[1][1] + s[2][2],
+                s[1][2] - s[2][1],
+                s[2][0] - s[0][2],
+                s[0][1] - s[1][0],
+            ],
+            [
+                s[1][2] - s[2][1],
+                s[0][0] - s[1][1] - s[2][2],
+                s[0][1] + s[1][0],
+                s[2][0] + s[0][2],
+            ],
+            [
+                s[2][0] - s[0][2],
+                s[0][1] + s[1][0],
+                -s[0][0] + s[1][1] - s[2][2],
+                s[1][2] + s[2][1],
+            ],
+            [
+                s[0][1] - s[1][0],
+                s[2][0] + s[0][2],
+                s[1][2] + s[2][1],
+                -s[0][0] - s[1][1] + s[2][2],
+            ],
+        ];
+        let mut rotation = largest_eigenvector(n).map(|x| x as f32);
+        if rotation[0] < 0. {
+            rotation = rotation.map(|x| -x);
+        }
+        let matrix = rotation_matrix(rotation);
+        let rotated = rotate(&matrix, reference_centroid);
+        let position = subtract(measured_centroid, rotated);
+        let mut pose = Pose {
+            position,
+            rotation,
+            residual: 0.,
+        };
+        let squared: f32 = reference
+            .iter()
+            .zip(&measured)
+            .map(|(r, m)| {
+                let error = subtract(pose.transform(*r), *m);
+                dot(error, error)
+            })
+            .sum();
+        pose.residual = (squared / reference.len() as f32).sqrt();
+        Some(pose)
+    }
+}
+
+impl Pose {
+    /// Returns the rotation as a matrix given as rows.
+    pub fn rotation_matrix(&self) -> [[f32; 3]; 3] {
+        rotation_matrix(self.rotation)
+    }
+
+    /// Returns the position in the lab of a point given in the coordinate system of the body.
+    pub fn transform(&self, point: [f32; 3]) -> [f32; 3] {
+        let rotated = rotate(&self.rotation_matrix(), point);
+        [
+            rotated[0] + self.position[0],
+            rotated[1] + self.position[1],
+            rotated[2] + self.position[2],
+        ]
+    }
+}
+
+fn rotation_matrix(quaternion: [f32; 4]) -> [[f32; 3]; 3] {
+    let [w, x, y, z] = quaternion;
+    [
+        [
+            1. - 2. * (y * y + z * z),
+            2. * (x * y - w * z),
+            2. * (x * z + w * y),
+        ],
+        [
+            2. * (x * y + w * z),
+            1. - 2. * (x * x + z * z),
+            2. * (y * z - w * x),
+        ],
+        [
+            2. * (x * z - w * y),
+            2. * (y * z + w * x),
+            1. - 2. * (x * x + y * y),
+        ],
+    ]
+}
+
+fn centroid(points: &[[f32; 3]]) -> [f32; 3] {
+    let mut sum = [0f32; 3];
+    for point in points {
+        for i in 0..3 {
+            sum[i] += point[i];
+        }
+    }
+    sum.map(|x| x / points.len() as f32)
+}
+
+/// Whether at least three of the points are not collinear.
+fn spans_plane(points: &[[f32; 3]]) -> bool {
+    if points.len() < 3 {
+        return false;
+    }
+    let farthest = points
+        .iter()
+        .map(|&point| subtract(point, points[0]))
+        .max_by(|a, b| dot(*a, *a).total_cmp(&dot(*b, *b)))
+        .unwrap_or([0.; 3]);
+    let size = dot(farthest, farthest);
+    if size <= f32::EPSILON || !size.is_finite() {
+        return false;
+    }
+    points.iter().any(|&point| {
+        let normal = cross(farthest, subtract(point, points[0]));
+        dot(normal, normal) > 1e-6 * size * size
+    })
+}
+
+/// Finds the eigenvector of the largest eigenvalue of a symmetric matrix
+/// with the Jacobi eigenvalue method.
+fn largest_eigenvector(mut a: [[f64; 4]; 4]) -> [f64; 4] {
+    let mut vectors = [[0f64; 4]; 4];
+    for (i, row) in vectors.iter_mut().enumerate() {
+        row[i] = 1.;
+    }
+    for _ in 0..50 {
+        let off_diagonal: f64 = (0..4)
+            .flat_map(|p| (p + 1..4).map(move |q| (p, q)))
+            .map(|(p, q)| a[p][q] * a[p][q])
+            .sum();
+        if off_diagonal < 1e-24 {
+            break;
+        }
+        for p in 0..4 {
+            for q in p + 1..4 {
+                if a[p][q].abs() < 1e-300 {
+                    continue;
+                }
+                let theta = (a[q][q] - a[p][p]) / (2. * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.).sqrt());
+                let c = 1. / (t * t + 1.).sqrt();
+                let s = t * c;
+                for row in a.iter_mut() {
+                    let (akp, akq) = (row[p], row[q]);
+                    row[p] = c * akp - s * akq;
+                    row[q] = s * akp + c * akq;
+                }
+                let (row_p, row_q) = (a[p], a[q]);
+                a[p] = [0, 1, 2, 3].map(|k| c * row_p[k] - s * row_q[k]);
+                a[q] = [0, 1, 2, 3].map(|k| s * row_p[k] + c * row_q[k]);
+                for row in vectors.iter_mut() {
+                    let (vp, vq) = (row[p], row[q]);
+                    row[p] = c * vp - s * vq;
+                    row[q] = s * vp + c * vq;
+                }
+            }
+        }
+    }
+    let largest = (0..4)
+        .max_by(|&i, &j| a[i][i].total_cmp(&a[j][j]))
+        .unwrap_or(0);
+    let vector = vectors.map(|row| row[largest]);
+    let length = vector.iter().map(|x| x * x).sum::<f64>().sqrt();
+    vector.map(|x| x / length)
+}
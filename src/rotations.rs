@@ -0,0 +1,513 @@
+//! Structures for the ROTATION group, which stores the poses of rigid bodies such as
+//! the segments of a skeleton or tracked objects, as written by 6DOF exports.
+use crate::parameters::{write_continued, Parameter, Parameters};
+use crate::processor::Processor;
+use crate::rigid_body::Pose;
+use crate::{C3dParseError, C3dWriteError};
+use grid::Grid;
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut, Range};
+
+/// The number of bytes of each sample: a 4x4 matrix and the reliability, as floats.
+pub(crate) const BYTES_PER_ROTATION: usize = 17 * 4;
+
+/// The pose of a rigid body in one sample, as a homogeneous transform from the
+/// coordinate system of the body to the coordinate system of the lab.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rotation {
+    /// The transform as rows, with the rotation in the upper-left 3x3 block and the
+    /// position of the body in the last column.
+    pub matrix: [[f32; 4]; 4],
+    /// A negative reliability marks the sample as invalid, e.g. when the body was not tracked.
+    pub reliability: f32,
+}
+
+impl Default for Rotation {
+    fn default() -> Self {
+        Rotation::invalid()
+    }
+}
+
+impl Rotation {
+    /// Returns a sample marked as invalid.
+    pub fn invalid() -> Self {
+        Rotation {
+            matrix: [[0.; 4]; 4],
+            reliability: -1.,
+        }
+    }
+
+    /// Creates a sample from a pose, using the residual of the pose as the reliability.
+    pub fn from_pose(pose: &Pose) -> Self {
+        let rotation = pose.rotation_matrix();
+        let mut matrix = [[0., 0., 0., 1.]; 4];
+        for i in 0..3 {
+            matrix[i] = [
+                rotation[i][0],
+                rotation[i][1],
+                rotation[i][2],
+                pose.position[i],
+            ];
+        }
+        Rotation {
+            matrix,
+            reliability: pose.residual,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.reliability >= 0. && self.matrix.iter().flatten().all(|x| x.is_finite())
+    }
+
+    /// Returns the position of the body in the coordinate system of the lab.
+    pub fn position(&self) -> [f32; 3] {
+        [self.matrix[0][3], self.matrix[1][3], self.matrix[2][3]]
+    }
+
+    /// Returns the rotation from the body to the lab as a matrix given as rows.
+    pub fn rotation_matrix(&self) -> [[f32; 3]; 3] {
+        [0, 1, 2].map(|i| [self.matrix[i][0], self.matrix[i][1], self.matrix[i][2]])
+    }
+
+    /// Returns the rotation from the body to the lab as a unit quaternion `[w, x, y, z]`
+    /// with a non-negative `w`.
+    pub fn quaternion(&self) -> [f32; 4] {
+        let m = self.rotation_matrix();
+        let trace = m[0][0] + m[1][1] + m[2][2];
+        // the largest component is found first to avoid dividing by a small number
+        let quaternion = if trace > 0. {
+            let s = (trace + 1.).sqrt() * 2.;
+            [
+                s / 4.,
+                (m[2][1] - m[1][2]) / s,
+                (m[0][2] - m[2][0]) / s,
+                (m[1][0] - m[0][1]) / s,
+            ]
+        } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+            let s = (1. + m[0][0] - m[1][1] - m[2][2]).sqrt() * 2.;
+            [
+                (m[2][1] - m[1][2]) / s,
+                s / 4.,
+                (m[0][1] + m[1][0]) / s,
+                (m[0][2] + m[2][0]) / s,
+            ]
+        } else if m[1][1] > m[2][2] {
+            let s = (1. + m[1][1] - m[0][0] - m[2][2]).sqrt() * 2.;
+            [
+                (m[0][2] - m[2][0]) / s,
+                (m[0][1] + m[1][0]) / s,
+                s / 4.,
+                (m[1][2] + m[2][1]) / s,
+            ]
+        } else {
+            let s = (1. + m[2][2] - m[0][0] - m[1][1]).sqrt() * 2.;
+            [
+                (m[1][0] - m[0][1]) / s,
+                (m[0][2] + m[2][0]) / s,
+                (m[1][2] + m[2][1]) / s,
+                s / 4.,
+            ]
+        };
+        match quaternion[0] < 0. {
+            true => quaternion.map(|x| -x),
+            false => quaternion,
+        }
+    }
+}
+
+/// The rigid bodies stored in the ROTATION group of a file.
+/// The samples of each body are stored after the point and analog frames, starting at
+/// the block given by ROTATION:DATA_START, with ROTATION:RATIO samples per point frame.
+/// Each sample is a 4x4 matrix stored by columns followed by its reliability, as floats.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rotations {
+    /// The samples of the bodies, as samples by bodies.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::grid"))]
+    pub rotations: Grid<Rotation>,
+    pub labels: Vec<String>,
+    pub descriptions: Vec<String>,
+    /// The number of samples of each body in each point frame.
+    pub ratio: u16,
+    /// The number of samples per second.
+    pub rate: f32,
+    /// The number of bodies declared by ROTATION:USED.
+    used: usize,
+    /// The block where the samples start, from ROTATION:DATA_START.
+    data_start: u16,
+}
+
+impl PartialEq for Rotations {
+    fn eq(&self, other: &Self) -> bool {
+        self.rotations.flatten() == other.rotations.flatten()
+            && self.rotations.cols() == other.rotations.cols()
+            && self.labels == other.labels
+            && self.descriptions == other.descriptions
+            && self.ratio == other.ratio
+            && self.rate == other.rate
+    }
+}
+
+impl Default for Rotations {
+    fn default() -> Self {
+        Rotations {
+            rotations: Grid::new(0, 0),
+            labels: Vec::new(),
+            descriptions: Vec::new(),
+            ratio: 1,
+            rate: 0.,
+            used: 0,
+            data_start: 0,
+        }
+    }
+}
+
+impl Deref for Rotations {
+    type Target = Grid<Rotation>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.rotations
+    }
+}
+
+impl DerefMut for Rotations {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.rotations
+    }
+}
+
+impl Rotations {
+    /// Whether the file has no ROTATION parameters or bodies.
+    pub(crate) fn is_unused(&self) -> bool {
+        self.used == 0 && self.rotations.cols() == 0 && self.labels.is_empty()
+    }
+
+    /// Returns the column of the body with the given label.
+    pub fn label_index(&self, label: &str) -> Option<usize> {
+        self.labels
+            .iter()
+            .take(self.rotations.cols())
+            .position(|x| x.trim() == label.trim())
+    }
+
+    /// Returns the samples of the body with the given label.
+    pub fn get_by_label(&self, label: &str) -> Option<Vec<Rotation>> {
+        let index = self.label_index(label)?;
+        Some(self.rotations.iter_col(index).cloned().collect())
+    }
+
+    /// Adds a body with the given samples, returning its column.
+    /// The first body sets the ratio of samples to point frames, from the number of
+    /// stored frames. Returns `None` if the number of samples does not match.
+    pub fn add_body(
+        &mut self,
+        label: &str,
+        description: &str,
+        samples: Vec<Rotation>,
+        num_frames: usize,
+        frame_rate: f32,
+    ) -> Option<usize> {
+        let bodies = self.rotations.cols();
+        if bodies == 0 {
+            if num_frames == 0 || samples.is_empty() || !samples.len().is_multiple_of(num_frames) {
+                return None;
+            }
+            self.ratio = (samples.len() / num_frames) as u16;
+            self.rate = frame_rate * self.ratio as f32;
+            self.rotations = Grid::from_vec(samples, 1);
+        } else {
+            if samples.len() != self.rotations.rows() {
+                return None;
+            }
+            self.rotations.push_col(samples);
+        }
+        self.labels.resize(bodies, String::new());
+        self.labels.push(label.to_string());
+        self.descriptions.resize(bodies, String::new());
+        self.descriptions.push(description.to_string());
+        self.used = self.rotations.cols();
+        Some(bodies)
+    }
+
+    pub(crate) fn from_parameters(
+        parameters: &mut Parameters,
+        point_rate: f32,
+    ) -> Result<Self, C3dParseError> {
+        let used: usize = match parameters.remove("ROTATION", "USED") {
+            Some(used) => u16::try_from(used.as_ref())? as usize,
+            None => 0,
+        };
+        let data_start = match parameters.remove("ROTATION", "DATA_START") {
+            Some(data_start) => data_start.as_ref().try_into()?,
+            None => 0,
+        };
+        let ratio = match parameters.remove("ROTATION", "RATIO") {
+            Some(ratio) => ratio.as_ref().try_into()?,
+            None => 1,
+        };
+        let rate = match parameters.remove("ROTATION", "RATE") {
+            Some(rate) => rate.as_ref().try_into()?,
+            None => point_rate * ratio as f32,
+        };
+        let labels = parameters
+            .remove_continued("ROTATION", "LABELS")?
+            .unwrap_or_default();
+        let descriptions = parameters
+            .remove_continued("ROTATION", "DESCRIPTIONS")?
+            .unwrap_or_default();
+        Ok(Rotations {
+            rotations: Grid::new(0, 0),
+            labels,
+            descriptions,
+            ratio,
+            rate,
+            used,
+            data_start,
+        })
+    }
+
+    /// Decodes the samples of the bodies, given the bytes of the data section starting at
+    /// the block `point_data_start`. Returns the number of bytes after the point data
+    /// start that hold samples, or `None` if there are no samples to decode.
+    /// Samples past the end of the data section are invalid, and only the frames whose
+    /// samples start in the data section are kept.
+    pub(crate) fn parse(
+        &mut self,
+        data_bytes: &[u8],
+        point_data_start: u16,
+        num_frames: usize,
+        processor: &Processor,
+    ) -> Option<usize> {
+        if self.used == 0 || self.data_start <= point_data_start || self.ratio == 0 {
+            return None;
+        }
+        let start = (self.data_start - point_data_start) as usize * 512;
+        let frame_bytes = self.ratio as usize * self.used * BYTES_PER_ROTATION;
+        let stored_frames = data_bytes.len().saturating_sub(start).div_ceil(frame_bytes);
+        let num_samples = num_frames.min(stored_frames) * self.ratio as usize;
+        let mut rotations = Grid::new(num_samples, self.used);
+        for sample in 0..num_samples {
+            for body in 0..self.used {
+                let offset = start + (sample * self.used + body) * BYTES_PER_ROTATION;
+                let bytes = match data_bytes.get(offset..offset + BYTES_PER_ROTATION) {
+                    Some(bytes) => bytes,
+                    None => continue,
+                };
+                let value = |i: usize| {
+                    processor.f32([
+                        bytes[i * 4],
+                        bytes[i * 4 + 1],
+                        bytes[i * 4 + 2],
+                        bytes[i * 4 + 3],
+                    ])
+                };
+                let mut matrix = [[0f32; 4]; 4];
+                for column in 0..4 {
+                    for (row, values) in matrix.iter_mut().enumerate() {
+                        values[column] = value(column * 4 + row);
+                    }
+                }
+                rotations[(sample, body)] = Rotation {
+                    matrix,
+                    reliability: value(16),
+                };
+            }
+        }
+        self.rotations = rotations;
+        Some(start + num_samples * self.used * BYTES_PER_ROTATION)
+    }
+
+    /// Keeps the samples of a range of point frames.
+    pub(crate) fn crop(&mut self, frames: Range<usize>) {
+        let ratio = self.ratio as usize;
+        let end = (frames.end * ratio).min(self.rotations.rows());
+        let start = (frames.start * ratio).min(end);
+        let cols = self.rotations.cols();
+        let samples: Vec<Rotation> = (start..end)
+            .flat_map(|row| self.rotations.iter_row(row).cloned().collect::<Vec<_>>())
+            .collect();
+        self.rotations = match samples.is_empty() {
+            true => Grid::new(0, cols),
+            false => Grid::from_vec(samples, cols),
+        };
+    }
+
+    /// Moves the valid samples into another lab coordinate system, mapping each position
+    /// `p` to `rotation * p + translation` and turning each orientation by the rotation.
+    pub(crate) fn transform(&mut self, rotation: &[[f32; 3]; 3], translation: [f32; 3]) {
+        for sample in self.rotations.iter_mut().filter(|x| x.is_valid()) {
+            let matrix = sample.matrix;
+            for i in 0..3 {
+                sample.matrix[i] = [0, 1, 2, 3]
+                    .map(|j| (0..3).map(|k| rotation[i][k] * matrix[k][j]).sum::<f32>());
+                sample.matrix[i][3] += translation[i];
+            }
+        }
+    }
+
+    /// Multiplies the positions of the valid samples by a factor, e.g. to convert them
+    /// to another length unit.
+    pub(crate) fn scale_positions(&mut self, factor: f32) {
+        for sample in self.rotations.iter_mut().filter(|x| x.is_valid()) {
+            for i in 0..3 {
+                sample.matrix[i][3] *= factor;
+            }
+        }
+    }
+
+    /// Returns the samples of the given number of point frames, with missing samples
+    /// invalid and the samples after them dropped.
+    pub(crate) fn frame_samples(&self, num_frames: usize) -> Grid<Rotation> {
+        let cols = self.rotations.cols();
+        let num_samples = num_frames * self.ratio as usize;
+        let samples: Vec<Rotation> = (0..num_samples)
+            .flat_map(|sample| {
+                (0..cols).map(move |body| {
+                    self.rotations
+                        .get(sample, body)
+                        .copied()
+                        .unwrap_or_default()
+                })
+            })
+            .collect();
+        match samples.is_empty() {
+            true => Grid::new(0, cols),
+            false => Grid::from_vec(samples, cols),
+        }
+    }
+
+    /// Replaces the samples by the poses at the given positions in samples, e.g. `1.5`
+    /// for halfway between the second and third sample, with `rate` samples per second.
+    /// The positions of the bodies are interpolated linearly and their rotations along
+    /// the shortest arc between the two nearest samples. Next to an invalid sample, the
+    /// nearest sample is used.
+    pub(crate) fn resample(&mut self, positions: &[f64], rate: f32) {
+        let cols = self.rotations.cols();
+        let rotations = &*self;
+        let samples: Vec<Rotation> = positions
+            .iter()
+            .flat_map(|&position| (0..cols).map(move |body| rotations.sample_at(body, position)))
+            .collect();
+        self.rotations = match samples.is_empty() {
+            true => Grid::new(0, cols),
+            false => Grid::from_vec(samples, cols),
+        };
+        self.rate = rate;
+    }
+
+    /// Interpolates the pose of a body at a position in samples.
+    fn sample_at(&self, body: usize, position: f64) -> Rotation {
+        let len = self.rotations.rows();
+        if len == 0 {
+            return Rotation::invalid();
+        }
+        let position = position.clamp(0., (len - 1) as f64);
+        let index = position.floor() as usize;
+        let fraction = (position - index as f64) as f32;
+        let before = self.rotations[(index, body)];
+        let after = self.rotations[((index + 1).min(len - 1), body)];
+        if fraction == 0. || !before.is_valid() || !after.is_valid() {
+            return match fraction < 0.5 {
+                true => before,
+                false => after,
+            };
+        }
+        let start = before.quaternion();
+        let mut end = after.quaternion();
+        let mut cos = start.iter().zip(end).map(|(a, b)| a * b).sum::<f32>();
+        if cos < 0. {
+            end = end.map(|x| -x);
+            cos = -cos;
+        }
+        let angle = cos.min(1.).acos();
+        let (weight_start, weight_end) = match angle < 1e-6 {
+            true => (1. - fraction, fraction),
+            false => (
+                ((1. - fraction) * angle).sin() / angle.sin(),
+                (fraction * angle).sin() / angle.sin(),
+            ),
+        };
+        let rotation: [f32; 4] =
+            std::array::from_fn(|i| weight_start * start[i] + weight_end * end[i]);
+        let norm = rotation.iter().map(|x| x * x).sum::<f32>().sqrt();
+        let (start, end) = (before.position(), after.position());
+        Rotation::from_pose(&Pose {
+            position: std::array::from_fn(|i| start[i] + fraction * (end[i] - start[i])),
+            rotation: rotation.map(|x| x / norm),
+            residual: before.reliability.max(after.reliability),
+        })
+    }
+
+    /// Writes the samples of the given number of point frames, with missing samples
+    /// written as invalid.
+    pub(crate) fn write_data(&self, num_frames: usize, processor: &Processor) -> Vec<u8> {
+        let bodies = self.rotations.cols();
+        let num_samples = num_frames * self.ratio as usize;
+        let mut bytes = Vec::with_capacity(num_samples * bodies * BYTES_PER_ROTATION);
+        for sample in 0..num_samples {
+            for body in 0..bodies {
+                let rotation = self
+                    .rotations
+                    .get(sample, body)
+                    .copied()
+                    .unwrap_or_default();
+                for column in 0..4 {
+                    for row in rotation.matrix {
+                        bytes.extend(processor.f32_to_bytes(row[column]));
+                    }
+                }
+                bytes.extend(processor.f32_to_bytes(rotation.reliability));
+            }
+        }
+        bytes
+    }
+
+    /// Writes the ROTATION parameters, with the samples starting at the block `data_start`.
+    pub(crate) fn write(
+        &self,
+        processor: &Processor,
+        group_names_to_ids: &HashMap<String, usize>,
+        data_start: u16,
+    ) -> Result<Vec<u8>, C3dWriteError> {
+        let mut bytes = Vec::new();
+        if self.is_unused() {
+            return Ok(bytes);
+        }
+        let group_id = group_names_to_ids["ROTATION"];
+        let used = self.rotations.cols();
+        let data_start = match used {
+            0 => 0,
+            _ => data_start,
+        };
+        for (name, value) in [
+            ("USED", used as i16),
+            ("DATA_START", data_start as i16),
+            ("RATIO", self.ratio as i16),
+        ] {
+            bytes.extend(Parameter::integer(value).write(
+                processor,
+                name.to_string(),
+                group_id,
+                false,
+            )?);
+        }
+        bytes.extend(Parameter::float(self.rate).write(
+            processor,
+            "RATE".to_string(),
+            group_id,
+            false,
+        )?);
+        let mut labels = self.labels.clone();
+        labels.resize(used, String::new());
+        let mut descriptions = self.descriptions.clone();
+        descriptions.resize(used, String::new());
+        let strings = |strings| Ok(Parameter::strings(strings));
+        for (name, entries) in [("LABELS", &labels), ("DESCRIPTIONS", &descriptions)] {
+            bytes.extend(write_continued(
+                entries, strings, processor, name, group_id,
+            )?);
+        }
+        Ok(bytes)
+    }
+}
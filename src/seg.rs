@@ -11,6 +11,7 @@ use std::collections::HashMap;
 /// since it provides useful information about the data if any issues
 /// need to be resolved related to collection or processing.
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Seg {
     /// The diameter of the marker in millimeters. It is good practice to
     /// use the same diameter for all markers in a collection.
@@ -18,6 +19,7 @@ pub struct Seg {
     /// A 3x2 grid of floats that defines the minimum and maximum values for each
     /// of the three dimensions of the marker data.
     // TODO: This should be a 3x2 grid of floats, or even better a custom type
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::option_grid"))]
     pub data_limits: Option<Grid<f32>>,
     /// A float that defines the acceleration factor used in the calculation of
     /// a new segment. For gait analysis, this value is typically 50mm/sec^2.
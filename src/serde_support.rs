@@ -0,0 +1,103 @@
+//! Serialization of the field types that do not implement serde themselves,
+//! used with `#[serde(with = "...")]` when the `serde` feature is enabled.
+use ::grid::Grid;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A grid is stored as its size and its elements in row-major order.
+#[derive(Serialize)]
+struct GridRef<'a, T> {
+    rows: usize,
+    cols: usize,
+    data: &'a [T],
+}
+
+#[derive(Deserialize)]
+struct GridData<T> {
+    rows: usize,
+    cols: usize,
+    data: Vec<T>,
+}
+
+impl<T> GridData<T> {
+    fn into_grid<E: Error>(self) -> Result<Grid<T>, E> {
+        if self.rows * self.cols != self.data.len() {
+            return Err(E::custom(format!(
+                "a {}x{} grid cannot hold {} elements",
+                self.rows,
+                self.cols,
+                self.data.len()
+            )));
+        }
+        Ok(Grid::from_vec(self.data, self.cols))
+    }
+}
+
+pub(crate) mod grid {
+    use super::*;
+
+    pub(crate) fn serialize<T: Serialize, S: Serializer>(
+        grid: &Grid<T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        GridRef {
+            rows: grid.rows(),
+            cols: grid.cols(),
+            data: grid.flatten(),
+        }
+        .serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, T: Deserialize<'de>, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Grid<T>, D::Error> {
+        GridData::deserialize(deserializer)?.into_grid()
+    }
+}
+
+pub(crate) mod option_grid {
+    use super::*;
+
+    pub(crate) fn serialize<T: Serialize, S: Serializer>(
+        grid: &Option<Grid<T>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        grid.as_ref()
+            .map(|grid| GridRef {
+                rows: grid.rows(),
+                cols: grid.cols(),
+                data: grid.flatten(),
+            })
+            .serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, T: Deserialize<'de>, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Grid<T>>, D::Error> {
+        Option::<GridData<T>>::deserialize(deserializer)?
+            .map(|grid| grid.into_grid())
+            .transpose()
+    }
+}
+
+/// The header block is stored as a sequence of 512 bytes.
+pub(crate) mod header_bytes {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        bytes: &[u8; 512],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        bytes.as_slice().serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<[u8; 512], D::Error> {
+        let bytes = Vec::<u8>::deserialize(deserializer)?;
+        let length = bytes.len();
+        bytes
+            .try_into()
+            .map_err(|_| D::Error::invalid_length(length, &"512 header bytes"))
+    }
+}
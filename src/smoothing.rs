@@ -0,0 +1,297 @@
+//! Window-based smoothers for marker trajectories, which have none of the transients
+//! at the ends of a trial of a filter run forwards and backwards.
+use crate::data::MarkerPoint;
+use crate::points::Derivatives;
+use crate::C3dFilterError;
+
+/// Smooths samples with a window centred on each sample.
+/// Samples closer to the ends than half a window use the samples that are there:
+/// the moving average shrinks its window to stay centred, and Savitzky–Golay fits its
+/// polynomial to the first or last window and evaluates it at the sample.
+/// Sequences shorter than the window are smoothed with a window of all their samples.
+///
+/// # Examples
+/// ```
+/// use c3dio::prelude::*;
+/// use c3dio::Smoother;
+///
+/// let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+/// c3d.smooth_points(&Smoother::savitzky_golay(9, 3)).unwrap();
+/// let velocity = c3d
+///     .points
+///     .smoothed_derivatives("LASI", &Smoother::savitzky_golay(9, 3))
+///     .unwrap()
+///     .velocity;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Smoother {
+    /// Replaces each sample by the mean of the window of samples centred on it.
+    MovingAverage { window: usize },
+    /// Replaces each sample by the value of a polynomial of the given order fit to the
+    /// window by least squares, which keeps peaks better than a moving average and
+    /// gives the derivatives of the signal from those of the polynomial.
+    SavitzkyGolay { window: usize, order: usize },
+}
+
+impl Smoother {
+    pub fn moving_average(window: usize) -> Self {
+        Smoother::MovingAverage { window }
+    }
+
+    pub fn savitzky_golay(window: usize, order: usize) -> Self {
+        Smoother::SavitzkyGolay { window, order }
+    }
+
+    /// Returns the number of samples in the window and the order of the polynomial,
+    /// which is 0 for a moving average, checking that the window is odd and longer
+    /// than the order.
+    fn window(&self) -> Result<(usize, usize), C3dFilterError> {
+        let (window, order) = match *self {
+            Smoother::MovingAverage { window } => (window, 0),
+            Smoother::SavitzkyGolay { window, order } => (window, order),
+        };
+        match window % 2 == 1 && window > order {
+            true => Ok((window, order)),
+            false => Err(C3dFilterError::InvalidWindow(window)),
+        }
+    }
+
+    pub fn smooth(&self, samples: &[f64]) -> Result<Vec<f64>, C3dFilterError> {
+        let (window, order) = self.window()?;
+        Ok(match self {
+            Smoother::MovingAverage { .. } => moving_average(samples, window / 2),
+            Smoother::SavitzkyGolay { .. } => savitzky_golay(samples, window, order, 0),
+        })
+    }
+
+    /// Returns a derivative of samples taken at the given rate, in their units per second
+    /// to the power of the derivative, from the derivatives of the Savitzky–Golay
+    /// polynomials. Fails with `InvalidDerivative` for a moving average or a derivative
+    /// above the order, and with `TooFewSamples` if there are not more samples than the
+    /// order.
+    pub fn derivative(
+        &self,
+        samples: &[f64],
+        derivative: usize,
+        rate: f64,
+    ) -> Result<Vec<f64>, C3dFilterError> {
+        let (window, order) = self.window()?;
+        if matches!(self, Smoother::MovingAverage { .. }) || derivative > order {
+            return Err(C3dFilterError::InvalidDerivative(derivative));
+        }
+        if samples.len() <= order {
+            return Err(C3dFilterError::TooFewSamples(samples.len(), order + 1));
+        }
+        let scale = rate.powi(derivative as i32);
+        Ok(savitzky_golay(samples, window, order, derivative)
+            .into_iter()
+            .map(|sample| sample * scale)
+            .collect())
+    }
+
+    /// Smooths each axis of a marker trajectory. Every run of valid samples is smoothed
+    /// on its own, so the samples next to a gap are not pulled towards the invalid
+    /// samples in it. Residuals and cameras are kept.
+    pub fn smooth_points(&self, points: &mut [MarkerPoint]) -> Result<(), C3dFilterError> {
+        self.window()?;
+        for run in valid_runs(points).collect::<Vec<_>>() {
+            for axis in 0..3 {
+                let samples = axis_samples(&points[run.clone()], axis);
+                for (point, sample) in points[run.clone()].iter_mut().zip(self.smooth(&samples)?) {
+                    point.point[axis] = sample as f32;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the velocity and acceleration of a marker trajectory from the derivatives
+    /// of the Savitzky–Golay polynomials of each run of valid samples.
+    /// Runs with no more samples than the order have neither, and polynomials of an
+    /// order below 2 give no acceleration.
+    pub(crate) fn derivatives(
+        &self,
+        points: &[MarkerPoint],
+        rate: f64,
+    ) -> Result<Derivatives, C3dFilterError> {
+        let (_, order) = self.window()?;
+        if matches!(self, Smoother::MovingAverage { .. }) {
+            return Err(C3dFilterError::InvalidDerivative(1));
+        }
+        let mut derivatives = Derivatives {
+            velocity: vec![None; points.len()],
+            acceleration: vec![None; points.len()],
+        };
+        for run in valid_runs(points).filter(|run| run.len() > order) {
+            let axes: Vec<Vec<f64>> = (0..3)
+                .map(|axis| axis_samples(&points[run.clone()], axis))
+                .collect();
+            let derivative = |derivative: usize| -> Result<Vec<[f32; 3]>, C3dFilterError> {
+                let axes = axes
+                    .iter()
+                    .map(|samples| self.derivative(samples, derivative, rate))
+                    .collect::<Result<Vec<Vec<f64>>, C3dFilterError>>()?;
+                Ok((0..run.len())
+                    .map(|i| [0, 1, 2].map(|axis| axes[axis][i] as f32))
+                    .collect())
+            };
+            for (frame, velocity) in run.clone().zip(derivative(1)?) {
+                derivatives.velocity[frame] = Some(velocity);
+            }
+            if order >= 2 {
+                for (frame, acceleration) in run.clone().zip(derivative(2)?) {
+                    derivatives.acceleration[frame] = Some(acceleration);
+                }
+            }
+        }
+        Ok(derivatives)
+    }
+}
+
+/// Returns the ranges of consecutive valid samples of a marker trajectory.
+fn valid_runs(points: &[MarkerPoint]) -> impl Iterator<Item = std::ops::Range<usize>> + '_ {
+    let mut start = 0;
+    std::iter::from_fn(move || {
+        while start < points.len() && !points[start].is_valid() {
+            start += 1;
+        }
+        if start == points.len() {
+            return None;
+        }
+        let end = points[start..]
+            .iter()
+            .position(|point| !point.is_valid())
+            .map_or(points.len(), |len| start + len);
+        let run = start..end;
+        start = end;
+        Some(run)
+    })
+}
+
+fn axis_samples(points: &[MarkerPoint], axis: usize) -> Vec<f64> {
+    points
+        .iter()
+        .map(|point| point.point[axis] as f64)
+        .collect()
+}
+
+/// Averages each sample with up to `half` samples on either side, as many on each side.
+fn moving_average(samples: &[f64], half: usize) -> Vec<f64> {
+    let len = samples.len();
+    (0..len)
+        .map(|i| {
+            let reach = half.min(i).min(len - 1 - i);
+            let window = &samples[i - reach..=i + reach];
+            window.iter().sum::<f64>() / window.len() as f64
+        })
+        .collect()
+}
+
+/// Returns a derivative, per sample, of the polynomials fit to the window around each
+/// sample, or to the first or last window for the samples near the ends.
+fn savitzky_golay(samples: &[f64], window: usize, order: usize, derivative: usize) -> Vec<f64> {
+    let len = samples.len();
+    let window = window.min(len);
+    if window <= order {
+        return samples.to_vec();
+    }
+    let half = window / 2;
+    let convolve = |start: usize, weights: &[f64]| {
+        samples[start..start + window]
+            .iter()
+            .zip(weights)
+            .map(|(sample, weight)| sample * weight)
+            .sum::<f64>()
+    };
+    // the samples near the ends are evaluated at their place in the first or last window
+    let edge_weights: Vec<Vec<f64>> = (0..window)
+        .map(|position| weights(window, order, derivative, position))
+        .collect();
+    let centre = &edge_weights[half];
+    (0..len)
+        .map(|i| {
+            if i < half {
+                convolve(0, &edge_weights[i])
+            } else if i + window - half > len {
+                convolve(len - window, &edge_weights[i + window - len])
+            } else {
+                convolve(i - half, centre)
+            }
+        })
+        .collect()
+}
+
+/// Returns the weights of the samples of a window that give a derivative, per sample,
+/// of the polynomial fit to them by least squares, at a position in the window.
+fn weights(window: usize, order: usize, derivative: usize, position: usize) -> Vec<f64> {
+    // the positions are scaled to [-1, 1] to keep the normal equations well conditioned
+    let centre = (window - 1) as f64 / 2.;
+    let scale = centre.max(1.);
+    let offsets: Vec<f64> = (0..window).map(|i| (i as f64 - centre) / scale).collect();
+    let x = (position as f64 - centre) / scale;
+    let terms = order + 1;
+    let normal: Vec<Vec<f64>> = (0..terms)
+        .map(|j| {
+            (0..terms)
+                .map(|k| offsets.iter().map(|t| t.powi((j + k) as i32)).sum())
+                .collect()
+        })
+        .collect();
+    // the derivative of each power of the position at x
+    let target: Vec<f64> = (0..terms)
+        .map(|k| match k < derivative {
+            true => 0.,
+            false => {
+                let falling: f64 = (k - derivative + 1..=k).map(|n| n as f64).product();
+                falling * x.powi((k - derivative) as i32)
+            }
+        })
+        .collect();
+    let solution = solve(normal, target);
+    let per_sample = scale.powi(derivative as i32);
+    offsets
+        .iter()
+        .map(|t| {
+            solution
+                .iter()
+                .enumerate()
+                .map(|(k, z)| z * t.powi(k as i32))
+                .sum::<f64>()
+                / per_sample
+        })
+        .collect()
+}
+
+/// Solves a small linear system by Gaussian elimination with partial pivoting.
+fn solve(mut matrix: Vec<Vec<f64>>, mut rhs: Vec<f64>) -> Vec<f64> {
+    let n = rhs.len();
+    for column in 0..n {
+        let pivot = (column..n)
+            .max_by(|&a, &b| matrix[a][column].abs().total_cmp(&matrix[b][column].abs()))
+            .unwrap_or(column);
+        matrix.swap(column, pivot);
+        rhs.swap(column, pivot);
+        let (above, below) = matrix.split_at_mut(column + 1);
+        let pivot_row = &above[column];
+        if pivot_row[column] == 0. {
+            continue;
+        }
+        for (offset, row) in below.iter_mut().enumerate() {
+            let factor = row[column] / pivot_row[column];
+            for (value, pivot_value) in row.iter_mut().zip(pivot_row).skip(column) {
+                *value -= factor * pivot_value;
+            }
+            rhs[column + 1 + offset] -= factor * rhs[column];
+        }
+    }
+    let mut solution = vec![0.; n];
+    for row in (0..n).rev() {
+        let known: f64 = (row + 1..n).map(|k| matrix[row][k] * solution[k]).sum();
+        solution[row] = match matrix[row][row] {
+            0. => 0.,
+            diagonal => (rhs[row] - known) / diagonal,
+        };
+    }
+    solution
+}
@@ -0,0 +1,145 @@
+//! The SUBJECTS group, which lists the subjects of trials captured by Vicon Nexus and
+//! other software that labels the markers of several subjects in one file.
+use crate::parameters::Parameters;
+use crate::C3dWriteError;
+
+/// A subject listed in the SUBJECTS group.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Subject {
+    /// The name of the subject, from SUBJECTS:NAMES.
+    pub name: String,
+    /// The prefix of the labels of the subject's markers, such as `Human1:`,
+    /// from SUBJECTS:LABEL_PREFIXES. Empty if the labels are not prefixed.
+    pub label_prefix: String,
+    /// The marker set the subject was labeled with, from SUBJECTS:MARKER_SETS,
+    /// e.g. `PlugInGait`. Empty if not given.
+    pub marker_set: String,
+}
+
+impl Subject {
+    pub fn new(name: &str, label_prefix: &str) -> Subject {
+        Subject {
+            name: name.to_string(),
+            label_prefix: label_prefix.to_string(),
+            marker_set: String::new(),
+        }
+    }
+
+    /// Returns a label without the subject's prefix, e.g. `RKNE` for `Human1:RKNE`,
+    /// or `None` if the label does not start with the prefix.
+    pub fn strip_prefix<'a>(&self, label: &'a str) -> Option<&'a str> {
+        label.trim().strip_prefix(self.label_prefix.trim())
+    }
+}
+
+/// The subjects of a trial, from the SUBJECTS group.
+/// See `C3d::subjects` and `C3d::set_subjects`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Subjects {
+    pub subjects: Vec<Subject>,
+    /// Whether the trial is a static calibration trial, from SUBJECTS:IS_STATIC.
+    pub is_static: bool,
+    /// Whether the point labels start with the label prefix of their subject,
+    /// from SUBJECTS:USES_PREFIXES. Files without the parameter are taken to use them.
+    pub uses_prefixes: bool,
+}
+
+impl Default for Subjects {
+    fn default() -> Self {
+        Subjects {
+            subjects: Vec::new(),
+            is_static: false,
+            uses_prefixes: true,
+        }
+    }
+}
+
+impl Subjects {
+    pub fn new() -> Subjects {
+        Subjects::default()
+    }
+
+    pub(crate) fn from_parameters(parameters: &Parameters) -> Subjects {
+        let strings = |parameter: &str| -> Vec<String> {
+            parameters
+                .get_vec::<String>("SUBJECTS", parameter)
+                .unwrap_or_default()
+        };
+        let names = strings("NAMES");
+        let prefixes = strings("LABEL_PREFIXES");
+        let marker_sets = strings("MARKER_SETS");
+        let used = match parameters.get_as::<u16>("SUBJECTS", "USED") {
+            Ok(used) => used as usize,
+            Err(_) => names.len().max(prefixes.len()),
+        };
+        let subjects = (0..used)
+            .map(|i| Subject {
+                name: names.get(i).cloned().unwrap_or_default(),
+                label_prefix: prefixes.get(i).cloned().unwrap_or_default(),
+                marker_set: marker_sets.get(i).cloned().unwrap_or_default(),
+            })
+            .collect();
+        let flag = |parameter: &str| parameters.get_as::<i16>("SUBJECTS", parameter).ok();
+        Subjects {
+            subjects,
+            is_static: flag("IS_STATIC").is_some_and(|flag| flag != 0),
+            uses_prefixes: flag("USES_PREFIXES").is_none_or(|flag| flag != 0),
+        }
+    }
+
+    /// Writes the subjects to the SUBJECTS group, keeping the parameters of the group
+    /// that are not described by `Subjects`. SUBJECTS:MARKER_SETS is only written if
+    /// a subject has a marker set or the group already has the parameter.
+    pub(crate) fn write_parameters(
+        &self,
+        parameters: &mut Parameters,
+    ) -> Result<(), C3dWriteError> {
+        let strings = |field: fn(&Subject) -> &String| -> Vec<String> {
+            self.subjects.iter().map(|s| field(s).clone()).collect()
+        };
+        parameters.set("SUBJECTS", "USED", self.subjects.len() as i16)?;
+        parameters.set("SUBJECTS", "NAMES", strings(|s| &s.name))?;
+        parameters.set("SUBJECTS", "LABEL_PREFIXES", strings(|s| &s.label_prefix))?;
+        parameters.set("SUBJECTS", "IS_STATIC", self.is_static as i16)?;
+        parameters.set("SUBJECTS", "USES_PREFIXES", self.uses_prefixes as i16)?;
+        if self.subjects.iter().any(|s| !s.marker_set.is_empty())
+            || parameters.contains("SUBJECTS", "MARKER_SETS")
+        {
+            parameters.set("SUBJECTS", "MARKER_SETS", strings(|s| &s.marker_set))?;
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.subjects.is_empty()
+    }
+
+    /// Returns the subject with the given name.
+    pub fn get(&self, name: &str) -> Option<&Subject> {
+        self.subjects.iter().find(|subject| subject.name == name)
+    }
+
+    /// Returns the subject a point label belongs to: the subject with the longest label
+    /// prefix the label starts with, or the only subject of a file whose labels are not
+    /// prefixed.
+    pub fn subject_of(&self, label: &str) -> Option<&Subject> {
+        let prefixed = self.uses_prefixes
+            && self
+                .subjects
+                .iter()
+                .any(|subject| !subject.label_prefix.trim().is_empty());
+        if !prefixed {
+            return match self.subjects.len() {
+                1 => self.subjects.first(),
+                _ => None,
+            };
+        }
+        self.subjects
+            .iter()
+            .filter(|subject| !subject.label_prefix.trim().is_empty())
+            .filter(|subject| subject.strip_prefix(label).is_some())
+            .max_by_key(|subject| subject.label_prefix.trim().len())
+    }
+}
@@ -0,0 +1,339 @@
+//! The TIMECODE group, which Vicon Nexus writes for trials captured with a video
+//! timecode source, and conversions between timecodes and frame numbers, e.g. to
+//! synchronize a trial with broadcast video.
+use crate::parameters::{ParameterData, Parameters};
+use crate::C3dWriteError;
+use std::fmt;
+
+/// The video standard of a timecode, from TIMECODE:STANDARD.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TimecodeStandard {
+    /// 25 frames per second.
+    Pal,
+    /// 30 frames per second, or 29.97 with drop-frame timecode.
+    Ntsc,
+    /// 24 frames per second.
+    Film,
+    /// Any other standard, as named in the file.
+    Other(String),
+}
+
+impl TimecodeStandard {
+    fn from_name(name: &str) -> TimecodeStandard {
+        match name.trim().to_ascii_uppercase().as_str() {
+            "PAL" => TimecodeStandard::Pal,
+            "NTSC" => TimecodeStandard::Ntsc,
+            "FILM" => TimecodeStandard::Film,
+            _ => TimecodeStandard::Other(name.trim().to_string()),
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            TimecodeStandard::Pal => "PAL",
+            TimecodeStandard::Ntsc => "NTSC",
+            TimecodeStandard::Film => "FILM",
+            TimecodeStandard::Other(name) => name,
+        }
+    }
+
+    /// Returns the number of frames counted in each second of timecode,
+    /// or `None` for other standards.
+    pub fn frames_per_second(&self) -> Option<u32> {
+        match self {
+            TimecodeStandard::Pal => Some(25),
+            TimecodeStandard::Ntsc => Some(30),
+            TimecodeStandard::Film => Some(24),
+            TimecodeStandard::Other(_) => None,
+        }
+    }
+}
+
+/// A timecode, `hours:minutes:seconds:frames`, with the subframe of a trial captured at
+/// a multiple of the timecode rate. Drop-frame timecodes skip the first frame numbers of
+/// every minute but every tenth, two numbers at 30 frames per second, to keep up with
+/// 29.97 Hz video.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Timecode {
+    pub hours: u32,
+    pub minutes: u32,
+    pub seconds: u32,
+    pub frames: u32,
+    pub subframe: u32,
+    pub drop_frame: bool,
+}
+
+impl Timecode {
+    pub fn new(hours: u32, minutes: u32, seconds: u32, frames: u32) -> Timecode {
+        Timecode {
+            hours,
+            minutes,
+            seconds,
+            frames,
+            ..Timecode::default()
+        }
+    }
+
+    /// Parses a timecode such as `10:00:00:00`, `10:00:00;00` for drop-frame timecode or
+    /// `10:00:00:00.1` with a subframe.
+    pub fn parse(timecode: &str) -> Option<Timecode> {
+        let timecode = timecode.trim();
+        let (timecode, subframe) = match timecode.split_once('.') {
+            Some((timecode, subframe)) => (timecode, subframe.parse().ok()?),
+            None => (timecode, 0),
+        };
+        let drop_frame = timecode.contains(';');
+        let fields: Vec<u32> = timecode
+            .split([':', ';'])
+            .map(|field| field.parse().ok())
+            .collect::<Option<_>>()?;
+        match fields[..] {
+            [hours, minutes, seconds, frames] => Some(Timecode {
+                hours,
+                minutes,
+                seconds,
+                frames,
+                subframe,
+                drop_frame,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of frames since `00:00:00:00` at the given nominal rate,
+    /// leaving out the frame numbers skipped by drop-frame timecode.
+    pub fn to_frame_count(&self, frames_per_second: u32) -> u64 {
+        let rate = frames_per_second as u64;
+        let seconds = self.hours as u64 * 3600 + self.minutes as u64 * 60 + self.seconds as u64;
+        let count = seconds * rate + self.frames as u64;
+        match dropped_per_minute(frames_per_second, self.drop_frame) {
+            0 => count,
+            dropped => {
+                let minutes = self.hours as u64 * 60 + self.minutes as u64;
+                count - dropped * (minutes - minutes / 10)
+            }
+        }
+    }
+
+    /// Returns the timecode of the frame counted from `00:00:00:00` at the given
+    /// nominal rate, the inverse of `to_frame_count`.
+    pub fn from_frame_count(count: u64, frames_per_second: u32, drop_frame: bool) -> Timecode {
+        let rate = frames_per_second.max(1) as u64;
+        let dropped = dropped_per_minute(frames_per_second, drop_frame);
+        let mut count = count;
+        if dropped > 0 {
+            let per_ten_minutes = rate * 600 - dropped * 9;
+            let per_minute = rate * 60 - dropped;
+            let (tens, rest) = (count / per_ten_minutes, count % per_ten_minutes);
+            count += dropped * 9 * tens;
+            if rest > dropped {
+                count += dropped * ((rest - dropped) / per_minute);
+            }
+        }
+        Timecode {
+            hours: (count / (rate * 3600)) as u32,
+            minutes: (count / (rate * 60) % 60) as u32,
+            seconds: (count / rate % 60) as u32,
+            frames: (count % rate) as u32,
+            subframe: 0,
+            drop_frame: dropped > 0,
+        }
+    }
+}
+
+/// Returns the number of frame numbers drop-frame timecode skips each minute, which is
+/// only defined for multiples of 30 frames per second.
+fn dropped_per_minute(frames_per_second: u32, drop_frame: bool) -> u64 {
+    match drop_frame && frames_per_second > 0 && frames_per_second.is_multiple_of(30) {
+        true => frames_per_second as u64 / 15,
+        false => 0,
+    }
+}
+
+impl fmt::Display for Timecode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let separator = if self.drop_frame { ';' } else { ':' };
+        write!(
+            f,
+            "{:02}:{:02}:{:02}{}{:02}",
+            self.hours, self.minutes, self.seconds, separator, self.frames
+        )?;
+        if self.subframe > 0 {
+            write!(f, ".{}", self.subframe)?;
+        }
+        Ok(())
+    }
+}
+
+/// The timecode settings of a trial, from the TIMECODE group.
+/// See `C3d::timecode` and `C3d::set_timecode`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimecodeGroup {
+    pub standard: TimecodeStandard,
+    /// Whether the timecode is drop-frame timecode, from TIMECODE:DROP_FRAMES.
+    pub drop_frames: bool,
+    /// Whether the timecode source numbers the two fields of each video frame,
+    /// from TIMECODE:FIELD_NUMBERS.
+    pub field_numbers: bool,
+    /// The number of frames of the trial in each frame of timecode,
+    /// from TIMECODE:SUBFRAMESPERFRAME.
+    pub subframes_per_frame: u16,
+    /// The timecode of the first frame of the trial, from TIMECODE:START.
+    pub start: Option<Timecode>,
+}
+
+impl TimecodeGroup {
+    pub fn new(standard: TimecodeStandard, subframes_per_frame: u16) -> TimecodeGroup {
+        TimecodeGroup {
+            standard,
+            drop_frames: false,
+            field_numbers: false,
+            subframes_per_frame,
+            start: None,
+        }
+    }
+
+    /// Reads the TIMECODE group, returning `None` for files without it.
+    /// A missing TIMECODE:SUBFRAMESPERFRAME is derived from the frame rate of the trial.
+    pub(crate) fn from_parameters(parameters: &Parameters, frame_rate: f32) -> Option<Self> {
+        parameters.get_group("TIMECODE")?;
+        let number = |parameter: &str| -> Option<Vec<f32>> {
+            Some(match &parameters.get("TIMECODE", parameter)?.data {
+                ParameterData::Integer(values) => values.iter().map(|&x| x as f32).collect(),
+                ParameterData::Float(values) => values.clone(),
+                ParameterData::Byte(values) => values.iter().map(|&x| x as f32).collect(),
+                ParameterData::Char(_) => return None,
+            })
+        };
+        let flag = |parameter: &str| {
+            number(parameter)
+                .and_then(|values| values.first().copied())
+                .is_some_and(|value| value != 0.)
+        };
+        let standard = parameters
+            .get_as::<String>("TIMECODE", "STANDARD")
+            .map(|name| TimecodeStandard::from_name(&name))
+            .unwrap_or(TimecodeStandard::Other(String::new()));
+        let drop_frames = flag("DROP_FRAMES");
+        let subframes_per_frame = number("SUBFRAMESPERFRAME")
+            .and_then(|values| values.first().copied())
+            .map(|value| value as u16)
+            .or_else(|| {
+                let rate = standard.frames_per_second()? as f32;
+                Some((frame_rate / rate).round() as u16)
+            })
+            .unwrap_or(1)
+            .max(1);
+        let start = match number("START") {
+            Some(values) if values.len() >= 4 => Some(Timecode {
+                hours: values[0] as u32,
+                minutes: values[1] as u32,
+                seconds: values[2] as u32,
+                frames: values[3] as u32,
+                subframe: values.get(4).map_or(0, |&x| x as u32),
+                drop_frame: drop_frames,
+            }),
+            _ => parameters
+                .get_as::<String>("TIMECODE", "START")
+                .ok()
+                .and_then(|start| Timecode::parse(&start))
+                .map(|start| Timecode {
+                    drop_frame: drop_frames,
+                    ..start
+                }),
+        };
+        Some(TimecodeGroup {
+            standard,
+            drop_frames,
+            field_numbers: flag("FIELD_NUMBERS"),
+            subframes_per_frame,
+            start,
+        })
+    }
+
+    /// Writes the settings to the TIMECODE group, keeping its other parameters.
+    pub(crate) fn write_parameters(
+        &self,
+        parameters: &mut Parameters,
+    ) -> Result<(), C3dWriteError> {
+        parameters.set("TIMECODE", "STANDARD", self.standard.name())?;
+        parameters.set("TIMECODE", "DROP_FRAMES", self.drop_frames as i16)?;
+        parameters.set("TIMECODE", "FIELD_NUMBERS", self.field_numbers as i16)?;
+        parameters.set(
+            "TIMECODE",
+            "SUBFRAMESPERFRAME",
+            self.subframes_per_frame as i16,
+        )?;
+        match &self.start {
+            Some(start) => {
+                let fields = [
+                    start.hours,
+                    start.minutes,
+                    start.seconds,
+                    start.frames,
+                    start.subframe,
+                ];
+                parameters.set(
+                    "TIMECODE",
+                    "START",
+                    fields.iter().map(|&x| x as i16).collect::<Vec<i16>>(),
+                )?;
+            }
+            None => {
+                parameters.remove("TIMECODE", "START");
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the nominal number of timecode frames per second, from the standard or,
+    /// for other standards, the frame rate of the trial and the subframes per frame.
+    pub fn frames_per_second(&self, frame_rate: f32) -> u32 {
+        self.standard
+            .frames_per_second()
+            .unwrap_or_else(|| (frame_rate / self.subframes_per_frame.max(1) as f32).round() as u32)
+    }
+
+    /// Returns the timecode of a frame of the trial, counted from zero at its first
+    /// frame, or `None` if the group has no start timecode.
+    pub fn timecode_of_frame(&self, frame: usize, frame_rate: f32) -> Option<Timecode> {
+        let rate = self.frames_per_second(frame_rate);
+        let subframes = self.subframes_per_frame.max(1) as u64;
+        let subframe = self.start_subframe(rate)? + frame as u64;
+        Some(Timecode {
+            subframe: (subframe % subframes) as u32,
+            ..Timecode::from_frame_count(subframe / subframes, rate, self.drop_frames)
+        })
+    }
+
+    /// Returns the frame of the trial, counted from zero at its first frame, that has
+    /// the given timecode, or `None` if the timecode is before the start of the trial
+    /// or the group has no start timecode.
+    pub fn frame_of_timecode(&self, timecode: &Timecode, frame_rate: f32) -> Option<usize> {
+        let rate = self.frames_per_second(frame_rate);
+        let subframes = self.subframes_per_frame.max(1) as u64;
+        let timecode = Timecode {
+            drop_frame: self.drop_frames,
+            ..*timecode
+        };
+        let subframe = timecode.to_frame_count(rate) * subframes + timecode.subframe as u64;
+        subframe
+            .checked_sub(self.start_subframe(rate)?)
+            .map(|frame| frame as usize)
+    }
+
+    /// Returns the number of subframes from `00:00:00:00` to the start of the trial.
+    fn start_subframe(&self, frames_per_second: u32) -> Option<u64> {
+        let start = Timecode {
+            drop_frame: self.drop_frames,
+            ..self.start?
+        };
+        Some(
+            start.to_frame_count(frames_per_second) * self.subframes_per_frame.max(1) as u64
+                + start.subframe as u64,
+        )
+    }
+}
@@ -0,0 +1,222 @@
+//! Units of the quantities stored as point data and conversions between them.
+
+/// The kind of quantity stored in a point.
+/// Besides marker trajectories, model outputs such as joint angles, forces,
+/// moments and powers are stored as points. Their labels are listed in the
+/// POINT:ANGLES, POINT:FORCES, POINT:MOMENTS, POINT:POWERS and POINT:SCALARS parameters.
+/// Points not listed in any of these parameters are markers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PointKind {
+    Marker,
+    Angle,
+    Force,
+    Moment,
+    Power,
+    Scalar,
+}
+
+impl PointKind {
+    /// Name of the POINT parameter listing the labels of points of this kind.
+    pub(crate) fn labels_parameter(&self) -> Option<&'static str> {
+        match self {
+            PointKind::Marker => None,
+            PointKind::Angle => Some("ANGLES"),
+            PointKind::Force => Some("FORCES"),
+            PointKind::Moment => Some("MOMENTS"),
+            PointKind::Power => Some("POWERS"),
+            PointKind::Scalar => Some("SCALARS"),
+        }
+    }
+
+    /// Name of the POINT parameter holding the units of points of this kind.
+    pub(crate) fn units_parameter(&self) -> Option<&'static str> {
+        match self {
+            PointKind::Marker => Some("UNITS"),
+            PointKind::Angle => Some("ANGLE_UNITS"),
+            PointKind::Force => Some("FORCE_UNITS"),
+            PointKind::Moment => Some("MOMENT_UNITS"),
+            PointKind::Power => Some("POWER_UNITS"),
+            PointKind::Scalar => None,
+        }
+    }
+}
+
+/// A point classified by the kind of quantity it stores, as returned by
+/// `C3d::point_channels`. Its samples are in column `index` of `C3d::points`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PointChannel {
+    pub index: usize,
+    pub label: String,
+    pub kind: PointKind,
+    /// The units from POINT:UNITS for markers and from the units parameter of the kind
+    /// otherwise, or `None` if the parameter is missing or the kind has none.
+    pub units: Option<String>,
+}
+
+impl PointChannel {
+    /// Whether the point is an output of a biomechanical model rather than a marker.
+    pub fn is_model_output(&self) -> bool {
+        self.kind != PointKind::Marker
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LengthUnit {
+    Millimeter,
+    Centimeter,
+    Meter,
+}
+
+impl LengthUnit {
+    /// Parses a unit as stored in POINT:UNITS, e.g. "mm".
+    pub fn from_symbol(symbol: &str) -> Option<LengthUnit> {
+        match symbol.trim().to_lowercase().as_str() {
+            "mm" => Some(LengthUnit::Millimeter),
+            "cm" => Some(LengthUnit::Centimeter),
+            "m" => Some(LengthUnit::Meter),
+            _ => None,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            LengthUnit::Millimeter => "mm",
+            LengthUnit::Centimeter => "cm",
+            LengthUnit::Meter => "m",
+        }
+    }
+
+    fn meters(&self) -> f32 {
+        match self {
+            LengthUnit::Millimeter => 0.001,
+            LengthUnit::Centimeter => 0.01,
+            LengthUnit::Meter => 1.,
+        }
+    }
+
+    /// Factor to multiply a value in this unit by to express it in `target`.
+    pub fn factor_to(&self, target: LengthUnit) -> f32 {
+        self.meters() / target.meters()
+    }
+
+    /// The unit of moments with lever arms in this unit, e.g. Nmm for mm.
+    pub fn moment(&self) -> MomentUnit {
+        match self {
+            LengthUnit::Millimeter => MomentUnit::NewtonMillimeter,
+            LengthUnit::Centimeter => MomentUnit::NewtonCentimeter,
+            LengthUnit::Meter => MomentUnit::NewtonMeter,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AngleUnit {
+    Degree,
+    Radian,
+}
+
+impl AngleUnit {
+    /// Parses a unit as stored in POINT:ANGLE_UNITS, e.g. "deg".
+    pub fn from_symbol(symbol: &str) -> Option<AngleUnit> {
+        match symbol.trim().to_lowercase().as_str() {
+            "deg" | "degrees" => Some(AngleUnit::Degree),
+            "rad" | "radians" => Some(AngleUnit::Radian),
+            _ => None,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            AngleUnit::Degree => "deg",
+            AngleUnit::Radian => "rad",
+        }
+    }
+
+    /// Factor to multiply a value in this unit by to express it in `target`.
+    pub fn factor_to(&self, target: AngleUnit) -> f32 {
+        match (self, target) {
+            (AngleUnit::Degree, AngleUnit::Radian) => 1f32.to_radians(),
+            (AngleUnit::Radian, AngleUnit::Degree) => 1f32.to_degrees(),
+            _ => 1.,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MomentUnit {
+    NewtonMillimeter,
+    NewtonCentimeter,
+    NewtonMeter,
+}
+
+impl MomentUnit {
+    /// Parses a unit as stored in POINT:MOMENT_UNITS, e.g. "Nmm".
+    pub fn from_symbol(symbol: &str) -> Option<MomentUnit> {
+        let symbol = symbol.trim().to_lowercase().replace(['.', ' ', '*'], "");
+        match symbol.as_str() {
+            "nmm" => Some(MomentUnit::NewtonMillimeter),
+            "ncm" => Some(MomentUnit::NewtonCentimeter),
+            "nm" => Some(MomentUnit::NewtonMeter),
+            _ => None,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            MomentUnit::NewtonMillimeter => "Nmm",
+            MomentUnit::NewtonCentimeter => "Ncm",
+            MomentUnit::NewtonMeter => "Nm",
+        }
+    }
+
+    /// The unit of the lever arm, in which the newtons are multiplied.
+    pub fn length(&self) -> LengthUnit {
+        match self {
+            MomentUnit::NewtonMillimeter => LengthUnit::Millimeter,
+            MomentUnit::NewtonCentimeter => LengthUnit::Centimeter,
+            MomentUnit::NewtonMeter => LengthUnit::Meter,
+        }
+    }
+
+    /// Factor to multiply a value in this unit by to express it in `target`.
+    pub fn factor_to(&self, target: MomentUnit) -> f32 {
+        self.length().factor_to(target.length())
+    }
+}
+
+/// The units to convert each kind of point to.
+/// Forces and powers are always stored in N and W, so they are never converted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UnitSet {
+    pub length: LengthUnit,
+    pub angle: AngleUnit,
+    pub moment: MomentUnit,
+}
+
+impl Default for UnitSet {
+    /// The units most commonly found in C3D files: mm, deg and Nmm.
+    fn default() -> Self {
+        UnitSet {
+            length: LengthUnit::Millimeter,
+            angle: AngleUnit::Degree,
+            moment: MomentUnit::NewtonMillimeter,
+        }
+    }
+}
+
+impl UnitSet {
+    /// SI units: m, rad and Nm.
+    pub fn si() -> UnitSet {
+        UnitSet {
+            length: LengthUnit::Meter,
+            angle: AngleUnit::Radian,
+            moment: MomentUnit::NewtonMeter,
+        }
+    }
+}
@@ -0,0 +1,504 @@
+//! Checks the header and parameters of a C3D file against the C3D specification.
+use crate::parameters::{ParameterData, Parameters};
+use crate::points::ScreenAxis;
+use crate::processor::Processor;
+use crate::ParseWarning;
+use std::fmt;
+
+/// How serious a problem found by validation is.
+/// Errors make the file invalid, while warnings are quirks that most readers accept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// Where in the file a problem was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ValidationLocation {
+    /// A 16-bit word of the header, numbered from 1 as in the specification.
+    Header(usize),
+    /// The first block of the parameter section, which describes the section.
+    ParameterSection,
+    Group(String),
+    Parameter(String, String),
+    Data,
+}
+
+impl fmt::Display for ValidationLocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidationLocation::Header(word) => write!(f, "header word {}", word),
+            ValidationLocation::ParameterSection => write!(f, "parameter section"),
+            ValidationLocation::Group(group) => write!(f, "{}", group),
+            ValidationLocation::Parameter(group, parameter) => {
+                write!(f, "{}:{}", group, parameter)
+            }
+            ValidationLocation::Data => write!(f, "data section"),
+        }
+    }
+}
+
+/// A single problem found by validation.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    pub location: ValidationLocation,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:?} in {}: {}",
+            self.severity, self.location, self.message
+        )
+    }
+}
+
+/// The problems found by `C3d::validate` or `C3d::validate_bytes`.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValidationReport {
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Returns true if no errors were found. Warnings do not make a file invalid.
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Warning)
+    }
+
+    pub(crate) fn error(&mut self, location: ValidationLocation, message: String) {
+        self.issues.push(ValidationIssue {
+            severity: Severity::Error,
+            location,
+            message,
+        });
+    }
+
+    pub(crate) fn warning(&mut self, location: ValidationLocation, message: String) {
+        self.issues.push(ValidationIssue {
+            severity: Severity::Warning,
+            location,
+            message,
+        });
+    }
+
+    /// Reports the warnings collected while reading the parameters.
+    pub(crate) fn parse_warnings(&mut self, warnings: &[ParseWarning]) {
+        for warning in warnings {
+            match warning {
+                ParseWarning::UnknownProcessorByte(byte) => self.error(
+                    ValidationLocation::ParameterSection,
+                    format!("unknown processor type {}", byte),
+                ),
+                ParseWarning::DuplicateParameter(group, parameter) => self.warning(
+                    parameter_location(group, parameter),
+                    "parameter is defined more than once, the first definition is used".to_string(),
+                ),
+                ParseWarning::DuplicateGroup(group) => self.warning(
+                    ValidationLocation::Group(group.clone()),
+                    "group is defined more than once, its parameters are merged".to_string(),
+                ),
+                ParseWarning::InvalidDescription(name) => self.warning(
+                    match name.split_once(':') {
+                        Some((group, parameter)) => parameter_location(group, parameter),
+                        None => ValidationLocation::Group(name.clone()),
+                    },
+                    "description is not valid text".to_string(),
+                ),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn parameter_location(group: &str, parameter: &str) -> ValidationLocation {
+    ValidationLocation::Parameter(group.to_string(), parameter.to_string())
+}
+
+/// Returns the first value of a numeric parameter.
+/// Integers are read as unsigned, as counts above 32767 are stored as negative numbers.
+pub(crate) fn number(parameters: &Parameters, group: &str, parameter: &str) -> Option<f32> {
+    match &parameters.get(group, parameter)?.data {
+        ParameterData::Integer(values) => values.first().map(|&x| x as u16 as f32),
+        ParameterData::Float(values) => values.first().copied(),
+        ParameterData::Byte(values) => values.first().map(|&x| x as f32),
+        _ => None,
+    }
+}
+
+/// Returns the number of entries of a parameter holding one entry per point or channel.
+/// Strings count one entry per row, and other parameters their last dimension.
+/// Parameters with more than 255 entries continue in `LABELS2`, `LABELS3` and so on,
+/// which are counted too.
+fn entries(parameters: &Parameters, group: &str, parameter: &str) -> Option<usize> {
+    let mut total = 0;
+    for index in 1.. {
+        let name = match index {
+            1 => parameter.to_string(),
+            _ => format!("{}{}", parameter, index),
+        };
+        let parameter = match parameters.get(group, &name) {
+            Some(parameter) => parameter,
+            None if index == 1 => return None,
+            None => break,
+        };
+        let entries = match parameter.data {
+            ParameterData::Char(_) => parameter.dimensions.get(1).copied(),
+            _ => parameter.dimensions.last().copied(),
+        };
+        total += entries.unwrap_or(1) as usize;
+    }
+    Some(total)
+}
+
+/// Checks that the required parameters are present and that the header and
+/// the parameters describe the same file.
+/// The length of the data section is checked when it is given.
+pub(crate) fn validate(
+    header: &[u8; 512],
+    parameter_blocks: &[u8],
+    parameters: &Parameters,
+    processor: &Processor,
+    warnings: &[ParseWarning],
+    data_length: Option<usize>,
+) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    report.parse_warnings(warnings);
+    check_required(parameters, &mut report);
+    check_entries(parameters, &mut report);
+    check_header(header, parameters, processor, &mut report);
+    check_data_start(header, parameter_blocks, parameters, processor, &mut report);
+    check_force_platforms(parameters, &mut report);
+    check_screen_axes(parameters, &mut report);
+    if let Some(data_length) = data_length {
+        check_data_length(header, parameters, processor, data_length, &mut report);
+    }
+    report
+}
+
+fn check_required(parameters: &Parameters, report: &mut ValidationReport) {
+    let mut required = vec![
+        ("POINT", "USED"),
+        ("POINT", "SCALE"),
+        ("POINT", "RATE"),
+        ("POINT", "DATA_START"),
+        ("POINT", "FRAMES"),
+        ("ANALOG", "USED"),
+    ];
+    if number(parameters, "POINT", "USED").is_some_and(|used| used > 0.) {
+        required.push(("POINT", "LABELS"));
+    }
+    if number(parameters, "ANALOG", "USED").is_some_and(|used| used > 0.) {
+        required.extend([
+            ("ANALOG", "LABELS"),
+            ("ANALOG", "SCALE"),
+            ("ANALOG", "OFFSET"),
+            ("ANALOG", "GEN_SCALE"),
+            ("ANALOG", "RATE"),
+        ]);
+    }
+    if number(parameters, "FORCE_PLATFORM", "USED").is_some_and(|used| used > 0.) {
+        required.extend([
+            ("FORCE_PLATFORM", "TYPE"),
+            ("FORCE_PLATFORM", "CORNERS"),
+            ("FORCE_PLATFORM", "ORIGIN"),
+            ("FORCE_PLATFORM", "CHANNEL"),
+        ]);
+    }
+    for (group, parameter) in required {
+        if !parameters.contains(group, parameter) {
+            report.error(
+                parameter_location(group, parameter),
+                "required parameter is missing".to_string(),
+            );
+        }
+    }
+}
+
+/// Checks that the parameters with one entry per point or channel have enough entries.
+fn check_entries(parameters: &Parameters, report: &mut ValidationReport) {
+    let groups = [
+        (
+            "POINT",
+            vec![
+                ("LABELS", Severity::Error),
+                ("DESCRIPTIONS", Severity::Warning),
+            ],
+        ),
+        (
+            "ANALOG",
+            vec![
+                ("LABELS", Severity::Error),
+                ("SCALE", Severity::Error),
+                ("OFFSET", Severity::Error),
+                ("DESCRIPTIONS", Severity::Warning),
+                ("UNITS", Severity::Warning),
+            ],
+        ),
+    ];
+    for (group, checks) in groups {
+        let used = match number(parameters, group, "USED") {
+            Some(used) => used as usize,
+            None => continue,
+        };
+        for (parameter, severity) in checks {
+            let entries = match entries(parameters, group, parameter) {
+                Some(entries) => entries,
+                None => continue,
+            };
+            if used > 0 && entries < used {
+                let message = format!("{} entries for {} used", entries, used);
+                match severity {
+                    Severity::Error => report.error(parameter_location(group, parameter), message),
+                    Severity::Warning => {
+                        report.warning(parameter_location(group, parameter), message)
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn check_header(
+    header: &[u8; 512],
+    parameters: &Parameters,
+    processor: &Processor,
+    report: &mut ValidationReport,
+) {
+    if header[1] != 0x50 {
+        report.error(
+            ValidationLocation::Header(1),
+            format!("expected key 0x50, found {:#04x}", header[1]),
+        );
+    }
+    let header_points = processor.u16([header[2], header[3]]) as f32;
+    if let Some(used) = number(parameters, "POINT", "USED") {
+        if used != header_points {
+            report.error(
+                ValidationLocation::Header(2),
+                format!("{} points, but POINT:USED is {}", header_points, used),
+            );
+        }
+    }
+    let header_samples = processor.u16([header[4], header[5]]) as usize;
+    let samples_per_channel = processor.u16([header[18], header[19]]) as usize;
+    if let Some(used) = number(parameters, "ANALOG", "USED") {
+        if used as usize * samples_per_channel != header_samples {
+            report.error(
+                ValidationLocation::Header(3),
+                format!(
+                    "{} analog samples per frame, but ANALOG:USED is {} with {} samples per channel",
+                    header_samples, used, samples_per_channel
+                ),
+            );
+        }
+    }
+    let header_scale = processor.f32([header[12], header[13], header[14], header[15]]);
+    if header_scale == 0. {
+        report.error(
+            ValidationLocation::Header(7),
+            "scale factor is 0".to_string(),
+        );
+    }
+    if let Some(scale) = number(parameters, "POINT", "SCALE") {
+        // the sign of the scale factor gives the data format
+        if (scale < 0.) != (header_scale < 0.) {
+            report.error(
+                parameter_location("POINT", "SCALE"),
+                format!(
+                    "{} gives a different data format than the header scale factor {}",
+                    scale, header_scale
+                ),
+            );
+        } else if (scale - header_scale).abs() > f32::EPSILON * scale.abs().max(1.) {
+            report.warning(
+                parameter_location("POINT", "SCALE"),
+                format!(
+                    "{} differs from the header scale factor {}",
+                    scale, header_scale
+                ),
+            );
+        }
+    }
+    let header_rate = processor.f32([header[20], header[21], header[22], header[23]]);
+    if let Some(rate) = number(parameters, "POINT", "RATE") {
+        if rate != header_rate {
+            report.warning(
+                parameter_location("POINT", "RATE"),
+                format!(
+                    "{} differs from the header frame rate {}",
+                    rate, header_rate
+                ),
+            );
+        }
+    }
+    let first_frame = processor.u16([header[6], header[7]]) as i64;
+    let last_frame = processor.u16([header[8], header[9]]) as i64;
+    if let Some(frames) = number(parameters, "POINT", "FRAMES") {
+        // the header cannot hold more than 65535 frames
+        if last_frame != u16::MAX as i64
+            && frames as u16 != u16::MAX
+            && frames > 0.
+            && last_frame - first_frame + 1 != frames as i64
+        {
+            report.error(
+                ValidationLocation::Header(5),
+                format!(
+                    "frames {} to {}, but POINT:FRAMES is {}",
+                    first_frame, last_frame, frames
+                ),
+            );
+        }
+    }
+}
+
+fn check_data_start(
+    header: &[u8; 512],
+    parameter_blocks: &[u8],
+    parameters: &Parameters,
+    processor: &Processor,
+    report: &mut ValidationReport,
+) {
+    let data_start = processor.u16([header[16], header[17]]) as usize;
+    let parameter_start = header[0] as usize;
+    let block_count = parameter_blocks.get(2).copied().unwrap_or(0);
+    let parameter_end = parameter_start + block_count as usize;
+    if data_start < parameter_end {
+        report.error(
+            ValidationLocation::Header(9),
+            format!(
+                "data starts at block {}, inside the parameter section ending at block {}",
+                data_start,
+                parameter_end - 1
+            ),
+        );
+    } else if data_start > parameter_end && block_count < u8::MAX {
+        // a count of 255 is written for longer sections too, which end at the data
+        report.warning(
+            ValidationLocation::Header(9),
+            format!(
+                "data starts at block {}, after unused blocks following the parameter section",
+                data_start
+            ),
+        );
+    }
+    if let Some(parameter) = number(parameters, "POINT", "DATA_START") {
+        if parameter as usize != data_start {
+            report.error(
+                parameter_location("POINT", "DATA_START"),
+                format!(
+                    "{} differs from the header data start {}",
+                    parameter, data_start
+                ),
+            );
+        }
+    }
+}
+
+fn check_force_platforms(parameters: &Parameters, report: &mut ValidationReport) {
+    let channels = match parameters.get("FORCE_PLATFORM", "CHANNEL") {
+        Some(channels) => channels,
+        None => return,
+    };
+    let platforms = number(parameters, "FORCE_PLATFORM", "USED").unwrap_or(0.) as usize;
+    let channels_per_platform = channels.dimensions.first().copied().unwrap_or(1) as usize;
+    let used = number(parameters, "ANALOG", "USED").unwrap_or(0.) as i16;
+    if let ParameterData::Integer(channels) = &channels.data {
+        // the parameter may hold entries for more platforms than are used
+        for &channel in channels.iter().take(platforms * channels_per_platform) {
+            if channel < 0 || channel > used {
+                report.error(
+                    parameter_location("FORCE_PLATFORM", "CHANNEL"),
+                    format!(
+                        "channel {} is not one of the {} analog channels",
+                        channel, used
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// Checks that POINT:X_SCREEN and POINT:Y_SCREEN are lab axes along different lab axes,
+/// as viewers cannot orient the data otherwise.
+fn check_screen_axes(parameters: &Parameters, report: &mut ValidationReport) {
+    let mut axes = Vec::new();
+    for name in ["X_SCREEN", "Y_SCREEN"] {
+        let chars = match parameters.get_as::<[char; 2]>("POINT", name) {
+            Ok(chars) => chars,
+            Err(_) => continue,
+        };
+        match ScreenAxis::from_chars(chars) {
+            Some(axis) => axes.push(axis),
+            None => report.warning(
+                parameter_location("POINT", name),
+                format!(
+                    "{} is not one of the axes +X, -X, +Y, -Y, +Z and -Z",
+                    chars.iter().collect::<String>()
+                ),
+            ),
+        }
+    }
+    if let [x, y] = axes[..] {
+        if ScreenAxis::out_of_screen(x, y).is_none() {
+            report.warning(
+                parameter_location("POINT", "Y_SCREEN"),
+                format!(
+                    "{} is along the same lab axis as POINT:X_SCREEN, {}",
+                    y.to_chars().iter().collect::<String>(),
+                    x.to_chars().iter().collect::<String>()
+                ),
+            );
+        }
+    }
+}
+
+fn check_data_length(
+    header: &[u8; 512],
+    parameters: &Parameters,
+    processor: &Processor,
+    data_length: usize,
+    report: &mut ValidationReport,
+) {
+    let first_frame = processor.u16([header[6], header[7]]) as usize;
+    let last_frame = processor.u16([header[8], header[9]]) as usize;
+    let frames = match number(parameters, "POINT", "FRAMES") {
+        Some(frames) if frames as u16 != u16::MAX => frames as usize,
+        _ => (last_frame + 1).saturating_sub(first_frame),
+    };
+    let scale = processor.f32([header[12], header[13], header[14], header[15]]);
+    let (point_bytes, analog_bytes) = match scale < 0. {
+        true => (16, 4),
+        false => (8, 2),
+    };
+    let bytes_per_frame = point_bytes * processor.u16([header[2], header[3]]) as usize
+        + analog_bytes * processor.u16([header[4], header[5]]) as usize;
+    if data_length < frames * bytes_per_frame {
+        report.error(
+            ValidationLocation::Data,
+            format!(
+                "{} bytes for {} frames of {} bytes",
+                data_length, frames, bytes_per_frame
+            ),
+        );
+    }
+}
@@ -0,0 +1,135 @@
+//! Recognizes the vendor of the software that wrote a file, and reads the metadata
+//! some vendors store in groups of their own.
+use crate::parameters::{ParameterData, Parameters};
+use crate::C3dWriteError;
+use std::collections::BTreeMap;
+
+/// The vendor of the software that wrote a file, as returned by `Manufacturer::vendor`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Vendor {
+    /// Vicon Nexus, Workstation and other Vicon software.
+    Vicon,
+    /// Qualisys Track Manager.
+    Qualisys,
+    /// Cortex and EVa RealTime by Motion Analysis.
+    MotionAnalysis,
+    /// Codamotion software by Charnwood Dynamics.
+    Codamotion,
+    /// Xsens MVN.
+    Xsens,
+    /// Any other vendor, named by MANUFACTURER:COMPANY, or MANUFACTURER:SOFTWARE
+    /// if the company is not given.
+    Other(String),
+}
+
+/// Names found in MANUFACTURER:COMPANY or MANUFACTURER:SOFTWARE, in lower case.
+const VENDOR_NAMES: [(&str, Vendor); 11] = [
+    ("vicon", Vendor::Vicon),
+    ("nexus", Vendor::Vicon),
+    ("qualisys", Vendor::Qualisys),
+    ("qtm", Vendor::Qualisys),
+    ("motion analysis", Vendor::MotionAnalysis),
+    ("cortex", Vendor::MotionAnalysis),
+    ("eva realtime", Vendor::MotionAnalysis),
+    ("charnwood", Vendor::Codamotion),
+    ("codamotion", Vendor::Codamotion),
+    ("xsens", Vendor::Xsens),
+    ("mvn", Vendor::Xsens),
+];
+
+impl Vendor {
+    pub(crate) fn recognize(company: Option<&str>, software: Option<&str>) -> Option<Vendor> {
+        let company = company.map(str::trim).filter(|name| !name.is_empty());
+        let software = software.map(str::trim).filter(|name| !name.is_empty());
+        for name in [company, software].into_iter().flatten() {
+            let name = name.to_lowercase();
+            if let Some((_, vendor)) = VENDOR_NAMES
+                .iter()
+                .find(|(needle, _)| name.contains(needle))
+            {
+                return Some(vendor.clone());
+            }
+        }
+        company
+            .or(software)
+            .map(|name| Vendor::Other(name.to_string()))
+    }
+}
+
+/// The subject measurements that Vicon Nexus stores in the PROCESSING group, such as the
+/// body mass and leg lengths entered for the Plug-in Gait model, keyed by parameter name.
+/// Lengths are in millimeters and masses in kilograms.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Processing {
+    pub measurements: BTreeMap<String, f32>,
+}
+
+impl Processing {
+    /// Reads the numeric parameters of the PROCESSING group, leaving the others out.
+    pub(crate) fn from_parameters(parameters: &Parameters) -> Processing {
+        let mut measurements = BTreeMap::new();
+        for parameter in parameters.parameters("PROCESSING").unwrap_or_default() {
+            let value = match &parameter.data {
+                ParameterData::Float(values) => values.first().copied(),
+                ParameterData::Integer(values) => values.first().map(|&x| x as f32),
+                ParameterData::Byte(values) => values.first().map(|&x| x as f32),
+                ParameterData::Char(_) => None,
+            };
+            if let Some(value) = value {
+                measurements.insert(parameter.name.clone(), value);
+            }
+        }
+        Processing { measurements }
+    }
+
+    /// Writes the measurements to the PROCESSING group, keeping its other parameters.
+    pub(crate) fn write_parameters(
+        &self,
+        parameters: &mut Parameters,
+    ) -> Result<(), C3dWriteError> {
+        for (name, value) in &self.measurements {
+            parameters.set("PROCESSING", name, *value)?;
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.measurements.is_empty()
+    }
+
+    /// Returns a measurement by name, ignoring case.
+    /// Names Nexus prefixes with `$`, such as `$BODYMASS`, are found without the prefix too.
+    pub fn get(&self, name: &str) -> Option<f32> {
+        let name = name.to_ascii_uppercase();
+        self.measurements
+            .get(&name)
+            .or_else(|| self.measurements.get(&format!("${}", name)))
+            .copied()
+    }
+
+    pub fn set(&mut self, name: &str, value: f32) {
+        self.measurements.insert(name.to_ascii_uppercase(), value);
+    }
+
+    pub fn body_mass(&self) -> Option<f32> {
+        self.get("BODYMASS")
+    }
+
+    pub fn height(&self) -> Option<f32> {
+        self.get("HEIGHT")
+    }
+
+    pub fn left_leg_length(&self) -> Option<f32> {
+        self.get("LLEGLENGTH")
+    }
+
+    pub fn right_leg_length(&self) -> Option<f32> {
+        self.get("RLEGLENGTH")
+    }
+
+    pub fn inter_asis_distance(&self) -> Option<f32> {
+        self.get("INTERASISDISTANCE")
+    }
+}
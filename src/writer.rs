@@ -0,0 +1,316 @@
+//! Streams frames to a C3D file as they are recorded, without keeping the whole trial
+//! in memory.
+use crate::builder::{C3dBuilder, C3dBuilderError};
+use crate::c3d::C3d;
+use crate::data::MarkerPoint;
+use crate::C3dWriteError;
+use grid::Grid;
+#[cfg(feature = "fs")]
+use std::fs::{File, OpenOptions};
+#[cfg(feature = "fs")]
+use std::io::{BufReader, BufWriter};
+use std::io::{Read, Seek, SeekFrom, Write};
+#[cfg(feature = "fs")]
+use std::path::PathBuf;
+
+/// Writes a C3D file one frame at a time, such as a live capture stream.
+/// The header and parameter section are written up front from the layout of a
+/// `C3dBuilder`, with room for the frame counts of a trial of any length, and are
+/// rewritten with the final number of frames by `close`, which pads the file to a
+/// whole block.
+/// A writer that is dropped without being closed leaves a file declaring no frames.
+/// Frames can also be appended to a file written earlier with `append`.
+///
+/// # Examples
+/// ```
+/// use c3dio::prelude::*;
+/// use c3dio::{C3dBuilder, C3dWriter};
+/// use std::io::Cursor;
+///
+/// let layout = C3dBuilder::new()
+///     .point_labels(&["LASI", "RASI"])
+///     .frame_rate(100.)
+///     .analog_channels(&["Fz"], 10);
+/// let mut writer = C3dWriter::new(Cursor::new(Vec::new()), layout).unwrap();
+/// for frame in 0..50 {
+///     let x = frame as f32;
+///     writer
+///         .push_frame(&[[x, 0., 0.], [x + 100., 0., 0.]], &[1.; 10])
+///         .unwrap();
+/// }
+/// let bytes = writer.close().unwrap().into_inner();
+/// let c3d = C3d::from_bytes(&bytes).unwrap();
+/// assert_eq!(c3d.points.rows(), 50);
+/// ```
+#[derive(Debug)]
+pub struct C3dWriter<W: Write + Seek> {
+    writer: W,
+    /// The layout of the file, holding the samples of the frame being written.
+    c3d: C3d,
+    num_frames: usize,
+    /// The number of blocks reserved for the parameter section.
+    parameter_blocks: usize,
+    /// The position of the header in the writer.
+    start: u64,
+    /// Whether the parameter section has room for the frame range of a trial too long
+    /// for the header.
+    long_trials: bool,
+}
+
+#[cfg(feature = "fs")]
+impl C3dWriter<BufWriter<File>> {
+    /// Creates a file and writes the header and parameter section of the layout to it.
+    /// See `new`.
+    pub fn create(file_name: &str, layout: C3dBuilder) -> Result<Self, C3dWriteError> {
+        let file_name = PathBuf::from(file_name);
+        let file = File::create(&file_name)
+            .map_err(|e| C3dWriteError::WriteError(file_name.clone(), e))?;
+        C3dWriter::new(BufWriter::new(file), layout)
+    }
+
+    /// Opens a file written earlier to append frames to it. See `append`.
+    pub fn open(file_name: &str) -> Result<Self, C3dWriteError> {
+        let file_name = PathBuf::from(file_name);
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&file_name)
+            .map_err(|e| C3dWriteError::WriteError(file_name.clone(), e))?;
+        let c3d = C3d::header_and_parameters_from_reader(BufReader::new(&mut file))
+            .map_err(|e| C3dWriteError::AppendParseError(Box::new(e)))?;
+        C3dWriter::resume(BufWriter::new(file), c3d, 0)
+    }
+}
+
+impl<W: Read + Write + Seek> C3dWriter<W> {
+    /// Reopens a C3D file starting at the current position of the writer, to append
+    /// frames after the last frame it declares.
+    /// The frames already in the file are not rewritten: `close` only patches the header,
+    /// POINT:FRAMES and the TRIAL group. The padding of the last block, and frames stored
+    /// past the declared count, e.g. by a writer that was never closed, are overwritten.
+    ///
+    /// The parameter section is rewritten in the blocks it already takes up, so a file
+    /// whose parameters do not fit again, or that has no room for the TRIAL group once
+    /// the trial grows past 65535 frames, returns `C3dWriteError::CannotAppend`.
+    pub fn append(mut writer: W) -> Result<Self, C3dWriteError> {
+        let start = writer
+            .stream_position()
+            .map_err(C3dWriteError::WriteHeaderError)?;
+        let c3d = C3d::header_and_parameters_from_reader(&mut writer)
+            .map_err(|e| C3dWriteError::AppendParseError(Box::new(e)))?;
+        C3dWriter::resume(writer, c3d, start)
+    }
+}
+
+impl<W: Write + Seek> C3dWriter<W> {
+    /// Writes the header and parameter section of the layout at the current position of
+    /// the writer. Any frames already pushed to the layout are written first.
+    pub fn new(mut writer: W, layout: C3dBuilder) -> Result<Self, C3dWriteError> {
+        let mut c3d = layout.build().map_err(C3dWriteError::InvalidLayout)?;
+        let start = writer
+            .stream_position()
+            .map_err(C3dWriteError::WriteHeaderError)?;
+        // the parameter section is largest when the trial is too long for the header
+        // and its frame range is stored in the TRIAL group
+        let parameter_blocks = c3d
+            .write_header_and_parameters_for(u16::MAX as usize + 1, 0)?
+            .1
+            .len()
+            / 512;
+        let num_frames = c3d.stored_frame_count();
+        let processor = c3d.processor();
+        let frames: Vec<u8> = (0..num_frames)
+            .flat_map(|frame| {
+                let mut bytes = c3d.points.write_frame(frame, &processor);
+                bytes.extend(
+                    c3d.analog
+                        .write_frame(frame, &processor, &c3d.points.format),
+                );
+                bytes
+            })
+            .collect();
+        // a single frame is kept to encode the frames pushed later
+        c3d.points.points = Grid::new(1, c3d.points.labels.len());
+        c3d.analog.analog = Grid::new(
+            c3d.analog.samples_per_channel_per_frame as usize,
+            c3d.analog.labels.len(),
+        );
+        let mut stream = C3dWriter {
+            writer,
+            c3d,
+            num_frames,
+            parameter_blocks,
+            start,
+            long_trials: true,
+        };
+        stream.write_header_and_parameters()?;
+        stream
+            .writer
+            .write_all(&frames)
+            .map_err(C3dWriteError::WriteDataError)?;
+        Ok(stream)
+    }
+
+    /// Writes a frame with the position of every point stored in each frame, as given
+    /// by POINT:USED, and the analog samples recorded during the frame, ordered by sample
+    /// and then by channel, as for `C3dBuilder::push_frame`.
+    pub fn push_frame(&mut self, points: &[[f32; 3]], analog: &[f64]) -> Result<(), C3dWriteError> {
+        let points: Vec<MarkerPoint> = points
+            .iter()
+            .map(|point| MarkerPoint::new(point[0], point[1], point[2]))
+            .collect();
+        self.push_marker_frame(&points, analog)
+    }
+
+    /// Writes a frame like `push_frame`, including the residual and cameras of each point.
+    pub fn push_marker_frame(
+        &mut self,
+        points: &[MarkerPoint],
+        analog: &[f64],
+    ) -> Result<(), C3dWriteError> {
+        // an appended file can store fewer points than it has labels
+        let num_points = self.c3d.points.cols();
+        if points.len() != num_points {
+            return Err(C3dWriteError::InvalidLayout(
+                C3dBuilderError::PointCountMismatch(num_points, points.len()),
+            ));
+        }
+        let num_samples = self.c3d.analog.rows() * self.c3d.analog.cols();
+        if analog.len() != num_samples {
+            return Err(C3dWriteError::InvalidLayout(
+                C3dBuilderError::AnalogSampleCountMismatch(num_samples, analog.len()),
+            ));
+        }
+        let last_frame = self.c3d.points.first_frame as usize + self.num_frames;
+        if last_frame > u32::MAX as usize {
+            return Err(C3dWriteError::InvalidLayout(
+                C3dBuilderError::TooManyFrames(self.num_frames + 1),
+            ));
+        }
+        if !self.long_trials && self.c3d.points.actual_fields(self.num_frames + 1).is_some() {
+            return Err(C3dWriteError::CannotAppend(
+                "the parameter section has no room for the TRIAL group".to_string(),
+            ));
+        }
+        if let Some(frame) = self.c3d.points.frame_mut(0) {
+            frame.copy_from_slice(points);
+        }
+        if let Some(frame) = self.c3d.analog.frame_mut(0) {
+            frame.copy_from_slice(analog);
+        }
+        let processor = self.c3d.processor();
+        let mut bytes = self.c3d.points.write_frame(0, &processor);
+        bytes.extend(
+            self.c3d
+                .analog
+                .write_frame(0, &processor, &self.c3d.points.format),
+        );
+        self.writer
+            .write_all(&bytes)
+            .map_err(C3dWriteError::WriteDataError)?;
+        self.num_frames += 1;
+        Ok(())
+    }
+
+    /// Returns the number of frames written so far.
+    pub fn frame_count(&self) -> usize {
+        self.num_frames
+    }
+
+    /// Pads the last frame to a whole block, as `C3d::write_to` does, rewrites the header
+    /// and parameter section with the number of frames written, and returns the writer
+    /// positioned after the padding.
+    pub fn close(mut self) -> Result<W, C3dWriteError> {
+        let position = self
+            .writer
+            .stream_position()
+            .map_err(C3dWriteError::WriteDataError)?;
+        let padding = (position - self.start).next_multiple_of(512) - (position - self.start);
+        self.writer
+            .write_all(&vec![0; padding as usize])
+            .map_err(C3dWriteError::WriteDataError)?;
+        let end = position + padding;
+        self.writer
+            .seek(SeekFrom::Start(self.start))
+            .map_err(C3dWriteError::WriteHeaderError)?;
+        self.write_header_and_parameters()?;
+        self.writer
+            .seek(SeekFrom::Start(end))
+            .map_err(C3dWriteError::WriteDataError)?;
+        self.writer.flush().map_err(C3dWriteError::WriteDataError)?;
+        Ok(self.writer)
+    }
+
+    /// Positions the writer after the last frame of a parsed file, keeping a single frame
+    /// of its layout to encode the frames appended.
+    fn resume(mut writer: W, mut c3d: C3d, start: u64) -> Result<Self, C3dWriteError> {
+        if !c3d.rotations.is_unused() {
+            return Err(C3dWriteError::CannotAppend(
+                "the ROTATION samples are stored after the frames".to_string(),
+            ));
+        }
+        let (data_start_block, points_per_frame) = c3d.header_layout();
+        let bytes_per_frame = c3d.bytes_per_frame().ok_or_else(|| {
+            C3dWriteError::CannotAppend("the analog samples per frame are invalid".to_string())
+        })?;
+        let samples_per_channel = c3d.analog.samples_per_channel_per_frame as usize;
+        let channels = match samples_per_channel {
+            0 => 0,
+            _ => c3d.analog.samples_per_frame as usize / samples_per_channel,
+        };
+        c3d.points.points = Grid::new(1, points_per_frame);
+        c3d.analog.analog = Grid::new(samples_per_channel, channels);
+
+        // the data section stays where it is, after the blocks of the parameter section
+        let data_start = start + (data_start_block as u64 - 1) * 512;
+        let end = writer
+            .seek(SeekFrom::End(0))
+            .map_err(C3dWriteError::WriteDataError)?;
+        let num_frames = match bytes_per_frame {
+            0 => c3d.declared_frame_count(),
+            _ => c3d
+                .declared_frame_count()
+                .min((end.saturating_sub(data_start) / bytes_per_frame as u64) as usize),
+        };
+        let parameter_blocks = (data_start_block as usize).saturating_sub(2);
+        let fits = |num_frames| -> Result<bool, C3dWriteError> {
+            let (_, parameter_bytes) =
+                c3d.write_header_and_parameters_for(num_frames, parameter_blocks)?;
+            Ok(parameter_bytes.len() == parameter_blocks * 512)
+        };
+        if !fits(num_frames)? {
+            return Err(C3dWriteError::CannotAppend(
+                "the parameters do not fit in the parameter section".to_string(),
+            ));
+        }
+        let long_trials = fits(u16::MAX as usize + 1)?;
+        writer
+            .seek(SeekFrom::Start(
+                data_start + (num_frames * bytes_per_frame) as u64,
+            ))
+            .map_err(C3dWriteError::WriteDataError)?;
+        Ok(C3dWriter {
+            writer,
+            c3d,
+            num_frames,
+            parameter_blocks,
+            start,
+            long_trials,
+        })
+    }
+
+    fn write_header_and_parameters(&mut self) -> Result<(), C3dWriteError> {
+        let points = &mut self.c3d.points;
+        points.set_frame_range(points.first_frame, self.num_frames);
+        let (header_bytes, parameter_bytes) = self
+            .c3d
+            .write_header_and_parameters_for(self.num_frames, self.parameter_blocks)?;
+        self.writer
+            .write_all(&header_bytes)
+            .map_err(C3dWriteError::WriteHeaderError)?;
+        self.writer
+            .write_all(&parameter_bytes)
+            .map_err(C3dWriteError::WriteParametersError)?;
+        Ok(())
+    }
+}
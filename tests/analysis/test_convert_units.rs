@@ -0,0 +1,193 @@
+use c3dio::prelude::*;
+use c3dio::LengthUnit;
+use c3dio::{PointKind, RigidBody, UnitSet};
+
+const MIXED_FILE: &str = "tests/c3d_org_samples/sample_03/gait-pig.c3d";
+
+#[test]
+fn point_kinds() {
+    let c3d = C3d::load(MIXED_FILE).unwrap();
+    let kinds = c3d.point_kinds();
+    assert_eq!(kinds.len(), c3d.points.cols());
+    assert_eq!(kinds[0], PointKind::Marker);
+    let index = |label: &str| c3d.points.labels.iter().position(|x| x == label).unwrap();
+    assert_eq!(kinds[index("A22:LKneeAngles")], PointKind::Angle);
+    assert_eq!(kinds[index("A22:LKneePower")], PointKind::Power);
+    assert!(kinds.contains(&PointKind::Force));
+    assert!(kinds.contains(&PointKind::Moment));
+}
+
+#[test]
+fn mm_to_m_only_converts_markers() {
+    let original = C3d::load(MIXED_FILE).unwrap();
+    let mut c3d = C3d::load(MIXED_FILE).unwrap();
+    c3d.convert_units_by_kind(UnitSet {
+        length: LengthUnit::Meter,
+        ..UnitSet::default()
+    });
+    assert_eq!(c3d.points.units, ['m', ' ', ' ', ' ']);
+
+    let kinds = c3d.point_kinds();
+    for frame in 0..c3d.points.rows() {
+        for (i, kind) in kinds.iter().enumerate() {
+            let converted = c3d.points[frame][i];
+            let point = original.points[frame][i];
+            for j in 0..3 {
+                match kind {
+                    PointKind::Marker => assert_eq!(converted[j], point[j] * 0.001),
+                    _ => assert_eq!(converted[j], point[j]),
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn convert_units_by_kind_round_trip_of_an_integer_file() {
+    let original = C3d::load("tests/c3d_org_samples/sample_02/pc_int.c3d").unwrap();
+    let mut c3d = original.clone();
+    c3d.convert_units_by_kind(UnitSet {
+        length: LengthUnit::Meter,
+        ..UnitSet::default()
+    });
+    assert!(c3d.points.scale_factor < original.points.scale_factor);
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    let written = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(written.points.units, ['m', ' ', ' ', ' ']);
+    for (written, original) in written.points.iter().zip(original.points.iter()) {
+        if !original.is_valid() {
+            continue;
+        }
+        for axis in 0..3 {
+            let expected = original[axis] * 0.001;
+            assert!((written[axis] - expected).abs() <= c3d.points.scale_factor);
+        }
+    }
+}
+
+fn assert_platforms_consistent(file: &str) {
+    let original = C3d::load(file).unwrap();
+    let mut c3d = C3d::load(file).unwrap();
+    assert_eq!(c3d.convert_units(LengthUnit::Meter), Some(0.001));
+    assert!(!c3d.forces.is_empty());
+    for i in 0..c3d.forces.len() {
+        let before = original.force_platform_data(i).unwrap();
+        let after = c3d.force_platform_data(i).unwrap();
+        for sample in 0..before.force.len() {
+            if before.force[sample][2].abs() < 50. {
+                continue;
+            }
+            for axis in 0..3 {
+                let force = before.force[sample][axis];
+                assert!((after.force[sample][axis] - force).abs() <= 1e-3 * force.abs().max(1.));
+                let center_of_pressure = before.center_of_pressure[sample][axis] * 0.001;
+                assert!((after.center_of_pressure[sample][axis] - center_of_pressure).abs() < 1e-5);
+            }
+        }
+    }
+}
+
+#[test]
+fn convert_units_keeps_type_2_platforms_consistent() {
+    assert_platforms_consistent("tests/c3d_org_samples/sample_09/PlugInC3D.c3d");
+}
+
+#[test]
+fn convert_units_keeps_calibrated_platforms_consistent() {
+    assert_platforms_consistent("tests/data/short.c3d");
+}
+
+#[test]
+fn convert_units_converts_markers_and_moments() {
+    let original = C3d::load(MIXED_FILE).unwrap();
+    let mut c3d = C3d::load(MIXED_FILE).unwrap();
+    assert_eq!(c3d.convert_units(LengthUnit::Meter), Some(0.001));
+    assert_eq!(c3d.points.units, ['m', ' ', ' ', ' ']);
+    assert!(c3d.points.scale_factor < original.points.scale_factor);
+    let units: String = c3d
+        .parameters
+        .get("POINT", "MOMENT_UNITS")
+        .unwrap()
+        .try_into()
+        .unwrap();
+    assert_eq!(units.trim(), "Nm");
+
+    let kinds = c3d.point_kinds();
+    for frame in 0..c3d.points.rows() {
+        for (i, kind) in kinds.iter().enumerate() {
+            let converted = c3d.points[frame][i];
+            let point = original.points[frame][i];
+            for j in 0..3 {
+                match kind {
+                    PointKind::Marker | PointKind::Moment => {
+                        assert_eq!(converted[j], point[j] * 0.001)
+                    }
+                    _ => assert_eq!(converted[j], point[j]),
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn convert_units_round_trip() {
+    let mut c3d = C3d::load("tests/c3d_org_samples/sample_09/PlugInC3D.c3d").unwrap();
+    c3d.convert_units(LengthUnit::Meter).unwrap();
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    let written = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(written.points.units, ['m', ' ', ' ', ' ']);
+    assert_eq!(written.forces, c3d.forces);
+    assert_eq!(written.analog.scales, c3d.analog.scales);
+    let (x, y) = (written.points.flatten(), c3d.points.flatten());
+    for (written, converted) in x.iter().zip(y.iter()) {
+        if !converted.is_valid() {
+            continue;
+        }
+        for axis in 0..3 {
+            assert!((written[axis] - converted[axis]).abs() <= c3d.points.scale_factor);
+        }
+    }
+
+    assert_eq!(
+        c3d.convert_units(LengthUnit::Millimeter).map(|x| x.round()),
+        Some(1000.)
+    );
+    assert_eq!(c3d.points.units, ['m', 'm', ' ', ' ']);
+}
+
+#[test]
+fn convert_units_unknown_units() {
+    let mut c3d = C3d::load(MIXED_FILE).unwrap();
+    c3d.points.units = ['f', 't', ' ', ' '];
+    assert_eq!(c3d.convert_units(LengthUnit::Meter), None);
+    assert_eq!(c3d.points.units, ['f', 't', ' ', ' ']);
+}
+
+#[test]
+fn convert_units_scales_rigid_bodies() {
+    let mut c3d = C3d::load("tests/c3d_org_samples/sample_09/PlugInC3D.c3d").unwrap();
+    let body = RigidBody::from_frame(&c3d, "PELVIS", &["LASI", "RASI", "SACR"], 100).unwrap();
+    c3d.add_rigid_body_rotation(&body).unwrap();
+    let mut by_kind = c3d.clone();
+    let original = c3d.rotations.clone();
+    c3d.convert_units(LengthUnit::Meter).unwrap();
+    for frame in 0..c3d.rotations.rows() {
+        let (before, after) = (original[(frame, 0)], c3d.rotations[(frame, 0)]);
+        if !before.is_valid() {
+            continue;
+        }
+        assert_eq!(after.rotation_matrix(), before.rotation_matrix());
+        for axis in 0..3 {
+            let position = before.position()[axis] * 0.001;
+            assert!((after.position()[axis] - position).abs() < 1e-6);
+        }
+    }
+
+    by_kind.convert_units_by_kind(UnitSet {
+        length: LengthUnit::Meter,
+        ..UnitSet::default()
+    });
+    assert_eq!(by_kind.rotations, c3d.rotations);
+}
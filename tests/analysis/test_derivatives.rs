@@ -0,0 +1,79 @@
+use c3dio::prelude::*;
+use c3dio::{Butterworth, C3dBuilder};
+
+/// A marker moving along x with constant acceleration, x = t² m, at 100 Hz.
+fn accelerating() -> C3d {
+    let mut builder = C3dBuilder::new().point_labels(&["M1"]).frame_rate(100.);
+    for frame in 0..50 {
+        let t = frame as f32 / 100.;
+        builder = builder.push_frame(&[[t * t, 1., 2.]], &[]).unwrap();
+    }
+    builder.build().unwrap()
+}
+
+#[test]
+fn central_differences() {
+    let c3d = accelerating();
+    let derivatives = c3d.points.derivatives("M1", None).unwrap();
+    assert_eq!(derivatives.velocity.len(), 50);
+    for frame in 1..49 {
+        let t = frame as f32 / 100.;
+        let velocity = derivatives.velocity[frame].unwrap();
+        assert!((velocity[0] - 2. * t).abs() < 1e-3);
+        assert!(velocity[1].abs() < 1e-3 && velocity[2].abs() < 1e-3);
+        let acceleration = derivatives.acceleration[frame].unwrap();
+        assert!((acceleration[0] - 2.).abs() < 0.05);
+    }
+    // one-sided differences at the ends
+    assert!((derivatives.velocity[0].unwrap()[0] - 0.01).abs() < 1e-3);
+    assert_eq!(derivatives.acceleration[0], None);
+    assert_eq!(derivatives.acceleration[49], None);
+}
+
+#[test]
+fn invalid_samples() {
+    let mut c3d = accelerating();
+    for frame in 20..23 {
+        c3d.points[frame][0].residual = -1.;
+    }
+    c3d.points[40][0].residual = -1.;
+    let derivatives = c3d.points.derivatives("M1", None).unwrap();
+    for frame in [20, 21, 22, 40] {
+        assert_eq!(derivatives.velocity[frame], None);
+        assert_eq!(derivatives.acceleration[frame], None);
+    }
+    for frame in [19, 23, 39, 41] {
+        assert!(derivatives.velocity[frame].is_some());
+        assert_eq!(derivatives.acceleration[frame], None);
+    }
+    assert!(derivatives.acceleration[24].is_some());
+}
+
+#[test]
+fn smoothing() {
+    let c3d = C3d::load("tests/c3d_org_samples/sample_09/PlugInC3D.c3d").unwrap();
+    let raw = c3d.points.derivatives("LASI", None).unwrap();
+    let filter = Butterworth::low_pass(6., 2);
+    let smoothed = c3d.points.derivatives("LASI", Some(&filter)).unwrap();
+    assert_eq!(raw.velocity.len(), 281);
+    // the marker is missing from the first 11 frames
+    assert_eq!(smoothed.velocity[5], None);
+    // smoothing reduces the noise that differentiating amplifies
+    let jerk = |accelerations: &[Option<[f32; 3]>]| {
+        accelerations[20..200]
+            .windows(2)
+            .map(|pair| (pair[1].unwrap()[2] - pair[0].unwrap()[2]).abs())
+            .sum::<f32>()
+    };
+    assert!(jerk(&smoothed.acceleration) < jerk(&raw.acceleration));
+
+    assert_eq!(
+        c3d.points.derivatives("NOPE", None),
+        Err(C3dFilterError::PointNotFound("NOPE".to_string()))
+    );
+    assert_eq!(
+        c3d.points
+            .derivatives("LASI", Some(&Butterworth::low_pass(40., 2))),
+        Err(C3dFilterError::InvalidCutoff(40., 30.))
+    );
+}
@@ -0,0 +1,104 @@
+use crate::common::plug_in_gait;
+use c3dio::emg::{linear_envelope, moving_rms, rectify, remove_offset};
+use c3dio::prelude::*;
+use c3dio::{EmgEnvelope, EmgProcessing};
+use std::f64::consts::PI;
+
+#[test]
+fn offset_and_rectification() {
+    assert_eq!(remove_offset(&[1., 2., 6.]), vec![-2., -1., 3.]);
+    assert_eq!(rectify(&[-2., -1., 3.]), vec![2., 1., 3.]);
+    assert!(remove_offset(&[]).is_empty());
+}
+
+#[test]
+fn rms() {
+    assert_eq!(moving_rms(&[2.; 10], 5), vec![2.; 10]);
+    assert_eq!(moving_rms(&[3., -4.], 1), vec![3., 4.]);
+    // the window is cut short at the ends
+    let rms = moving_rms(&[0., 0., 3., 4.], 2);
+    assert_eq!(rms[0], 0.);
+    assert_eq!(rms[3], 4.);
+    assert_eq!(rms[2], (12.5f64).sqrt());
+
+    let sine: Vec<f64> = (0..1000)
+        .map(|i| 2. * (2. * PI * 50. * i as f64 / 1000.).sin())
+        .collect();
+    let rms = moving_rms(&sine, 100);
+    for value in &rms[100..900] {
+        assert!((value - 2. / 2f64.sqrt()).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn envelope() {
+    // a 100 Hz carrier in bursts of 0.5 s
+    let signal: Vec<f64> = (0..2000)
+        .map(|i| {
+            let t = i as f64 / 1000.;
+            let burst = match (t * 2.) as usize % 2 {
+                0 => 1.,
+                _ => 0.,
+            };
+            burst * (2. * PI * 100. * t).sin()
+        })
+        .collect();
+    let envelope = linear_envelope(&signal, 1000., 6.).unwrap();
+    assert!(envelope[250] > 0.5);
+    assert!(envelope[750].abs() < 0.05);
+    assert_eq!(
+        linear_envelope(&signal, 10., 6.),
+        Err(C3dFilterError::InvalidCutoff(6., 5.))
+    );
+}
+
+#[test]
+fn emg_channel() {
+    let c3d = plug_in_gait();
+    let envelope = c3d.emg_envelope("EMG1", &EmgProcessing::default()).unwrap();
+    assert_eq!(envelope.len(), c3d.analog.rows());
+    assert!(envelope.iter().all(|x| x.is_finite()));
+
+    let rms = EmgProcessing::default()
+        .with_band_pass(None)
+        .with_envelope(EmgEnvelope::Rms(0.05));
+    let rms = c3d.emg_envelope("EMG1", &rms).unwrap();
+    assert!(rms.iter().all(|&x| x >= 0.));
+    let rectified = EmgProcessing::default().with_envelope(EmgEnvelope::Rectified);
+    let rectified = c3d.emg_envelope("EMG1", &rectified).unwrap();
+    assert!(rectified.iter().all(|&x| x >= 0.));
+
+    assert_eq!(
+        c3d.emg_envelope("EMG99", &EmgProcessing::default()),
+        Err(C3dFilterError::ChannelNotFound("EMG99".to_string()))
+    );
+}
+
+#[test]
+fn add_envelope_channel() {
+    let mut c3d = plug_in_gait();
+    let channels = c3d.analog.cols();
+    let processing = EmgProcessing::default();
+    let index = c3d
+        .add_emg_envelope("EMG1", "EMG1_ENV", &processing)
+        .unwrap();
+    assert_eq!(index, channels);
+    assert_eq!(c3d.analog.cols(), channels + 1);
+    assert_eq!(c3d.analog.labels[index], "EMG1_ENV");
+    assert_eq!(c3d.analog.units[index], "V");
+    assert_eq!(
+        c3d.analog.samples_per_frame,
+        (channels as u16 + 1) * c3d.analog.samples_per_channel_per_frame
+    );
+
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    let written = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(written.analog.labels[index], "EMG1_ENV");
+    let envelope = c3d.emg_envelope("EMG1", &processing).unwrap();
+    // the file stores integers, so the envelope is rounded to the scale of the channel
+    let step = (c3d.analog.scales[index] * c3d.analog.gen_scale).abs() as f64;
+    for (row, value) in envelope.iter().enumerate() {
+        assert!((written.analog[row][index] - value).abs() <= step);
+    }
+}
@@ -0,0 +1,109 @@
+use crate::common::plug_in_gait;
+use c3dio::events::detect::{detect, Detection, Foot, GaitEvent};
+use c3dio::prelude::*;
+use c3dio::Event;
+
+/// Checks that each event of the file was detected within the given number of frames.
+fn assert_detected(c3d: &C3d, detected: &[GaitEvent], events: &[&Event], frames: f32) {
+    for event in events {
+        let found = detected.iter().any(|detected| {
+            detected.label == event.label
+                && detected.context == event.context
+                && (detected.time - event.time).abs() * c3d.points.frame_rate <= frames
+        });
+        assert!(found, "{} {} {}", event.label, event.context, event.time);
+    }
+}
+
+#[test]
+fn force_platforms() {
+    let c3d = plug_in_gait();
+    let detected = detect(
+        &c3d,
+        &Detection::ForcePlatforms(20.),
+        &[Foot::left(), Foot::right()],
+    )
+    .unwrap();
+    // one step on each of the two platforms
+    assert_eq!(detected.len(), 4);
+    let labels: Vec<(&str, &str)> = detected
+        .iter()
+        .map(|event| (event.label.as_str(), event.context.as_str()))
+        .collect();
+    assert_eq!(
+        labels,
+        vec![
+            ("Foot Strike", "Right"),
+            ("Foot Strike", "Left"),
+            ("Foot Off", "Right"),
+            ("Foot Off", "Left")
+        ]
+    );
+    // the strikes and offs of the steps on the platforms, as labelled in the file
+    let events = [0, 2, 3, 5].map(|i| c3d.events.event(i).unwrap());
+    assert_detected(&c3d, &detected, &events, 2.);
+}
+
+#[test]
+fn coordinates() {
+    let c3d = plug_in_gait();
+    let detection = Detection::Coordinates(vec!["SACR".to_string()]);
+    let detected = detect(&c3d, &detection, &[Foot::left(), Foot::right()]).unwrap();
+    let events: Vec<&Event> = c3d.events.iter().collect();
+    assert_detected(&c3d, &detected, &events, 3.);
+}
+
+#[test]
+fn foot_velocity() {
+    let c3d = plug_in_gait();
+    let detection = Detection::FootVelocity(500.);
+    let detected = detect(&c3d, &detection, &[Foot::left(), Foot::right()]).unwrap();
+    let events: Vec<&Event> = c3d.events.iter().collect();
+    assert_detected(&c3d, &detected, &events, 5.);
+}
+
+#[test]
+fn add_detected_events() {
+    let original = plug_in_gait();
+    let mut c3d = plug_in_gait();
+    while c3d.events.remove_event(0).is_some() {}
+    let detection = Detection::Coordinates(vec!["SACR".to_string()]);
+    let added = c3d
+        .detect_gait_events(&detection, &[Foot::left(), Foot::right()])
+        .unwrap();
+    assert_eq!(added, c3d.events.len());
+    assert!(c3d
+        .events
+        .windows(2)
+        .all(|pair| pair[0].time <= pair[1].time));
+    // the cycles start within a few frames of those of the events in the file
+    for context in ["Left", "Right"] {
+        let cycles = c3d.gait_cycles(context);
+        for cycle in original.gait_cycles(context) {
+            assert!(cycles
+                .iter()
+                .any(|x| x.start.abs_diff(cycle.start) <= 3 && x.end.abs_diff(cycle.end) <= 3));
+        }
+    }
+
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    let written = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(written.events.len(), added);
+}
+
+#[test]
+fn missing_markers() {
+    let mut c3d = plug_in_gait();
+    let feet = [Foot::new("Left", "LHEEL", "LTOE")];
+    assert_eq!(
+        detect(&c3d, &Detection::FootVelocity(500.), &feet),
+        Err(C3dFilterError::PointNotFound("LHEEL".to_string()))
+    );
+    let detection = Detection::Coordinates(vec!["PELVIS".to_string()]);
+    assert_eq!(
+        c3d.detect_gait_events(&detection, &[Foot::left()]),
+        Err(C3dFilterError::PointNotFound("PELVIS".to_string()))
+    );
+    assert_eq!(c3d.events.len(), 8);
+}
@@ -0,0 +1,40 @@
+use crate::common::plug_in_gait;
+
+#[test]
+fn events_in_range_includes_start_and_excludes_end() {
+    let c3d = plug_in_gait();
+    let start = c3d.events[1].time as f64;
+    let end = c3d.events[4].time as f64;
+    let events = c3d.events_in_range(start, end);
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[0], &c3d.events[1]);
+    assert_eq!(events[1], &c3d.events[2]);
+    assert_eq!(events[2], &c3d.events[3]);
+    assert!(events
+        .iter()
+        .all(|event| event.time as f64 >= start && (event.time as f64) < end));
+}
+
+#[test]
+fn events_in_range_empty() {
+    let c3d = plug_in_gait();
+    let time = c3d.events[0].time as f64;
+    assert!(c3d.events_in_range(time, time).is_empty());
+    assert!(c3d.events_in_range(100., 200.).is_empty());
+    assert_eq!(c3d.events_in_range(0., f64::INFINITY).len(), 8);
+}
+
+#[test]
+fn events_by_context() {
+    let c3d = plug_in_gait();
+    let contexts = c3d.events_by_context();
+    assert_eq!(contexts.len(), 2);
+    let left = &contexts["Left"];
+    let right = &contexts["Right"];
+    assert_eq!(left.len(), 4);
+    assert_eq!(right.len(), 4);
+    assert!(left.iter().all(|event| event.context.trim() == "Left"));
+    assert!(right.iter().all(|event| event.context.trim() == "Right"));
+    assert!(left.windows(2).all(|pair| pair[0].time < pair[1].time));
+    assert_eq!(right[0], &c3d.events[0]);
+}
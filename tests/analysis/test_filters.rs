@@ -0,0 +1,133 @@
+use c3dio::prelude::*;
+use c3dio::Butterworth;
+use std::f64::consts::PI;
+
+fn sine(frequency: f64, rate: f64, len: usize) -> Vec<f64> {
+    (0..len)
+        .map(|i| (2. * PI * frequency * i as f64 / rate).sin())
+        .collect()
+}
+
+fn max_difference(a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - b).abs())
+        .fold(0., f64::max)
+}
+
+#[test]
+fn low_pass() {
+    let slow = sine(1., 100., 500);
+    let fast = sine(30., 100., 500);
+    let signal: Vec<f64> = slow.iter().zip(&fast).map(|(a, b)| a + b).collect();
+    let filtered = Butterworth::low_pass(6., 4)
+        .filtfilt(&signal, 100.)
+        .unwrap();
+    // the filter does not shift the slow sine in time
+    assert!(max_difference(&filtered[50..450], &slow[50..450]) < 0.01);
+}
+
+#[test]
+fn high_pass() {
+    let fast = sine(30., 100., 500);
+    let signal: Vec<f64> = fast.iter().map(|x| x + 5.).collect();
+    let filtered = Butterworth::high_pass(10., 2)
+        .filtfilt(&signal, 100.)
+        .unwrap();
+    assert!(max_difference(&filtered[50..450], &fast[50..450]) < 0.05);
+}
+
+#[test]
+fn band_pass() {
+    let slow = sine(1., 1000., 2000);
+    let middle = sine(100., 1000., 2000);
+    let fast = sine(450., 1000., 2000);
+    let signal: Vec<f64> = (0..2000)
+        .map(|i| slow[i] + middle[i] + fast[i] + 2.)
+        .collect();
+    let filtered = Butterworth::band_pass(20., 300., 4)
+        .filtfilt(&signal, 1000.)
+        .unwrap();
+    assert!(max_difference(&filtered[200..1800], &middle[200..1800]) < 0.05);
+}
+
+#[test]
+fn constant_signal() {
+    let signal = vec![3.; 100];
+    let filtered = Butterworth::low_pass(6., 3)
+        .filtfilt(&signal, 100.)
+        .unwrap();
+    assert!(max_difference(&filtered, &signal) < 1e-9);
+    assert_eq!(
+        Butterworth::low_pass(6., 2).filtfilt(&[1.], 100.).unwrap(),
+        vec![1.]
+    );
+}
+
+#[test]
+fn invalid_filters() {
+    let signal = sine(1., 100., 100);
+    assert_eq!(
+        Butterworth::low_pass(6., 0).filtfilt(&signal, 100.),
+        Err(C3dFilterError::ZeroOrder)
+    );
+    assert_eq!(
+        Butterworth::low_pass(50., 2).filtfilt(&signal, 100.),
+        Err(C3dFilterError::InvalidCutoff(50., 50.))
+    );
+    assert_eq!(
+        Butterworth::high_pass(-1., 2).filtfilt(&signal, 100.),
+        Err(C3dFilterError::InvalidCutoff(-1., 50.))
+    );
+    assert_eq!(
+        Butterworth::band_pass(20., 10., 2).filtfilt(&signal, 100.),
+        Err(C3dFilterError::InvalidBand(20., 10.))
+    );
+}
+
+#[test]
+fn filter_points() {
+    let original = C3d::load("tests/c3d_org_samples/sample_09/PlugInC3D.c3d").unwrap();
+    let mut c3d = original.clone();
+    c3d.filter_points(&Butterworth::low_pass(6., 2)).unwrap();
+    let index = c3d.points.labels.iter().position(|x| x == "LASI").unwrap();
+    for frame in 0..c3d.points.rows() {
+        let (point, original) = (&c3d.points[frame][index], &original.points[frame][index]);
+        assert_eq!(point.residual, original.residual);
+        assert_eq!(point.cameras, original.cameras);
+        match original.is_valid() {
+            // the marker moves up to 15 mm per frame, and filtering moves it by a few mm
+            true => {
+                for axis in 0..3 {
+                    assert!((point.point[axis] - original.point[axis]).abs() < 10.);
+                }
+            }
+            false => assert_eq!(point.point, original.point),
+        }
+    }
+    assert_ne!(c3d.points, original.points);
+
+    let mut c3d = original.clone();
+    assert_eq!(
+        c3d.filter_points(&Butterworth::low_pass(30., 2)),
+        Err(C3dFilterError::InvalidCutoff(30., 30.))
+    );
+    assert_eq!(c3d.points, original.points);
+}
+
+#[test]
+fn filter_analog() {
+    let mut c3d = C3d::load("tests/c3d_org_samples/sample_09/PlugInC3D.c3d").unwrap();
+    c3d.filter_analog(&Butterworth::high_pass(10., 2)).unwrap();
+    // the high-pass filter removes the offset of every channel
+    for channel in 0..c3d.analog.cols() {
+        let samples: Vec<f64> = c3d.analog.iter_col(channel).cloned().collect();
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let peak = samples.iter().fold(0., |peak: f64, x| peak.max(x.abs()));
+        assert!(mean.abs() <= 0.01 * peak.max(1e-9));
+    }
+
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    assert!(C3d::from_bytes(&bytes).is_ok());
+}
@@ -0,0 +1,174 @@
+use c3dio::prelude::*;
+use c3dio::ForceFrame;
+use grid::Grid;
+
+fn magnitude(vector: [f32; 3]) -> f32 {
+    vector.iter().map(|x| x * x).sum::<f32>().sqrt()
+}
+
+/// A 400 by 600 mm type 2 platform whose axes are the lab axes rotated by 180 degrees about Y.
+fn synthetic_platform(channels: &[f64]) -> (ForcePlatform, Analog) {
+    let mut platform = ForcePlatform {
+        plate_type: ForcePlatformType::Type2,
+        channels: [1, 2, 3, 4, 5, 6, 0, 0],
+        ..Default::default()
+    };
+    *platform.corners = [
+        [1000., 600., 0.],
+        [1400., 600., 0.],
+        [1400., 0., 0.],
+        [1000., 0., 0.],
+    ];
+    *platform.origin = [0., 0., 40.];
+    let mut analog = Analog::default();
+    analog.analog = Grid::from_vec(channels.to_vec(), 6);
+    (platform, analog)
+}
+
+#[test]
+fn vertical_force_at_known_point() {
+    // 500 N pushing down into the plate at (10, 20) mm from the surface center, in plate coordinates
+    let fz = 500.;
+    let (x, y) = (10., 20.);
+    let (platform, analog) = synthetic_platform(&[0., 0., fz, y * fz, -x * fz, 0.]);
+    assert_eq!(platform.center(), [1200., 300., 0.]);
+    let axes = platform.axes().unwrap();
+    assert_eq!(axes[0], [-1., 0., 0.]);
+    assert_eq!(axes[2], [0., 0., -1.]);
+
+    let data = platform.data(&analog).unwrap();
+    assert_eq!(data.force, vec![[0., 0., -500.]]);
+    let center_of_pressure = data.center_of_pressure[0];
+    assert!((center_of_pressure[0] - 1190.).abs() < 1e-3);
+    assert!((center_of_pressure[1] - 320.).abs() < 1e-3);
+    assert!(center_of_pressure[2].abs() < 1e-3);
+    assert!(magnitude(data.free_moment[0]) < 1e-3);
+}
+
+#[test]
+fn shear_force_is_transferred_to_the_surface() {
+    // a shear force along X applied at the surface center creates a moment about
+    // the transducer origin 40 mm below the surface
+    let (fx, fz) = (100., 500.);
+    let (platform, analog) = synthetic_platform(&[fx, 0., fz, 0., -40. * fx, 30.]);
+    let data = platform.data(&analog).unwrap();
+    let center_of_pressure = data.center_of_pressure[0];
+    assert!((center_of_pressure[0] - 1200.).abs() < 1e-3);
+    assert!((center_of_pressure[1] - 300.).abs() < 1e-3);
+    assert!((data.free_moment[0][2] + 30.).abs() < 1e-3);
+}
+
+#[test]
+fn type_1_center_of_pressure_is_read_from_the_channels() {
+    let mut platform = ForcePlatform {
+        plate_type: ForcePlatformType::Type1,
+        ..Default::default()
+    };
+    *platform.origin = [0., 0., 40.];
+    let values = [10., 0., 500., 15., -25., 7., 0., 0.];
+    let center_of_pressure = platform.center_of_pressure(values).unwrap();
+    assert!((center_of_pressure[0] - 15.).abs() < 1e-3);
+    assert!((center_of_pressure[1] + 25.).abs() < 1e-3);
+    let (force, _) = platform.force_and_moment(values);
+    assert_eq!(force, [10., 0., 500.]);
+}
+
+#[test]
+fn type_3_channels_are_combined() {
+    // sensors 120 mm from the center along X and 200 mm along Y, 40 mm below the surface
+    let mut platform = ForcePlatform {
+        plate_type: ForcePlatformType::Type3,
+        ..Default::default()
+    };
+    *platform.origin = [120., 200., -40.];
+    // 400 N shared by the four vertical sensors, with more load on sensors 2 and 3 on the -X side
+    let values = [0., 0., 0., 0., 80., 120., 120., 80.];
+    let (force, _) = platform.force_and_moment(values);
+    assert_eq!(force, [0., 0., 400.]);
+    let center_of_pressure = platform.center_of_pressure(values).unwrap();
+    assert!((center_of_pressure[0] + 24.).abs() < 1e-3);
+    assert!(center_of_pressure[1].abs() < 1e-3);
+    assert!(platform.center_of_pressure([0.; 8]).is_none());
+}
+
+#[test]
+fn plate_and_lab_frames() {
+    // 500 N at (10, 20) mm with a 30 N shear along X, whose moment about the
+    // transducer origin 40 mm below the surface is taken out of MY
+    let (fz, x, y) = (500., 10., 20.);
+    let (platform, analog) = synthetic_platform(&[30., 0., fz, y * fz, -x * fz - 40. * 30., 0.]);
+    let plate = platform.data_in(&analog, ForceFrame::Plate).unwrap();
+    assert_eq!(plate.frame, ForceFrame::Plate);
+    assert_eq!(plate.force, vec![[30., 0., 500.]]);
+    let center_of_pressure = plate.center_of_pressure[0];
+    assert!((center_of_pressure[0] - 10.).abs() < 1e-3);
+    assert!((center_of_pressure[1] - 20.).abs() < 1e-3);
+
+    // the platform is upside down relative to the lab, so X and Z change sign
+    let lab = platform.data(&analog).unwrap();
+    assert_eq!(lab.frame, ForceFrame::Lab);
+    assert_eq!(lab.force, vec![[-30., 0., -500.]]);
+    assert_eq!(
+        plate.expressed_in(&platform, ForceFrame::Lab),
+        Some(lab.clone())
+    );
+    let back = lab.expressed_in(&platform, ForceFrame::Plate).unwrap();
+    for (a, b) in back.center_of_pressure[0]
+        .iter()
+        .zip(plate.center_of_pressure[0])
+    {
+        assert!((a - b).abs() < 1e-3);
+    }
+    assert_eq!(back.force, plate.force);
+
+    // plate coordinates do not need the corners
+    let mut unplaced = platform.clone();
+    *unplaced.corners = [[0.; 3]; 4];
+    assert!(unplaced.data(&analog).is_none());
+    assert_eq!(unplaced.data_in(&analog, ForceFrame::Plate), Some(plate));
+}
+
+#[test]
+fn missing_channels() {
+    let (mut platform, analog) = synthetic_platform(&[0.; 6]);
+    platform.channels[5] = 7;
+    assert!(platform.data(&analog).is_none());
+}
+
+#[test]
+fn calibrated_platforms() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    assert!(!c3d.forces.is_empty());
+    for (i, platform) in c3d.forces.iter().enumerate() {
+        assert_eq!(platform.plate_type, ForcePlatformType::Type4);
+        assert!(platform.cal_matrix.is_some());
+        let data = c3d.force_platform_data(i).unwrap();
+        assert_eq!(data.force.len(), c3d.analog.rows());
+        let corners = *platform.corners;
+        let min_x = corners.iter().map(|c| c[0]).fold(f32::MAX, f32::min);
+        let max_x = corners.iter().map(|c| c[0]).fold(f32::MIN, f32::max);
+        let min_y = corners.iter().map(|c| c[1]).fold(f32::MAX, f32::min);
+        let max_y = corners.iter().map(|c| c[1]).fold(f32::MIN, f32::max);
+        let mut stance = 0;
+        for ((force, center_of_pressure), free_moment) in data
+            .force
+            .iter()
+            .zip(&data.center_of_pressure)
+            .zip(&data.free_moment)
+        {
+            if force[2] > 200. {
+                stance += 1;
+                assert!(center_of_pressure[0] > min_x && center_of_pressure[0] < max_x);
+                assert!(center_of_pressure[1] > min_y && center_of_pressure[1] < max_y);
+                assert!(free_moment[0].abs() < 1e-3 && free_moment[1].abs() < 1e-3);
+            }
+        }
+        assert!(stance > 0);
+    }
+    assert!(c3d.force_platform_data(c3d.forces.len()).is_none());
+    let plate = c3d.force_platform_data_in(0, ForceFrame::Plate).unwrap();
+    assert_eq!(
+        plate.expressed_in(&c3d.forces[0], ForceFrame::Lab),
+        c3d.force_platform_data(0)
+    );
+}
@@ -0,0 +1,49 @@
+use crate::common::plug_in_gait;
+
+#[test]
+fn event_frame() {
+    let c3d = plug_in_gait();
+    // Foot Strike, Right at 67 / 60 s
+    assert_eq!(c3d.event_frame(&c3d.events[0]), Some(67));
+    let mut late = c3d.events[0].clone();
+    late.time = 100.;
+    assert_eq!(c3d.event_frame(&late), None);
+}
+
+#[test]
+fn gait_cycles() {
+    let c3d = plug_in_gait();
+    assert_eq!(c3d.gait_cycles("Right"), vec![67..139]);
+    assert_eq!(c3d.gait_cycles("Left"), vec![108..181]);
+    assert!(c3d.gait_cycles("General").is_empty());
+
+    let cycle = c3d.gait_cycles("Left").remove(0);
+    let rows = c3d.analog_rows(cycle.clone());
+    let samples = c3d.analog.samples_per_channel_per_frame as usize;
+    assert_eq!(rows, cycle.start * samples..cycle.end * samples);
+}
+
+#[test]
+fn frames_between_events() {
+    let c3d = plug_in_gait();
+    // stance of the left foot, from foot strike to foot off
+    assert_eq!(
+        c3d.frames_between_events("Foot Strike", "Foot Off", "Left"),
+        vec![108..148]
+    );
+    // the events are paired in order of time, not in the order they are stored
+    let mut reversed = c3d.clone();
+    reversed.events.reverse();
+    assert_eq!(
+        reversed.frames_between_events("Foot Off", "Foot Strike", " Left "),
+        vec![78..108, 148..181]
+    );
+}
+
+#[test]
+fn analog_rows_clamped() {
+    let c3d = plug_in_gait();
+    let end = c3d.analog.rows();
+    assert_eq!(c3d.analog_rows(0..10_000), 0..end);
+    assert_eq!(c3d.analog_rows(10_000..20_000), end..end);
+}
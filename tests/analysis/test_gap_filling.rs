@@ -0,0 +1,106 @@
+use crate::common::plug_in_gait;
+use c3dio::prelude::*;
+use c3dio::GapFill;
+
+fn index(c3d: &C3d, label: &str) -> usize {
+    c3d.points.labels.iter().position(|x| x == label).unwrap()
+}
+
+fn remove_frames(c3d: &mut C3d, label: &str, frames: std::ops::Range<usize>) {
+    let index = index(c3d, label);
+    for frame in frames {
+        c3d.points[frame][index].residual = -1.;
+    }
+}
+
+fn distance(a: &MarkerPoint, b: &MarkerPoint) -> f32 {
+    (0..3)
+        .map(|axis| (a.point[axis] - b.point[axis]).powi(2))
+        .sum::<f32>()
+        .sqrt()
+}
+
+#[test]
+fn spline() {
+    let original = plug_in_gait();
+    let mut c3d = plug_in_gait();
+    remove_frames(&mut c3d, "LASI", 100..106);
+    assert_eq!(c3d.gaps("LASI").unwrap(), vec![100..106]);
+    assert_eq!(c3d.fill_gaps("LASI", &GapFill::Spline, 10), Some(6));
+    assert_eq!(c3d.gaps("LASI"), Some(vec![]));
+
+    let index = index(&c3d, "LASI");
+    assert_eq!(c3d.points[99][index], original.points[99][index]);
+    assert_eq!(c3d.points[106][index], original.points[106][index]);
+    for frame in 100..106 {
+        let point = &c3d.points[frame][index];
+        assert_eq!(point.residual, 0.);
+        assert!(distance(point, &original.points[frame][index]) < 5.);
+    }
+}
+
+#[test]
+fn pattern() {
+    let original = plug_in_gait();
+    let mut c3d = plug_in_gait();
+    remove_frames(&mut c3d, "LASI", 100..120);
+    let donor = GapFill::Pattern("RASI".to_string());
+    assert_eq!(c3d.fill_gaps("LASI", &donor, 20), Some(20));
+
+    let index = index(&c3d, "LASI");
+    for frame in 100..120 {
+        let point = &c3d.points[frame][index];
+        assert!(point.is_valid());
+        assert!(distance(point, &original.points[frame][index]) < 10.);
+    }
+
+    // the donor is missing over part of the gap
+    let mut c3d = plug_in_gait();
+    remove_frames(&mut c3d, "LASI", 100..120);
+    remove_frames(&mut c3d, "RASI", 110..112);
+    assert_eq!(c3d.fill_gaps("LASI", &donor, 20), Some(0));
+    assert_eq!(c3d.gaps("LASI").unwrap(), vec![100..120]);
+}
+
+#[test]
+fn gaps_at_ends_and_long_gaps() {
+    let mut c3d = plug_in_gait();
+    // the marker is missing from the first 11 and last 63 frames of the trial
+    remove_frames(&mut c3d, "LASI", 50..53);
+    remove_frames(&mut c3d, "LASI", 100..130);
+    assert_eq!(c3d.gaps("LASI"), Some(vec![50..53, 100..130]));
+    assert_eq!(c3d.fill_gaps("LASI", &GapFill::Spline, 10), Some(3));
+    assert_eq!(c3d.gaps("LASI").unwrap(), vec![100..130]);
+
+    let index = index(&c3d, "LASI");
+    assert!(!c3d.points[5][index].is_valid());
+    assert!(!c3d.points[275][index].is_valid());
+}
+
+#[test]
+fn missing_labels() {
+    let mut c3d = plug_in_gait();
+    assert_eq!(c3d.gaps("NOPE"), None);
+    assert_eq!(c3d.fill_gaps("NOPE", &GapFill::Spline, 10), None);
+    let donor = GapFill::Pattern("NOPE".to_string());
+    assert_eq!(c3d.fill_gaps("LASI", &donor, 10), None);
+}
+
+#[test]
+fn write_filled() {
+    let mut c3d = plug_in_gait();
+    remove_frames(&mut c3d, "LASI", 100..106);
+    c3d.fill_gaps("LASI", &GapFill::Spline, 10);
+
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    let written = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(written.gaps("LASI"), Some(vec![]));
+    // the file stores integers, so the filled samples are rounded to the scale factor
+    let index = index(&c3d, "LASI");
+    for frame in 100..106 {
+        let point = &written.points[frame][index];
+        assert_eq!(point.residual, 0.);
+        assert!(distance(point, &c3d.points[frame][index]) < c3d.points.scale_factor);
+    }
+}
@@ -0,0 +1,54 @@
+use c3dio::prelude::*;
+use grid::Grid;
+
+fn synthetic_c3d() -> C3d {
+    // the distal segment is the proximal segment rotated 90 degrees about z
+    let frame = vec![
+        MarkerPoint::new(0., 0., 0.),
+        MarkerPoint::new(1., 0., 0.),
+        MarkerPoint::new(0., 1., 0.),
+        MarkerPoint::new(0., 0., 0.),
+        MarkerPoint::new(0., 1., 0.),
+        MarkerPoint::new(-1., 0., 0.),
+    ];
+    let mut data = frame.clone();
+    data.extend(frame);
+    let mut c3d = C3d::new();
+    c3d.points.points = Grid::from_vec(data, 6);
+    c3d.points.labels = ["P1", "P2", "P3", "D1", "D2", "D3"]
+        .iter()
+        .map(|x| x.to_string())
+        .collect();
+    c3d
+}
+
+#[test]
+fn right_angle() {
+    let c3d = synthetic_c3d();
+    let angles = c3d
+        .joint_angle(["P1", "P2", "P3"], ["D1", "D2", "D3"])
+        .unwrap();
+    assert_eq!(angles.len(), 2);
+    for angle in angles {
+        assert!((angle - 90.).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn invalid_marker_is_nan() {
+    let mut c3d = synthetic_c3d();
+    c3d.points[1][4].residual = -1.;
+    let angles = c3d
+        .joint_angle(["P1", "P2", "P3"], ["D1", "D2", "D3"])
+        .unwrap();
+    assert!((angles[0] - 90.).abs() < 1e-4);
+    assert!(angles[1].is_nan());
+}
+
+#[test]
+fn missing_label() {
+    let c3d = synthetic_c3d();
+    assert!(c3d
+        .joint_angle(["P1", "P2", "P3"], ["D1", "D2", "XX"])
+        .is_none());
+}
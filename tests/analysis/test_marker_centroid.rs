@@ -0,0 +1,44 @@
+use c3dio::prelude::*;
+use grid::Grid;
+
+fn synthetic_c3d() -> C3d {
+    let data = vec![
+        MarkerPoint::new(0., 0., 0.),
+        MarkerPoint::new(3., 0., 6.),
+        MarkerPoint::new(0., 3., 3.),
+        MarkerPoint::new(1., 1., 1.),
+        MarkerPoint::new(2., 4., 1.),
+        MarkerPoint::new(3., 1., 4.),
+    ];
+    let mut c3d = C3d::new();
+    c3d.points.points = Grid::from_vec(data, 3);
+    c3d.points.labels = ["A", "B", "C"].iter().map(|x| x.to_string()).collect();
+    c3d
+}
+
+#[test]
+fn centroid_is_average() {
+    let c3d = synthetic_c3d();
+    let centroids = c3d.marker_centroid(&["A", "B", "C"]);
+    assert_eq!(centroids, vec![[1., 1., 3.], [2., 2., 2.]]);
+}
+
+#[test]
+fn centroid_ignores_invalid_markers() {
+    let mut c3d = synthetic_c3d();
+    c3d.points[0][2].residual = -1.;
+    c3d.points[1][0].residual = -1.;
+    c3d.points[1][1].residual = -1.;
+    c3d.points[1][2].residual = -1.;
+    let centroids = c3d.marker_centroid(&["A", "B", "C"]);
+    assert_eq!(centroids[0], [1.5, 0., 3.]);
+    assert!(centroids[1].iter().all(|x| x.is_nan()));
+}
+
+#[test]
+fn bounds() {
+    let c3d = synthetic_c3d();
+    let bounds = c3d.marker_set_bounds(&["A", "B", "C"]);
+    assert_eq!(bounds[0], ([0., 0., 0.], [3., 3., 6.]));
+    assert_eq!(bounds[1], ([1., 1., 1.], [3., 4., 4.]));
+}
@@ -0,0 +1,67 @@
+use c3dio::prelude::*;
+use c3dio::PointKind;
+
+const PIG_FILE: &str = "tests/c3d_org_samples/sample_03/gait-pig.c3d";
+
+#[test]
+fn point_channels_follow_point_kinds() {
+    let c3d = C3d::load(PIG_FILE).unwrap();
+    let channels = c3d.point_channels();
+    let kinds = c3d.point_kinds();
+    assert_eq!(channels.len(), c3d.points.cols());
+    for (i, channel) in channels.iter().enumerate() {
+        assert_eq!(channel.index, i);
+        assert_eq!(channel.kind, kinds[i]);
+        assert_eq!(channel.label, c3d.points.labels[i]);
+    }
+}
+
+#[test]
+fn model_outputs_with_units() {
+    let c3d = C3d::load(PIG_FILE).unwrap();
+    let expected = [
+        (PointKind::Angle, "deg"),
+        (PointKind::Force, "N"),
+        (PointKind::Moment, "Nmm"),
+        (PointKind::Power, "W"),
+    ];
+    for (kind, units) in expected {
+        let channels = c3d.points_of_kind(kind);
+        assert!(!channels.is_empty());
+        for channel in channels {
+            assert_eq!(channel.kind, kind);
+            assert_eq!(channel.units.as_deref(), Some(units));
+        }
+    }
+    let angles = c3d.points_of_kind(PointKind::Angle);
+    assert!(angles.iter().any(|x| x.label == "A22:LKneeAngles"));
+
+    let markers = c3d.points_of_kind(PointKind::Marker);
+    assert!(markers
+        .iter()
+        .all(|x| x.units.as_deref() == Some("mm") && !x.is_model_output()));
+    let outputs = c3d.model_outputs();
+    assert_eq!(outputs.len() + markers.len(), c3d.points.cols());
+    assert!(outputs.iter().all(|x| x.kind != PointKind::Marker));
+}
+
+#[test]
+fn point_channels_preserved_on_write() {
+    let c3d = C3d::load(PIG_FILE).unwrap();
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    let written = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(written.point_channels(), c3d.point_channels());
+    let cropped = c3d.crop(0..10);
+    assert_eq!(cropped.point_channels(), c3d.point_channels());
+}
+
+#[test]
+fn markers_only() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    assert!(c3d.model_outputs().is_empty());
+    assert_eq!(
+        c3d.points_of_kind(PointKind::Marker).len(),
+        c3d.points.cols()
+    );
+}
@@ -0,0 +1,108 @@
+use c3dio::prelude::*;
+use c3dio::quality::ForcePlatformQuality;
+use c3dio::C3dBuilder;
+
+/// Twenty frames of two markers and a type 2 platform, unloaded with some noise in the
+/// first ten frames and loaded in the others, with one vertical force sample clipped.
+fn trial() -> C3d {
+    let platform = ForcePlatform::new(
+        ForcePlatformType::Type2,
+        [
+            [400., 600., 0.],
+            [0., 600., 0.],
+            [0., 0., 0.],
+            [400., 0., 0.],
+        ],
+        [0., 0., -40.],
+        &[1, 2, 3, 4, 5, 6],
+    );
+    let mut builder = C3dBuilder::new()
+        .point_labels(&["M1", "M2"])
+        .frame_rate(100.)
+        .analog_channels(&["FX", "FY", "FZ", "MX", "MY", "MZ"], 1)
+        .force_platform(platform);
+    for frame in 0..20 {
+        let (fx, fz) = match frame {
+            0..=9 => (if frame % 2 == 0 { 1. } else { -1. }, 2.),
+            15 => (0., 32767.),
+            _ => (0., 600.),
+        };
+        builder = builder
+            .push_frame(&[[frame as f32; 3], [0.; 3]], &[fx, 0., fz, 0., 0., 0.])
+            .unwrap();
+    }
+    let mut c3d = builder.build().unwrap();
+    for frame in 0..20 {
+        c3d.points[frame][0].residual = 1.5;
+        c3d.points[frame][1].residual = -1.;
+    }
+    for frame in [3, 4, 8, 19] {
+        c3d.points[frame][0].residual = -1.;
+    }
+    c3d
+}
+
+#[test]
+fn marker_quality() {
+    let report = trial().quality_report();
+    assert_eq!(report.frames, 20);
+    let m1 = &report.markers[0];
+    assert_eq!(m1.label, "M1");
+    assert_eq!(m1.visible_frames, 16);
+    assert_eq!(m1.visible_percent, 80.);
+    // the missing last frame is not a gap
+    assert_eq!((m1.gaps, m1.longest_gap), (2, 2));
+    assert_eq!(m1.mean_residual, Some(1.5));
+    let m2 = &report.markers[1];
+    assert_eq!((m2.visible_frames, m2.visible_percent), (0, 0.));
+    assert_eq!((m2.gaps, m2.longest_gap, m2.mean_residual), (0, 0, None));
+}
+
+#[test]
+fn analog_clipping_and_baseline_noise() {
+    let report = trial().quality_report();
+    assert_eq!(report.analog.len(), 6);
+    assert_eq!(report.analog[2].label, "FZ");
+    assert_eq!(report.analog[2].clipped_samples, Some(1));
+    assert!(report.analog[2].is_clipped());
+    assert_eq!(report.analog[0].clipped_samples, Some(0));
+    assert!(!report.analog[0].is_clipped());
+
+    let platform = &report.force_platforms[0];
+    assert_eq!(platform.unloaded_samples, 10);
+    let baseline = platform.baseline.unwrap();
+    assert!(baseline[0].abs() < 1e-6 && (baseline[2].abs() - 2.).abs() < 1e-6);
+    let noise = platform.baseline_noise.unwrap();
+    assert!((noise[0] - 1.).abs() < 1e-6);
+    assert!(noise[1].abs() < 1e-6 && noise[2].abs() < 1e-6);
+
+    // without a known converter range, clipping is not measured
+    let mut c3d = trial();
+    c3d.analog.bits = 0;
+    assert_eq!(c3d.quality_report().analog[2].clipped_samples, None);
+    // a platform that is always loaded has no baseline
+    for sample in 0..10 {
+        c3d.analog[sample][2] = 600.;
+    }
+    let platform = &c3d.quality_report().force_platforms[0];
+    assert_eq!(*platform, ForcePlatformQuality::default());
+}
+
+#[test]
+fn quality_of_a_sample_file() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let report = c3d.quality_report();
+    assert_eq!(report.markers.len(), c3d.points.cols());
+    assert_eq!(report.analog.len(), c3d.analog.cols());
+    assert_eq!(report.force_platforms.len(), c3d.forces.len());
+    for (column, marker) in report.markers.iter().enumerate() {
+        let gaps = c3d.gaps(&c3d.points.labels[column]).unwrap();
+        assert_eq!(marker.gaps, gaps.len());
+        assert!(marker.visible_percent <= 100.);
+    }
+    // the platforms are unloaded before and after the foot strikes
+    assert!(report
+        .force_platforms
+        .iter()
+        .all(|platform| platform.unloaded_samples > 0));
+}
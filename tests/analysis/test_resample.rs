@@ -0,0 +1,107 @@
+use crate::common::plug_in_gait;
+use c3dio::prelude::*;
+use c3dio::{Interpolation, RigidBody};
+
+#[test]
+fn downsample() {
+    let c3d = plug_in_gait();
+    let resampled = c3d.resample(30., Interpolation::Linear);
+    assert_eq!(resampled.points.frame_rate, 30.);
+    assert_eq!(resampled.frame_count(), 141);
+    assert_eq!(resampled.declared_frame_count(), 141);
+    assert_eq!(resampled.points[1][0], c3d.points[2][0]);
+    assert_eq!(resampled.points[140][5], c3d.points[280][5]);
+    // the analog rate is a multiple of the new frame rate, so the samples are kept
+    assert_eq!(resampled.analog.rate, 1200.);
+    assert_eq!(resampled.analog.samples_per_channel_per_frame, 40);
+    assert_eq!(resampled.analog.rows(), 141 * 40);
+    assert_eq!(resampled.analog[5000], c3d.analog[5000]);
+    for (event, original) in resampled.events.iter().zip(c3d.events.iter()) {
+        assert_eq!(event.time, original.time);
+    }
+    assert_eq!(resampled.gait_cycles("Left"), vec![54..90]);
+
+    let mut bytes = Vec::new();
+    resampled
+        .write_to(&mut bytes, resampled.processor())
+        .unwrap();
+    assert!(C3d::validate_bytes(&bytes).unwrap().is_valid());
+    let written = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(written.points, resampled.points);
+    assert_eq!(written.analog, resampled.analog);
+}
+
+#[test]
+fn upsample() {
+    let c3d = plug_in_gait();
+    let linear = c3d.resample(120., Interpolation::Linear);
+    let cubic = c3d.resample(120., Interpolation::Cubic);
+    assert_eq!(linear.frame_count(), 561);
+    assert_eq!(cubic.frame_count(), 561);
+    let (before, after) = (c3d.points[100][0], c3d.points[101][0]);
+    assert!(before.is_valid() && after.is_valid());
+    for axis in 0..3 {
+        let midpoint = (before[axis] + after[axis]) / 2.;
+        assert!((linear.points[201][0][axis] - midpoint).abs() < 1e-3);
+        assert!((cubic.points[201][0][axis] - midpoint).abs() < 1.);
+    }
+    assert_eq!(cubic.points[200][0], before);
+    // 1200 Hz is a multiple of 120 Hz
+    assert_eq!(linear.analog.samples_per_channel_per_frame, 10);
+    assert_eq!(linear.analog.flatten()[..100], c3d.analog.flatten()[..100]);
+}
+
+#[test]
+fn resample_analog() {
+    let c3d = plug_in_gait();
+    // 1200 Hz is not a multiple of 70 Hz, so the analog data is resampled to 20 samples per frame
+    let resampled = c3d.resample(70., Interpolation::Linear);
+    assert_eq!(resampled.analog.samples_per_channel_per_frame, 20);
+    assert_eq!(resampled.analog.rate, 1400.);
+    assert_eq!(resampled.analog.rows(), resampled.frame_count() * 20);
+    assert_eq!(resampled.analog[0], c3d.analog[0]);
+}
+
+#[test]
+fn resample_invalid_rate() {
+    let c3d = plug_in_gait();
+    assert_eq!(c3d.resample(0., Interpolation::Linear), c3d);
+}
+
+#[test]
+fn resample_rotations() {
+    let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let labels: Vec<&str> = c3d.points.labels[..3].iter().map(|x| x.as_str()).collect();
+    let body = RigidBody::from_frame(&c3d, "BODY", &labels, 0).unwrap();
+    c3d.add_rigid_body_rotation(&body).unwrap();
+    let rate = c3d.points.frame_rate * 2.;
+    let resampled = c3d.resample(rate, Interpolation::Linear);
+    assert_eq!(resampled.rotations.rows(), resampled.stored_frame_count());
+    assert_eq!(resampled.rotations.rate, rate);
+    for frame in 0..c3d.stored_frame_count() - 1 {
+        let (before, after) = (c3d.rotations[(frame, 0)], c3d.rotations[(frame + 1, 0)]);
+        assert_eq!(resampled.rotations[(2 * frame, 0)], before);
+        let halfway = resampled.rotations[(2 * frame + 1, 0)];
+        if !(before.is_valid() && after.is_valid()) {
+            continue;
+        }
+        assert!(halfway.is_valid());
+        for axis in 0..3 {
+            let midpoint = (before.position()[axis] + after.position()[axis]) / 2.;
+            assert!((halfway.position()[axis] - midpoint).abs() < 1e-3);
+        }
+        // the rotation halfway is as far from the rotations on either side
+        let cos = |a: [f32; 4], b: [f32; 4]| a.iter().zip(b).map(|(a, b)| a * b).sum::<f32>();
+        let q = halfway.quaternion();
+        assert!(
+            (cos(q, before.quaternion()).abs() - cos(q, after.quaternion()).abs()).abs() < 1e-4
+        );
+    }
+
+    let mut bytes = Vec::new();
+    resampled
+        .write_to(&mut bytes, resampled.processor())
+        .unwrap();
+    let written = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(written.rotations, resampled.rotations);
+}
@@ -0,0 +1,197 @@
+use c3dio::prelude::*;
+use c3dio::RigidBody;
+use grid::Grid;
+
+const REFERENCE: [[f32; 3]; 4] = [
+    [100., 0., 0.],
+    [0., 50., 0.],
+    [-100., 0., 20.],
+    [0., -50., -20.],
+];
+
+fn quaternion_rotate(q: [f32; 4], p: [f32; 3]) -> [f32; 3] {
+    let [w, x, y, z] = q;
+    let u = [x, y, z];
+    let cross = |a: [f32; 3], b: [f32; 3]| {
+        [
+            a[1] * b[2] - a[2] * b[1],
+            a[2] * b[0] - a[0] * b[2],
+            a[0] * b[1] - a[1] * b[0],
+        ]
+    };
+    let t = cross(u, p).map(|x| 2. * x);
+    let c = cross(u, t);
+    [0, 1, 2].map(|i| p[i] + w * t[i] + c[i])
+}
+
+fn rotations() -> Vec<([f32; 4], [f32; 3])> {
+    let half = std::f32::consts::FRAC_PI_4;
+    let axis = [1. / 3f32.sqrt(); 3];
+    let angle = 1.2f32 / 2.;
+    vec![
+        ([1., 0., 0., 0.], [0., 0., 0.]),
+        ([half.cos(), 0., 0., half.sin()], [10., 20., 30.]),
+        (
+            [
+                angle.cos(),
+                axis[0] * angle.sin(),
+                axis[1] * angle.sin(),
+                axis[2] * angle.sin(),
+            ],
+            [-500., 250., 900.],
+        ),
+    ]
+}
+
+fn synthetic_c3d() -> C3d {
+    let mut data = Vec::new();
+    for (rotation, position) in rotations() {
+        for marker in REFERENCE {
+            let rotated = quaternion_rotate(rotation, marker);
+            data.push(MarkerPoint::new(
+                rotated[0] + position[0],
+                rotated[1] + position[1],
+                rotated[2] + position[2],
+            ));
+        }
+    }
+    let mut c3d = C3d::new();
+    c3d.points.points = Grid::from_vec(data, 4);
+    c3d.points.labels = ["M1", "M2", "M3", "M4"]
+        .iter()
+        .map(|x| x.to_string())
+        .collect();
+    c3d.points.descriptions = vec![String::new(); 4];
+    c3d
+}
+
+fn body() -> RigidBody {
+    RigidBody::new("BODY", &["M1", "M2", "M3", "M4"], &REFERENCE).unwrap()
+}
+
+fn assert_close(a: &[f32], b: &[f32], tolerance: f32) {
+    for (a, b) in a.iter().zip(b) {
+        assert!((a - b).abs() < tolerance, "{:?} != {:?}", a, b);
+    }
+}
+
+#[test]
+fn poses_match_motion() {
+    let c3d = synthetic_c3d();
+    let poses = c3d.rigid_body_poses(&body()).unwrap();
+    assert_eq!(poses.len(), 3);
+    for (pose, (rotation, position)) in poses.iter().zip(rotations()) {
+        let pose = pose.unwrap();
+        assert_close(&pose.rotation, &rotation, 1e-4);
+        assert_close(&pose.position, &position, 1e-2);
+        assert!(pose.residual < 1e-2);
+    }
+}
+
+#[test]
+fn pose_with_invalid_markers() {
+    let mut c3d = synthetic_c3d();
+    c3d.points[(1, 0)].residual = -1.;
+    c3d.points[(2, 0)].residual = -1.;
+    c3d.points[(2, 1)].residual = -1.;
+    let poses = c3d.rigid_body_poses(&body()).unwrap();
+    let (rotation, position) = rotations()[1];
+    let pose = poses[1].unwrap();
+    assert_close(&pose.rotation, &rotation, 1e-4);
+    assert_close(&pose.position, &position, 1e-2);
+    assert!(poses[2].is_none());
+}
+
+#[test]
+fn transform_places_reference() {
+    let c3d = synthetic_c3d();
+    let poses = c3d.rigid_body_poses(&body()).unwrap();
+    let pose = poses[2].unwrap();
+    for (marker, reference) in REFERENCE.iter().enumerate() {
+        assert_close(
+            &pose.transform(*reference),
+            &c3d.points[(2, marker)].point,
+            1e-2,
+        );
+    }
+}
+
+#[test]
+fn body_from_frame() {
+    let c3d = synthetic_c3d();
+    let body = RigidBody::from_frame(&c3d, "BODY", &["M1", "M2", "M3", "M4"], 1).unwrap();
+    let centroid: Vec<f32> = (0..3)
+        .map(|i| body.reference.iter().map(|x| x[i]).sum::<f32>())
+        .collect();
+    assert_close(&centroid, &[0.; 3], 1e-3);
+    let poses = c3d.rigid_body_poses(&body).unwrap();
+    // in the frame that defines the body, the body is not rotated
+    assert_close(&poses[1].unwrap().rotation, &[1., 0., 0., 0.], 1e-4);
+    assert!(RigidBody::from_frame(&c3d, "BODY", &["M1", "NOT A MARKER"], 1).is_none());
+}
+
+#[test]
+fn collinear_markers() {
+    let body = RigidBody::new(
+        "LINE",
+        &["A", "B", "C"],
+        &[[0., 0., 0.], [1., 0., 0.], [2., 0., 0.]],
+    )
+    .unwrap();
+    let markers: Vec<MarkerPoint> = body
+        .reference
+        .iter()
+        .map(|x| MarkerPoint::new(x[0], x[1], x[2]))
+        .collect();
+    assert!(body.fit(&markers).is_none());
+}
+
+#[test]
+fn missing_label() {
+    let c3d = synthetic_c3d();
+    let body = RigidBody::new("BODY", &["M1", "M2", "X"], &REFERENCE[..3]).unwrap();
+    assert!(c3d.rigid_body_poses(&body).is_none());
+    assert!(RigidBody::new("BODY", &["M1"], &REFERENCE).is_none());
+}
+
+#[test]
+fn rigid_body_points() {
+    let mut c3d = synthetic_c3d();
+    c3d.points[(2, 0)].residual = -1.;
+    c3d.points[(2, 1)].residual = -1.;
+    let origin = c3d.add_rigid_body_points(&body(), 10.).unwrap();
+    assert_eq!(origin, 4);
+    assert_eq!(c3d.points.cols(), 8);
+    assert_eq!(
+        c3d.points.labels[4..],
+        ["BODY_O", "BODY_X", "BODY_Y", "BODY_Z"]
+    );
+    assert!(c3d.is_dirty());
+    let (_, position) = rotations()[1];
+    assert_close(&c3d.points[(1, 4)].point, &position, 1e-2);
+    // rotated 90 degrees about z, the X axis points along Y
+    let x_axis = [position[0], position[1] + 10., position[2]];
+    assert_close(&c3d.points[(1, 5)].point, &x_axis, 1e-2);
+    assert!(!c3d.points[(2, 4)].is_valid());
+}
+
+#[test]
+fn write_rigid_body_points() {
+    let mut c3d = C3d::load("tests/c3d_org_samples/sample_03/gait-pig.c3d").unwrap();
+    let labels = ["A22:LASI", "A22:RASI", "A22:SACR"];
+    let pelvis = RigidBody::from_frame(&c3d, "PELVIS", &labels, 0).unwrap();
+    let origin = c3d.add_rigid_body_points(&pelvis, 100.).unwrap();
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    let written = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(written.points.cols(), origin + 4);
+    assert_eq!(written.points.labels[origin].trim(), "PELVIS_O");
+    assert_eq!(written.points.labels[origin + 3].trim(), "PELVIS_Z");
+    let poses = c3d.rigid_body_poses(&pelvis).unwrap();
+    let position = poses[0].unwrap().position;
+    // integer files store points in steps of the scale factor
+    let step = c3d.points.scale_factor.abs();
+    for (written, expected) in written.points[(0, origin)].point.iter().zip(position) {
+        assert!((written - expected).abs() <= step);
+    }
+}
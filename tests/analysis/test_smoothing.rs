@@ -0,0 +1,175 @@
+use c3dio::prelude::*;
+use c3dio::{C3dBuilder, Smoother};
+
+fn max_difference(a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - b).abs())
+        .fold(0., f64::max)
+}
+
+/// A marker moving along x with x = 1000 t³ mm, at 100 Hz.
+fn cubic() -> C3d {
+    let mut builder = C3dBuilder::new().point_labels(&["M1"]).frame_rate(100.);
+    for frame in 0..40 {
+        let t = frame as f32 / 100.;
+        builder = builder
+            .push_frame(&[[1000. * t * t * t, 1., 2.]], &[])
+            .unwrap();
+    }
+    builder.build().unwrap()
+}
+
+#[test]
+fn moving_average() {
+    let samples: Vec<f64> = (0..20).map(|i| i as f64).collect();
+    let smoothed = Smoother::moving_average(5).smooth(&samples).unwrap();
+    // a line is kept, with the window shrinking towards the ends
+    assert!(max_difference(&smoothed, &samples) < 1e-12);
+    let spike: Vec<f64> = (0..9).map(|i| if i == 4 { 5. } else { 0. }).collect();
+    let smoothed = Smoother::moving_average(5).smooth(&spike).unwrap();
+    assert_eq!(smoothed[4], 1.);
+    assert_eq!(smoothed[0], 0.);
+}
+
+#[test]
+fn savitzky_golay_keeps_polynomials() {
+    let samples: Vec<f64> = (0..30)
+        .map(|i| {
+            let x = i as f64;
+            0.5 * x * x * x - 2. * x * x + x - 7.
+        })
+        .collect();
+    let smoothed = Smoother::savitzky_golay(7, 3).smooth(&samples).unwrap();
+    // the ends are evaluated on the polynomials of the first and last windows
+    assert!(max_difference(&smoothed, &samples) < 1e-6);
+    // a window longer than the samples fits them all
+    let short = Smoother::savitzky_golay(51, 3)
+        .smooth(&samples[..6])
+        .unwrap();
+    assert!(max_difference(&short, &samples[..6]) < 1e-6);
+}
+
+#[test]
+fn savitzky_golay_derivatives() {
+    let rate = 100.;
+    let samples: Vec<f64> = (0..30)
+        .map(|i| {
+            let t = i as f64 / rate;
+            3. * t * t + t
+        })
+        .collect();
+    let smoother = Smoother::savitzky_golay(9, 2);
+    let velocity = smoother.derivative(&samples, 1, rate).unwrap();
+    let acceleration = smoother.derivative(&samples, 2, rate).unwrap();
+    for i in 0..30 {
+        let t = i as f64 / rate;
+        assert!((velocity[i] - (6. * t + 1.)).abs() < 1e-6);
+        assert!((acceleration[i] - 6.).abs() < 1e-6);
+    }
+    assert_eq!(
+        smoother.derivative(&samples, 3, rate),
+        Err(C3dFilterError::InvalidDerivative(3))
+    );
+    assert_eq!(
+        Smoother::moving_average(5).derivative(&samples, 1, rate),
+        Err(C3dFilterError::InvalidDerivative(1))
+    );
+    assert_eq!(
+        smoother.derivative(&samples[..2], 1, rate),
+        Err(C3dFilterError::TooFewSamples(2, 3))
+    );
+}
+
+#[test]
+fn invalid_windows() {
+    let samples = [1., 2., 3.];
+    for smoother in [
+        Smoother::moving_average(4),
+        Smoother::moving_average(0),
+        Smoother::savitzky_golay(5, 5),
+    ] {
+        let window = match smoother {
+            Smoother::MovingAverage { window } => window,
+            Smoother::SavitzkyGolay { window, .. } => window,
+        };
+        assert_eq!(
+            smoother.smooth(&samples),
+            Err(C3dFilterError::InvalidWindow(window))
+        );
+    }
+    let original = cubic();
+    let mut c3d = original.clone();
+    assert_eq!(
+        c3d.smooth_points(&Smoother::moving_average(2)),
+        Err(C3dFilterError::InvalidWindow(2))
+    );
+    assert_eq!(c3d.points, original.points);
+}
+
+#[test]
+fn smooth_points() {
+    let original = C3d::load("tests/c3d_org_samples/sample_09/PlugInC3D.c3d").unwrap();
+    let mut c3d = original.clone();
+    c3d.smooth_points(&Smoother::savitzky_golay(11, 3)).unwrap();
+    let index = c3d.points.labels.iter().position(|x| x == "LASI").unwrap();
+    for frame in 0..c3d.points.rows() {
+        let (point, original) = (&c3d.points[frame][index], &original.points[frame][index]);
+        assert_eq!(point.residual, original.residual);
+        assert_eq!(point.cameras, original.cameras);
+        match original.is_valid() {
+            true => {
+                for axis in 0..3 {
+                    assert!((point.point[axis] - original.point[axis]).abs() < 10.);
+                }
+            }
+            false => assert_eq!(point.point, original.point),
+        }
+    }
+    assert_ne!(c3d.points, original.points);
+}
+
+#[test]
+fn gaps_are_skipped() {
+    let mut c3d = cubic();
+    for frame in 15..18 {
+        c3d.points[frame][0].residual = -1.;
+        c3d.points[frame][0].point = [0.; 3];
+    }
+    let original = c3d.clone();
+    c3d.smooth_points(&Smoother::savitzky_golay(7, 3)).unwrap();
+    for frame in 0..40 {
+        let (point, original) = (&c3d.points[frame][0], &original.points[frame][0]);
+        assert_eq!(point.residual, original.residual);
+        // the samples next to the gap are not pulled towards its zeros
+        assert!((point.point[0] - original.point[0]).abs() < 1e-2);
+    }
+
+    let derivatives = c3d
+        .points
+        .smoothed_derivatives("M1", &Smoother::savitzky_golay(7, 3))
+        .unwrap();
+    for frame in 0..40 {
+        let t = frame as f32 / 100.;
+        match (15..18).contains(&frame) {
+            true => {
+                assert_eq!(derivatives.velocity[frame], None);
+                assert_eq!(derivatives.acceleration[frame], None);
+            }
+            false => {
+                let velocity = derivatives.velocity[frame].unwrap();
+                assert!((velocity[0] - 3000. * t * t).abs() < 0.5);
+                assert!(velocity[1].abs() < 1e-3);
+                let acceleration = derivatives.acceleration[frame].unwrap();
+                assert!((acceleration[0] - 6000. * t).abs() < 5.);
+            }
+        }
+    }
+    // no acceleration from a line
+    let derivatives = c3d
+        .points
+        .smoothed_derivatives("M1", &Smoother::savitzky_golay(7, 1))
+        .unwrap();
+    assert!(derivatives.acceleration.iter().all(Option::is_none));
+    assert!(derivatives.velocity[0].is_some());
+}
@@ -0,0 +1,57 @@
+use c3dio::prelude::*;
+
+const TWO_SUBJECTS: &str = "tests/c3d_org_samples/sample_37/ART-Human_2persons_integer.c3d";
+
+#[test]
+fn split_by_subject() {
+    let c3d = C3d::load(TWO_SUBJECTS).unwrap();
+    let split = c3d.split_by_subject();
+    let names: Vec<&str> = split.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, vec!["Human1", "Human2"]);
+
+    let mut num_points = 0;
+    for (name, subject) in &split {
+        let prefix = format!("{}:", name);
+        assert!(subject.points.cols() > 0);
+        assert_eq!(subject.points.labels.len(), subject.points.cols());
+        assert!(subject.points.labels.iter().all(|x| x.starts_with(&prefix)));
+        assert_eq!(subject.points.rows(), c3d.points.rows());
+
+        let names: Vec<String> = subject
+            .parameters
+            .get("SUBJECTS", "NAMES")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        assert_eq!(names, vec![name.clone()]);
+        let used: i16 = subject
+            .parameters
+            .get("SUBJECTS", "USED")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        assert_eq!(used, 1);
+
+        let label = &subject.points.labels[0];
+        let original = c3d.points.labels.iter().position(|x| x == label).unwrap();
+        for frame in 0..c3d.points.rows() {
+            assert_eq!(subject.points[frame][0], c3d.points[frame][original]);
+        }
+        num_points += subject.points.cols();
+    }
+    assert_eq!(num_points, c3d.points.cols());
+}
+
+#[test]
+fn split_by_label_prefix() {
+    let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    for (i, label) in c3d.points.labels.iter_mut().enumerate() {
+        *label = format!("Subject{}:{}", i % 2 + 1, label);
+    }
+    let split = c3d.split_by_subject();
+    assert_eq!(split.len(), 2);
+    assert_eq!(split[0].0, "Subject1");
+    assert_eq!(split[0].1.points.cols(), c3d.points.cols().div_ceil(2));
+    assert_eq!(split[1].0, "Subject2");
+    assert_eq!(split[1].1.points.cols(), c3d.points.cols() / 2);
+}
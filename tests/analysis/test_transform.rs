@@ -0,0 +1,215 @@
+use crate::common::plug_in_gait;
+use c3dio::prelude::*;
+use c3dio::{PointKind, RigidBody, ScreenAxis};
+
+/// From a lab with Z pointing up to one with Y pointing up: (x, y, z) -> (x, z, -y).
+const Z_UP_TO_Y_UP: [[f32; 3]; 3] = [[1., 0., 0.], [0., 0., 1.], [0., -1., 0.]];
+const Y_UP_TO_Z_UP: [[f32; 3]; 3] = [[1., 0., 0.], [0., 0., -1.], [0., 1., 0.]];
+
+fn index(c3d: &C3d, label: &str) -> usize {
+    c3d.points.labels.iter().position(|x| x == label).unwrap()
+}
+
+fn assert_close(a: [f32; 3], b: [f32; 3], tolerance: f32) {
+    for axis in 0..3 {
+        assert!((a[axis] - b[axis]).abs() <= tolerance, "{:?} {:?}", a, b);
+    }
+}
+
+#[test]
+fn transform_points() {
+    let original = plug_in_gait();
+    let mut c3d = plug_in_gait();
+    c3d.transform(Z_UP_TO_Y_UP, [100., 0., 0.]);
+    let kinds = c3d.point_kinds();
+    let (marker, angle, force) = (
+        index(&c3d, "LASI"),
+        index(&c3d, "LHipAngles"),
+        index(&c3d, "LGroundReactionForce"),
+    );
+    assert_eq!(kinds[marker], PointKind::Marker);
+    assert_eq!(kinds[angle], PointKind::Angle);
+    assert_eq!(kinds[force], PointKind::Force);
+    for frame in 0..c3d.points.rows() {
+        let before = original.points[frame][marker];
+        let after = c3d.points[frame][marker];
+        match before.is_valid() {
+            true => {
+                let [x, y, z] = before.point;
+                assert_close(after.point, [x + 100., z, -y], 1e-3);
+                assert_eq!(after.residual, before.residual);
+            }
+            false => assert_eq!(after, before),
+        }
+        assert_eq!(c3d.points[frame][angle], original.points[frame][angle]);
+        let [x, y, z] = original.points[frame][force].point;
+        if original.points[frame][force].is_valid() {
+            assert_close(c3d.points[frame][force].point, [x, z, -y], 1e-3);
+        }
+    }
+    assert_eq!(c3d.points.x_screen, Some(['+', 'X']));
+    assert_eq!(c3d.points.y_screen, Some(['+', 'Y']));
+
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    let written = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(written.points.y_screen, Some(['+', 'Y']));
+}
+
+#[test]
+fn transform_force_platforms() {
+    let original = plug_in_gait();
+    let mut c3d = plug_in_gait();
+    c3d.transform(Z_UP_TO_Y_UP, [100., 0., 0.]);
+    for platform in 0..c3d.forces.len() {
+        for corner in 0..4 {
+            let [x, y, z] = original.forces[platform].corners[corner];
+            assert_close(
+                c3d.forces[platform].corners[corner],
+                [x + 100., z, -y],
+                1e-3,
+            );
+        }
+        assert_eq!(
+            c3d.forces[platform].origin,
+            original.forces[platform].origin
+        );
+        let before = original.force_platform_data(platform).unwrap();
+        let after = c3d.force_platform_data(platform).unwrap();
+        for sample in (0..before.force.len()).step_by(97) {
+            let [x, y, z] = before.force[sample];
+            assert_close(after.force[sample], [x, z, -y], 1e-2);
+            let [x, y, z] = before.center_of_pressure[sample];
+            if x.is_finite() {
+                assert_close(after.center_of_pressure[sample], [x + 100., z, -y], 1e-2);
+            }
+        }
+    }
+}
+
+#[test]
+fn inverse_transform() {
+    let original = plug_in_gait();
+    let mut c3d = plug_in_gait();
+    c3d.transform(Z_UP_TO_Y_UP, [0., 0., 0.]);
+    c3d.transform(Y_UP_TO_Z_UP, [0., 0., 0.]);
+    let marker = index(&c3d, "RKNE");
+    for frame in 0..c3d.points.rows() {
+        assert_close(
+            c3d.points[frame][marker].point,
+            original.points[frame][marker].point,
+            1e-3,
+        );
+    }
+    assert_eq!(c3d.points.x_screen, original.points.x_screen);
+    assert_eq!(c3d.points.y_screen, original.points.y_screen);
+}
+
+#[test]
+fn z_screen() {
+    let mut c3d = plug_in_gait();
+    c3d.parameters.set("POINT", "Z_SCREEN", "+Y").unwrap();
+    c3d.transform(Z_UP_TO_Y_UP, [0., 0., 0.]);
+    assert_eq!(
+        c3d.parameters
+            .get_as::<[char; 2]>("POINT", "Z_SCREEN")
+            .unwrap(),
+        ['-', 'Z']
+    );
+}
+
+#[test]
+fn screen_axes() {
+    assert_eq!(ScreenAxis::from_chars(['+', 'X']), Some(ScreenAxis::PlusX));
+    assert_eq!(ScreenAxis::from_chars(['-', 'z']), Some(ScreenAxis::MinusZ));
+    assert_eq!(ScreenAxis::from_chars([' ', 'Y']), Some(ScreenAxis::PlusY));
+    assert_eq!(ScreenAxis::from_chars(['+', 'W']), None);
+    assert_eq!(ScreenAxis::MinusY.to_chars(), ['-', 'Y']);
+    assert_eq!(ScreenAxis::MinusY.direction(), [0., -1., 0.]);
+    assert_eq!(
+        ScreenAxis::out_of_screen(ScreenAxis::PlusX, ScreenAxis::PlusY),
+        Some(ScreenAxis::PlusZ)
+    );
+    assert_eq!(
+        ScreenAxis::out_of_screen(ScreenAxis::PlusX, ScreenAxis::PlusZ),
+        Some(ScreenAxis::MinusY)
+    );
+    assert_eq!(
+        ScreenAxis::out_of_screen(ScreenAxis::PlusX, ScreenAxis::MinusX),
+        None
+    );
+    assert_eq!(ScreenAxis::PlusZ.rotated(&Z_UP_TO_Y_UP), ScreenAxis::PlusY);
+
+    let c3d = plug_in_gait();
+    assert_eq!(c3d.points.x_screen_axis(), Some(ScreenAxis::PlusX));
+    assert_eq!(c3d.points.y_screen_axis(), Some(ScreenAxis::PlusZ));
+}
+
+#[test]
+fn set_screen_axes() {
+    let mut c3d = plug_in_gait();
+    c3d.parameters.set("POINT", "Z_SCREEN", "-Y").unwrap();
+    c3d.transform(Z_UP_TO_Y_UP, [0., 0., 0.]);
+    // the screen follows the data, with Y up after the rotation
+    assert_eq!(c3d.points.y_screen_axis(), Some(ScreenAxis::PlusY));
+    c3d.set_screen_axes(ScreenAxis::MinusZ, ScreenAxis::PlusY)
+        .unwrap();
+    assert_eq!(
+        c3d.parameters
+            .get_as::<[char; 2]>("POINT", "Z_SCREEN")
+            .unwrap(),
+        ['+', 'X']
+    );
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    let written = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(written.points.x_screen_axis(), Some(ScreenAxis::MinusZ));
+    assert_eq!(written.points.y_screen_axis(), Some(ScreenAxis::PlusY));
+
+    assert!(matches!(
+        c3d.set_screen_axes(ScreenAxis::PlusY, ScreenAxis::MinusY),
+        Err(C3dWriteError::InvalidScreenAxes(
+            ScreenAxis::PlusY,
+            ScreenAxis::MinusY
+        ))
+    ));
+    assert_eq!(c3d.points.x_screen, Some(['-', 'Z']));
+    // files without Z_SCREEN are not given one
+    let mut c3d = plug_in_gait();
+    c3d.set_screen_axes(ScreenAxis::PlusY, ScreenAxis::PlusZ)
+        .unwrap();
+    assert!(!c3d.parameters.contains("POINT", "Z_SCREEN"));
+}
+
+#[test]
+fn transform_rigid_bodies() {
+    let mut c3d = plug_in_gait();
+    let labels = ["LASI", "RASI", "SACR"];
+    let body = RigidBody::from_frame(&c3d, "PELVIS", &labels, 100).unwrap();
+    c3d.add_rigid_body_rotation(&body).unwrap();
+    let original = c3d.clone();
+    c3d.transform(Z_UP_TO_Y_UP, [100., 0., 0.]);
+    for frame in 0..c3d.rotations.rows() {
+        let (before, after) = (original.rotations[(frame, 0)], c3d.rotations[(frame, 0)]);
+        if !before.is_valid() {
+            assert_eq!(after, before);
+            continue;
+        }
+        let [x, y, z] = before.position();
+        assert_close(after.position(), [x + 100., z, -y], 1e-3);
+        let (rotation, transformed) = (before.rotation_matrix(), after.rotation_matrix());
+        for column in 0..3 {
+            let [x, y, z] = [0, 1, 2].map(|row| rotation[row][column]);
+            let column = [0, 1, 2].map(|row| transformed[row][column]);
+            assert_close(column, [x, z, -y], 1e-6);
+        }
+    }
+
+    c3d.transform(Y_UP_TO_Z_UP, [-100., 0., 0.]);
+    for frame in 0..c3d.rotations.rows() {
+        let (before, after) = (original.rotations[(frame, 0)], c3d.rotations[(frame, 0)]);
+        if before.is_valid() {
+            assert_close(after.position(), before.position(), 1e-3);
+        }
+    }
+}
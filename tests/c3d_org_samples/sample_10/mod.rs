@@ -8,14 +8,51 @@ use common::assert_read_write;
 fn compare_force_plate_types() {
     // Sample10: differentiate between TYPE-2 and TYPE-4 force data
     // as well as TYPE-3 data with different force plate types
-    let two = C3d::load("tests/c3d_org_samples/sample_10/TYPE-2.C3D").unwrap();
-    let _twoa = C3d::load("tests/c3d_org_samples/sample_10/TYPE-2a.c3d").unwrap();
+    let _two = C3d::load("tests/c3d_org_samples/sample_10/TYPE-2.C3D").unwrap();
+    let twoa = C3d::load("tests/c3d_org_samples/sample_10/TYPE-2a.c3d").unwrap();
     let _three = C3d::load("tests/c3d_org_samples/sample_10/TYPE-3.c3d").unwrap();
-    let four = C3d::load("tests/c3d_org_samples/sample_10/TYPE-4.C3D").unwrap();
-    let _foura = C3d::load("tests/c3d_org_samples/sample_10/TYPE-4a.c3d").unwrap();
+    let _four = C3d::load("tests/c3d_org_samples/sample_10/TYPE-4.C3D").unwrap();
+    let foura = C3d::load("tests/c3d_org_samples/sample_10/TYPE-4a.c3d").unwrap();
 
-    //TODO: show two and four have the same analog data after the calibration matrix is applied
-    //assert_eq!(two.force(0, 0).unwrap(), four.force(0, 0).unwrap());
+    // the diagonal of the calibration matrix is applied to the analog scales of the type 2
+    // file, so both files only differ by the cross-talk of the off-diagonal terms
+    assert_eq!(twoa.forces.len(), foura.forces.len());
+    let mut loaded_frames = 0;
+    for plate in 0..twoa.forces.len() {
+        for frame in 0..twoa.points.rows() {
+            let two_force = twoa.force(plate, frame).unwrap();
+            let four_force = foura.force(plate, frame).unwrap();
+            if two_force[2].abs() < 100. {
+                continue;
+            }
+            loaded_frames += 1;
+            assert!(((two_force[2] - four_force[2]) / two_force[2]).abs() < 0.01);
+            let two_center = twoa.center_of_pressure(plate, frame).unwrap();
+            let four_center = foura.center_of_pressure(plate, frame).unwrap();
+            assert!((two_center[0] - four_center[0]).abs() < 2.);
+            assert!((two_center[1] - four_center[1]).abs() < 2.);
+        }
+    }
+    assert!(loaded_frames > 0);
+}
+
+#[test]
+fn type_3_center_of_pressure() {
+    // the first platform is a 400 by 597 mm Kistler platform
+    let three = C3d::load("tests/c3d_org_samples/sample_10/TYPE-3.c3d").unwrap();
+    assert_eq!(three.forces[0].plate_type, ForcePlatformType::Type3);
+    let mut loaded_frames = 0;
+    for frame in 0..three.points.rows() {
+        let force = three.force(0, frame).unwrap();
+        if force[2].abs() < 100. {
+            continue;
+        }
+        loaded_frames += 1;
+        let center_of_pressure = three.center_of_pressure(0, frame).unwrap();
+        assert!(center_of_pressure[0].abs() < 200.);
+        assert!(center_of_pressure[1].abs() < 298.5);
+    }
+    assert!(loaded_frames > 0);
 }
 
 #[test]
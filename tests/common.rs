@@ -30,11 +30,29 @@ pub fn assert_eq_data(c3d1: &C3d, c3d2: &C3d) {
 
 #[allow(dead_code)]
 pub fn assert_read_write(path: &str) {
+    assert_read_write_with_options(path, ParseOptions::default());
+}
+
+#[allow(dead_code)]
+pub fn assert_read_write_with_options(path: &str, options: ParseOptions) {
     let temp_dir = TestFiles::new();
     temp_dir.file(path, " ");
     let temp_path = temp_dir.path().join(path).to_str().unwrap().to_string();
-    let c3d1 = C3d::load(path).unwrap();
+    let c3d1 = C3d::load_with_options(path, options).unwrap();
     let c3d1 = c3d1.write(&temp_path).unwrap();
     let c3d2 = C3d::load(&temp_path).unwrap();
     assert_eq!(c3d1, &c3d2);
 }
+
+#[allow(dead_code)]
+pub fn plug_in_gait() -> C3d {
+    C3d::load("tests/c3d_org_samples/sample_09/PlugInC3D.c3d").unwrap()
+}
+
+#[allow(dead_code)]
+pub fn rewritten(c3d: &C3d) -> C3d {
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    assert!(C3d::validate_bytes(&bytes).unwrap().is_valid());
+    C3d::from_bytes(&bytes).unwrap()
+}
@@ -0,0 +1,101 @@
+use crate::common::rewritten;
+use c3dio::prelude::*;
+use c3dio::C3dBuilder;
+
+#[test]
+fn add_point_round_trip() {
+    let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let num_points = c3d.points.cols();
+    let num_frames = c3d.points.rows();
+    let samples: Vec<MarkerPoint> = (0..num_frames)
+        .map(|frame| MarkerPoint::new(frame as f32, 1., 2.))
+        .collect();
+    let index = c3d
+        .add_point("VIRTUAL", "A virtual marker", samples)
+        .unwrap();
+    assert_eq!(index, num_points);
+    assert!(c3d.is_dirty());
+
+    let written = rewritten(&c3d);
+    assert_eq!(written.points.cols(), num_points + 1);
+    assert_eq!(written.points.labels[index].trim(), "VIRTUAL");
+    assert_eq!(
+        written.points.descriptions[index].trim(),
+        "A virtual marker"
+    );
+    assert_eq!(written.points[(3, "VIRTUAL")].point, [3., 1., 2.]);
+    assert_eq!(written.frame_count(), num_frames);
+    assert_eq!(written.points, c3d.points);
+    assert_eq!(written.analog.analog.flatten(), c3d.analog.analog.flatten());
+    assert!(written.validate().unwrap().is_valid());
+}
+
+#[test]
+fn add_point_errors() {
+    let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let num_frames = c3d.points.rows();
+    let label = c3d.points.labels[0].clone();
+    let samples = vec![MarkerPoint::new(0., 0., 0.); num_frames];
+    assert_eq!(
+        c3d.add_point(&label, "", samples.clone()),
+        Err(C3dMergeError::LabelCollision(label.trim().to_string()))
+    );
+    assert_eq!(
+        c3d.add_point("SHORT", "", samples[1..].to_vec()),
+        Err(C3dMergeError::FrameCountMismatch(
+            num_frames - 1,
+            num_frames
+        ))
+    );
+    assert!(!c3d.is_dirty());
+    assert_eq!(
+        c3d.add_computed_point("MID", "", &[&label, "NOT A MARKER"], |x| x[0]),
+        Err(C3dMergeError::LabelNotFound("NOT A MARKER".to_string()))
+    );
+}
+
+#[test]
+fn add_computed_point() {
+    let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let first = c3d.points.labels[0].clone();
+    let second = c3d.points.labels[1].clone();
+    c3d.points[(2, 1)].residual = -1.;
+    let index = c3d
+        .add_computed_point("MID", "Midpoint", &[&first, &second], |x| {
+            [0, 1, 2].map(|i| (x[0][i] + x[1][i]) / 2.)
+        })
+        .unwrap();
+    for frame in 0..c3d.points.rows() {
+        let (a, b) = (c3d.points[(frame, 0)], c3d.points[(frame, 1)]);
+        let mid = c3d.points[(frame, index)];
+        if !a.is_valid() || !b.is_valid() {
+            assert!(!mid.is_valid());
+            continue;
+        }
+        assert_eq!(mid.residual, a.residual.max(b.residual));
+        for i in 0..3 {
+            assert_eq!(mid[i], (a[i] + b[i]) / 2.);
+        }
+    }
+    assert!(!c3d.points[(2, index)].is_valid());
+}
+
+#[test]
+fn add_point_to_analog_only() {
+    let mut builder = C3dBuilder::new()
+        .frame_rate(100.)
+        .point_units("mm")
+        .analog_channels(&["Fz"], 10);
+    for frame in 0..20 {
+        builder = builder.push_frame(&[], &[frame as f64; 10]).unwrap();
+    }
+    let mut c3d = builder.build().unwrap();
+    assert_eq!(c3d.points.cols(), 0);
+    let samples = vec![MarkerPoint::new(1., 2., 3.); 20];
+    assert_eq!(c3d.add_point("FIRST", "", samples), Ok(0));
+    let written = rewritten(&c3d);
+    assert_eq!(written.points.size(), (20, 1));
+    assert_eq!(written.points[(19, 0)].point, [1., 2., 3.]);
+    assert_eq!(written.analog.size(), (200, 1));
+    assert!(written.validate().unwrap().is_valid());
+}
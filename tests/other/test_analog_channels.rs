@@ -0,0 +1,91 @@
+use crate::common::rewritten;
+use c3dio::prelude::*;
+
+#[test]
+fn channels_match_parameters() {
+    let c3d = C3d::load("tests/c3d_org_samples/sample_14/sample14.c3d").unwrap();
+    let gains: Vec<i16> = c3d.parameters.get_vec("ANALOG", "GAIN").unwrap();
+    let channels = c3d.analog_channels();
+    assert_eq!(channels.len(), c3d.analog.cols());
+    for (index, channel) in channels.iter().enumerate() {
+        assert_eq!(channel.index, index);
+        assert_eq!(channel.label, c3d.analog.labels[index]);
+        assert_eq!(channel.description, c3d.analog.descriptions[index]);
+        assert_eq!(channel.units, c3d.analog.units[index]);
+        assert_eq!(channel.gain, Some(gains[index]));
+        assert_eq!(channel.scale, c3d.analog.scales[index]);
+    }
+    assert_eq!(channels[0].offset, 2048);
+    assert_eq!(c3d.analog_channel(" EMG1 "), Some(channels[2].clone()));
+    assert_eq!(c3d.analog_channel("EMG99"), None);
+
+    // files without ANALOG:GAIN have no gains
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    assert!(c3d.analog_channels().iter().all(|x| x.gain.is_none()));
+}
+
+#[test]
+fn set_channel() {
+    let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let original = c3d.clone();
+    let mut channel = c3d.analog_channel("F1Z").unwrap();
+    channel.label = "FZ_LEFT".to_string();
+    channel.description = "Vertical force".to_string();
+    channel.units = "kN".to_string();
+    channel.gain = Some(2);
+    channel.scale /= 2.;
+    channel.offset = -3;
+    c3d.set_analog_channel(&channel).unwrap();
+    assert_eq!(c3d.analog_channel("FZ_LEFT"), Some(channel.clone()));
+    // the channels without a gain are given the code for an unknown gain
+    let gains: Vec<i16> = c3d.parameters.get_vec("ANALOG", "GAIN").unwrap();
+    assert_eq!(gains.len(), c3d.analog.cols());
+    assert_eq!(gains[channel.index], 2);
+    assert_eq!(gains[0], 0);
+    // the other channels and the samples are kept
+    let mut first = original.analog_channels()[0].clone();
+    first.gain = Some(0);
+    assert_eq!(c3d.analog_channels()[0], first);
+    assert_eq!(c3d.analog.as_slice(), original.analog.as_slice());
+
+    let written = rewritten(&c3d);
+    assert_eq!(written.analog_channel("FZ_LEFT"), Some(channel.clone()));
+    assert_eq!(written.analog_channels()[0], first);
+    let index = channel.index;
+    for (a, b) in written
+        .analog
+        .iter_col(index)
+        .zip(original.analog.iter_col(index))
+    {
+        assert!((a - b).abs() <= channel.scale.abs() as f64);
+    }
+}
+
+#[test]
+fn invalid_channels() {
+    let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let original = c3d.clone();
+    let mut channel = c3d.analog_channels()[0].clone();
+    channel.index = c3d.analog.cols();
+    assert!(matches!(
+        c3d.set_analog_channel(&channel),
+        Err(C3dWriteError::InvalidAnalogChannel(_))
+    ));
+    let mut channel = c3d.analog_channels()[0].clone();
+    channel.label = "NEW".to_string();
+    channel.gain = Some(1);
+    channel.offset = 40_000;
+    assert!(matches!(
+        c3d.set_analog_channel(&channel),
+        Err(C3dWriteError::InvalidAnalogChannel(_))
+    ));
+    assert_eq!(c3d.analog, original.analog);
+    assert!(!c3d.parameters.contains("ANALOG", "GAIN"));
+
+    // unsigned offsets keep their range
+    c3d.analog.offset = AnalogOffset::Unsigned(vec![0; c3d.analog.cols()]);
+    c3d.set_analog_channel(&channel).unwrap();
+    assert_eq!(c3d.analog_channel("NEW").unwrap().offset, 40_000);
+    channel.offset = -1;
+    assert!(c3d.set_analog_channel(&channel).is_err());
+}
@@ -0,0 +1,75 @@
+use crate::common::rewritten;
+use c3dio::prelude::*;
+use c3dio::C3dBuilder;
+use grid::Grid;
+
+fn analog_only(format: DataFormat, scale_factor: f32) -> C3d {
+    let mut builder = C3dBuilder::new()
+        .frame_rate(100.)
+        .point_units("m")
+        .data_format(format, scale_factor)
+        .analog_channels(&["Fx", "Fz", "EMG1"], 10)
+        .analog_units(&["N", "N", "V"]);
+    for frame in 0..20 {
+        let analog: Vec<f64> = (0..30).map(|i| ((frame * 30 + i) % 100) as f64).collect();
+        builder = builder.push_frame(&[], &analog).unwrap();
+    }
+    builder.build().unwrap()
+}
+
+#[test]
+fn analog_only_round_trip() {
+    for (format, scale_factor) in [(DataFormat::Float, 1.), (DataFormat::Integer, 1.)] {
+        let c3d = analog_only(format.clone(), scale_factor);
+        let written = rewritten(&c3d);
+        assert_eq!(written.points.size(), (0, 0));
+        assert_eq!(written.analog.size(), (200, 3));
+        assert_eq!(written.analog.analog.flatten(), c3d.analog.analog.flatten());
+        assert_eq!(written.frame_count(), 20);
+        assert_eq!(written.declared_frame_count(), 20);
+        assert_eq!(written.points.frame_rate, 100.);
+        assert_eq!(written.analog.rate, 1000.);
+        assert_eq!(written.points.format, format);
+        assert!(written.validate().unwrap().is_valid());
+    }
+}
+
+#[test]
+fn analog_only_keeps_point_parameters() {
+    let written = rewritten(&analog_only(DataFormat::Float, 1.));
+    assert_eq!(written.points.units, ['m', ' ', ' ', ' ']);
+    // nothing interpreted by the points is left over to be written twice
+    assert!(written
+        .parameters
+        .get_group("POINT")
+        .is_none_or(|group| group.is_empty()));
+    let rewritten = rewritten(&written);
+    assert_eq!(rewritten.points.units, written.points.units);
+    assert_eq!(
+        rewritten.analog.analog.flatten(),
+        written.analog.analog.flatten()
+    );
+}
+
+#[test]
+fn analog_only_from_file() {
+    for file in [
+        "tests/data/short.c3d",
+        "tests/c3d_org_samples/sample_09/PlugInC3D.c3d",
+    ] {
+        let mut c3d = C3d::load(file).unwrap();
+        c3d.points.points = Grid::new(0, 0);
+        c3d.points.labels.clear();
+        c3d.points.descriptions.clear();
+        let frames = c3d.frame_count();
+        let written = rewritten(&c3d);
+        assert_eq!(written.points.size(), (0, 0));
+        assert_eq!(written.analog.size(), c3d.analog.size());
+        assert_eq!(written.frame_count(), frames);
+        assert_eq!(written.forces.len(), c3d.forces.len());
+        let expected = c3d.analog.analog.flatten();
+        for (value, expected) in written.analog.analog.flatten().iter().zip(expected) {
+            assert!((value - expected).abs() <= expected.abs() * 1e-6 + 1e-6);
+        }
+    }
+}
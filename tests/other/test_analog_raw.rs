@@ -0,0 +1,65 @@
+use crate::common::{plug_in_gait, rewritten};
+use c3dio::prelude::*;
+use c3dio::C3dBuilder;
+
+fn unsigned_integer(bits: i16, offset: u16, scale: f32, raw: &[u16]) -> C3d {
+    let mut builder = C3dBuilder::new()
+        .frame_rate(100.)
+        .data_format(DataFormat::Integer, 1.)
+        .analog_channels(&["EMG"], 1);
+    for &sample in raw {
+        let value = (sample as f64 - offset as f64) * scale as f64;
+        builder = builder.push_frame(&[], &[value]).unwrap();
+    }
+    let mut c3d = builder.build().unwrap();
+    c3d.analog.offset = AnalogOffset::Unsigned(vec![offset]);
+    c3d.analog.scales = vec![scale];
+    c3d.analog.gen_scale = 1.;
+    c3d.analog.bits = bits;
+    c3d
+}
+
+#[test]
+fn unsigned_16_bit_samples() {
+    let raw = [0, 1000, 32767, 32768, 40000, 65535];
+    let c3d = rewritten(&unsigned_integer(16, 32768, 0.5, &raw));
+    assert_eq!(c3d.analog.format(), AnalogFormat::Unsigned);
+    assert_eq!(c3d.analog.raw_range(), Some((0, 65535)));
+    let expected: Vec<f64> = raw.iter().map(|&x| x as f64).collect();
+    assert_eq!(c3d.analog.raw().flatten(), &expected);
+    let scaled: Vec<f64> = raw.iter().map(|&x| (x as f64 - 32768.) * 0.5).collect();
+    assert_eq!(c3d.analog.scaled().flatten(), &scaled);
+}
+
+#[test]
+fn unsigned_12_bit_samples() {
+    let raw = [0, 2047, 2048, 4095];
+    let c3d = rewritten(&unsigned_integer(12, 2048, 0.01, &raw));
+    assert_eq!(c3d.analog.bits, 12);
+    assert_eq!(c3d.analog.raw_range(), Some((0, 4095)));
+    for (value, expected) in c3d.analog.raw().flatten().iter().zip(raw) {
+        assert!((value - expected as f64).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn signed_raw_samples() {
+    let c3d = plug_in_gait();
+    assert_eq!(c3d.analog.format(), AnalogFormat::Signed);
+    for value in c3d.analog.raw().flatten() {
+        assert!((value - value.round()).abs() < 1e-3);
+        assert!(value.abs() <= i16::MAX as f64 + 1.);
+    }
+    assert_eq!(c3d.analog.scaled().flatten(), c3d.analog.analog.flatten());
+}
+
+#[test]
+fn unsigned_float_samples() {
+    let c3d = C3d::load("tests/c3d_org_samples/sample_19/sample19.c3d").unwrap();
+    assert_eq!(c3d.analog.format(), AnalogFormat::Unsigned);
+    let (min, max) = c3d.analog.raw_range().unwrap();
+    assert_eq!((min, max), (0, 65535));
+    for value in c3d.analog.raw().flatten() {
+        assert!(*value >= min as f64 - 1e-2 && *value <= max as f64 + 1e-2);
+    }
+}
@@ -0,0 +1,46 @@
+use c3dio::prelude::*;
+
+#[test]
+fn points_to_array3() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let (shape, samples) = c3d.points.to_array3();
+    assert_eq!(shape, [c3d.points.rows(), c3d.points.cols(), 4]);
+    assert_eq!(samples.len(), shape.iter().product::<usize>());
+    for frame in [0, shape[0] / 2, shape[0] - 1] {
+        for point in 0..shape[1] {
+            let start = (frame * shape[1] + point) * 4;
+            let stored = c3d.points[frame][point];
+            assert_eq!(samples[start..start + 3], stored.point);
+            assert_eq!(samples[start + 3], stored.residual);
+        }
+    }
+}
+
+#[test]
+fn analog_to_array2() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let (shape, samples) = c3d.analog.to_array2();
+    assert_eq!(shape, [c3d.analog.rows(), c3d.analog.cols()]);
+    for row in [0, shape[0] - 1] {
+        assert_eq!(
+            samples[row * shape[1]..(row + 1) * shape[1]],
+            c3d.analog[row]
+        );
+    }
+}
+
+#[test]
+fn point_positions() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let positions = c3d.points.positions(1);
+    assert_eq!(positions.len(), c3d.points.rows());
+    assert_eq!(positions[3], c3d.points[3][1].point);
+    assert!(c3d.points.positions(c3d.points.cols()).is_empty());
+}
+
+#[test]
+fn empty_arrays() {
+    let c3d = C3d::new();
+    assert_eq!(c3d.points.to_array3(), ([0, 0, 4], Vec::new()));
+    assert_eq!(c3d.analog.to_array2(), ([0, 0], Vec::new()));
+}
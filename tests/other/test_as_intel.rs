@@ -0,0 +1,36 @@
+use c3dio::prelude::*;
+use std::borrow::Cow;
+use test_files::TestFiles;
+
+#[test]
+fn intel_file_is_borrowed() {
+    let c3d = C3d::load("tests/c3d_org_samples/sample_02/pc_real.c3d").unwrap();
+    assert_eq!(c3d.processor(), Processor::Intel);
+    assert!(matches!(c3d.as_intel(), Cow::Borrowed(_)));
+}
+
+#[test]
+fn dec_file_is_converted() {
+    let c3d = C3d::load("tests/c3d_org_samples/sample_02/dec_real.c3d").unwrap();
+    assert_eq!(c3d.processor(), Processor::Dec);
+    let intel = c3d.as_intel();
+    assert!(matches!(intel, Cow::Owned(_)));
+    assert_eq!(intel.processor(), Processor::Intel);
+    assert_eq!(c3d.processor(), Processor::Dec);
+    assert_eq!(intel.frame_byte_offset(0), c3d.frame_byte_offset(0));
+
+    let files = TestFiles::new();
+    let path = files.path().join("as_intel.c3d");
+    intel.write_path(path.clone()).unwrap();
+    let written = C3d::load_path(path).unwrap();
+    assert_eq!(written.processor(), Processor::Intel);
+    assert_eq!(written.points.size(), c3d.points.size());
+    for frame in 0..c3d.points.rows() {
+        for marker in 0..c3d.points.cols() {
+            assert_eq!(
+                written.points[frame][marker].point,
+                c3d.points[frame][marker].point
+            );
+        }
+    }
+}
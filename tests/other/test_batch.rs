@@ -0,0 +1,64 @@
+use c3dio::batch::{load_metadata, process};
+use c3dio::prelude::*;
+use std::path::Path;
+use test_files::TestFiles;
+
+#[test]
+fn load_the_metadata_of_a_directory() {
+    let entries = load_metadata("tests/c3d_org_samples/sample_02");
+    let names: Vec<_> = entries
+        .iter()
+        .map(|entry| entry.path.file_name().unwrap().to_str().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "dec_int.c3d",
+            "dec_real.c3d",
+            "pc_int.c3d",
+            "pc_real.c3d",
+            "sgi_int.c3d",
+            "sgi_real.c3d"
+        ]
+    );
+    for entry in &entries {
+        let c3d = entry.result.as_ref().unwrap();
+        let loaded = C3d::load(entry.path.to_str().unwrap()).unwrap();
+        assert_eq!(c3d.points.labels, loaded.points.labels);
+        assert_eq!(c3d.declared_frame_count(), loaded.frame_count());
+        assert_eq!(c3d.points.rows(), 0);
+    }
+}
+
+fn frame_count(path: &Path) -> Result<usize, C3dParseError> {
+    Ok(C3d::load(path.to_str().unwrap())?.frame_count())
+}
+
+fn nothing(_: &Path) -> Result<(), C3dParseError> {
+    Ok(())
+}
+
+#[test]
+fn failures_do_not_stop_the_batch() {
+    let files = TestFiles::new();
+    let dir = files.path();
+    std::fs::create_dir_all(dir.join("nested")).unwrap();
+    std::fs::copy("tests/data/short.c3d", dir.join("nested/SHORT.C3D")).unwrap();
+    std::fs::write(dir.join("broken.c3d"), [0u8; 100]).unwrap();
+    std::fs::write(dir.join("notes.txt"), "not a trial").unwrap();
+    let entries = process(dir, frame_count);
+    let missing = process(dir.join("missing"), nothing);
+
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].path, dir.join("broken.c3d"));
+    assert!(entries[0].result.is_err());
+    assert_eq!(entries[1].path, dir.join("nested/SHORT.C3D"));
+    let frames = C3d::load("tests/data/short.c3d").unwrap().frame_count();
+    assert_eq!(*entries[1].result.as_ref().unwrap(), frames);
+
+    assert_eq!(missing.len(), 1);
+    assert!(matches!(
+        missing[0].result,
+        Err(C3dParseError::ReadError(_))
+    ));
+}
@@ -0,0 +1,131 @@
+use c3dio::prelude::*;
+use c3dio::{C3dBuilder, C3dRef, DataType};
+
+#[test]
+fn borrowed_matches_owned() {
+    for file in [
+        "tests/data/short.c3d",
+        "tests/c3d_org_samples/sample_01/Eb015pi.c3d",
+        "tests/c3d_org_samples/sample_01/Eb015vr.c3d",
+        "tests/c3d_org_samples/sample_01/Eb015sr.c3d",
+    ] {
+        let bytes = std::fs::read(file).unwrap();
+        let borrowed = C3d::from_bytes_ref(&bytes).unwrap();
+        let owned = C3d::from_bytes(&bytes).unwrap();
+        assert_eq!(borrowed.processor(), owned.processor(), "{}", file);
+        assert_eq!(borrowed.frame_rate(), owned.points.frame_rate);
+        assert_eq!(borrowed.first_frame(), owned.points.first_frame);
+        assert_eq!(borrowed.last_frame(), owned.points.last_frame);
+        let labels = borrowed.point_labels().unwrap();
+        assert_eq!(
+            &labels[..owned.points.labels.len()],
+            &owned.points.labels[..]
+        );
+        let labels = borrowed.analog_labels().unwrap();
+        assert_eq!(
+            &labels[..owned.analog.labels.len()],
+            &owned.analog.labels[..]
+        );
+        assert_eq!(borrowed.to_c3d().unwrap(), owned);
+    }
+}
+
+#[test]
+fn borrowed_parameters() {
+    let mut c3d = C3dBuilder::new()
+        .point_labels(&["LASI", "RASI"])
+        .frame_rate(100.)
+        .push_frame(&[[0.; 3], [1.; 3]], &[])
+        .unwrap()
+        .build()
+        .unwrap();
+    c3d.parameters
+        .add_parameter(
+            "Custom",
+            "Gains",
+            "per channel",
+            vec![3],
+            ParameterData::Float(vec![0.5, 1., 2.]),
+        )
+        .unwrap();
+    c3d.parameters
+        .add_parameter(
+            "CUSTOM",
+            "SIDE",
+            "",
+            vec![5, 2],
+            ParameterData::Char("Left Right".chars().collect()),
+        )
+        .unwrap();
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, Processor::Dec).unwrap();
+
+    let borrowed = C3dRef::from_bytes(&bytes).unwrap();
+    assert_eq!(borrowed.processor(), Processor::Dec);
+    let gains = borrowed.parameter("custom", "gains").unwrap();
+    assert_eq!(gains.description, "per channel");
+    assert_eq!(gains.data_type, DataType::Float);
+    assert_eq!(gains.floats(), Some(vec![0.5, 1., 2.]));
+    assert_eq!(gains.integers(), None);
+    assert_eq!(gains.dimensions(), vec![3]);
+    assert_eq!(
+        gains.to_parameter().unwrap(),
+        *c3d.parameters.get("CUSTOM", "GAINS").unwrap()
+    );
+    assert_eq!(borrowed.group_of(gains).unwrap().name, "CUSTOM");
+    assert_eq!(
+        borrowed.strings("CUSTOM", "SIDE"),
+        Some(vec!["Left".into(), "Right".into()])
+    );
+    assert!(borrowed.parameter("POINT", "GAINS").is_none());
+    assert!(borrowed.group("point").is_some());
+    assert_eq!(
+        borrowed.point_labels(),
+        Some(vec!["LASI".into(), "RASI".into()])
+    );
+}
+
+#[test]
+fn borrowed_without_data() {
+    // the metadata of a file with its data section cut off can still be read
+    let bytes = std::fs::read("tests/data/short.c3d").unwrap();
+    let data_start = u16::from_le_bytes([bytes[16], bytes[17]]) as usize;
+    let truncated = &bytes[..512 * (data_start - 1)];
+    let borrowed = C3d::from_bytes_ref(truncated).unwrap();
+    let owned = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(
+        borrowed.point_labels().unwrap()[..owned.points.labels.len()],
+        owned.points.labels[..]
+    );
+    assert!(matches!(
+        C3dRef::from_bytes(&bytes[..512]),
+        Err(C3dParseError::InsufficientBlocks(_))
+    ));
+}
+
+#[test]
+fn borrowed_descriptions_match_owned() {
+    let mut bytes = std::fs::read("tests/data/short.c3d").unwrap();
+    let index = bytes
+        .windows(12)
+        .position(|x| x == b"Marker names")
+        .unwrap();
+    bytes[index + 6] = 0xff;
+    let owned = C3d::from_bytes(&bytes).unwrap();
+    let description = owned.parameters.description("POINT", "LABELS").unwrap();
+    assert_eq!(description, "Marker\u{fffd}names");
+    let borrowed = C3d::from_bytes_ref(&bytes).unwrap();
+    let labels = borrowed.parameter("POINT", "LABELS").unwrap();
+    assert_eq!(labels.description, description);
+}
+
+#[test]
+fn group_id_of_minus_128_is_error() {
+    let mut bytes = std::fs::read("tests/data/short.c3d").unwrap();
+    let first_group = (bytes[0] as usize - 1) * 512 + 4;
+    bytes[first_group + 1] = 0x80;
+    assert!(matches!(
+        C3d::from_bytes_ref(&bytes),
+        Err(C3dParseError::InvalidGroupId)
+    ));
+}
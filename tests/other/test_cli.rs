@@ -0,0 +1,76 @@
+use c3dio::prelude::*;
+use std::process::{Command, Output};
+use test_files::TestFiles;
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_c3d"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn info_prints_the_summary() {
+    let output = run(&["info", "tests/data/short.c3d"]);
+    assert!(output.status.success());
+    let expected = C3d::load("tests/data/short.c3d").unwrap().summary();
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+}
+
+#[test]
+fn validate_reports_each_file() {
+    let output = run(&["validate", "tests/data/short.c3d"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.ends_with("tests/data/short.c3d: valid\n"));
+
+    let output = run(&["validate", "tests/data/short.c3d", "tests/data/missing.c3d"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn convert_writes_the_export() {
+    let files = TestFiles::new();
+    let path = files.path().join("short.trc");
+    let file_name = path.to_str().unwrap();
+    let output = run(&[
+        "convert",
+        "tests/data/short.c3d",
+        "--to",
+        "trc",
+        "-o",
+        file_name,
+    ]);
+    assert!(output.status.success());
+    let trc = std::fs::read(file_name).unwrap();
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let mut expected = Vec::new();
+    Trc::from_c3d(&c3d).write_to(&mut expected).unwrap();
+    // the first line names the file
+    let body = |bytes: &[u8]| bytes.splitn(2, |&b| b == b'\n').nth(1).unwrap().to_vec();
+    assert_eq!(body(&trc), body(&expected));
+}
+
+#[test]
+fn crop_writes_the_frames_kept() {
+    let files = TestFiles::new();
+    let path = files.path().join("cropped.c3d");
+    let file_name = path.to_str().unwrap();
+    let output = run(&[
+        "crop",
+        "tests/data/short.c3d",
+        "--frames",
+        "100:",
+        "--output",
+        file_name,
+    ]);
+    assert!(output.status.success());
+    let cropped = C3d::load(file_name).unwrap();
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    assert_eq!(cropped.points, c3d.crop(100..usize::MAX).points);
+
+    for frames in ["20:10", "10-20", "a:"] {
+        let output = run(&["crop", "tests/data/short.c3d", "--frames", frames]);
+        assert!(!output.status.success());
+    }
+}
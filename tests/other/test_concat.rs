@@ -0,0 +1,96 @@
+use crate::common::plug_in_gait;
+use c3dio::prelude::*;
+use c3dio::{Event, RigidBody};
+
+#[test]
+fn concat_crops() {
+    let c3d = plug_in_gait();
+    let parts = [c3d.crop(0..100), c3d.crop(100..200), c3d.crop(200..281)];
+    let concat = C3d::concat(&[&parts[0], &parts[1], &parts[2]]).unwrap();
+    assert_eq!(concat.frame_count(), c3d.frame_count());
+    assert_eq!(concat.declared_frame_count(), c3d.frame_count());
+    assert_eq!(concat.points.flatten(), c3d.points.flatten());
+    assert_eq!(concat.analog.flatten(), c3d.analog.flatten());
+    assert_eq!(concat.points.last_frame, c3d.points.last_frame);
+
+    let mut events: Vec<&Event> = c3d.events.iter().collect();
+    events.sort_by(|a, b| a.time.total_cmp(&b.time));
+    assert_eq!(concat.events.num_events(), events.len());
+    for (event, original) in concat.events.iter().zip(events) {
+        assert_eq!(event.label, original.label);
+        assert!((event.time - original.time).abs() < 1e-4);
+        assert_eq!(concat.event_frame(event), c3d.event_frame(original));
+    }
+
+    let mut bytes = Vec::new();
+    concat.write_to(&mut bytes, concat.processor()).unwrap();
+    assert!(C3d::validate_bytes(&bytes).unwrap().is_valid());
+    let written = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(written.points, concat.points);
+    assert_eq!(written.gait_cycles("Left"), c3d.gait_cycles("Left"));
+}
+
+#[test]
+fn concat_single() {
+    let c3d = plug_in_gait();
+    let concat = C3d::concat(&[&c3d]).unwrap();
+    assert_eq!(concat.points, c3d.points);
+    assert_eq!(concat.events, c3d.events);
+}
+
+#[test]
+fn concat_incompatible() {
+    let c3d = plug_in_gait();
+    assert_eq!(C3d::concat(&[]).unwrap_err(), C3dMergeError::NoTrials);
+
+    let mut relabelled = c3d.clone();
+    relabelled.points.labels[0] = "OTHER".to_string();
+    assert_eq!(
+        C3d::concat(&[&c3d, &c3d, &relabelled]).unwrap_err(),
+        C3dMergeError::PointLabelsMismatch(2)
+    );
+
+    let mut faster = c3d.clone();
+    faster.points.frame_rate = 120.;
+    assert_eq!(
+        C3d::concat(&[&c3d, &faster]).unwrap_err(),
+        C3dMergeError::FrameRateMismatch(1, 60., 120.)
+    );
+
+    let mut analog = c3d.clone();
+    analog.analog.samples_per_channel_per_frame += 1;
+    assert_eq!(
+        C3d::concat(&[&c3d, &analog]).unwrap_err(),
+        C3dMergeError::AnalogLayoutMismatch(1)
+    );
+}
+
+#[test]
+fn concat_rotations() {
+    let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let labels: Vec<&str> = c3d.points.labels[..3].iter().map(|x| x.as_str()).collect();
+    let body = RigidBody::from_frame(&c3d, "BODY", &labels, 0).unwrap();
+    c3d.add_rigid_body_rotation(&body).unwrap();
+    let (first, second) = (c3d.crop(0..10), c3d.crop(10..20));
+    let concat = C3d::concat(&[&first, &second]).unwrap();
+    assert_eq!(concat.rotations, c3d.crop(0..20).rotations);
+
+    // missing samples of a trial are padded so the next trial keeps its frames
+    let mut short = first.clone();
+    short.rotations.pop_row();
+    let concat = C3d::concat(&[&short, &second]).unwrap();
+    assert_eq!(concat.rotations.rows(), 20);
+    assert!(!concat.rotations[(9, 0)].is_valid());
+    assert_eq!(concat.rotations[(10, 0)], c3d.rotations[(10, 0)]);
+    let mut bytes = Vec::new();
+    concat.write_to(&mut bytes, concat.processor()).unwrap();
+    let written = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(written.rotations, concat.rotations);
+
+    let mut without = second.clone();
+    without.rotations = Default::default();
+    assert_eq!(
+        C3d::concat(&[&first, &without]).unwrap_err(),
+        C3dMergeError::RotationLayoutMismatch(1)
+    );
+}
@@ -0,0 +1,63 @@
+use crate::common::{plug_in_gait, rewritten};
+use c3dio::prelude::*;
+
+#[test]
+fn crop() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let cropped = c3d.crop(10..60);
+    assert_eq!(cropped.frame_count(), 50);
+    assert_eq!(cropped.declared_frame_count(), 50);
+    assert_eq!(
+        (cropped.points.first_frame, cropped.points.last_frame),
+        (1, 50)
+    );
+    assert_eq!(cropped.points[0][0], c3d.points[10][0]);
+    assert_eq!(cropped.points[49][48], c3d.points[59][48]);
+    let samples = c3d.analog.samples_per_channel_per_frame as usize;
+    assert_eq!(cropped.analog.rows(), 50 * samples);
+    assert_eq!(cropped.analog[0][0], c3d.analog[10 * samples][0]);
+
+    let written = rewritten(&cropped);
+    assert_eq!(written.points, cropped.points);
+    assert_eq!(written.analog, cropped.analog);
+    assert_eq!(written.declared_frame_count(), 50);
+}
+
+#[test]
+fn crop_clamped() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    assert_eq!(c3d.crop(100..1000).frame_count(), 51);
+    let empty = c3d.crop(200..300);
+    assert_eq!(empty.frame_count(), 0);
+    assert_eq!(empty.analog.rows(), 0);
+    assert!(empty.events.is_empty());
+}
+
+#[test]
+fn crop_events() {
+    let c3d = plug_in_gait();
+    let cycle = c3d.gait_cycles("Right").remove(0);
+    let cropped = c3d.crop(cycle.clone());
+    assert_eq!(cropped.frame_count(), cycle.len());
+    // foot strike, foot off and foot strike of the left foot, and foot off of the right foot
+    assert_eq!(cropped.events.num_events(), 4);
+    assert_eq!(cropped.events[0].time, 0.);
+    for event in cropped.events.iter() {
+        let original = c3d
+            .events
+            .iter()
+            .find(|x| x.label == event.label && x.context == event.context && x.time > event.time)
+            .unwrap();
+        assert_eq!(
+            cropped.event_frame(event).unwrap(),
+            c3d.event_frame(original).unwrap() - cycle.start
+        );
+    }
+    let written = rewritten(&cropped);
+    assert_eq!(written.events.num_events(), 4);
+    assert!(written.gait_cycles("Left").is_empty());
+    assert_eq!(
+        written.frames_between_events("Foot Strike", "Foot Off", "Right"),
+        vec![0..48]
+    );
+}
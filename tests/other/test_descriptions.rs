@@ -0,0 +1,129 @@
+use crate::common::rewritten;
+use c3dio::prelude::*;
+
+/// Parameters of short.c3d that are parsed into the fields of `C3d` and written from them.
+const PARSED: [(&str, &str); 7] = [
+    ("POINT", "LABELS"),
+    ("POINT", "SCALE"),
+    ("POINT", "RATE"),
+    ("ANALOG", "LABELS"),
+    ("ANALOG", "GEN_SCALE"),
+    ("MANUFACTURER", "COMPANY"),
+    ("FORCE_PLATFORM", "CORNERS"),
+];
+
+#[test]
+fn descriptions_of_parsed_parameters_are_kept() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    assert_eq!(
+        c3d.parameters.description("POINT", "LABELS"),
+        Some("Marker names")
+    );
+    let written = rewritten(&c3d);
+    for (group, parameter) in PARSED {
+        assert!(c3d.parameters.description(group, parameter).is_some());
+        assert_eq!(
+            written.parameters.description(group, parameter),
+            c3d.parameters.description(group, parameter),
+            "{}:{}",
+            group,
+            parameter
+        );
+    }
+    assert_eq!(
+        written.parameters.description("SUBJECTS", "NAMES"),
+        Some("MarkerSet names.")
+    );
+}
+
+#[test]
+fn descriptions_can_be_set() {
+    let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    c3d.parameters
+        .set_description("POINT", "RATE", "Camera frame rate")
+        .unwrap();
+    c3d.parameters
+        .set_description("SUBJECTS", "NAMES", "The subjects")
+        .unwrap();
+    c3d.parameters
+        .set_description("POINT", "UNITS", "Length units")
+        .unwrap();
+    let written = rewritten(&c3d);
+    for (group, parameter, description) in [
+        ("POINT", "RATE", "Camera frame rate"),
+        ("SUBJECTS", "NAMES", "The subjects"),
+        ("POINT", "UNITS", "Length units"),
+    ] {
+        assert_eq!(
+            written.parameters.description(group, parameter),
+            Some(description)
+        );
+    }
+    assert!(matches!(
+        c3d.parameters
+            .set_description("POINT", "RATE", &"x".repeat(256)),
+        Err(C3dWriteError::ParameterDescriptionTooLong(_))
+    ));
+}
+
+#[test]
+fn set_keeps_the_description_of_parsed_parameters() {
+    let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    c3d.parameters.set("POINT", "SCALE", -0.5).unwrap();
+    assert_eq!(
+        c3d.parameters.get("POINT", "SCALE").unwrap().description,
+        "Video data scale factor"
+    );
+}
+
+#[test]
+fn locks_are_read_from_the_name_length() {
+    let mut bytes = std::fs::read("tests/data/short.c3d").unwrap();
+    let index = bytes
+        .windows(9)
+        .position(|x| x == b"\x07\x05Company")
+        .unwrap();
+    bytes[index] = -7i8 as u8;
+    let index = bytes
+        .windows(10)
+        .position(|x| x == b"\x08\xffSUBJECTS")
+        .unwrap();
+    bytes[index] = -8i8 as u8;
+    let c3d = C3d::from_bytes(&bytes).unwrap();
+    assert!(c3d.parameters.is_locked("MANUFACTURER", "COMPANY"));
+    assert!(!c3d.parameters.is_locked("MANUFACTURER", "SOFTWARE"));
+    assert!(c3d.parameters.is_group_locked("SUBJECTS"));
+    assert!(!c3d.parameters.is_group_locked("POINT"));
+    let original = C3d::load("tests/data/short.c3d").unwrap();
+    assert_eq!(c3d.manufacturer, original.manufacturer);
+
+    let mut written_bytes = Vec::new();
+    c3d.write_to(&mut written_bytes, Processor::Intel).unwrap();
+    let written = C3d::from_bytes(&written_bytes).unwrap();
+    assert!(written.parameters.is_locked("MANUFACTURER", "COMPANY"));
+    assert!(written.parameters.is_group_locked("SUBJECTS"));
+    assert!(written_bytes.windows(9).any(|x| x == b"\xf9\x05COMPANY"));
+}
+
+#[test]
+fn parameters_can_be_locked_and_unlocked() {
+    let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    c3d.parameters.lock("POINT", "LABELS");
+    c3d.parameters.lock("SUBJECTS", "NAMES");
+    c3d.parameters.lock_group("ANALOG");
+    let mut written = rewritten(&c3d);
+    assert!(written.parameters.is_locked("POINT", "LABELS"));
+    assert!(written.parameters.is_locked("SUBJECTS", "NAMES"));
+    assert!(written.parameters.is_group_locked("ANALOG"));
+    assert!(!written.parameters.is_locked("POINT", "SCALE"));
+    assert_eq!(written.points, c3d.points);
+    assert_eq!(written.parameters, c3d.parameters);
+
+    written.parameters.unlock("SUBJECTS", "NAMES");
+    written.parameters.unlock_group("ANALOG");
+    assert_ne!(written.parameters, c3d.parameters);
+    let unlocked = rewritten(&written);
+    assert!(!unlocked.parameters.is_locked("SUBJECTS", "NAMES"));
+    assert!(!unlocked.parameters.is_group_locked("ANALOG"));
+    assert!(unlocked.parameters.is_locked("POINT", "LABELS"));
+}
@@ -0,0 +1,215 @@
+use c3dio::diff::approx_eq;
+use c3dio::prelude::*;
+use c3dio::{DiffOptions, DiffSide, Difference};
+
+fn short() -> C3d {
+    C3d::load("tests/data/short.c3d").unwrap()
+}
+
+#[test]
+fn a_file_has_no_differences_with_itself() {
+    let c3d = short();
+    assert!(c3d.diff(&c3d).unwrap().is_empty());
+}
+
+#[test]
+fn converted_processors_only_differ_in_the_processor() {
+    let c3d = short();
+    for processor in [Processor::Dec, Processor::SgiMips] {
+        let mut bytes = Vec::new();
+        c3d.write_to(&mut bytes, processor).unwrap();
+        let converted = C3d::from_bytes(&bytes).unwrap();
+        let diff = converted.diff(&c3d).unwrap();
+        assert_eq!(
+            diff.differences,
+            vec![Difference::Header {
+                field: "processor".to_string(),
+                left: format!("{:?}", processor),
+                right: "Intel".to_string(),
+            }]
+        );
+    }
+}
+
+#[test]
+fn integer_and_float_samples_have_the_same_data() {
+    let integer = C3d::load("tests/c3d_org_samples/sample_02/pc_int.c3d").unwrap();
+    let float = C3d::load("tests/c3d_org_samples/sample_02/pc_real.c3d").unwrap();
+    // the integer samples are rounded to the point scale of 0.28
+    let options = DiffOptions {
+        point_tolerance: 0.5,
+        ..Default::default()
+    };
+    let diff = integer.diff_with_options(&float, &options).unwrap();
+    assert!(diff.differences.iter().all(|difference| matches!(
+        difference,
+        Difference::Header { .. } | Difference::Parameter { .. }
+    )));
+    assert!(diff.differences.contains(&Difference::Header {
+        field: "data format".to_string(),
+        left: "Integer".to_string(),
+        right: "Float".to_string(),
+    }));
+}
+
+#[test]
+fn data_differences_are_reported_per_point_and_channel() {
+    let c3d = short();
+    let mut changed = c3d.clone();
+    changed.points[3][2].point[0] += 1.;
+    changed.points[5][2].point[1] -= 2.;
+    changed.analog[10][1] += 0.5;
+    let diff = c3d.diff(&changed).unwrap();
+    assert_eq!(
+        diff.differences,
+        vec![
+            Difference::Point {
+                label: c3d.points.labels[2].clone(),
+                frames: 2,
+                first_frame: 3,
+                max_difference: 2.,
+            },
+            Difference::Analog {
+                label: c3d.analog.labels[1].clone(),
+                samples: 1,
+                first_sample: 10,
+                max_difference: 0.5,
+            },
+        ]
+    );
+}
+
+#[test]
+fn differences_within_the_tolerance_are_ignored() {
+    let c3d = short();
+    let mut changed = c3d.clone();
+    changed.points[3][2].point[0] += 0.01;
+    changed.analog[10][1] += 0.001;
+    assert!(!c3d.diff(&changed).unwrap().is_empty());
+    let options = DiffOptions {
+        point_tolerance: 0.1,
+        analog_tolerance: 0.01,
+        ..Default::default()
+    };
+    assert!(c3d
+        .diff_with_options(&changed, &options)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn points_are_paired_by_label() {
+    let c3d = short();
+    let mut reordered = c3d.clone();
+    reordered.points.labels.swap(0, 1);
+    for row in 0..reordered.points.rows() {
+        reordered.points[row].swap(0, 1);
+    }
+    let diff = c3d.diff(&reordered).unwrap();
+    assert!(diff
+        .differences
+        .iter()
+        .all(|difference| matches!(difference, Difference::Parameter { .. })));
+
+    let mut renamed = c3d.clone();
+    renamed.points.labels[0] = "RENAMED".to_string();
+    let diff = c3d.diff(&renamed).unwrap();
+    assert!(diff.differences.contains(&Difference::MissingPoint {
+        label: c3d.points.labels[0].clone(),
+        only_in: DiffSide::Left,
+    }));
+    assert!(diff.differences.contains(&Difference::MissingPoint {
+        label: "RENAMED".to_string(),
+        only_in: DiffSide::Right,
+    }));
+}
+
+#[test]
+fn parameters_and_events_are_compared() {
+    let c3d = short();
+    let mut changed = c3d.clone();
+    changed.points.frame_rate = 50.;
+    changed.events.add_event("Foot Strike", "Left", 0.1);
+    let diff = c3d.diff(&changed).unwrap();
+    assert_eq!(diff.differences.len(), 2);
+    assert!(matches!(
+        &diff.differences[0],
+        Difference::Parameter { group, parameter, .. } if group == "POINT" && parameter == "RATE"
+    ));
+    assert!(matches!(
+        &diff.differences[1],
+        Difference::MissingEvent { event, only_in: DiffSide::Right } if event.label == "Foot Strike"
+    ));
+
+    let mut shifted = changed.clone();
+    let event = shifted.events.len() - 1;
+    shifted.events.event_mut(event).unwrap().time += 1e-6;
+    assert!(shifted.diff(&changed).unwrap().is_empty());
+}
+
+/// Returns the float `steps` single precision steps above `value`.
+fn ulps_above(value: f32, steps: u32) -> f32 {
+    match value > 0. {
+        true => f32::from_bits(value.to_bits() + steps),
+        false => -f32::from_bits((-value).to_bits() - steps),
+    }
+}
+
+#[test]
+fn approx_eq_counts_single_precision_steps() {
+    for value in [1e-3_f32, 0.75, 1000., -5e6] {
+        let above = ulps_above(value, 3) as f64;
+        assert!(approx_eq(value as f64, above, 3), "{}", value);
+        assert!(!approx_eq(value as f64, above, 2), "{}", value);
+    }
+    // values too small for a DEC float are flushed to zero
+    assert!(approx_eq(1e-40, 0., 0));
+    assert!(approx_eq(-1e-38, 2e-39, 0));
+    assert!(!approx_eq(1e-37, 0., 4));
+    assert!(approx_eq(f64::NAN, f64::NAN, 0));
+    assert!(!approx_eq(f64::NAN, 1., 4));
+    assert!(approx_eq(f64::INFINITY, f64::INFINITY, 0));
+    assert!(!approx_eq(f64::INFINITY, f64::MAX, 4));
+}
+
+#[test]
+fn processor_conversions_are_approximately_equal() {
+    let mut c3d = short();
+    c3d.points[0][1].point[2] = 0.;
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, Processor::Dec).unwrap();
+    let converted = C3d::from_bytes(&bytes).unwrap();
+    assert!(!converted.diff(&c3d).unwrap().is_empty());
+    assert!(converted.approx_eq(&c3d, &DiffOptions::processor_conversion()));
+
+    // the rounding of another converter is within a few steps, and tiny values are flushed
+    let mut rounded = converted.clone();
+    rounded.points.frame_rate = ulps_above(rounded.points.frame_rate, 1);
+    for frame in 0..rounded.points.rows() {
+        let point = &mut rounded.points[frame][0].point;
+        point[0] = ulps_above(point[0], 2);
+    }
+    rounded.points[0][1].point[2] = 1e-40;
+    rounded.analog[0][0] = rounded.analog[0][0].next_up();
+    let exact = DiffOptions {
+        point_tolerance: 0.,
+        analog_tolerance: 0.,
+        parameter_tolerance: 0.,
+        time_tolerance: 0.,
+        max_ulps: None,
+    };
+    assert!(!rounded.approx_eq(&c3d, &exact));
+    let steps = DiffOptions {
+        max_ulps: Some(4),
+        ..exact
+    };
+    assert!(rounded.approx_eq(&c3d, &steps));
+    assert!(rounded.approx_eq(&c3d, &DiffOptions::processor_conversion()));
+
+    let mut moved = converted.clone();
+    moved.points[3][0].point[0] += 1.;
+    assert!(!moved.approx_eq(&c3d, &DiffOptions::processor_conversion()));
+    let mut integer = converted;
+    integer.convert_format(DataFormat::Integer);
+    assert!(!integer.approx_eq(&c3d, &DiffOptions::processor_conversion()));
+}
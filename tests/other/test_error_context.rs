@@ -0,0 +1,69 @@
+use c3dio::prelude::*;
+use std::error::Error;
+
+/// Gives POINT:LABELS an unknown data type, returning the bytes and the offset of the
+/// parameter in the file.
+fn bytes_with_invalid_labels() -> (Vec<u8>, usize) {
+    let mut bytes = std::fs::read("tests/data/short.c3d").unwrap();
+    let name = bytes
+        .windows(8)
+        .position(|x| x == b"\x06\x02LABELS")
+        .unwrap();
+    bytes[name + 10] = 7;
+    (bytes, name)
+}
+
+#[test]
+fn errors_locate_the_parameter() {
+    let (bytes, offset) = bytes_with_invalid_labels();
+    let error = C3d::from_bytes(&bytes).unwrap_err();
+    assert!(matches!(error.kind(), C3dParseError::InvalidDataType));
+    assert_eq!(error.parameter(), Some(("POINT", "LABELS")));
+    assert_eq!(error.offset(), Some(offset as u64));
+
+    let contexts = error.contexts();
+    assert_eq!(contexts.len(), 2);
+    assert_eq!(contexts[0].part, "the parameter section");
+    assert_eq!(contexts[0].offset, Some(512));
+    assert_eq!(contexts[0].block(), Some(2));
+    assert_eq!(contexts[1].part, "parameter");
+    assert_eq!(contexts[1].block(), Some(offset as u64 / 512 + 1));
+    assert!(matches!(error.into_kind(), C3dParseError::InvalidDataType));
+}
+
+#[test]
+fn errors_are_displayed_with_their_context() {
+    let (bytes, offset) = bytes_with_invalid_labels();
+    let error = C3d::from_bytes(&bytes).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "a parameter has an unknown data type, in parameter POINT:LABELS at byte {} \
+             (block {}), in the parameter section at byte 512 (block 2)",
+            offset,
+            offset / 512 + 1
+        )
+    );
+    let source = error.source().unwrap();
+    assert!(source.to_string().ends_with("(block 3)"));
+    assert_eq!(
+        C3dParseError::Cancelled.to_string(),
+        "loading was cancelled"
+    );
+}
+
+#[test]
+fn errors_of_known_groups_name_the_group() {
+    let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    c3d.parameters.set("EVENT", "USED", 40i16).unwrap();
+    c3d.events.supports_events_labels = true;
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    // the header holds at most 18 events
+    bytes[300] = 40;
+    let error = C3d::from_bytes(&bytes).unwrap_err();
+    assert!(matches!(error.kind(), C3dParseError::TooManyEvents(40)));
+    assert_eq!(error.contexts()[0].group.as_deref(), Some("EVENT"));
+    assert!(error.to_string().ends_with(", in group EVENT"));
+    assert_eq!(error.offset(), None);
+}
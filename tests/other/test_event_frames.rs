@@ -0,0 +1,130 @@
+use c3dio::prelude::*;
+use c3dio::{C3dBuilder, Event, WriteOptions};
+
+/// A trial at 250 Hz starting at frame 37, with an event halfway through every fourth frame.
+fn half_frame_events() -> C3d {
+    let mut builder = C3dBuilder::new()
+        .point_labels(&["M1"])
+        .frame_rate(250.)
+        .first_frame(37);
+    for frame in 0..1000 {
+        builder = builder.push_frame(&[[frame as f32; 3]], &[]).unwrap();
+    }
+    let mut c3d = builder.build().unwrap();
+    for (i, frame) in (0..999).step_by(4).enumerate() {
+        let time = ((36 + frame) as f64 + 0.5) / 250.;
+        c3d.events
+            .add_event("Foot Strike", "Left", time as f32)
+            .description = i.to_string();
+    }
+    c3d
+}
+
+/// Returns the frame of each event in the original trial, by its description.
+fn original_frames(c3d: &C3d) -> Vec<usize> {
+    c3d.events
+        .iter()
+        .map(|event| c3d.event_frame(event).unwrap())
+        .collect()
+}
+
+fn original_frame(frames: &[usize], event: &Event) -> usize {
+    frames[event.description.parse::<usize>().unwrap()]
+}
+
+#[test]
+fn frame_of_an_event() {
+    let mut event = Event::new();
+    assert_eq!(event.frame(100.), Some(1));
+    event.time = 0.015;
+    assert_eq!(event.frame(100.), Some(2));
+    // halfway between two frames is the later one
+    event.time = 0.5 / 128.;
+    assert_eq!(event.frame(128.), Some(2));
+    event.time = -0.5 / 128.;
+    assert_eq!(event.frame(128.), Some(1));
+    event.time = -0.1;
+    assert_eq!(event.frame(100.), None);
+    assert_eq!(event.frame(0.), None);
+
+    event.set_frame(11, 100.);
+    assert_eq!(event.time, 0.1);
+    event.time = 0.1234;
+    event.snap_to_frame(100.);
+    assert_eq!(event.time, 0.12);
+    assert_eq!(event.frame(100.), Some(13));
+}
+
+#[test]
+fn crop_keeps_the_frames_of_events() {
+    let c3d = half_frame_events();
+    let frames = original_frames(&c3d);
+    for start in [0, 1, 7, 500, 997] {
+        let cropped = c3d.crop(start..1000);
+        let kept = frames.iter().filter(|&&frame| frame >= start).count();
+        assert_eq!(cropped.events.len(), kept);
+        for event in cropped.events.iter() {
+            assert_eq!(
+                cropped.event_frame(event).unwrap() + start,
+                original_frame(&frames, event)
+            );
+        }
+    }
+}
+
+#[test]
+fn split_and_concat_keep_the_frames_of_events() {
+    let c3d = half_frame_events();
+    let frames = original_frames(&c3d);
+    let parts = c3d.split_every(333);
+    assert_eq!(parts.len(), 4);
+    let mut start = 0;
+    for part in &parts {
+        for event in part.events.iter() {
+            assert_eq!(
+                part.event_frame(event).unwrap() + start,
+                original_frame(&frames, event)
+            );
+        }
+        start += part.frame_count();
+    }
+    let parts: Vec<&C3d> = parts.iter().collect();
+    let joined = C3d::concat(&parts).unwrap();
+    assert_eq!(joined.events.len(), c3d.events.len());
+    for event in joined.events.iter() {
+        assert_eq!(
+            joined.event_frame(event).unwrap(),
+            original_frame(&frames, event)
+        );
+    }
+}
+
+#[test]
+fn snap_events_on_write() {
+    let c3d = half_frame_events();
+    let frames = original_frames(&c3d);
+    let mut bytes = Vec::new();
+    c3d.write_to_with_options(
+        &mut bytes,
+        &WriteOptions::default().with_snapped_events(true),
+    )
+    .unwrap();
+    let snapped = C3d::from_bytes(&bytes).unwrap();
+    for (event, frame) in snapped.events.iter().zip(&frames) {
+        assert_eq!(snapped.event_frame(event), Some(*frame));
+        let time = ((36 + frame) as f64 / 250.) as f32;
+        assert_eq!(event.time, time);
+    }
+    // the file written from is left unchanged
+    assert_eq!(c3d.events, half_frame_events().events);
+
+    let mut bytes = Vec::new();
+    c3d.write_to_with_options(&mut bytes, &WriteOptions::default())
+        .unwrap();
+    let times = |c3d: &C3d| -> Vec<f32> { c3d.events.iter().map(|event| event.time).collect() };
+    assert_eq!(times(&C3d::from_bytes(&bytes).unwrap()), times(&c3d));
+
+    let mut edited = c3d.clone();
+    edited.snap_events_to_frames();
+    assert_eq!(times(&edited), times(&snapped));
+}
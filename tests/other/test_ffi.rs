@@ -0,0 +1,89 @@
+use c3dio::ffi::*;
+use c3dio::prelude::*;
+use std::ffi::{c_char, CStr, CString};
+
+fn label(read: impl Fn(*mut c_char, usize) -> usize) -> String {
+    let len = read(std::ptr::null_mut(), 0);
+    let mut buffer = vec![0 as c_char; len + 1];
+    assert_eq!(read(buffer.as_mut_ptr(), buffer.len()), len);
+    unsafe { CStr::from_ptr(buffer.as_ptr()) }
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn ffi_reads_like_the_rust_api() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let path = CString::new("tests/data/short.c3d").unwrap();
+    unsafe {
+        let handle = c3dio_load(path.as_ptr());
+        assert!(!handle.is_null());
+        assert_eq!(c3dio_frame_count(handle), c3d.stored_frame_count());
+        assert_eq!(c3dio_point_count(handle), c3d.points.cols());
+        assert_eq!(c3dio_point_rate(handle), c3d.points.frame_rate);
+        assert_eq!(c3dio_analog_channel_count(handle), c3d.analog.cols());
+        assert_eq!(c3dio_analog_sample_count(handle), c3d.analog.rows());
+        assert_eq!(c3dio_analog_rate(handle), c3d.analog.rate);
+        assert_eq!(
+            label(|buffer, len| c3dio_point_label(handle, 1, buffer, len)),
+            c3d.points.labels[1].trim()
+        );
+        assert_eq!(
+            label(|buffer, len| c3dio_analog_label(handle, 0, buffer, len)),
+            c3d.analog.labels[0].trim()
+        );
+
+        let frames = c3dio_frame_count(handle);
+        let mut samples = vec![0f32; frames * 4];
+        assert_eq!(
+            c3dio_read_point(handle, 1, samples.as_mut_ptr(), samples.len()),
+            frames
+        );
+        assert_eq!(samples[4..7], c3d.points[1][1].point);
+        assert_eq!(samples[7], c3d.points[1][1].residual);
+        // a short buffer is filled as far as it goes
+        assert_eq!(c3dio_read_point(handle, 1, samples.as_mut_ptr(), 9), 2);
+
+        let mut analog = vec![0f64; c3d.analog.rows()];
+        assert_eq!(
+            c3dio_read_analog(handle, 0, analog.as_mut_ptr(), analog.len()),
+            c3d.analog.rows()
+        );
+        assert_eq!(analog[3], c3d.analog[3][0]);
+        assert_eq!(
+            c3dio_read_analog(handle, c3d.analog.cols(), analog.as_mut_ptr(), 1),
+            0
+        );
+        c3dio_free(handle);
+    }
+}
+
+#[test]
+fn ffi_round_trips_through_bytes() {
+    let bytes = std::fs::read("tests/data/short.c3d").unwrap();
+    unsafe {
+        let handle = c3dio_from_bytes(bytes.as_ptr(), bytes.len());
+        assert!(!handle.is_null());
+        let mut len = 0;
+        let written = c3dio_write_bytes(handle, &mut len);
+        assert!(!written.is_null());
+        let copy = C3d::from_bytes(std::slice::from_raw_parts(written, len)).unwrap();
+        assert_eq!(copy.points, C3d::from_bytes(&bytes).unwrap().points);
+        c3dio_free_bytes(written, len);
+        c3dio_free(handle);
+    }
+}
+
+#[test]
+fn ffi_reports_failures() {
+    let path = CString::new("tests/data/missing.c3d").unwrap();
+    unsafe {
+        assert!(c3dio_load(path.as_ptr()).is_null());
+        assert!(!label(|buffer, len| c3dio_last_error(buffer, len)).is_empty());
+        assert!(c3dio_from_bytes([0u8; 16].as_ptr(), 16).is_null());
+        assert_eq!(c3dio_frame_count(std::ptr::null()), 0);
+        assert_eq!(c3dio_write(std::ptr::null(), path.as_ptr()), -1);
+        c3dio_free(std::ptr::null_mut());
+    }
+}
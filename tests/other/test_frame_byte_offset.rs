@@ -0,0 +1,49 @@
+use c3dio::prelude::*;
+
+#[test]
+fn frame_byte_offset() {
+    let bytes = std::fs::read("tests/data/short.c3d").unwrap();
+    let c3d = C3d::from_bytes(&bytes).unwrap();
+    let data_start_block = u16::from_le_bytes([bytes[16], bytes[17]]) as u64;
+    let stride = (c3d.points.cols() * 16 + c3d.analog.samples_per_frame as usize * 4) as u64;
+
+    assert_eq!(c3d.frame_byte_offset(0), Some((data_start_block - 1) * 512));
+    for frame in 1..c3d.points.rows() {
+        assert_eq!(
+            c3d.frame_byte_offset(frame),
+            Some(c3d.frame_byte_offset(frame - 1).unwrap() + stride)
+        );
+    }
+
+    let offset = c3d.frame_byte_offset(1).unwrap() as usize;
+    let x = f32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+    assert_eq!(x, c3d.points[1][0][0]);
+}
+
+#[test]
+fn frame_byte_offset_out_of_range() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    assert_eq!(c3d.frame_byte_offset(c3d.points.rows()), None);
+    assert_eq!(C3d::new().frame_byte_offset(0), None);
+}
+
+#[test]
+fn frame_byte_offset_after_conversion() {
+    let c3d = C3d::load("tests/c3d_org_samples/sample_01/Eb015si.c3d").unwrap();
+    assert_eq!(c3d.frame_byte_offset(1), Some(5456));
+    assert_eq!(c3d.as_intel().frame_byte_offset(1), Some(5456));
+
+    for (file, processor) in [
+        (
+            "tests/c3d_org_samples/sample_01/Eb015vi.c3d",
+            Processor::Intel,
+        ),
+        ("tests/data/short.c3d", Processor::SgiMips),
+        ("tests/data/short.c3d", Processor::Dec),
+    ] {
+        let mut c3d = C3d::load(file).unwrap();
+        let offset = c3d.frame_byte_offset(1);
+        c3d.convert_to(processor);
+        assert_eq!(c3d.frame_byte_offset(1), offset, "{}", file);
+    }
+}
@@ -0,0 +1,79 @@
+use c3dio::prelude::*;
+
+#[test]
+fn frame_count() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    assert_eq!(c3d.frame_count(), c3d.points.rows());
+    assert_eq!(c3d.frame_count(), c3d.stored_frame_count());
+    assert_eq!(c3d.frame_count(), c3d.declared_frame_count());
+    assert_eq!(
+        c3d.frame_count() * c3d.analog.samples_per_channel_per_frame as usize,
+        c3d.analog.rows()
+    );
+}
+
+#[test]
+fn frame_count_truncated() {
+    let bytes = std::fs::read("tests/data/short.c3d").unwrap();
+    let c3d = C3d::from_bytes(&bytes).unwrap();
+    let frames_to_keep = c3d.frame_count() / 2;
+    let end = c3d.frame_byte_offset(frames_to_keep).unwrap() as usize;
+
+    let truncated = C3d::from_bytes(&bytes[..end]).unwrap();
+    assert_eq!(truncated.frame_count(), frames_to_keep);
+    assert_eq!(truncated.frame_count(), truncated.points.rows());
+    assert_eq!(truncated.declared_frame_count(), c3d.frame_count());
+    assert_eq!(
+        truncated.frame_count() * truncated.analog.samples_per_channel_per_frame as usize,
+        truncated.analog.rows()
+    );
+}
+
+#[test]
+fn truncated_data_is_reported() {
+    let bytes = std::fs::read("tests/data/short.c3d").unwrap();
+    let c3d = C3d::from_bytes(&bytes).unwrap();
+    assert!(c3d.warnings.is_empty());
+    let frames_to_keep = c3d.frame_count() / 2;
+    // the file ends in the middle of a frame, which is not read
+    let end = c3d.frame_byte_offset(frames_to_keep).unwrap() as usize + 1000;
+    let expected = vec![ParseWarning::TruncatedData(
+        frames_to_keep,
+        c3d.frame_count(),
+    )];
+    for options in [ParseOptions::default(), ParseOptions::lenient()] {
+        let truncated = C3d::from_bytes_with_options(&bytes[..end], options.clone()).unwrap();
+        assert_eq!(truncated.warnings, expected);
+        assert_eq!(truncated.frame_count(), frames_to_keep);
+        assert_eq!(
+            truncated.analog.rows(),
+            frames_to_keep * c3d.analog.samples_per_channel_per_frame as usize
+        );
+        let from_reader =
+            C3d::from_reader_with_options(std::io::Cursor::new(&bytes[..end]), options).unwrap();
+        assert_eq!(from_reader.warnings, expected);
+        assert_eq!(from_reader, truncated);
+    }
+}
+
+#[test]
+fn frame_count_empty() {
+    assert_eq!(C3d::new().frame_count(), 0);
+}
+
+#[test]
+fn files_truncated_anywhere_are_handled() {
+    let bytes = std::fs::read("tests/data/short.c3d").unwrap();
+    let data_start = 512 * (u16::from_le_bytes([bytes[16], bytes[17]]) as usize - 1);
+    for end in (0..bytes.len()).step_by(2039) {
+        for options in [ParseOptions::default(), ParseOptions::lenient()] {
+            let truncated = &bytes[..end];
+            let result = C3d::from_bytes_with_options(truncated, options.clone());
+            let from_reader =
+                C3d::from_reader_with_options(std::io::Cursor::new(truncated), options);
+            // only files that end before their data section cannot be read
+            assert_eq!(result.is_ok(), end >= data_start);
+            assert_eq!(from_reader.is_ok(), end >= data_start);
+        }
+    }
+}
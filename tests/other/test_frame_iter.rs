@@ -0,0 +1,54 @@
+use c3dio::prelude::*;
+use c3dio::Frame;
+use std::io::Cursor;
+use std::path::PathBuf;
+
+fn assert_frames_match_load(path: &str) {
+    let c3d = C3d::load(path).unwrap();
+    let frames = C3d::frames(PathBuf::from(path)).unwrap();
+    assert_eq!(frames.c3d().points.labels, c3d.points.labels);
+    assert_eq!(frames.c3d().parameters, c3d.parameters);
+    let samples = c3d.analog.samples_per_channel_per_frame as usize;
+    let mut num_frames = 0;
+    for (i, frame) in frames.enumerate() {
+        let frame = frame.unwrap();
+        assert_eq!(frame.index, i);
+        let expected: Vec<MarkerPoint> = c3d.points.iter_row(i).cloned().collect();
+        assert_eq!(frame.points, expected);
+        assert_eq!(frame.analog.rows(), samples.min(c3d.analog.rows()));
+        for row in 0..frame.analog.rows() {
+            let expected: Vec<f64> = c3d.analog.iter_row(i * samples + row).cloned().collect();
+            let actual: Vec<f64> = frame.analog.iter_row(row).cloned().collect();
+            assert_eq!(actual, expected);
+        }
+        num_frames += 1;
+    }
+    assert_eq!(num_frames, c3d.stored_frame_count());
+}
+
+#[test]
+fn frames_match_load() {
+    assert_frames_match_load("tests/data/short.c3d");
+    assert_frames_match_load("tests/c3d_org_samples/sample_02/pc_int.c3d");
+    assert_frames_match_load("tests/c3d_org_samples/sample_02/dec_real.c3d");
+    assert_frames_match_load("tests/c3d_org_samples/sample_02/sgi_int.c3d");
+}
+
+#[test]
+fn frames_num_frames() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let frames = C3d::frames(PathBuf::from("tests/data/short.c3d")).unwrap();
+    assert_eq!(frames.num_frames(), c3d.declared_frame_count());
+}
+
+#[test]
+fn frames_stop_at_truncated_data() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let offset = c3d.frame_byte_offset(3).unwrap() as usize;
+    let bytes = std::fs::read("tests/data/short.c3d").unwrap();
+    let frames: Vec<Frame> = C3d::frames_from_reader(Cursor::new(&bytes[..offset + 10]))
+        .unwrap()
+        .map(|frame| frame.unwrap())
+        .collect();
+    assert_eq!(frames.len(), 3);
+}
@@ -0,0 +1,90 @@
+use c3dio::prelude::*;
+use c3dio::{FrameSink, LabelMatch};
+use std::io::Cursor;
+use std::ops::ControlFlow;
+use std::path::PathBuf;
+
+/// Keeps a copy of every frame given to it, to compare with a loaded file.
+#[derive(Default)]
+struct Collect {
+    labels: Vec<String>,
+    points: Vec<MarkerPoint>,
+    analog: Vec<f64>,
+    indices: Vec<usize>,
+}
+
+impl FrameSink for Collect {
+    fn on_start(&mut self, c3d: &C3d) {
+        self.labels = c3d.points.labels.clone();
+    }
+
+    fn on_frame(
+        &mut self,
+        points: &[MarkerPoint],
+        analog: &[f64],
+        index: usize,
+    ) -> ControlFlow<()> {
+        self.points.extend_from_slice(points);
+        self.analog.extend_from_slice(analog);
+        self.indices.push(index);
+        ControlFlow::Continue(())
+    }
+}
+
+#[test]
+fn sink_matches_load() {
+    for path in [
+        "tests/data/short.c3d",
+        "tests/c3d_org_samples/sample_02/dec_real.c3d",
+        "tests/c3d_org_samples/sample_02/sgi_int.c3d",
+    ] {
+        let c3d = C3d::load(path).unwrap();
+        let mut sink = Collect::default();
+        let frames = C3d::stream_frames(PathBuf::from(path), &mut sink).unwrap();
+        assert_eq!(frames, c3d.stored_frame_count(), "{}", path);
+        assert_eq!(sink.labels, c3d.points.labels);
+        assert_eq!(sink.indices, (0..frames).collect::<Vec<_>>());
+        assert_eq!(sink.points, c3d.points.flatten()[..]);
+        assert_eq!(sink.analog, c3d.analog.as_slice());
+    }
+}
+
+#[test]
+fn closure_sink_stops_early() {
+    let bytes = std::fs::read("tests/data/short.c3d").unwrap();
+    let c3d = C3d::from_bytes(&bytes).unwrap();
+    let label = c3d.points.find_label("THEA", LabelMatch::Trimmed).unwrap();
+    let mut sum = [0.; 3];
+    let mut last = 0;
+    let frames = C3d::stream_frames_from_reader(
+        Cursor::new(&bytes),
+        &mut |points: &[MarkerPoint], _: &[f64], index: usize| {
+            for (sum, x) in sum.iter_mut().zip(points[label].point) {
+                *sum += x;
+            }
+            last = index;
+            match index {
+                9 => ControlFlow::Break(()),
+                _ => ControlFlow::Continue(()),
+            }
+        },
+    )
+    .unwrap();
+    assert_eq!((frames, last), (10, 9));
+    let mut expected = [0.; 3];
+    for frame in 0..10 {
+        for (expected, x) in expected.iter_mut().zip(c3d.points[frame][label].point) {
+            *expected += x;
+        }
+    }
+    assert_eq!(sum, expected);
+
+    // the iterator continues after the frames given to a sink
+    let mut iter = C3d::frames_from_reader(Cursor::new(&bytes)).unwrap();
+    let mut sink = |_: &[MarkerPoint], _: &[f64], index: usize| match index {
+        4 => ControlFlow::Break(()),
+        _ => ControlFlow::Continue(()),
+    };
+    assert_eq!(iter.drive(&mut sink).unwrap(), 5);
+    assert_eq!(iter.next().unwrap().unwrap().index, 5);
+}
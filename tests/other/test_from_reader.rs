@@ -0,0 +1,57 @@
+use c3dio::prelude::*;
+use std::fs::File;
+use std::io::{BufReader, Cursor, Seek, SeekFrom};
+
+const FILE: &str = "tests/c3d_org_samples/sample_02/sgi_int.c3d";
+
+#[test]
+fn reader_matches_file_load() {
+    let from_file = C3d::load(FILE).unwrap();
+    let from_buffer = C3d::from_reader(BufReader::new(File::open(FILE).unwrap())).unwrap();
+    let from_cursor = C3d::from_reader(Cursor::new(std::fs::read(FILE).unwrap())).unwrap();
+    assert_eq!(from_buffer, from_file);
+    assert_eq!(from_cursor, from_file);
+    assert_eq!(from_cursor.processor(), from_file.processor());
+}
+
+#[test]
+fn reader_starts_at_current_position() {
+    let mut bytes = vec![0u8; 100];
+    bytes.extend(std::fs::read(FILE).unwrap());
+    let mut cursor = Cursor::new(bytes);
+    cursor.seek(SeekFrom::Start(100)).unwrap();
+    assert_eq!(C3d::from_reader(cursor).unwrap(), C3d::load(FILE).unwrap());
+}
+
+#[test]
+fn reader_with_options() {
+    let bytes = std::fs::read(FILE).unwrap();
+    let c3d = C3d::from_reader_with_options(
+        Cursor::new(bytes.clone()),
+        ParseOptions::default().with_preserved_bytes(),
+    )
+    .unwrap();
+    assert_eq!(c3d.original_bytes(), Some(bytes.as_slice()));
+}
+
+#[test]
+fn frames_from_reader() {
+    let c3d = C3d::load(FILE).unwrap();
+    let frames = C3d::frames_from_reader(Cursor::new(std::fs::read(FILE).unwrap())).unwrap();
+    let mut num_frames = 0;
+    for (i, frame) in frames.enumerate() {
+        let expected: Vec<MarkerPoint> = c3d.points.iter_row(i).cloned().collect();
+        assert_eq!(frame.unwrap().points, expected);
+        num_frames += 1;
+    }
+    assert_eq!(num_frames, c3d.points.rows());
+}
+
+#[test]
+fn reader_too_short() {
+    let bytes = std::fs::read(FILE).unwrap();
+    assert!(matches!(
+        C3d::from_reader(Cursor::new(&bytes[..100])),
+        Err(C3dParseError::ReadError(_))
+    ));
+}
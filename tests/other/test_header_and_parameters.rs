@@ -0,0 +1,44 @@
+use c3dio::prelude::*;
+use std::io::Cursor;
+use std::path::PathBuf;
+
+#[test]
+fn metadata_matches_full_parse() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let metadata = C3d::load_header_and_parameters(PathBuf::from("tests/data/short.c3d")).unwrap();
+    assert_eq!(metadata.points.labels, c3d.points.labels);
+    assert_eq!(metadata.points.frame_rate, c3d.points.frame_rate);
+    assert_eq!(metadata.points.units, c3d.points.units);
+    assert_eq!(metadata.analog.labels, c3d.analog.labels);
+    assert_eq!(metadata.analog.rate, c3d.analog.rate);
+    assert_eq!(metadata.events, c3d.events);
+    assert_eq!(metadata.forces, c3d.forces);
+    assert_eq!(metadata.declared_frame_count(), c3d.declared_frame_count());
+    assert!(metadata.declared_frame_count() > 0);
+}
+
+#[test]
+fn data_section_is_not_read() {
+    let bytes = std::fs::read("tests/data/short.c3d").unwrap();
+    let full = C3d::from_bytes(&bytes).unwrap();
+    let metadata = C3d::header_and_parameters_from_reader(Cursor::new(&bytes)).unwrap();
+    assert_eq!(metadata.points.rows(), 0);
+    assert_eq!(metadata.analog.rows(), 0);
+
+    // a file truncated right after its parameter section still has all its metadata
+    let data_start_block = u16::from_le_bytes([bytes[16], bytes[17]]) as usize;
+    let truncated = &bytes[..512 * (data_start_block - 1)];
+    let metadata = C3d::header_and_parameters_from_reader(Cursor::new(truncated)).unwrap();
+    assert_eq!(metadata.points.labels, full.points.labels);
+    assert_eq!(metadata.declared_frame_count(), full.declared_frame_count());
+}
+
+#[test]
+fn readers_match_the_file_loaders() {
+    let path = PathBuf::from("tests/data/short.c3d");
+    let bytes = std::fs::read(&path).unwrap();
+    let header = C3d::header_from_reader(Cursor::new(&bytes)).unwrap();
+    assert_eq!(header, C3d::load_header(path.clone()).unwrap());
+    let parameters = C3d::parameters_from_reader(Cursor::new(&bytes)).unwrap();
+    assert_eq!(parameters, C3d::load_parameters(path).unwrap());
+}
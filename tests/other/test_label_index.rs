@@ -0,0 +1,71 @@
+use c3dio::prelude::*;
+use c3dio::LabelMatch;
+
+fn plug_in_c3d() -> C3d {
+    C3d::load("tests/c3d_org_samples/sample_09/PlugInC3D.c3d").unwrap()
+}
+
+#[test]
+fn point_by_label() {
+    let c3d = plug_in_c3d();
+    let column = c3d.points.labels.iter().position(|x| x == "RASI").unwrap();
+    let trajectory = c3d.points.get_by_label("RASI").unwrap();
+    assert_eq!(trajectory.len(), c3d.points.rows());
+    for (frame, point) in trajectory.iter().enumerate() {
+        assert_eq!(*point, c3d.points[frame][column]);
+        assert_eq!(*point, c3d.points[(frame, "RASI")]);
+    }
+    assert_eq!(c3d.points.get_by_label(" RASI "), Some(trajectory));
+    assert_eq!(c3d.points.get_by_label("rasi"), None);
+    assert_eq!(c3d.points.get_by_label("NOT_A_MARKER"), None);
+}
+
+#[test]
+fn analog_by_label() {
+    let c3d = plug_in_c3d();
+    let column = c3d.analog.labels.iter().position(|x| x == "FZ1").unwrap();
+    let channel = c3d.analog.get_by_label("FZ1").unwrap();
+    assert_eq!(channel.len(), c3d.analog.rows());
+    for (row, value) in channel.iter().enumerate() {
+        assert_eq!(*value, c3d.analog[(row, column)]);
+        assert_eq!(*value, c3d.analog[(row, "FZ1")]);
+    }
+    assert_eq!(c3d.analog.get_by_label("Fz1"), None);
+}
+
+#[test]
+fn label_matching() {
+    let c3d = plug_in_c3d();
+    let column = c3d.points.find_label("RASI", LabelMatch::Exact);
+    assert!(column.is_some());
+    assert_eq!(c3d.points.find_label(" RASI", LabelMatch::Exact), None);
+    assert_eq!(c3d.points.find_label(" RASI", LabelMatch::Trimmed), column);
+    assert_eq!(
+        c3d.points.find_label(" rAsI", LabelMatch::IgnoreCase),
+        column
+    );
+    assert_eq!(
+        c3d.analog
+            .get_by_label_matching("fz1", LabelMatch::IgnoreCase),
+        c3d.analog.get_by_label("FZ1")
+    );
+}
+
+#[test]
+fn index_mut_by_label() {
+    let mut c3d = plug_in_c3d();
+    c3d.points[(0, "RASI")].point = [1., 2., 3.];
+    c3d.analog[(0, "FZ1")] = 42.;
+    assert_eq!(
+        c3d.points.get_by_label("RASI").unwrap()[0].point,
+        [1., 2., 3.]
+    );
+    assert_eq!(c3d.analog.get_by_label("FZ1").unwrap()[0], 42.);
+}
+
+#[test]
+#[should_panic]
+fn index_by_missing_label() {
+    let c3d = plug_in_c3d();
+    let _ = c3d.points[(0, "NOT_A_MARKER")];
+}
@@ -0,0 +1,241 @@
+use c3dio::prelude::*;
+use c3dio::DuplicatePolicy;
+
+fn bytes_with_processor_byte(byte: u8) -> Vec<u8> {
+    let mut bytes = std::fs::read("tests/data/short.c3d").unwrap();
+    let parameter_start_block = bytes[0] as usize;
+    bytes[512 * (parameter_start_block - 1) + 3] = byte;
+    bytes
+}
+
+#[test]
+fn unknown_processor_byte_is_error_in_strict_mode() {
+    let bytes = bytes_with_processor_byte(0x42);
+    assert!(matches!(
+        C3d::from_bytes_with_options(&bytes, ParseOptions::strict()),
+        Err(C3dParseError::InvalidProcessorType)
+    ));
+}
+
+#[test]
+fn unknown_processor_byte_is_error_by_default() {
+    let bytes = bytes_with_processor_byte(0x99);
+    assert!(matches!(
+        C3d::from_bytes(&bytes),
+        Err(C3dParseError::InvalidProcessorType)
+    ));
+    assert!(matches!(
+        C3d::from_bytes_with_options(&bytes, ParseOptions::default()),
+        Err(C3dParseError::InvalidProcessorType)
+    ));
+}
+
+#[test]
+fn unknown_processor_byte_defaults_to_intel_in_lenient_mode() {
+    let bytes = bytes_with_processor_byte(0x42);
+    let c3d = C3d::from_bytes_with_options(&bytes, ParseOptions::lenient()).unwrap();
+    assert_eq!(c3d.processor(), Processor::Intel);
+    assert_eq!(c3d.warnings, vec![ParseWarning::UnknownProcessorByte(0x42)]);
+    assert_eq!(c3d, C3d::load("tests/data/short.c3d").unwrap());
+}
+
+#[test]
+fn valid_file_has_no_warnings() {
+    let c3d = C3d::load_with_options("tests/data/short.c3d", ParseOptions::lenient()).unwrap();
+    assert!(c3d.warnings.is_empty());
+}
+
+/// Renames MANUFACTURER:VERSION to COMPANY, so the group defines COMPANY twice.
+fn bytes_with_duplicate_parameter() -> Vec<u8> {
+    let mut bytes = std::fs::read("tests/data/short.c3d").unwrap();
+    let index = bytes
+        .windows(9)
+        .position(|x| x == b"\x07\x05Version")
+        .unwrap();
+    bytes[index + 2..index + 9].copy_from_slice(b"Company");
+    bytes
+}
+
+#[test]
+fn duplicate_parameter_is_error_in_strict_mode() {
+    let bytes = bytes_with_duplicate_parameter();
+    match C3d::from_bytes_with_options(&bytes, ParseOptions::strict())
+        .map_err(C3dParseError::into_kind)
+    {
+        Err(C3dParseError::DuplicateParameter(group, parameter)) => {
+            assert_eq!(group, "MANUFACTURER");
+            assert_eq!(parameter, "COMPANY");
+        }
+        result => panic!("expected DuplicateParameter, got {:?}", result),
+    }
+}
+
+#[test]
+fn duplicate_parameter_keeps_first_in_lenient_mode() {
+    let bytes = bytes_with_duplicate_parameter();
+    let c3d = C3d::from_bytes_with_options(&bytes, ParseOptions::lenient()).unwrap();
+    let original = C3d::load("tests/data/short.c3d").unwrap();
+    assert_eq!(c3d.manufacturer.company, original.manufacturer.company);
+    assert_eq!(c3d.manufacturer.version, None);
+    assert_eq!(
+        c3d.warnings,
+        vec![ParseWarning::DuplicateParameter(
+            "MANUFACTURER".to_string(),
+            "COMPANY".to_string()
+        )]
+    );
+}
+
+/// Renames MANUFACTURER:COMPANY to VERSION, so the group defines VERSION twice,
+/// first as text and then as numbers.
+fn bytes_with_duplicate_version() -> Vec<u8> {
+    let mut bytes = std::fs::read("tests/data/short.c3d").unwrap();
+    let index = bytes
+        .windows(9)
+        .position(|x| x == b"\x07\x05Company")
+        .unwrap();
+    bytes[index + 2..index + 9].copy_from_slice(b"Version");
+    bytes
+}
+
+#[test]
+fn duplicate_policy_chooses_the_definition_kept() {
+    let bytes = bytes_with_duplicate_version();
+    let parse = |policy| {
+        let options = ParseOptions::default().with_duplicates(policy);
+        C3d::from_bytes_with_options(&bytes, options).unwrap()
+    };
+    let first = parse(DuplicatePolicy::FirstWins);
+    assert_eq!(
+        first.manufacturer.version,
+        Some(ManufacturerVersion::String(
+            "Motion Analysis Corp.".to_string()
+        ))
+    );
+    let last = parse(DuplicatePolicy::LastWins);
+    assert_eq!(
+        last.manufacturer.version,
+        Some(ManufacturerVersion::Array(vec![7, 2, 16]))
+    );
+    for c3d in [first, last] {
+        assert_eq!(c3d.manufacturer.company, None);
+        assert_eq!(
+            c3d.warnings,
+            vec![ParseWarning::DuplicateParameter(
+                "MANUFACTURER".to_string(),
+                "VERSION".to_string()
+            )]
+        );
+    }
+}
+
+#[test]
+fn every_definition_of_a_duplicate_parameter_is_kept() {
+    let bytes = bytes_with_duplicate_version();
+    let c3d = C3d::from_bytes_with_options(&bytes, ParseOptions::lenient()).unwrap();
+    assert_eq!(
+        c3d.parameters.duplicates(),
+        vec![("MANUFACTURER", "VERSION")]
+    );
+    let definitions = c3d.parameters.definitions("MANUFACTURER", "VERSION");
+    assert_eq!(definitions.len(), 2);
+    assert_eq!(definitions[0].description, "Company name");
+    assert_eq!(definitions[1].data, ParameterData::Integer(vec![7, 2, 16]));
+    assert!(c3d
+        .parameters
+        .definitions("MANUFACTURER", "COMPANY")
+        .is_empty());
+    assert_eq!(c3d.parameters.definitions("SUBJECTS", "USED").len(), 1);
+}
+
+#[test]
+fn duplicate_groups_are_merged() {
+    // renames the EVENT group to POINT, so both groups define USED, LABELS and DESCRIPTIONS
+    let mut bytes = std::fs::read("tests/data/short.c3d").unwrap();
+    let index = bytes
+        .windows(7)
+        .position(|x| x == b"\x05\xf9EVENT")
+        .unwrap();
+    bytes[index + 2..index + 7].copy_from_slice(b"POINT");
+    assert!(matches!(
+        C3d::from_bytes_with_options(&bytes, ParseOptions::strict())
+            .map_err(C3dParseError::into_kind),
+        Err(C3dParseError::DuplicateParameter(..))
+    ));
+    let options = ParseOptions::default().with_duplicates(DuplicatePolicy::FirstWins);
+    let c3d = C3d::from_bytes_with_options(&bytes, options).unwrap();
+    assert_eq!(
+        c3d.warnings[0],
+        ParseWarning::DuplicateGroup("POINT".to_string())
+    );
+    assert_eq!(
+        c3d.parameters.duplicates(),
+        vec![
+            ("POINT", "USED"),
+            ("POINT", "LABELS"),
+            ("POINT", "DESCRIPTIONS")
+        ]
+    );
+    assert_eq!(
+        c3d.points,
+        C3d::load("tests/data/short.c3d").unwrap().points
+    );
+}
+
+#[test]
+fn invalid_description_is_read_lossily() {
+    let mut bytes = std::fs::read("tests/data/short.c3d").unwrap();
+    let index = bytes.windows(11).position(|x| x == b"Information").unwrap();
+    bytes[index] = 0xFF;
+    let c3d = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(
+        c3d.warnings,
+        vec![ParseWarning::InvalidDescription("MANUFACTURER".to_string())]
+    );
+    assert!(c3d
+        .parameters
+        .get_group_description("MANUFACTURER")
+        .unwrap()
+        .starts_with("\u{FFFD}nformation about"));
+    let c3d = C3d::from_bytes_with_options(&bytes, ParseOptions::lenient()).unwrap();
+    assert_eq!(c3d.warnings.len(), 1);
+    assert_eq!(
+        c3d.points,
+        C3d::load("tests/data/short.c3d").unwrap().points
+    );
+}
+
+#[test]
+fn frame_count_mismatch_is_error_in_strict_mode() {
+    let file = "tests/c3d_org_samples/sample_13/Dance1.c3d";
+    assert!(matches!(
+        C3d::load_with_options(file, ParseOptions::strict()),
+        Err(C3dParseError::NumFramesMismatch(498, 499))
+    ));
+    let c3d = C3d::load_with_options(file, ParseOptions::lenient()).unwrap();
+    // the data section only holds the 498 frames of the header
+    assert_eq!(
+        c3d.warnings,
+        vec![
+            ParseWarning::FrameCountMismatch(498, 499),
+            ParseWarning::TruncatedData(498, 499)
+        ]
+    );
+    assert!(c3d.points.rows() > 0);
+}
+
+#[test]
+fn trailing_data_is_reported() {
+    let mut bytes = std::fs::read("tests/data/short.c3d").unwrap();
+    bytes.extend(vec![0; 1024]);
+    let c3d = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(c3d.warnings.len(), 1);
+    assert!(matches!(
+        c3d.warnings[0],
+        ParseWarning::TrailingData(trailing) if trailing >= 1024
+    ));
+    assert_eq!(
+        c3d.points,
+        C3d::load("tests/data/short.c3d").unwrap().points
+    );
+}
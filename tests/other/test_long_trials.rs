@@ -0,0 +1,115 @@
+use c3dio::prelude::*;
+use c3dio::C3dBuilder;
+use std::ops::ControlFlow;
+
+const FRAMES: usize = 70000;
+
+fn long_trial() -> C3d {
+    let mut builder = C3dBuilder::new()
+        .frame_rate(100.)
+        .point_labels(&["A"])
+        .analog_channels(&["Fz"], 1);
+    for i in 0..FRAMES {
+        builder = builder.push_frame(&[[i as f32; 3]], &[i as f64]).unwrap();
+    }
+    builder.build().unwrap()
+}
+
+fn written(c3d: &C3d) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    bytes
+}
+
+/// Replaces every occurrence of a parameter name, so readers no longer recognise it.
+fn rename(bytes: &mut [u8], name: &[u8], new_name: &[u8]) {
+    for i in 0..bytes.len() - name.len() {
+        if &bytes[i..i + name.len()] == name {
+            bytes[i..i + name.len()].copy_from_slice(new_name);
+        }
+    }
+}
+
+#[test]
+fn long_trial_round_trip() {
+    let c3d = long_trial();
+    assert_eq!(c3d.points.first_frame, 1);
+    assert_eq!(c3d.points.last_frame, u16::MAX);
+    let bytes = written(&c3d);
+    assert_eq!(u16::from_le_bytes([bytes[8], bytes[9]]), u16::MAX);
+    assert!(C3d::validate_bytes(&bytes).unwrap().is_valid());
+
+    let reloaded = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(reloaded.frame_count(), FRAMES);
+    assert_eq!(reloaded.declared_frame_count(), FRAMES);
+    assert_eq!(
+        reloaded.points[(FRAMES - 1, 0)].point,
+        [(FRAMES - 1) as f32; 3]
+    );
+    assert_eq!(reloaded.analog[(FRAMES - 1, 0)], (FRAMES - 1) as f64);
+    assert_eq!(
+        C3d::from_bytes(&written(&reloaded)).unwrap().points,
+        reloaded.points
+    );
+}
+
+#[test]
+fn long_trial_frames() {
+    let bytes = written(&long_trial());
+    let frames = C3d::frames_from_reader(std::io::Cursor::new(bytes)).unwrap();
+    assert_eq!(frames.num_frames(), FRAMES);
+    assert_eq!(frames.count(), FRAMES);
+}
+
+#[test]
+fn saturated_frame_count() {
+    let mut bytes = written(&long_trial());
+    // without TRIAL:ACTUAL_END_FIELD the header and a POINT:FRAMES of 65535 are all there is
+    rename(&mut bytes, b"ACTUAL_END_FIELD", b"ACTUAL_END_FIELX");
+    let frames = bytes.windows(6).position(|x| x == b"FRAMES").unwrap();
+    // the name is followed by the offset to the next parameter, the type and the dimensions
+    let dimensions = frames + 6 + 2 + 1;
+    assert_eq!(bytes[dimensions - 1], 4);
+    let value = dimensions + 1 + bytes[dimensions] as usize;
+    bytes[value..value + 4].copy_from_slice(&(u16::MAX as f32).to_le_bytes());
+    // such files are read to their end, so the padding of the last block is cut off,
+    // leaving the 16 bytes of the point and 4 bytes of the analog sample of each frame
+    let data_start = 512 * (u16::from_le_bytes([bytes[16], bytes[17]]) as usize - 1);
+    bytes.truncate(data_start + FRAMES * 20);
+
+    let c3d = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(c3d.frame_count(), FRAMES);
+    let frames = C3d::frames_from_reader(std::io::Cursor::new(bytes)).unwrap();
+    assert_eq!(frames.count(), FRAMES);
+}
+
+#[test]
+fn saturated_frame_count_without_samples() {
+    let mut builder = C3dBuilder::new().frame_rate(100.);
+    for _ in 0..u16::MAX {
+        builder = builder.push_frame(&[], &[]).unwrap();
+    }
+    let mut bytes = written(&builder.build().unwrap());
+    assert_eq!(u16::from_le_bytes([bytes[2], bytes[3]]), 0);
+    assert_eq!(u16::from_le_bytes([bytes[4], bytes[5]]), 0);
+    assert_eq!(u16::from_le_bytes([bytes[8], bytes[9]]), u16::MAX);
+    // the builder stores POINT:FRAMES from the points, of which there are none
+    let frames = bytes.windows(6).position(|x| x == b"FRAMES").unwrap();
+    let dimensions = frames + 6 + 2 + 1;
+    assert_eq!(bytes[dimensions - 1], 4);
+    let value = dimensions + 1 + bytes[dimensions] as usize;
+    bytes[value..value + 4].copy_from_slice(&(u16::MAX as f32).to_le_bytes());
+
+    // frames without any samples take no bytes, so only the declared frames are read
+    let mut frames = C3d::frames_from_reader(std::io::Cursor::new(&bytes)).unwrap();
+    assert_eq!(frames.num_frames(), u16::MAX as usize);
+    let count = frames.by_ref().take(u16::MAX as usize + 1).count();
+    assert_eq!(count, u16::MAX as usize);
+    assert!(frames.next().is_none());
+    let mut frames = C3d::frames_from_reader(std::io::Cursor::new(&bytes)).unwrap();
+    let mut sink = |_: &[MarkerPoint], _: &[f64], index: usize| match index {
+        65535.. => ControlFlow::Break(()),
+        _ => ControlFlow::Continue(()),
+    };
+    assert_eq!(frames.drive(&mut sink).unwrap(), u16::MAX as usize);
+}
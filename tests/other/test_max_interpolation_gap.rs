@@ -0,0 +1,27 @@
+use c3dio::prelude::*;
+use test_files::TestFiles;
+
+#[test]
+fn max_interpolation_gap() {
+    for file in ["pc_int", "dec_int", "sgi_int"] {
+        let c3d = C3d::load(&format!("tests/c3d_org_samples/sample_02/{}.c3d", file)).unwrap();
+        assert_eq!(c3d.max_interpolation_gap(), Some(10));
+    }
+}
+
+#[test]
+fn max_interpolation_gap_not_set() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    assert_eq!(c3d.max_interpolation_gap(), None);
+    assert_eq!(C3d::new().max_interpolation_gap(), None);
+}
+
+#[test]
+fn max_interpolation_gap_preserved_on_write() {
+    let c3d = C3d::load("tests/c3d_org_samples/sample_02/pc_real.c3d").unwrap();
+    let files = TestFiles::new();
+    let path = files.path().join("max_interpolation_gap.c3d");
+    c3d.write_path(path.clone()).unwrap();
+    let written = C3d::load_header(path).unwrap();
+    assert_eq!(written.max_interpolation_gap(), Some(10));
+}
@@ -0,0 +1,85 @@
+use c3dio::prelude::*;
+use c3dio::LabelCollision;
+
+const FILE: &str = "tests/c3d_org_samples/sample_09/PlugInC3D.c3d";
+
+fn load_labels(labels: &[String]) -> C3d {
+    let labels: Vec<&str> = labels.iter().map(|label| label.as_str()).collect();
+    C3d::load_with_options(FILE, ParseOptions::default().with_point_labels(&labels)).unwrap()
+}
+
+#[test]
+fn merge_points() {
+    let c3d = C3d::load(FILE).unwrap();
+    // markers and model outputs
+    let first = load_labels(&c3d.points.labels[..18]);
+    let second = load_labels(&c3d.points.labels[18..]);
+    let merged = first.merge_points(&second, LabelCollision::Error).unwrap();
+    assert_eq!(merged.points.labels, c3d.points.labels);
+    assert_eq!(merged.points.descriptions.len(), c3d.points.cols());
+    assert_eq!(merged.points.flatten(), c3d.points.flatten());
+    assert_eq!(merged.point_kinds(), c3d.point_kinds());
+    assert_eq!(merged.analog, first.analog);
+
+    let mut bytes = Vec::new();
+    merged.write_to(&mut bytes, merged.processor()).unwrap();
+    assert!(C3d::validate_bytes(&bytes).unwrap().is_valid());
+    assert_eq!(C3d::from_bytes(&bytes).unwrap().points, merged.points);
+}
+
+#[test]
+fn merge_points_collisions() {
+    let c3d = C3d::load(FILE).unwrap();
+    let label = c3d.points.labels[0].trim().to_string();
+    assert_eq!(
+        c3d.merge_points(&c3d, LabelCollision::Error).unwrap_err(),
+        C3dMergeError::LabelCollision(label.clone())
+    );
+    let skipped = c3d.merge_points(&c3d, LabelCollision::Skip).unwrap();
+    assert_eq!(skipped.points, c3d.points);
+
+    let prefixed = c3d
+        .merge_points(&c3d, LabelCollision::Prefix("B:".to_string()))
+        .unwrap();
+    assert_eq!(prefixed.points.cols(), 2 * c3d.points.cols());
+    assert_eq!(
+        prefixed.points.labels[c3d.points.cols()],
+        format!("B:{}", label)
+    );
+    assert_eq!(
+        prefixed
+            .points
+            .iter_col(c3d.points.cols())
+            .collect::<Vec<_>>(),
+        c3d.points.iter_col(0).collect::<Vec<_>>()
+    );
+    // labels that collide again after prefixing are not merged
+    assert!(prefixed
+        .merge_points(&c3d, LabelCollision::Prefix("B:".to_string()))
+        .is_err());
+}
+
+#[test]
+fn merge_points_resampled() {
+    let c3d = C3d::load(FILE).unwrap();
+    let half = c3d.crop(0..141);
+    let merged = c3d
+        .merge_points(&half, LabelCollision::Prefix("Half:".to_string()))
+        .unwrap();
+    assert_eq!(merged.frame_count(), c3d.frame_count());
+    let column = c3d.points.cols();
+    assert_eq!(merged.points[0][column], half.points[0][0]);
+    assert_eq!(merged.points[2][column], half.points[1][0]);
+    assert_eq!(merged.points[280][column], half.points[140][0]);
+}
+
+#[test]
+fn merge_points_frame_rate_mismatch() {
+    let c3d = C3d::load(FILE).unwrap();
+    let mut other = c3d.clone();
+    other.points.frame_rate *= 2.;
+    assert!(matches!(
+        c3d.merge_points(&other, LabelCollision::Skip),
+        Err(C3dMergeError::FrameRateMismatch(..))
+    ));
+}
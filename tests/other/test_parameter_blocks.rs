@@ -0,0 +1,82 @@
+use c3dio::prelude::*;
+use std::io::Cursor;
+
+/// Files whose parameter section starts after block 2, or whose data starts after
+/// unused blocks following the parameters.
+const OFFSET_SECTIONS: [&str; 3] = [
+    "tests/c3d_org_samples/sample_08/TESTBPI.c3d",
+    "tests/c3d_org_samples/sample_08/TESTDPI.c3d",
+    "tests/c3d_org_samples/sample_23/Vicon_analysis.c3d",
+];
+
+/// Adds parameters taking about 300 blocks, more than the block count byte can hold.
+fn with_many_blocks(mut c3d: C3d) -> C3d {
+    for i in 0..150 {
+        let values: Vec<f32> = (0..250).map(|j| (i * 250 + j) as f32).collect();
+        c3d.parameters
+            .set("BIG", &format!("P{}", i), values)
+            .unwrap();
+    }
+    c3d
+}
+
+#[test]
+fn parameters_start_at_the_block_given_by_the_header() {
+    for file in OFFSET_SECTIONS {
+        let bytes = std::fs::read(file).unwrap();
+        let loaded = C3d::load(file).unwrap();
+        let parsed = C3d::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed.parameters, loaded.parameters, "{}", file);
+        assert_eq!(parsed.points, loaded.points, "{}", file);
+        assert!(C3d::validate_bytes(&bytes).unwrap().is_valid(), "{}", file);
+    }
+}
+
+#[test]
+fn sections_of_many_blocks_round_trip() {
+    let c3d = with_many_blocks(C3d::load("tests/data/short.c3d").unwrap());
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, Processor::Intel).unwrap();
+    let data_start = u16::from_le_bytes([bytes[16], bytes[17]]) as usize;
+    assert!(data_start > 2 + 255);
+    assert_eq!(bytes[512 + 2], 255);
+
+    let parsed = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(parsed.parameters, c3d.parameters);
+    assert_eq!(parsed.points, c3d.points);
+    assert_eq!(C3d::from_reader(Cursor::new(&bytes)).unwrap(), parsed);
+    assert!(C3d::validate_bytes(&bytes).unwrap().issues.is_empty());
+}
+
+#[test]
+fn data_moves_when_the_parameters_grow() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, Processor::Intel).unwrap();
+    let mut grown = Vec::new();
+    with_many_blocks(c3d)
+        .write_to(&mut grown, Processor::Intel)
+        .unwrap();
+    let data_start = |bytes: &[u8]| u16::from_le_bytes([bytes[16], bytes[17]]) as usize;
+    assert!(data_start(&grown) > data_start(&bytes) + 255);
+    let data = |bytes: &[u8]| bytes[512 * (data_start(bytes) - 1)..].to_vec();
+    assert_eq!(data(&grown), data(&bytes));
+}
+
+#[test]
+fn parameters_too_large_to_skip_are_rejected() {
+    let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let data = ParameterData::Float(vec![0.; 255 * 255]);
+    for name in ["A", "B"] {
+        let parameter = Parameter::new(String::new(), vec![255, 255], data.clone()).unwrap();
+        c3d.parameters.insert("HUGE", name, parameter);
+    }
+    let mut bytes = Vec::new();
+    match c3d.write_to(&mut bytes, Processor::Intel) {
+        Err(C3dWriteError::ParameterTooLarge(name)) => assert_eq!(name, "A"),
+        other => panic!(
+            "expected the parameter to be rejected, got {:?}",
+            other.err()
+        ),
+    }
+}
@@ -0,0 +1,205 @@
+use c3dio::prelude::*;
+use c3dio::ParseLimits;
+use std::io::Cursor;
+
+fn short() -> Vec<u8> {
+    std::fs::read("tests/data/short.c3d").unwrap()
+}
+
+fn limited(limits: ParseLimits) -> ParseOptions {
+    ParseOptions::default().with_limits(limits)
+}
+
+fn limit_exceeded(result: Result<C3d, C3dParseError>) -> (String, usize, usize) {
+    match result.map_err(C3dParseError::into_kind) {
+        Err(C3dParseError::LimitExceeded(what, size, limit)) => (what, size, limit),
+        other => panic!("expected a limit to be exceeded, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn untrusted_limits_accept_ordinary_files() {
+    let bytes = short();
+    let options = limited(ParseLimits::untrusted());
+    let c3d = C3d::from_bytes_with_options(&bytes, options.clone()).unwrap();
+    assert_eq!(c3d, C3d::from_bytes(&bytes).unwrap());
+    assert!(C3d::from_reader_with_options(Cursor::new(&bytes), options).is_ok());
+}
+
+#[test]
+fn frames_are_limited() {
+    let bytes = short();
+    let frames = C3d::from_bytes(&bytes).unwrap().frame_count();
+    let limits = ParseLimits {
+        max_frames: Some(frames - 1),
+        ..ParseLimits::default()
+    };
+    let result = C3d::from_bytes_with_options(&bytes, limited(limits.clone()));
+    assert_eq!(
+        limit_exceeded(result),
+        ("frames".to_string(), frames, frames - 1)
+    );
+    let limits = ParseLimits {
+        max_frames: Some(frames),
+        ..limits
+    };
+    assert!(C3d::from_bytes_with_options(&bytes, limited(limits)).is_ok());
+}
+
+#[test]
+fn parameters_are_limited() {
+    let limits = ParseLimits {
+        max_parameters: Some(10),
+        ..ParseLimits::default()
+    };
+    let result = C3d::from_bytes_with_options(&short(), limited(limits));
+    assert_eq!(limit_exceeded(result), ("parameters".to_string(), 11, 10));
+}
+
+#[test]
+fn label_length_is_limited() {
+    let limits = ParseLimits {
+        max_label_length: Some(2),
+        ..ParseLimits::default()
+    };
+    let error = C3d::from_bytes_with_options(&short(), limited(limits)).unwrap_err();
+    let (_, parameter) = error.parameter().unwrap();
+    assert!(parameter.starts_with("LABELS"));
+    assert!(matches!(
+        error.kind(),
+        C3dParseError::LimitExceeded(what, _, 2) if what == "characters in a label"
+    ));
+}
+
+#[test]
+fn allocation_is_limited() {
+    let bytes = short();
+    let limits = ParseLimits {
+        max_allocation: Some(bytes.len()),
+        ..ParseLimits::default()
+    };
+    // the decoded points take more memory than the bytes they are stored in
+    let (what, size, _) = limit_exceeded(C3d::from_bytes_with_options(
+        &bytes,
+        limited(limits.clone()),
+    ));
+    assert_eq!(what, "bytes of memory");
+    assert!(size > bytes.len());
+
+    let limits = ParseLimits {
+        max_allocation: Some(1000),
+        ..ParseLimits::default()
+    };
+    let options = limited(limits).with_preserved_bytes();
+    let (_, size, limit) =
+        limit_exceeded(C3d::from_reader_with_options(Cursor::new(&bytes), options));
+    assert_eq!((size, limit), (1001, 1000));
+}
+
+#[test]
+fn header_sizes_do_not_allocate_frames() {
+    let mut bytes = short();
+    // the most points and frames the header can declare
+    bytes[2..4].copy_from_slice(&u16::MAX.to_le_bytes());
+    bytes[6..8].copy_from_slice(&1u16.to_le_bytes());
+    bytes[8..10].copy_from_slice(&u16::MAX.to_le_bytes());
+    let c3d = C3d::header_from_reader(Cursor::new(&bytes)).unwrap();
+    assert_eq!(c3d.points.rows(), 0);
+    assert_eq!(c3d.points.last_frame, u16::MAX);
+}
+
+#[test]
+fn invalid_start_blocks_are_errors() {
+    let mut bytes = short();
+    bytes[0] = 0;
+    assert!(matches!(
+        C3d::from_bytes(&bytes).map_err(C3dParseError::into_kind),
+        Err(C3dParseError::InvalidParameterStartBlock)
+    ));
+    assert!(matches!(
+        C3d::from_reader(Cursor::new(&bytes)).map_err(C3dParseError::into_kind),
+        Err(C3dParseError::InvalidParameterStartBlock)
+    ));
+
+    let mut bytes = short();
+    bytes[16..18].copy_from_slice(&2u16.to_le_bytes());
+    assert!(matches!(
+        C3d::from_reader(Cursor::new(&bytes)).map_err(C3dParseError::into_kind),
+        Err(C3dParseError::InvalidDataStartBlock)
+    ));
+}
+
+#[test]
+fn parameter_sizes_are_checked_against_the_section() {
+    let mut bytes = short();
+    let name = bytes
+        .windows(8)
+        .position(|x| x == b"\x06\x02LABELS")
+        .unwrap();
+    // a parameter with as many dimensions as a byte can count
+    bytes[name + 11] = 255;
+    assert!(C3d::from_bytes(&bytes).is_err());
+
+    // a parameter section ending in the middle of a parameter
+    let mut bytes = short();
+    bytes[16..18].copy_from_slice(&3u16.to_le_bytes());
+    assert!(C3d::from_bytes(&bytes).is_err());
+    assert!(C3d::from_reader(Cursor::new(&bytes)).is_err());
+}
+
+#[test]
+fn missing_required_parameters_are_errors() {
+    let mut bytes = short();
+    let name = bytes
+        .windows(9)
+        .position(|x| x[0] == 7 && &x[2..] == b"CHANNEL")
+        .unwrap();
+    // renames FORCE_PLATFORM:CHANNEL, which the platforms in use require
+    bytes[name + 8] = b'#';
+    assert!(matches!(
+        C3d::from_bytes(&bytes).map_err(C3dParseError::into_kind),
+        Err(C3dParseError::ParameterNotFound(_, _))
+    ));
+    assert!(C3d::repair(&bytes).is_err());
+    assert!(!C3d::validate_bytes(&bytes).unwrap().is_valid());
+}
+
+#[test]
+fn group_ids_without_a_positive_counterpart_are_errors() {
+    let mut bytes = short();
+    // the ID of the first group, after the parameter header and its name length
+    let first_group = (bytes[0] as usize - 1) * 512 + 4;
+    bytes[first_group + 1] = 0x80;
+    let invalid_group_id = |result: Result<C3d, C3dParseError>| {
+        matches!(
+            result.map_err(C3dParseError::into_kind),
+            Err(C3dParseError::InvalidGroupId)
+        )
+    };
+    assert!(invalid_group_id(C3d::from_bytes(&bytes)));
+    assert!(invalid_group_id(C3d::from_reader(Cursor::new(&bytes))));
+    assert!(invalid_group_id(C3d::header_and_parameters_from_reader(
+        Cursor::new(&bytes)
+    )));
+    assert!(C3d::frames_from_reader(Cursor::new(&bytes)).is_err());
+    assert!(C3d::repair(&bytes).is_err());
+    assert!(C3d::validate_bytes(&bytes).is_err());
+}
+
+#[test]
+fn huge_frame_counts_are_bounded_by_the_data() {
+    let mut bytes = short();
+    let frames = C3d::from_bytes(&bytes).unwrap().frame_count();
+    let name = bytes
+        .windows(8)
+        .position(|x| x == b"\x06\x02FRAMES")
+        .unwrap();
+    // POINT:FRAMES as a float far larger than any file, over the start of its
+    // description, which is emptied
+    let data_type = name + 8 + 2;
+    bytes[data_type] = 4;
+    bytes[data_type + 2..data_type + 6].copy_from_slice(&1e30f32.to_le_bytes());
+    bytes[data_type + 6] = 0;
+    let c3d = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(c3d.frame_count(), frames);
+}
@@ -0,0 +1,68 @@
+use c3dio::prelude::*;
+
+fn write_with_rate(frame_rate: f32) -> Vec<u8> {
+    let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    c3d.points.frame_rate = frame_rate;
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    bytes
+}
+
+/// Stores the POINT:RATE parameter of a written file as an integer.
+/// The parameter length is kept by starting an empty description right after the data.
+fn store_rate_as_integer(bytes: &mut [u8], rate: u8) {
+    let parameter_start = (bytes[0] as usize - 1) * 512;
+    let parameters = &bytes[parameter_start..];
+    let group = parameters
+        .windows(7)
+        .position(|window| window[0] == 5 && &window[2..7] == b"POINT")
+        .unwrap();
+    let group_id = (parameters[group + 1] as i8).unsigned_abs();
+    let parameter = parameters
+        .windows(6)
+        .position(|window| window[0] == 4 && window[1] == group_id && &window[2..6] == b"RATE")
+        .unwrap();
+    let type_position = parameter_start + parameter + 8;
+    let data_position = type_position + 2 + bytes[type_position + 1] as usize;
+    bytes[type_position] = 2;
+    bytes[data_position..data_position + 4].copy_from_slice(&[rate, 0, 0, 0]);
+}
+
+#[test]
+fn non_integer_float_rate() {
+    let bytes = write_with_rate(59.94);
+    let c3d = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(c3d.points.frame_rate, 59.94);
+    let times = c3d.points.frame_times();
+    assert_eq!(times.len(), c3d.points.rows());
+    assert_eq!(times[0], 0.);
+    for pair in times.windows(2) {
+        assert!((pair[1] - pair[0] - 1. / 59.94).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn integer_rate() {
+    let mut bytes = write_with_rate(60.);
+    store_rate_as_integer(&mut bytes, 60);
+    let c3d = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(c3d.points.frame_rate, 60.);
+}
+
+#[test]
+fn integer_rate_keeps_non_integer_header_rate() {
+    let mut bytes = write_with_rate(59.94);
+    store_rate_as_integer(&mut bytes, 60);
+    let c3d = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(c3d.points.frame_rate, 59.94);
+}
+
+#[test]
+fn integer_rate_mismatch() {
+    let mut bytes = write_with_rate(59.94);
+    store_rate_as_integer(&mut bytes, 50);
+    assert!(matches!(
+        C3d::from_bytes(&bytes).map_err(C3dParseError::into_kind),
+        Err(C3dParseError::FrameRateMismatch(_, _))
+    ));
+}
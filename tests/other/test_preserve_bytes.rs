@@ -0,0 +1,131 @@
+use c3dio::prelude::*;
+
+const FILE: &str = "tests/c3d_org_samples/sample_02/pc_int.c3d";
+
+fn load() -> C3d {
+    C3d::load_with_options(FILE, ParseOptions::default().with_preserved_bytes()).unwrap()
+}
+
+fn write(c3d: &C3d) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    bytes
+}
+
+#[test]
+fn unmodified_file_is_written_verbatim() {
+    let original = std::fs::read(FILE).unwrap();
+    let c3d = load();
+    assert!(!c3d.is_dirty());
+    assert_eq!(c3d.original_bytes(), Some(original.as_slice()));
+    assert_eq!(write(&c3d), original);
+
+    let c3d =
+        C3d::from_bytes_with_options(&original, ParseOptions::default().with_preserved_bytes())
+            .unwrap();
+    assert_eq!(write(&c3d), original);
+}
+
+#[test]
+fn bytes_not_preserved_by_default() {
+    let c3d = C3d::load(FILE).unwrap();
+    assert_eq!(c3d.original_bytes(), None);
+    assert_ne!(write(&c3d), std::fs::read(FILE).unwrap());
+}
+
+#[test]
+fn field_mutation_forces_reserialization() {
+    let original = std::fs::read(FILE).unwrap();
+    let mut c3d = load();
+    c3d.points[5][3].point[0] += 10.;
+    let bytes = write(&c3d);
+    assert_ne!(bytes, original);
+    let written = C3d::from_bytes(&bytes).unwrap();
+    let expected = c3d.points[5][3].point[0];
+    assert!((written.points[5][3].point[0] - expected).abs() <= c3d.points.scale_factor);
+}
+
+#[test]
+fn mutating_method_marks_file_dirty() {
+    let original = std::fs::read(FILE).unwrap();
+    let mut c3d = load();
+    c3d.convert_to(Processor::Dec);
+    assert!(c3d.is_dirty());
+    let bytes = write(&c3d);
+    assert_ne!(bytes, original);
+    assert_eq!(C3d::from_bytes(&bytes).unwrap().processor(), Processor::Dec);
+
+    let mut c3d = load();
+    c3d.mark_dirty();
+    assert_ne!(write(&c3d), original);
+}
+
+fn with_trailing_blocks() -> (Vec<u8>, Vec<u8>) {
+    let mut bytes = std::fs::read(FILE).unwrap();
+    bytes.resize(bytes.len().div_ceil(512) * 512, 0);
+    let trailing: Vec<u8> = (0..1024).map(|i| (i % 251) as u8).collect();
+    bytes.extend_from_slice(&trailing);
+    (bytes, trailing)
+}
+
+#[test]
+fn trailing_blocks_kept_when_modified() {
+    let (bytes, trailing) = with_trailing_blocks();
+    let options = ParseOptions::default().with_preserved_bytes();
+    let mut c3d = C3d::from_bytes_with_options(&bytes, options.clone()).unwrap();
+    assert_eq!(c3d.trailing_bytes(), trailing.as_slice());
+    assert_eq!(write(&c3d), bytes);
+
+    c3d.points[5][3].point[0] += 10.;
+    c3d.mark_dirty();
+    let written = write(&c3d);
+    assert_ne!(written, bytes);
+    assert_eq!(written.len() % 512, 0);
+    assert!(written.ends_with(&trailing));
+    let reparsed = C3d::from_bytes_with_options(&written, options).unwrap();
+    assert_eq!(reparsed.trailing_bytes(), trailing.as_slice());
+    assert_eq!(reparsed.points.rows(), c3d.points.rows());
+}
+
+#[test]
+fn trailing_blocks_dropped_by_default() {
+    let (bytes, trailing) = with_trailing_blocks();
+    let c3d = C3d::from_bytes(&bytes).unwrap();
+    assert!(c3d.trailing_bytes().is_empty());
+    assert!(c3d
+        .warnings
+        .iter()
+        .any(|x| matches!(x, ParseWarning::TrailingData(n) if *n >= trailing.len())));
+    assert!(!write(&c3d).ends_with(&trailing));
+}
+
+#[test]
+fn vendor_groups_kept_when_modified() {
+    let file = "tests/c3d_org_samples/sample_09/PlugInC3D.c3d";
+    let options = ParseOptions::default().with_preserved_bytes();
+    let mut c3d = C3d::load_with_options(file, options).unwrap();
+    c3d.mark_dirty();
+    let written = C3d::from_bytes(&write(&c3d)).unwrap();
+    assert_eq!(written.parameters, c3d.parameters);
+    let mut groups: Vec<&String> = written.parameters.groups();
+    groups.sort();
+    let mut expected: Vec<&String> = c3d.parameters.groups();
+    expected.sort();
+    assert_eq!(groups, expected);
+}
+
+#[test]
+fn format_change_forces_reserialization() {
+    let file = "tests/c3d_org_samples/sample_02/pc_real.c3d";
+    let original = std::fs::read(file).unwrap();
+    let mut c3d =
+        C3d::from_bytes_with_options(&original, ParseOptions::default().with_preserved_bytes())
+            .unwrap();
+    c3d.points.format = DataFormat::Integer;
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    assert_ne!(bytes, original);
+    let written = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(written.points.format, DataFormat::Integer);
+    assert_eq!(written.points.labels, c3d.points.labels);
+}
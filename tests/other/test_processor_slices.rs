@@ -0,0 +1,66 @@
+use c3dio::prelude::*;
+
+const PROCESSORS: [Processor; 3] = [Processor::Intel, Processor::Dec, Processor::SgiMips];
+
+#[test]
+fn slices_match_single_values() {
+    let bytes: Vec<u8> = (0..1031).map(|i| (i % 251) as u8 | 0x01).collect();
+    for processor in PROCESSORS {
+        let f32s = processor.f32_slice(&bytes);
+        assert_eq!(f32s.len(), bytes.len() / 4);
+        for (value, x) in f32s.iter().zip(bytes.chunks_exact(4)) {
+            assert_eq!(
+                value.to_bits(),
+                processor.f32([x[0], x[1], x[2], x[3]]).to_bits()
+            );
+        }
+        let i16s = processor.i16_slice(&bytes);
+        let u16s = processor.u16_slice(&bytes);
+        assert_eq!(i16s.len(), bytes.len() / 2);
+        for ((signed, unsigned), x) in i16s.iter().zip(&u16s).zip(bytes.chunks_exact(2)) {
+            assert_eq!(*signed, processor.i16([x[0], x[1]]));
+            assert_eq!(*unsigned, processor.u16([x[0], x[1]]));
+        }
+    }
+}
+
+#[test]
+fn slices_round_trip_through_bytes() {
+    let f32s: Vec<f32> = (0..500).map(|i| (i as f32 - 250.) * 1.37).collect();
+    let i16s: Vec<i16> = (0..500).map(|i| (i * 131 - 32000) as i16).collect();
+    let u16s: Vec<u16> = (0..500).map(|i| i * 131).collect();
+    for processor in PROCESSORS {
+        let bytes = processor.f32_slice_to_bytes(&f32s);
+        assert_eq!(bytes.len(), f32s.len() * 4);
+        assert_eq!(processor.f32_slice(&bytes), f32s);
+        assert_eq!(
+            processor.i16_slice(&processor.i16_slice_to_bytes(&i16s)),
+            i16s
+        );
+        assert_eq!(
+            processor.u16_slice(&processor.u16_slice_to_bytes(&u16s)),
+            u16s
+        );
+    }
+}
+
+#[test]
+fn slices_are_stored_with_the_processor_byte_order() {
+    assert_eq!(
+        Processor::Intel.i16_slice_to_bytes(&[0x1234]),
+        vec![0x34, 0x12]
+    );
+    assert_eq!(
+        Processor::SgiMips.i16_slice_to_bytes(&[0x1234]),
+        vec![0x12, 0x34]
+    );
+    assert_eq!(
+        Processor::SgiMips.f32_slice_to_bytes(&[1.]),
+        1f32.to_be_bytes().to_vec()
+    );
+    // DEC floats swap the words and have an exponent two larger
+    assert_eq!(
+        Processor::Dec.f32_slice_to_bytes(&[1.]),
+        vec![0x80, 0x40, 0, 0]
+    );
+}
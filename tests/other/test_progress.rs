@@ -0,0 +1,160 @@
+use c3dio::prelude::*;
+use c3dio::{Progress, ProgressStage, WriteOptions};
+use std::io::Cursor;
+use std::ops::ControlFlow;
+use test_files::TestFiles;
+
+fn assert_monotone(reports: &[Progress], stage: ProgressStage) {
+    let stage_reports: Vec<&Progress> = reports.iter().filter(|p| p.stage == stage).collect();
+    assert!(!stage_reports.is_empty());
+    for pair in stage_reports.windows(2) {
+        assert!(pair[0].done <= pair[1].done);
+        assert_eq!(pair[0].total, pair[1].total);
+    }
+    let last = stage_reports.last().unwrap();
+    assert_eq!(last.done, last.total);
+    assert_eq!(last.fraction(), 1.);
+}
+
+#[test]
+fn load_with_progress_matches_load() {
+    let mut reports = Vec::new();
+    let c3d = C3d::load_with_progress("tests/data/short.c3d", ParseOptions::default(), |p| {
+        reports.push(p);
+        ControlFlow::Continue(())
+    })
+    .unwrap();
+    assert_eq!(c3d, C3d::load("tests/data/short.c3d").unwrap());
+    assert_monotone(&reports, ProgressStage::Reading);
+    assert_monotone(&reports, ProgressStage::Decoding);
+    let file_size = std::fs::metadata("tests/data/short.c3d").unwrap().len();
+    assert!(reports
+        .iter()
+        .filter(|p| p.stage == ProgressStage::Reading)
+        .all(|p| p.total == file_size));
+    let decoding = reports.last().unwrap();
+    assert_eq!(decoding.total, c3d.points.rows() as u64);
+}
+
+#[test]
+fn load_with_progress_preserving_bytes() {
+    let options = ParseOptions {
+        preserve_bytes: true,
+        ..Default::default()
+    };
+    let mut reports = Vec::new();
+    let c3d = C3d::load_with_progress("tests/data/short.c3d", options.clone(), |p| {
+        reports.push(p);
+        ControlFlow::Continue(())
+    })
+    .unwrap();
+    assert_eq!(
+        c3d,
+        C3d::load_with_options("tests/data/short.c3d", options).unwrap()
+    );
+    assert_monotone(&reports, ProgressStage::Reading);
+    assert_monotone(&reports, ProgressStage::Decoding);
+}
+
+#[test]
+fn cancel_load() {
+    let result =
+        C3d::load_with_progress(
+            "tests/data/short.c3d",
+            ParseOptions::default(),
+            |p| match p.stage {
+                ProgressStage::Decoding => ControlFlow::Break(()),
+                _ => ControlFlow::Continue(()),
+            },
+        );
+    assert!(matches!(result, Err(C3dParseError::Cancelled)));
+    let bytes = std::fs::read("tests/data/short.c3d").unwrap();
+    let result =
+        C3d::from_reader_with_progress(Cursor::new(bytes), ParseOptions::default(), |_| {
+            ControlFlow::Break(())
+        });
+    assert!(matches!(result, Err(C3dParseError::Cancelled)));
+}
+
+#[test]
+fn write_to_with_progress_matches_write_to() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let mut expected = Vec::new();
+    c3d.write_to(&mut expected, c3d.processor()).unwrap();
+    let mut bytes = Vec::new();
+    let mut reports = Vec::new();
+    c3d.write_to_with_progress(&mut bytes, &WriteOptions::default(), |p| {
+        reports.push(p);
+        ControlFlow::Continue(())
+    })
+    .unwrap();
+    assert_eq!(bytes, expected);
+    assert_monotone(&reports, ProgressStage::Encoding);
+    assert_monotone(&reports, ProgressStage::Writing);
+    assert_eq!(reports.last().unwrap().total, expected.len() as u64);
+}
+
+#[test]
+fn write_to_with_progress_converts_processor() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let mut expected = Vec::new();
+    c3d.write_to(&mut expected, Processor::Dec).unwrap();
+    let mut bytes = Vec::new();
+    let options = WriteOptions {
+        processor: Some(Processor::Dec),
+        ..Default::default()
+    };
+    c3d.write_to_with_progress(&mut bytes, &options, |_| ControlFlow::Continue(()))
+        .unwrap();
+    assert_eq!(bytes, expected);
+}
+
+#[test]
+fn writers_convert_preserved_files_alike() {
+    let options = ParseOptions {
+        preserve_bytes: true,
+        ..Default::default()
+    };
+    let c3d = C3d::load_with_options("tests/data/short.c3d", options).unwrap();
+    let mut dec = Vec::new();
+    c3d.write_to(&mut dec, Processor::Dec).unwrap();
+    let mut bytes = Vec::new();
+    let options = WriteOptions::default().with_processor(Processor::Dec);
+    c3d.write_to_with_progress(&mut bytes, &options, |_| ControlFlow::Continue(()))
+        .unwrap();
+    assert_eq!(bytes, dec);
+
+    let options = WriteOptions::integer().with_processor(Processor::Dec);
+    let mut expected = Vec::new();
+    c3d.write_to_with_options(&mut expected, &options).unwrap();
+    assert_ne!(expected, dec);
+    let mut bytes = Vec::new();
+    c3d.write_to_with_progress(&mut bytes, &options, |_| ControlFlow::Continue(()))
+        .unwrap();
+    assert_eq!(bytes, expected);
+    assert_eq!(
+        C3d::from_bytes(&bytes).unwrap().points.format,
+        DataFormat::Integer
+    );
+}
+
+#[test]
+fn cancel_write() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let files = TestFiles::new();
+    let path = files.path().join("progress.c3d");
+    let file_name = path.to_str().unwrap();
+    let result = c3d.write_with_progress(file_name, &WriteOptions::default(), |p| match p.stage {
+        ProgressStage::Writing => ControlFlow::Break(()),
+        _ => ControlFlow::Continue(()),
+    });
+    assert!(matches!(result, Err(C3dWriteError::Cancelled)));
+    assert!(!std::path::Path::new(file_name).exists());
+
+    c3d.write_with_progress(file_name, &WriteOptions::default(), |_| {
+        ControlFlow::Continue(())
+    })
+    .unwrap();
+    let written = C3d::load(file_name).unwrap();
+    assert_eq!(written.points, c3d.points);
+}
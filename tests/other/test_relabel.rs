@@ -0,0 +1,90 @@
+use c3dio::prelude::*;
+use c3dio::{LabelMatch, LabelPreset, PointKind};
+use std::collections::HashMap;
+
+fn mapping(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(old, new)| (old.to_string(), new.to_string()))
+        .collect()
+}
+
+#[test]
+fn relabel_markers() {
+    let mut c3d = C3d::load("tests/c3d_org_samples/sample_09/PlugInC3D.c3d").unwrap();
+    let lasi = c3d.points.get_by_label("LASI").unwrap();
+    let report = c3d.relabel_points(&mapping(&[("LASI", "L_ASIS"), ("XXXX", "YYYY")]));
+    assert_eq!(
+        report.renamed,
+        vec![("LASI".to_string(), "L_ASIS".to_string())]
+    );
+    assert_eq!(report.unmatched, vec!["XXXX".to_string()]);
+    assert!(report.duplicates.is_empty());
+    assert!(!report.is_clean());
+    assert!(c3d.is_dirty());
+    assert_eq!(c3d.points.get_by_label("LASI"), None);
+    assert_eq!(c3d.points.get_by_label("L_ASIS"), Some(lasi));
+
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    let written = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(written.points.labels, c3d.points.labels);
+}
+
+#[test]
+fn relabel_descriptions() {
+    let mut c3d = C3d::load("tests/c3d_org_samples/sample_09/PlugInC3D.c3d").unwrap();
+    let column = c3d.points.find_label("RKNE", LabelMatch::Trimmed).unwrap();
+    c3d.points.descriptions[column] = "RKNE".to_string();
+    let other = c3d.points.find_label("RTOE", LabelMatch::Trimmed).unwrap();
+    c3d.relabel_points(&mapping(&[("RKNE", "R_KNEE"), ("RTOE", "R_TOE")]));
+    assert_eq!(c3d.points.descriptions[column], "R_KNEE");
+    assert_eq!(
+        c3d.points.descriptions[other],
+        "Right toe (2nd metatarsel head)"
+    );
+}
+
+#[test]
+fn relabel_reports_duplicates() {
+    let mut c3d = C3d::load("tests/c3d_org_samples/sample_09/PlugInC3D.c3d").unwrap();
+    let report = c3d.relabel_points(&mapping(&[("LASI", "RASI")]));
+    assert_eq!(report.duplicates, vec!["RASI".to_string()]);
+}
+
+#[test]
+fn relabel_model_outputs() {
+    let mut c3d = C3d::load("tests/c3d_org_samples/sample_03/gait-pig.c3d").unwrap();
+    let angles = c3d.points_of_kind(PointKind::Angle).len();
+    let report = c3d.relabel_points(&mapping(&[("A22:LKneeAngles", "LKneeAngles")]));
+    assert!(report.is_clean());
+    let listed: Vec<String> = c3d.parameters.get_vec("POINT", "ANGLES").unwrap();
+    assert!(listed.contains(&"LKneeAngles".to_string()));
+    assert!(!listed.contains(&"A22:LKneeAngles".to_string()));
+    assert_eq!(c3d.points_of_kind(PointKind::Angle).len(), angles);
+}
+
+#[test]
+fn relabel_with_preset() {
+    let mut c3d = C3d::load("tests/c3d_org_samples/sample_03/gait-pig.c3d").unwrap();
+    let preset = LabelPreset::with_prefix("A22", "A22:");
+    let report = c3d.relabel_points(&preset.to_plug_in_gait());
+    assert_eq!(report.renamed.len(), 13);
+    assert!(report.duplicates.is_empty());
+    assert!(c3d.points.get_by_label("SACR").is_some());
+    assert!(c3d.points.get_by_label("A22:LKneeAngles").is_some());
+
+    let report = c3d.relabel_points(&preset.to_custom());
+    assert_eq!(report.renamed.len(), 13);
+    assert!(c3d.points.get_by_label("A22:SACR").is_some());
+}
+
+#[test]
+fn preset_inverse() {
+    let preset = LabelPreset::new("Lab", &[("L.ASIS", "LASI"), ("R.ASIS", "RASI")]);
+    assert_eq!(
+        preset.to_custom(),
+        mapping(&[("LASI", "L.ASIS"), ("RASI", "R.ASIS")])
+    );
+    assert_eq!(preset.to_plug_in_gait(), preset.mapping);
+}
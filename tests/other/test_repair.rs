@@ -0,0 +1,80 @@
+use c3dio::prelude::*;
+use c3dio::Repair;
+
+fn short_bytes() -> Vec<u8> {
+    std::fs::read("tests/data/short.c3d").unwrap()
+}
+
+/// The repaired file can be written and loaded again without leniency.
+fn assert_rewrites(c3d: &C3d) -> C3d {
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    assert!(C3d::validate_bytes(&bytes).unwrap().is_valid());
+    C3d::from_bytes(&bytes).unwrap()
+}
+
+#[test]
+fn valid_file() {
+    let (c3d, repairs) = C3d::repair_file("tests/data/short.c3d").unwrap();
+    assert!(repairs.is_empty(), "{:?}", repairs);
+    let loaded = C3d::load("tests/data/short.c3d").unwrap();
+    assert_eq!(c3d.points, loaded.points);
+    assert_eq!(c3d.analog, loaded.analog);
+}
+
+#[test]
+fn stale_header_data_start() {
+    let mut bytes = short_bytes();
+    bytes[16] = 5;
+    let (c3d, repairs) = C3d::repair(&bytes).unwrap();
+    assert_eq!(repairs, vec![Repair::HeaderDataStart(5, 14)]);
+    let loaded = C3d::load("tests/data/short.c3d").unwrap();
+    assert_eq!(c3d.points, loaded.points);
+    assert_eq!(assert_rewrites(&c3d).points, loaded.points);
+}
+
+#[test]
+fn header_frame_count() {
+    let mut bytes = short_bytes();
+    bytes[8] += 3;
+    assert!(C3d::from_bytes_with_options(&bytes, ParseOptions::strict()).is_err());
+    let (c3d, repairs) = C3d::repair(&bytes).unwrap();
+    assert_eq!(repairs, vec![Repair::FrameCount(154, 151, 151)]);
+    assert_eq!(c3d.points.rows(), 151);
+    assert_eq!(assert_rewrites(&c3d).points.rows(), 151);
+}
+
+#[test]
+fn missing_labels() {
+    let mut bytes = short_bytes();
+    // second dimension of POINT:LABELS, one label short of the 49 points
+    bytes[1166 + 11] = 48;
+    let (c3d, repairs) = C3d::repair(&bytes).unwrap();
+    assert_eq!(repairs, vec![Repair::MissingLabels("POINT".to_string(), 1)]);
+    assert_eq!(c3d.points.labels.len(), 49);
+    assert_eq!(c3d.points.labels.last().unwrap(), "M49");
+    assert_eq!(assert_rewrites(&c3d).points.labels, c3d.points.labels);
+}
+
+#[test]
+fn frame_count_mismatch() {
+    let file = "tests/c3d_org_samples/sample_13/Dance1.c3d";
+    assert!(C3d::load_with_options(file, ParseOptions::strict()).is_err());
+    let (c3d, repairs) = C3d::repair_file(file).unwrap();
+    assert!(repairs
+        .iter()
+        .any(|repair| matches!(repair, Repair::FrameCount(..))));
+    let reloaded = assert_rewrites(&c3d);
+    assert_eq!(reloaded.points.rows(), c3d.points.rows());
+}
+
+#[test]
+fn truncated_parameter_section() {
+    let bytes = short_bytes();
+    // the parameter section takes up blocks 2 to 13, which end at byte 6656
+    assert!(C3d::from_bytes(&bytes[..6100]).is_err());
+    assert!(C3d::repair(&bytes[..6100]).is_err());
+    for length in (1024..6656).step_by(97) {
+        let _ = C3d::repair(&bytes[..length]);
+    }
+}
@@ -0,0 +1,100 @@
+use c3dio::prelude::*;
+use test_files::TestFiles;
+
+fn load(file: &str) -> C3d {
+    C3d::load(&format!("tests/c3d_org_samples/sample_02/{}.c3d", file)).unwrap()
+}
+
+#[test]
+fn integer_residual_is_scaled() {
+    let c3d = load("pc_int");
+    assert_eq!(c3d.points.format, DataFormat::Integer);
+    let point = c3d.points[5][3];
+    // the residual is stored as 6 and POINT:SCALE is 0.28118187
+    assert!((point.residual - 6. * c3d.points.scale_factor).abs() < 1e-6);
+    assert!((point.residual - 1.6870912).abs() < 1e-6);
+}
+
+#[test]
+fn residuals_match_across_storage_formats() {
+    let integer = load("pc_int");
+    let float = load("pc_real");
+    assert_eq!(float.points.format, DataFormat::Float);
+    assert_eq!(integer.points.size(), float.points.size());
+    for (integer, float) in integer.points.iter().zip(float.points.iter()) {
+        assert_eq!(integer.is_valid(), float.is_valid());
+        assert!((integer.residual - float.residual).abs() < 1e-6);
+        assert_eq!(integer.cameras, float.cameras);
+    }
+}
+
+#[test]
+fn residuals_preserved_on_write() {
+    for file in ["pc_int", "pc_real"] {
+        let mut c3d = load(file);
+        // a zero residual is a valid point, not a missing one
+        c3d.points[5][3].residual = 0.;
+        let files = TestFiles::new();
+        let path = files.path().join(format!("{}.c3d", file));
+        c3d.write_path(path.clone()).unwrap();
+        let written = C3d::load_path(path).unwrap();
+        assert_eq!(written.points[5][3].residual, 0.);
+        assert!(written.points[5][3].is_valid());
+        for (original, written) in c3d.points.iter().zip(written.points.iter()) {
+            assert_eq!(original.is_valid(), written.is_valid());
+            assert!((original.residual - written.residual).abs() < 1e-5);
+        }
+    }
+}
+
+#[test]
+fn sample_accessors_match_across_storage_formats() {
+    let integer = load("pc_int");
+    let float = load("pc_real");
+    let mut invalid = 0;
+    for frame in 0..integer.points.rows() {
+        for point in 0..integer.points.cols() {
+            let sample = integer.points[frame][point];
+            assert_eq!(integer.points.is_valid(frame, point), sample.is_valid());
+            assert_eq!(
+                integer.points.is_valid(frame, point),
+                float.points.is_valid(frame, point)
+            );
+            assert_eq!(
+                integer.points.cameras_seen(frame, point),
+                float.points.cameras_seen(frame, point)
+            );
+            let cameras = integer.points.cameras_seen(frame, point);
+            for camera in 0..7 {
+                assert_eq!(cameras.contains(&camera), sample.cameras[camera]);
+            }
+            match integer.points.residual(frame, point) {
+                Some(residual) => {
+                    assert_eq!(residual, sample.residual);
+                    let float = float.points.residual(frame, point).unwrap();
+                    assert!((residual - float).abs() < 1e-6);
+                }
+                None => {
+                    invalid += 1;
+                    assert!(!sample.is_valid());
+                    assert!(cameras.is_empty());
+                }
+            }
+        }
+    }
+    assert!(invalid > 0);
+    let residuals = integer.points.residuals(3);
+    assert_eq!(residuals.len(), integer.points.rows());
+    assert_eq!(residuals[5], Some(integer.points[5][3].residual));
+}
+
+#[test]
+fn sample_accessors_out_of_range() {
+    let c3d = load("pc_int");
+    let (frames, points) = (c3d.points.rows(), c3d.points.cols());
+    assert!(!c3d.points.is_valid(frames, 0));
+    assert!(!c3d.points.is_valid(0, points));
+    assert_eq!(c3d.points.residual(frames, 0), None);
+    assert!(c3d.points.cameras_seen(0, points).is_empty());
+    assert!(c3d.points.residuals(points).is_empty());
+}
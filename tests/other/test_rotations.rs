@@ -0,0 +1,165 @@
+use c3dio::prelude::*;
+use c3dio::{RigidBody, Rotation};
+
+fn body(c3d: &C3d) -> RigidBody {
+    let labels: Vec<&str> = c3d.points.labels[..3].iter().map(|x| x.as_str()).collect();
+    RigidBody::from_frame(c3d, "BODY", &labels, 0).unwrap()
+}
+
+fn written(c3d: &C3d, processor: Processor) -> C3d {
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, processor).unwrap();
+    C3d::from_bytes(&bytes).unwrap()
+}
+
+#[test]
+fn no_rotations() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    assert_eq!(c3d.rotations.cols(), 0);
+    assert!(c3d.parameters.get_group("ROTATION").is_none());
+    let written = written(&c3d, c3d.processor());
+    assert!(written.parameters.get_group("ROTATION").is_none());
+}
+
+#[test]
+fn rotation_round_trip() {
+    let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let body = body(&c3d);
+    assert_eq!(c3d.add_rigid_body_rotation(&body), Some(0));
+    assert!(c3d.is_dirty());
+    assert_eq!(c3d.rotations.rows(), c3d.points.rows());
+    assert_eq!(c3d.rotations.ratio, 1);
+    assert_eq!(c3d.rotations.rate, c3d.points.frame_rate);
+    for processor in [Processor::Intel, Processor::Dec, Processor::SgiMips] {
+        let written = written(&c3d, processor);
+        assert_eq!(written.rotations, c3d.rotations);
+        assert_eq!(written.rotations.labels, ["BODY"]);
+        assert!(written.warnings.is_empty());
+        assert_eq!(written.points, c3d.points);
+    }
+    let poses = c3d.rigid_body_poses(&body).unwrap();
+    for (frame, pose) in poses.iter().enumerate() {
+        let rotation = c3d.rotations[(frame, 0)];
+        match pose {
+            Some(pose) => {
+                assert!(rotation.is_valid());
+                assert_eq!(rotation.position(), pose.position);
+                for (a, b) in rotation.quaternion().iter().zip(pose.rotation) {
+                    assert!((a - b).abs() < 1e-4);
+                }
+            }
+            None => assert!(!rotation.is_valid()),
+        }
+    }
+}
+
+#[test]
+fn rotation_data_layout() {
+    let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    c3d.add_rigid_body_rotation(&body(&c3d)).unwrap();
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, Processor::Intel).unwrap();
+    let metadata = c3d.parameter_metadata().unwrap();
+    let data_start = metadata
+        .iter()
+        .find(|(key, _)| key == "ROTATION:DATA_START")
+        .map(|(_, value)| value.trim_matches(['[', ']']).parse::<usize>().unwrap())
+        .unwrap();
+    let point_data_start = u16::from_le_bytes([bytes[16], bytes[17]]) as usize;
+    assert!(data_start > point_data_start);
+    // each sample is a 4x4 matrix stored by columns, followed by the reliability
+    let start = (data_start - 1) * 512;
+    let value = |i: usize| {
+        let offset = start + i * 4;
+        f32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    };
+    let rotation = c3d.rotations[(0, 0)];
+    assert_eq!(value(0), rotation.matrix[0][0]);
+    assert_eq!(value(1), rotation.matrix[1][0]);
+    assert_eq!(value(12), rotation.matrix[0][3]);
+    assert_eq!(value(15), 1.);
+    assert_eq!(value(16), rotation.reliability);
+    // the file ends on a whole block
+    assert_eq!(
+        bytes.len(),
+        (start + c3d.points.rows() * 68).next_multiple_of(512)
+    );
+}
+
+#[test]
+fn several_samples_per_frame() {
+    let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let num_frames = c3d.points.rows();
+    let samples: Vec<Rotation> = (0..num_frames * 2)
+        .map(|i| Rotation {
+            matrix: [
+                [1., 0., 0., i as f32],
+                [0., 1., 0., 0.],
+                [0., 0., 1., 0.],
+                [0., 0., 0., 1.],
+            ],
+            reliability: 0.5,
+        })
+        .collect();
+    let rate = c3d.points.frame_rate;
+    let rotations = &mut c3d.rotations;
+    assert_eq!(
+        rotations.add_body("A", "", samples.clone(), num_frames, rate),
+        Some(0)
+    );
+    assert_eq!(
+        rotations.add_body("B", "", samples, num_frames, rate),
+        Some(1)
+    );
+    assert_eq!(
+        rotations.add_body("C", "", Vec::new(), num_frames, rate),
+        None
+    );
+    assert_eq!(rotations.ratio, 2);
+    assert_eq!(rotations.rate, rate * 2.);
+    assert_eq!(rotations.label_index("B"), Some(1));
+    assert_eq!(
+        rotations.get_by_label("A").unwrap()[3].position(),
+        [3., 0., 0.]
+    );
+    assert_eq!(rotations[(0, 0)].quaternion(), [1., 0., 0., 0.]);
+    // a body added from poses needs one sample per frame
+    let body = body(&c3d);
+    assert_eq!(c3d.add_rigid_body_rotation(&body), None);
+
+    let written = written(&c3d, c3d.processor());
+    assert_eq!(written.rotations, c3d.rotations);
+    let cropped = c3d.crop(2..4);
+    assert_eq!(cropped.rotations.rows(), 4);
+    assert_eq!(cropped.rotations[(0, 1)].position(), [4., 0., 0.]);
+}
+
+#[test]
+fn quaternion_from_matrix() {
+    // 180 degrees about x, where the trace is negative
+    let rotation = Rotation {
+        matrix: [
+            [1., 0., 0., 0.],
+            [0., -1., 0., 0.],
+            [0., 0., -1., 0.],
+            [0., 0., 0., 1.],
+        ],
+        reliability: 0.,
+    };
+    assert_eq!(rotation.quaternion(), [0., 1., 0., 0.]);
+    // 90 degrees about z
+    let rotation = Rotation {
+        matrix: [
+            [0., -1., 0., 0.],
+            [1., 0., 0., 0.],
+            [0., 0., 1., 0.],
+            [0., 0., 0., 1.],
+        ],
+        reliability: 0.,
+    };
+    let half = std::f32::consts::FRAC_1_SQRT_2;
+    for (a, b) in rotation.quaternion().iter().zip([half, 0., 0., half]) {
+        assert!((a - b).abs() < 1e-6);
+    }
+    assert!(!Rotation::invalid().is_valid());
+}
@@ -0,0 +1,59 @@
+use c3dio::prelude::*;
+
+const FILE: &str = "tests/data/short.c3d";
+
+#[test]
+fn load_selected_points() {
+    let full = C3d::load(FILE).unwrap();
+    let options = ParseOptions::default().with_point_labels(&["RSHO", "THEA", "MISSING"]);
+    let c3d = C3d::load_with_options(FILE, options).unwrap();
+    // points keep the order of the file
+    assert_eq!(c3d.points.labels, vec!["THEA", "RSHO"]);
+    assert_eq!(c3d.points.size(), (full.points.rows(), 2));
+    let thea = full.points.labels.iter().position(|l| l == "THEA").unwrap();
+    let rsho = full.points.labels.iter().position(|l| l == "RSHO").unwrap();
+    for frame in 0..full.points.rows() {
+        assert_eq!(c3d.points[(frame, 0)], full.points[(frame, thea)]);
+        assert_eq!(c3d.points[(frame, 1)], full.points[(frame, rsho)]);
+    }
+    assert_eq!(c3d.analog, full.analog);
+}
+
+#[test]
+fn load_selected_analog_channels() {
+    let full = C3d::load(FILE).unwrap();
+    let labels = ["F2X", "F2Y", "F2Z", "M2X", "M2Y", "M2Z"];
+    let options = ParseOptions::default()
+        .with_point_labels(&[])
+        .with_analog_labels(&labels);
+    let c3d = C3d::load_with_options(FILE, options).unwrap();
+    assert_eq!(c3d.points.cols(), 0);
+    assert_eq!(c3d.analog.labels, labels);
+    assert_eq!(c3d.analog.size(), (full.analog.rows(), 6));
+    assert_eq!(c3d.analog.scales, full.analog.scales[6..12]);
+    for sample in 0..full.analog.rows() {
+        assert_eq!(c3d.analog[sample], full.analog[sample][6..12]);
+    }
+
+    // the force platforms refer to the decoded channels
+    assert_eq!(c3d.forces[0].channels, [0; 8]);
+    assert_eq!(c3d.forces[1].channels, [1, 2, 3, 4, 5, 6, 0, 0]);
+    assert!(c3d.force_platform_data(0).is_none());
+    assert_eq!(c3d.force_platform_data(1), full.force_platform_data(1));
+}
+
+#[test]
+fn write_selected_channels() {
+    let options = ParseOptions::default()
+        .with_preserved_bytes()
+        .with_point_labels(&["RSHO"])
+        .with_analog_labels(&["F1Z"]);
+    let c3d = C3d::load_with_options(FILE, options).unwrap();
+    assert!(c3d.original_bytes().is_none());
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    let written = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(written.points.labels, vec!["RSHO"]);
+    assert_eq!(written.analog.labels, vec!["F1Z"]);
+    assert_eq!(written.points.points.flatten(), c3d.points.points.flatten());
+}
@@ -0,0 +1,51 @@
+use c3dio::prelude::*;
+
+fn json_round_trip(path: &str) {
+    let c3d = C3d::load(path).unwrap();
+    let json = serde_json::to_string(&c3d).unwrap();
+    let deserialized: C3d = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, c3d);
+    assert_eq!(deserialized.processor(), c3d.processor());
+}
+
+#[test]
+fn c3d_json_round_trip() {
+    json_round_trip("tests/data/short.c3d");
+    json_round_trip("tests/c3d_org_samples/sample_02/dec_int.c3d");
+    json_round_trip("tests/c3d_org_samples/sample_09/PlugInC3D.c3d");
+}
+
+#[test]
+fn write_deserialized() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let json = serde_json::to_vec(&c3d).unwrap();
+    let deserialized: C3d = serde_json::from_slice(&json).unwrap();
+    let mut bytes = Vec::new();
+    deserialized
+        .write_to(&mut bytes, deserialized.processor())
+        .unwrap();
+    assert_eq!(C3d::from_bytes(&bytes).unwrap(), c3d);
+}
+
+#[test]
+fn parameters_and_events_json() {
+    let c3d = C3d::load("tests/c3d_org_samples/sample_09/PlugInC3D.c3d").unwrap();
+    let json = serde_json::to_value(&c3d.events).unwrap();
+    let events: Events = serde_json::from_value(json).unwrap();
+    assert_eq!(events, c3d.events);
+    let json = serde_json::to_string(&c3d.parameters).unwrap();
+    let parameters: Parameters = serde_json::from_str(&json).unwrap();
+    assert_eq!(parameters, c3d.parameters);
+}
+
+#[test]
+fn grid_size_is_checked() {
+    let json = r#"{"point":[0.0,0.0,0.0],"residual":0.0,"cameras":[false,false,false,false,false,false,false]}"#;
+    let point: MarkerPoint = serde_json::from_str(json).unwrap();
+    assert_eq!(point, MarkerPoint::new(0., 0., 0.));
+
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let mut json = serde_json::to_value(&c3d).unwrap();
+    json["points"]["points"]["rows"] = serde_json::json!(1);
+    assert!(serde_json::from_value::<C3d>(json).is_err());
+}
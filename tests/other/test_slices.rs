@@ -0,0 +1,76 @@
+use c3dio::prelude::*;
+
+#[test]
+fn point_slices_borrow_the_storage() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let points = &c3d.points;
+    assert_eq!(points.as_slice().len(), points.rows() * points.cols());
+    for frame in [0, points.rows() - 1] {
+        let samples = points.frame(frame).unwrap();
+        assert_eq!(samples.len(), points.cols());
+        for (point, sample) in samples.iter().enumerate() {
+            assert_eq!(sample, &points[frame][point]);
+            assert_eq!(sample, &points.as_slice()[frame * points.cols() + point]);
+        }
+    }
+    assert!(points.frame(points.rows()).is_none());
+}
+
+#[test]
+fn analog_slices_hold_the_rows_of_a_frame() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let analog = &c3d.analog;
+    let rows = analog.samples_per_channel_per_frame as usize;
+    assert!(rows > 0);
+    assert_eq!(analog.as_slice().len(), analog.rows() * analog.cols());
+    let frames = analog.rows() / rows;
+    let samples = analog.frame(1).unwrap();
+    assert_eq!(samples.len(), rows * analog.cols());
+    for row in 0..rows {
+        for channel in 0..analog.cols() {
+            assert_eq!(
+                samples[row * analog.cols() + channel],
+                analog[rows + row][channel]
+            );
+        }
+    }
+    assert!(analog.frame(frames - 1).is_some());
+    assert!(analog.frame(frames).is_none());
+}
+
+#[test]
+fn mutable_slices_edit_the_samples() {
+    let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    c3d.points.frame_mut(2).unwrap()[0].point = [1., 2., 3.];
+    assert_eq!(c3d.points[2][0].point, [1., 2., 3.]);
+    let channels = c3d.analog.cols();
+    c3d.analog.frame_mut(3).unwrap()[channels + 1] = 0.5;
+    let rows = c3d.analog.samples_per_channel_per_frame as usize;
+    assert_eq!(c3d.analog[3 * rows + 1][1], 0.5);
+}
+
+#[test]
+fn analog_samples_are_aligned_to_the_frames_of_the_points() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let analog = &c3d.analog;
+    let rows = analog.samples_per_channel_per_frame as usize;
+    assert_eq!(analog.frame_count(), analog.rows() / rows);
+    assert_eq!(analog.frames().count(), analog.frame_count());
+    assert_eq!(analog.frames().nth(2), analog.frame(2));
+
+    assert_eq!(analog.samples_of_frame(3), Some(3 * rows..4 * rows));
+    assert_eq!(analog.frame_of_sample(3 * rows), Some((3, 0)));
+    assert_eq!(analog.frame_of_sample(4 * rows - 1), Some((3, rows - 1)));
+    assert!(analog.samples_of_frame(analog.frame_count()).is_none());
+    assert!(analog.frame_of_sample(analog.rows()).is_none());
+
+    let samples = analog.frame_samples(3, 1).unwrap();
+    let expected: Vec<f64> = analog
+        .iter_col(1)
+        .skip(3 * rows)
+        .take(rows)
+        .copied()
+        .collect();
+    assert_eq!(samples, expected);
+    assert!(analog.frame_samples(3, analog.cols()).is_none());
+}
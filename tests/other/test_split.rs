@@ -0,0 +1,71 @@
+use crate::common::{plug_in_gait, rewritten};
+use c3dio::prelude::*;
+use c3dio::C3dBuilder;
+
+#[test]
+fn split_by_events() {
+    let c3d = plug_in_gait();
+    let cycles = c3d.split_by_events("Foot Strike", "Left");
+    assert_eq!(cycles.len(), 1);
+    let cycle = &cycles[0];
+    assert_eq!(cycle.frame_count(), 181 - 108);
+    assert_eq!((cycle.points.first_frame, cycle.points.last_frame), (1, 73));
+    assert_eq!(cycle.points[0][0], c3d.points[108][0]);
+    // the cycle starts with its foot strike, re-timed to the first frame
+    let strike = cycle
+        .events
+        .iter()
+        .find(|x| x.label == "Foot Strike" && x.context == "Left")
+        .unwrap();
+    assert_eq!(cycle.event_frame(strike), Some(0));
+
+    let written = rewritten(cycle);
+    assert_eq!(written.declared_frame_count(), 73);
+    assert_eq!(written.points, cycle.points);
+    assert_eq!(written.events.num_events(), cycle.events.num_events());
+
+    assert!(c3d.split_by_events("Foot Strike", "General").is_empty());
+}
+
+#[test]
+fn split_every() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let frames = c3d.frame_count();
+    let parts = c3d.split_every(40);
+    assert_eq!(parts.len(), frames.div_ceil(40));
+    assert_eq!(parts.iter().map(C3d::frame_count).sum::<usize>(), frames);
+    assert_eq!(parts[0].frame_count(), 40);
+    assert_eq!(parts.last().unwrap().frame_count(), frames % 40);
+    let samples = c3d.analog.samples_per_channel_per_frame as usize;
+    assert_eq!(parts[1].points[0][0], c3d.points[40][0]);
+    assert_eq!(parts[1].analog[0][0], c3d.analog[40 * samples][0]);
+    for part in &parts {
+        assert_eq!(rewritten(part).declared_frame_count(), part.frame_count());
+    }
+
+    assert_eq!(c3d.split_every(10_000).len(), 1);
+    assert!(c3d.split_every(0).is_empty());
+}
+
+#[test]
+fn split_long_trial() {
+    let mut builder = C3dBuilder::new().frame_rate(100.).point_labels(&["A"]);
+    for i in 0..70_000 {
+        builder = builder.push_frame(&[[i as f32; 3]], &[]).unwrap();
+    }
+    let c3d = builder.build().unwrap();
+    let parts = c3d.split_every(40_000);
+    assert_eq!(parts.len(), 2);
+    // each part fits in the header, so the TRIAL fields are only needed for the trial
+    let second = rewritten(&parts[1]);
+    assert_eq!(second.declared_frame_count(), 30_000);
+    assert_eq!(
+        (second.points.first_frame, second.points.last_frame),
+        (1, 30_000)
+    );
+    assert_eq!(second.points[0][0].point, [40_000.; 3]);
+    assert_eq!(
+        rewritten(&c3d.split_every(66_000)[0]).declared_frame_count(),
+        66_000
+    );
+}
@@ -0,0 +1,129 @@
+use c3dio::prelude::*;
+use c3dio::{Subject, Subjects};
+
+const TABLE_TENNIS: &str = "tests/c3d_org_samples/sample_00/Vicon Motion Systems/TableTennis.c3d";
+
+#[test]
+fn subjects_are_read_from_the_subjects_group() {
+    let c3d = C3d::load(TABLE_TENNIS).unwrap();
+    let subjects = c3d.subjects();
+    let names: Vec<&str> = subjects.subjects.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["Table", "Player01", "Player02"]);
+    assert!(subjects.uses_prefixes);
+    assert!(!subjects.is_static);
+    let player = subjects.get("Player01").unwrap();
+    assert_eq!(player.label_prefix, "Player01:");
+    assert_eq!(player.marker_set, "PlugInGait FullBody Ai");
+    assert_eq!(player.strip_prefix("Player01:LFHD"), Some("LFHD"));
+    assert_eq!(player.strip_prefix("Player02:LFHD"), None);
+}
+
+#[test]
+fn points_are_filtered_by_subject() {
+    let c3d = C3d::load(TABLE_TENNIS).unwrap();
+    let subjects = c3d.subjects();
+    let mut total = 0;
+    for subject in &subjects.subjects {
+        let points = c3d.subject_points(&subject.name);
+        assert!(!points.is_empty());
+        for point in &points {
+            assert!(point.label.starts_with(&subject.label_prefix));
+            assert_eq!(c3d.points.labels[point.index], point.label);
+        }
+        total += points.len();
+    }
+    let unowned = c3d
+        .points
+        .labels
+        .iter()
+        .filter(|label| subjects.subject_of(label).is_none())
+        .count();
+    assert_eq!(total + unowned, c3d.points.labels.len());
+    assert!(c3d.subject_points("Player03").is_empty());
+}
+
+#[test]
+fn labels_without_prefixes_belong_to_the_only_subject() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let subjects = c3d.subjects();
+    assert_eq!(subjects.subjects.len(), 1);
+    assert!(!subjects.uses_prefixes);
+    assert_eq!(subjects.subjects[0].name, "HelenHayes");
+    assert_eq!(
+        c3d.subject_points("HelenHayes").len(),
+        c3d.points.labels.len()
+    );
+}
+
+#[test]
+fn files_without_the_group_have_no_subjects() {
+    let c3d = C3d::load("tests/c3d_org_samples/sample_01/Eb015pi.c3d").unwrap();
+    assert!(c3d.subjects().is_empty());
+    assert!(c3d.subjects().subject_of("RKNE").is_none());
+}
+
+#[test]
+fn subjects_are_written_back() {
+    let mut c3d = C3d::load(TABLE_TENNIS).unwrap();
+    let mut subjects = c3d.subjects();
+    subjects.subjects.truncate(2);
+    subjects.subjects[0].name = "PingPongTable".to_string();
+    subjects.is_static = true;
+    c3d.set_subjects(&subjects).unwrap();
+
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    let written = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(written.subjects(), subjects);
+    assert_eq!(
+        written
+            .parameters
+            .get_as::<i16>("SUBJECTS", "USED")
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        written.subjects().subjects[1].marker_set,
+        "PlugInGait FullBody Ai"
+    );
+    assert_eq!(
+        written.subject_points("PingPongTable").len(),
+        c3d.subject_points("PingPongTable").len()
+    );
+}
+
+#[test]
+fn subjects_are_added_to_files_without_the_group() {
+    let mut c3d = C3d::load("tests/c3d_org_samples/sample_01/Eb015pi.c3d").unwrap();
+    let mut subjects = Subjects::new();
+    subjects.subjects.push(Subject::new("Patient", ""));
+    c3d.set_subjects(&subjects).unwrap();
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    let written = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(written.subjects(), subjects);
+    assert!(!written.parameters.contains("SUBJECTS", "MARKER_SETS"));
+    assert_eq!(
+        written.subject_points("Patient").len(),
+        written.points.cols()
+    );
+}
+
+#[test]
+fn default_subjects_use_prefixes() {
+    assert_eq!(Subjects::default(), Subjects::new());
+    let mut c3d = C3d::load("tests/c3d_org_samples/sample_01/Eb015pi.c3d").unwrap();
+    let mut subjects = Subjects::default();
+    subjects.subjects.push(Subject::new("Patient", "Patient:"));
+    c3d.set_subjects(&subjects).unwrap();
+    assert_eq!(
+        c3d.parameters
+            .get_as::<i16>("SUBJECTS", "USES_PREFIXES")
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        c3d.subjects().subject_of("Patient:RKNE").unwrap().name,
+        "Patient"
+    );
+}
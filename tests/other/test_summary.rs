@@ -0,0 +1,55 @@
+use c3dio::prelude::*;
+use c3dio::C3dBuilder;
+
+#[test]
+fn summary_of_a_loaded_file() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let summary = c3d.summary();
+    let lines: Vec<&str> = summary.lines().collect();
+    assert_eq!(lines[0], "Processor: Intel, Float data");
+    assert_eq!(lines[1], "Frames: 151 (1 to 151) at 60 Hz, 2.517 s");
+    assert_eq!(lines[2], "Points: 49 in mm");
+    assert!(lines[3].starts_with("  THEA, FHEA, RHEA,"));
+    assert_eq!(
+        lines[4],
+        "Analog: 18 channels at 960 Hz, 16 samples per frame"
+    );
+    assert!(lines[5].starts_with("  F1X (N), F1Y (N), F1Z (N), M1X (Nmm),"));
+    assert_eq!(lines[6], "Force platforms: 2");
+    assert_eq!(lines[7], "  1: Type4, channels [1, 2, 3, 4, 5, 6]");
+    assert_eq!(lines[9], "Events: 8");
+    assert_eq!(lines[10], "  0.567 s: LHS");
+    assert_eq!(lines[11], "  0.733 s: RTO");
+    assert_eq!(
+        lines.last(),
+        Some(&"Manufacturer: Motion Analysis Corp., Cortex")
+    );
+}
+
+#[test]
+fn summary_of_an_empty_file() {
+    assert_eq!(
+        C3d::new().summary(),
+        "Processor: Intel, Float data\nFrames: 0\nPoints: 0\nAnalog: 0 channels\n\
+         Force platforms: 0\nEvents: 0\n"
+    );
+}
+
+#[test]
+fn summary_of_a_built_file() {
+    let mut c3d = C3dBuilder::new()
+        .point_labels(&["LASI", "RASI"])
+        .frame_rate(100.)
+        .push_frame(&[[0., 0., 0.], [1., 0., 0.]], &[])
+        .unwrap()
+        .push_frame(&[[0., 0., 0.], [1., 0., 0.]], &[])
+        .unwrap()
+        .build()
+        .unwrap();
+    c3d.events.add_event("Foot Strike", "Right", 0.01);
+    let summary = c3d.summary();
+    assert!(summary.contains("Frames: 2 (1 to 2) at 100 Hz, 0.020 s\n"));
+    assert!(summary.contains("\n  LASI, RASI\n"));
+    assert!(summary.contains("Analog: 0 channels\n"));
+    assert!(summary.contains("Events: 1\n  0.010 s: Right Foot Strike\n"));
+}
@@ -0,0 +1,140 @@
+use c3dio::prelude::*;
+use c3dio::{C3dBuilder, C3dRef, TextEncoding, WriteOptions};
+
+/// The bytes of a file with a point label and a group description in Windows-1252.
+fn windows_1252_file() -> Vec<u8> {
+    let mut bytes = std::fs::read("tests/data/short.c3d").unwrap();
+    let index = bytes.windows(4).position(|x| x == b"THEA").unwrap();
+    // "ŠHEA"
+    bytes[index] = 0x8A;
+    let index = bytes.windows(11).position(|x| x == b"Information").unwrap();
+    // "Informatión"
+    bytes[index + 9] = 0xF3;
+    bytes
+}
+
+fn written(c3d: &C3d, options: &WriteOptions) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    c3d.write_to_with_options(&mut bytes, options).unwrap();
+    bytes
+}
+
+fn contains(bytes: &[u8], text: &[u8]) -> bool {
+    bytes.windows(text.len()).any(|x| x == text)
+}
+
+#[test]
+fn encode_and_decode() {
+    let bytes = [b'a', 0x80, 0xE9, 0x9C];
+    assert_eq!(TextEncoding::Latin1.decode(&bytes), "a\u{80}é\u{9C}");
+    assert_eq!(TextEncoding::Windows1252.decode(&bytes), "a€éœ");
+    assert_eq!(TextEncoding::Utf8.decode(&bytes), "a\u{FFFD}\u{FFFD}");
+    assert_eq!(TextEncoding::Windows1252.encode("a€éœ"), bytes);
+    assert_eq!(TextEncoding::Latin1.encode("€é→"), [b'?', 0xE9, b'?']);
+    assert_eq!(TextEncoding::Windows1252.encode("€é→"), [0x80, 0xE9, b'?']);
+    assert_eq!(TextEncoding::Utf8.encode("é"), "é".as_bytes());
+}
+
+#[test]
+fn parse_windows_1252() {
+    let bytes = windows_1252_file();
+    // by default descriptions are UTF-8 and labels one byte per character
+    let c3d = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(c3d.points.labels[0], "\u{8A}HEA");
+    assert_eq!(
+        c3d.warnings,
+        vec![ParseWarning::InvalidDescription("MANUFACTURER".to_string())]
+    );
+
+    let options = ParseOptions::default().with_encoding(TextEncoding::Windows1252);
+    let c3d = C3d::from_bytes_with_options(&bytes, options.clone()).unwrap();
+    assert!(c3d.warnings.is_empty());
+    assert_eq!(c3d.encoding(), Some(TextEncoding::Windows1252));
+    assert_eq!(c3d.points.labels[0], "ŠHEA");
+    let description = c3d
+        .parameters
+        .get_group_description("MANUFACTURER")
+        .unwrap();
+    assert!(description.starts_with("Informatión"));
+
+    // a file is written in the encoding it was parsed with
+    let bytes = written(&c3d, &WriteOptions::default());
+    assert!(contains(&bytes, b"\x8AHEA"));
+    assert!(contains(&bytes, b"Informati\xF3n"));
+    let rewritten = C3d::from_bytes_with_options(&bytes, options).unwrap();
+    assert_eq!(rewritten.points.labels, c3d.points.labels);
+    assert_eq!(rewritten.parameters, c3d.parameters);
+
+    // and can be moved to UTF-8
+    let bytes = written(
+        &c3d,
+        &WriteOptions::default().with_encoding(TextEncoding::Utf8),
+    );
+    assert!(contains(&bytes, "ŠHEA".as_bytes()));
+    assert!(contains(&bytes, "Informatión".as_bytes()));
+    let options = ParseOptions::default().with_encoding(TextEncoding::Utf8);
+    let rewritten = C3d::from_bytes_with_options(&bytes, options).unwrap();
+    assert!(rewritten.warnings.is_empty());
+    assert_eq!(rewritten.points.labels[0], "ŠHEA");
+    assert_eq!(rewritten.points.labels[1..], c3d.points.labels[1..]);
+    assert_eq!(
+        rewritten.parameters.get_group_description("MANUFACTURER"),
+        Some(description)
+    );
+}
+
+#[test]
+fn write_utf8_labels() {
+    let mut c3d = C3dBuilder::new()
+        .point_labels(&["Šárka", "LASI"])
+        .frame_rate(100.)
+        .push_frame(&[[0.; 3], [1.; 3]], &[])
+        .unwrap()
+        .build()
+        .unwrap();
+    c3d.points.descriptions = vec!["Ferse links, größer".to_string(), String::new()];
+    c3d.parameters
+        .add_group("CUSTOM", "Kraftmessplatte für Messung")
+        .unwrap();
+    let options = WriteOptions::default().with_encoding(TextEncoding::Utf8);
+    let bytes = written(&c3d, &options);
+    assert!(C3d::validate_bytes(&bytes).unwrap().is_valid());
+    let parsed = C3d::from_bytes_with_options(
+        &bytes,
+        ParseOptions::default().with_encoding(TextEncoding::Utf8),
+    )
+    .unwrap();
+    assert_eq!(parsed.points.labels, ["Šárka", "LASI"]);
+    assert_eq!(parsed.points.descriptions[0], "Ferse links, größer");
+    // the labels are widened to fit the longest of them in UTF-8
+    let borrowed = C3dRef::from_bytes(&bytes).unwrap();
+    let labels = borrowed.parameter("POINT", "LABELS").unwrap();
+    assert!(labels.dimensions()[0] as usize >= "Šárka".len());
+    assert!(contains(&bytes, "Šárka".as_bytes()));
+
+    // the default writes character data one byte per character and descriptions in UTF-8
+    let bytes = written(&c3d, &WriteOptions::default());
+    assert!(contains(&bytes, b"\x8A\xE1rka"));
+    assert!(contains(&bytes, b"gr\xF6\xDFer"));
+    assert!(contains(&bytes, "für".as_bytes()));
+    let parsed = C3d::from_bytes_with_options(
+        &bytes,
+        ParseOptions::default().with_encoding(TextEncoding::Windows1252),
+    )
+    .unwrap();
+    assert_eq!(parsed.points.labels, ["Šárka", "LASI"]);
+    assert_eq!(parsed.points.descriptions[0], "Ferse links, größer");
+
+    let bytes = written(
+        &c3d,
+        &WriteOptions::default().with_encoding(TextEncoding::Latin1),
+    );
+    assert!(contains(&bytes, b"f\xFCr"));
+    // Latin-1 has no `Š`
+    assert!(contains(&bytes, b"?\xE1rka"));
+    let parsed = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(
+        parsed.parameters.get_group_description("CUSTOM").unwrap(),
+        "Kraftmessplatte f\u{FFFD}r Messung"
+    );
+}
@@ -0,0 +1,79 @@
+use c3dio::prelude::*;
+use c3dio::{C3dBuilder, Interpolation};
+
+fn linear_trial(first_frame: u16) -> C3d {
+    let mut builder = C3dBuilder::new()
+        .point_labels(&["M1"])
+        .frame_rate(100.)
+        .first_frame(first_frame)
+        .analog_channels(&["EMG"], 10);
+    for frame in 0..10 {
+        let analog: Vec<f64> = (0..10).map(|i| (frame * 10 + i) as f64).collect();
+        builder = builder
+            .push_frame(&[[frame as f32 * 10., 0., 0.]], &analog)
+            .unwrap();
+    }
+    builder.build().unwrap()
+}
+
+#[test]
+fn point_at_time() {
+    let c3d = linear_trial(1);
+    assert_eq!(c3d.frame_position(0.), Some(0.));
+    assert_eq!(c3d.frame_position(0.025), Some(2.5));
+    let nearest = c3d.point_at_time("M1", 0.024, None).unwrap();
+    assert_eq!(nearest.point, [20., 0., 0.]);
+    let linear = c3d
+        .point_at_time("M1", 0.025, Some(Interpolation::Linear))
+        .unwrap();
+    assert!((linear.point[0] - 25.).abs() < 1e-4);
+    let cubic = c3d
+        .point_at_time("M1", 0.025, Some(Interpolation::Cubic))
+        .unwrap();
+    assert!((cubic.point[0] - 25.).abs() < 1e-4);
+}
+
+#[test]
+fn analog_at_time() {
+    let c3d = linear_trial(1);
+    assert_eq!(c3d.analog_at_time("EMG", 0.0123, None), Some(12.));
+    let linear = c3d
+        .analog_at_time("EMG", 0.01235, Some(Interpolation::Linear))
+        .unwrap();
+    assert!((linear - 12.35).abs() < 1e-6);
+    assert_eq!(c3d.analog_at_time("EMG", 0.0995, None), Some(99.));
+}
+
+#[test]
+fn time_from_first_frame() {
+    // times count from frame 1, so frame 11 is stored first and starts at 0.1 s
+    let c3d = linear_trial(11);
+    assert_eq!(c3d.frame_position(0.1), Some(0.));
+    assert_eq!(c3d.frame_position(0.), None);
+    assert_eq!(
+        c3d.point_at_time("M1", 0.13, None).unwrap().point,
+        [30., 0., 0.]
+    );
+    assert_eq!(c3d.analog_at_time("EMG", 0.1, None), Some(0.));
+}
+
+#[test]
+fn time_outside_trial() {
+    let c3d = linear_trial(1);
+    assert_eq!(c3d.point_at_time("M1", -0.01, None), None);
+    assert_eq!(c3d.point_at_time("M1", 0.1, None), None);
+    assert!(c3d.point_at_time("M1", 0.094, None).is_some());
+    assert_eq!(c3d.analog_at_time("EMG", 0.1, None), None);
+    assert_eq!(c3d.point_at_time("M2", 0.05, None), None);
+    assert_eq!(c3d.analog_at_time("M1", 0.05, None), None);
+}
+
+#[test]
+fn time_of_events() {
+    let c3d = C3d::load("tests/c3d_org_samples/sample_03/gait-pig.c3d").unwrap();
+    for event in c3d.events.iter() {
+        let frame = c3d.event_frame(event);
+        let position = c3d.frame_position(event.time as f64);
+        assert_eq!(frame, position.map(|position| position.round() as usize));
+    }
+}
@@ -0,0 +1,92 @@
+use c3dio::prelude::*;
+use c3dio::{C3dBuilder, Timecode, TimecodeGroup, TimecodeStandard};
+
+#[test]
+fn timecodes_are_parsed_and_displayed() {
+    let timecode = Timecode::parse("10:01:02:03").unwrap();
+    assert_eq!(timecode, Timecode::new(10, 1, 2, 3));
+    assert_eq!(timecode.to_string(), "10:01:02:03");
+    let timecode = Timecode::parse("01:00:00;02.3").unwrap();
+    assert!(timecode.drop_frame);
+    assert_eq!(timecode.subframe, 3);
+    assert_eq!(timecode.to_string(), "01:00:00;02.3");
+    assert_eq!(Timecode::parse("10:00:00"), None);
+    assert_eq!(Timecode::parse("10:00:xx:00"), None);
+}
+
+#[test]
+fn drop_frame_timecodes_skip_frame_numbers() {
+    let timecode = |s: &str| Timecode::parse(s).unwrap();
+    assert_eq!(timecode("00:00:59;29").to_frame_count(30), 1799);
+    assert_eq!(timecode("00:01:00;02").to_frame_count(30), 1800);
+    assert_eq!(timecode("00:10:00;00").to_frame_count(30), 17982);
+    assert_eq!(timecode("01:00:00;00").to_frame_count(30), 107892);
+    assert_eq!(timecode("00:01:00:00").to_frame_count(30), 1800);
+    assert_eq!(
+        Timecode::from_frame_count(1800, 30, true),
+        timecode("00:01:00;02")
+    );
+    for count in (0..200_000).step_by(7) {
+        for (rate, drop_frame) in [(30, true), (60, true), (25, false), (24, false)] {
+            let timecode = Timecode::from_frame_count(count, rate, drop_frame);
+            assert_eq!(timecode.to_frame_count(rate), count);
+        }
+    }
+}
+
+#[test]
+fn files_without_the_group_have_no_timecode() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    assert_eq!(c3d.timecode(), None);
+    assert_eq!(c3d.frame_timecode(0), None);
+}
+
+#[test]
+fn frames_are_converted_to_timecodes() {
+    let mut builder = C3dBuilder::new().point_labels(&["A"]).frame_rate(100.);
+    for _ in 0..1000 {
+        builder = builder.push_frame(&[[0., 0., 0.]], &[]).unwrap();
+    }
+    let mut c3d = builder.build().unwrap();
+    let mut timecode = TimecodeGroup::new(TimecodeStandard::Pal, 4);
+    timecode.start = Some(Timecode {
+        subframe: 2,
+        ..Timecode::new(10, 0, 0, 24)
+    });
+    c3d.set_timecode(&timecode).unwrap();
+
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    let c3d = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(c3d.timecode(), Some(timecode.clone()));
+    assert_eq!(c3d.frame_timecode(0).unwrap().to_string(), "10:00:00:24.2");
+    assert_eq!(c3d.frame_timecode(2).unwrap().to_string(), "10:00:01:00");
+    assert_eq!(c3d.frame_timecode(102).unwrap().to_string(), "10:00:02:00");
+    let frame = Timecode::parse("10:00:02:00.1").unwrap();
+    assert_eq!(c3d.timecode_frame(&frame), Some(103));
+    assert_eq!(c3d.timecode_frame(&Timecode::new(10, 0, 0, 0)), None);
+    assert_eq!(c3d.timecode_frame(&Timecode::new(11, 0, 0, 0)), None);
+    for frame in [0, 1, 5, 999] {
+        let timecode = c3d.frame_timecode(frame).unwrap();
+        assert_eq!(c3d.timecode_frame(&timecode), Some(frame));
+    }
+}
+
+#[test]
+fn subframes_are_derived_from_the_frame_rate() {
+    let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    c3d.parameters.set("TIMECODE", "STANDARD", "NTSC").unwrap();
+    c3d.parameters.set("TIMECODE", "DROP_FRAMES", 1i16).unwrap();
+    c3d.parameters
+        .set("TIMECODE", "START", "01:00:00;02")
+        .unwrap();
+    let timecode = c3d.timecode().unwrap();
+    assert_eq!(timecode.standard, TimecodeStandard::Ntsc);
+    assert!(timecode.drop_frames);
+    assert!(!timecode.field_numbers);
+    assert_eq!(
+        timecode.subframes_per_frame,
+        (c3d.points.frame_rate / 30.).round() as u16
+    );
+    assert_eq!(c3d.frame_timecode(0).unwrap().to_string(), "01:00:00;02");
+}
@@ -0,0 +1,82 @@
+use c3dio::prelude::*;
+use std::io::Cursor;
+
+const OPENSIM_TRC: &str = "PathFileType\t4\t(X/Y/Z)\twalk.trc
+DataRate\tCameraRate\tNumFrames\tNumMarkers\tUnits\tOrigDataRate\tOrigDataStartFrame\tOrigNumFrames
+60\t60\t3\t2\tmm\t60\t10\t3
+Frame#\tTime\tLASI\t\t\tRASI\t\t
+\t\tX1\tY1\tZ1\tX2\tY2\tZ2
+
+10\t0.15\t1.0\t2.0\t3.0\t4.0\t5.0\t6.0
+11\t0.1667\t1.5\t2.5\t3.5\t\t\t
+12\t0.1833\t2.0\t3.0\t4.0\tNaN\tNaN\tNaN
+";
+
+#[test]
+fn read_opensim_trc() {
+    let trc = Trc::from_reader(Cursor::new(OPENSIM_TRC)).unwrap();
+    assert_eq!(trc.data_rate, 60.);
+    assert_eq!(trc.num_frames, 3);
+    assert_eq!(trc.first_frame, 10);
+    assert_eq!(trc.marker_names, vec!["LASI", "RASI"]);
+    assert_eq!(trc.units, ['m', 'm', ' ', ' ']);
+    assert_eq!(trc.data[(1, 0)].point, [1.5, 2.5, 3.5]);
+    assert!(!trc.data[(1, 1)].is_valid());
+    assert!(!trc.data[(2, 1)].is_valid());
+
+    let c3d = trc.to_c3d().unwrap();
+    assert_eq!(c3d.points.labels, vec!["LASI", "RASI"]);
+    assert_eq!(c3d.points.frame_rate, 60.);
+    assert_eq!(c3d.points.first_frame, 10);
+    assert_eq!(c3d.points.last_frame, 12);
+    assert_eq!(c3d.points.size(), (3, 2));
+    assert_eq!(c3d.points[(0, 1)].point, [4., 5., 6.]);
+    assert!(!c3d.points[(1, 1)].is_valid());
+
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    let written = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(written.points.labels, vec!["LASI", "RASI"]);
+    assert_eq!(written.points.points.flatten(), c3d.points.points.flatten());
+}
+
+#[test]
+fn trc_round_trip() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let mut bytes = Vec::new();
+    Trc::from_c3d(&c3d).write_to(&mut bytes).unwrap();
+    let imported = Trc::from_reader(Cursor::new(bytes))
+        .unwrap()
+        .to_c3d()
+        .unwrap();
+    assert_eq!(imported.points.frame_rate, c3d.points.frame_rate);
+    assert_eq!(imported.points.first_frame, c3d.points.first_frame);
+    assert_eq!(imported.points.size(), c3d.points.size());
+    for (imported, original) in imported.points.iter().zip(c3d.points.iter()) {
+        assert_eq!(imported.is_valid(), original.is_valid());
+        if original.is_valid() {
+            assert_eq!(imported.point, original.point);
+        }
+    }
+}
+
+#[test]
+fn invalid_trc() {
+    let trc = OPENSIM_TRC.replace("\t1.5\t", "\tx\t");
+    assert!(matches!(
+        Trc::from_reader(Cursor::new(trc)),
+        Err(C3dParseError::InvalidTrcLine(8, _))
+    ));
+    // more markers than the columns of the file have room for
+    let trc = OPENSIM_TRC.replace("\t3\t2\tmm", "\t3\t1000000000\tmm");
+    assert!(matches!(
+        Trc::from_reader(Cursor::new(trc)),
+        Err(C3dParseError::InvalidTrcLine(3, _))
+    ));
+    let trc = OPENSIM_TRC.replace("\t3\t2\tmm", "\t3\t3\tmm");
+    assert!(Trc::from_reader(Cursor::new(trc)).is_err());
+    assert!(matches!(
+        Trc::from_reader(Cursor::new("PathFileType\t4\n")),
+        Err(C3dParseError::InvalidTrcLine(_, _))
+    ));
+}
@@ -0,0 +1,62 @@
+use c3dio::prelude::*;
+
+#[test]
+fn get_parsed_parameter() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let names = c3d
+        .parameters
+        .get_vec::<String>("SUBJECTS", "NAMES")
+        .unwrap();
+    assert_eq!(names, vec!["HelenHayes".to_string()]);
+}
+
+#[test]
+fn set_and_get() {
+    let mut parameters = Parameters::new();
+    parameters.set("POINT", "SCALE", -0.1f32).unwrap();
+    parameters.set("POINT", "USED", 2i16).unwrap();
+    parameters
+        .set(
+            "POINT",
+            "LABELS",
+            vec!["LASI".to_string(), "RASI".to_string()],
+        )
+        .unwrap();
+    assert_eq!(parameters.get_as::<f32>("POINT", "SCALE").unwrap(), -0.1);
+    assert_eq!(parameters.get_as::<i16>("POINT", "USED").unwrap(), 2);
+    assert_eq!(
+        parameters.get_vec::<String>("POINT", "LABELS").unwrap(),
+        vec!["LASI", "RASI"]
+    );
+}
+
+#[test]
+fn set_keeps_description() {
+    let mut parameters = Parameters::new();
+    let mut scale = Parameter::float(1.);
+    scale.description = "scale factor".to_string();
+    parameters.insert("POINT", "SCALE", scale);
+    parameters.set("POINT", "SCALE", 0.5f32).unwrap();
+    let scale = parameters.get("POINT", "SCALE").unwrap();
+    assert_eq!(scale.description, "scale factor");
+    assert_eq!(parameters.get_as::<f32>("POINT", "SCALE").unwrap(), 0.5);
+}
+
+#[test]
+fn missing_parameter() {
+    let parameters = Parameters::new();
+    assert!(matches!(
+        parameters.get_as::<f32>("POINT", "SCALE"),
+        Err(C3dParseError::ParameterNotFound(_, _))
+    ));
+}
+
+#[test]
+fn wrong_type() {
+    let mut parameters = Parameters::new();
+    parameters.set("POINT", "UNITS", "mm").unwrap();
+    assert!(matches!(
+        parameters.get_as::<f32>("POINT", "UNITS"),
+        Err(C3dParseError::InvalidData(_, _))
+    ));
+}
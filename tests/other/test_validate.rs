@@ -0,0 +1,114 @@
+use c3dio::prelude::*;
+use c3dio::{C3dBuilder, ScreenAxis, ValidationLocation, ValidationReport};
+
+fn errors_at(report: &ValidationReport, location: ValidationLocation) -> usize {
+    report
+        .errors()
+        .filter(|issue| issue.location == location)
+        .count()
+}
+
+fn parameter(group: &str, parameter: &str) -> ValidationLocation {
+    ValidationLocation::Parameter(group.to_string(), parameter.to_string())
+}
+
+#[test]
+fn valid_file() {
+    let report = C3d::validate_file("tests/data/short.c3d").unwrap();
+    assert!(report.is_valid(), "{:?}", report);
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    assert!(c3d.validate().unwrap().is_valid());
+
+    // the file as written is valid too
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    assert!(C3d::validate_bytes(&bytes).unwrap().is_valid());
+}
+
+#[test]
+fn constructed_file() {
+    let c3d = C3dBuilder::new()
+        .frame_rate(100.)
+        .point_labels(&["A", "B"])
+        .analog_channels(&["Fz"], 2)
+        .push_frame(&[[0.; 3], [1.; 3]], &[1., 2.])
+        .unwrap()
+        .build()
+        .unwrap();
+    let report = c3d.validate().unwrap();
+    assert!(report.issues.is_empty(), "{:?}", report);
+}
+
+#[test]
+fn missing_labels() {
+    let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    c3d.points.labels.pop();
+    let report = c3d.validate().unwrap();
+    assert!(!report.is_valid());
+    assert_eq!(errors_at(&report, parameter("POINT", "LABELS")), 1);
+}
+
+#[test]
+fn stale_data_start() {
+    let mut bytes = std::fs::read("tests/data/short.c3d").unwrap();
+    let data_start = u16::from_le_bytes([bytes[16], bytes[17]]) + 1;
+    bytes[16..18].copy_from_slice(&data_start.to_le_bytes());
+    let report = C3d::validate_bytes(&bytes).unwrap();
+    assert_eq!(errors_at(&report, parameter("POINT", "DATA_START")), 1);
+    assert!(report
+        .warnings()
+        .any(|issue| issue.location == ValidationLocation::Header(9)));
+}
+
+#[test]
+fn scale_factor_sign() {
+    let mut bytes = std::fs::read("tests/data/short.c3d").unwrap();
+    let scale = f32::from_le_bytes(bytes[12..16].try_into().unwrap());
+    assert!(scale < 0.);
+    bytes[12..16].copy_from_slice(&(-scale).to_le_bytes());
+    let report = C3d::validate_bytes(&bytes).unwrap();
+    assert_eq!(errors_at(&report, parameter("POINT", "SCALE")), 1);
+    // integer frames are smaller, so the data section still holds every frame
+    assert_eq!(errors_at(&report, ValidationLocation::Data), 0);
+}
+
+#[test]
+fn frame_count_mismatch() {
+    let report = C3d::validate_file("tests/c3d_org_samples/sample_13/Dance1.c3d").unwrap();
+    assert_eq!(errors_at(&report, ValidationLocation::Header(5)), 1);
+    let issue = report.errors().next().unwrap();
+    assert_eq!(
+        issue.to_string(),
+        "Error in header word 5: frames 2 to 499, but POINT:FRAMES is 499"
+    );
+}
+
+#[test]
+fn truncated_data() {
+    let bytes = std::fs::read("tests/data/short.c3d").unwrap();
+    let report = C3d::validate_bytes(&bytes[..bytes.len() - 1024]).unwrap();
+    assert_eq!(errors_at(&report, ValidationLocation::Data), 1);
+}
+
+#[test]
+fn contradicting_screen_axes() {
+    let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let warnings_at = |report: &ValidationReport, name: &str| {
+        report
+            .warnings()
+            .filter(|issue| issue.location == parameter("POINT", name))
+            .count()
+    };
+    c3d.points.x_screen = Some(['+', 'Y']);
+    c3d.points.y_screen = Some(['-', 'Y']);
+    let report = c3d.validate().unwrap();
+    assert!(report.is_valid());
+    assert_eq!(warnings_at(&report, "Y_SCREEN"), 1);
+    c3d.points.x_screen = Some(['+', 'W']);
+    let report = c3d.validate().unwrap();
+    assert_eq!(warnings_at(&report, "X_SCREEN"), 1);
+    assert_eq!(warnings_at(&report, "Y_SCREEN"), 0);
+    c3d.set_screen_axes(ScreenAxis::PlusX, ScreenAxis::PlusY)
+        .unwrap();
+    assert!(c3d.validate().unwrap().issues.is_empty());
+}
@@ -0,0 +1,92 @@
+use c3dio::prelude::*;
+use c3dio::Vendor;
+
+fn vendor(file_name: &str) -> Option<Vendor> {
+    C3d::load_header_and_parameters(file_name.into())
+        .unwrap()
+        .manufacturer
+        .vendor()
+}
+
+#[test]
+fn vendors_are_recognized() {
+    let samples = "tests/c3d_org_samples";
+    assert_eq!(vendor("tests/data/short.c3d"), Some(Vendor::MotionAnalysis));
+    assert_eq!(
+        vendor(&format!("{}/sample_32/vicon_zerowire.c3d", samples)),
+        Some(Vendor::Vicon)
+    );
+    assert_eq!(
+        vendor(&format!("{}/sample_17/128analogchannels.c3d", samples)),
+        Some(Vendor::Vicon)
+    );
+    assert_eq!(
+        vendor(&format!("{}/sample_34/Basketball.c3d", samples)),
+        Some(Vendor::Xsens)
+    );
+    assert_eq!(
+        vendor(&format!(
+            "{}/sample_00/Codamotion/codamotion_gaitwands_20150204.c3d",
+            samples
+        )),
+        Some(Vendor::Codamotion)
+    );
+    assert_eq!(
+        vendor(&format!("{}/sample_23/Vicon_analysis.c3d", samples)),
+        Some(Vendor::Other("Motion Lab Systems".to_string()))
+    );
+    assert_eq!(vendor(&format!("{}/sample_02/pc_real.c3d", samples)), None);
+}
+
+#[test]
+fn qualisys_is_recognized_by_company_or_software() {
+    let mut manufacturer = Manufacturer::new();
+    manufacturer.software = Some("Qualisys Track Manager".to_string());
+    assert_eq!(manufacturer.vendor(), Some(Vendor::Qualisys));
+    manufacturer.company = Some("Qualisys AB".to_string());
+    assert_eq!(manufacturer.vendor(), Some(Vendor::Qualisys));
+}
+
+#[test]
+fn processing_measurements() {
+    let c3d = C3d::load("tests/c3d_org_samples/sample_32/vicon_zerowire.c3d").unwrap();
+    let processing = c3d.processing();
+    assert_eq!(processing.body_mass(), Some(54.5));
+    assert_eq!(processing.height(), Some(1655.));
+    assert_eq!(processing.left_leg_length(), Some(880.));
+    assert_eq!(processing.get("lkneewidth"), Some(93.4));
+    assert!((processing.inter_asis_distance().unwrap() - 223.847).abs() < 1e-3);
+
+    assert!(C3d::load("tests/data/short.c3d")
+        .unwrap()
+        .processing()
+        .is_empty());
+}
+
+#[test]
+fn processing_names_with_a_prefix() {
+    let c3d = C3d::load_with_options(
+        "tests/c3d_org_samples/sample_33/bigparlove.c3d",
+        ParseOptions::lenient(),
+    )
+    .unwrap();
+    let processing = c3d.processing();
+    assert!(processing.measurements.contains_key("$BODYMASS"));
+    assert_eq!(processing.body_mass(), processing.get("$BODYMASS"));
+}
+
+#[test]
+fn processing_is_written_back() {
+    let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let mut processing = c3d.processing();
+    processing.set("BodyMass", 72.5);
+    processing.set("LLegLength", 910.);
+    c3d.set_processing(&processing).unwrap();
+    assert!(c3d.is_dirty());
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    let written = C3d::from_bytes(&bytes).unwrap().processing();
+    assert_eq!(written, processing);
+    assert_eq!(written.body_mass(), Some(72.5));
+    assert_eq!(written.left_leg_length(), Some(910.));
+}
@@ -1,9 +1,101 @@
+mod common;
+
 mod write {
+    mod test_builder;
+    mod test_bvh;
+    mod test_csv;
+    mod test_custom_groups;
+    mod test_events;
+    mod test_many_labels;
+    mod test_parameter_metadata;
+    mod test_parameters_in_place;
+    mod test_sto;
+    mod test_stream_writer;
+    mod test_table;
+    mod test_transcode;
+    mod test_trc;
     mod test_write_c3d;
+    mod test_write_integer;
+    mod test_write_to;
 }
 
 mod other {
+    mod test_add_point;
+    mod test_analog_channels;
+    mod test_analog_only;
+    mod test_analog_raw;
+    mod test_array_export;
+    mod test_as_intel;
+    #[cfg(feature = "rayon")]
+    mod test_batch;
+    mod test_borrowed;
     mod test_byte_and_file_parity;
+    #[cfg(feature = "cli")]
+    mod test_cli;
+    mod test_concat;
+    mod test_crop;
+    mod test_descriptions;
+    mod test_diff;
+    mod test_error_context;
+    mod test_event_frames;
+    #[cfg(feature = "ffi")]
+    mod test_ffi;
+    mod test_frame_byte_offset;
+    mod test_frame_count;
+    mod test_frame_iter;
+    mod test_frame_sink;
+    mod test_from_reader;
+    mod test_header_and_parameters;
+    mod test_label_index;
+    mod test_lenient_parsing;
+    mod test_long_trials;
+    mod test_max_interpolation_gap;
+    mod test_merge_points;
+    mod test_parameter_blocks;
+    mod test_parse_limits;
+    mod test_point_rate;
+    mod test_preserve_bytes;
+    mod test_processor_slices;
+    mod test_progress;
+    mod test_relabel;
+    mod test_repair;
+    mod test_residuals;
+    mod test_rotations;
+    mod test_selective_loading;
+    #[cfg(feature = "serde")]
+    mod test_serde;
+    mod test_slices;
+    mod test_split;
+    mod test_subjects;
+    mod test_summary;
+    mod test_text_encoding;
+    mod test_time_sampling;
+    mod test_timecode;
+    mod test_trc_import;
+    mod test_typed_parameters;
+    mod test_validate;
+    mod test_vendor;
+}
+
+mod analysis {
+    mod test_convert_units;
+    mod test_derivatives;
+    mod test_emg;
+    mod test_event_detection;
+    mod test_events_in_range;
+    mod test_filters;
+    mod test_force_platforms;
+    mod test_gait_cycles;
+    mod test_gap_filling;
+    mod test_joint_angle;
+    mod test_marker_centroid;
+    mod test_point_channels;
+    mod test_quality;
+    mod test_resample;
+    mod test_rigid_body;
+    mod test_smoothing;
+    mod test_split_by_subject;
+    mod test_transform;
 }
 
 mod c3d_org_samples {
@@ -0,0 +1,166 @@
+use c3dio::prelude::*;
+use c3dio::C3dBuilder;
+
+fn build(format: DataFormat, scale_factor: f32, processor: Processor) -> C3d {
+    let mut builder = C3dBuilder::new()
+        .point_labels(&["LASI", "RASI", "SACR"])
+        .point_units("mm")
+        .frame_rate(50.)
+        .data_format(format, scale_factor)
+        .processor(processor)
+        .analog_channels(&["Fx", "Fz"], 4)
+        .analog_units(&["N", "N"]);
+    for frame in 0..20 {
+        let x = frame as f32;
+        let analog: Vec<f64> = (0..8).map(|i| (frame * 8 + i) as f64).collect();
+        builder = builder
+            .push_frame(&[[x, 0., 10.], [x, 100., 10.], [x, 50., -20.]], &analog)
+            .unwrap();
+    }
+    builder.build().unwrap()
+}
+
+fn write_and_read(c3d: &C3d) -> C3d {
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    C3d::from_bytes(&bytes).unwrap()
+}
+
+#[test]
+fn builder_fills_header_and_parameters() {
+    let c3d = build(DataFormat::Float, 1., Processor::Intel);
+    assert_eq!(c3d.points.size(), (20, 3));
+    assert_eq!(c3d.points.first_frame, 1);
+    assert_eq!(c3d.points.last_frame, 20);
+    assert_eq!(c3d.analog.size(), (80, 2));
+    assert_eq!(c3d.analog.samples_per_frame, 8);
+    assert_eq!(c3d.analog.rate, 200.);
+
+    let written = write_and_read(&c3d);
+    assert_eq!(written.points.labels, vec!["LASI", "RASI", "SACR"]);
+    assert_eq!(written.points.units, ['m', 'm', ' ', ' ']);
+    assert_eq!(written.points.frame_rate, 50.);
+    assert_eq!(written.points.flatten(), c3d.points.flatten());
+    assert_eq!(written.analog.labels, vec!["Fx", "Fz"]);
+    assert_eq!(written.analog.units, vec!["N", "N"]);
+    assert_eq!(written.analog.rate, 200.);
+    assert_eq!(written.analog.flatten(), c3d.analog.flatten());
+    assert_eq!(written.frame_count(), 20);
+}
+
+#[test]
+fn builder_integer_format() {
+    let c3d = build(DataFormat::Integer, 0.5, Processor::SgiMips);
+    let written = write_and_read(&c3d);
+    assert_eq!(written.processor(), Processor::SgiMips);
+    assert_eq!(written.points.format, DataFormat::Integer);
+    assert_eq!(written.points.scale_factor, 0.5);
+    assert_eq!(written.points[7][2].point, [7., 50., -20.]);
+    assert_eq!(written.analog[9][1], 19.);
+}
+
+#[test]
+fn builder_without_frames() {
+    let c3d = C3dBuilder::new().frame_rate(100.).build().unwrap();
+    let written = write_and_read(&c3d);
+    assert_eq!(written.points.cols(), 0);
+    assert_eq!(written.frame_count(), 0);
+}
+
+#[test]
+fn builder_rejects_mismatched_frames() {
+    let builder = C3dBuilder::new()
+        .point_labels(&["A", "B"])
+        .analog_channels(&["Fz"], 2);
+    assert!(matches!(
+        C3dBuilder::new()
+            .point_labels(&["A", "B"])
+            .push_frame(&[[0.; 3]], &[]),
+        Err(C3dBuilderError::PointCountMismatch(2, 1))
+    ));
+    assert!(matches!(
+        builder.push_frame(&[[0.; 3], [0.; 3]], &[1.]),
+        Err(C3dBuilderError::AnalogSampleCountMismatch(2, 1))
+    ));
+    let changed = C3dBuilder::new()
+        .point_labels(&["A"])
+        .push_frame(&[[0.; 3]], &[])
+        .unwrap()
+        .point_labels(&["A", "B"]);
+    assert!(matches!(
+        changed.build(),
+        Err(C3dBuilderError::PointCountMismatch(2, 1))
+    ));
+}
+
+fn force_platforms() -> C3dBuilder {
+    let corners = |x: f32| {
+        [
+            [x + 400., 600., 0.],
+            [x, 600., 0.],
+            [x, 0., 0.],
+            [x + 400., 0., 0.],
+        ]
+    };
+    let mut rows = [[0.; 6]; 6];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[i] = 2.;
+    }
+    // the second platform reads Fz from channel 7, and its Fy picks up part of it
+    rows[1][2] = 0.5;
+    C3dBuilder::new()
+        .frame_rate(100.)
+        .analog_channels(&["Fx1", "Fy1", "Fz1", "Mx1", "My1", "Mz1", "Fz2"], 1)
+        .force_platform(ForcePlatform::new(
+            ForcePlatformType::Type2,
+            corners(0.),
+            [0., 0., 40.],
+            &[1, 2, 3, 4, 5, 6],
+        ))
+        .force_platform(
+            ForcePlatform::new(
+                ForcePlatformType::Type4,
+                corners(500.),
+                [0., 0., 40.],
+                &[1, 2, 7, 4, 5, 6],
+            )
+            .with_cal_matrix(rows),
+        )
+}
+
+#[test]
+fn builder_force_platforms() {
+    let c3d = force_platforms()
+        .push_frame(&[], &[0., 0., 300., 0., 0., 0., 100.])
+        .unwrap()
+        .build()
+        .unwrap();
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, Processor::Intel).unwrap();
+    assert!(C3d::validate_bytes(&bytes).unwrap().is_valid());
+    let written = C3d::from_bytes(&bytes).unwrap();
+    assert_eq!(written.forces, c3d.forces);
+    assert_eq!(written.forces.len(), 2);
+    assert_eq!(written.force(0, 0), Some([0., 0., 300.]));
+    assert_eq!(written.force(1, 0), Some([0., 50., 200.]));
+}
+
+#[test]
+fn builder_rejects_missing_force_platform_channels() {
+    let builder = C3dBuilder::new()
+        .analog_channels(&["Fz"], 1)
+        .force_platform(ForcePlatform::new(
+            ForcePlatformType::Type2,
+            [[0.; 3]; 4],
+            [0.; 3],
+            &[1, 2, 3, 4, 5, 6],
+        ));
+    assert!(matches!(
+        builder.build(),
+        Err(C3dBuilderError::ForcePlatformChannelNotFound(0, 2))
+    ));
+    assert!(matches!(
+        force_platforms().analog_channels(&["Fz"], 1).build(),
+        Err(C3dBuilderError::ForcePlatformChannelNotFound(0, 2))
+    ));
+}
@@ -0,0 +1,103 @@
+use c3dio::prelude::*;
+use c3dio::Bvh;
+use test_files::TestFiles;
+
+fn bvh_lines(bvh: &Bvh) -> Vec<String> {
+    let mut bytes = Vec::new();
+    bvh.write_to(&mut bytes).unwrap();
+    String::from_utf8(bytes)
+        .unwrap()
+        .lines()
+        .map(|line| line.to_string())
+        .collect()
+}
+
+fn motion_values(line: &str) -> Vec<f32> {
+    line.split(' ')
+        .map(|value| value.parse().unwrap())
+        .collect()
+}
+
+#[test]
+fn bvh_hierarchy() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let lines = bvh_lines(&Bvh::from_c3d(&c3d));
+    let num_frames = c3d.points.rows();
+    let num_markers = c3d.points.cols();
+    assert_eq!(lines[0], "HIERARCHY");
+    assert_eq!(lines[1], "ROOT Markers");
+    let joints: Vec<&String> = lines.iter().filter(|line| line.contains("JOINT")).collect();
+    assert_eq!(joints.len(), num_markers);
+    assert_eq!(
+        joints[0].trim(),
+        format!("JOINT {}", c3d.points.labels[0].trim().replace(' ', "_"))
+    );
+    let motion = lines.iter().position(|line| line == "MOTION").unwrap();
+    assert_eq!(lines[motion + 1], format!("Frames: {}", num_frames));
+    let frame_time: f32 = lines[motion + 2]
+        .strip_prefix("Frame Time: ")
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!((frame_time - 1. / c3d.points.frame_rate).abs() < 1e-6);
+    assert_eq!(lines.len(), motion + 3 + num_frames);
+    let values = motion_values(&lines[motion + 3]);
+    assert_eq!(values.len(), 6 + 3 * num_markers);
+}
+
+#[test]
+fn bvh_selected_markers() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let labels = [c3d.points.labels[2].clone(), c3d.points.labels[0].clone()];
+    let bvh = Bvh::from_c3d_markers(&c3d, &[&labels[0], "NOT A MARKER", &labels[1]]);
+    assert_eq!(bvh.positions.cols(), 2);
+    assert_eq!(bvh.joint_names[0], labels[0].trim().replace(' ', "_"));
+    let point = c3d.points[(0, 2)];
+    if point.is_valid() {
+        assert_eq!(bvh.positions[(0, 0)], point.point);
+    }
+}
+
+#[test]
+fn bvh_holds_gaps() {
+    let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let valid = c3d.points[(3, 0)];
+    for frame in 0..3 {
+        c3d.points[(frame, 0)].residual = -1.;
+    }
+    c3d.points[(3, 0)].residual = 0.;
+    c3d.points[(4, 0)].residual = -1.;
+    let bvh = Bvh::from_c3d(&c3d);
+    for frame in 0..5 {
+        assert_eq!(bvh.positions[(frame, 0)], valid.point);
+    }
+}
+
+#[test]
+fn bvh_axes_and_scale() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let bvh = Bvh::from_c3d(&c3d);
+    let converted = bvh.clone().with_y_up().with_scale(0.001);
+    let [x, y, z] = bvh.positions[(0, 0)];
+    let [cx, cy, cz] = converted.positions[(0, 0)];
+    assert!((cx - x * 0.001).abs() < 1e-6);
+    assert!((cy - z * 0.001).abs() < 1e-6);
+    assert!((cz + y * 0.001).abs() < 1e-6);
+    let lines = bvh_lines(&converted);
+    let motion = lines.iter().position(|line| line == "MOTION").unwrap();
+    let values = motion_values(&lines[motion + 3]);
+    assert_eq!(values[..6], [0.; 6]);
+    assert_eq!(values[6..9], [cx, cy, cz]);
+}
+
+#[test]
+fn bvh_write_extension() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let bvh = Bvh::from_c3d(&c3d);
+    let files = TestFiles::new();
+    assert!(bvh.write(files.path().join("short.trc")).is_err());
+    let path = files.path().join("short.bvh");
+    bvh.write(path.clone()).unwrap();
+    let written = std::fs::read_to_string(path).unwrap();
+    assert!(written.starts_with("HIERARCHY\n"));
+}
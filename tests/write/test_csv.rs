@@ -0,0 +1,99 @@
+use c3dio::prelude::*;
+use c3dio::{Csv, CsvValue};
+
+fn csv_lines(csv: &Csv) -> Vec<String> {
+    let mut bytes = Vec::new();
+    csv.write_to(&mut bytes).unwrap();
+    String::from_utf8(bytes)
+        .unwrap()
+        .lines()
+        .map(|line| line.to_string())
+        .collect()
+}
+
+#[test]
+fn points_csv() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let lines = csv_lines(&Csv::points_from_c3d(&c3d));
+    let num_points = c3d.points.cols();
+    assert_eq!(lines.len(), 1 + c3d.points.rows());
+    let header: Vec<&str> = lines[0].split(',').collect();
+    assert_eq!(header.len(), 2 + 3 * num_points);
+    assert_eq!(header[..2], ["Frame", "Time"]);
+    assert_eq!(header[2], format!("{}_X", c3d.points.labels[0].trim()));
+    for (frame, line) in lines[1..].iter().enumerate() {
+        let values: Vec<&str> = line.split(',').collect();
+        assert_eq!(values.len(), 2 + 3 * num_points);
+        assert_eq!(
+            values[0].parse::<usize>().unwrap(),
+            frame + c3d.points.first_frame as usize
+        );
+        let point = &c3d.points[(frame, 0)];
+        if point.is_valid() {
+            assert_eq!(values[3].parse::<f32>().unwrap(), point[1]);
+        } else {
+            assert!(values[3].is_empty());
+        }
+    }
+}
+
+#[test]
+fn analog_csv_with_options() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let lines = csv_lines(
+        &Csv::analog_from_c3d(&c3d)
+            .with_delimiter('\t')
+            .with_precision(2),
+    );
+    assert_eq!(lines.len(), 1 + c3d.analog.rows());
+    let header: Vec<&str> = lines[0].split('\t').collect();
+    assert_eq!(header.len(), 1 + c3d.analog.cols());
+    assert_eq!(header[0], "Time");
+    let values: Vec<&str> = lines[2].split('\t').collect();
+    assert_eq!(values[1], format!("{:.2}", c3d.analog[1][0]));
+    let time = values[0].parse::<f64>().unwrap();
+    let expected = (c3d.points.first_frame - 1) as f64 / c3d.points.frame_rate as f64
+        + 1. / c3d.analog.rate as f64;
+    assert!((time - expected).abs() < 0.01);
+}
+
+#[test]
+fn points_and_analog_csv() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let csv = Csv::from_c3d(&c3d);
+    let samples_per_frame = c3d.analog.samples_per_channel_per_frame as usize;
+    let num_columns = 2 + 3 * c3d.points.cols() + c3d.analog.cols() * samples_per_frame;
+    assert_eq!(csv.column_names.len(), num_columns);
+    assert_eq!(csv.rows.len(), c3d.points.rows());
+    assert!(csv.rows.iter().all(|row| row.len() == num_columns));
+    let first_analog = 2 + 3 * c3d.points.cols();
+    assert_eq!(
+        csv.column_names[first_analog + 1],
+        format!("{}_2", c3d.analog.labels[0].trim())
+    );
+    assert_eq!(
+        csv.rows[3][first_analog + 1],
+        CsvValue::F64(c3d.analog[3 * samples_per_frame + 1][0])
+    );
+}
+
+#[test]
+fn events_csv() {
+    let c3d = C3d::load("tests/c3d_org_samples/sample_09/PlugInC3D.c3d").unwrap();
+    let csv = Csv::events_from_c3d(&c3d);
+    assert_eq!(csv.rows.len(), c3d.events.len());
+    let lines = csv_lines(&csv);
+    assert_eq!(lines[0], "Time,Context,Label,Description");
+    assert_eq!(lines.len(), 1 + c3d.events.len());
+}
+
+#[test]
+fn quoted_text() {
+    let csv = Csv {
+        delimiter: ',',
+        precision: None,
+        column_names: vec!["Label".to_string()],
+        rows: vec![vec![CsvValue::Text("Foot \"strike\", left".to_string())]],
+    };
+    assert_eq!(csv_lines(&csv)[1], "\"Foot \"\"strike\"\", left\"");
+}
@@ -0,0 +1,99 @@
+use c3dio::prelude::*;
+
+fn write_and_read(c3d: &C3d) -> C3d {
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    C3d::from_bytes(&bytes).unwrap()
+}
+
+#[test]
+fn add_processing_group() {
+    let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    c3d.parameters
+        .add_group("Processing", "Processing metadata")
+        .unwrap();
+    c3d.parameters
+        .add_parameter(
+            "PROCESSING",
+            "SOFTWARE",
+            "Software version",
+            vec![6],
+            ParameterData::Char("v1.2.3".chars().collect()),
+        )
+        .unwrap();
+    c3d.parameters
+        .add_parameter(
+            "PROCESSING",
+            "CUTOFFS",
+            "Filter cutoffs in Hz",
+            vec![2],
+            ParameterData::Float(vec![6., 12.]),
+        )
+        .unwrap();
+
+    let written = write_and_read(&c3d);
+    assert_eq!(
+        written
+            .parameters
+            .get_group_description("PROCESSING")
+            .unwrap(),
+        "Processing metadata"
+    );
+    assert_eq!(
+        written
+            .parameters
+            .get_as::<String>("PROCESSING", "SOFTWARE")
+            .unwrap(),
+        "v1.2.3"
+    );
+    let cutoffs = written.parameters.get("PROCESSING", "CUTOFFS").unwrap();
+    assert_eq!(cutoffs.description, "Filter cutoffs in Hz");
+    assert_eq!(cutoffs.data, ParameterData::Float(vec![6., 12.]));
+    assert_eq!(written.points, c3d.points);
+    assert_eq!(written.events, c3d.events);
+}
+
+#[test]
+fn add_parameter_creates_group() {
+    let mut parameters = Parameters::new();
+    parameters
+        .add_parameter(
+            "filter",
+            "order",
+            "",
+            vec![1],
+            ParameterData::Integer(vec![4]),
+        )
+        .unwrap();
+    assert_eq!(parameters.get_as::<i16>("FILTER", "ORDER").unwrap(), 4);
+}
+
+#[test]
+fn add_parameter_checks_dimensions() {
+    let mut parameters = Parameters::new();
+    assert!(matches!(
+        parameters.add_parameter(
+            "FILTER",
+            "CUTOFFS",
+            "",
+            vec![3],
+            ParameterData::Float(vec![6., 12.])
+        ),
+        Err(C3dWriteError::InvalidParameterDimensions(_))
+    ));
+}
+
+#[test]
+fn too_many_groups() {
+    let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    for i in 0..127 {
+        c3d.parameters
+            .add_group(&format!("GROUP{}", i), "")
+            .unwrap();
+    }
+    let mut bytes = Vec::new();
+    assert!(matches!(
+        c3d.write_to(&mut bytes, c3d.processor()),
+        Err(C3dWriteError::TooManyGroups(_))
+    ));
+}
@@ -0,0 +1,131 @@
+use crate::common::{plug_in_gait, rewritten};
+use c3dio::prelude::*;
+
+#[test]
+fn add_event() {
+    let mut c3d = plug_in_gait();
+    let event = c3d.events.add_event("Foot Strike", "Left", 0.5);
+    event.icon_id = 1;
+    assert_eq!(event.id, ['F', 'o', 'o', 't']);
+    assert_eq!(c3d.events.num_events(), 9);
+
+    let written = rewritten(&c3d);
+    assert_eq!(written.events.num_events(), 9);
+    let event = written.events.event(8).unwrap();
+    assert_eq!(event.label.trim(), "Foot Strike");
+    assert_eq!(event.context.trim(), "Left");
+    assert_eq!(event.time, 0.5);
+    assert_eq!(event.icon_id, 1);
+}
+
+#[test]
+fn edit_event() {
+    let mut c3d = plug_in_gait();
+    c3d.events.event_mut(0).unwrap().time = 0.25;
+    c3d.events.event_mut(0).unwrap().context = "Right".to_string();
+    assert!(c3d.events.event_mut(8).is_none());
+    let written = rewritten(&c3d);
+    assert_eq!(written.events[0].time, 0.25);
+    assert_eq!(written.events[0].context.trim(), "Right");
+}
+
+#[test]
+fn remove_event() {
+    let mut c3d = plug_in_gait();
+    let second = c3d.events[1].clone();
+    let removed = c3d.events.remove_event(0).unwrap();
+    assert!(c3d.events.remove_event(7).is_none());
+    assert_eq!(c3d.events.num_events(), 7);
+    assert_eq!(c3d.events[0], second);
+
+    let written = rewritten(&c3d);
+    assert_eq!(written.events.num_events(), 7);
+    assert!(written
+        .events
+        .iter()
+        .all(|event| event.time != removed.time));
+
+    // a file without events is written without any
+    while c3d.events.remove_event(0).is_some() {}
+    assert_eq!(rewritten(&c3d).events.num_events(), 0);
+}
+
+#[test]
+fn sort_by_time() {
+    let mut c3d = plug_in_gait();
+    c3d.events.add_event("Start", "General", 0.);
+    c3d.events.sort_by_time();
+    assert_eq!(c3d.events[0].label, "Start");
+    assert!(c3d
+        .events
+        .windows(2)
+        .all(|events| events[0].time <= events[1].time));
+    let written = rewritten(&c3d);
+    assert_eq!(written.events[0].label.trim(), "Start");
+}
+
+#[test]
+fn more_events_than_the_header_holds() {
+    let mut c3d = plug_in_gait();
+    for i in 0..12 {
+        c3d.events.add_event("Event", "General", 10. + i as f32);
+    }
+    let written = rewritten(&c3d);
+    assert_eq!(written.events.num_events(), 20);
+    assert_eq!(written.events[19].time, 21.);
+    assert_eq!(written.events[19].label.trim(), "Event");
+}
+
+#[test]
+fn vicon_event_attributes_are_kept() {
+    let c3d = C3d::load("tests/c3d_org_samples/sample_32/vicon_zerowire.c3d").unwrap();
+    let written = rewritten(&c3d);
+    assert_eq!(written.events.num_events(), 10);
+    for (event, original) in written.events.iter().zip(c3d.events.iter()) {
+        assert_eq!(event.subject.trim(), "Scott Drummond");
+        assert_eq!(event.icon_id, original.icon_id);
+        assert_eq!(event.generic_flag, original.generic_flag);
+    }
+    assert_eq!(written.events[0].icon_id, 1);
+    assert_eq!(written.events[1].icon_id, 2);
+
+    let context = written.events.context();
+    assert_eq!(context, c3d.events.context());
+    assert_eq!(context.used, Some(3));
+    assert_eq!(context.colour("General"), Some([255, 164, 0]));
+    assert_eq!(context.colour("Left"), Some([255, 0, 0]));
+    assert_eq!(context.colour("Right"), Some([0, 255, 0]));
+    assert_eq!(context.icon_id("Right"), Some(2));
+    assert_eq!(context.colour("Other"), None);
+}
+
+#[test]
+fn event_labels_are_read_without_header_labels() {
+    let c3d = C3d::load("tests/c3d_org_samples/sample_23/Vicon_analysis.c3d").unwrap();
+    assert_eq!(c3d.events[0].label.trim(), "foot strike");
+    assert_eq!(rewritten(&c3d).events[1].label.trim(), "foot off");
+}
+
+#[test]
+fn event_contexts_are_edited() {
+    let mut c3d = C3d::load("tests/c3d_org_samples/sample_32/vicon_zerowire.c3d").unwrap();
+    let context = c3d.events.context_mut();
+    context.used = Some(4);
+    context.labels.as_mut().unwrap().push("Ball".to_string());
+    context
+        .descriptions
+        .as_mut()
+        .unwrap()
+        .push("Ball events".to_string());
+    context.icon_ids.as_mut().unwrap().push(3);
+    context.colours.as_mut().unwrap().push([0, 0, 255]);
+    c3d.events.add_event("Bounce", "Ball", 1.).icon_id = 3;
+
+    let written = rewritten(&c3d);
+    let context = written.events.context();
+    assert_eq!(context.used, Some(4));
+    assert_eq!(context.colour("Ball"), Some([0, 0, 255]));
+    assert_eq!(context.colour("Right"), Some([0, 255, 0]));
+    assert_eq!(context.icon_id("Ball"), Some(3));
+    assert_eq!(written.events[10].icon_id, 3);
+}
@@ -0,0 +1,89 @@
+use c3dio::prelude::*;
+use c3dio::C3dBuilder;
+use std::path::PathBuf;
+use test_files::TestFiles;
+
+const POINTS: usize = 300;
+const CHANNELS: usize = 520;
+
+fn build(format: DataFormat) -> C3d {
+    let point_labels: Vec<String> = (0..POINTS).map(|i| format!("M{:03}", i)).collect();
+    let point_labels: Vec<&str> = point_labels.iter().map(|x| x.as_str()).collect();
+    let analog_labels: Vec<String> = (0..CHANNELS).map(|i| format!("EMG{:03}", i)).collect();
+    let analog_labels: Vec<&str> = analog_labels.iter().map(|x| x.as_str()).collect();
+    let units: Vec<&str> = (0..CHANNELS)
+        .map(|i| if i % 2 == 0 { "V" } else { "mV" })
+        .collect();
+    let scales: Vec<f32> = (0..CHANNELS).map(|i| 0.001 * (i + 1) as f32).collect();
+    let mut builder = C3dBuilder::new()
+        .point_labels(&point_labels)
+        .point_units("mm")
+        .frame_rate(100.)
+        .data_format(format, 0.1)
+        .analog_channels(&analog_labels, 2)
+        .analog_units(&units)
+        .analog_scales(&scales);
+    for frame in 0..4 {
+        let points: Vec<[f32; 3]> = (0..POINTS).map(|i| [i as f32, frame as f32, 10.]).collect();
+        let analog: Vec<f64> = (0..2 * CHANNELS)
+            .map(|i| (i % CHANNELS) as f64 * 0.001 * (i % CHANNELS + 1) as f64)
+            .collect();
+        builder = builder.push_frame(&points, &analog).unwrap();
+    }
+    builder.build().unwrap()
+}
+
+fn write(c3d: &C3d, files: &TestFiles, file_name: &str) -> PathBuf {
+    let path = files.path().join(file_name);
+    c3d.write_path(path.clone()).unwrap();
+    path
+}
+
+#[test]
+fn labels_continued_after_255_entries() {
+    for (format, file_name) in [
+        (DataFormat::Float, "float.c3d"),
+        (DataFormat::Integer, "integer.c3d"),
+    ] {
+        let c3d = build(format);
+        assert_eq!(c3d.points.cols(), POINTS);
+        assert_eq!(c3d.analog.cols(), CHANNELS);
+        let files = TestFiles::new();
+        let path = write(&c3d, &files, file_name);
+        let parameters = C3d::load_parameters(path.clone()).unwrap().parameters;
+        let written = C3d::load_path(path).unwrap();
+
+        assert!(parameters.get("POINT", "LABELS2").is_some());
+        assert!(parameters.get("POINT", "LABELS3").is_none());
+        assert!(parameters.get("POINT", "DESCRIPTIONS2").is_some());
+        for name in ["LABELS", "DESCRIPTIONS", "UNITS", "SCALE", "OFFSET"] {
+            assert!(parameters.get("ANALOG", &format!("{}3", name)).is_some());
+            assert!(parameters.get("ANALOG", &format!("{}4", name)).is_none());
+        }
+        let labels: Vec<String> = parameters.get_vec("POINT", "LABELS").unwrap();
+        assert_eq!(labels.len(), 255);
+
+        assert_eq!(written.points.labels, c3d.points.labels);
+        assert_eq!(written.points.descriptions.len(), POINTS);
+        assert_eq!(written.analog.labels, c3d.analog.labels);
+        assert_eq!(written.analog.descriptions.len(), CHANNELS);
+        assert_eq!(written.analog.units, c3d.analog.units);
+        assert_eq!(written.analog.scales, c3d.analog.scales);
+        assert_eq!(written.analog.offset, c3d.analog.offset);
+        assert_eq!(written.points.size(), c3d.points.size());
+        assert_eq!(written.points[3][POINTS - 1].point, [299., 3., 10.]);
+        for channel in [0, 254, 255, 300, CHANNELS - 1] {
+            let scale = c3d.analog.scales[channel] as f64;
+            assert!((written.analog[1][channel] - c3d.analog[1][channel]).abs() <= scale);
+        }
+    }
+}
+
+#[test]
+fn labels_continued_round_trip() {
+    let c3d = build(DataFormat::Float);
+    let files = TestFiles::new();
+    let written = C3d::load_path(write(&c3d, &files, "round_trip.c3d")).unwrap();
+    let rewritten = C3d::load_path(write(&written, &files, "round_trip_2.c3d")).unwrap();
+    assert_eq!(rewritten, written);
+}
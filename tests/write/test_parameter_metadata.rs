@@ -0,0 +1,50 @@
+use c3dio::prelude::*;
+
+#[test]
+fn parameter_metadata() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let metadata = c3d.parameter_metadata().unwrap();
+    let value = |key: &str| {
+        metadata
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    };
+    assert_eq!(value("POINT:RATE"), Some("[60]"));
+    assert_eq!(value("POINT:UNITS"), Some("[\"mm\"]"));
+    assert_eq!(value("ANALOG:USED"), Some("[18]"));
+    assert!(value("POINT:LABELS")
+        .unwrap()
+        .starts_with("[\"THEA\",\"FHEA\""));
+    let mut sorted = metadata.clone();
+    sorted.sort();
+    assert_eq!(metadata, sorted);
+}
+
+#[test]
+fn parameter_metadata_is_json() {
+    for file in [
+        "tests/data/short.c3d",
+        "tests/c3d_org_samples/sample_03/gait-pig.c3d",
+        "tests/c3d_org_samples/sample_09/PlugInC3D.c3d",
+    ] {
+        let c3d = C3d::load(file).unwrap();
+        for (key, value) in c3d.parameter_metadata().unwrap() {
+            let parsed: serde_json::Value = serde_json::from_str(&value).unwrap();
+            assert!(parsed.is_array(), "{} is not an array: {}", key, value);
+        }
+    }
+}
+
+#[test]
+fn parameter_metadata_escapes_strings() {
+    let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    c3d.parameters.add_group("NOTES", "").unwrap();
+    c3d.parameters
+        .set("NOTES", "TEXT", "say \"hi\" \\ bye".to_string())
+        .unwrap();
+    let metadata = c3d.parameter_metadata().unwrap();
+    let (_, value) = metadata.iter().find(|(k, _)| k == "NOTES:TEXT").unwrap();
+    let parsed: Vec<String> = serde_json::from_str(value).unwrap();
+    assert_eq!(parsed, vec!["say \"hi\" \\ bye".to_string()]);
+}
@@ -0,0 +1,107 @@
+use c3dio::prelude::*;
+use c3dio::LengthUnit;
+use std::io::Cursor;
+use test_files::TestFiles;
+
+fn data_start(bytes: &[u8]) -> usize {
+    512 * (u16::from_le_bytes([bytes[16], bytes[17]]) as usize - 1)
+}
+
+#[test]
+fn fix_a_label_and_add_an_event_in_place() {
+    let bytes = std::fs::read("tests/data/short.c3d").unwrap();
+    let original = C3d::from_bytes(&bytes).unwrap();
+    let mut c3d = C3d::header_and_parameters_from_reader(Cursor::new(&bytes)).unwrap();
+    assert_eq!(c3d.points.rows(), 0);
+    c3d.points.labels[0] = "LASI".to_string();
+    c3d.events.add_event("Foot Strike", "Left", 0.5);
+    let mut edited = Cursor::new(bytes.clone());
+    c3d.write_parameters_in_place_to(&mut edited).unwrap();
+    let edited = edited.into_inner();
+    assert_eq!(edited.len(), bytes.len());
+    let start = data_start(&bytes);
+    assert_eq!(data_start(&edited), start);
+    assert_eq!(edited[start..], bytes[start..]);
+
+    let reloaded = C3d::from_bytes(&edited).unwrap();
+    assert_eq!(reloaded.points.labels[0], "LASI");
+    assert_eq!(reloaded.points.labels[1..], original.points.labels[1..]);
+    assert_eq!(reloaded.points.as_slice(), original.points.as_slice());
+    assert_eq!(reloaded.analog, original.analog);
+    let event = reloaded.events.iter().last().unwrap();
+    assert_eq!((event.label.as_str(), event.time), ("Foot Strike", 0.5));
+}
+
+#[test]
+fn fully_loaded_files_are_written_in_place() {
+    let bytes = std::fs::read("tests/data/short.c3d").unwrap();
+    let mut c3d = C3d::from_bytes(&bytes).unwrap();
+    c3d.analog.labels[0] = "FZ1".to_string();
+    let mut edited = Cursor::new(bytes.clone());
+    c3d.write_parameters_in_place_to(&mut edited).unwrap();
+    let edited = edited.into_inner();
+    let start = data_start(&bytes);
+    assert_eq!(edited[start..], bytes[start..]);
+    assert_eq!(C3d::from_bytes(&edited).unwrap(), c3d);
+}
+
+#[test]
+fn write_parameters_in_place_of_a_file() {
+    let files = TestFiles::new();
+    let path = files.path().join("in_place.c3d");
+    let file_name = path.to_str().unwrap();
+    std::fs::copy("tests/data/short.c3d", file_name).unwrap();
+    let mut c3d = C3d::load_header_and_parameters(file_name.into()).unwrap();
+    c3d.points.labels[1] = "RASI".to_string();
+    c3d.write_parameters_in_place(file_name).unwrap();
+    assert_eq!(C3d::load(file_name).unwrap().points.labels[1], "RASI");
+}
+
+#[test]
+fn parameters_that_do_not_fit_are_rejected() {
+    let bytes = std::fs::read("tests/data/short.c3d").unwrap();
+    let mut c3d = C3d::header_and_parameters_from_reader(Cursor::new(&bytes)).unwrap();
+    c3d.parameters
+        .add_parameter(
+            "CUSTOM",
+            "NOTES",
+            "",
+            vec![255, 20],
+            ParameterData::Char(vec!['x'; 255 * 20]),
+        )
+        .unwrap();
+    let mut edited = Cursor::new(bytes.clone());
+    assert!(matches!(
+        c3d.write_parameters_in_place_to(&mut edited),
+        Err(C3dWriteError::CannotWriteInPlace(_))
+    ));
+    assert_eq!(edited.into_inner(), bytes);
+}
+
+#[test]
+fn changes_to_the_data_layout_are_rejected() {
+    let bytes = std::fs::read("tests/data/short.c3d").unwrap();
+    let c3d = C3d::from_bytes(&bytes).unwrap();
+    let mut integer = c3d.clone();
+    integer.convert_format(DataFormat::Integer);
+    let cropped = c3d.crop(0..10);
+    let mut dec = c3d.clone();
+    dec.convert_to(Processor::Dec);
+    // the data on disk would keep its old units
+    let mut meters = c3d.clone();
+    meters.convert_units(LengthUnit::Meter).unwrap();
+    let mut scaled = c3d.clone();
+    scaled.points.scale_factor *= 2.;
+    for changed in [integer, cropped, dec, meters, scaled] {
+        let mut edited = Cursor::new(bytes.clone());
+        assert!(matches!(
+            changed.write_parameters_in_place_to(&mut edited),
+            Err(C3dWriteError::CannotWriteInPlace(_))
+        ));
+        assert_eq!(edited.into_inner(), bytes);
+    }
+    assert!(matches!(
+        c3d.write_parameters_in_place_to(Cursor::new(vec![0u8; 100])),
+        Err(C3dWriteError::InPlaceParseError(_))
+    ));
+}
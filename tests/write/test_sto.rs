@@ -0,0 +1,71 @@
+use c3dio::prelude::*;
+use test_files::TestFiles;
+
+fn sto_lines(sto: &Sto) -> Vec<String> {
+    let mut bytes = Vec::new();
+    sto.write_to(&mut bytes).unwrap();
+    String::from_utf8(bytes)
+        .unwrap()
+        .lines()
+        .map(|line| line.to_string())
+        .collect()
+}
+
+#[test]
+fn analog_sto() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let lines = sto_lines(&Sto::from_c3d(&c3d));
+    let (num_rows, num_columns) = c3d.analog.size();
+    assert_eq!(lines[1], "version=1");
+    assert_eq!(lines[2], format!("nRows={}", num_rows));
+    assert_eq!(lines[3], format!("nColumns={}", num_columns + 1));
+    assert_eq!(lines[4], "inDegrees=no");
+    assert_eq!(lines[5], "endheader");
+    let names: Vec<&str> = lines[6].split('\t').collect();
+    assert_eq!(names.len(), num_columns + 1);
+    assert_eq!(names[0], "time");
+    assert_eq!(names[1], c3d.analog.labels[0].trim());
+    assert_eq!(lines.len(), 7 + num_rows);
+
+    let start = (c3d.points.first_frame - 1) as f32 / c3d.points.frame_rate;
+    for (row, line) in lines[7..].iter().enumerate().step_by(100) {
+        let values: Vec<&str> = line.split('\t').collect();
+        let time = values[0].parse::<f32>().unwrap();
+        assert!((time - start - row as f32 / c3d.analog.rate).abs() < 1e-4);
+        assert_eq!(values[1].parse::<f64>().unwrap(), c3d.analog[row][0]);
+    }
+}
+
+#[test]
+fn force_sto() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let sto = Sto::forces_from_c3d(&c3d);
+    assert_eq!(sto.data.size(), (c3d.points.rows(), 6 * c3d.forces.len()));
+    assert_eq!(sto.column_names[0], "1_ground_force_vx");
+    assert_eq!(sto.column_names[11], "2_ground_force_pz");
+    let force = c3d.force(1, 10).unwrap();
+    let center_of_pressure = c3d.center_of_pressure(1, 10).unwrap();
+    assert_eq!(sto.data[(10, 6)], force[0] as f64);
+    assert_eq!(sto.data[(10, 8)], force[2] as f64);
+    assert_eq!(sto.data[(10, 10)], center_of_pressure[1] as f64);
+
+    let lines = sto_lines(&sto);
+    let values: Vec<&str> = lines[8].split('\t').collect();
+    assert_eq!(values.len(), 1 + 6 * c3d.forces.len());
+    let time = values[0].parse::<f32>().unwrap();
+    assert_eq!(time, c3d.points.first_frame as f32 / c3d.points.frame_rate);
+}
+
+#[test]
+fn write_mot() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let files = TestFiles::new();
+    let path = files.path().join("forces.mot");
+    Sto::forces_from_c3d(&c3d).write(path.clone()).unwrap();
+    let contents = std::fs::read_to_string(path).unwrap();
+    assert_eq!(contents.lines().next().unwrap(), "forces.mot");
+    assert!(matches!(
+        Sto::from_c3d(&c3d).write(files.path().join("forces.txt")),
+        Err(C3dWriteError::InvalidFileExtension(_))
+    ));
+}
@@ -0,0 +1,246 @@
+use c3dio::prelude::*;
+use c3dio::{C3dBuilder, C3dWriter};
+use std::io::Cursor;
+use test_files::TestFiles;
+
+fn layout(format: DataFormat, processor: Processor) -> C3dBuilder {
+    C3dBuilder::new()
+        .point_labels(&["LASI", "RASI", "SACR"])
+        .point_units("mm")
+        .frame_rate(50.)
+        .data_format(format, 0.1)
+        .processor(processor)
+        .analog_channels(&["Fx", "Fz"], 4)
+        .analog_units(&["N", "N"])
+}
+
+fn frame(frame: usize) -> ([[f32; 3]; 3], Vec<f64>) {
+    let x = frame as f32;
+    let analog = (0..8).map(|i| (frame * 8 + i) as f64).collect();
+    ([[x, 0., 10.], [x, 100., 10.], [x, 50., -20.]], analog)
+}
+
+fn built(format: DataFormat, processor: Processor, frames: usize) -> Vec<u8> {
+    let mut builder = layout(format, processor);
+    for i in 0..frames {
+        let (points, analog) = frame(i);
+        builder = builder.push_frame(&points, &analog).unwrap();
+    }
+    let c3d = builder.build().unwrap();
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, processor).unwrap();
+    bytes
+}
+
+fn streamed(format: DataFormat, processor: Processor, frames: usize) -> Vec<u8> {
+    let mut writer = C3dWriter::new(Cursor::new(Vec::new()), layout(format, processor)).unwrap();
+    for i in 0..frames {
+        let (points, analog) = frame(i);
+        writer.push_frame(&points, &analog).unwrap();
+    }
+    assert_eq!(writer.frame_count(), frames);
+    writer.close().unwrap().into_inner()
+}
+
+#[test]
+fn streamed_file_matches_built_file() {
+    for format in [DataFormat::Float, DataFormat::Integer] {
+        for processor in [Processor::Intel, Processor::Dec, Processor::SgiMips] {
+            let built = built(format.clone(), processor, 20);
+            let streamed = streamed(format.clone(), processor, 20);
+            // both end on a whole block
+            assert_eq!(built.len() % 512, 0);
+            assert_eq!(streamed.len() % 512, 0);
+            let expected = C3d::from_bytes(&built).unwrap();
+            let c3d = C3d::from_bytes(&streamed).unwrap();
+            assert!(c3d.warnings.is_empty());
+            assert_eq!(c3d.processor(), processor);
+            assert_eq!(c3d.points, expected.points);
+            assert_eq!(c3d.analog, expected.analog);
+            assert_eq!(c3d.points.last_frame, 20);
+        }
+    }
+}
+
+#[test]
+fn frames_pushed_to_the_layout_are_written_first() {
+    let (points, analog) = frame(0);
+    let layout = layout(DataFormat::Float, Processor::Intel)
+        .push_frame(&points, &analog)
+        .unwrap();
+    let mut writer = C3dWriter::new(Cursor::new(Vec::new()), layout).unwrap();
+    assert_eq!(writer.frame_count(), 1);
+    for i in 1..5 {
+        let (points, analog) = frame(i);
+        writer.push_frame(&points, &analog).unwrap();
+    }
+    let c3d = C3d::from_bytes(&writer.close().unwrap().into_inner()).unwrap();
+    let expected = C3d::from_bytes(&built(DataFormat::Float, Processor::Intel, 5)).unwrap();
+    assert_eq!(c3d.points, expected.points);
+    assert_eq!(c3d.analog, expected.analog);
+}
+
+#[test]
+fn long_streams_store_their_frame_range_in_the_trial_group() {
+    let layout = C3dBuilder::new().point_labels(&["M1"]).frame_rate(1000.);
+    let mut writer = C3dWriter::new(Cursor::new(Vec::new()), layout).unwrap();
+    let frames = u16::MAX as usize + 10;
+    for i in 0..frames {
+        writer.push_frame(&[[i as f32, 0., 0.]], &[]).unwrap();
+    }
+    let c3d = C3d::from_bytes(&writer.close().unwrap().into_inner()).unwrap();
+    assert_eq!(c3d.points.rows(), frames);
+    assert_eq!(
+        c3d.points[frames - 1][0].point,
+        [(frames - 1) as f32, 0., 0.]
+    );
+}
+
+#[test]
+fn frames_must_match_the_layout() {
+    let mut writer = C3dWriter::new(
+        Cursor::new(Vec::new()),
+        layout(DataFormat::Float, Processor::Intel),
+    )
+    .unwrap();
+    let (points, analog) = frame(0);
+    assert!(matches!(
+        writer.push_frame(&points[..2], &analog),
+        Err(C3dWriteError::InvalidLayout(
+            C3dBuilderError::PointCountMismatch(3, 2)
+        ))
+    ));
+    assert!(matches!(
+        writer.push_frame(&points, &analog[..4]),
+        Err(C3dWriteError::InvalidLayout(
+            C3dBuilderError::AnalogSampleCountMismatch(8, 4)
+        ))
+    ));
+    assert_eq!(writer.frame_count(), 0);
+}
+
+#[test]
+fn create_streams_to_a_file() {
+    let files = TestFiles::new();
+    let path = files.path().join("stream.c3d");
+    let file_name = path.to_str().unwrap();
+    let mut writer =
+        C3dWriter::create(file_name, layout(DataFormat::Float, Processor::Intel)).unwrap();
+    for i in 0..3 {
+        let (points, analog) = frame(i);
+        writer.push_frame(&points, &analog).unwrap();
+    }
+    writer.close().unwrap();
+    let c3d = C3d::load(file_name).unwrap();
+    assert_eq!(c3d.points.rows(), 3);
+}
+
+#[test]
+fn appended_file_matches_built_file() {
+    for format in [DataFormat::Float, DataFormat::Integer] {
+        for processor in [Processor::Intel, Processor::Dec, Processor::SgiMips] {
+            let bytes = streamed(format.clone(), processor, 10);
+            let mut writer = C3dWriter::append(Cursor::new(bytes)).unwrap();
+            assert_eq!(writer.frame_count(), 10);
+            for i in 10..20 {
+                let (points, analog) = frame(i);
+                writer.push_frame(&points, &analog).unwrap();
+            }
+            let bytes = writer.close().unwrap().into_inner();
+            let built = built(format.clone(), processor, 20);
+            assert_eq!(bytes.len(), built.len());
+            let c3d = C3d::from_bytes(&bytes).unwrap();
+            let expected = C3d::from_bytes(&built).unwrap();
+            assert!(c3d.warnings.is_empty());
+            assert_eq!(c3d.points, expected.points);
+            assert_eq!(c3d.analog, expected.analog);
+            assert_eq!(c3d.points.last_frame, 20);
+        }
+    }
+}
+
+#[test]
+fn append_to_a_written_file() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    let mut writer = C3dWriter::append(Cursor::new(bytes)).unwrap();
+    assert_eq!(writer.frame_count(), c3d.points.rows());
+    for i in 0..5 {
+        writer
+            .push_marker_frame(c3d.points.frame(i).unwrap(), c3d.analog.frame(i).unwrap())
+            .unwrap();
+    }
+    let appended = C3d::from_bytes(&writer.close().unwrap().into_inner()).unwrap();
+    let frames = c3d.points.rows();
+    assert!(appended.warnings.is_empty());
+    assert_eq!(appended.points.rows(), frames + 5);
+    assert_eq!(appended.points.labels, c3d.points.labels);
+    for i in 0..5 {
+        assert_eq!(appended.points.frame(frames + i), c3d.points.frame(i));
+        assert_eq!(appended.analog.frame(frames + i), c3d.analog.frame(i));
+    }
+    assert_eq!(
+        appended.points.frame(frames - 1),
+        c3d.points.frame(frames - 1)
+    );
+}
+
+#[test]
+fn append_to_a_file_with_more_labels_than_points() {
+    let bytes = std::fs::read("tests/c3d_org_samples/sample_01/Eb015pi.c3d").unwrap();
+    let c3d = C3d::from_bytes(&bytes).unwrap();
+    assert!(c3d.points.labels.len() > c3d.points.cols());
+    let mut writer = C3dWriter::append(Cursor::new(bytes)).unwrap();
+    assert!(matches!(
+        writer.push_marker_frame(&vec![MarkerPoint::default(); c3d.points.labels.len()], &[]),
+        Err(C3dWriteError::InvalidLayout(
+            C3dBuilderError::PointCountMismatch(_, _)
+        ))
+    ));
+    for i in 0..2 {
+        writer
+            .push_marker_frame(c3d.points.frame(i).unwrap(), c3d.analog.frame(i).unwrap())
+            .unwrap();
+    }
+    let appended = C3d::from_bytes(&writer.close().unwrap().into_inner()).unwrap();
+    let frames = c3d.points.rows();
+    assert_eq!(appended.points.rows(), frames + 2);
+    assert_eq!(appended.points.labels, c3d.points.labels);
+    for i in 0..2 {
+        assert_eq!(appended.points.frame(frames + i), c3d.points.frame(i));
+        assert_eq!(appended.analog.frame(frames + i), c3d.analog.frame(i));
+    }
+}
+
+#[test]
+fn open_appends_to_a_file() {
+    let files = TestFiles::new();
+    let path = files.path().join("append.c3d");
+    let file_name = path.to_str().unwrap();
+    let mut writer =
+        C3dWriter::create(file_name, layout(DataFormat::Float, Processor::Intel)).unwrap();
+    for i in 0..3 {
+        let (points, analog) = frame(i);
+        writer.push_frame(&points, &analog).unwrap();
+    }
+    writer.close().unwrap();
+    let mut writer = C3dWriter::open(file_name).unwrap();
+    for i in 3..5 {
+        let (points, analog) = frame(i);
+        writer.push_frame(&points, &analog).unwrap();
+    }
+    writer.close().unwrap();
+    let c3d = C3d::load(file_name).unwrap();
+    let expected = C3d::from_bytes(&built(DataFormat::Float, Processor::Intel, 5)).unwrap();
+    assert_eq!(c3d.points, expected.points);
+    assert_eq!(c3d.analog, expected.analog);
+}
+
+#[test]
+fn append_rejects_files_that_are_not_c3d() {
+    assert!(matches!(
+        C3dWriter::append(Cursor::new(vec![0u8; 100])),
+        Err(C3dWriteError::AppendParseError(_))
+    ));
+}
@@ -0,0 +1,42 @@
+use c3dio::prelude::*;
+use c3dio::{Csv, Table};
+
+#[test]
+fn table_from_c3d() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let csv = Csv::from_c3d(&c3d);
+    let table = Table::from_c3d(&c3d);
+    assert_eq!(table.column_names, csv.column_names);
+    assert_eq!(table.columns.len(), csv.column_names.len());
+    assert_eq!(table.rows(), c3d.points.rows());
+    let frame = table.column("Frame").unwrap();
+    assert_eq!(frame[0], c3d.points.first_frame.max(1) as f64);
+    let time = table.column("Time").unwrap();
+    assert_eq!(time[1] - time[0], 1. / c3d.points.frame_rate as f64);
+}
+
+#[test]
+fn table_points() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let table = Table::points_from_c3d(&c3d);
+    let label = c3d.points.labels[0].trim();
+    let x = table.column(&format!("{}_X", label)).unwrap();
+    for (frame, x) in x.iter().enumerate() {
+        let point = c3d.points[frame][0];
+        match point.is_valid() {
+            true => assert_eq!(*x, point.point[0] as f64),
+            false => assert!(x.is_nan()),
+        }
+    }
+}
+
+#[test]
+fn table_analog() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let table = Table::analog_from_c3d(&c3d);
+    assert_eq!(table.rows(), c3d.analog.rows());
+    let label = c3d.analog.labels[0].trim();
+    let channel = table.column(label).unwrap();
+    assert_eq!(channel[5], c3d.analog[5][0]);
+    assert_eq!(table.column("not a column"), None);
+}
@@ -0,0 +1,87 @@
+use c3dio::prelude::*;
+use test_files::TestFiles;
+
+fn load(file: &str) -> C3d {
+    C3d::load(&format!("tests/c3d_org_samples/sample_02/{}.c3d", file)).unwrap()
+}
+
+fn write(c3d: &C3d, processor: Processor) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, processor).unwrap();
+    bytes
+}
+
+#[test]
+fn transcoding_round_trip_is_byte_exact() {
+    for file in ["dec_int", "dec_real", "sgi_int", "sgi_real"] {
+        let c3d = load(file);
+        assert_ne!(c3d.processor(), Processor::Intel);
+        let expected = write(&c3d, c3d.processor());
+        let intel = C3d::from_bytes(&write(&c3d, Processor::Intel)).unwrap();
+        assert_eq!(intel.processor(), Processor::Intel);
+        assert_eq!(intel.parameters, c3d.parameters);
+        assert_eq!(intel.points, c3d.points);
+        assert_eq!(intel.analog, c3d.analog);
+        assert_eq!(write(&intel, c3d.processor()), expected);
+    }
+}
+
+#[test]
+fn transcoded_files_match_intel_originals() {
+    for (file, intel) in [("dec_real", "pc_real"), ("sgi_int", "pc_int")] {
+        let intel = load(intel);
+        let converted = C3d::from_bytes(&write(&load(file), Processor::Intel)).unwrap();
+        assert_eq!(converted.points, intel.points);
+        assert_eq!(converted.analog.labels, intel.analog.labels);
+    }
+}
+
+#[test]
+fn unknown_parameters_are_re_encoded() {
+    let mut c3d = load("dec_real");
+    c3d.parameters.add_group("CUSTOM", "Custom group").unwrap();
+    c3d.parameters
+        .add_parameter(
+            "CUSTOM",
+            "VALUES",
+            "Mixed values",
+            vec![3],
+            ParameterData::Float(vec![0.1, -2.5, 1e6]),
+        )
+        .unwrap();
+    c3d.parameters
+        .add_parameter(
+            "CUSTOM",
+            "COUNTS",
+            "Counts",
+            vec![2],
+            ParameterData::Integer(vec![-3, 1000]),
+        )
+        .unwrap();
+    for processor in [Processor::Intel, Processor::SgiMips, Processor::Dec] {
+        let written = C3d::from_bytes(&write(&c3d, processor)).unwrap();
+        assert_eq!(written.processor(), processor);
+        assert_eq!(
+            written.parameters.get("CUSTOM", "VALUES"),
+            c3d.parameters.get("CUSTOM", "VALUES")
+        );
+        assert_eq!(
+            written.parameters.get("CUSTOM", "COUNTS"),
+            c3d.parameters.get("CUSTOM", "COUNTS")
+        );
+    }
+}
+
+#[test]
+fn write_with_processor_to_file() {
+    let c3d = load("sgi_real");
+    let files = TestFiles::new();
+    let path = files.path().join("transcode.c3d");
+    c3d.write_with_processor(path.to_str().unwrap(), Processor::Intel)
+        .unwrap();
+    let written = C3d::load_path(path).unwrap();
+    assert_eq!(written.processor(), Processor::Intel);
+    assert_eq!(c3d.processor(), Processor::SgiMips);
+    assert_eq!(written.points, c3d.points);
+    assert_eq!(written.parameters, c3d.parameters);
+}
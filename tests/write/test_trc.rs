@@ -0,0 +1,79 @@
+use c3dio::prelude::*;
+use test_files::TestFiles;
+
+fn trc_lines(c3d: &C3d) -> Vec<String> {
+    let mut bytes = Vec::new();
+    Trc::from_c3d(c3d).write_to(&mut bytes).unwrap();
+    String::from_utf8(bytes)
+        .unwrap()
+        .lines()
+        .map(|line| line.to_string())
+        .collect()
+}
+
+#[test]
+fn trc_header() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let lines = trc_lines(&c3d);
+    let num_frames = c3d.points.rows();
+    let num_markers = c3d.points.cols();
+    assert!(lines[0].starts_with("PathFileType\t4\t(X/Y/Z)"));
+    let values: Vec<&str> = lines[2].split('\t').collect();
+    assert_eq!(values[0].parse::<f32>().unwrap(), c3d.points.frame_rate);
+    assert_eq!(values[2].parse::<usize>().unwrap(), num_frames);
+    assert_eq!(values[3].parse::<usize>().unwrap(), num_markers);
+    assert_eq!(
+        values[4],
+        c3d.points.units.iter().collect::<String>().trim()
+    );
+
+    let labels: Vec<&str> = lines[3].split('\t').collect();
+    assert_eq!(labels.len(), 2 + 3 * num_markers);
+    assert_eq!(labels[2], c3d.points.labels[0].trim());
+    let axes: Vec<&str> = lines[4].split('\t').collect();
+    assert_eq!(axes.len(), 2 + 3 * num_markers);
+    assert_eq!(axes[2..5], ["X1", "Y1", "Z1"]);
+    assert!(lines[5].is_empty());
+    assert_eq!(lines.len(), 6 + num_frames);
+}
+
+#[test]
+fn trc_rows() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let lines = trc_lines(&c3d);
+    let num_markers = c3d.points.cols();
+    let times = c3d.points.frame_times();
+    for (i, line) in lines[6..].iter().enumerate() {
+        let values: Vec<&str> = line.split('\t').collect();
+        assert_eq!(values.len(), 2 + 3 * num_markers);
+        assert_eq!(
+            values[0].parse::<usize>().unwrap(),
+            i + c3d.points.first_frame as usize
+        );
+        assert!((values[1].parse::<f32>().unwrap() - times[i]).abs() < 1e-5);
+        for j in 0..num_markers {
+            let point = &c3d.points[(i, j)];
+            if point.is_valid() {
+                assert_eq!(values[2 + 3 * j].parse::<f32>().unwrap(), point[0]);
+                assert_eq!(values[4 + 3 * j].parse::<f32>().unwrap(), point[2]);
+            } else {
+                assert!(values[2 + 3 * j].is_empty());
+            }
+        }
+    }
+}
+
+#[test]
+fn trc_write_path() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let files = TestFiles::new();
+    let path = files.path().join("short.trc");
+    Trc::from_c3d(&c3d).write(path.clone()).unwrap();
+    let contents = std::fs::read_to_string(path).unwrap();
+    let first_line = contents.lines().next().unwrap();
+    assert_eq!(first_line, "PathFileType\t4\t(X/Y/Z)\tshort.trc");
+    assert!(matches!(
+        Trc::from_c3d(&c3d).write(files.path().join("short")),
+        Err(C3dWriteError::InvalidFileExtension(_))
+    ));
+}
@@ -0,0 +1,112 @@
+use c3dio::prelude::*;
+use c3dio::WriteOptions;
+use test_files::TestFiles;
+
+fn write(c3d: &C3d, options: &WriteOptions) -> C3d {
+    let mut bytes = Vec::new();
+    c3d.write_to_with_options(&mut bytes, options).unwrap();
+    C3d::from_bytes(&bytes).unwrap()
+}
+
+fn assert_close(written: &C3d, c3d: &C3d) {
+    let scale = written.points.scale_factor;
+    for (written, point) in written.points.iter().zip(c3d.points.iter()) {
+        assert_eq!(written.is_valid(), point.is_valid());
+        if point.is_valid() {
+            for axis in 0..3 {
+                assert!((written[axis] - point[axis]).abs() <= scale);
+            }
+        }
+    }
+    for channel in 0..c3d.analog.cols() {
+        let scale = (written.analog.scales[channel] * written.analog.gen_scale).abs() as f64;
+        for (written, sample) in written
+            .analog
+            .iter_col(channel)
+            .zip(c3d.analog.iter_col(channel))
+        {
+            assert!((written - sample).abs() <= scale);
+        }
+    }
+}
+
+#[test]
+fn float_file_written_as_integers() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    assert_eq!(c3d.points.format, DataFormat::Float);
+    let written = write(&c3d, &WriteOptions::integer());
+    assert_eq!(written.points.format, DataFormat::Integer);
+    assert_eq!(c3d.points.format, DataFormat::Float);
+    assert!(written.points.scale_factor > 0.);
+    assert_close(&written, &c3d);
+
+    // the scales use the full 16-bit range
+    let largest = c3d
+        .points
+        .iter()
+        .filter(|x| x.is_valid())
+        .flat_map(|x| x.point)
+        .fold(0f32, |largest, x| largest.max(x.abs()));
+    assert!((largest / written.points.scale_factor - i16::MAX as f32).abs() < 1.);
+}
+
+#[test]
+fn integers_keep_resolution_of_recomputed_scales() {
+    let c3d = C3d::load("tests/c3d_org_samples/sample_02/pc_real.c3d").unwrap();
+    let written = write(&c3d, &WriteOptions::integer());
+    assert_close(&written, &c3d);
+    let integer = C3d::load("tests/c3d_org_samples/sample_02/pc_int.c3d").unwrap();
+    assert!(written.points.scale_factor <= integer.points.scale_factor);
+}
+
+#[test]
+fn integer_file_written_as_floats_and_back() {
+    let c3d = C3d::load("tests/c3d_org_samples/sample_09/PlugInC3D.c3d").unwrap();
+    assert_eq!(c3d.points.format, DataFormat::Integer);
+    let float = write(
+        &c3d,
+        &WriteOptions::default().with_format(DataFormat::Float),
+    );
+    assert_eq!(float.points.format, DataFormat::Float);
+    assert_eq!(float.points, c3d.points);
+    assert_eq!(float.analog, c3d.analog);
+
+    let integer = write(&float, &WriteOptions::integer());
+    assert_eq!(integer.points.format, DataFormat::Integer);
+    assert_close(&integer, &c3d);
+}
+
+#[test]
+fn same_format_is_written_unchanged() {
+    let c3d = C3d::load("tests/c3d_org_samples/sample_09/PlugInC3D.c3d").unwrap();
+    let mut expected = Vec::new();
+    c3d.write_to(&mut expected, c3d.processor()).unwrap();
+    let mut bytes = Vec::new();
+    c3d.write_to_with_options(&mut bytes, &WriteOptions::integer())
+        .unwrap();
+    assert_eq!(bytes, expected);
+}
+
+#[test]
+fn integer_with_processor() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let options = WriteOptions::integer().with_processor(Processor::Dec);
+    let written = write(&c3d, &options);
+    assert_eq!(written.processor(), Processor::Dec);
+    assert_eq!(written.points.format, DataFormat::Integer);
+    assert_close(&written, &c3d);
+}
+
+#[test]
+fn convert_format_in_place() {
+    let mut c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let original = c3d.clone();
+    c3d.convert_format(DataFormat::Integer);
+    assert_eq!(c3d.points.format, DataFormat::Integer);
+    let files = TestFiles::new();
+    let path = files.path().join("write_integer.c3d");
+    c3d.write_path(path.clone()).unwrap();
+    let written = C3d::load_path(path).unwrap();
+    assert_eq!(written.points.format, DataFormat::Integer);
+    assert_close(&written, &original);
+}
@@ -0,0 +1,44 @@
+use c3dio::prelude::*;
+use test_files::TestFiles;
+
+#[test]
+fn write_to_buffer_matches_write_path() {
+    let c3d = C3d::load("tests/data/short.c3d").unwrap();
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    let files = TestFiles::new();
+    let path = files.path().join("write_to.c3d");
+    c3d.write_path(path.clone()).unwrap();
+    let written = std::fs::read(path).unwrap();
+    assert_eq!(bytes, written);
+    assert_eq!(C3d::from_bytes(&bytes).unwrap(), c3d);
+}
+
+#[test]
+fn write_to_with_processor() {
+    let c3d = C3d::load("tests/c3d_org_samples/sample_02/pc_real.c3d").unwrap();
+    for processor in [Processor::Intel, Processor::Dec, Processor::SgiMips] {
+        let mut bytes = Vec::new();
+        c3d.write_to(&mut bytes, processor).unwrap();
+        let written = C3d::from_bytes(&bytes).unwrap();
+        assert_eq!(written.processor(), processor);
+        assert_eq!(written.points, c3d.points);
+        assert_eq!(written.analog, c3d.analog);
+    }
+    assert_eq!(c3d.processor(), Processor::Intel);
+}
+
+#[test]
+fn write_to_preserved_bytes() {
+    let original = std::fs::read("tests/data/short.c3d").unwrap();
+    let c3d =
+        C3d::from_bytes_with_options(&original, ParseOptions::default().with_preserved_bytes())
+            .unwrap();
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, c3d.processor()).unwrap();
+    assert_eq!(bytes, original);
+    let mut bytes = Vec::new();
+    c3d.write_to(&mut bytes, Processor::Dec).unwrap();
+    assert_ne!(bytes, original);
+    assert_eq!(C3d::from_bytes(&bytes).unwrap().processor(), Processor::Dec);
+}